@@ -0,0 +1,92 @@
+//! Check-conflicts command
+//!
+//! Fail when uncommitted changes add unresolved merge conflict markers.
+//! Exits with [`EXIT_CONFLICTS`] when markers are found and [`EXIT_ERROR`]
+//! when the check itself fails (e.g. outside a git repository).
+
+use anyhow::Result;
+use clap::Args;
+
+use cr_core::diff::{DiffData, DiffParser, DiffSource};
+
+/// Exit code when the changes add conflict markers
+pub const EXIT_CONFLICTS: i32 = 1;
+
+/// Exit code when the diff could not be read
+pub const EXIT_ERROR: i32 = 2;
+
+/// Arguments for the check-conflicts command
+#[derive(Debug, Args)]
+pub struct CheckConflictsArgs {
+    /// Only check staged changes
+    #[arg(long)]
+    pub staged: bool,
+}
+
+/// Execute the check-conflicts command
+///
+/// Returns whether the changes are free of conflict markers.
+pub fn execute(args: CheckConflictsArgs) -> Result<bool> {
+    use colored::Colorize;
+
+    let parser = DiffParser::new();
+    let mut diffs = vec![parser.parse_from_git(&DiffSource::Staged)?];
+    if !args.staged {
+        diffs.push(parser.parse_from_git(&DiffSource::WorkingTree)?);
+    }
+
+    let found = conflict_locations(&diffs);
+    if found.is_empty() {
+        println!("{} No conflict markers found", "✓".green());
+        return Ok(true);
+    }
+
+    eprintln!(
+        "{} Unresolved conflict markers in {} location(s):",
+        "✗".red(),
+        found.len()
+    );
+    for location in &found {
+        eprintln!("  {}", location);
+    }
+    Ok(false)
+}
+
+/// `path:line` for every added conflict marker, without duplicates
+fn conflict_locations(diffs: &[DiffData]) -> Vec<String> {
+    let mut locations = Vec::new();
+    for diff in diffs {
+        for (file_id, line) in DiffParser::verify_no_conflict_markers(diff) {
+            let Some(file) = diff.get_file(&file_id) else {
+                continue;
+            };
+            let location = format!("{}:{}", file.display_path().display(), line);
+            if !locations.contains(&location) {
+                locations.push(location);
+            }
+        }
+    }
+    locations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflict_locations() {
+        let raw = "diff --git a/src/a.rs b/src/a.rs\n\
+                   --- a/src/a.rs\n\
+                   +++ b/src/a.rs\n\
+                   @@ -1,1 +1,4 @@\n \
+                   fn a() {}\n\
+                   +<<<<<<< HEAD\n\
+                   +=======\n\
+                   +>>>>>>> feature\n";
+        let diff = DiffParser::new().parse(raw).unwrap();
+
+        let locations = conflict_locations(&[diff.clone(), diff]);
+        assert_eq!(locations, vec!["src/a.rs:2", "src/a.rs:3", "src/a.rs:4"]);
+        assert!(conflict_locations(&[DiffData::empty()]).is_empty());
+    }
+}
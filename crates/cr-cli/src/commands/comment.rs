@@ -0,0 +1,474 @@
+//! Comment command
+//!
+//! Manage comments in review sessions.
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use serde::Deserialize;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use cr_core::comment::{Comment, CommentBuilder, CommentValidator, DiffSide, Severity};
+use cr_core::config::Config;
+use cr_core::diff::{FileDiff, Line, LineType};
+use cr_core::session::{Session, SessionManager};
+use cr_core::types::CommentId;
+
+/// Comment subcommands
+#[derive(Debug, Subcommand)]
+pub enum CommentCommand {
+    /// Add a tag to comments in a session
+    Tag {
+        /// Tag to add
+        tag: String,
+
+        /// Comment IDs to tag
+        #[arg(long = "id")]
+        ids: Vec<String>,
+
+        /// Tag every imported (non-manual) comment
+        #[arg(long)]
+        all_imported: bool,
+
+        /// Session ID (defaults to the latest session)
+        #[arg(long, short)]
+        session: Option<String>,
+    },
+
+    /// Search comment content (substring match by default)
+    Search {
+        /// Text, or a pattern with --regex
+        query: String,
+
+        /// Rank comments by similarity (normalized Levenshtein) instead
+        #[arg(long, conflicts_with = "regex")]
+        fuzzy: bool,
+
+        /// Minimum similarity (0.0-1.0) for --fuzzy
+        #[arg(long, default_value_t = 0.5, requires = "fuzzy")]
+        threshold: f64,
+
+        /// Treat the query as a regular expression
+        #[arg(long)]
+        regex: bool,
+
+        /// Session ID (defaults to the latest session)
+        #[arg(long, short)]
+        session: Option<String>,
+    },
+
+    /// Resolve every comment on files matching a glob pattern
+    ResolveFile {
+        /// File glob, e.g. `src/lib.rs` or `src/**/*.rs`
+        pattern: String,
+
+        /// Only comments on these lines
+        #[arg(long, value_name = "START-END", value_parser = parse_line_range)]
+        lines: Option<RangeInclusive<usize>>,
+
+        /// Session ID (defaults to the latest session)
+        #[arg(long, short)]
+        session: Option<String>,
+    },
+}
+
+/// Execute the comment command
+pub fn execute(cmd: CommentCommand, workspace: Option<SessionManager>) -> Result<()> {
+    let Some(manager) = super::existing_manager(workspace)? else {
+        return Ok(());
+    };
+
+    match cmd {
+        CommentCommand::Tag {
+            tag,
+            ids,
+            all_imported,
+            session,
+        } => tag_comments(manager, &tag, &ids, all_imported, session.as_deref()),
+        CommentCommand::Search {
+            query,
+            fuzzy,
+            threshold,
+            regex,
+            session,
+        } => search_comments(
+            &manager,
+            &query,
+            fuzzy,
+            threshold,
+            regex,
+            session.as_deref(),
+        ),
+        CommentCommand::ResolveFile {
+            pattern,
+            lines,
+            session,
+        } => resolve_file(&manager, &pattern, lines, session.as_deref()),
+    }
+}
+
+/// Parse a `START-END` line range (a single `N` means just that line)
+fn parse_line_range(s: &str) -> Result<RangeInclusive<usize>> {
+    let (start, end) = s.split_once('-').unwrap_or((s, s));
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .context(format!("Expected START-END line numbers, got '{}'", s))
+    };
+    let (start, end) = (parse(start)?, parse(end)?);
+    if start > end {
+        anyhow::bail!("Line range {} ends before it starts", s);
+    }
+    Ok(start..=end)
+}
+
+/// A comment authored in an annotation file
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnnotationSpec {
+    /// File path as it appears in the diff
+    pub file: String,
+    /// Line number in the new version of the file
+    pub line: usize,
+    /// Severity (info/warning/critical)
+    #[serde(default)]
+    pub severity: Option<String>,
+    /// Comment content
+    pub content: String,
+    /// Tags for categorization
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Suggested fix, usually a fenced code block
+    #[serde(default)]
+    pub suggested_fix: Option<String>,
+}
+
+/// Parse annotation specs from YAML
+pub fn parse_annotations(yaml: &str) -> Result<Vec<AnnotationSpec>> {
+    serde_yaml::from_str(yaml).context("Failed to parse annotation file")
+}
+
+/// Find the diff line closest to a new-file line number
+///
+/// Only hunks whose new range covers the line are considered, so comments
+/// never attach to unrelated parts of the file.
+fn find_closest_line(file: &FileDiff, line_num: usize) -> Option<&Line> {
+    file.hunks
+        .iter()
+        .filter(|h| {
+            line_num >= h.new_range.start && line_num < h.new_range.end().max(h.new_range.start + 1)
+        })
+        .flat_map(|h| h.lines.iter())
+        .filter_map(|l| l.new_line_num.map(|n| (n.abs_diff(line_num), l)))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, l)| l)
+}
+
+/// Add comments from an annotation file to a session
+pub fn annotate_session(
+    manager: &SessionManager,
+    session_id: &str,
+    from: &Path,
+    config_path: &Path,
+) -> Result<()> {
+    use colored::Colorize;
+
+    let mut session = load_session(manager, Some(session_id))?;
+
+    let yaml =
+        std::fs::read_to_string(from).context(format!("Failed to read {}", from.display()))?;
+    let specs = parse_annotations(&yaml)?;
+
+    let config = Config::load(config_path).unwrap_or_default();
+    let validator = CommentValidator::new()
+        .with_suggested_fix_syntax_check(config.review.validation.check_suggested_fix_syntax);
+
+    let mut added = 0;
+    for spec in &specs {
+        let severity = match spec.severity.as_deref() {
+            Some(s) => match Severity::from_short_string(&s.to_lowercase()) {
+                Some(severity) => severity,
+                None => {
+                    eprintln!(
+                        "{} {}:{}: unknown severity '{}', skipping",
+                        "⚠".yellow(),
+                        spec.file,
+                        spec.line,
+                        s
+                    );
+                    continue;
+                }
+            },
+            None => Severity::Info,
+        };
+
+        let Some(file) = session
+            .diff_data
+            .get_file_by_path(&PathBuf::from(&spec.file))
+        else {
+            eprintln!("{} {}: file not in diff, skipping", "⚠".yellow(), spec.file);
+            continue;
+        };
+
+        let Some(line) = find_closest_line(file, spec.line) else {
+            eprintln!(
+                "{} {}:{}: line not in diff, skipping",
+                "⚠".yellow(),
+                spec.file,
+                spec.line
+            );
+            continue;
+        };
+
+        let side = match line.line_type {
+            LineType::Deleted => DiffSide::Old,
+            _ => DiffSide::New,
+        };
+
+        let mut builder = CommentBuilder::new(file.id.clone(), line.id.clone(), side)
+            .content(&spec.content)
+            .severity(severity)
+            .tags(spec.tags.iter().cloned())
+            .source("annotation")
+            .file_path(&spec.file)
+            .line_number(line.display_line_num().unwrap_or(spec.line));
+        if let Some(fix) = &spec.suggested_fix {
+            builder = builder.suggested_fix(fix);
+        }
+        let comment = builder.build().context(format!(
+            "Invalid annotation for {}:{}",
+            spec.file, spec.line
+        ))?;
+
+        for warning in validator.check_suggested_fix_roundtrips(&comment).warnings {
+            eprintln!("{} {}:{}: {}", "⚠".yellow(), spec.file, spec.line, warning);
+        }
+
+        session.comments.add(comment)?;
+        added += 1;
+    }
+
+    manager.save(&mut session)?;
+
+    println!(
+        "{} Added {} of {} annotations to session {}.",
+        "✓".green(),
+        added,
+        specs.len(),
+        session.id
+    );
+
+    Ok(())
+}
+
+/// Load a session by ID, or the latest session if no ID is given
+pub fn load_session(manager: &SessionManager, id: Option<&str>) -> Result<Session> {
+    match id {
+        Some(id) => {
+            let session_id = manager.resolve_id(id)?;
+            manager
+                .load(&session_id)
+                .context(format!("Session '{}' not found", id))
+        }
+        None => manager
+            .load_latest()?
+            .context("No sessions found. Use --session <ID> to select one"),
+    }
+}
+
+/// Check whether a comment was imported rather than written by hand
+fn is_imported(comment: &Comment) -> bool {
+    comment
+        .metadata
+        .source
+        .as_deref()
+        .map(|s| s != "manual")
+        .unwrap_or(false)
+}
+
+fn tag_comments(
+    manager: SessionManager,
+    tag: &str,
+    ids: &[String],
+    all_imported: bool,
+    session_id: Option<&str>,
+) -> Result<()> {
+    use colored::Colorize;
+
+    if ids.is_empty() && !all_imported {
+        anyhow::bail!("Specify comments with --id <ID> or use --all-imported");
+    }
+
+    let mut session = load_session(&manager, session_id)?;
+
+    let mut tagged = 0;
+    if !ids.is_empty() {
+        let comment_ids = ids
+            .iter()
+            .map(|id| CommentId::from_string(id).context(format!("Invalid comment ID: {}", id)))
+            .collect::<Result<Vec<_>>>()?;
+        tagged += session.comments.bulk_tag(&comment_ids, tag)?;
+    }
+    if all_imported {
+        tagged += session.comments.bulk_tag_filtered(is_imported, tag)?;
+    }
+
+    manager.save(&mut session)?;
+
+    println!(
+        "{} Tagged {} comments with '{}' in session {}.",
+        "✓".green(),
+        tagged,
+        tag.cyan(),
+        session.id
+    );
+
+    Ok(())
+}
+
+fn resolve_file(
+    manager: &SessionManager,
+    pattern: &str,
+    lines: Option<RangeInclusive<usize>>,
+    session_id: Option<&str>,
+) -> Result<()> {
+    use colored::Colorize;
+
+    let mut session = load_session(manager, session_id)?;
+    let resolved = session.comments.resolve_by_pattern(pattern, lines)?;
+    manager.save(&mut session)?;
+
+    println!(
+        "{} Resolved {} comments on '{}' in session {}.",
+        "✓".green(),
+        resolved,
+        pattern.cyan(),
+        session.id
+    );
+
+    Ok(())
+}
+
+fn search_comments(
+    manager: &SessionManager,
+    query: &str,
+    fuzzy: bool,
+    threshold: f64,
+    regex: bool,
+    session_id: Option<&str>,
+) -> Result<()> {
+    use colored::Colorize;
+
+    let session = load_session(manager, session_id)?;
+    let matches: Vec<(&Comment, Option<f64>)> = if fuzzy {
+        session
+            .comments
+            .fuzzy_search_content(query, threshold)
+            .into_iter()
+            .map(|(c, score)| (c, Some(score)))
+            .collect()
+    } else {
+        let mut found = if regex {
+            session.comments.search_by_regex(query)?
+        } else {
+            session.comments.search(query)
+        };
+        found.sort_by_key(|c| c.created_at);
+        found.into_iter().map(|c| (c, None)).collect()
+    };
+
+    if matches.is_empty() {
+        println!("No matching comments.");
+        return Ok(());
+    }
+    for (comment, score) in &matches {
+        let location = match (&comment.metadata.file_path, comment.metadata.line_number) {
+            (Some(path), Some(line)) => format!("{}:{}", path, line),
+            (Some(path), None) => path.clone(),
+            _ => comment.file_id().to_string(),
+        };
+        let score = score
+            .map(|s| format!(" ({:.0}%)", s * 100.0))
+            .unwrap_or_default();
+        println!(
+            "[{}] {} {}{}",
+            comment.severity.to_short_string(),
+            location.cyan(),
+            comment.content.lines().next().unwrap_or_default(),
+            score.dimmed()
+        );
+    }
+    println!(
+        "\n{} matching comments in session {}",
+        matches.len(),
+        session.id
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cr_core::types::{FileId, LineId};
+
+    #[test]
+    fn test_parse_line_range() {
+        assert_eq!(parse_line_range("10-20").unwrap(), 10..=20);
+        assert_eq!(parse_line_range("7").unwrap(), 7..=7);
+        assert!(parse_line_range("20-10").is_err());
+        assert!(parse_line_range("a-b").is_err());
+    }
+
+    fn build_comment(source: Option<&str>) -> Comment {
+        let builder = CommentBuilder::new(
+            FileId::from_string("f1"),
+            LineId::from_string("l1"),
+            DiffSide::New,
+        )
+        .content("Test");
+        match source {
+            Some(source) => builder.source(source).build().unwrap(),
+            None => builder.build().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_parse_annotations() {
+        let yaml = r#"
+- file: src/main.rs
+  line: 42
+  severity: warning
+  content: Check this
+  tags: [security]
+- file: src/lib.rs
+  line: 1
+  content: Minimal entry
+"#;
+        let specs = parse_annotations(yaml).unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[0].severity.as_deref(), Some("warning"));
+        assert_eq!(specs[0].tags, vec!["security".to_string()]);
+        assert!(specs[1].severity.is_none());
+        assert!(specs[1].tags.is_empty());
+    }
+
+    #[test]
+    fn test_find_closest_line() {
+        use cr_core::diff::DiffParser;
+
+        let diff = DiffParser::new()
+            .parse("diff --git a/a.rs b/a.rs\n@@ -10,2 +10,3 @@\n ctx\n-old\n+new\n+more\n")
+            .unwrap();
+        let file = &diff.files[0];
+
+        assert_eq!(find_closest_line(file, 11).unwrap().content, "new");
+        assert_eq!(find_closest_line(file, 12).unwrap().content, "more");
+        assert!(find_closest_line(file, 50).is_none());
+    }
+
+    #[test]
+    fn test_is_imported() {
+        assert!(is_imported(&build_comment(Some("clippy"))));
+        assert!(!is_imported(&build_comment(Some("manual"))));
+        assert!(!is_imported(&build_comment(None)));
+    }
+}
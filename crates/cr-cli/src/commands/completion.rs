@@ -0,0 +1,176 @@
+//! Completion command
+//!
+//! Generate shell completion scripts.
+
+use anyhow::{Context, Result};
+use clap::{Args, CommandFactory};
+use clap_complete::Shell;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Arguments for the completion command
+#[derive(Debug, Args)]
+pub struct CompletionArgs {
+    /// Shell to generate completions for
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+/// Bash wrapper completing `--session`/`-s` with session IDs and `export --format`
+/// with the formats `export --list-formats` reports
+const BASH_DYNAMIC: &str = r#"
+_cr-helper_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" prev="${COMP_WORDS[COMP_CWORD-1]}"
+    case "$prev" in
+        --session|-s)
+            COMPREPLY=($(compgen -W "$(cr-helper session list --porcelain --limit 100 2>/dev/null | grep SESSION_ID | cut -d= -f2)" -- "$cur"))
+            return 0
+            ;;
+        --format|-f)
+            if [[ "${COMP_WORDS[1]}" == export ]]; then
+                COMPREPLY=($(compgen -W "$(cr-helper export --list-formats 2>/dev/null)" -- "$cur"))
+                return 0
+            fi
+            ;;
+    esac
+    _cr__helper "$@"
+}
+
+complete -F _cr-helper_dynamic -o bashdefault -o default cr-helper
+"#;
+
+/// Zsh counterpart of [`BASH_DYNAMIC`]
+const ZSH_DYNAMIC: &str = r#"
+_cr-helper_dynamic() {
+    case "${words[CURRENT-1]}" in
+        --session|-s)
+            compadd -- ${(f)"$(cr-helper session list --porcelain --limit 100 2>/dev/null | grep SESSION_ID | cut -d= -f2)"}
+            return
+            ;;
+        --format|-f)
+            if [[ "${words[2]}" == export ]]; then
+                compadd -- ${(f)"$(cr-helper export --list-formats 2>/dev/null)"}
+                return
+            fi
+            ;;
+    esac
+    _cr-helper "$@"
+}
+
+compdef _cr-helper_dynamic cr-helper
+"#;
+
+/// Execute the completion command
+pub fn execute(args: CompletionArgs) -> Result<()> {
+    let script = generate_script(args.shell);
+    std::io::stdout()
+        .write_all(script.as_bytes())
+        .context("Failed to write to stdout")
+}
+
+/// Completion script for `shell`, with dynamic session and format
+/// completion for bash and zsh
+pub fn generate_script(shell: Shell) -> String {
+    let mut command = super::Cli::command();
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut command, "cr-helper", &mut script);
+
+    let mut script = String::from_utf8_lossy(&script).into_owned();
+    match shell {
+        Shell::Bash => script.push_str(BASH_DYNAMIC),
+        Shell::Zsh => script.push_str(ZSH_DYNAMIC),
+        _ => {}
+    }
+    script
+}
+
+/// Shell startup file and the line that loads completions from it, for the
+/// user's `$SHELL` (bash and zsh only)
+pub fn rc_file_entry() -> Option<(PathBuf, String)> {
+    let shell = match Shell::from_env()? {
+        shell @ (Shell::Bash | Shell::Zsh) => shell,
+        _ => return None,
+    };
+    let home = dirs::home_dir()?;
+    let (rc_file, line) = match shell {
+        Shell::Bash => (".bashrc", "source <(cr-helper completion bash)"),
+        _ => (".zshrc", "eval \"$(cr-helper completion zsh)\""),
+    };
+    Some((home.join(rc_file), line.to_string()))
+}
+
+/// Whether `rc_file` already has `line`
+pub fn rc_file_has_entry(rc_file: &Path, line: &str) -> bool {
+    fs::read_to_string(rc_file)
+        .map(|content| content.lines().any(|l| l.trim() == line))
+        .unwrap_or(false)
+}
+
+/// Append `line` to `rc_file` unless it is already there
+///
+/// Returns whether the file was changed.
+pub fn add_to_rc_file(rc_file: &Path, line: &str) -> Result<bool> {
+    let existing = match fs::read_to_string(rc_file) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).context(format!("Failed to read {}", rc_file.display())),
+    };
+    if existing.lines().any(|l| l.trim() == line) {
+        return Ok(false);
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(rc_file)
+        .context(format!("Failed to open {}", rc_file.display()))?;
+    // Leave a blank line after any existing content
+    let separator = if existing.is_empty() {
+        ""
+    } else if existing.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    write!(file, "{}# cr-helper shell completion\n{}\n", separator, line)
+        .context(format!("Failed to write {}", rc_file.display()))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_script() {
+        let bash = generate_script(Shell::Bash);
+        assert!(bash.contains("_cr__helper()"));
+        assert!(bash.contains("session list --porcelain"));
+        assert!(bash.ends_with("complete -F _cr-helper_dynamic -o bashdefault -o default cr-helper\n"));
+
+        let zsh = generate_script(Shell::Zsh);
+        assert!(zsh.starts_with("#compdef cr-helper"));
+        assert!(zsh.contains("export --list-formats"));
+
+        let fish = generate_script(Shell::Fish);
+        assert!(!fish.contains("_cr-helper_dynamic"));
+    }
+
+    #[test]
+    fn test_add_to_rc_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let rc_file = dir.path().join(".bashrc");
+        fs::write(&rc_file, "export EDITOR=vim").unwrap();
+
+        let line = "source <(cr-helper completion bash)";
+        assert!(!rc_file_has_entry(&rc_file, line));
+        assert!(add_to_rc_file(&rc_file, line).unwrap());
+        assert!(rc_file_has_entry(&rc_file, line));
+        assert!(!add_to_rc_file(&rc_file, line).unwrap());
+        assert_eq!(
+            fs::read_to_string(&rc_file).unwrap(),
+            "export EDITOR=vim\n\n# cr-helper shell completion\nsource <(cr-helper completion bash)\n"
+        );
+    }
+}
@@ -5,7 +5,7 @@
 use anyhow::{Context, Result};
 use clap::Subcommand;
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
 
 /// Config subcommands
 #[derive(Debug, Subcommand)]
@@ -35,25 +35,21 @@ pub enum ConfigCommand {
 }
 
 /// Execute the config command
-pub fn execute(cmd: ConfigCommand) -> Result<()> {
+///
+/// `config_path` is the `--config` file, or `.cr-helper/config.toml`.
+pub fn execute(cmd: ConfigCommand, config_path: &Path) -> Result<()> {
     match cmd {
-        ConfigCommand::Show { json } => show_config(json),
-        ConfigCommand::Edit => edit_config(),
-        ConfigCommand::Reset { force } => reset_config(force),
-        ConfigCommand::Validate => validate_config(),
+        ConfigCommand::Show { json } => show_config(config_path, json),
+        ConfigCommand::Edit => edit_config(config_path),
+        ConfigCommand::Reset { force } => reset_config(config_path, force),
+        ConfigCommand::Validate => validate_config(config_path),
         ConfigCommand::Templates => list_templates(),
     }
 }
 
-fn get_config_path() -> PathBuf {
-    PathBuf::from(".cr-helper/config.toml")
-}
-
-fn show_config(as_json: bool) -> Result<()> {
+fn show_config(config_path: &Path, as_json: bool) -> Result<()> {
     use colored::Colorize;
 
-    let config_path = get_config_path();
-
     if !config_path.exists() {
         eprintln!(
             "{} Configuration not found. Run '{}' to create.",
@@ -63,7 +59,7 @@ fn show_config(as_json: bool) -> Result<()> {
         return Ok(());
     }
 
-    let content = fs::read_to_string(&config_path)?;
+    let content = fs::read_to_string(config_path)?;
 
     if as_json {
         let config: toml::Value = toml::from_str(&content)?;
@@ -79,11 +75,9 @@ fn show_config(as_json: bool) -> Result<()> {
     Ok(())
 }
 
-fn edit_config() -> Result<()> {
+fn edit_config(config_path: &Path) -> Result<()> {
     use colored::Colorize;
 
-    let config_path = get_config_path();
-
     if !config_path.exists() {
         eprintln!(
             "{} Configuration not found. Run '{}' to create.",
@@ -107,13 +101,13 @@ fn edit_config() -> Result<()> {
     println!("Opening {} in {}...", config_path.display(), editor.cyan());
 
     let status = std::process::Command::new(&editor)
-        .arg(&config_path)
+        .arg(config_path)
         .status()
         .context(format!("Failed to open editor: {}", editor))?;
 
     if status.success() {
         // Validate after edit
-        let content = fs::read_to_string(&config_path)?;
+        let content = fs::read_to_string(config_path)?;
         match toml::from_str::<toml::Value>(&content) {
             Ok(_) => println!("{} Configuration saved and validated.", "✓".green()),
             Err(e) => eprintln!("{} Configuration has errors: {}", "✗".red(), e),
@@ -123,11 +117,9 @@ fn edit_config() -> Result<()> {
     Ok(())
 }
 
-fn reset_config(force: bool) -> Result<()> {
+fn reset_config(config_path: &Path, force: bool) -> Result<()> {
     use colored::Colorize;
 
-    let config_path = get_config_path();
-
     if !force {
         use dialoguer::Confirm;
 
@@ -149,7 +141,7 @@ fn reset_config(force: bool) -> Result<()> {
             config_path.display(),
             chrono::Local::now().format("%Y%m%d-%H%M%S")
         );
-        fs::copy(&config_path, &backup_path)?;
+        fs::copy(config_path, &backup_path)?;
         println!("{} Backed up to {}", "✓".green(), backup_path);
     }
 
@@ -173,10 +165,14 @@ include_suggestions = true
 [diff]
 include_patterns = ["*"]
 exclude_patterns = [".git/", "node_modules/", "target/", "__pycache__/"]
+
+[storage]
+# Oldest sessions beyond this count are deleted (0 = unlimited)
+max_sessions = 100
 "#;
 
     fs::create_dir_all(config_path.parent().unwrap())?;
-    fs::write(&config_path, default_config)?;
+    fs::write(config_path, default_config)?;
 
     println!(
         "{} Configuration reset to defaults.",
@@ -186,11 +182,9 @@ exclude_patterns = [".git/", "node_modules/", "target/", "__pycache__/"]
     Ok(())
 }
 
-fn validate_config() -> Result<()> {
+fn validate_config(config_path: &Path) -> Result<()> {
     use colored::Colorize;
 
-    let config_path = get_config_path();
-
     if !config_path.exists() {
         eprintln!(
             "{} Configuration not found at {}",
@@ -200,7 +194,7 @@ fn validate_config() -> Result<()> {
         return Ok(());
     }
 
-    let content = fs::read_to_string(&config_path)?;
+    let content = fs::read_to_string(config_path)?;
 
     match toml::from_str::<toml::Value>(&content) {
         Ok(config) => {
@@ -274,8 +268,13 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_get_config_path() {
-        let path = get_config_path();
-        assert!(path.ends_with("config.toml"));
+    fn test_reset_config_uses_given_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("custom").join("cr.toml");
+        reset_config(&path, true).unwrap();
+
+        let config = cr_core::config::Config::load(&path).unwrap();
+        assert_eq!(config.storage.max_sessions, 100);
+        assert!(!dir.path().join(".cr-helper").exists());
     }
 }
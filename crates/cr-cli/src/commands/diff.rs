@@ -0,0 +1,157 @@
+//! Diff command
+//!
+//! Show `git diff` output without the files excluded by `.crignore`.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use cr_core::config::Config;
+use cr_core::diff::{CrIgnore, DiffData, DiffParser, DiffSource, CRIGNORE_PATH};
+
+/// Arguments for the diff command
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// Show staged changes
+    #[arg(long)]
+    pub cached: bool,
+
+    /// Show a per-file summary instead of the patch
+    #[arg(long)]
+    pub stat: bool,
+
+    /// Limit the diff to these paths (after `--`)
+    #[arg(last = true)]
+    pub pathspecs: Vec<String>,
+}
+
+/// Execute the diff command
+pub fn execute(args: DiffArgs, config_path: &Path) -> Result<()> {
+    let config = Config::load(config_path).unwrap_or_default();
+    let ignore = CrIgnore::load(Path::new(CRIGNORE_PATH))
+        .context(format!("Failed to read {}", CRIGNORE_PATH))?;
+
+    let parser = DiffParser::with_config(config.diff.parser_config());
+    let mut diff = parser.parse_from_git(&diff_source(&args))?;
+    let ignored = ignore.filter_diff(&mut diff);
+    tracing::info!("Skipped {} files matching {}", ignored, CRIGNORE_PATH);
+
+    if args.stat {
+        print!("{}", render_stat(&diff));
+        return Ok(());
+    }
+
+    let patch = diff.patch_string();
+    if patch.is_empty() {
+        return Ok(());
+    }
+    if std::io::stdout().is_terminal() {
+        for pager in [&["delta"][..], &["less", "-R"]] {
+            if page(pager, &patch).is_ok() {
+                return Ok(());
+            }
+        }
+    }
+    std::io::stdout()
+        .write_all(patch.as_bytes())
+        .context("Failed to write to stdout")
+}
+
+/// The git diff source `cr-helper review` would use for the same flags
+fn diff_source(args: &DiffArgs) -> DiffSource {
+    if args.pathspecs.is_empty() {
+        return if args.cached {
+            DiffSource::Staged
+        } else {
+            DiffSource::WorkingTree
+        };
+    }
+    let mut git_args = if args.cached {
+        DiffSource::Staged.to_git_args()
+    } else {
+        Vec::new()
+    };
+    git_args.push("--".to_string());
+    git_args.extend(args.pathspecs.iter().cloned());
+    DiffSource::Custom { args: git_args }
+}
+
+/// Pipe `text` into a pager command, waiting for it to exit
+fn page(command: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager may quit before reading everything
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// `git diff --stat`-style summary
+fn render_stat(diff: &DiffData) -> String {
+    let summary = diff.stat_summary();
+    if summary.is_empty() {
+        return String::new();
+    }
+
+    let width = summary
+        .iter()
+        .map(|(path, ..)| path.display().to_string().chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut output = String::new();
+    for (path, insertions, deletions, bar) in &summary {
+        output.push_str(&format!(
+            " {:<width$} | {:>4} {}\n",
+            path.display(),
+            insertions + deletions,
+            bar,
+            width = width
+        ));
+    }
+    output.push_str(&format!(
+        " {} file(s) changed, {} insertion(s)(+), {} deletion(s)(-)\n",
+        summary.len(),
+        diff.stats.insertions,
+        diff.stats.deletions
+    ));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_source() {
+        let args = |cached: bool, pathspecs: &[&str]| DiffArgs {
+            cached,
+            stat: false,
+            pathspecs: pathspecs.iter().map(|p| p.to_string()).collect(),
+        };
+
+        assert!(matches!(diff_source(&args(false, &[])), DiffSource::WorkingTree));
+        assert!(matches!(diff_source(&args(true, &[])), DiffSource::Staged));
+        assert_eq!(
+            diff_source(&args(true, &["src"])).to_git_args(),
+            ["--staged", "--", "src"]
+        );
+        assert_eq!(diff_source(&args(false, &["src"])).to_git_args(), ["--", "src"]);
+    }
+
+    #[test]
+    fn test_render_stat() {
+        let input = "diff --git a/src/main.rs b/src/main.rs\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1 +1,2 @@\n-a\n+b\n+c\n";
+        let diff = DiffParser::new().parse(input).unwrap();
+
+        let stat = render_stat(&diff);
+        assert!(stat.starts_with(" src/main.rs |    3 "));
+        assert!(stat.ends_with(" 1 file(s) changed, 2 insertion(s)(+), 1 deletion(s)(-)\n"));
+        assert!(render_stat(&DiffData::empty()).is_empty());
+    }
+}
@@ -3,9 +3,13 @@
 //! Diagnose installation and configuration.
 
 use anyhow::Result;
-use clap::Args;
+use clap::{Args, ValueEnum};
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::Duration;
+
+/// Pinned sessions beyond this are better archived
+const MAX_PINNED_SESSIONS: usize = 20;
 
 /// Arguments for the doctor command
 #[derive(Debug, Args)]
@@ -18,9 +22,55 @@ pub struct DoctorArgs {
     #[arg(long)]
     pub project: bool,
 
-    /// Verbose output
-    #[arg(short, long)]
+    /// Verbose output (set from the global `--verbose` flag)
+    #[arg(skip)]
     pub verbose: bool,
+
+    /// Treat warnings as errors
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "text")]
+    pub output: DoctorOutput,
+
+    /// Check that a patch file for `review --from-patch` is readable
+    #[arg(long, value_name = "PATH")]
+    pub from_patch: Option<PathBuf>,
+
+    /// Measure the round-trip latency of each detected agent
+    #[arg(long)]
+    pub ping: bool,
+
+    /// Remove orphaned temp and lock files and stale review locks instead of
+    /// only reporting them
+    #[arg(long)]
+    pub fix: bool,
+}
+
+/// Output format for doctor results
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum DoctorOutput {
+    /// Human-readable report
+    Text,
+    /// JSON object with errors, warnings and pass status
+    Json,
+}
+
+/// Outcome of a doctor run
+#[derive(Debug, Default)]
+pub struct DoctorResult {
+    /// Failed checks
+    pub errors: Vec<String>,
+    /// Checks that passed with a warning
+    pub warnings: Vec<String>,
+}
+
+impl DoctorResult {
+    /// Check if no errors were found
+    pub fn passed(&self) -> bool {
+        self.errors.is_empty()
+    }
 }
 
 /// Check result
@@ -58,56 +108,115 @@ impl CheckResult {
             suggestion: suggestion.map(|s| s.to_string()),
         }
     }
+
+    fn is_warning(&self) -> bool {
+        self.passed && self.message.starts_with('⚠')
+    }
+
+    /// One-line summary used in machine-readable output
+    fn summary(&self) -> String {
+        let message = self.message.trim_start_matches('⚠').trim_start();
+        format!("{}: {}", self.name, message)
+    }
+}
+
+/// Split check results into errors and warnings
+fn collect_result<'a>(results: impl IntoIterator<Item = &'a CheckResult>, strict: bool) -> DoctorResult {
+    let mut outcome = DoctorResult::default();
+    for result in results {
+        if !result.passed {
+            outcome.errors.push(result.summary());
+        } else if result.is_warning() {
+            if strict {
+                outcome.errors.push(result.summary());
+            } else {
+                outcome.warnings.push(result.summary());
+            }
+        }
+    }
+    outcome
 }
 
 /// Execute the doctor command
-pub fn execute(args: DoctorArgs) -> Result<()> {
-    use colored::Colorize;
-
-    let mut results = Vec::new();
-    let mut warnings = Vec::new();
-    let mut errors = Vec::new();
+pub fn execute(args: DoctorArgs, config_path: &std::path::Path) -> Result<DoctorResult> {
+    let config = cr_core::config::Config::load(config_path).unwrap_or_default();
+    let mut sections = Vec::new();
 
     // System checks
-    println!("\n{}", "1. System Environment".bold().underline());
-    results.extend(check_system_environment());
+    sections.push(("1. System Environment", check_system_environment()));
 
     // Project checks
     if args.project || !args.claude_code {
-        println!("\n{}", "2. Project Configuration".bold().underline());
-        results.extend(check_project_configuration());
+        let mut results = check_project_configuration(args.fix, &config);
+        if let Some(path) = &args.from_patch {
+            results.push(check_patch_file(path));
+        }
+        sections.push(("2. Project Configuration", results));
     }
 
     // Claude Code checks
     if args.claude_code || !args.project {
-        println!("\n{}", "3. Claude Code Integration".bold().underline());
+        let mut results = check_detected_agents(std::path::Path::new("."));
         results.extend(check_claude_code_integration());
+        sections.push(("3. Claude Code Integration", results));
+    }
+
+    // Agent latency checks
+    if args.ping {
+        sections.push(("4. Agent Latency", check_agent_latency(std::path::Path::new("."), &config)));
     }
 
-    // Print results
-    for result in &results {
-        let status = if result.passed {
-            if result.message.starts_with('⚠') {
-                "⚠".yellow()
+    let outcome = collect_result(sections.iter().flat_map(|(_, results)| results), args.strict);
+
+    match args.output {
+        DoctorOutput::Json => {
+            let json = serde_json::json!({
+                "errors": outcome.errors,
+                "warnings": outcome.warnings,
+                "passed": outcome.passed(),
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        DoctorOutput::Text => print_report(&sections, &args),
+    }
+
+    Ok(outcome)
+}
+
+/// Print the human-readable report, each section followed by its results
+fn print_report(sections: &[(&str, Vec<CheckResult>)], args: &DoctorArgs) {
+    use colored::Colorize;
+
+    let mut warnings = Vec::new();
+    let mut errors = Vec::new();
+
+    for (section, results) in sections {
+        println!("\n{}", section.bold().underline());
+
+        for result in results {
+            let status = if result.passed {
+                if result.is_warning() {
+                    "⚠".yellow()
+                } else {
+                    "✓".green()
+                }
             } else {
-                "✓".green()
-            }
-        } else {
-            "✗".red()
-        };
+                "✗".red()
+            };
 
-        println!("   {} {}: {}", status, result.name, result.message);
+            println!("   {} {}: {}", status, result.name, result.message);
 
-        if args.verbose {
-            if let Some(suggestion) = &result.suggestion {
-                println!("     {}", suggestion.dimmed());
+            if args.verbose {
+                if let Some(suggestion) = &result.suggestion {
+                    println!("     {}", suggestion.dimmed());
+                }
             }
-        }
 
-        if !result.passed {
-            errors.push(result);
-        } else if result.message.starts_with('⚠') {
-            warnings.push(result);
+            if !result.passed || (args.strict && result.is_warning()) {
+                errors.push(result);
+            } else if result.is_warning() {
+                warnings.push(result);
+            }
         }
     }
 
@@ -142,8 +251,6 @@ pub fn execute(args: DoctorArgs) -> Result<()> {
     if errors.is_empty() && warnings.is_empty() {
         println!("\n{} All checks passed!", "✓".green());
     }
-
-    Ok(())
 }
 
 fn check_system_environment() -> Vec<CheckResult> {
@@ -190,13 +297,23 @@ fn check_system_environment() -> Vec<CheckResult> {
     results
 }
 
-fn check_project_configuration() -> Vec<CheckResult> {
+fn check_project_configuration(fix: bool, config: &cr_core::config::Config) -> Vec<CheckResult> {
     let mut results = Vec::new();
 
     // Git repository
     let is_git_repo = PathBuf::from(".git").exists();
     if is_git_repo {
         results.push(CheckResult::ok("Git repository", "detected"));
+    } else if cr_integration::HgDetector::is_hg_repo(std::path::Path::new(".")) {
+        results.push(CheckResult::ok(
+            "Mercurial repository",
+            "detected (use 'cr-helper review --hg')",
+        ));
+    } else if cr_integration::SvnDetector::is_svn_working_copy(std::path::Path::new(".")) {
+        results.push(CheckResult::ok(
+            "Subversion working copy",
+            "detected (use 'cr-helper review --svn')",
+        ));
     } else {
         results.push(CheckResult::warn(
             "Git repository",
@@ -257,9 +374,309 @@ fn check_project_configuration() -> Vec<CheckResult> {
         ));
     }
 
+    results.extend(check_temp_files(fix));
+    results.extend(check_lock_files(fix));
+    results.extend(check_pinned_sessions());
+    results.extend(check_review_lock(std::path::Path::new("."), fix));
+    results.extend(check_latest_session(config));
+
     results
 }
 
+/// Session storage for the current project, if any exists
+fn detect_session_storage() -> Option<cr_storage::FileSystemStorage> {
+    super::open_existing_storage().ok().flatten()
+}
+
+/// Report temp files left by interrupted session writes
+///
+/// They are only removed with `--fix`, so repeated runs without it report
+/// the same thing.
+fn check_temp_files(fix: bool) -> Option<CheckResult> {
+    use cr_core::session::SessionStorage;
+
+    let storage = detect_session_storage()?;
+    let result = if fix {
+        storage
+            .prune_orphaned_temp_files()
+            .map(|n| CheckResult::ok("Temp files", &format!("removed {} orphaned temp file(s)", n)))
+    } else {
+        storage.orphaned_temp_files().map(|files| temp_files_result(files.len()))
+    };
+    Some(result.unwrap_or_else(|e| {
+        CheckResult::warn(
+            "Temp files",
+            &format!("could not scan sessions directory: {}", e),
+            None,
+        )
+    }))
+}
+
+fn temp_files_result(orphaned: usize) -> CheckResult {
+    if orphaned > 0 {
+        CheckResult::warn(
+            "Temp files",
+            &format!("{} orphaned temp file(s) from interrupted writes", orphaned),
+            Some("Remove them with 'cr-helper doctor --fix'"),
+        )
+    } else {
+        CheckResult::ok("Temp files", "no orphaned temp files")
+    }
+}
+
+/// Report lock files left behind by deleted sessions
+///
+/// Like temp files, they are only removed with `--fix`.
+fn check_lock_files(fix: bool) -> Option<CheckResult> {
+    let storage = detect_session_storage()?;
+    let result = if fix {
+        storage
+            .prune_orphaned_lock_files()
+            .map(|n| CheckResult::ok("Lock files", &format!("removed {} orphaned lock file(s)", n)))
+    } else {
+        storage.orphaned_lock_files().map(|files| lock_files_result(files.len()))
+    };
+    Some(result.unwrap_or_else(|e| {
+        CheckResult::warn(
+            "Lock files",
+            &format!("could not scan sessions directory: {}", e),
+            None,
+        )
+    }))
+}
+
+fn lock_files_result(orphaned: usize) -> CheckResult {
+    if orphaned > 0 {
+        CheckResult::warn(
+            "Lock files",
+            &format!("{} lock file(s) of deleted sessions", orphaned),
+            Some("Remove them with 'cr-helper doctor --fix'"),
+        )
+    } else {
+        CheckResult::ok("Lock files", "no orphaned lock files")
+    }
+}
+
+/// Check that a `--from-patch` file is readable, non-empty and parses
+fn check_patch_file(path: &std::path::Path) -> CheckResult {
+    let content = match std::fs::read(path) {
+        Ok(content) => content,
+        Err(e) => {
+            return CheckResult::fail(
+                "Patch file",
+                &format!("cannot read {}: {}", path.display(), e),
+                None,
+            )
+        }
+    };
+    if content.iter().all(u8::is_ascii_whitespace) {
+        return CheckResult::fail(
+            "Patch file",
+            &format!("{} is empty", path.display()),
+            Some("Regenerate it with `git diff > file.patch`"),
+        );
+    }
+    match cr_core::diff::DiffParser::new().parse_from_file(path) {
+        Ok(diff) => CheckResult::ok(
+            "Patch file",
+            &format!("{} ({} files)", path.display(), diff.files.len()),
+        ),
+        Err(e) => CheckResult::warn("Patch file", &format!("{}: {}", path.display(), e), None),
+    }
+}
+
+/// Warn when so many sessions are pinned that rotation can't keep up
+fn check_pinned_sessions() -> Option<CheckResult> {
+    use cr_core::session::{SessionFilter, SessionManager};
+
+    let storage = detect_session_storage()?;
+    let pinned = SessionManager::new(storage)
+        .search(SessionFilter::new().with_pinned(true))
+        .ok()?
+        .len();
+    Some(pinned_sessions_result(pinned))
+}
+
+fn pinned_sessions_result(pinned: usize) -> CheckResult {
+    if pinned > MAX_PINNED_SESSIONS {
+        CheckResult::warn(
+            "Pinned sessions",
+            &format!("{} sessions are pinned (more than {})", pinned, MAX_PINNED_SESSIONS),
+            Some("Archive old milestones with 'cr-helper export' and 'cr-helper session unpin'"),
+        )
+    } else {
+        CheckResult::ok("Pinned sessions", &format!("{} pinned", pinned))
+    }
+}
+
+/// Check for a review lock left behind by a review that did not exit cleanly
+///
+/// A stale lock is removed with `--fix`.
+fn check_review_lock(project_dir: &std::path::Path, fix: bool) -> Option<CheckResult> {
+    use cr_integration::{LockFile, LOCK_FILE};
+
+    let result = match LockFile::read(project_dir) {
+        Ok(None) => return None,
+        Ok(Some(session)) if session.is_alive() => CheckResult::ok(
+            "Review lock",
+            &format!("review running (pid {})", session.pid),
+        ),
+        Ok(Some(_)) if fix => match LockFile::remove_stale(project_dir) {
+            Ok(Some(session)) => CheckResult::ok(
+                "Review lock",
+                &format!("removed stale lock from pid {}", session.pid),
+            ),
+            // Taken over by a review that started in the meantime
+            Ok(None) => return check_review_lock(project_dir, false),
+            Err(e) => CheckResult::warn(
+                "Review lock",
+                &format!("could not remove stale lock: {}", e),
+                Some(&format!("Remove it with: rm {}", LOCK_FILE)),
+            ),
+        },
+        Ok(Some(session)) => CheckResult::warn(
+            "Review lock",
+            &format!("stale lock from pid {}", session.pid),
+            Some("Remove it with 'cr-helper doctor --fix'"),
+        ),
+        Err(e) => CheckResult::warn(
+            "Review lock",
+            &e.to_string(),
+            Some(&format!("Remove it with: rm {}", LOCK_FILE)),
+        ),
+    };
+    Some(result)
+}
+
+/// Check whether the latest session was created on the current commit
+/// and has enough of its changed lines commented
+fn check_latest_session(config: &cr_core::config::Config) -> Vec<CheckResult> {
+    use cr_core::session::SessionManager;
+
+    let Some(storage) = detect_session_storage() else {
+        return Vec::new();
+    };
+    let Ok(Some(session)) = SessionManager::new(storage).load_latest() else {
+        return Vec::new();
+    };
+
+    let mut results = vec![check_session_head(&session)];
+    results.extend(check_coverage(&session, config.review.min_coverage_percent));
+    results
+}
+
+fn check_session_head(session: &cr_core::session::Session) -> CheckResult {
+    match session.is_stale_in(std::path::Path::new(".")) {
+        Ok(true) => CheckResult::warn(
+            "Latest session",
+            &format!("{} was created on a different commit", session.id),
+            Some("Run 'cr-helper review' to start a session with a fresh diff"),
+        ),
+        Ok(false) => CheckResult::ok("Latest session", "up to date with HEAD"),
+        Err(_) => CheckResult::warn(
+            "Latest session",
+            "could not determine git HEAD",
+            None,
+        ),
+    }
+}
+
+/// Warn if the latest session's comment coverage is below `[review] min_coverage_percent`
+fn check_coverage(session: &cr_core::session::Session, min: f64) -> Option<CheckResult> {
+    if session.diff_data.files.is_empty() {
+        return None;
+    }
+
+    let coverage = session.summary().coverage_percent;
+
+    let result = if coverage < min {
+        CheckResult::warn(
+            "Review coverage",
+            &format!("{:.0}% of changed lines commented (minimum {:.0}%)", coverage, min),
+            Some("Comment on more of the changed code, or lower [review] min_coverage_percent"),
+        )
+    } else {
+        CheckResult::ok("Review coverage", &format!("{:.0}% of changed lines commented", coverage))
+    };
+    Some(result)
+}
+
+/// One result per agent detected in `dir`, with its confidence score
+fn check_detected_agents(dir: &std::path::Path) -> Vec<CheckResult> {
+    let ranked = cr_integration::detect_agents_in(dir)
+        .map(|detected| detected.ranked())
+        .unwrap_or_default();
+    if ranked.is_empty() {
+        return vec![CheckResult::warn(
+            "Detected agents",
+            "none",
+            Some("Run in a project set up for an agent, e.g. with a .claude/ directory"),
+        )];
+    }
+    ranked
+        .iter()
+        .map(|(agent, score)| {
+            CheckResult::ok(
+                &format!("Agent: {}", agent.name()),
+                &format!("confidence {}", score),
+            )
+        })
+        .collect()
+}
+
+/// Ping each detected agent that has an adapter, plus GitHub when `gh` is installed
+fn check_agent_latency(dir: &std::path::Path, config: &cr_core::config::Config) -> Vec<CheckResult> {
+    use cr_integration::{AgentAdapter, AgentType};
+
+    let max = Duration::from_millis(config.health.max_agent_latency_ms);
+
+    let mut results = Vec::new();
+    let detected = cr_integration::detect_agents_in(dir)
+        .map(|detected| detected.ranked())
+        .unwrap_or_default();
+    for (agent, _) in detected {
+        let adapter: Box<dyn AgentAdapter> = match agent {
+            AgentType::ClaudeCode => {
+                Box::new(cr_integration::ClaudeCodeAdapter::with_project_dir(dir.to_path_buf()))
+            }
+            _ => continue,
+        };
+        results.push(latency_result(agent.name(), adapter.ping(), max));
+    }
+
+    let has_gh = Command::new("gh")
+        .arg("--version")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    if has_gh {
+        let github = cr_integration::GitHubAdapter::with_project_dir(dir.to_path_buf());
+        results.push(latency_result("GitHub", github.ping(), max));
+    }
+
+    if results.is_empty() {
+        results.push(CheckResult::warn(
+            "Agent latency",
+            "no agent to ping",
+            Some("Only Claude Code and GitHub (via gh) support --ping"),
+        ));
+    }
+    results
+}
+
+/// Fail when `latency` exceeds `[health] max_agent_latency_ms`; warn if the ping failed
+fn latency_result(name: &str, latency: Result<Duration>, max: Duration) -> CheckResult {
+    match latency {
+        Ok(latency) if latency > max => CheckResult::fail(
+            name,
+            &format!("{}ms (limit {}ms)", latency.as_millis(), max.as_millis()),
+            Some("Check the agent's connection, or raise [health] max_agent_latency_ms"),
+        ),
+        Ok(latency) => CheckResult::ok(name, &format!("{}ms", latency.as_millis())),
+        Err(e) => CheckResult::warn(name, &format!("ping failed: {:#}", e), None),
+    }
+}
+
 fn check_claude_code_integration() -> Vec<CheckResult> {
     let mut results = Vec::new();
 
@@ -404,4 +821,131 @@ mod tests {
         assert!(!result.passed);
         assert!(result.suggestion.is_some());
     }
+
+    #[test]
+    fn test_check_review_lock() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(check_review_lock(dir.path(), false).is_none());
+
+        std::fs::create_dir_all(dir.path().join(".claude")).unwrap();
+        std::fs::write(
+            dir.path().join(cr_integration::LOCK_FILE),
+            r#"{"pid":4294967295,"project_path":"/p","started_at":"2024-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        let result = check_review_lock(dir.path(), false).unwrap();
+        assert!(result.message.contains("stale lock from pid 4294967295"));
+        assert!(result.suggestion.unwrap().contains("--fix"));
+
+        let result = check_review_lock(dir.path(), true).unwrap();
+        assert!(result.passed && !result.is_warning());
+        assert!(result.message.contains("removed stale lock from pid 4294967295"));
+        assert!(check_review_lock(dir.path(), true).is_none());
+    }
+
+    #[test]
+    fn test_check_detected_agents() {
+        let dir = tempfile::tempdir().unwrap();
+        let results = check_detected_agents(dir.path());
+        // A global ~/.claude install is detected even in an empty project
+        let global = dirs::home_dir().is_some_and(|h| h.join(".claude").is_dir());
+        assert_eq!(results[0].is_warning(), !global);
+
+        std::fs::create_dir(dir.path().join(".claude")).unwrap();
+        std::fs::create_dir(dir.path().join(".cursor")).unwrap();
+        let results = check_detected_agents(dir.path());
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.passed && !r.is_warning()));
+        assert_eq!(results[0].message, "confidence 100");
+    }
+
+    #[test]
+    fn test_check_patch_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("change.patch");
+        assert!(!check_patch_file(&path).passed);
+
+        std::fs::write(&path, "\n").unwrap();
+        assert!(check_patch_file(&path).message.contains("is empty"));
+
+        std::fs::write(&path, "--- a/x.rs\n+++ b/x.rs\n@@ -1 +1 @@\n-a\n+b\n").unwrap();
+        let result = check_patch_file(&path);
+        assert!(result.passed && !result.is_warning());
+        assert!(result.message.ends_with("(1 files)"));
+    }
+
+    #[test]
+    fn test_pinned_sessions_result() {
+        let result = pinned_sessions_result(MAX_PINNED_SESSIONS);
+        assert!(result.passed && !result.is_warning());
+        let result = pinned_sessions_result(MAX_PINNED_SESSIONS + 1);
+        assert!(result.is_warning());
+        assert!(result.message.ends_with("21 sessions are pinned (more than 20)"));
+    }
+
+    #[test]
+    fn test_temp_files_result() {
+        let result = temp_files_result(0);
+        assert!(result.passed && !result.is_warning());
+        let result = temp_files_result(2);
+        assert!(result.is_warning());
+        assert!(result.suggestion.unwrap().contains("--fix"));
+    }
+
+    #[test]
+    fn test_lock_files_result() {
+        let result = lock_files_result(0);
+        assert!(result.passed && !result.is_warning());
+        let result = lock_files_result(3);
+        assert!(result.is_warning());
+        assert!(result.message.contains("3 lock file(s)"));
+    }
+
+    #[test]
+    fn test_short_verbose_flag() {
+        use clap::Parser;
+        use super::super::{Cli, Commands};
+
+        let cli = Cli::try_parse_from(["cr-helper", "doctor", "-v", "--fix"]).unwrap();
+        assert_eq!(cli.verbose, 1);
+        assert!(matches!(cli.command, Commands::Doctor(DoctorArgs { fix: true, .. })));
+    }
+
+    #[test]
+    fn test_collect_result_strict() {
+        let results = vec![
+            CheckResult::ok("a", "fine"),
+            CheckResult::warn("b", "meh", None),
+            CheckResult::fail("c", "broken", None),
+        ];
+
+        let outcome = collect_result(&results, false);
+        assert_eq!(outcome.warnings, vec!["b: meh"]);
+        assert_eq!(outcome.errors, vec!["c: broken"]);
+        assert!(!outcome.passed());
+
+        let outcome = collect_result(&results[..2], true);
+        assert!(outcome.warnings.is_empty());
+        assert_eq!(outcome.errors, vec!["b: meh"]);
+        assert!(!outcome.passed());
+
+        assert!(collect_result(&results[..2], false).passed());
+    }
+
+    #[test]
+    fn test_latency_result() {
+        let max = Duration::from_millis(5000);
+
+        let result = latency_result("Claude Code", Ok(Duration::from_millis(45)), max);
+        assert!(result.passed && !result.is_warning());
+        assert_eq!(result.summary(), "Claude Code: 45ms");
+
+        let result = latency_result("Claude Code", Ok(Duration::from_millis(6000)), max);
+        assert!(!result.passed);
+        assert_eq!(result.message, "6000ms (limit 5000ms)");
+
+        let result = latency_result("GitHub", Err(anyhow::anyhow!("not logged in")), max);
+        assert!(result.is_warning());
+        assert_eq!(result.summary(), "GitHub: ping failed: not logged in");
+    }
 }
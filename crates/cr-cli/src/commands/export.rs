@@ -4,13 +4,17 @@
 
 use anyhow::{Context, Result};
 use clap::{Args, ValueEnum};
+use std::collections::HashMap;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use cr_core::export::ExportManager;
+use cr_core::comment::{CommentState, Severity};
+use cr_core::config::Config;
+use cr_core::export::{
+    ExportFilter, ExportManager, MarkdownEnhancedExporter, MarkdownExecutiveExporter,
+    MarkdownExporter, TemplateExporter,
+};
 use cr_core::session::SessionManager;
-use cr_core::types::SessionId;
-use cr_storage::FileSystemStorage;
 
 /// Export format options
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -23,6 +27,13 @@ pub enum ExportFormat {
     Markdown,
     /// Enhanced Markdown with anchors and frontmatter
     MarkdownEnhanced,
+    /// One-page plain-English summary for non-technical readers
+    MarkdownExecutive,
+    /// Compiler-style `file:line:col: severity: message` lines for editors
+    /// (vim: `set errorformat=%f:%l:%c:\ %m,%f:%l:\ %m,%-G%.%#`)
+    InlineAnnotations,
+    /// Custom minijinja template (requires --template)
+    Template,
 }
 
 /// Arguments for the export command
@@ -51,19 +62,85 @@ pub struct ExportArgs {
     /// Session storage directory
     #[arg(long)]
     pub sessions_dir: Option<PathBuf>,
+
+    /// Include changes since this baseline session (markdown)
+    #[arg(long, value_name = "SESSION_ID")]
+    pub since: Option<String>,
+
+    /// Template file for --format template
+    #[arg(long)]
+    pub template: Option<PathBuf>,
+
+    /// Template variable available as `{{ vars.KEY }}` (repeatable)
+    #[arg(long = "template-vars", value_name = "KEY=VALUE", value_parser = parse_template_var)]
+    pub template_vars: Vec<(String, String)>,
+
+    /// File of KEY=VALUE template variables (one per line, # comments)
+    #[arg(long, value_name = "PATH")]
+    pub template_vars_file: Option<PathBuf>,
+
+    /// Write one report per commented file, plus an index
+    #[arg(long)]
+    pub per_file: bool,
+
+    /// Directory for --per-file reports
+    #[arg(long, value_name = "DIR", default_value = "cr-helper-export", requires = "per_file")]
+    pub output_dir: PathBuf,
+
+    /// Skip comments less severe than this (info, warning, critical)
+    #[arg(long, value_name = "SEVERITY", value_parser = parse_severity)]
+    pub min_severity: Option<Severity>,
+
+    /// Only export open or acknowledged comments
+    #[arg(long)]
+    pub only_open: bool,
+
+    /// Only export comments with this tag (repeatable)
+    #[arg(long, value_name = "TAG")]
+    pub only_tag: Vec<String>,
+
+    /// Only export comments on this file or directory (repeatable)
+    #[arg(long, value_name = "PATH")]
+    pub only_file: Vec<String>,
+
+    /// Only export files with these change modes, and their comments
+    #[command(flatten)]
+    pub file_modes: super::review::FileModeArgs,
+
+    /// Print the start of the export instead of writing it
+    #[arg(long, conflicts_with_all = ["output", "per_file"])]
+    pub preview: bool,
+
+    /// Number of lines shown by --preview
+    #[arg(long, default_value = "50", requires = "preview")]
+    pub lines: usize,
+
+    /// Print the available --format values, one per line, and exit
+    #[arg(long)]
+    pub list_formats: bool,
 }
 
 /// Execute the export command
-pub fn execute(args: ExportArgs) -> Result<()> {
+pub fn execute(args: ExportArgs, workspace: Option<SessionManager>, config_path: &Path) -> Result<()> {
     use colored::Colorize;
 
+    if args.list_formats {
+        // `template` is registered per export, once --template is read
+        let manager = ExportManager::new();
+        let mut formats = manager.registered_formats();
+        formats.push("template");
+        formats.sort();
+        for format in formats {
+            println!("{}", format);
+        }
+        return Ok(());
+    }
+
     // Set up storage
-    let storage_path = args
-        .sessions_dir
-        .clone()
-        .unwrap_or_else(|| PathBuf::from(".cr-helper/sessions"));
-    let storage = FileSystemStorage::new(&storage_path)?;
-    let manager = SessionManager::new(storage);
+    let manager = match workspace {
+        Some(manager) if args.sessions_dir.is_none() => manager,
+        _ => SessionManager::new(super::open_storage(args.sessions_dir.as_deref())?),
+    };
 
     // Load session
     let session = if args.latest {
@@ -71,8 +148,7 @@ pub fn execute(args: ExportArgs) -> Result<()> {
             .load_latest()?
             .context("No sessions found")?
     } else if let Some(session_id) = &args.session {
-        let id = SessionId::from_string(session_id)
-            .context(format!("Invalid session ID: {}", session_id))?;
+        let id = manager.resolve_id(session_id)?;
         manager
             .load(&id)
             .context(format!("Session '{}' not found", session_id))?
@@ -90,7 +166,43 @@ pub fn execute(args: ExportArgs) -> Result<()> {
     );
 
     // Set up exporter - ExportManager::new() already registers default exporters
-    let export_manager = ExportManager::new();
+    let mut export_manager = ExportManager::new();
+    export_manager.set_filter(export_filter(&args));
+    let config = Config::load(config_path).unwrap_or_default();
+    let mut markdown = MarkdownExporter::new().with_toc(config.export.include_toc);
+    if let Some(baseline_id) = &args.since {
+        let id = manager.resolve_id(baseline_id)?;
+        let baseline = manager
+            .load(&id)
+            .context(format!("Session '{}' not found", baseline_id))?;
+        markdown = markdown.with_delta(session.delta_from(&baseline));
+    }
+    export_manager.register(Box::new(markdown));
+    export_manager.register(Box::new(
+        MarkdownEnhancedExporter::new()
+            .with_function_context(config.export.use_function_context)
+            .with_toc(config.export.include_toc),
+    ));
+    export_manager.register(Box::new(
+        MarkdownExecutiveExporter::new().with_risk_thresholds(config.review.risk_thresholds),
+    ));
+    if let ExportFormat::Template = args.format {
+        let template = args
+            .template
+            .as_ref()
+            .context("--format template requires --template <path>")?;
+
+        let mut vars = HashMap::new();
+        if let Some(path) = &args.template_vars_file {
+            let content = std::fs::read_to_string(path)
+                .context(format!("Failed to read {}", path.display()))?;
+            vars.extend(parse_template_vars_file(&content)?);
+        }
+        vars.extend(args.template_vars.iter().cloned());
+
+        let exporter = TemplateExporter::from_file(template)?.with_vars(vars);
+        export_manager.register(Box::new(exporter));
+    }
 
     // Get format name
     let format_name = match args.format {
@@ -98,10 +210,28 @@ pub fn execute(args: ExportArgs) -> Result<()> {
         ExportFormat::JsonCompact => "json-compact",
         ExportFormat::Markdown => "markdown",
         ExportFormat::MarkdownEnhanced => "markdown-enhanced",
+        ExportFormat::MarkdownExecutive => "markdown-executive",
+        ExportFormat::InlineAnnotations => "inline-annotations",
+        ExportFormat::Template => "template",
     };
 
+    if args.preview {
+        print!("{}", export_manager.preview(&session, format_name, args.lines)?);
+        let size = export_manager.estimate_size(&session, format_name)?;
+        eprintln!("{}", format!("Full export: ~{} bytes", size).dimmed());
+        return Ok(());
+    }
+
+    if args.per_file {
+        return export_per_file(&export_manager, &session, format_name, &args.output_dir);
+    }
+
     // Export
-    let output = export_manager.export(&session, format_name)?;
+    let output = if config.export.validate {
+        export_manager.export_validated(&session, format_name)?
+    } else {
+        export_manager.export(&session, format_name)?
+    };
 
     // Write output
     if let Some(output_path) = args.output {
@@ -118,6 +248,104 @@ pub fn execute(args: ExportArgs) -> Result<()> {
     Ok(())
 }
 
+/// Write one report per commented file and an `index.md` / `index.json`
+fn export_per_file(
+    export_manager: &ExportManager,
+    session: &cr_core::session::Session,
+    format_name: &str,
+    output_dir: &Path,
+) -> Result<()> {
+    use colored::Colorize;
+
+    let reports = export_manager.split_by_file(session, format_name)?;
+    if reports.is_empty() {
+        eprintln!("{}", "No comments to export.".yellow());
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(output_dir)
+        .context(format!("Failed to create {}", output_dir.display()))?;
+    for (name, content) in &reports {
+        let path = output_dir.join(name);
+        std::fs::write(&path, content).context(format!("Failed to write to {}", path.display()))?;
+    }
+
+    let names: Vec<&Path> = reports.iter().map(|(name, _)| name.as_path()).collect();
+    let json = format_name.starts_with("json");
+    let index_path = output_dir.join(if json { "index.json" } else { "index.md" });
+    std::fs::write(&index_path, render_index(session, &names, json)?)
+        .context(format!("Failed to write to {}", index_path.display()))?;
+
+    eprintln!(
+        "{} Exported {} report(s) to {}",
+        "✓".green(),
+        reports.len(),
+        output_dir.display()
+    );
+    Ok(())
+}
+
+/// Index listing per-file reports
+fn render_index(session: &cr_core::session::Session, names: &[&Path], json: bool) -> Result<String> {
+    if json {
+        let index = serde_json::json!({
+            "session": session.id.to_string(),
+            "reports": names,
+        });
+        return Ok(serde_json::to_string_pretty(&index)?);
+    }
+
+    let mut output = format!("# Code Review Report\n\n**Session:** `{}`\n\n", session.id);
+    for name in names {
+        output.push_str(&format!("- [{}]({})\n", name.display(), name.display()));
+    }
+    Ok(output)
+}
+
+/// Comment filter from the `--min-severity` / `--only-*` flags
+fn export_filter(args: &ExportArgs) -> ExportFilter {
+    ExportFilter {
+        min_severity: args.min_severity,
+        states: if args.only_open {
+            vec![CommentState::Open, CommentState::Acknowledged]
+        } else {
+            Vec::new()
+        },
+        tags: args.only_tag.clone(),
+        files: args.only_file.clone(),
+        file_modes: args.file_modes.modes(),
+    }
+}
+
+/// Parse a severity name (`info`, `warning`, `critical` or `i`/`w`/`c`)
+fn parse_severity(s: &str) -> Result<Severity> {
+    Severity::from_short_string(&s.to_lowercase())
+        .context(format!("Unknown severity '{}' (use info, warning or critical)", s))
+}
+
+/// Parse a `KEY=VALUE` template variable
+fn parse_template_var(s: &str) -> Result<(String, String)> {
+    let (key, value) = s
+        .split_once('=')
+        .context(format!("Expected KEY=VALUE, got '{}'", s))?;
+    let key = key.trim();
+    if key.is_empty() {
+        anyhow::bail!("Empty variable name in '{}'", s);
+    }
+    Ok((key.to_string(), value.trim().to_string()))
+}
+
+/// Parse a `KEY=VALUE` variables file, skipping blank lines and `#` comments
+fn parse_template_vars_file(content: &str) -> Result<Vec<(String, String)>> {
+    content
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| parse_template_var(line).context(format!("line {}", i + 1)))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,5 +357,54 @@ mod tests {
         assert!(ExportFormat::from_str("json-compact", true).is_ok());
         assert!(ExportFormat::from_str("markdown", true).is_ok());
         assert!(ExportFormat::from_str("markdown-enhanced", true).is_ok());
+        assert!(ExportFormat::from_str("inline-annotations", true).is_ok());
+        assert!(ExportFormat::from_str("template", true).is_ok());
+    }
+
+    #[test]
+    fn test_render_index() {
+        let session = cr_core::session::Session::new(
+            cr_core::session::DiffSource::WorkingTree,
+            cr_core::diff::DiffData::empty(),
+        );
+        let names = [Path::new("src_main_rs.md")];
+
+        let md = render_index(&session, &names, false).unwrap();
+        assert!(md.contains("- [src_main_rs.md](src_main_rs.md)\n"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_index(&session, &names, true).unwrap()).unwrap();
+        assert_eq!(json["reports"][0], "src_main_rs.md");
+    }
+
+    #[test]
+    fn test_parse_severity() {
+        assert_eq!(parse_severity("Warning").unwrap(), Severity::Warning);
+        assert_eq!(parse_severity("c").unwrap(), Severity::Critical);
+        assert!(parse_severity("high").is_err());
+    }
+
+    #[test]
+    fn test_parse_template_var() {
+        assert_eq!(
+            parse_template_var("pr_url=https://x/y?a=b").unwrap(),
+            ("pr_url".to_string(), "https://x/y?a=b".to_string())
+        );
+        assert!(parse_template_var("novalue").is_err());
+        assert!(parse_template_var("=value").is_err());
+    }
+
+    #[test]
+    fn test_parse_template_vars_file() {
+        let content = "# CI variables\nreviewer_name=Sam\n\n  build = 42\n";
+        let vars = parse_template_vars_file(content).unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                ("reviewer_name".to_string(), "Sam".to_string()),
+                ("build".to_string(), "42".to_string()),
+            ]
+        );
+        assert!(parse_template_vars_file("ok=1\nbroken").is_err());
     }
 }
@@ -0,0 +1,49 @@
+//! Import-checkstyle command
+//!
+//! Turn a Checkstyle XML report read from stdin into review comments.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::io::Read;
+
+use cr_core::comment::CommentManager;
+use cr_core::session::DiffSource;
+
+/// Arguments for the import-checkstyle command
+#[derive(Debug, Args)]
+pub struct ImportCheckstyleArgs {
+    #[command(flatten)]
+    pub target: super::ImportTargetArgs,
+}
+
+/// Execute the import-checkstyle command
+pub fn execute(args: ImportCheckstyleArgs) -> Result<()> {
+    use colored::Colorize;
+
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .context("Failed to read Checkstyle report from stdin")?;
+    let comments = CommentManager::import_from_checkstyle(&input)?;
+    if comments.is_empty() {
+        println!("{}", "No Checkstyle findings found.".yellow());
+        return Ok(());
+    }
+
+    let count = comments.len();
+    let source = || {
+        Ok(DiffSource::Report {
+            tool: "checkstyle".to_string(),
+        })
+    };
+    let session = super::import_comments(&args.target, source, comments)?;
+
+    println!(
+        "{} Imported {} Checkstyle finding(s) into session {}",
+        "✓".green(),
+        count.to_string().cyan(),
+        session.id.to_string().cyan()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! Import-lsp command
+//!
+//! Turn language server diagnostics read from stdin into review comments.
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use std::io::Read;
+
+use cr_core::comment::{Comment, CommentBuilder};
+use cr_core::session::DiffSource;
+
+/// Arguments for the import-lsp command
+#[derive(Debug, Args)]
+pub struct ImportLspArgs {
+    #[command(flatten)]
+    pub target: super::ImportTargetArgs,
+
+    /// Source label for the comments (recorded as `lsp-<name>`)
+    #[arg(long)]
+    pub server_name: Option<String>,
+}
+
+/// Execute the import-lsp command
+pub fn execute(args: ImportLspArgs) -> Result<()> {
+    use colored::Colorize;
+
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .context("Failed to read diagnostics from stdin")?;
+    let comments = parse_diagnostics(&input, args.server_name.as_deref())?;
+    if comments.is_empty() {
+        println!("{}", "No diagnostics found.".yellow());
+        return Ok(());
+    }
+
+    let count = comments.len();
+    let server = args
+        .server_name
+        .clone()
+        .or_else(|| server_from_source(&comments))
+        .unwrap_or_else(|| "unknown".to_string());
+    let session = super::import_comments(&args.target, || Ok(DiffSource::Lsp { server }), comments)?;
+
+    println!(
+        "{} Imported {} diagnostic(s) into session {}",
+        "✓".green(),
+        count.to_string().cyan(),
+        session.id.to_string().cyan()
+    );
+
+    Ok(())
+}
+
+/// Parse a JSON array of LSP diagnostics into comments
+fn parse_diagnostics(input: &str, server_name: Option<&str>) -> Result<Vec<Comment>> {
+    let value: serde_json::Value =
+        serde_json::from_str(input).context("Diagnostics are not valid JSON")?;
+    let Some(diagnostics) = value.as_array() else {
+        bail!("Expected a JSON array of LSP diagnostics");
+    };
+
+    diagnostics
+        .iter()
+        .enumerate()
+        .map(|(i, diagnostic)| {
+            let mut builder = CommentBuilder::from_language_server_diagnostic(diagnostic)
+                .context(format!("Diagnostic #{}", i + 1))?;
+            if let Some(name) = server_name {
+                builder = builder.source(format!("lsp-{}", name));
+            }
+            Ok(builder.build()?)
+        })
+        .collect()
+}
+
+/// Server name from the first comment's `lsp-<name>` source
+fn server_from_source(comments: &[Comment]) -> Option<String> {
+    comments
+        .iter()
+        .filter_map(|c| c.metadata.source.as_deref()?.strip_prefix("lsp-"))
+        .next()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIAGNOSTICS: &str = r#"[
+        {"uri": "src/a.rs", "range": {"start": {"line": 2}}, "severity": 2,
+         "source": "rust-analyzer", "message": "unused variable"},
+        {"uri": "src/b.rs", "range": {"start": {"line": 0}}, "severity": 1,
+         "source": "rust-analyzer", "message": "cannot find value"}
+    ]"#;
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let comments = parse_diagnostics(DIAGNOSTICS, None).unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].metadata.line_number, Some(3));
+        assert_eq!(
+            server_from_source(&comments).as_deref(),
+            Some("rust-analyzer")
+        );
+
+        let comments = parse_diagnostics(DIAGNOSTICS, Some("ra")).unwrap();
+        assert_eq!(comments[1].metadata.source.as_deref(), Some("lsp-ra"));
+
+        assert!(parse_diagnostics("{}", None).is_err());
+        assert!(parse_diagnostics(r#"[{"uri": "a.rs"}]"#, None).is_err());
+    }
+}
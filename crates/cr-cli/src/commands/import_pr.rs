@@ -0,0 +1,49 @@
+//! Import-pr command
+//!
+//! Turn a GitHub pull request's review comments into review comments.
+
+use anyhow::Result;
+use clap::Args;
+
+use cr_core::session::DiffSource;
+use cr_integration::GitHubAdapter;
+
+/// Arguments for the import-pr command
+#[derive(Debug, Args)]
+pub struct ImportPrArgs {
+    /// Pull request number
+    pub number: u64,
+
+    #[command(flatten)]
+    pub target: super::ImportTargetArgs,
+}
+
+/// Execute the import-pr command
+pub fn execute(args: ImportPrArgs) -> Result<()> {
+    use colored::Colorize;
+
+    let adapter = GitHubAdapter::new();
+    let comments = adapter.import_pr_comments(args.number)?;
+    if comments.is_empty() {
+        println!("{}", "No review comments found.".yellow());
+        return Ok(());
+    }
+
+    let count = comments.len();
+    let source = || {
+        Ok(DiffSource::PullRequest {
+            number: args.number,
+            base: adapter.pr_base(args.number)?,
+        })
+    };
+    let session = super::import_comments(&args.target, source, comments)?;
+
+    println!(
+        "{} Imported {} review comment(s) into session {}",
+        "✓".green(),
+        count.to_string().cyan(),
+        session.id.to_string().cyan()
+    );
+
+    Ok(())
+}
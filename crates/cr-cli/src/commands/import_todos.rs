@@ -0,0 +1,145 @@
+//! Import-todos command
+//!
+//! Create a review session from inline TODO/FIXME/HACK/XXX comments.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+use cr_core::comment::{Comment, CommentBuilder};
+use cr_core::diff::{CrIgnore, DiffData, CRIGNORE_PATH};
+use cr_core::session::{DiffSource, SessionManager, SessionMetadata};
+
+/// Arguments for the import-todos command
+#[derive(Debug, Args)]
+pub struct ImportTodosArgs {
+    /// Directory to scan
+    #[arg(long, default_value = ".")]
+    pub path: PathBuf,
+
+    /// Session storage directory
+    #[arg(long)]
+    pub sessions_dir: Option<PathBuf>,
+}
+
+/// Execute the import-todos command
+pub fn execute(args: ImportTodosArgs) -> Result<()> {
+    use colored::Colorize;
+
+    let ignore = CrIgnore::load(Path::new(CRIGNORE_PATH))
+        .context(format!("Failed to read {}", CRIGNORE_PATH))?;
+
+    let mut files = Vec::new();
+    collect_files(&args.path, &ignore, &mut files)
+        .context(format!("Failed to scan {}", args.path.display()))?;
+
+    let comments: Vec<Comment> = files.iter().flat_map(|f| scan_file(f)).collect();
+    if comments.is_empty() {
+        println!("{}", "No TODO comments found.".yellow());
+        return Ok(());
+    }
+
+    let storage = super::open_storage(args.sessions_dir.as_deref())?;
+    let manager = SessionManager::new(storage);
+
+    let source = DiffSource::Todos {
+        path: display_path(&args.path),
+    };
+    let metadata = SessionMetadata::with_name(source.description());
+    let mut session = manager.create_with_metadata(source, DiffData::empty(), metadata)?;
+    let count = comments.len();
+    for comment in comments {
+        session.comments.add(comment)?;
+    }
+    manager.save(&mut session)?;
+
+    println!(
+        "{} Imported {} TODO comment(s) from {} file(s) into session {}",
+        "✓".green(),
+        count.to_string().cyan(),
+        files.len(),
+        session.id.to_string().cyan()
+    );
+
+    Ok(())
+}
+
+/// Recursively collect files, skipping hidden entries, paths matched by
+/// `.gitignore` and friends, and paths ignored by `crignore`
+fn collect_files(
+    dir: &Path,
+    crignore: &CrIgnore,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), ignore::Error> {
+    let crignore = crignore.clone();
+    let walker = ignore::WalkBuilder::new(dir)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |entry| {
+            let path = entry.path();
+            let relative = path.strip_prefix(".").unwrap_or(path);
+            entry.depth() == 0 || !crignore.is_ignored(relative)
+        })
+        .build();
+
+    for entry in walker {
+        let entry = entry?;
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(())
+}
+
+/// Extract TODO comments from one file, skipping files that are not UTF-8 text
+fn scan_file(path: &Path) -> Vec<Comment> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let file_path = display_path(path);
+
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| CommentBuilder::from_todo_comment(line, &file_path, i + 1))
+        .filter_map(|builder| builder.source("import-todos").build().ok())
+        .collect()
+}
+
+/// Path as shown in comments, without a leading `./`
+fn display_path(path: &Path) -> String {
+    match path.strip_prefix(".") {
+        Ok(p) if p.as_os_str().is_empty() => ".".to_string(),
+        Ok(p) => p.to_string_lossy().to_string(),
+        Err(_) => path.to_string_lossy().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_collect_and_scan() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::create_dir_all(dir.path().join("gen")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".git")).unwrap();
+        std::fs::write(dir.path().join("src/a.rs"), "fn a() {}\n// TODO: test me\n").unwrap();
+        std::fs::write(dir.path().join("gen/b.rs"), "// FIXME: generated\n").unwrap();
+        std::fs::write(dir.path().join(".git/c"), "// TODO: hidden\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("target")).unwrap();
+        std::fs::write(dir.path().join("target/d.rs"), "// TODO: build output\n").unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+
+        let mut files = Vec::new();
+        collect_files(dir.path(), &CrIgnore::parse("gen/"), &mut files).unwrap();
+        assert_eq!(files, vec![dir.path().join("src/a.rs")]);
+
+        let comments = scan_file(&files[0]);
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].content, "test me");
+        assert_eq!(comments[0].metadata.line_number, Some(2));
+    }
+}
@@ -121,6 +121,10 @@ pub fn execute(args: InitArgs) -> Result<()> {
     println!("  4. Start your first review:");
     println!("     ");
     println!("     {}", "cr-helper review".cyan());
+    println!("     ");
+    println!("  5. Optionally, enable shell completion (bash shown; also zsh, fish, ...):");
+    println!("     ");
+    println!("     {}", "echo 'source <(cr-helper completion bash)' >> ~/.bashrc".cyan());
     println!("\n{} Tip: Run '{}' to verify your setup", "💡".to_string(), "cr-helper doctor".cyan());
 
     Ok(())
@@ -176,6 +180,10 @@ include_suggestions = true
 # Rust file patterns
 include_patterns = ["*.rs", "Cargo.toml", "Cargo.lock"]
 exclude_patterns = ["target/"]
+
+[storage]
+# Oldest sessions beyond this count are deleted (0 = unlimited)
+max_sessions = 100
 "#
             .to_string()
         }
@@ -207,6 +215,10 @@ include_suggestions = true
 # TypeScript/JavaScript file patterns
 include_patterns = ["*.ts", "*.tsx", "*.js", "*.jsx", "*.json"]
 exclude_patterns = ["node_modules/", "dist/", "build/", "*.min.js"]
+
+[storage]
+# Oldest sessions beyond this count are deleted (0 = unlimited)
+max_sessions = 100
 "#
             .to_string()
         }
@@ -238,6 +250,10 @@ include_suggestions = true
 # Python file patterns
 include_patterns = ["*.py", "pyproject.toml", "setup.py", "requirements*.txt"]
 exclude_patterns = ["__pycache__/", "*.pyc", ".venv/", "venv/", ".eggs/"]
+
+[storage]
+# Oldest sessions beyond this count are deleted (0 = unlimited)
+max_sessions = 100
 "#
             .to_string()
         }
@@ -269,6 +285,10 @@ include_suggestions = true
 # Go file patterns
 include_patterns = ["*.go", "go.mod", "go.sum"]
 exclude_patterns = ["vendor/"]
+
+[storage]
+# Oldest sessions beyond this count are deleted (0 = unlimited)
+max_sessions = 100
 "#
             .to_string()
         }
@@ -298,6 +318,10 @@ include_suggestions = true
 # File patterns (customize for your project)
 include_patterns = ["*"]
 exclude_patterns = [".git/", "node_modules/", "target/", "__pycache__/"]
+
+[storage]
+# Oldest sessions beyond this count are deleted (0 = unlimited)
+max_sessions = 100
 "#
             .to_string()
         }
@@ -0,0 +1,54 @@
+//! Inject command
+//!
+//! Write review results where Claude Code can read them in later turns.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::path::PathBuf;
+
+use cr_core::session::SessionManager;
+use cr_integration::ClaudeCodeAdapter;
+
+/// Arguments for the inject command
+#[derive(Debug, Args)]
+pub struct InjectArgs {
+    /// Session to inject (default: most recent)
+    #[arg(long)]
+    pub session: Option<String>,
+
+    /// Session storage directory
+    #[arg(long)]
+    pub sessions_dir: Option<PathBuf>,
+}
+
+/// Execute the inject command
+pub fn execute(args: InjectArgs) -> Result<()> {
+    use colored::Colorize;
+
+    let storage = super::open_storage(args.sessions_dir.as_deref())?;
+    let manager = SessionManager::new(storage);
+
+    let session = match &args.session {
+        Some(id) => {
+            let session_id = manager.resolve_id(id)?;
+            manager
+                .load(&session_id)
+                .context(format!("Session '{}' not found", id))?
+        }
+        None => manager
+            .load_latest()?
+            .context("No sessions found. Run 'cr-helper review' first.")?,
+    };
+
+    let adapter = ClaudeCodeAdapter::new();
+    adapter.inject_review_context(&session)?;
+
+    println!(
+        "{} Review context for session {} written to {}",
+        "✓".green(),
+        session.id.to_string().cyan(),
+        adapter.review_context_path().display()
+    );
+
+    Ok(())
+}
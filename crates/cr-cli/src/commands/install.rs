@@ -2,8 +2,9 @@
 //!
 //! Install cr-helper to Agent CLI tools (Claude Code, etc.)
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Args, ValueEnum};
+use cr_integration::AgentType;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -31,13 +32,48 @@ pub enum Component {
     All,
 }
 
+/// Agent to install to
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+pub enum InstallAgent {
+    /// Claude Code
+    ClaudeCode,
+    /// Cursor
+    Cursor,
+    /// GitHub Copilot
+    Copilot,
+    /// Windsurf
+    Windsurf,
+    /// Aider
+    Aider,
+}
+
+impl From<InstallAgent> for AgentType {
+    fn from(agent: InstallAgent) -> Self {
+        match agent {
+            InstallAgent::ClaudeCode => AgentType::ClaudeCode,
+            InstallAgent::Cursor => AgentType::Cursor,
+            InstallAgent::Copilot => AgentType::Copilot,
+            InstallAgent::Windsurf => AgentType::Windsurf,
+            InstallAgent::Aider => AgentType::Aider,
+        }
+    }
+}
+
 /// Arguments for the install command
 #[derive(Debug, Args)]
 pub struct InstallArgs {
-    /// Install to Claude Code
+    /// Install to Claude Code (same as `--agent claude-code`)
     #[arg(long)]
     pub claude_code: bool,
 
+    /// Add VSCode tasks and suggested keybindings to .vscode/ instead
+    #[arg(long, conflicts_with_all = ["claude_code", "agent"])]
+    pub vscode: bool,
+
+    /// Agent to install to, when several are detected
+    #[arg(long, value_enum, conflicts_with = "claude_code")]
+    pub agent: Option<InstallAgent>,
+
     /// Installation scope
     #[arg(long, value_enum, default_value = "project")]
     pub scope: InstallScope,
@@ -75,10 +111,47 @@ pub struct InstallArgs {
 pub fn execute(args: InstallArgs) -> Result<()> {
     use colored::Colorize;
 
-    if !args.claude_code {
+    if args.vscode {
+        if args.dry_run {
+            println!("\n📋 Dry run - no changes will be made");
+            println!("Would write .vscode/tasks.json, .vscode/extensions.json and .vscode/keybindings.json.example");
+            return Ok(());
+        }
+        install_vscode_integration(Path::new("."))?;
+        println!("{} Added cr-helper tasks to .vscode/tasks.json", "✓".green());
+        println!("{} Wrote suggested keybindings to .vscode/keybindings.json.example", "✓".green());
+        println!("\n{}", "Next steps:".bold());
+        println!("  1. Run {} in VSCode", "Preferences: Open Keyboard Shortcuts (JSON)".cyan());
+        println!("  2. Copy the entries from .vscode/keybindings.json.example into it");
+        println!("  3. Press Ctrl+Alt+R to start a review, Ctrl+Alt+E to export the report");
+        return Ok(());
+    }
+
+    let explicit = if args.claude_code {
+        Some(AgentType::ClaudeCode)
+    } else {
+        args.agent.map(AgentType::from)
+    };
+    let ranked = cr_integration::detect_agents()?.ranked();
+
+    let Some(agent) = choose_agent(explicit, &ranked) else {
+        if ranked.len() > 1 {
+            println!("{}", "Multiple agents detected:".yellow());
+            for (agent, score) in &ranked {
+                println!("  {} (confidence {})", agent.name(), score);
+            }
+        }
         println!("{}", "Please specify an agent to install to:".yellow());
         println!("  --claude-code    Install to Claude Code");
+        println!("  --agent <AGENT>  Install to the given agent");
         return Ok(());
+    };
+
+    if agent != AgentType::ClaudeCode {
+        anyhow::bail!(
+            "Installing to {} is not supported yet; only Claude Code is",
+            agent.name()
+        );
     }
 
     println!("{} Installing cr-helper to Claude Code...", "🚀".to_string());
@@ -190,6 +263,10 @@ pub fn execute(args: InstallArgs) -> Result<()> {
     )?;
     println!("{} Updated {}", "✓".green(), settings_path.display());
 
+    if !args.yes {
+        offer_shell_completion()?;
+    }
+
     // Print summary
     println!("\n{} Installation complete!", "✅".to_string());
     println!("\n{}", "Next steps:".bold());
@@ -209,6 +286,127 @@ pub fn execute(args: InstallArgs) -> Result<()> {
     Ok(())
 }
 
+/// VSCode tasks added by `install --vscode`, as (label, command)
+const VSCODE_TASKS: [(&str, &str); 2] = [
+    ("cr-helper: Start Review", "cr-helper review"),
+    ("cr-helper: Export Report", "cr-helper export --latest --format markdown"),
+];
+
+/// Suggested keybindings for [`VSCODE_TASKS`]
+const VSCODE_KEYBINDINGS: [&str; 2] = ["ctrl+alt+r", "ctrl+alt+e"];
+
+/// Shows binary files, which the review screen can't
+const VSCODE_HEX_EDITOR: &str = "ms-vscode.hexeditor";
+
+/// Add cr-helper tasks to `.vscode/tasks.json`, recommend the hex editor in
+/// `.vscode/extensions.json` and write `.vscode/keybindings.json.example`
+///
+/// Existing files are updated in place: tasks with the same label are
+/// replaced and other entries are kept. Files that aren't plain JSON (e.g.
+/// with comments) are left alone with an error.
+pub fn install_vscode_integration(project_dir: &Path) -> Result<()> {
+    let vscode_dir = project_dir.join(".vscode");
+    fs::create_dir_all(&vscode_dir)
+        .context(format!("Failed to create {}", vscode_dir.display()))?;
+
+    let tasks_path = vscode_dir.join("tasks.json");
+    let mut tasks = read_json_object(&tasks_path)?;
+    if tasks.get("version").is_none() {
+        tasks["version"] = serde_json::json!("2.0.0");
+    }
+    if !tasks["tasks"].is_array() {
+        tasks["tasks"] = serde_json::json!([]);
+    }
+    let list = tasks["tasks"].as_array_mut().unwrap();
+    for (label, command) in VSCODE_TASKS {
+        list.retain(|t| t["label"] != label);
+        list.push(serde_json::json!({
+            "label": label,
+            "type": "shell",
+            "command": command,
+            "problemMatcher": [],
+            "presentation": { "reveal": "always", "panel": "dedicated", "focus": true }
+        }));
+    }
+    write_json(&tasks_path, &tasks)?;
+
+    let extensions_path = vscode_dir.join("extensions.json");
+    let mut extensions = read_json_object(&extensions_path)?;
+    if !extensions["recommendations"].is_array() {
+        extensions["recommendations"] = serde_json::json!([]);
+    }
+    let recommendations = extensions["recommendations"].as_array_mut().unwrap();
+    if !recommendations.iter().any(|r| r == VSCODE_HEX_EDITOR) {
+        recommendations.push(serde_json::json!(VSCODE_HEX_EDITOR));
+    }
+    write_json(&extensions_path, &extensions)?;
+
+    let keybindings: Vec<_> = VSCODE_TASKS
+        .iter()
+        .zip(VSCODE_KEYBINDINGS)
+        .map(|((label, _), key)| {
+            serde_json::json!({
+                "key": key,
+                "command": "workbench.action.tasks.runTask",
+                "args": label
+            })
+        })
+        .collect();
+    write_json(&vscode_dir.join("keybindings.json.example"), &serde_json::json!(keybindings))
+}
+
+/// A JSON object from `path`, or an empty one if the file doesn't exist
+fn read_json_object(path: &Path) -> Result<serde_json::Value> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(serde_json::json!({})),
+        Err(e) => return Err(e).context(format!("Failed to read {}", path.display())),
+    };
+    let value: serde_json::Value = serde_json::from_str(&content)
+        .context(format!("{} is not plain JSON; add the cr-helper entries by hand", path.display()))?;
+    if !value.is_object() {
+        anyhow::bail!("{} does not contain a JSON object", path.display());
+    }
+    Ok(value)
+}
+
+fn write_json(path: &Path, value: &serde_json::Value) -> Result<()> {
+    let content = serde_json::to_string_pretty(value)? + "\n";
+    fs::write(path, content).context(format!("Failed to write {}", path.display()))
+}
+
+/// Pick the agent to install to: the explicit choice, else the only one detected
+fn choose_agent(explicit: Option<AgentType>, ranked: &[(AgentType, u32)]) -> Option<AgentType> {
+    match (explicit, ranked) {
+        (Some(agent), _) => Some(agent),
+        (None, [(agent, _)]) => Some(*agent),
+        _ => None,
+    }
+}
+
+/// Ask to load `cr-helper completion` from the user's shell startup file
+fn offer_shell_completion() -> Result<()> {
+    use super::completion::{add_to_rc_file, rc_file_entry, rc_file_has_entry};
+    use colored::Colorize;
+    use dialoguer::Confirm;
+
+    let Some((rc_file, line)) = rc_file_entry() else {
+        return Ok(());
+    };
+    if rc_file_has_entry(&rc_file, &line) {
+        return Ok(());
+    }
+
+    let confirmed = Confirm::new()
+        .with_prompt(format!("Add shell completion to {}?", rc_file.display()))
+        .default(false)
+        .interact()?;
+    if confirmed && add_to_rc_file(&rc_file, &line)? {
+        println!("{} Added shell completion to {}", "✓".green(), rc_file.display());
+    }
+    Ok(())
+}
+
 fn install_skill_component(base_dir: &Path) -> Result<()> {
     let skill_dir = base_dir.join("skills/cr-helper");
     fs::create_dir_all(&skill_dir)?;
@@ -335,6 +533,52 @@ mod tests {
         assert!(InstallScope::from_str("global", true).is_ok());
     }
 
+    #[test]
+    fn test_install_vscode_integration() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vscode = dir.path().join(".vscode");
+        fs::create_dir_all(&vscode).unwrap();
+        fs::write(
+            vscode.join("tasks.json"),
+            r#"{"version": "2.0.0", "tasks": [{"label": "build", "command": "make"}]}"#,
+        )
+        .unwrap();
+
+        install_vscode_integration(dir.path()).unwrap();
+        install_vscode_integration(dir.path()).unwrap();
+
+        let read = |name: &str| -> serde_json::Value {
+            serde_json::from_str(&fs::read_to_string(vscode.join(name)).unwrap()).unwrap()
+        };
+        let labels: Vec<_> = read("tasks.json")["tasks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["label"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(labels, ["build", "cr-helper: Start Review", "cr-helper: Export Report"]);
+        assert_eq!(read("extensions.json")["recommendations"], serde_json::json!([VSCODE_HEX_EDITOR]));
+        assert_eq!(read("keybindings.json.example")[1]["args"], "cr-helper: Export Report");
+
+        fs::write(vscode.join("tasks.json"), "// comment\n{}").unwrap();
+        assert!(install_vscode_integration(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_choose_agent() {
+        let both = [(AgentType::ClaudeCode, 100), (AgentType::Copilot, 60)];
+        assert_eq!(choose_agent(None, &both), None);
+        assert_eq!(
+            choose_agent(Some(AgentType::Copilot), &both),
+            Some(AgentType::Copilot)
+        );
+        assert_eq!(
+            choose_agent(None, &both[..1]),
+            Some(AgentType::ClaudeCode)
+        );
+        assert_eq!(choose_agent(None, &[]), None);
+    }
+
     #[test]
     fn test_component_values() {
         assert!(Component::from_str("skill", true).is_ok());
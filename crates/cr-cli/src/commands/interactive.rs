@@ -0,0 +1,223 @@
+//! Prompt-based review (`review --interactive`)
+//!
+//! Walks through the diff hunk by hunk in the terminal and asks for comments
+//! with dialoguer prompts instead of opening the TUI.
+
+use anyhow::Result;
+use std::path::PathBuf;
+
+use cr_core::comment::{Comment, CommentBuilder, DiffSide, Severity};
+use cr_core::config::Config;
+use cr_core::diff::{DeltaRenderer, FileDiff, Hunk, Line, LineType};
+use cr_core::export::ExportManager;
+use cr_core::session::{Session, SessionManager};
+
+/// Review every hunk of `session`, saving after each comment
+pub fn run(session: &mut Session, manager: &SessionManager, config: &Config) -> Result<()> {
+    use colored::Colorize;
+    use dialoguer::Confirm;
+
+    let renderer = DeltaRenderer::new();
+    let use_delta = DeltaRenderer::is_available();
+    let total: usize = session.diff_data.files.iter().map(|f| f.hunks.len()).sum();
+    let files = session.diff_data.files.clone();
+
+    let mut position = 0;
+    let mut added = 0;
+    for file in &files {
+        for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+            position += 1;
+            let path = file.display_path().display().to_string();
+            println!(
+                "\n{} {}",
+                format!("[{}/{}]", position, total).dimmed(),
+                path.bold()
+            );
+            let patch = hunk_patch(file, hunk_idx);
+            if use_delta {
+                print!("{}", renderer.render_or_fallback(&patch));
+            } else {
+                print!("{}", patch);
+            }
+
+            let mut prompt = "Add comment here?";
+            while Confirm::new()
+                .with_prompt(prompt)
+                .default(false)
+                .interact()?
+            {
+                let comment = prompt_comment(file, hunk)?;
+                session.comments.add(comment)?;
+                manager.save(session)?;
+                added += 1;
+                prompt = "Add another comment here?";
+            }
+        }
+    }
+
+    print_summary(session, added);
+
+    if added > 0
+        && Confirm::new()
+            .with_prompt("Export?")
+            .default(true)
+            .interact()?
+    {
+        let path = export(session, &config.export.default_format)?;
+        println!("{} Exported to {}", "✓".green(), path.display());
+    }
+    Ok(())
+}
+
+/// Ask for severity, content, tags and an optional fix, and build the comment
+fn prompt_comment(file: &FileDiff, hunk: &Hunk) -> Result<Comment> {
+    use dialoguer::{Confirm, Editor, Input, Select};
+
+    let severities = [Severity::Critical, Severity::Warning, Severity::Info];
+    let choice = Select::new()
+        .with_prompt("Severity?")
+        .items(&["critical", "warning", "info"])
+        .default(2)
+        .interact()?;
+    let content: String = Input::new().with_prompt("Content").interact_text()?;
+    let tags: String = Input::new()
+        .with_prompt("Tags (comma-separated)")
+        .allow_empty(true)
+        .interact_text()?;
+    let fix = if Confirm::new()
+        .with_prompt("Suggested fix? (opens $EDITOR)")
+        .default(false)
+        .interact()?
+    {
+        Editor::new().edit("")?
+    } else {
+        None
+    };
+
+    build_comment(
+        file,
+        hunk,
+        severities[choice],
+        &content,
+        &tags,
+        fix.as_deref(),
+    )
+}
+
+/// Comment on the first changed line of `hunk`
+fn build_comment(
+    file: &FileDiff,
+    hunk: &Hunk,
+    severity: Severity,
+    content: &str,
+    tags: &str,
+    fix: Option<&str>,
+) -> Result<Comment> {
+    let line =
+        target_line(hunk).ok_or_else(|| anyhow::anyhow!("Hunk {} has no lines", hunk.header))?;
+    let side = match line.line_type {
+        LineType::Deleted => DiffSide::Old,
+        _ => DiffSide::New,
+    };
+
+    let mut builder = CommentBuilder::new(file.id.clone(), line.id.clone(), side)
+        .content(content.trim())
+        .severity(severity)
+        .tags(parse_tags(tags))
+        .file_path(file.display_path().to_string_lossy())
+        .line_number(line.new_line_num.or(line.old_line_num).unwrap_or(0));
+    if let Some(fix) = fix.map(str::trim).filter(|f| !f.is_empty()) {
+        builder = builder.suggested_fix(fix);
+    }
+    Ok(builder.build()?)
+}
+
+/// First added or deleted line, else the first line
+fn target_line(hunk: &Hunk) -> Option<&Line> {
+    hunk.lines
+        .iter()
+        .find(|l| matches!(l.line_type, LineType::Added | LineType::Deleted))
+        .or_else(|| hunk.lines.first())
+}
+
+fn parse_tags(input: &str) -> Vec<String> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// A hunk with file headers, so delta can detect the language
+fn hunk_patch(file: &FileDiff, hunk_idx: usize) -> String {
+    let path = file.display_path().display().to_string();
+    format!(
+        "diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n{1}",
+        path,
+        file.patch_for_hunk(hunk_idx).unwrap_or_default()
+    )
+}
+
+fn print_summary(session: &Session, added: usize) {
+    use colored::Colorize;
+
+    let counts = session.comments.count_by_severity();
+    let count = |severity| counts.get(&severity).copied().unwrap_or(0);
+    println!("\n{}", "Review summary".bold().underline());
+    println!("  Added: {} comments", added.to_string().yellow());
+    println!(
+        "  Total: {} critical, {} warning, {} info",
+        count(Severity::Critical).to_string().red(),
+        count(Severity::Warning).to_string().yellow(),
+        count(Severity::Info).to_string().blue()
+    );
+}
+
+/// Export to `.cr-helper/exports/<session>.<ext>`, like the TUI's `:export`
+fn export(session: &Session, format: &str) -> Result<PathBuf> {
+    let manager = ExportManager::new();
+    let exporter = manager
+        .get(format)
+        .ok_or_else(|| anyhow::anyhow!("Unknown export format: {}", format))?;
+    let path = PathBuf::from(".cr-helper/exports")
+        .join(session.id.to_string())
+        .with_extension(exporter.file_extension());
+    manager.export_to_file(session, format, &path)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cr_core::diff::DiffParser;
+
+    #[test]
+    fn test_build_comment_targets_first_change() {
+        let diff = DiffParser::new()
+            .parse(
+                "diff --git a/src/a.rs b/src/a.rs\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1,2 +1,2 @@\n fn a() {}\n-old();\n+new();\n",
+            )
+            .unwrap();
+        let file = &diff.files[0];
+        let hunk = &file.hunks[0];
+
+        let comment = build_comment(
+            file,
+            hunk,
+            Severity::Warning,
+            " Use the new API ",
+            "api, ,perf",
+            Some("  \n"),
+        )
+        .unwrap();
+        assert_eq!(comment.content, "Use the new API");
+        assert_eq!(comment.severity, Severity::Warning);
+        assert_eq!(comment.tags, vec!["api", "perf"]);
+        assert_eq!(comment.line_ids()[0], &hunk.lines[1].id);
+        assert_eq!(comment.metadata.line_number, Some(2));
+        assert!(comment.extensions.suggested_fix().is_none());
+
+        assert!(hunk_patch(file, 0).starts_with("diff --git a/src/a.rs b/src/a.rs\n"));
+    }
+}
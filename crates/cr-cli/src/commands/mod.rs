@@ -2,16 +2,132 @@
 //!
 //! This module contains all CLI command implementations.
 
+pub mod check_conflicts;
+pub mod comment;
+pub mod completion;
 pub mod config;
+pub mod diff;
 pub mod doctor;
 pub mod export;
+pub mod import_checkstyle;
+pub mod import_lsp;
+pub mod import_pr;
+pub mod import_todos;
 pub mod init;
+pub mod inject;
 pub mod install;
+pub mod interactive;
+pub mod project;
 pub mod review;
 pub mod session;
+pub mod storage;
 pub mod uninstall;
+pub mod workspace;
 
-use clap::{Parser, Subcommand};
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand};
+use cr_core::comment::Comment;
+use cr_core::diff::DiffData;
+use cr_core::session::{DiffSource, Session, SessionManager, SessionMetadata};
+use cr_storage::FileSystemStorage;
+use std::path::{Path, PathBuf};
+
+/// Session storage used when no project is registered for the current directory
+const LOCAL_SESSIONS_DIR: &str = ".cr-helper/sessions";
+
+/// Config file used without `--config`, relative to the project directory
+pub const DEFAULT_CONFIG_PATH: &str = ".cr-helper/config.toml";
+
+/// Open session storage for a command
+///
+/// Uses `dir` if given, otherwise the registered project's storage, falling
+/// back to `.cr-helper/sessions` in the current directory.
+pub fn open_storage(dir: Option<&Path>) -> anyhow::Result<FileSystemStorage> {
+    if let Some(dir) = dir {
+        return Ok(FileSystemStorage::new(dir)?);
+    }
+    match project::detect_project_storage()? {
+        Some(storage) => Ok(storage),
+        None => Ok(FileSystemStorage::new(LOCAL_SESSIONS_DIR)?),
+    }
+}
+
+/// Like [`open_storage`] without a directory, but `None` instead of creating
+/// `.cr-helper/sessions` when it does not exist yet
+pub fn open_existing_storage() -> anyhow::Result<Option<FileSystemStorage>> {
+    if project::detect_project_storage()?.is_none() && !Path::new(LOCAL_SESSIONS_DIR).exists() {
+        return Ok(None);
+    }
+    open_storage(None).map(Some)
+}
+
+/// Where the import commands put their comments
+#[derive(Debug, Args)]
+pub struct ImportTargetArgs {
+    /// Add comments to this session instead of creating a new one
+    #[arg(long)]
+    pub session: Option<String>,
+
+    /// Session storage directory
+    #[arg(long)]
+    pub sessions_dir: Option<PathBuf>,
+}
+
+/// Add imported comments to the `--session` session, or to a new session
+/// for `source`, and save it
+///
+/// `source` is only called when a new session is created.
+pub fn import_comments(
+    args: &ImportTargetArgs,
+    source: impl FnOnce() -> anyhow::Result<DiffSource>,
+    comments: Vec<Comment>,
+) -> anyhow::Result<Session> {
+    let storage = open_storage(args.sessions_dir.as_deref())?;
+    let manager = SessionManager::new(storage);
+
+    let mut session = match &args.session {
+        Some(id) => {
+            let session_id = manager.resolve_id(id)?;
+            manager
+                .load(&session_id)
+                .context(format!("Session '{}' not found", id))?
+        }
+        None => {
+            let source = source()?;
+            let metadata = SessionMetadata::with_name(source.description());
+            manager.create_with_metadata(source, DiffData::empty(), metadata)?
+        }
+    };
+    for comment in comments {
+        session.comments.add(comment)?;
+    }
+    manager.save(&mut session)?;
+    Ok(session)
+}
+
+/// Session manager for commands that work on existing sessions
+///
+/// Uses the `--workspace` manager if one was selected. Otherwise warns and
+/// returns `None` when there is no session storage yet.
+pub fn existing_manager(
+    workspace: Option<SessionManager>,
+) -> anyhow::Result<Option<SessionManager>> {
+    if workspace.is_some() {
+        return Ok(workspace);
+    }
+    match open_existing_storage()? {
+        Some(storage) => Ok(Some(SessionManager::new(storage))),
+        None => {
+            use colored::Colorize;
+            eprintln!(
+                "{} No sessions directory found. Run '{}' first.",
+                "⚠".yellow(),
+                "cr-helper init".cyan()
+            );
+            Ok(None)
+        }
+    }
+}
 
 /// cr-helper - Code Review Helper for Claude Code
 #[derive(Debug, Parser)]
@@ -31,6 +147,10 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub no_color: bool,
 
+    /// Run in a workspace configured under [[workspaces]]
+    #[arg(long, global = true)]
+    pub workspace: Option<String>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -44,9 +164,30 @@ pub enum Commands {
     /// Export review session
     Export(export::ExportArgs),
 
+    /// Show `git diff` without files excluded by .crignore
+    Diff(diff::DiffArgs),
+
+    /// Create a session from inline TODO/FIXME comments
+    ImportTodos(import_todos::ImportTodosArgs),
+
+    /// Fail if uncommitted changes add merge conflict markers
+    CheckConflicts(check_conflicts::CheckConflictsArgs),
+
+    /// Import language server diagnostics (JSON array on stdin)
+    ImportLsp(import_lsp::ImportLspArgs),
+
+    /// Import a Checkstyle XML report (read from stdin)
+    ImportCheckstyle(import_checkstyle::ImportCheckstyleArgs),
+
+    /// Import a GitHub pull request's review comments (uses `gh`)
+    ImportPr(import_pr::ImportPrArgs),
+
     /// Initialize cr-helper in current project
     Init(init::InitArgs),
 
+    /// Write review results to .claude/cr-helper/review-context.md
+    Inject(inject::InjectArgs),
+
     /// Install cr-helper to Agent CLI (Claude Code, etc.)
     Install(install::InstallArgs),
 
@@ -61,8 +202,27 @@ pub enum Commands {
     Config(config::ConfigCommand),
 
     /// Manage review sessions
-    #[command(subcommand)]
+    #[command(subcommand, alias = "sessions")]
     Session(session::SessionCommand),
+
+    /// Manage comments in review sessions
+    #[command(subcommand)]
+    Comment(comment::CommentCommand),
+
+    /// Manage per-project session storage
+    #[command(subcommand)]
+    Project(project::ProjectCommand),
+
+    /// Inspect or relocate session storage
+    #[command(subcommand)]
+    Storage(storage::StorageCommand),
+
+    /// List workspaces configured for multi-repository setups
+    #[command(subcommand)]
+    Workspace(workspace::WorkspaceCommand),
+
+    /// Print a shell completion script
+    Completion(completion::CompletionArgs),
 }
 
 /// Run the CLI application
@@ -77,16 +237,66 @@ pub fn run() -> anyhow::Result<()> {
         colored::control::set_override(false);
     }
 
+    let workspaces_config = cli
+        .config
+        .clone()
+        .unwrap_or_else(|| std::path::PathBuf::from(DEFAULT_CONFIG_PATH));
+    let workspace = match &cli.workspace {
+        Some(name) => {
+            if !matches!(
+                cli.command,
+                Commands::Session(_) | Commands::Comment(_) | Commands::Export(_)
+            ) {
+                anyhow::bail!(
+                    "--workspace is only supported by the session, comment and export commands"
+                );
+            }
+            Some(workspace::open(name, &workspaces_config)?)
+        }
+        None => None,
+    };
+    // A workspace's own config applies unless --config names one
+    let config_path = match (&cli.config, &workspace) {
+        (None, Some(workspace)) => workspace.root.join(DEFAULT_CONFIG_PATH),
+        _ => workspaces_config.clone(),
+    };
+
     // Dispatch to command handler
     match cli.command {
-        Commands::Review(args) => review::execute(args),
-        Commands::Export(args) => export::execute(args),
+        Commands::Review(args) => review::execute(args, &config_path),
+        Commands::Export(args) => export::execute(args, workspace.map(|w| w.manager), &config_path),
+        Commands::Diff(args) => diff::execute(args, &config_path),
+        Commands::ImportTodos(args) => import_todos::execute(args),
+        Commands::ImportLsp(args) => import_lsp::execute(args),
+        Commands::ImportCheckstyle(args) => import_checkstyle::execute(args),
+        Commands::ImportPr(args) => import_pr::execute(args),
+        Commands::CheckConflicts(args) => match check_conflicts::execute(args) {
+            Ok(true) => Ok(()),
+            Ok(false) => std::process::exit(check_conflicts::EXIT_CONFLICTS),
+            // Keep errors apart from conflicts so hooks only block on the latter
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                std::process::exit(check_conflicts::EXIT_ERROR);
+            }
+        },
+        Commands::Inject(args) => inject::execute(args),
         Commands::Init(args) => init::execute(args),
         Commands::Install(args) => install::execute(args),
         Commands::Uninstall(args) => uninstall::execute(args),
-        Commands::Doctor(args) => doctor::execute(args),
-        Commands::Config(cmd) => config::execute(cmd),
-        Commands::Session(cmd) => session::execute(cmd),
+        Commands::Doctor(mut args) => {
+            args.verbose = cli.verbose > 0;
+            if !doctor::execute(args, &config_path)?.passed() {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Commands::Config(cmd) => config::execute(cmd, &config_path),
+        Commands::Session(cmd) => session::execute(cmd, workspace, &config_path),
+        Commands::Comment(cmd) => comment::execute(cmd, workspace.map(|w| w.manager)),
+        Commands::Project(cmd) => project::execute(cmd),
+        Commands::Storage(cmd) => storage::execute(cmd),
+        Commands::Workspace(cmd) => workspace::execute(cmd, &workspaces_config),
+        Commands::Completion(args) => completion::execute(args),
     }
 }
 
@@ -121,4 +331,30 @@ mod tests {
         let cmd = Cli::command();
         assert!(cmd.get_about().is_some());
     }
+
+    #[test]
+    fn test_import_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = r#"<checkstyle><file name="A.java"><error line="3" severity="warning" message="x"/></file></checkstyle>"#;
+        let comments = || cr_core::comment::CommentManager::import_from_checkstyle(report).unwrap();
+        let mut args = ImportTargetArgs {
+            session: None,
+            sessions_dir: Some(dir.path().to_path_buf()),
+        };
+        let source = || Ok(DiffSource::Report { tool: "checkstyle".to_string() });
+
+        let created = import_comments(&args, source, comments()).unwrap();
+        assert_eq!(created.comments.count(), 1);
+        assert_eq!(created.metadata.name.as_deref(), Some(created.source_description().as_str()));
+
+        // An existing session is reused and the source is never asked for
+        args.session = Some(created.id.to_string());
+        let source = || -> anyhow::Result<DiffSource> { panic!("no session should be created") };
+        let updated = import_comments(&args, source, comments()).unwrap();
+        assert_eq!(updated.id, created.id);
+        assert_eq!(updated.comments.count(), 2);
+
+        args.session = Some("missing".to_string());
+        assert!(import_comments(&args, || unreachable!(), comments()).is_err());
+    }
 }
@@ -0,0 +1,97 @@
+//! Project command
+//!
+//! Register projects for isolated, per-project session storage.
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use std::path::PathBuf;
+
+use cr_storage::{FileSystemStorage, ProjectIndex};
+
+/// Project subcommands
+#[derive(Debug, Subcommand)]
+pub enum ProjectCommand {
+    /// Register a directory as a project with its own session storage
+    Init {
+        /// Project directory (default: current directory)
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// List registered projects
+    List,
+}
+
+/// Execute the project command
+pub fn execute(cmd: ProjectCommand) -> Result<()> {
+    match cmd {
+        ProjectCommand::Init { path } => init_project(path),
+        ProjectCommand::List => list_projects(),
+    }
+}
+
+/// Open session storage for the registered project containing the current directory
+pub fn detect_project_storage() -> Result<Option<FileSystemStorage>> {
+    let index = ProjectIndex::load(&ProjectIndex::default_path())?;
+    let cwd = std::env::current_dir()?;
+
+    match index.find_containing(&cwd) {
+        Some(entry) => {
+            tracing::debug!("Using project storage {} for {:?}", entry.key, entry.path);
+            Ok(Some(FileSystemStorage::for_project(&entry.path)?))
+        }
+        None => Ok(None),
+    }
+}
+
+fn init_project(path: Option<PathBuf>) -> Result<()> {
+    use colored::Colorize;
+
+    let project_dir = match path {
+        Some(path) => path,
+        None => std::env::current_dir()?,
+    };
+
+    let index_path = ProjectIndex::default_path();
+    let mut index = ProjectIndex::load(&index_path)?;
+    let entry = index
+        .register(&project_dir)
+        .context(format!("Failed to register {}", project_dir.display()))?;
+    index.save(&index_path)?;
+
+    let storage = FileSystemStorage::for_project(&entry.path)?;
+
+    println!(
+        "{} Registered project {}",
+        "✓".green(),
+        entry.path.display().to_string().cyan()
+    );
+    println!("  Key: {}", entry.key);
+    println!("  Sessions: {}", storage.sessions_dir().display());
+
+    Ok(())
+}
+
+fn list_projects() -> Result<()> {
+    use colored::Colorize;
+
+    let index = ProjectIndex::load(&ProjectIndex::default_path())?;
+
+    if index.projects.is_empty() {
+        println!(
+            "No projects registered. Run '{}' to register one.",
+            "cr-helper project init".cyan()
+        );
+        return Ok(());
+    }
+
+    for entry in &index.projects {
+        println!(
+            "  {} {}",
+            entry.key.to_string().yellow(),
+            entry.path.display()
+        );
+    }
+
+    Ok(())
+}
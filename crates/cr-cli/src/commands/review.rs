@@ -3,13 +3,19 @@
 //! Start a code review session with TUI interface.
 
 use anyhow::{Context, Result};
+use clap::builder::RangedU64ValueParser;
 use clap::Args;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use cr_core::diff::DiffParser;
-use cr_core::session::{DiffSource, SessionManager};
+use cr_core::config::Config;
+use cr_core::diff::{
+    ConflictMarkerDetector, CrIgnore, DiffData, DiffParser, DiffStats, FileMode, LintDetectors,
+    LongLineDetector, ParserConfig, TabVsSpaceDetector, TrailingWhitespaceDetector, CRIGNORE_PATH,
+};
+use cr_core::session::{
+    DiffSource, SessionManager, SessionMetadata, INCREMENTAL_BASE_SESSION_KEY, SKIPPED_FILES_KEY,
+};
 use cr_core::types::SessionId;
-use cr_storage::FileSystemStorage;
 
 /// Arguments for the review command
 #[derive(Debug, Args)]
@@ -26,6 +32,37 @@ pub struct ReviewArgs {
     #[arg(long)]
     pub commit: Option<String>,
 
+    /// Review a Mercurial working copy against REV (default: its parent)
+    #[arg(long, value_name = "REV", num_args = 0..=1, default_missing_value = ".")]
+    pub hg: Option<String>,
+
+    /// Review a Subversion working copy, optionally against revision REV
+    #[arg(long, value_name = "REV", num_args = 0..=1)]
+    pub svn: Option<Option<u64>>,
+
+    /// Review a .patch/.diff file (git, hg, svn or plain unified format)
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["staged", "commit", "hg", "svn"])]
+    pub from_patch: Option<PathBuf>,
+
+    /// Review a diff piped on stdin (git or plain `diff -u` output)
+    #[arg(long, conflicts_with_all = ["staged", "commit", "hg", "svn", "from_patch", "session"])]
+    pub stdin: bool,
+
+    /// Only review what changed between REF and HEAD
+    #[arg(
+        long,
+        value_name = "REF",
+        conflicts_with_all = ["staged", "commit", "hg", "svn", "from_patch", "stdin", "session"]
+    )]
+    pub only_changes_since: Option<String>,
+
+    /// Only review what changed since the latest session was created
+    #[arg(
+        long,
+        conflicts_with_all = ["staged", "commit", "hg", "svn", "from_patch", "stdin", "session", "only_changes_since"]
+    )]
+    pub only_changes_since_last_review: bool,
+
     /// Include untracked (new) files in the review
     #[arg(long, short = 'u')]
     pub untracked: bool,
@@ -41,31 +78,165 @@ pub struct ReviewArgs {
     /// Don't start TUI, just create session
     #[arg(long)]
     pub no_tui: bool,
+
+    /// Open the TUI at this file (exact path or path suffix)
+    #[arg(long)]
+    pub file: Option<String>,
+
+    /// Split diffs with more than N files into linked sessions of at most N files
+    #[arg(long, value_name = "N")]
+    pub split_threshold: Option<usize>,
+
+    /// Only review the N most-changed files, skipping the rest
+    #[arg(long, value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub focus_hot_files: Option<usize>,
+
+    /// Don't rotate out old sessions when creating this one
+    #[arg(long)]
+    pub no_rotate: bool,
+
+    /// Let rotation delete old sessions even if they have comments
+    #[arg(long, conflicts_with = "no_rotate")]
+    pub force_rotate: bool,
+
+    /// Add warnings for trailing whitespace on added lines
+    #[arg(long)]
+    pub check_trailing_whitespace: bool,
+
+    /// Add warnings for indentation mixing tabs and spaces
+    #[arg(long)]
+    pub check_tabs: bool,
+
+    /// Add warnings for added lines longer than N characters
+    #[arg(long, value_name = "N")]
+    pub max_line_length: Option<usize>,
+
+    /// Add critical comments for unresolved merge conflict markers
+    #[arg(long)]
+    pub check_conflicts: bool,
+
+    /// Lines of context around each change (default: [diff] context_lines, or git's 3)
+    #[arg(long, value_name = "N")]
+    pub context_lines: Option<usize>,
+
+    /// Highlight the changed words within changed lines (`git diff --word-diff`)
+    #[arg(long)]
+    pub word_diff: bool,
+
+    /// Only show files assigned to this reviewer in the TUI
+    #[arg(long, value_name = "NAME")]
+    pub reviewer_filter: Option<String>,
+
+    /// Review hunk by hunk with terminal prompts instead of the TUI
+    #[arg(long, conflicts_with = "no_tui")]
+    pub interactive: bool,
+
+    /// Only show files with these change modes in the TUI
+    #[command(flatten)]
+    pub file_modes: FileModeArgs,
+
+    /// Don't preview binary files (images, archives, hex dumps) in the TUI
+    #[arg(long)]
+    pub no_binary_preview: bool,
+
+    /// Leave binary files (and `.crignore` `[binary]` paths) out of the session
+    #[arg(long)]
+    pub no_binary: bool,
+
+    /// Leave lockfiles (`[diff] lockfile_patterns`) out of the session
+    #[arg(long)]
+    pub exclude_lockfiles: bool,
+
+    /// Show the git log around this commit in a TUI sidebar (toggle with `L`)
+    #[arg(long, value_name = "SHA")]
+    pub context_commit: Option<String>,
+}
+
+/// `--only-added` / `--only-deleted` / ... flags, shared with `export`
+///
+/// Several flags combine; none means every file.
+#[derive(Debug, Args, Default, Clone)]
+pub struct FileModeArgs {
+    /// Only added files
+    #[arg(long)]
+    pub only_added: bool,
+
+    /// Only deleted files
+    #[arg(long)]
+    pub only_deleted: bool,
+
+    /// Only modified files
+    #[arg(long)]
+    pub only_modified: bool,
+
+    /// Only renamed files
+    #[arg(long)]
+    pub only_renamed: bool,
+}
+
+impl FileModeArgs {
+    /// Selected modes (empty when no flag is set)
+    pub fn modes(&self) -> Vec<FileMode> {
+        [
+            (self.only_added, FileMode::Added),
+            (self.only_deleted, FileMode::Deleted),
+            (self.only_modified, FileMode::Modified),
+            (self.only_renamed, FileMode::Renamed),
+        ]
+        .into_iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, mode)| mode)
+        .collect()
+    }
 }
 
 /// Execute the review command
-pub fn execute(args: ReviewArgs) -> Result<()> {
+pub fn execute(args: ReviewArgs, config_path: &Path) -> Result<()> {
     use colored::Colorize;
 
     println!("{}", "Starting code review...".cyan());
 
     // Determine diff source
-    let diff_source = determine_diff_source(&args)?;
-    tracing::info!("Diff source: {:?}", diff_source);
+    let mut diff_source = determine_diff_source(&args)?;
+
+    let config = Config::load(config_path).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring invalid config: {}", e);
+        Config::default()
+    });
 
     // Set up storage
-    let storage_path = args
-        .output
-        .clone()
-        .unwrap_or_else(|| PathBuf::from(".cr-helper/sessions"));
-    let storage = FileSystemStorage::new(&storage_path)?;
-    let mut manager = SessionManager::new(storage);
+    let storage = super::open_storage(args.output.as_deref())?;
+    let mut manager = SessionManager::new(storage.with_format(config.storage.format));
+    manager.set_repo_root(std::env::current_dir()?);
+    let mut parser_config = config.diff.parser_config();
+    if args.context_lines.is_some() {
+        parser_config.context_lines = args.context_lines;
+    }
+    parser_config.word_diff = args.word_diff;
+    parser_config.accept_plain_unified = args.stdin;
+    if !args.no_rotate {
+        let max = config.storage.max_sessions;
+        manager.set_max_sessions((max > 0).then_some(max));
+        manager.set_force_rotate(args.force_rotate);
+    }
+
+    let base_session = if args.only_changes_since_last_review {
+        let (base_id, hash) = last_review_base(&manager)?;
+        diff_source = DiffSource::CommitRange {
+            from: hash,
+            to: "HEAD".to_string(),
+        };
+        Some(base_id)
+    } else {
+        None
+    };
+    tracing::info!("Diff source: {:?}", diff_source);
 
     // Create or resume session
-    let session = if let Some(session_id) = args.session {
+    let detectors = lint_detectors(&args);
+    let mut session = if let Some(session_id) = &args.session {
         println!("Resuming session: {}", session_id.yellow());
-        let id = SessionId::from_string(&session_id)
-            .context(format!("Invalid session ID: {}", session_id))?;
+        let id = manager.resolve_id(session_id)?;
         manager
             .load(&id)
             .context(format!("Session '{}' not found", session_id))?
@@ -74,9 +245,62 @@ pub fn execute(args: ReviewArgs) -> Result<()> {
         if args.untracked {
             println!("{}", "Including untracked files...".dimmed());
         }
-        create_new_session(&diff_source, &mut manager, args.untracked)?
+        let mut sessions = create_new_sessions(
+            &diff_source,
+            &mut manager,
+            parser_config,
+            args.untracked,
+            FileLimits {
+                split_threshold: args.split_threshold,
+                focus_hot_files: args.focus_hot_files,
+                max_files: config.diff.max_files(),
+            },
+            FileExclusions {
+                binary: args.no_binary,
+                lockfile_patterns: args.exclude_lockfiles.then_some(&config.diff.lockfile_patterns[..]),
+            },
+        )?;
+        if let DiffSource::CommitRange { from, .. } = &diff_source {
+            if base_session.is_some() || args.only_changes_since.is_some() {
+                let files: usize = sessions.iter().map(|s| s.diff_data.files.len()).sum();
+                println!(
+                    "{} files changed since {}",
+                    files.to_string().cyan(),
+                    match &base_session {
+                        Some(id) => format!("the last review ({})", id.short()),
+                        None => from.clone(),
+                    }
+                );
+            }
+        }
+        if let Some(base_id) = &base_session {
+            for session in &mut sessions {
+                session
+                    .extensions
+                    .set(INCREMENTAL_BASE_SESSION_KEY, base_id.as_str());
+                manager.save(session)?;
+            }
+        }
+        if sessions.len() > 1 {
+            print_batch(&sessions);
+            if args.no_tui {
+                return Ok(());
+            }
+            let first = choose_batch_part(&sessions, args.output.as_deref())?;
+            sessions.swap_remove(first)
+        } else {
+            sessions.remove(0)
+        }
     };
 
+    if !detectors.is_empty() {
+        let added = add_lint_comments(&mut session, &detectors)?;
+        if added > 0 {
+            manager.save(&mut session)?;
+        }
+        println!("Lint checks added {} comments", added.to_string().yellow());
+    }
+
     let session_id = session.id.clone();
     println!("Session ID: {}", session_id.to_string().green());
 
@@ -84,14 +308,97 @@ pub fn execute(args: ReviewArgs) -> Result<()> {
     if args.no_tui {
         print_session_info(&session);
         Ok(())
+    } else if args.interactive {
+        super::interactive::run(&mut session, &manager, &config)
     } else {
         // Run TUI
-        run_tui(session, manager)
+        run_tui(session, manager, &args, &config)
+    }
+}
+
+fn lint_detectors(args: &ReviewArgs) -> LintDetectors {
+    let mut detectors = LintDetectors::new();
+    if args.check_trailing_whitespace {
+        detectors = detectors.with(TrailingWhitespaceDetector);
+    }
+    if args.check_tabs {
+        detectors = detectors.with(TabVsSpaceDetector);
+    }
+    if let Some(max) = args.max_line_length {
+        detectors = detectors.with(LongLineDetector(max));
+    }
+    if args.check_conflicts {
+        detectors = detectors.with(ConflictMarkerDetector);
+    }
+    detectors
+}
+
+/// Add detector findings, skipping ones already on the same line (e.g. on resume)
+fn add_lint_comments(
+    session: &mut cr_core::session::Session,
+    detectors: &LintDetectors,
+) -> Result<usize> {
+    let mut added = 0;
+    for comment in detectors.detect(&session.diff_data) {
+        let line_id = comment.line_ids()[0].clone();
+        let duplicate = session
+            .comments
+            .get_by_line(&line_id)
+            .iter()
+            .any(|c| c.content == comment.content);
+        if !duplicate {
+            session.comments.add(comment)?;
+            added += 1;
+        }
     }
+    Ok(added)
+}
+
+/// The latest session and the HEAD it was created at
+fn last_review_base(manager: &SessionManager) -> Result<(SessionId, String)> {
+    let latest = manager
+        .load_latest()?
+        .context("No previous review session found")?;
+    let hash = latest.metadata.checkin_hash.clone().with_context(|| {
+        format!(
+            "Session {} has no recorded git HEAD to diff against",
+            latest.id.short()
+        )
+    })?;
+    Ok((latest.id, hash))
 }
 
 fn determine_diff_source(args: &ReviewArgs) -> Result<DiffSource> {
-    if args.staged {
+    if let Some(since) = &args.only_changes_since {
+        Ok(DiffSource::CommitRange {
+            from: since.clone(),
+            to: "HEAD".to_string(),
+        })
+    } else if args.stdin {
+        Ok(DiffSource::Stdin)
+    } else if let Some(path) = &args.from_patch {
+        Ok(DiffSource::Patch {
+            path: path.display().to_string(),
+        })
+    } else if let Some(rev) = &args.hg {
+        let cwd = std::env::current_dir()?;
+        if !cr_integration::HgDetector::is_hg_repo(&cwd) {
+            anyhow::bail!(
+                "--hg given but {} is not in a Mercurial repository",
+                cwd.display()
+            );
+        }
+        Ok(DiffSource::Mercurial { rev: rev.clone() })
+    } else if let Some(rev) = args.svn {
+        let cwd = std::env::current_dir()?;
+        if !cr_integration::SvnDetector::is_svn_working_copy(&cwd) {
+            anyhow::bail!(
+                "--svn given but {} is not in a Subversion working copy",
+                cwd.display()
+            );
+        }
+        Ok(DiffSource::Svn { rev })
+    } else if args.staged {
         Ok(DiffSource::Staged)
     } else if let Some(commit) = &args.commit {
         Ok(DiffSource::Commit {
@@ -106,38 +413,86 @@ fn determine_diff_source(args: &ReviewArgs) -> Result<DiffSource> {
     }
 }
 
-fn create_new_session(
+/// Files left out of a new session (`--no-binary` / `--exclude-lockfiles`)
+#[derive(Debug, Default, Clone, Copy)]
+struct FileExclusions<'a> {
+    /// Drop binary files and `.crignore` `[binary]` paths
+    binary: bool,
+    /// Drop files matching these patterns
+    lockfile_patterns: Option<&'a [String]>,
+}
+
+/// Drop the files `exclusions` asks for, returning how many binary files and
+/// lockfiles were removed
+fn exclude_files(diff: &mut DiffData, exclusions: FileExclusions, ignore: &CrIgnore) -> (usize, usize) {
+    let before = diff.files.len();
+    if exclusions.binary {
+        let text_modes = [FileMode::Added, FileMode::Deleted, FileMode::Modified, FileMode::Renamed, FileMode::Copied];
+        *diff = diff.filter_by_mode(&text_modes);
+        diff.files.retain(|f| !ignore.is_binary(f.display_path()));
+        diff.stats = DiffStats::from_diff(diff);
+    }
+    let binary = before - diff.files.len();
+
+    let lockfiles = exclusions
+        .lockfile_patterns
+        .map(|patterns| CrIgnore::parse(&patterns.join("\n")).filter_diff(diff))
+        .unwrap_or(0);
+    (binary, lockfiles)
+}
+
+/// How many files go into new sessions
+#[derive(Debug, Default, Clone, Copy)]
+struct FileLimits {
+    /// Split into linked sessions of at most this many files (`--split-threshold`)
+    split_threshold: Option<usize>,
+    /// Keep only this many of the most-changed files (`--focus-hot-files`)
+    focus_hot_files: Option<usize>,
+    /// Drop files past this count unless splitting (`[diff] max_files_per_session`)
+    max_files: Option<usize>,
+}
+
+fn create_new_sessions(
     source: &DiffSource,
     manager: &mut SessionManager,
+    parser_config: ParserConfig,
     include_untracked: bool,
-) -> Result<cr_core::session::Session> {
+    limits: FileLimits,
+    exclusions: FileExclusions,
+) -> Result<Vec<cr_core::session::Session>> {
     use colored::Colorize;
-    use cr_core::diff::DiffSource as ParserDiffSource;
 
-    // Convert session DiffSource to parser DiffSource
-    let parser_source = match source {
-        DiffSource::WorkingTree => ParserDiffSource::WorkingTree,
-        DiffSource::Staged => ParserDiffSource::Staged,
-        DiffSource::Commit { commit } => ParserDiffSource::Commit {
-            commit: commit.clone(),
-        },
-        DiffSource::CommitRange { from, to } => ParserDiffSource::CommitRange {
-            from: from.clone(),
-            to: to.clone(),
-        },
-        DiffSource::Branch { branch } => ParserDiffSource::Branch {
-            branch: branch.clone(),
-        },
-        DiffSource::PullRequest { base, .. } => ParserDiffSource::CommitRange {
-            from: base.clone(),
-            to: "HEAD".to_string(),
-        },
-        DiffSource::Custom { args } => ParserDiffSource::Custom { args: args.clone() },
-    };
+    // Non-git sources are parsed by their dedicated parser calls below
+    let parser_source = source
+        .to_parser_source()
+        .unwrap_or(cr_core::diff::DiffSource::WorkingTree);
 
     // Parse diff using DiffParser with untracked option
-    let parser = DiffParser::new();
-    let diff_data = parser.parse_from_git_with_options(&parser_source, include_untracked)?;
+    let parser = DiffParser::with_config(parser_config);
+    let mut diff_data = match source {
+        DiffSource::Mercurial { rev } => parser.parse_from_hg_rev(rev)?,
+        DiffSource::Svn { rev } => parser.parse_from_svn_rev(*rev)?,
+        DiffSource::Patch { path } => parser.parse_from_file(Path::new(path))?,
+        DiffSource::Stdin => {
+            // Without splitting or focusing, files past the cap are never built
+            let max_files = limits
+                .max_files
+                .filter(|_| limits.split_threshold.is_none() && limits.focus_hot_files.is_none());
+            parser
+                .parse_reader(std::io::stdin().lock(), max_files)
+                .context("Failed to read diff from stdin")?
+        }
+        _ => parser.parse_from_git_with_options(&parser_source, include_untracked)?,
+    };
+    if exclusions.binary || exclusions.lockfile_patterns.is_some() {
+        let ignore = if exclusions.binary {
+            CrIgnore::load(Path::new(CRIGNORE_PATH)).context(format!("Failed to read {}", CRIGNORE_PATH))?
+        } else {
+            CrIgnore::default()
+        };
+        let (binary, lockfiles) = exclude_files(&mut diff_data, exclusions, &ignore);
+        tracing::info!("Excluded {} binary files, {} lockfiles", binary, lockfiles);
+    }
 
     if diff_data.files.is_empty() {
         println!("{}", "No changes detected.".yellow());
@@ -151,10 +506,112 @@ fn create_new_session(
         diff_data.stats.deletions.to_string().red()
     );
 
-    // Create session
-    let session = manager.create(source.clone(), diff_data)?;
+    // Focus on the hottest files
+    let skipped = match limits.focus_hot_files {
+        Some(n) if diff_data.files.len() > n => {
+            let skipped = diff_data.retain_hottest(n);
+            println!(
+                "Focusing on the {} most-changed files ({} skipped)",
+                n.to_string().cyan(),
+                skipped.len().to_string().yellow()
+            );
+            skipped
+        }
+        _ => Vec::new(),
+    };
+
+    // Split large diffs into linked sessions, or cut them at the per-session limit
+    let split_threshold = limits
+        .split_threshold
+        .filter(|n| *n > 0 && diff_data.files.len() > *n);
+    if let (None, Some(max)) = (split_threshold, limits.max_files) {
+        // Stdin diffs may already have been cut while parsing
+        if diff_data.truncate_files(max) || diff_data.metadata.total_files().is_some() {
+            println!(
+                "{} Large diff: showing first {} of {} files ([diff] max_files_per_session)",
+                "⚠".yellow(),
+                diff_data.files.len(),
+                diff_data.metadata.total_files().unwrap_or_default()
+            );
+        }
+    }
+    let mut sessions = match split_threshold {
+        Some(threshold) => {
+            let parts = diff_data.split(threshold);
+            println!(
+                "Splitting into {} sessions of at most {} files",
+                parts.len().to_string().cyan(),
+                threshold
+            );
+            manager.create_batch(parts, source.clone(), SessionMetadata::default())?
+        }
+        None => vec![manager.create(source.clone(), diff_data)?],
+    };
+
+    if !skipped.is_empty() {
+        for session in &mut sessions {
+            session.extensions.set(SKIPPED_FILES_KEY, &skipped);
+            manager.save(session)?;
+        }
+    }
+
+    Ok(sessions)
+}
+
+fn print_batch(sessions: &[cr_core::session::Session]) {
+    use colored::Colorize;
+
+    println!("\n{}", "Batch sessions:".bold());
+    for session in sessions {
+        println!(
+            "  {} {} ({} files)",
+            session.id.to_string().green(),
+            session.metadata.name.as_deref().unwrap_or(""),
+            session.file_count()
+        );
+    }
+}
+
+/// Ask which part of a batch to open, optionally opening the others in tmux windows
+fn choose_batch_part(
+    sessions: &[cr_core::session::Session],
+    output: Option<&std::path::Path>,
+) -> Result<usize> {
+    use dialoguer::Select;
+
+    let mut items: Vec<String> = sessions
+        .iter()
+        .map(|s| s.metadata.name.clone().unwrap_or_else(|| s.id.to_string()))
+        .collect();
+    let in_tmux = std::env::var_os("TMUX").is_some();
+    if in_tmux {
+        items.push("Open all (other parts in new tmux windows)".to_string());
+    }
+
+    let choice = Select::new()
+        .with_prompt("Which part do you want to review first?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    if choice < sessions.len() {
+        return Ok(choice);
+    }
+
+    // Open parts 2..n in new tmux windows and part 1 here
+    let exe = std::env::current_exe().context("Failed to locate cr-helper executable")?;
+    for session in &sessions[1..] {
+        let mut command = format!("{} review --session {}", exe.display(), session.id);
+        if let Some(output) = output {
+            command.push_str(&format!(" --output {}", output.display()));
+        }
+        std::process::Command::new("tmux")
+            .args(["new-window", "-d", &command])
+            .status()
+            .context("Failed to run tmux new-window")?;
+    }
 
-    Ok(session)
+    Ok(0)
 }
 
 fn print_session_info(session: &cr_core::session::Session) {
@@ -190,17 +647,80 @@ fn print_session_info(session: &cr_core::session::Session) {
     }
 }
 
-fn run_tui(session: cr_core::session::Session, mut manager: SessionManager) -> Result<()> {
-    use cr_ui::App;
+fn run_tui(
+    session: cr_core::session::Session,
+    mut manager: SessionManager,
+    args: &ReviewArgs,
+    config: &Config,
+) -> Result<()> {
+    use cr_ui::{App, TuiConfig};
+
+    let reviewer = args.reviewer_filter.as_deref();
+    let mut shown = session.clone();
+    if let Some(reviewer) = reviewer {
+        let files: Vec<_> = session
+            .files_for_reviewer(reviewer)
+            .into_iter()
+            .cloned()
+            .collect();
+        if files.is_empty() {
+            anyhow::bail!("No files in this diff are assigned to {}", reviewer);
+        }
+        shown.diff_data.files = files;
+        shown.diff_data.stats = DiffStats::from_diff(&shown.diff_data);
+    }
 
-    let mut app = App::new(session)?;
+    let has_checkpoints = manager
+        .checkpoints(&session.id)
+        .is_ok_and(|c| !c.is_empty());
+    let mut app = App::new(shown, TuiConfig::from(&config.ui))?;
+    if reviewer.is_some() {
+        app.set_unfiltered_diff(session.diff_data.clone());
+    }
+    app.set_session_manager(manager.clone());
+    app.state.has_checkpoints = has_checkpoints;
+    app.state.export_format = Some(config.export.default_format.clone());
+    app.state.export_on_save = config.ui.export_on_save;
+    app.state.no_binary_preview = args.no_binary_preview;
+    app.set_risk_thresholds(config.review.risk_thresholds);
+    app.state.severity_keywords = config.review.severity_keywords.clone();
+    let file_modes = args.file_modes.modes();
+    if !file_modes.is_empty() {
+        app.set_file_mode_filter(file_modes);
+    }
+    if let Some(rev) = args.context_commit.as_deref() {
+        let sha = cr_integration::GitLog::resolve(rev)?;
+        app.state.commit_log = cr_integration::GitLog::fetch(&sha, 10)
+            .context(format!("Failed to read git log around {}", rev))?;
+        app.state.context_commit = Some(sha);
+    }
+    if let Some(path) = args.file.as_deref() {
+        if !app.goto_file_path(path) {
+            app.state.set_message(format!("File not in diff: {}", path));
+        }
+    }
+    let _lock = app
+        .acquire_lock()
+        .map_err(|e| tracing::warn!("Failed to write review lock: {}", e))
+        .ok();
     app.run()?;
 
-    // Save session after TUI exits
+    // Save session after TUI exits (`:session` may have switched to another)
     let mut session = app.get_session();
     manager.save(&mut session)?;
 
     println!("Session saved: {}", session.id);
+
+    if config.integration.claude_code.auto_inject_on_review {
+        let adapter = cr_integration::ClaudeCodeAdapter::new();
+        match adapter.inject_review_context(&session) {
+            Ok(()) => println!(
+                "Review context written to {}",
+                adapter.review_context_path().display()
+            ),
+            Err(e) => tracing::warn!("Failed to write review context: {}", e),
+        }
+    }
     Ok(())
 }
 
@@ -208,16 +728,138 @@ fn run_tui(session: cr_core::session::Session, mut manager: SessionManager) -> R
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_exclude_files() {
+        let input = "diff --git a/logo.png b/logo.png\nBinary files a/logo.png and b/logo.png differ\n\
+                     diff --git a/app.min.js b/app.min.js\n@@ -1 +1 @@\n-a\n+b\n\
+                     diff --git a/Cargo.lock b/Cargo.lock\n@@ -1 +1 @@\n-a\n+b\n\
+                     diff --git a/src/main.rs b/src/main.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        let diff = DiffParser::new().parse(input).unwrap();
+        let patterns = Config::default().diff.lockfile_patterns;
+        let lockfiles = FileExclusions {
+            binary: false,
+            lockfile_patterns: Some(&patterns),
+        };
+
+        let mut excluded = diff.clone();
+        let ignore = CrIgnore::parse("[binary]\n*.min.js");
+        let all = FileExclusions { binary: true, ..lockfiles };
+        assert_eq!(exclude_files(&mut excluded, all, &ignore), (2, 1));
+        assert_eq!(excluded.files.len(), 1);
+        assert_eq!(excluded.stats.files_changed, 1);
+
+        let mut excluded = diff;
+        assert_eq!(exclude_files(&mut excluded, lockfiles, &ignore), (0, 1));
+        assert_eq!(excluded.files.len(), 3);
+    }
+
+    #[test]
+    fn test_create_new_sessions_limits() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let patch = temp.path().join("changes.patch");
+        let input = (0..5)
+            .map(|i| format!("diff --git a/f{i}.rs b/f{i}.rs\n@@ -1 +1,2 @@\n a\n+b\n"))
+            .collect::<String>();
+        std::fs::write(&patch, input + "diff --git a/Cargo.lock b/Cargo.lock\n@@ -1 +1 @@\n-a\n+b\n").unwrap();
+        let source = DiffSource::Patch {
+            path: patch.display().to_string(),
+        };
+        let mut manager = SessionManager::new(cr_storage::FileSystemStorage::new(temp.path().join("sessions")).unwrap());
+        let patterns = Config::default().diff.lockfile_patterns;
+        let exclusions = FileExclusions {
+            binary: false,
+            lockfile_patterns: Some(&patterns),
+        };
+        let create = |manager: &mut SessionManager, limits| {
+            create_new_sessions(&source, manager, ParserConfig::default(), false, limits, exclusions).unwrap()
+        };
+
+        // The cap counts files left after exclusions
+        let capped = FileLimits {
+            max_files: Some(5),
+            ..Default::default()
+        };
+        let sessions = create(&mut manager, capped);
+        assert_eq!(sessions[0].diff_data.files.len(), 5);
+        assert_eq!(sessions[0].diff_data.metadata.total_files(), None);
+
+        let capped = FileLimits {
+            max_files: Some(2),
+            ..Default::default()
+        };
+        let sessions = create(&mut manager, capped);
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].diff_data.files.len(), 2);
+        assert_eq!(sessions[0].diff_data.metadata.total_files(), Some(5));
+
+        // Splitting keeps every file
+        let split = FileLimits {
+            split_threshold: Some(2),
+            ..capped
+        };
+        let sessions = create(&mut manager, split);
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(sessions.iter().map(|s| s.diff_data.files.len()).sum::<usize>(), 5);
+    }
+
+    #[test]
+    fn test_file_mode_args() {
+        assert!(FileModeArgs::default().modes().is_empty());
+        let args = FileModeArgs {
+            only_deleted: true,
+            only_renamed: true,
+            ..Default::default()
+        };
+        assert_eq!(args.modes(), vec![FileMode::Deleted, FileMode::Renamed]);
+    }
+
+    #[test]
+    fn test_focus_hot_files_arg() {
+        use crate::commands::{Cli, Commands};
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from(["cr-helper", "review", "--focus-hot-files", "3"]).unwrap();
+        let Commands::Review(args) = cli.command else {
+            panic!("expected review");
+        };
+        assert_eq!(args.focus_hot_files, Some(3));
+        assert!(Cli::try_parse_from(["cr-helper", "review", "--focus-hot-files", "0"]).is_err());
+    }
+
     #[test]
     fn test_determine_diff_source_staged() {
         let args = ReviewArgs {
             git_args: vec![],
             staged: true,
             commit: None,
+            hg: None,
+            svn: None,
+            from_patch: None,
+            only_changes_since: None,
+            only_changes_since_last_review: false,
             untracked: false,
             session: None,
             output: None,
             no_tui: false,
+            file: None,
+            split_threshold: None,
+            focus_hot_files: None,
+            check_trailing_whitespace: false,
+            check_tabs: false,
+            max_line_length: None,
+            check_conflicts: false,
+            context_lines: None,
+            reviewer_filter: None,
+            context_commit: None,
+            word_diff: false,
+            stdin: false,
+            file_modes: FileModeArgs::default(),
+            no_binary_preview: false,
+            no_binary: false,
+            exclude_lockfiles: false,
+            interactive: false,
+            no_rotate: false,
+            force_rotate: false,
         };
         let source = determine_diff_source(&args).unwrap();
         assert!(matches!(source, DiffSource::Staged));
@@ -229,27 +871,156 @@ mod tests {
             git_args: vec![],
             staged: false,
             commit: Some("abc123".to_string()),
+            hg: None,
+            svn: None,
+            from_patch: None,
+            only_changes_since: None,
+            only_changes_since_last_review: false,
             untracked: false,
             session: None,
             output: None,
             no_tui: false,
+            file: None,
+            split_threshold: None,
+            focus_hot_files: None,
+            check_trailing_whitespace: false,
+            check_tabs: false,
+            max_line_length: None,
+            check_conflicts: false,
+            context_lines: None,
+            reviewer_filter: None,
+            context_commit: None,
+            word_diff: false,
+            stdin: false,
+            file_modes: FileModeArgs::default(),
+            no_binary_preview: false,
+            no_binary: false,
+            exclude_lockfiles: false,
+            interactive: false,
+            no_rotate: false,
+            force_rotate: false,
         };
         let source = determine_diff_source(&args).unwrap();
         assert!(matches!(source, DiffSource::Commit { .. }));
     }
 
+    #[test]
+    fn test_determine_diff_source_only_changes_since() {
+        let args = ReviewArgs {
+            git_args: vec![],
+            staged: false,
+            commit: None,
+            hg: None,
+            svn: None,
+            from_patch: None,
+            only_changes_since: Some("v1.2".to_string()),
+            only_changes_since_last_review: false,
+            untracked: false,
+            session: None,
+            output: None,
+            no_tui: false,
+            file: None,
+            split_threshold: None,
+            focus_hot_files: None,
+            check_trailing_whitespace: false,
+            check_tabs: false,
+            max_line_length: None,
+            check_conflicts: false,
+            context_lines: None,
+            reviewer_filter: None,
+            context_commit: None,
+            word_diff: false,
+            stdin: false,
+            file_modes: FileModeArgs::default(),
+            no_binary_preview: false,
+            no_binary: false,
+            exclude_lockfiles: false,
+            interactive: false,
+            no_rotate: false,
+            force_rotate: false,
+        };
+        let source = determine_diff_source(&args).unwrap();
+        assert_eq!(
+            source.to_git_args(),
+            vec!["v1.2..HEAD".to_string()]
+        );
+    }
+
     #[test]
     fn test_determine_diff_source_working_tree() {
         let args = ReviewArgs {
             git_args: vec![],
             staged: false,
             commit: None,
+            hg: None,
+            svn: None,
+            from_patch: None,
+            only_changes_since: None,
+            only_changes_since_last_review: false,
             untracked: false,
             session: None,
             output: None,
             no_tui: false,
+            file: None,
+            split_threshold: None,
+            focus_hot_files: None,
+            check_trailing_whitespace: false,
+            check_tabs: false,
+            max_line_length: None,
+            check_conflicts: false,
+            context_lines: None,
+            reviewer_filter: None,
+            context_commit: None,
+            word_diff: false,
+            stdin: false,
+            file_modes: FileModeArgs::default(),
+            no_binary_preview: false,
+            no_binary: false,
+            exclude_lockfiles: false,
+            interactive: false,
+            no_rotate: false,
+            force_rotate: false,
         };
         let source = determine_diff_source(&args).unwrap();
         assert!(matches!(source, DiffSource::WorkingTree));
     }
+
+    #[test]
+    fn test_determine_diff_source_patch() {
+        let args = ReviewArgs {
+            git_args: vec![],
+            staged: false,
+            commit: None,
+            hg: None,
+            svn: None,
+            from_patch: Some(PathBuf::from("fix.patch")),
+            only_changes_since: None,
+            only_changes_since_last_review: false,
+            untracked: false,
+            session: None,
+            output: None,
+            no_tui: false,
+            file: None,
+            split_threshold: None,
+            focus_hot_files: None,
+            check_trailing_whitespace: false,
+            check_tabs: false,
+            max_line_length: None,
+            check_conflicts: false,
+            context_lines: None,
+            reviewer_filter: None,
+            context_commit: None,
+            word_diff: false,
+            stdin: false,
+            file_modes: FileModeArgs::default(),
+            no_binary_preview: false,
+            no_binary: false,
+            exclude_lockfiles: false,
+            interactive: false,
+            no_rotate: false,
+            force_rotate: false,
+        };
+        let source = determine_diff_source(&args).unwrap();
+        assert!(matches!(source, DiffSource::Patch { path } if path == "fix.patch"));
+    }
 }
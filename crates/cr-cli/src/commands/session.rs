@@ -3,12 +3,72 @@
 //! Manage review sessions.
 
 use anyhow::{Context, Result};
-use clap::Subcommand;
-use std::path::PathBuf;
+use clap::{Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
 
-use cr_core::session::SessionManager;
-use cr_core::types::SessionId;
-use cr_storage::FileSystemStorage;
+use cr_core::config::{Config, RiskThresholds};
+use cr_core::session::{
+    ReviewAssignment, RiskLevel, Session, SessionInfo, SessionManager, SessionSortOrder,
+    SortDirection, SortField, Verdict,
+};
+
+use super::workspace::Workspace;
+
+/// Output format for `session watch`
+#[derive(Debug, Clone, Copy, ValueEnum, Default)]
+pub enum WatchFormat {
+    /// `<session_id> <timestamp>` per line
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Order for `session list` (largest / newest first)
+#[derive(Debug, Clone, Copy, ValueEnum, Default)]
+pub enum ListSort {
+    /// Last update time
+    #[default]
+    Updated,
+    /// Creation time
+    Created,
+    /// Number of comments
+    Comments,
+    /// Number of files
+    Files,
+    /// Risk score (see `session score`)
+    Risk,
+    /// Words of feedback written
+    WordCount,
+}
+
+impl ListSort {
+    fn order(self) -> SessionSortOrder {
+        let by = match self {
+            ListSort::Updated => SortField::UpdatedAt,
+            ListSort::Created => SortField::CreatedAt,
+            ListSort::Comments => SortField::CommentCount,
+            ListSort::Files => SortField::FileCount,
+            ListSort::Risk => SortField::RiskScore,
+            ListSort::WordCount => SortField::WordCount,
+        };
+        SessionSortOrder {
+            by,
+            direction: SortDirection::Descending,
+        }
+    }
+}
+
+/// Change to make to a session's tags
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum TagAction {
+    /// Add a tag (ignored if already present)
+    Add,
+    /// Remove a tag
+    Remove,
+    /// Remove all tags
+    Clear,
+}
 
 /// Session subcommands
 #[derive(Debug, Subcommand)]
@@ -23,9 +83,27 @@ pub enum SessionCommand {
         #[arg(long)]
         json: bool,
 
+        /// Stable KEY=VALUE output for scripts (one blank-line-separated block per session)
+        #[arg(long, conflicts_with_all = ["json", "detailed"])]
+        porcelain: bool,
+
         /// Limit number of sessions
         #[arg(long, short, default_value = "10")]
         limit: usize,
+
+        /// Sort order
+        #[arg(long, value_enum, default_value = "updated")]
+        sort: ListSort,
+    },
+
+    /// Show a session's risk score and level
+    Score {
+        /// Session ID
+        id: String,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Show session details
@@ -36,6 +114,10 @@ pub enum SessionCommand {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+
+        /// Also show how much feedback was written
+        #[arg(long)]
+        word_count: bool,
     },
 
     /// Delete a session
@@ -50,7 +132,7 @@ pub enum SessionCommand {
 
     /// Clean old sessions
     Clean {
-        /// Delete sessions older than this many days
+        /// Delete unpinned sessions older than this many days
         #[arg(long, default_value = "30")]
         older_than: u64,
 
@@ -58,35 +140,412 @@ pub enum SessionCommand {
         #[arg(long, short)]
         yes: bool,
     },
+
+    /// Show comment changes between two sessions
+    Diff {
+        /// Baseline (earlier) session ID
+        id1: String,
+
+        /// Newer session ID
+        id2: String,
+    },
+
+    /// Compare review metrics against an older session
+    Compare {
+        /// Baseline (earlier) session ID
+        #[arg(required_unless_present = "vs_latest")]
+        id1: Option<String>,
+
+        /// Newer session ID
+        #[arg(required_unless_present = "vs_latest")]
+        id2: Option<String>,
+
+        /// Compare against the most recent session created before this one
+        #[arg(long, conflicts_with_all = ["id1", "id2"])]
+        vs_latest: bool,
+
+        /// Session to compare with --vs-latest (default: latest)
+        #[arg(long, short, requires = "vs_latest")]
+        session: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Keep a session from being deleted by rotation or `session clean`
+    Pin {
+        /// Session ID
+        id: String,
+    },
+
+    /// Let rotation and `session clean` delete a pinned session again
+    Unpin {
+        /// Session ID
+        id: String,
+    },
+
+    /// Remove comment index entries for comments that no longer exist
+    Heal {
+        /// Session ID
+        id: String,
+    },
+
+    /// Add comments to a session from a YAML annotation file
+    Annotate {
+        /// Session ID
+        #[arg(long, short)]
+        session: String,
+
+        /// YAML file with a list of {file, line, severity, content, tags}
+        #[arg(long)]
+        from: PathBuf,
+    },
+
+    /// Resolve the comments an agent's response says it fixed
+    ApplyResponse {
+        /// Session ID
+        #[arg(long, short)]
+        session: String,
+
+        /// Text file with the agent's response
+        file: PathBuf,
+    },
+
+    /// Render a session's review screen to an SVG file
+    Screenshot {
+        /// Session ID
+        id: String,
+
+        /// Output file (default: .cr-helper/screenshots/<id>.svg)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// Screen width in columns
+        #[arg(long, default_value = "120")]
+        width: u16,
+
+        /// Screen height in rows
+        #[arg(long, default_value = "40")]
+        height: u16,
+    },
+
+    /// Record a walkthrough of a session's review screen as an asciicast
+    /// (`asciinema play <file>`)
+    Record {
+        /// Session ID
+        id: String,
+
+        /// Frames (lines stepped through) per second
+        #[arg(long, default_value = "10")]
+        fps: u8,
+
+        /// Output file
+        #[arg(long, short, default_value = "session.cast")]
+        output: PathBuf,
+
+        /// Playback speed multiplier
+        #[arg(long, default_value = "1.0")]
+        speed: f64,
+    },
+
+    /// Print the diff a session reviewed, rebuilt from the saved session
+    ExportDiff {
+        /// Session ID
+        id: String,
+
+        /// Write to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// Output format: patch (default) or json
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Check with `git apply --check` whether the patch still applies
+        #[arg(long)]
+        git_apply_dry_run: bool,
+    },
+
+    /// Group a session's comments by topic
+    Cluster {
+        /// Session ID
+        id: String,
+
+        /// Number of clusters (0 picks one automatically)
+        #[arg(long, default_value = "0")]
+        clusters: usize,
+    },
+
+    /// Save a named snapshot of a session
+    Checkpoint {
+        /// Session ID
+        id: String,
+
+        /// Checkpoint name (e.g. "after security pass")
+        name: String,
+    },
+
+    /// List a session's checkpoints
+    Checkpoints {
+        /// Session ID
+        id: String,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Print session IDs as sessions are saved (until interrupted)
+    Watch {
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: WatchFormat,
+    },
+
+    /// Rename a session
+    Rename {
+        /// Session ID
+        id: String,
+
+        /// New session name
+        name: String,
+
+        /// Print the updated session info as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Assign files matching glob patterns to a reviewer
+    Assign {
+        /// Session ID
+        id: String,
+
+        /// Reviewer name
+        reviewer: String,
+
+        /// File glob patterns (e.g. `src/auth/**`)
+        #[arg(required = true)]
+        patterns: Vec<String>,
+
+        /// Print the updated session info as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Add, remove or clear a session's tags
+    Tag {
+        /// What to do with the tag
+        #[arg(value_enum)]
+        action: TagAction,
+
+        /// Session ID
+        id: String,
+
+        /// Tag name (not needed for `clear`)
+        tag: Option<String>,
+
+        /// Print the updated session info as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show reviews currently running in this project
+    Active {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 /// Execute the session command
-pub fn execute(cmd: SessionCommand) -> Result<()> {
-    let storage_path = PathBuf::from(".cr-helper/sessions");
-
-    if !storage_path.exists() {
-        use colored::Colorize;
-        eprintln!(
-            "{} No sessions directory found. Run '{}' first.",
-            "⚠".yellow(),
-            "cr-helper init".cyan()
-        );
-        return Ok(());
+pub fn execute(cmd: SessionCommand, workspace: Option<Workspace>, config_path: &Path) -> Result<()> {
+    match cmd {
+        // Running reviews are tracked by lock file, not session storage
+        SessionCommand::Active { json } => list_active(json),
+        cmd => {
+            let (workspace, root) = match workspace {
+                Some(w) => (Some(w.manager), w.root),
+                None => (None, PathBuf::from(".")),
+            };
+            let Some(manager) = super::existing_manager(workspace)? else {
+                return Ok(());
+            };
+            execute_with(cmd, manager, &root, config_path)
+        }
     }
+}
 
-    let storage = FileSystemStorage::new(&storage_path)?;
-    let manager = SessionManager::new(storage);
-
+/// Execute a session command against `manager`'s storage
+///
+/// `root` is the repository the sessions belong to; git calls run there.
+fn execute_with(
+    cmd: SessionCommand,
+    manager: SessionManager,
+    root: &Path,
+    config_path: &Path,
+) -> Result<()> {
     match cmd {
         SessionCommand::List {
             detailed,
             json,
+            porcelain,
             limit,
-        } => list_sessions(manager, detailed, json, limit),
-        SessionCommand::Show { id, json } => show_session(manager, &id, json),
+            sort,
+        } => {
+            if porcelain {
+                print!("{}", porcelain_list(&manager.list_sorted(sort.order(), Some(limit))?));
+                return Ok(());
+            }
+            list_sessions(manager, detailed, json, limit, sort)
+        }
+        SessionCommand::Score { id, json } => score_session(manager, &id, json, config_path),
+        SessionCommand::Show {
+            id,
+            json,
+            word_count,
+        } => show_session(manager, &id, json, word_count, root),
         SessionCommand::Delete { id, yes } => delete_session(manager, &id, yes),
         SessionCommand::Clean { older_than, yes } => clean_sessions(manager, older_than, yes),
+        SessionCommand::Diff { id1, id2 } => diff_sessions(manager, &id1, &id2),
+        SessionCommand::Compare {
+            id1,
+            id2,
+            vs_latest,
+            session,
+            json,
+        } => {
+            let (baseline, current) = if vs_latest {
+                let current = super::comment::load_session(&manager, session.as_deref())?;
+                (previous_session(&manager, &current)?, current)
+            } else {
+                let load = |id: Option<String>| -> Result<Session> {
+                    let id = id.unwrap_or_default();
+                    let session_id = manager.resolve_id(&id)?;
+                    manager
+                        .load(&session_id)
+                        .context(format!("Session '{}' not found", id))
+                };
+                (load(id1)?, load(id2)?)
+            };
+            let config = Config::load(config_path).unwrap_or_default();
+            compare_sessions(&baseline, &current, &config.review.risk_thresholds, json)
+        }
+        SessionCommand::Pin { id } => pin_session(manager, &id, true),
+        SessionCommand::Unpin { id } => pin_session(manager, &id, false),
+        SessionCommand::Heal { id } => heal_session(manager, &id),
+        SessionCommand::Annotate { session, from } => {
+            super::comment::annotate_session(&manager, &session, &from, config_path)
+        }
+        SessionCommand::ApplyResponse { session, file } => {
+            apply_response(manager, &session, &file)
+        }
+        SessionCommand::Screenshot {
+            id,
+            output,
+            width,
+            height,
+        } => screenshot_session(manager, &id, output, width, height),
+        SessionCommand::Record {
+            id,
+            fps,
+            output,
+            speed,
+        } => record_session(manager, &id, fps, &output, speed),
+        SessionCommand::ExportDiff {
+            id,
+            output,
+            format,
+            git_apply_dry_run,
+        } => export_diff(
+            manager,
+            &id,
+            output,
+            format.as_deref(),
+            git_apply_dry_run,
+            root,
+        ),
+        SessionCommand::Cluster { id, clusters } => cluster_session(manager, &id, clusters),
+        SessionCommand::Checkpoint { id, name } => checkpoint_session(manager, &id, &name),
+        SessionCommand::Checkpoints { id, json } => list_checkpoints(manager, &id, json),
+        SessionCommand::Watch { format } => watch_sessions(manager, format),
+        SessionCommand::Rename { id, name, json } => {
+            let session = rename(&manager, &id, &name)?;
+            print_metadata(&session, json)
+        }
+        SessionCommand::Assign {
+            id,
+            reviewer,
+            patterns,
+            json,
+        } => {
+            let session = assign(&manager, &id, &reviewer, patterns)?;
+            print_metadata(&session, json)
+        }
+        SessionCommand::Tag {
+            action,
+            id,
+            tag,
+            json,
+        } => {
+            let tag = || tag.as_deref().context("Specify the tag to add or remove");
+            let session = match action {
+                TagAction::Add => tag_add(&manager, &id, tag()?)?,
+                TagAction::Remove => tag_remove(&manager, &id, tag()?)?,
+                TagAction::Clear => tag_clear(&manager, &id)?,
+            };
+            print_metadata(&session, json)
+        }
+        SessionCommand::Active { json } => list_active(json),
+    }
+}
+
+fn list_active(as_json: bool) -> Result<()> {
+    use colored::Colorize;
+    use cr_integration::{AgentAdapter, ClaudeCodeAdapter};
+
+    let sessions = ClaudeCodeAdapter::new().list_running_sessions()?;
+
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&sessions)?);
+        return Ok(());
+    }
+
+    if sessions.is_empty() {
+        println!("No active reviews.");
+        return Ok(());
+    }
+
+    println!("{}", "Active reviews:".bold().underline());
+    println!();
+    for session in &sessions {
+        println!(
+            "  pid {} {} (started {})",
+            session.pid.to_string().green(),
+            session.project_path.display(),
+            session.started_at.format("%Y-%m-%d %H:%M:%S").to_string().dimmed()
+        );
     }
+
+    Ok(())
+}
+
+/// `session list --porcelain` output
+fn porcelain_list(sessions: &[SessionInfo]) -> String {
+    sessions
+        .iter()
+        .map(|info| {
+            format!(
+                "SESSION_ID={}\nNAME={}\nFILES={}\nCOMMENTS={}\nUPDATED_AT={}\n",
+                info.id,
+                info.metadata.name.as_deref().unwrap_or(""),
+                info.file_count,
+                info.comment_count,
+                info.updated_at.to_rfc3339()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 fn list_sessions(
@@ -94,18 +553,17 @@ fn list_sessions(
     detailed: bool,
     as_json: bool,
     limit: usize,
+    sort: ListSort,
 ) -> Result<()> {
     use colored::Colorize;
 
-    let sessions = manager.list()?;
+    let sessions = manager.list_sorted(sort.order(), Some(limit))?;
 
     if sessions.is_empty() {
         println!("No sessions found.");
         return Ok(());
     }
 
-    let sessions: Vec<_> = sessions.into_iter().take(limit).collect();
-
     if as_json {
         let json = serde_json::to_string_pretty(&sessions)?;
         println!("{}", json);
@@ -116,10 +574,15 @@ fn list_sessions(
     println!();
 
     for info in &sessions {
+        let pin = if info.metadata.pinned { " 📌" } else { "" };
         if detailed {
-            println!("  {}", info.id.to_string().green());
+            println!("  {}{}", info.id.to_string().green(), pin);
             println!("    Files: {}", info.file_count);
             println!("    Comments: {}", info.comment_count);
+            println!("    Risk score: {:.1}", info.risk_score);
+            if let Some(path) = &info.hottest_file {
+                println!("    Hottest file: {}", path);
+            }
             if let Some(name) = &info.metadata.name {
                 println!("    Name: {}", name);
             }
@@ -147,11 +610,23 @@ fn list_sessions(
                 format!("{}d ago", age / 24)
             };
 
+            let risk = match sort {
+                ListSort::Risk => format!(", risk {:.1}", info.risk_score),
+                ListSort::WordCount => format!(", {} words", info.word_count),
+                _ => String::new(),
+            };
+            let hottest = match &info.hottest_file {
+                Some(path) => format!(", hottest {}", path),
+                None => String::new(),
+            };
             println!(
-                "  {} {} files, {} comments ({})",
-                info.id.to_string().green(),
+                "  {}{} {} files{}, {} comments{} ({})",
+                info.id.short().green(),
+                pin,
                 info.file_count.to_string().cyan(),
+                hottest.dimmed(),
                 info.comment_count.to_string().yellow(),
+                risk,
                 age_str.dimmed()
             );
         }
@@ -170,11 +645,301 @@ fn list_sessions(
     Ok(())
 }
 
-fn show_session(manager: SessionManager, id: &str, as_json: bool) -> Result<()> {
+fn screenshot_session(
+    manager: SessionManager,
+    id: &str,
+    output: Option<PathBuf>,
+    width: u16,
+    height: u16,
+) -> Result<()> {
+    use colored::Colorize;
+
+    let session_id = manager.resolve_id(id)?;
+    let session = manager
+        .load(&session_id)
+        .context(format!("Session '{}' not found", id))?;
+
+    let path = output.unwrap_or_else(|| {
+        PathBuf::from(".cr-helper/screenshots").join(format!("{}.svg", session_id))
+    });
+    cr_ui::App::headless(session, width, height).screenshot(&path)?;
+
+    println!("{} Screenshot saved to {}", "✓".green(), path.display());
+    Ok(())
+}
+
+fn record_session(manager: SessionManager, id: &str, fps: u8, output: &Path, speed: f64) -> Result<()> {
+    use colored::Colorize;
+
+    let session_id = manager.resolve_id(id)?;
+    let session = manager
+        .load(&session_id)
+        .context(format!("Session '{}' not found", id))?;
+
+    cr_ui::App::headless(session, 120, 40).record_session_video_with_speed(output, fps, speed)?;
+
+    println!("{} Recording saved to {}", "✓".green(), output.display());
+    println!("  Play it with: asciinema play {}", output.display());
+    Ok(())
+}
+
+fn export_diff(
+    manager: SessionManager,
+    id: &str,
+    output: Option<PathBuf>,
+    format: Option<&str>,
+    git_apply_dry_run: bool,
+    root: &Path,
+) -> Result<()> {
     use colored::Colorize;
 
-    let session_id = SessionId::from_string(id)
-        .context(format!("Invalid session ID: {}", id))?;
+    let session_id = manager.resolve_id(id)?;
+    let session = manager
+        .load(&session_id)
+        .context(format!("Session '{}' not found", id))?;
+
+    let patch = session.diff_data.patch_string();
+    let content = match format.unwrap_or("patch") {
+        "patch" | "diff" => patch.clone(),
+        "json" => serde_json::to_string_pretty(&session.diff_data)?,
+        other => anyhow::bail!("Unknown diff format '{}' (expected patch or json)", other),
+    };
+    if session.diff_data.files.iter().any(|f| f.needs_loading()) {
+        eprintln!(
+            "{} Some files were never loaded and only have headers in the output",
+            "⚠".yellow()
+        );
+    }
+    for file in &session.diff_data.files {
+        let kind = if file.is_symlink() {
+            "a symlink"
+        } else if file.is_executable() {
+            "executable"
+        } else {
+            continue;
+        };
+        eprintln!(
+            "{} {} is {}; check its mode before applying the patch",
+            "⚠".yellow(),
+            file.display_path().display(),
+            kind
+        );
+    }
+
+    match &output {
+        Some(path) => {
+            std::fs::write(path, &content)?;
+            eprintln!("{} Diff written to {}", "✓".green(), path.display());
+        }
+        None => print!("{}", content),
+    }
+
+    if git_apply_dry_run {
+        match git_apply_check(&patch, root)? {
+            0 => eprintln!("{} Patch applies cleanly", "✓".green()),
+            rejected => eprintln!(
+                "{} Patch no longer applies ({} hunks rejected)",
+                "⚠".yellow(),
+                rejected
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Run `git apply --check` on `patch` in `root`, returning the number of rejected hunks
+fn git_apply_check(patch: &str, root: &Path) -> Result<usize> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["apply", "--check", "--verbose", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run git apply")?;
+    child
+        .stdin
+        .take()
+        .context("Failed to open git apply stdin")?
+        .write_all(patch.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        return Ok(0);
+    }
+    Ok(count_rejected_hunks(&String::from_utf8_lossy(&output.stderr)).max(1))
+}
+
+/// Count `error: patch failed` lines (one per rejected hunk) in `git apply` output
+fn count_rejected_hunks(stderr: &str) -> usize {
+    stderr
+        .lines()
+        .filter(|line| line.starts_with("error: patch failed"))
+        .count()
+}
+
+fn watch_sessions(manager: SessionManager, format: WatchFormat) -> Result<()> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let _handle = manager.watch(move |id| {
+        let _ = tx.send(id);
+    })?;
+
+    for id in rx {
+        let timestamp = chrono::Utc::now().to_rfc3339();
+        match format {
+            WatchFormat::Text => println!("{} {}", id, timestamp),
+            WatchFormat::Json => println!(
+                "{}",
+                serde_json::json!({ "session_id": id.to_string(), "timestamp": timestamp })
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Load a session, apply `change` to it and save it
+fn update_session(
+    manager: &SessionManager,
+    id: &str,
+    change: impl FnOnce(&mut Session),
+) -> Result<Session> {
+    let session_id = manager.resolve_id(id)?;
+    let mut updated = None;
+    manager
+        .update(&session_id, |session| {
+            change(session);
+            session.touch();
+            updated = Some(session.clone());
+            Ok(())
+        })
+        .context(format!("Session '{}' not found", id))?;
+    Ok(updated.expect("update ran"))
+}
+
+/// Set a session's name
+pub fn rename(manager: &SessionManager, id: &str, name: &str) -> Result<Session> {
+    update_session(manager, id, |session| {
+        session.metadata.name = Some(name.to_string())
+    })
+}
+
+/// Add a tag to a session unless it already has it
+pub fn tag_add(manager: &SessionManager, id: &str, tag: &str) -> Result<Session> {
+    update_session(manager, id, |session| {
+        if !session.metadata.tags.iter().any(|t| t == tag) {
+            session.metadata.tags.push(tag.to_string());
+        }
+    })
+}
+
+/// Remove a tag from a session
+pub fn tag_remove(manager: &SessionManager, id: &str, tag: &str) -> Result<Session> {
+    update_session(manager, id, |session| {
+        session.metadata.tags.retain(|t| t != tag)
+    })
+}
+
+/// Remove all of a session's tags
+pub fn tag_clear(manager: &SessionManager, id: &str) -> Result<Session> {
+    update_session(manager, id, |session| session.metadata.tags.clear())
+}
+
+/// Add file patterns to a reviewer's assignment
+pub fn assign(
+    manager: &SessionManager,
+    id: &str,
+    reviewer: &str,
+    patterns: Vec<String>,
+) -> Result<Session> {
+    update_session(manager, id, |session| {
+        let assignments = &mut session.metadata.assignments;
+        let index = match assignments.iter().position(|a| a.reviewer == reviewer) {
+            Some(index) => index,
+            None => {
+                assignments.push(ReviewAssignment {
+                    reviewer: reviewer.to_string(),
+                    file_patterns: Vec::new(),
+                });
+                assignments.len() - 1
+            }
+        };
+        let existing = &mut assignments[index].file_patterns;
+        for pattern in patterns {
+            if !existing.contains(&pattern) {
+                existing.push(pattern);
+            }
+        }
+    })
+}
+
+/// Print a session's metadata after an update
+fn print_metadata(session: &Session, as_json: bool) -> Result<()> {
+    use colored::Colorize;
+
+    if as_json {
+        let info = SessionInfo::from(session);
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    println!("{} Updated session {}", "✓".green(), session.id.to_string().green());
+    println!(
+        "  Name: {}",
+        session.metadata.name.as_deref().unwrap_or("(none)")
+    );
+    let tags = if session.metadata.tags.is_empty() {
+        "(none)".to_string()
+    } else {
+        session.metadata.tags.join(", ")
+    };
+    println!("  Tags: {}", tags.cyan());
+    Ok(())
+}
+
+fn score_session(
+    manager: SessionManager,
+    id: &str,
+    as_json: bool,
+    config_path: &Path,
+) -> Result<()> {
+    use colored::Colorize;
+
+    let session_id = manager.resolve_id(id)?;
+    let session = manager
+        .load(&session_id)
+        .context(format!("Session '{}' not found", id))?;
+    let config = Config::load(config_path).unwrap_or_default();
+    let risk = session.compute_risk_score_with(&config.review.risk_thresholds);
+
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&risk)?);
+        return Ok(());
+    }
+
+    let level = match risk.level {
+        RiskLevel::Low => risk.level.as_str().green(),
+        RiskLevel::Medium => risk.level.as_str().yellow(),
+        RiskLevel::High | RiskLevel::Critical => risk.level.as_str().red().bold(),
+    };
+    println!("Risk: {} (score {:.1})", level, risk.score);
+    println!("  {}", risk.explanation);
+    Ok(())
+}
+
+fn show_session(
+    manager: SessionManager,
+    id: &str,
+    as_json: bool,
+    word_count: bool,
+    root: &Path,
+) -> Result<()> {
+    use colored::Colorize;
+
+    let session_id = manager.resolve_id(id)?;
     let session = manager
         .load(&session_id)
         .context(format!("Session '{}' not found", id))?;
@@ -205,6 +970,15 @@ fn show_session(manager: SessionManager, id: &str, as_json: bool) -> Result<()>
         "  Updated: {}",
         session.updated_at.format("%Y-%m-%d %H:%M:%S")
     );
+    if let Some(hash) = &session.metadata.checkin_hash {
+        println!("  Commit: {}", &hash[..7.min(hash.len())]);
+    }
+    if session.is_stale_in(root).unwrap_or(false) {
+        println!(
+            "  {}",
+            "⚠ Session was created on a different commit".yellow()
+        );
+    }
 
     println!();
     println!("{}", "Diff Statistics".bold());
@@ -249,6 +1023,21 @@ fn show_session(manager: SessionManager, id: &str, as_json: bool) -> Result<()>
         println!("  Info: {}", info.to_string().blue());
     }
 
+    if word_count {
+        let words = session.word_count_summary();
+        println!();
+        println!("{}", "Feedback".bold());
+        println!("  Words: {}", words.total_words.to_string().cyan());
+        println!("  Characters: {}", words.total_chars);
+        println!("  Average per comment: {:.1} words", words.avg_comment_length);
+        println!("  Longest comment: {} words", words.longest_comment);
+        let mut by_severity: Vec<_> = words.by_severity.iter().collect();
+        by_severity.sort_by_key(|(severity, _)| std::cmp::Reverse(**severity));
+        for (severity, count) in by_severity {
+            println!("  {}: {} words", severity, count);
+        }
+    }
+
     if !session.diff_data.files.is_empty() {
         println!();
         println!("{}", "Files".bold());
@@ -269,12 +1058,289 @@ fn show_session(manager: SessionManager, id: &str, as_json: bool) -> Result<()>
     Ok(())
 }
 
+fn diff_sessions(manager: SessionManager, id1: &str, id2: &str) -> Result<()> {
+    use colored::Colorize;
+
+    let load = |id: &str| -> Result<cr_core::session::Session> {
+        let session_id = manager.resolve_id(id)?;
+        manager
+            .load(&session_id)
+            .context(format!("Session '{}' not found", id))
+    };
+    let baseline = load(id1)?;
+    let current = load(id2)?;
+    let delta = current.delta_from(&baseline);
+
+    println!(
+        "{} {} → {}",
+        "Changes".bold().underline(),
+        baseline.id.to_string().yellow(),
+        current.id.to_string().green()
+    );
+    println!();
+
+    if delta.is_empty() {
+        println!("No comment changes.");
+        return Ok(());
+    }
+
+    let describe = |c: &cr_core::comment::Comment| {
+        format!(
+            "{}:{} {}",
+            c.metadata.file_path.as_deref().unwrap_or("?"),
+            c.metadata.line_number.unwrap_or(0),
+            c.content.lines().next().unwrap_or("")
+        )
+    };
+
+    for comment in &delta.added {
+        println!("  {} [{}] {}", "+".green(), comment.severity, describe(comment));
+    }
+    for (old, new) in &delta.changed {
+        let what = if old.state != new.state {
+            format!("{:?} → {:?}", old.state, new.state)
+        } else {
+            "edited".to_string()
+        };
+        println!("  {} ({}) {}", "~".yellow(), what, describe(new));
+    }
+    for comment in &delta.removed {
+        println!("  {} {}", "-".red(), describe(comment));
+    }
+
+    println!();
+    println!(
+        "  {} added, {} changed ({} resolved), {} removed",
+        delta.added.len().to_string().green(),
+        delta.changed.len().to_string().yellow(),
+        delta.resolved.len(),
+        delta.removed.len().to_string().red()
+    );
+
+    Ok(())
+}
+
+fn cluster_session(manager: SessionManager, id: &str, n_clusters: usize) -> Result<()> {
+    use colored::Colorize;
+
+    let session_id = manager.resolve_id(id)?;
+    let session = manager
+        .load(&session_id)
+        .context(format!("Session '{}' not found", id))?;
+
+    let clusters = session.comments.cluster_by_topic(n_clusters);
+    if clusters.is_empty() {
+        println!("No comments to cluster.");
+        return Ok(());
+    }
+
+    for (i, ids) in clusters.iter().enumerate() {
+        let label = session.comments.topic_label(ids, 3);
+        let label = if label.is_empty() {
+            "(no shared terms)".to_string()
+        } else {
+            label.join(", ")
+        };
+        println!(
+            "{} {} ({} comments)",
+            format!("Cluster {}:", i + 1).bold(),
+            label.cyan(),
+            ids.len()
+        );
+        for comment in ids.iter().filter_map(|id| session.comments.get(id)) {
+            println!(
+                "  [{}] {}:{} {}",
+                comment.severity,
+                comment.metadata.file_path.as_deref().unwrap_or("?"),
+                comment.metadata.line_number.unwrap_or(0),
+                comment.content.lines().next().unwrap_or("")
+            );
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+fn checkpoint_session(manager: SessionManager, id: &str, name: &str) -> Result<()> {
+    use colored::Colorize;
+
+    let session_id = manager.resolve_id(id)?;
+    let checkpoint_id = manager
+        .checkpoint(&session_id, name)
+        .context(format!("Failed to checkpoint session '{}'", id))?;
+
+    println!(
+        "{} Checkpoint '{}' saved as {}",
+        "✓".green(),
+        name,
+        checkpoint_id.to_string().green()
+    );
+    Ok(())
+}
+
+/// The most recently created session before `current`, ignoring checkpoints
+fn previous_session(manager: &SessionManager, current: &Session) -> Result<Session> {
+    let previous = manager
+        .list()?
+        .into_iter()
+        .filter(|s| s.id != current.id && s.checkpoint_of.is_none())
+        .filter(|s| s.created_at < current.created_at)
+        .max_by_key(|s| s.created_at)
+        .context(format!("No session older than {} to compare with", current.id))?;
+    manager
+        .load(&previous.id)
+        .context(format!("Session '{}' not found", previous.id))
+}
+
+fn compare_sessions(
+    baseline: &Session,
+    current: &Session,
+    thresholds: &RiskThresholds,
+    as_json: bool,
+) -> Result<()> {
+    use colored::Colorize;
+
+    let comparison = current.compare_to_baseline(baseline, thresholds);
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&comparison)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} {} → {}",
+        "Compared".bold().underline(),
+        baseline.id.to_string().yellow(),
+        current.id.to_string().green()
+    );
+    println!();
+    println!("  Open critical:     {:+}", comparison.new_critical);
+    println!("  Open warnings:     {:+}", comparison.new_warnings);
+    println!("  Resolved critical: {:+}", comparison.resolved_critical);
+    println!("  Resolved warnings: {:+}", comparison.resolved_warnings);
+    println!("  Coverage:          {:+.1}%", comparison.coverage_delta);
+    println!("  Risk score:        {:+.1}", comparison.risk_delta);
+    println!(
+        "  Risk level:        {} → {}",
+        comparison.baseline_risk_level, comparison.risk_level
+    );
+    println!();
+
+    let verdict = match comparison.verdict {
+        Verdict::Improving => comparison.verdict.as_str().green(),
+        Verdict::Stable => comparison.verdict.as_str().normal(),
+        Verdict::Degrading => comparison.verdict.as_str().red().bold(),
+    };
+    println!("Verdict: {}", verdict);
+    Ok(())
+}
+
+fn pin_session(manager: SessionManager, id: &str, pin: bool) -> Result<()> {
+    use colored::Colorize;
+
+    let session_id = manager.resolve_id(id)?;
+    manager
+        .update(&session_id, |session| {
+            if pin {
+                session.pin();
+            } else {
+                session.unpin();
+            }
+            Ok(())
+        })
+        .context(format!("Failed to update session '{}'", id))?;
+
+    let action = if pin { "Pinned" } else { "Unpinned" };
+    println!("{} {} session {}", "✓".green(), action, session_id);
+    Ok(())
+}
+
+fn heal_session(manager: SessionManager, id: &str) -> Result<()> {
+    use colored::Colorize;
+
+    let session_id = manager.resolve_id(id)?;
+    let mut phantoms = 0;
+    manager
+        .update(&session_id, |session| {
+            phantoms = session.comments.heal();
+            Ok(())
+        })
+        .context(format!("Failed to update session '{}'", id))?;
+
+    if phantoms == 0 {
+        println!("{} Comment index of session {} is consistent", "✓".green(), session_id);
+    } else {
+        println!(
+            "{} Removed {} phantom comment IDs from session {}",
+            "✓".green(),
+            phantoms,
+            session_id
+        );
+    }
+    Ok(())
+}
+
+fn apply_response(manager: SessionManager, id: &str, file: &Path) -> Result<()> {
+    use colored::Colorize;
+
+    let response =
+        std::fs::read_to_string(file).context(format!("Failed to read {}", file.display()))?;
+    let session_id = manager.resolve_id(id)?;
+    let mut resolved = 0;
+    manager
+        .update(&session_id, |session| {
+            resolved = session.apply_review_suggestions(&response)?;
+            Ok(())
+        })
+        .context(format!("Failed to update session '{}'", id))?;
+
+    println!(
+        "{} Resolved {} comments in session {}",
+        "✓".green(),
+        resolved,
+        session_id
+    );
+    Ok(())
+}
+
+fn list_checkpoints(manager: SessionManager, id: &str, as_json: bool) -> Result<()> {
+    use colored::Colorize;
+
+    let session_id = manager.resolve_id(id)?;
+    let checkpoints = manager.checkpoints(&session_id)?;
+
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&checkpoints)?);
+        return Ok(());
+    }
+    if checkpoints.is_empty() {
+        println!("No checkpoints for {}.", session_id.short());
+        return Ok(());
+    }
+
+    for info in &checkpoints {
+        println!(
+            "  {} {} ({} comments, {})",
+            info.id.short().green(),
+            info.metadata.name.as_deref().unwrap_or(""),
+            info.comment_count.to_string().yellow(),
+            info.created_at.format("%Y-%m-%d %H:%M").to_string().dimmed()
+        );
+    }
+    println!();
+    println!(
+        "  Compare with: cr-helper session diff {} {}",
+        checkpoints[0].id.short(),
+        session_id.short()
+    );
+    Ok(())
+}
+
 fn delete_session(manager: SessionManager, id: &str, yes: bool) -> Result<()> {
     use colored::Colorize;
 
     // Check if exists
-    let session_id = SessionId::from_string(id)
-        .context(format!("Invalid session ID: {}", id))?;
+    let session_id = manager.resolve_id(id)?;
     let session = manager
         .load(&session_id)
         .context(format!("Session '{}' not found", id))?;
@@ -314,7 +1380,7 @@ fn clean_sessions(manager: SessionManager, older_than_days: u64, yes: bool) -> R
     let sessions = manager.list()?;
     let old_sessions: Vec<_> = sessions
         .iter()
-        .filter(|s| s.updated_at < cutoff)
+        .filter(|s| s.updated_at < cutoff && !s.metadata.pinned)
         .collect();
 
     if old_sessions.is_empty() {
@@ -374,6 +1440,7 @@ fn clean_sessions(manager: SessionManager, older_than_days: u64, yes: bool) -> R
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cr_storage::FileSystemStorage;
 
     #[test]
     fn test_session_command_list() {
@@ -381,15 +1448,98 @@ mod tests {
         let _cmd = SessionCommand::List {
             detailed: false,
             json: false,
+            porcelain: false,
             limit: 10,
+            sort: ListSort::Updated,
         };
     }
 
+    #[test]
+    fn test_porcelain_list() {
+        use cr_core::session::DiffSource;
+
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SessionManager::new(FileSystemStorage::new(dir.path()).unwrap());
+        let first = manager.create_empty(DiffSource::WorkingTree).unwrap();
+        manager.create_empty(DiffSource::Staged).unwrap();
+
+        let output = porcelain_list(&manager.list().unwrap());
+        let blocks: Vec<&str> = output.split("\n\n").collect();
+        assert_eq!(blocks.len(), 2);
+        assert!(output.contains(&format!("SESSION_ID={}\n", first.id)));
+        assert_eq!(blocks[0].lines().nth(2), Some("FILES=0"));
+        assert!(porcelain_list(&[]).is_empty());
+    }
+
     #[test]
     fn test_session_command_show() {
         let _cmd = SessionCommand::Show {
             id: "test".to_string(),
             json: false,
+            word_count: true,
         };
     }
+
+    #[test]
+    fn test_count_rejected_hunks() {
+        let stderr = "Checking patch src/lib.rs...\n\
+                      error: while searching for:\nfn main() {\n\
+                      error: patch failed: src/lib.rs:1\n\
+                      error: patch failed: src/lib.rs:40\n\
+                      error: src/lib.rs: patch does not apply\n";
+        assert_eq!(count_rejected_hunks(stderr), 2);
+        assert_eq!(count_rejected_hunks(""), 0);
+    }
+
+    #[test]
+    fn test_rename_and_tag() {
+        use cr_core::session::DiffSource;
+
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SessionManager::new(FileSystemStorage::new(dir.path()).unwrap());
+        let session = manager.create_empty(DiffSource::WorkingTree).unwrap();
+        let id = session.id.to_string();
+
+        let session = rename(&manager, &id, "auth refactor").unwrap();
+        assert_eq!(session.metadata.name.as_deref(), Some("auth refactor"));
+
+        tag_add(&manager, &id, "security").unwrap();
+        tag_add(&manager, &id, "security").unwrap();
+        let session = tag_add(&manager, &id, "backend").unwrap();
+        assert_eq!(session.metadata.tags, vec!["security", "backend"]);
+
+        let session = tag_remove(&manager, &id, "security").unwrap();
+        assert_eq!(session.metadata.tags, vec!["backend"]);
+
+        tag_clear(&manager, &id).unwrap();
+        let loaded = manager.load(&session.id).unwrap();
+        assert!(loaded.metadata.tags.is_empty());
+        assert_eq!(loaded.metadata.name.as_deref(), Some("auth refactor"));
+
+        assert!(rename(&manager, "not-a-session", "x").is_err());
+    }
+
+    #[test]
+    fn test_assign() {
+        use cr_core::session::DiffSource;
+
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SessionManager::new(FileSystemStorage::new(dir.path()).unwrap());
+        let session = manager.create_empty(DiffSource::WorkingTree).unwrap();
+        let id = session.id.to_string();
+
+        assign(&manager, &id, "alice", vec!["src/auth/**".to_string()]).unwrap();
+        let session = assign(
+            &manager,
+            &id,
+            "alice",
+            vec!["src/auth/**".to_string(), "docs/*.md".to_string()],
+        )
+        .unwrap();
+        assert_eq!(session.metadata.assignments.len(), 1);
+        assert_eq!(
+            session.metadata.assignments[0].file_patterns,
+            vec!["src/auth/**", "docs/*.md"]
+        );
+    }
 }
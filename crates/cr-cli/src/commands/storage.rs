@@ -0,0 +1,105 @@
+//! Storage command
+//!
+//! Inspect and relocate session storage.
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use std::path::PathBuf;
+
+use cr_core::session::SessionStorage;
+use cr_storage::FileSystemStorage;
+
+/// Storage subcommands
+#[derive(Debug, Subcommand)]
+pub enum StorageCommand {
+    /// Move all sessions to another storage directory
+    Migrate {
+        /// Current storage directory
+        #[arg(long)]
+        from: PathBuf,
+
+        /// New storage directory
+        #[arg(long)]
+        to: PathBuf,
+    },
+
+    /// Show storage location, session count and disk usage
+    Info {
+        /// Storage directory (default: project or .cr-helper/sessions)
+        #[arg(long)]
+        sessions_dir: Option<PathBuf>,
+    },
+}
+
+/// Execute the storage command
+pub fn execute(cmd: StorageCommand) -> Result<()> {
+    match cmd {
+        StorageCommand::Migrate { from, to } => migrate(from, to),
+        StorageCommand::Info { sessions_dir } => info(sessions_dir),
+    }
+}
+
+fn migrate(from: PathBuf, to: PathBuf) -> Result<()> {
+    use colored::Colorize;
+
+    if !from.exists() {
+        anyhow::bail!("Storage directory {} not found", from.display());
+    }
+    let count = FileSystemStorage::migrate_storage(&from, &to).context(format!(
+        "Failed to migrate sessions from {}",
+        from.display()
+    ))?;
+
+    println!(
+        "{} Migrated {} sessions to {}",
+        "✓".green(),
+        count.to_string().cyan(),
+        to.display()
+    );
+    Ok(())
+}
+
+fn info(sessions_dir: Option<PathBuf>) -> Result<()> {
+    use colored::Colorize;
+
+    let storage = super::open_storage(sessions_dir.as_deref())?;
+    let sessions = storage.list()?;
+
+    println!("{}", "Session Storage".bold().underline());
+    println!();
+    println!("  Location: {}", storage.sessions_dir().display());
+    println!("  Sessions: {}", sessions.len().to_string().cyan());
+    println!("  Disk usage: {}", format_bytes(storage.disk_usage()?));
+    if let Some(oldest) = sessions.iter().map(|s| s.created_at).min() {
+        println!("  Oldest: {}", oldest.format("%Y-%m-%d %H:%M:%S"));
+    }
+    if let Some(newest) = sessions.iter().map(|s| s.created_at).max() {
+        println!("  Newest: {}", newest.format("%Y-%m-%d %H:%M:%S"));
+    }
+    Ok(())
+}
+
+/// Format a byte count as B, KB or MB
+fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let bytes_f = bytes as f64;
+    if bytes_f < KB {
+        format!("{} B", bytes)
+    } else if bytes_f < KB * KB {
+        format!("{:.1} KB", bytes_f / KB)
+    } else {
+        format!("{:.1} MB", bytes_f / (KB * KB))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MB");
+    }
+}
@@ -0,0 +1,93 @@
+//! Workspace command
+//!
+//! Inspect the repositories configured under `[[workspaces]]` in config.toml.
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use std::path::{Path, PathBuf};
+
+use cr_core::config::Config;
+use cr_core::session::SessionManager;
+use cr_storage::StorageRegistry;
+
+/// Workspace subcommands
+#[derive(Debug, Subcommand)]
+pub enum WorkspaceCommand {
+    /// List configured workspaces with their session counts
+    List,
+}
+
+/// Execute the workspace command
+pub fn execute(cmd: WorkspaceCommand, config_path: &Path) -> Result<()> {
+    match cmd {
+        WorkspaceCommand::List => list_workspaces(config_path),
+    }
+}
+
+/// A workspace selected with `--workspace`
+pub struct Workspace {
+    /// Manager for the workspace's session storage
+    pub manager: SessionManager,
+    /// Repository root, used for git calls and the workspace's own config
+    pub root: PathBuf,
+}
+
+/// Open the named workspace
+///
+/// The manager is built by the storage registry, so the workspace's sessions
+/// are used without changing the current directory.
+pub fn open(name: &str, config_path: &Path) -> Result<Workspace> {
+    let config =
+        Config::load(config_path).context(format!("Failed to load {}", config_path.display()))?;
+    let workspace = config.workspace(name).with_context(|| {
+        format!(
+            "Unknown workspace '{}' (configure it under [[workspaces]] in {})",
+            name,
+            config_path.display()
+        )
+    })?;
+
+    tracing::debug!("Using workspace {} at {:?}", name, workspace.path);
+    let manager = StorageRegistry::from_workspaces(&config.workspaces)?
+        .manager(Some(name))
+        .context(format!(
+            "Workspace '{}' not found at {}",
+            name,
+            workspace.path.display()
+        ))?;
+    Ok(Workspace {
+        manager,
+        root: workspace.path.clone(),
+    })
+}
+
+fn list_workspaces(config_path: &Path) -> Result<()> {
+    use colored::Colorize;
+
+    let config =
+        Config::load(config_path).context(format!("Failed to load {}", config_path.display()))?;
+
+    if config.workspaces.is_empty() {
+        println!(
+            "No workspaces configured. Add [[workspaces]] entries to {}.",
+            config_path.display()
+        );
+        return Ok(());
+    }
+
+    let registry = StorageRegistry::from_workspaces(&config.workspaces)?;
+    for workspace in &config.workspaces {
+        let sessions = match registry.manager(Some(&workspace.name)) {
+            Ok(manager) => format!("{} sessions", manager.count()?).cyan(),
+            Err(_) => "not found".red(),
+        };
+        println!(
+            "  {} {} ({})",
+            workspace.name.yellow(),
+            workspace.path.display(),
+            sessions
+        );
+    }
+
+    Ok(())
+}
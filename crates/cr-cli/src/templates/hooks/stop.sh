@@ -19,6 +19,20 @@ if [ -f "$SETTINGS_FILE" ]; then
     BLOCK_ON_CRITICAL=$(jq -r '.["cr-helper"].block_on_critical // true' "$SETTINGS_FILE" 2>/dev/null || echo "true")
 fi
 
+# Always refuse to stop with unresolved conflict markers
+# (check-conflicts exits 1 for conflicts, 2 if the check itself failed)
+if command -v cr-helper &> /dev/null; then
+    CONFLICT_STATUS=0
+    cr-helper check-conflicts > /dev/null || CONFLICT_STATUS=$?
+    if [ "$CONFLICT_STATUS" -eq 1 ]; then
+        echo "[cr-helper] Error: unresolved merge conflict markers in the files above."
+        echo "[cr-helper] Resolve the conflicts before proceeding."
+        exit 1
+    elif [ "$CONFLICT_STATUS" -ne 0 ]; then
+        echo "[cr-helper] Warning: conflict check failed (exit $CONFLICT_STATUS), skipping it"
+    fi
+fi
+
 # Skip if auto-review disabled
 if [ "$AUTO_REVIEW" != "true" ]; then
     exit 0
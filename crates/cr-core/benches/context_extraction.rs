@@ -0,0 +1,70 @@
+//! Per-comment vs batch context extraction on sessions with 100 comments
+//!
+//! Run with `cargo bench -p cr-core --bench context_extraction`.
+
+use cr_core::comment::{CommentBuilder, DiffSide};
+use cr_core::diff::{DiffData, DiffParser};
+use cr_core::export::{ContextExtractor, Exporter, MarkdownExporter};
+use cr_core::session::{DiffSource, Session};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// `files` files with one hunk of `lines` lines each, and 100 comments
+/// spread evenly over the files and hunks
+fn session_with_comments(files: usize, lines: usize) -> Session {
+    let mut raw = String::new();
+    for f in 0..files {
+        raw.push_str(&format!(
+            "diff --git a/src/f{f}.rs b/src/f{f}.rs\n--- a/src/f{f}.rs\n+++ b/src/f{f}.rs\n@@ -1,{} +1,{} @@\n",
+            lines / 2,
+            lines
+        ));
+        for i in 0..lines / 2 {
+            raw.push_str(&format!(" let kept_{i} = {i};\n+let added_{i} = {i};\n"));
+        }
+    }
+    let diff: DiffData = DiffParser::new().parse(&raw).unwrap();
+
+    let per_file = 100 / files;
+    let mut session = Session::new(DiffSource::WorkingTree, diff.clone());
+    for file in &diff.files {
+        let lines = &file.hunks[0].lines;
+        for n in 0..per_file {
+            let line = &lines[n * lines.len() / per_file + 7];
+            let comment = CommentBuilder::new(file.id.clone(), line.id.clone(), DiffSide::New)
+                .content("Check this")
+                .build()
+                .unwrap();
+            session.comments.add(comment).unwrap();
+        }
+    }
+    session
+}
+
+fn bench_context_extraction(c: &mut Criterion) {
+    let extractor = ContextExtractor::new(2);
+
+    for (name, files, lines) in [("10 files", 10, 500), ("1 file", 1, 5000)] {
+        let session = session_with_comments(files, lines);
+        let comments = session.comments.all();
+
+        c.bench_function(&format!("extract per comment ({name})"), |b| {
+            b.iter(|| {
+                for comment in &comments {
+                    black_box(extractor.extract(comment, &session.diff_data));
+                }
+            })
+        });
+        c.bench_function(&format!("extract_batch ({name})"), |b| {
+            b.iter(|| {
+                black_box(extractor.extract_batch(comments.iter().copied(), &session.diff_data))
+            })
+        });
+        c.bench_function(&format!("markdown export ({name})"), |b| {
+            let exporter = MarkdownExporter::new();
+            b.iter(|| black_box(exporter.export(&session).unwrap()))
+        });
+    }
+}
+
+criterion_group!(benches, bench_context_extraction);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+//! Listing info for a ~10MB session file: full load vs `SessionInfo::from_json_fast`
+//!
+//! Run with `cargo bench -p cr-core --bench session_info`.
+
+use cr_core::diff::{DiffData, DiffParser};
+use cr_core::session::{DiffSource, Session, SessionFile, SessionInfo};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::io::BufReader;
+use std::path::Path;
+
+/// 75 files of 500 changed lines each, about 10MB of JSON
+fn large_session() -> Session {
+    let mut raw = String::new();
+    for f in 0..75 {
+        raw.push_str(&format!(
+            "diff --git a/src/f{f}.rs b/src/f{f}.rs\n--- a/src/f{f}.rs\n+++ b/src/f{f}.rs\n@@ -1,250 +1,250 @@\n"
+        ));
+        for i in 0..250 {
+            raw.push_str(&format!("-let old_value_{i} = compute({i});\n+let new_value_{i} = compute_checked({i})?;\n"));
+        }
+    }
+    let diff: DiffData = DiffParser::new().parse(&raw).unwrap();
+    Session::new(DiffSource::WorkingTree, diff)
+}
+
+fn full_info(path: &Path) -> SessionInfo {
+    let file = std::fs::File::open(path).unwrap();
+    let session_file: SessionFile = serde_json::from_reader(BufReader::new(file)).unwrap();
+    session_file.into_session().info()
+}
+
+fn bench_session_info(c: &mut Criterion) {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("session.json");
+    let json = serde_json::to_string_pretty(&SessionFile::new(large_session())).unwrap();
+    println!("Session file: {:.1} MB", json.len() as f64 / 1_000_000.0);
+    std::fs::write(&path, json).unwrap();
+
+    c.bench_function("session info (full load)", |b| b.iter(|| black_box(full_info(&path))));
+    c.bench_function("session info (from_json_fast)", |b| {
+        b.iter(|| black_box(SessionInfo::from_json_fast(&path).unwrap()))
+    });
+}
+
+criterion_group!(benches, bench_session_info);
+criterion_main!(benches);
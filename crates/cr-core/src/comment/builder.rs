@@ -1,6 +1,7 @@
 //! Comment builder for fluent API
 
 use super::model::{Comment, CommentMetadata, CommentState, DiffSide, LineReference, Severity};
+use crate::config::SeverityKeywords;
 use crate::error::{CrHelperError, Result};
 use crate::types::{CommentId, Extensions, FileId, LineId};
 use chrono::Utc;
@@ -61,6 +62,167 @@ impl CommentBuilder {
         }
     }
 
+    /// Create a builder from an inline `TODO`-style marker in a source line
+    ///
+    /// Recognizes `TODO`, `FIXME`, `HACK` and `XXX` (case-insensitive) as whole
+    /// words and uses the text after the marker as content. Returns `None` if
+    /// the line has no marker.
+    pub fn from_todo_comment(source_line: &str, file_path: &str, line_num: usize) -> Option<Self> {
+        let (marker, rest) = find_todo_marker(source_line)?;
+        let severity = match marker {
+            "TODO" => Severity::Info,
+            _ => Severity::Warning,
+        };
+        let message = rest
+            .trim_start_matches(|c: char| c == ':' || c == '-' || c.is_whitespace())
+            .trim_end_matches("*/")
+            .trim();
+        let content = if message.is_empty() {
+            marker.to_string()
+        } else {
+            message.to_string()
+        };
+
+        let path = std::path::Path::new(file_path);
+        let builder = Self::new(
+            FileId::from_path(path),
+            LineId::from_content(path, source_line),
+            DiffSide::New,
+        )
+        .content(content)
+        .severity(severity)
+        .tag(marker.to_lowercase())
+        .line_number(line_num)
+        .file_path(file_path);
+        Some(builder)
+    }
+
+    /// Create a builder from an LSP `Diagnostic` object with a `uri` field
+    ///
+    /// Uses `range.start.line` (0-based), `severity` (1 = error -> critical,
+    /// 2 = warning, 3/4 = info), `message`, `code` (added as a tag) and
+    /// `source` (recorded as `lsp-<source>`). `file://` URIs are made relative
+    /// to the current directory. When the file can be read, the line ID is
+    /// derived from the line's content so the comment attaches to the diff.
+    pub fn from_language_server_diagnostic(json: &serde_json::Value) -> Result<Self> {
+        let field = |name: &str| {
+            CrHelperError::Validation(format!("LSP diagnostic is missing '{}'", name))
+        };
+        let uri = json.get("uri").and_then(|v| v.as_str()).ok_or_else(|| field("uri"))?;
+        let line = json
+            .pointer("/range/start/line")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| field("range.start.line"))? as usize;
+        let message = json
+            .get("message")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| field("message"))?;
+        let severity = match json.get("severity").and_then(|v| v.as_u64()) {
+            Some(1) => Severity::Critical,
+            Some(2) => Severity::Warning,
+            _ => Severity::Info,
+        };
+
+        let mut builder = Self::at_source_line(&uri_to_path(uri), line + 1)
+            .content(message)
+            .severity(severity);
+        match json.get("code") {
+            Some(serde_json::Value::String(code)) => builder = builder.tag(code.clone()),
+            Some(serde_json::Value::Number(code)) => builder = builder.tag(code.to_string()),
+            _ => {}
+        }
+        builder = match json.get("source").and_then(|v| v.as_str()) {
+            Some(source) => builder.source(format!("lsp-{}", source)),
+            None => builder.source("lsp"),
+        };
+        Ok(builder)
+    }
+
+    /// Create a builder for a comment on 1-based `line` of the file at `file_path`
+    ///
+    /// Absolute paths under the current directory are made relative. When the
+    /// file can be read, the line ID is derived from the line's content so the
+    /// comment attaches to the diff.
+    pub(crate) fn at_source_line(file_path: &str, line: usize) -> Self {
+        let file_path = relative_to_cwd(file_path);
+        let path = std::path::Path::new(&file_path);
+        let line_id = match std::fs::read_to_string(path).ok().and_then(|content| {
+            content
+                .lines()
+                .nth(line.saturating_sub(1))
+                .map(str::to_string)
+        }) {
+            Some(content) => LineId::from_content(path, &content),
+            None => LineId::from_position(path, "", line),
+        };
+
+        Self::new(FileId::from_path(path), line_id, DiffSide::New)
+            .line_number(line)
+            .file_path(file_path.clone())
+    }
+
+    /// Create a builder from a GitHub pull request review comment
+    ///
+    /// Uses `path`, `line` (or `original_line` for outdated comments),
+    /// `side` (`LEFT` is the old side), `body` and `user.login`. A comment
+    /// with neither line (a file-level comment) is anchored to the file, like
+    /// a file comment added in the TUI. A leading
+    /// 🔴/🟡/🔵 sets the severity (default info); that icon, a following
+    /// `**SEVERITY**:` label and a trailing 📍 location line, as written by
+    /// the Claude Code adapter, are dropped from the content.
+    pub fn from_pr_review_comment(json: &serde_json::Value) -> Result<Self> {
+        let field = |name: &str| {
+            CrHelperError::Validation(format!("PR review comment is missing '{}'", name))
+        };
+        let path = json.get("path").and_then(|v| v.as_str()).ok_or_else(|| field("path"))?;
+        let line = json
+            .get("line")
+            .and_then(|v| v.as_u64())
+            .or_else(|| json.get("original_line").and_then(|v| v.as_u64()))
+            .map(|line| line as usize);
+        let body = json.get("body").and_then(|v| v.as_str()).ok_or_else(|| field("body"))?;
+        let side = match json.get("side").and_then(|v| v.as_str()) {
+            Some("LEFT") => DiffSide::Old,
+            _ => DiffSide::New,
+        };
+        let (severity, content) = parse_severity_icon(body);
+
+        let file = std::path::Path::new(path);
+        let Some(line) = line else {
+            let builder = Self::new(FileId::from_path(file), LineId::from_string("file-comment"), side);
+            return Ok(builder
+                .content(content)
+                .severity(severity)
+                .file_path(path)
+                .source("github-pr")
+                .pr_author(json));
+        };
+        let line_id = match side {
+            DiffSide::New => std::fs::read_to_string(file)
+                .ok()
+                .and_then(|text| text.lines().nth(line.saturating_sub(1)).map(str::to_string))
+                .map(|text| LineId::from_content(file, &text)),
+            DiffSide::Old => None,
+        }
+        .unwrap_or_else(|| LineId::from_position(file, "", line));
+
+        let builder = Self::new(FileId::from_path(file), line_id, side)
+            .content(content)
+            .severity(severity)
+            .line_number(line)
+            .file_path(path)
+            .source("github-pr");
+        Ok(builder.pr_author(json))
+    }
+
+    /// Set the author from a GitHub API object's `user.login`, if present
+    fn pr_author(self, json: &serde_json::Value) -> Self {
+        match json.pointer("/user/login").and_then(|v| v.as_str()) {
+            Some(login) => self.author(login),
+            None => self,
+        }
+    }
+
     /// Set the comment content
     pub fn content(mut self, content: impl Into<String>) -> Self {
         self.content = Some(content.into());
@@ -142,6 +304,30 @@ impl CommentBuilder {
         self
     }
 
+    /// Guess a severity from the default keyword lists
+    pub fn auto_severity(content: &str, tags: &[String]) -> Severity {
+        Self::auto_severity_with(content, tags, &SeverityKeywords::default())
+    }
+
+    /// Guess a severity from tags and content keywords (case-insensitive)
+    ///
+    /// Critical keywords win over warning ones; anything else is info.
+    pub fn auto_severity_with(content: &str, tags: &[String], keywords: &SeverityKeywords) -> Severity {
+        let content = content.to_lowercase();
+        let matches = |wanted_tags: &[String], words: &[String]| {
+            tags.iter().any(|tag| wanted_tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+                || words.iter().any(|word| content.contains(&word.to_lowercase()))
+        };
+
+        if matches(&keywords.critical_tags, &keywords.critical_words) {
+            Severity::Critical
+        } else if matches(&keywords.warning_tags, &keywords.warning_words) {
+            Severity::Warning
+        } else {
+            Severity::Info
+        }
+    }
+
     /// Build the comment
     pub fn build(self) -> Result<Comment> {
         let content = self.content.ok_or_else(|| {
@@ -171,6 +357,90 @@ impl CommentBuilder {
     }
 }
 
+/// Severity from a leading 🔴/🟡/🔵 icon, and the body without the icon,
+/// its `**SEVERITY**:` label and a trailing `📍` location line
+fn parse_severity_icon(body: &str) -> (Severity, String) {
+    let body = body.trim();
+    let (severity, rest) = [("🔴", Severity::Critical), ("🟡", Severity::Warning), ("🔵", Severity::Info)]
+        .into_iter()
+        .find_map(|(icon, severity)| body.strip_prefix(icon).map(|rest| (severity, rest.trim_start())))
+        .unwrap_or((Severity::Info, body));
+    let label = format!("**{}**:", severity.to_string().to_uppercase());
+    let rest = rest.strip_prefix(label.as_str()).unwrap_or(rest).trim_start();
+    let rest = match rest.rsplit_once('\n') {
+        Some((content, last)) if last.trim_start().starts_with('📍') => content,
+        _ => rest,
+    };
+    (severity, rest.trim_end().to_string())
+}
+
+/// Path of a `file://` URI
+///
+/// Percent-escapes are decoded; other strings are returned unchanged.
+fn uri_to_path(uri: &str) -> String {
+    let Some(path) = uri.strip_prefix("file://") else {
+        return uri.to_string();
+    };
+    percent_decode(path)
+}
+
+/// `path` relative to the current directory if it is inside it
+fn relative_to_cwd(path: &str) -> String {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| {
+            std::path::Path::new(path)
+                .strip_prefix(cwd)
+                .ok()
+                .map(|p| p.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Decode `%XX` escapes, leaving malformed ones as they are
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Markers recognized by [`CommentBuilder::from_todo_comment`]
+const TODO_MARKERS: [&str; 4] = ["TODO", "FIXME", "HACK", "XXX"];
+
+/// Find the first whole-word marker in a line and the text following it
+fn find_todo_marker(line: &str) -> Option<(&'static str, &str)> {
+    let upper = line.to_ascii_uppercase();
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    TODO_MARKERS
+        .iter()
+        .flat_map(|marker| upper.match_indices(marker).map(move |(i, _)| (i, *marker)))
+        .filter(|(i, marker)| {
+            let before = upper[..*i].chars().next_back();
+            let after = upper[i + marker.len()..].chars().next();
+            !before.is_some_and(is_word) && !after.is_some_and(is_word)
+        })
+        .min_by_key(|(i, _)| *i)
+        .map(|(i, marker)| (marker, &line[i + marker.len()..]))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -293,4 +563,154 @@ mod tests {
 
         assert!(comment.line_ref.is_range());
     }
+
+    #[test]
+    fn test_auto_severity() {
+        let no_tags: Vec<String> = Vec::new();
+        assert_eq!(
+            CommentBuilder::auto_severity("Password stored as a plain CREDENTIAL", &no_tags),
+            Severity::Critical
+        );
+        assert_eq!(
+            CommentBuilder::auto_severity("Looks fine", &["Security".to_string()]),
+            Severity::Critical
+        );
+        assert_eq!(
+            CommentBuilder::auto_severity("This should use a HashSet", &no_tags),
+            Severity::Warning
+        );
+        assert_eq!(CommentBuilder::auto_severity("Nice cleanup", &no_tags), Severity::Info);
+
+        let keywords = SeverityKeywords {
+            warning_words: vec!["nit".to_string()],
+            ..SeverityKeywords::default()
+        };
+        assert_eq!(
+            CommentBuilder::auto_severity_with("nit: rename this", &no_tags, &keywords),
+            Severity::Warning
+        );
+        assert_eq!(
+            CommentBuilder::auto_severity_with("You should rename this", &no_tags, &keywords),
+            Severity::Info
+        );
+    }
+
+    #[test]
+    fn test_from_todo_comment() {
+        let comment = CommentBuilder::from_todo_comment("    // TODO: handle errors", "src/a.rs", 12)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(comment.content, "handle errors");
+        assert_eq!(comment.severity, Severity::Info);
+        assert_eq!(comment.tags, vec!["todo"]);
+        assert_eq!(comment.metadata.line_number, Some(12));
+        assert_eq!(comment.metadata.file_path.as_deref(), Some("src/a.rs"));
+
+        let comment = CommentBuilder::from_todo_comment("x = 1  # fixme - racy", "a.py", 3)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(comment.content, "racy");
+        assert_eq!(comment.severity, Severity::Warning);
+
+        let comment = CommentBuilder::from_todo_comment("/* HACK */", "a.c", 1)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(comment.content, "HACK");
+        assert_eq!(comment.severity, Severity::Warning);
+
+        assert!(CommentBuilder::from_todo_comment("let todo_list = vec![];", "a.rs", 1).is_none());
+        assert!(CommentBuilder::from_todo_comment("plain code", "a.rs", 1).is_none());
+    }
+
+    #[test]
+    fn test_from_language_server_diagnostic() {
+        let json = serde_json::json!({
+            "uri": "file:///nonexistent/my%20dir/a.rs",
+            "range": {"start": {"line": 9, "character": 4}, "end": {"line": 9, "character": 8}},
+            "severity": 1,
+            "code": "E0308",
+            "source": "rust-analyzer",
+            "message": "mismatched types"
+        });
+        let comment = CommentBuilder::from_language_server_diagnostic(&json)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(comment.content, "mismatched types");
+        assert_eq!(comment.severity, Severity::Critical);
+        assert_eq!(comment.tags, vec!["E0308"]);
+        assert_eq!(comment.metadata.line_number, Some(10));
+        assert_eq!(comment.metadata.file_path.as_deref(), Some("/nonexistent/my dir/a.rs"));
+        assert_eq!(comment.metadata.source.as_deref(), Some("lsp-rust-analyzer"));
+
+        let json = serde_json::json!({
+            "uri": "src/b.py",
+            "range": {"start": {"line": 0}},
+            "severity": 2,
+            "code": 401,
+            "message": "unused import"
+        });
+        let comment = CommentBuilder::from_language_server_diagnostic(&json)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(comment.severity, Severity::Warning);
+        assert_eq!(comment.tags, vec!["401"]);
+        assert_eq!(comment.metadata.source.as_deref(), Some("lsp"));
+
+        let missing = serde_json::json!({"uri": "a.rs", "message": "x"});
+        assert!(CommentBuilder::from_language_server_diagnostic(&missing).is_err());
+    }
+
+    #[test]
+    fn test_from_pr_review_comment() {
+        let json = serde_json::json!({
+            "path": "src/nonexistent.rs",
+            "line": 42,
+            "side": "RIGHT",
+            "body": "🔴 **CRITICAL**: SQL injection\n   📍 `src/nonexistent.rs:42`",
+            "user": {"login": "octocat"}
+        });
+        let comment = CommentBuilder::from_pr_review_comment(&json).unwrap().build().unwrap();
+        assert_eq!(comment.content, "SQL injection");
+        assert_eq!(comment.severity, Severity::Critical);
+        assert_eq!(comment.metadata.line_number, Some(42));
+        assert_eq!(comment.metadata.file_path.as_deref(), Some("src/nonexistent.rs"));
+        assert_eq!(comment.metadata.source.as_deref(), Some("github-pr"));
+        assert_eq!(comment.metadata.author.as_deref(), Some("octocat"));
+
+        let json = serde_json::json!({
+            "path": "a.rs",
+            "line": null,
+            "original_line": 3,
+            "side": "LEFT",
+            "body": "Plain remark"
+        });
+        let comment = CommentBuilder::from_pr_review_comment(&json).unwrap().build().unwrap();
+        assert_eq!(comment.severity, Severity::Info);
+        assert_eq!(comment.content, "Plain remark");
+        assert_eq!(comment.metadata.line_number, Some(3));
+        assert!(matches!(comment.line_ref, LineReference::SingleLine { side: DiffSide::Old, .. }));
+
+        // File-level comments have no line at all
+        let json = serde_json::json!({
+            "path": "a.rs",
+            "line": null,
+            "original_line": null,
+            "subject_type": "file",
+            "body": "Split this file up",
+            "user": {"login": "octocat"}
+        });
+        let comment = CommentBuilder::from_pr_review_comment(&json).unwrap().build().unwrap();
+        assert_eq!(comment.metadata.line_number, None);
+        assert_eq!(comment.line_ids(), vec![&LineId::from_string("file-comment")]);
+        assert_eq!(comment.metadata.author.as_deref(), Some("octocat"));
+
+        let (severity, content) = parse_severity_icon("🟡 Consider a guard clause");
+        assert_eq!((severity, content.as_str()), (Severity::Warning, "Consider a guard clause"));
+        assert!(CommentBuilder::from_pr_review_comment(&serde_json::json!({"path": "a.rs"})).is_err());
+    }
 }
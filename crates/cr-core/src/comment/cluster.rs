@@ -0,0 +1,196 @@
+//! Topic clustering of comments (TF-IDF vectors + k-means)
+//!
+//! Used by [`CommentManager::cluster_by_topic`](super::CommentManager::cluster_by_topic).
+
+use std::collections::HashMap;
+
+/// Upper bound on clusters tried when the count is auto-detected
+const MAX_AUTO_CLUSTERS: usize = 10;
+
+/// k-means gives up after this many passes without converging
+const MAX_ITERATIONS: usize = 50;
+
+/// Common words that say nothing about a comment's topic
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "this", "that", "with", "from", "via", "are", "was", "not", "but", "can",
+    "should", "could", "would", "will", "have", "has", "here", "there", "its", "into", "than",
+    "then", "use", "when", "which", "you", "your",
+];
+
+/// Lowercase words of at least three letters, minus stop words
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|w| w.chars().count() >= 3 && !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// L2-normalized TF-IDF vectors for tokenized documents
+pub(crate) fn tfidf(docs: &[Vec<String>]) -> Vec<Vec<f64>> {
+    let mut vocab: HashMap<&str, usize> = HashMap::new();
+    let mut doc_freq: Vec<usize> = Vec::new();
+    for doc in docs {
+        let mut seen = Vec::new();
+        for term in doc {
+            let next = vocab.len();
+            let idx = *vocab.entry(term.as_str()).or_insert(next);
+            if idx == doc_freq.len() {
+                doc_freq.push(0);
+            }
+            if !seen.contains(&idx) {
+                seen.push(idx);
+                doc_freq[idx] += 1;
+            }
+        }
+    }
+
+    let n = docs.len() as f64;
+    docs.iter()
+        .map(|doc| {
+            let mut vector = vec![0.0; vocab.len()];
+            for term in doc {
+                vector[vocab[term.as_str()]] += 1.0 / doc.len() as f64;
+            }
+            for (idx, weight) in vector.iter_mut().enumerate() {
+                // Smoothed IDF, so terms in every document still count a little
+                *weight *= ((1.0 + n) / (1.0 + doc_freq[idx] as f64)).ln() + 1.0;
+            }
+            let norm = vector.iter().map(|w| w * w).sum::<f64>().sqrt();
+            if norm > 0.0 {
+                vector.iter_mut().for_each(|w| *w /= norm);
+            }
+            vector
+        })
+        .collect()
+}
+
+fn distance_sq(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Cluster `vectors` into `k` groups, returning each vector's cluster and
+/// the within-cluster sum of squares
+///
+/// Centroids start at mutually distant points (the first vector, then the
+/// farthest from those chosen so far), which keeps results deterministic.
+pub(crate) fn kmeans(vectors: &[Vec<f64>], k: usize) -> (Vec<usize>, f64) {
+    let k = k.clamp(1, vectors.len().max(1));
+    if vectors.is_empty() {
+        return (Vec::new(), 0.0);
+    }
+
+    let mut centroids = vec![vectors[0].clone()];
+    while centroids.len() < k {
+        let farthest = vectors
+            .iter()
+            .max_by(|a, b| {
+                let da = centroids.iter().map(|c| distance_sq(a, c)).fold(f64::MAX, f64::min);
+                let db = centroids.iter().map(|c| distance_sq(b, c)).fold(f64::MAX, f64::min);
+                da.total_cmp(&db)
+            })
+            .cloned()
+            .unwrap_or_default();
+        centroids.push(farthest);
+    }
+
+    let nearest = |v: &[f64], centroids: &[Vec<f64>]| {
+        (0..centroids.len())
+            .min_by(|&a, &b| distance_sq(v, &centroids[a]).total_cmp(&distance_sq(v, &centroids[b])))
+            .unwrap_or(0)
+    };
+
+    let mut assignment: Vec<usize> = vectors.iter().map(|v| nearest(v, &centroids)).collect();
+    for _ in 0..MAX_ITERATIONS {
+        for (c, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<&Vec<f64>> = vectors
+                .iter()
+                .zip(&assignment)
+                .filter(|(_, a)| **a == c)
+                .map(|(v, _)| v)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            for (i, value) in centroid.iter_mut().enumerate() {
+                *value = members.iter().map(|m| m[i]).sum::<f64>() / members.len() as f64;
+            }
+        }
+        let next: Vec<usize> = vectors.iter().map(|v| nearest(v, &centroids)).collect();
+        if next == assignment {
+            break;
+        }
+        assignment = next;
+    }
+
+    let wcss = vectors
+        .iter()
+        .zip(&assignment)
+        .map(|(v, &c)| distance_sq(v, &centroids[c]))
+        .sum();
+    (assignment, wcss)
+}
+
+/// Pick a cluster count with the elbow method
+///
+/// Tries 1..=10 clusters and returns the count where the drop in
+/// within-cluster sum of squares flattens out the most.
+pub(crate) fn elbow_cluster_count(vectors: &[Vec<f64>]) -> usize {
+    let max_k = vectors.len().min(MAX_AUTO_CLUSTERS);
+    if max_k < 3 {
+        return max_k.max(1);
+    }
+    let wcss: Vec<f64> = (1..=max_k).map(|k| kmeans(vectors, k).1).collect();
+    if wcss[0] <= f64::EPSILON {
+        return 1;
+    }
+    (1..max_k - 1)
+        .max_by(|&a, &b| {
+            let bend = |i: usize| wcss[i - 1] - 2.0 * wcss[i] + wcss[i + 1];
+            bend(a).total_cmp(&bend(b))
+        })
+        .map(|i| i + 1)
+        .unwrap_or(1)
+}
+
+/// The `n` terms occurring in the most documents, ties broken alphabetically
+pub(crate) fn top_terms(docs: &[Vec<String>], n: usize) -> Vec<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for doc in docs {
+        let mut seen: Vec<&str> = Vec::new();
+        for term in doc {
+            if !seen.contains(&term.as_str()) {
+                seen.push(term);
+                *counts.entry(term).or_insert(0) += 1;
+            }
+        }
+    }
+    let mut terms: Vec<(&str, usize)> = counts.into_iter().collect();
+    terms.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    terms.into_iter().take(n).map(|(t, _)| t.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kmeans_separates_topics() {
+        let docs: Vec<Vec<String>> = [
+            "sql injection in query",
+            "query allows sql injection",
+            "unclear variable name",
+            "rename unclear variable",
+        ]
+        .iter()
+        .map(|d| tokenize(d))
+        .collect();
+        let vectors = tfidf(&docs);
+
+        let (assignment, _) = kmeans(&vectors, 2);
+        assert_eq!(assignment[0], assignment[1]);
+        assert_eq!(assignment[2], assignment[3]);
+        assert_ne!(assignment[0], assignment[2]);
+        assert_eq!(kmeans(&vectors, 4).1, 0.0);
+        assert_eq!(tokenize("Use the HashMap here"), vec!["hashmap"]);
+    }
+}
@@ -1,8 +1,18 @@
 //! Comment indexing for fast lookup
 
-use super::model::{Comment, LineReference, Severity};
+use super::manager::CommentManager;
+use super::model::{Comment, CommentState, LineReference, Severity};
 use crate::types::{CommentId, FileId, LineId};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// Problems found while rebuilding a [`CommentIndex`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexValidationResult {
+    /// Whether no issues were found
+    pub is_valid: bool,
+    /// Human-readable descriptions of each issue
+    pub issues: Vec<String>,
+}
 
 /// Multi-dimensional index for comments
 #[derive(Debug, Clone, Default)]
@@ -13,6 +23,10 @@ pub struct CommentIndex {
     by_file: HashMap<FileId, Vec<CommentId>>,
     /// Index by severity
     by_severity: HashMap<Severity, Vec<CommentId>>,
+    /// Index by (state, severity)
+    state_severity: HashMap<(CommentState, Severity), Vec<CommentId>>,
+    /// Index by file, then display line number
+    by_file_line: HashMap<FileId, HashMap<usize, Vec<CommentId>>>,
 }
 
 impl CommentIndex {
@@ -42,6 +56,22 @@ impl CommentIndex {
             .entry(comment.severity)
             .or_default()
             .push(comment.id.clone());
+
+        // Index by state and severity
+        self.state_severity
+            .entry((comment.state, comment.severity))
+            .or_default()
+            .push(comment.id.clone());
+
+        // Index by file and line number
+        if let Some(line) = comment.metadata.line_number {
+            self.by_file_line
+                .entry(comment.file_id().clone())
+                .or_default()
+                .entry(line)
+                .or_default()
+                .push(comment.id.clone());
+        }
     }
 
     /// Remove a comment from the index
@@ -71,6 +101,36 @@ impl CommentIndex {
                 self.by_severity.remove(&comment.severity);
             }
         }
+
+        // Remove from state/severity index
+        let key = (comment.state, comment.severity);
+        if let Some(ids) = self.state_severity.get_mut(&key) {
+            ids.retain(|id| id != &comment.id);
+            if ids.is_empty() {
+                self.state_severity.remove(&key);
+            }
+        }
+
+        // Remove from file/line index
+        if let Some(line) = comment.metadata.line_number {
+            if let Some(lines) = self.by_file_line.get_mut(comment.file_id()) {
+                if let Some(ids) = lines.get_mut(&line) {
+                    ids.retain(|id| id != &comment.id);
+                    if ids.is_empty() {
+                        lines.remove(&line);
+                    }
+                }
+                if lines.is_empty() {
+                    self.by_file_line.remove(comment.file_id());
+                }
+            }
+        }
+    }
+
+    /// Re-index a comment after it changed
+    pub fn update_comment(&mut self, old: &Comment, new: &Comment) {
+        self.remove(old);
+        self.add(new);
     }
 
     /// Get comments by line ID
@@ -78,6 +138,11 @@ impl CommentIndex {
         self.by_line.get(line_id).cloned().unwrap_or_default()
     }
 
+    /// Number of comments on a line
+    pub fn count_by_line(&self, line_id: &LineId) -> usize {
+        self.by_line.get(line_id).map_or(0, Vec::len)
+    }
+
     /// Get comments by file ID
     pub fn get_by_file(&self, file_id: &FileId) -> Vec<CommentId> {
         self.by_file.get(file_id).cloned().unwrap_or_default()
@@ -88,6 +153,21 @@ impl CommentIndex {
         self.by_severity.get(&severity).cloned().unwrap_or_default()
     }
 
+    /// Get comments by state and severity
+    pub fn get_by_state_and_severity(&self, state: CommentState, severity: Severity) -> Vec<CommentId> {
+        self.state_severity
+            .get(&(state, severity))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Get a file's comments grouped by line number
+    ///
+    /// Comments without a line number are not included.
+    pub fn get_by_file_range(&self, file_id: &FileId) -> HashMap<usize, Vec<CommentId>> {
+        self.by_file_line.get(file_id).cloned().unwrap_or_default()
+    }
+
     /// Check if a line has any comments
     pub fn has_comments_on_line(&self, line_id: &LineId) -> bool {
         self.by_line
@@ -106,11 +186,43 @@ impl CommentIndex {
         self.by_file.keys().collect()
     }
 
+    /// Every indexed comment ID, once per index entry
+    fn indexed_ids(&self) -> impl Iterator<Item = &CommentId> {
+        self.by_line
+            .values()
+            .chain(self.by_file.values())
+            .chain(self.by_severity.values())
+            .chain(self.state_severity.values())
+            .chain(self.by_file_line.values().flat_map(|lines| lines.values()))
+            .flatten()
+    }
+
+    /// Drop `ids` from every index, without needing the comments themselves
+    pub fn purge(&mut self, ids: &HashSet<CommentId>) {
+        fn retain<K: Eq + std::hash::Hash>(map: &mut HashMap<K, Vec<CommentId>>, ids: &HashSet<CommentId>) {
+            map.retain(|_, entries| {
+                entries.retain(|id| !ids.contains(id));
+                !entries.is_empty()
+            });
+        }
+
+        retain(&mut self.by_line, ids);
+        retain(&mut self.by_file, ids);
+        retain(&mut self.by_severity, ids);
+        retain(&mut self.state_severity, ids);
+        self.by_file_line.retain(|_, lines| {
+            retain(lines, ids);
+            !lines.is_empty()
+        });
+    }
+
     /// Clear the entire index
     pub fn clear(&mut self) {
         self.by_line.clear();
         self.by_file.clear();
         self.by_severity.clear();
+        self.state_severity.clear();
+        self.by_file_line.clear();
     }
 
     /// Rebuild index from a collection of comments
@@ -120,6 +232,102 @@ impl CommentIndex {
             self.add(comment.borrow());
         }
     }
+
+    /// Rebuild the index from `comments` and report how the previous index
+    /// differed from the rebuilt one
+    ///
+    /// Reports entries the previous index lacked or had extra (e.g. a
+    /// comment removed without updating the index), and comments stored
+    /// under a map key other than their own ID.
+    pub fn rebuild_from_scratch(
+        &mut self,
+        comments: &HashMap<CommentId, Comment>,
+    ) -> IndexValidationResult {
+        let previous = std::mem::take(self);
+        self.rebuild(comments.values());
+
+        let mut issues = key_mismatches(comments);
+        let before = previous.entries();
+        let after = self.entries();
+        for (entry, count) in &after {
+            let had = before.get(entry).copied().unwrap_or(0);
+            if had < *count {
+                issues.push(format!("Comment {} was missing from the index under {}", entry.1, entry.0));
+            }
+        }
+        for (entry, count) in &before {
+            let has = after.get(entry).copied().unwrap_or(0);
+            if has < *count {
+                issues.push(format!("Comment {} had a stale index entry under {}", entry.1, entry.0));
+            }
+        }
+        issues.sort();
+
+        IndexValidationResult {
+            is_valid: issues.is_empty(),
+            issues,
+        }
+    }
+
+    /// Every `(key, comment)` pair of every index, with how often it occurs
+    fn entries(&self) -> HashMap<(String, CommentId), usize> {
+        let mut entries = HashMap::new();
+        let mut count = |key: String, ids: &[CommentId]| {
+            for id in ids {
+                *entries.entry((key.clone(), id.clone())).or_insert(0) += 1;
+            }
+        };
+        for (line_id, ids) in &self.by_line {
+            count(format!("line {}", line_id), ids);
+        }
+        for (file_id, ids) in &self.by_file {
+            count(format!("file {}", file_id), ids);
+        }
+        for (severity, ids) in &self.by_severity {
+            count(format!("severity {:?}", severity), ids);
+        }
+        for ((state, severity), ids) in &self.state_severity {
+            count(format!("state {:?}/{:?}", state, severity), ids);
+        }
+        for (file_id, lines) in &self.by_file_line {
+            for (line, ids) in lines {
+                count(format!("file {} line {}", file_id, line), ids);
+            }
+        }
+        entries
+    }
+}
+
+/// Comments stored under a map key other than their own ID
+pub(super) fn key_mismatches(comments: &HashMap<CommentId, Comment>) -> Vec<String> {
+    comments
+        .iter()
+        .filter(|(key, comment)| *key != &comment.id)
+        .map(|(key, comment)| format!("Comment {} is stored under ID {}", comment.id, key))
+        .collect()
+}
+
+/// Finds index entries for comments that no longer exist
+///
+/// These "phantom" IDs are left behind if a comment is dropped from the
+/// manager without being removed from the index.
+pub struct PhantomCommentDetector;
+
+impl PhantomCommentDetector {
+    /// Indexed comment IDs missing from `manager`, sorted and without
+    /// duplicates
+    pub fn scan(manager: &CommentManager) -> Vec<CommentId> {
+        let mut phantoms: Vec<CommentId> = manager
+            .index()
+            .indexed_ids()
+            .filter(|id| manager.get(id).is_none())
+            .cloned()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        phantoms.sort_by_key(|id| id.to_string());
+        phantoms
+    }
 }
 
 #[cfg(test)]
@@ -147,6 +355,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rebuild_from_scratch() {
+        let mut index = CommentIndex::new();
+        let comment = create_test_comment("file1", "line1", Severity::Warning);
+        let mut comments = HashMap::new();
+        comments.insert(comment.id.clone(), comment.clone());
+
+        // Built from nothing, every entry was missing
+        let result = index.rebuild_from_scratch(&comments);
+        assert!(!result.is_valid);
+        assert!(result.issues.iter().all(|i| i.contains("was missing")));
+        assert_eq!(index.get_by_file(&FileId::from_string("file1")).len(), 1);
+
+        // An index that matches the comments is valid
+        let result = index.rebuild_from_scratch(&comments);
+        assert!(result.is_valid, "{:?}", result.issues);
+
+        // A comment dropped without updating the index leaves stale entries
+        let stale = create_test_comment("file2", "line9", Severity::Critical);
+        index.add(&stale);
+        let result = index.rebuild_from_scratch(&comments);
+        assert_eq!(result.issues.len(), 4);
+        assert!(result.issues.iter().all(|i| i.contains(&format!("{} had a stale index entry", stale.id))));
+        assert!(index.get_by_file(&FileId::from_string("file2")).is_empty());
+
+        // A comment added without indexing it is missing from the index
+        let mut unindexed = create_test_comment("file1", "line2", Severity::Info);
+        unindexed.line_ref = LineReference::Range {
+            file_id: FileId::from_string("file1"),
+            start_line_id: LineId::from_string("line2"),
+            end_line_id: LineId::from_string("line2"),
+            side: DiffSide::New,
+        };
+        comments.insert(CommentId::new(), unindexed);
+        let result = index.rebuild_from_scratch(&comments);
+        assert!(result.issues.iter().any(|i| i.contains("was missing from the index under line line2")));
+        assert!(result.issues.iter().any(|i| i.contains("is stored under ID")));
+        assert!(result.issues.iter().all(|i| !i.contains("stale")));
+    }
+
+    #[test]
+    fn test_phantom_comment_detector() {
+        let mut manager = CommentManager::new();
+        let kept = create_test_comment("file1", "line1", Severity::Warning);
+        let phantom = create_test_comment("file1", "line1", Severity::Critical);
+        manager.add(kept.clone()).unwrap();
+        manager.add(phantom.clone()).unwrap();
+        assert!(PhantomCommentDetector::scan(&manager).is_empty());
+
+        manager.forget_unindexed(&phantom.id);
+        assert_eq!(PhantomCommentDetector::scan(&manager), vec![phantom.id.clone()]);
+
+        assert_eq!(manager.heal(), 1);
+        assert!(PhantomCommentDetector::scan(&manager).is_empty());
+        assert_eq!(manager.get_by_line(&LineId::from_string("line1")).len(), 1);
+        assert!(manager.get_by_severity(Severity::Critical).is_empty());
+        assert_eq!(manager.heal(), 0);
+    }
+
     #[test]
     fn test_add_and_get_by_line() {
         let mut index = CommentIndex::new();
@@ -160,6 +427,28 @@ mod tests {
         assert_eq!(results[0], comment.id);
     }
 
+    #[test]
+    fn test_get_by_file_range() {
+        let mut index = CommentIndex::new();
+        let mut comment1 = create_test_comment("file1", "line1", Severity::Warning);
+        comment1.metadata.line_number = Some(10);
+        let mut comment2 = create_test_comment("file1", "line2", Severity::Info);
+        comment2.metadata.line_number = Some(10);
+        let unnumbered = create_test_comment("file1", "line3", Severity::Info);
+        index.add(&comment1);
+        index.add(&comment2);
+        index.add(&unnumbered);
+
+        let file_id = FileId::from_string("file1");
+        let lines = index.get_by_file_range(&file_id);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[&10], vec![comment1.id.clone(), comment2.id.clone()]);
+
+        index.remove(&comment1);
+        index.remove(&comment2);
+        assert!(index.get_by_file_range(&file_id).is_empty());
+    }
+
     #[test]
     fn test_add_and_get_by_file() {
         let mut index = CommentIndex::new();
@@ -241,5 +530,34 @@ mod tests {
         assert!(index.by_line.is_empty());
         assert!(index.by_file.is_empty());
         assert!(index.by_severity.is_empty());
+        assert!(index.state_severity.is_empty());
+    }
+
+    #[test]
+    fn test_state_severity_index() {
+        let mut index = CommentIndex::new();
+        let comment = create_test_comment("file1", "line1", Severity::Critical);
+        index.add(&comment);
+
+        assert_eq!(
+            index.get_by_state_and_severity(CommentState::Open, Severity::Critical),
+            vec![comment.id.clone()]
+        );
+        assert!(index
+            .get_by_state_and_severity(CommentState::Resolved, Severity::Critical)
+            .is_empty());
+
+        let mut resolved = comment.clone();
+        resolved.state = CommentState::Resolved;
+        index.update_comment(&comment, &resolved);
+
+        assert!(index
+            .get_by_state_and_severity(CommentState::Open, Severity::Critical)
+            .is_empty());
+        assert_eq!(
+            index.get_by_state_and_severity(CommentState::Resolved, Severity::Critical),
+            vec![comment.id.clone()]
+        );
+        assert_eq!(index.get_by_severity(Severity::Critical).len(), 1);
     }
 }
@@ -1,17 +1,27 @@
 //! Comment manager for CRUD operations
 
-use super::index::CommentIndex;
-use super::model::{Comment, CommentState, Severity};
+use super::builder::CommentBuilder;
+use super::cluster;
+use super::index::{CommentIndex, PhantomCommentDetector};
+use super::model::{Comment, CommentState, DiffSide, LineReference, Severity};
+use crate::diff::{DiffData, Line};
 use crate::error::{CrHelperError, Result};
 use crate::types::{CommentId, FileId, LineId};
+use globset::Glob;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
 
 /// Manager for comments with indexing support
 #[derive(Debug, Clone, Serialize)]
 pub struct CommentManager {
     /// All comments by ID
     comments: HashMap<CommentId, Comment>,
+    /// Manual ordering set with [`CommentManager::reorder`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    order: Vec<CommentId>,
     /// Multi-dimensional index
     #[serde(skip)]
     index: CommentIndex,
@@ -22,6 +32,7 @@ impl CommentManager {
     pub fn new() -> Self {
         Self {
             comments: HashMap::new(),
+            order: Vec::new(),
             index: CommentIndex::new(),
         }
     }
@@ -39,6 +50,9 @@ impl CommentManager {
 
         self.index.add(&comment);
         self.comments.insert(id.clone(), comment);
+        if !self.order.is_empty() {
+            self.order.push(id.clone());
+        }
         Ok(id)
     }
 
@@ -48,6 +62,9 @@ impl CommentManager {
     }
 
     /// Get a mutable comment by ID
+    ///
+    /// Changes to severity or state made through this reference are not
+    /// re-indexed; use [`CommentManager::update_comment`] for those.
     pub fn get_mut(&mut self, id: &CommentId) -> Option<&mut Comment> {
         self.comments.get_mut(id)
     }
@@ -64,14 +81,115 @@ impl CommentManager {
 
     /// Update comment state
     pub fn update_state(&mut self, id: &CommentId, state: CommentState) -> Result<()> {
+        self.update_comment(id, |comment| comment.set_state(state))
+    }
+
+    /// Modify a comment and keep the index in sync
+    pub fn update_comment(&mut self, id: &CommentId, f: impl FnOnce(&mut Comment)) -> Result<()> {
         let comment = self.comments.get_mut(id).ok_or_else(|| {
             CrHelperError::CommentNotFound(id.to_string())
         })?;
 
-        comment.set_state(state);
+        let old = comment.clone();
+        f(comment);
+        self.index.update_comment(&old, comment);
         Ok(())
     }
 
+    /// Add a tag to each of the given comments
+    ///
+    /// Returns the number of comments that were actually modified; comments
+    /// that already carry the tag are left untouched. Tags are not part of the
+    /// index, so no re-indexing is needed.
+    pub fn bulk_tag(&mut self, ids: &[CommentId], tag: &str) -> Result<usize> {
+        if tag.trim().is_empty() {
+            return Err(CrHelperError::Validation("Tags cannot be empty".to_string()));
+        }
+
+        if let Some(missing) = ids.iter().find(|id| !self.comments.contains_key(id)) {
+            return Err(CrHelperError::CommentNotFound(missing.to_string()));
+        }
+
+        let mut modified = 0;
+        for id in ids {
+            if let Some(comment) = self.comments.get_mut(id) {
+                if !comment.tags.iter().any(|t| t == tag) {
+                    comment.add_tag(tag);
+                    modified += 1;
+                }
+            }
+        }
+
+        Ok(modified)
+    }
+
+    /// Add a tag to every comment matching the filter
+    ///
+    /// Returns the number of comments that were actually modified; fails
+    /// like [`bulk_tag`](Self::bulk_tag) on an empty tag.
+    pub fn bulk_tag_filtered(&mut self, filter: impl Fn(&Comment) -> bool, tag: &str) -> Result<usize> {
+        let ids: Vec<CommentId> = self
+            .comments
+            .values()
+            .filter(|c| filter(c))
+            .map(|c| c.id.clone())
+            .collect();
+
+        self.bulk_tag(&ids, tag)
+    }
+
+    /// Resolve every comment whose file path matches a glob pattern
+    ///
+    /// With `line_range`, only comments whose line number falls inside it
+    /// match. Returns the number of comments that were not resolved before.
+    pub fn resolve_by_pattern(
+        &mut self,
+        file_pattern: &str,
+        line_range: Option<RangeInclusive<usize>>,
+    ) -> Result<usize> {
+        self.set_state_by_pattern(file_pattern, line_range, CommentState::Resolved)
+    }
+
+    /// Reopen every comment whose file path matches a glob pattern
+    ///
+    /// The counterpart of [`CommentManager::resolve_by_pattern`].
+    pub fn reopen_by_pattern(
+        &mut self,
+        file_pattern: &str,
+        line_range: Option<RangeInclusive<usize>>,
+    ) -> Result<usize> {
+        self.set_state_by_pattern(file_pattern, line_range, CommentState::Open)
+    }
+
+    fn set_state_by_pattern(
+        &mut self,
+        file_pattern: &str,
+        line_range: Option<RangeInclusive<usize>>,
+        state: CommentState,
+    ) -> Result<usize> {
+        let matcher = Glob::new(file_pattern)
+            .map_err(|e| CrHelperError::Validation(format!("Invalid file pattern '{}': {}", file_pattern, e)))?
+            .compile_matcher();
+
+        let ids: Vec<CommentId> = self
+            .comments
+            .values()
+            .filter(|c| c.state != state)
+            .filter(|c| c.metadata.file_path.as_ref().is_some_and(|p| matcher.is_match(p)))
+            .filter(|c| match (&line_range, c.metadata.line_number) {
+                (None, _) => true,
+                (Some(range), Some(line)) => range.contains(&line),
+                (Some(_), None) => false,
+            })
+            .map(|c| c.id.clone())
+            .collect();
+
+        for id in &ids {
+            self.update_state(id, state)?;
+        }
+        Ok(ids.len())
+    }
+
     /// Delete a comment
     pub fn delete(&mut self, id: &CommentId) -> Result<Comment> {
         let comment = self.comments.remove(id).ok_or_else(|| {
@@ -79,9 +197,33 @@ impl CommentManager {
         })?;
 
         self.index.remove(&comment);
+        self.order.retain(|o| o != id);
         Ok(comment)
     }
 
+    /// Remove index entries for comments that no longer exist
+    ///
+    /// Returns how many phantom comment IDs were found (see
+    /// [`PhantomCommentDetector`]).
+    pub fn heal(&mut self) -> usize {
+        let phantoms: HashSet<CommentId> = PhantomCommentDetector::scan(self).into_iter().collect();
+        if !phantoms.is_empty() {
+            self.index.purge(&phantoms);
+        }
+        phantoms.len()
+    }
+
+    /// The index, for [`PhantomCommentDetector`]
+    pub(super) fn index(&self) -> &CommentIndex {
+        &self.index
+    }
+
+    /// Drop a comment but leave it in the index, as a buggy delete would
+    #[cfg(test)]
+    pub(super) fn forget_unindexed(&mut self, id: &CommentId) {
+        self.comments.remove(id);
+    }
+
     /// Delete all comments for a file
     pub fn delete_by_file(&mut self, file_id: &FileId) -> usize {
         let ids: Vec<CommentId> = self.index.get_by_file(file_id);
@@ -91,6 +233,7 @@ impl CommentManager {
             if let Some(comment) = self.comments.remove(&id) {
                 self.index.remove(&comment);
             }
+            self.order.retain(|o| o != &id);
         }
 
         count
@@ -101,13 +244,45 @@ impl CommentManager {
         self.comments.values().collect()
     }
 
-    /// Get all comments sorted by creation time
+    /// Get all comments in manual order, falling back to creation time
     pub fn all_sorted(&self) -> Vec<&Comment> {
+        let ranks: HashMap<&CommentId, usize> =
+            self.order.iter().enumerate().map(|(i, id)| (id, i)).collect();
         let mut comments: Vec<_> = self.comments.values().collect();
-        comments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        comments.sort_by(|a, b| match (ranks.get(&a.id), ranks.get(&b.id)) {
+            (Some(x), Some(y)) => x.cmp(y),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => a.created_at.cmp(&b.created_at),
+        });
         comments
     }
 
+    /// Move a comment before another one, or to the end if `before` is `None`
+    ///
+    /// The first call fixes the current order of all comments; comments added
+    /// afterwards go to the end.
+    pub fn reorder(&mut self, id: &CommentId, before: Option<&CommentId>) -> Result<()> {
+        for id in std::iter::once(id).chain(before) {
+            if !self.comments.contains_key(id) {
+                return Err(CrHelperError::CommentNotFound(id.to_string()));
+            }
+        }
+        if before == Some(id) {
+            return Ok(());
+        }
+
+        if self.order.is_empty() {
+            self.order = self.all_sorted().iter().map(|c| c.id.clone()).collect();
+        }
+        self.order.retain(|o| o != id);
+        let idx = before
+            .and_then(|b| self.order.iter().position(|o| o == b))
+            .unwrap_or(self.order.len());
+        self.order.insert(idx, id.clone());
+        Ok(())
+    }
+
     /// Get comments by line ID
     pub fn get_by_line(&self, line_id: &LineId) -> Vec<&Comment> {
         self.index
@@ -117,6 +292,11 @@ impl CommentManager {
             .collect()
     }
 
+    /// Number of comments on a line, without collecting them
+    pub fn count_by_line(&self, line_id: &LineId) -> usize {
+        self.index.count_by_line(line_id)
+    }
+
     /// Get comments by file ID
     pub fn get_by_file(&self, file_id: &FileId) -> Vec<&Comment> {
         self.index
@@ -126,6 +306,30 @@ impl CommentManager {
             .collect()
     }
 
+    /// Get a file's comments with a line number in `start_line..=end_line_inclusive`
+    ///
+    /// Results are ordered by line number.
+    pub fn get_by_line_range(
+        &self,
+        file_id: &FileId,
+        start_line: usize,
+        end_line_inclusive: usize,
+    ) -> Vec<&Comment> {
+        let mut lines: Vec<_> = self
+            .index
+            .get_by_file_range(file_id)
+            .into_iter()
+            .filter(|(line, _)| (start_line..=end_line_inclusive).contains(line))
+            .collect();
+        lines.sort_by_key(|(line, _)| *line);
+
+        lines
+            .into_iter()
+            .flat_map(|(_, ids)| ids)
+            .filter_map(|id| self.comments.get(&id))
+            .collect()
+    }
+
     /// Get comments by severity
     pub fn get_by_severity(&self, severity: Severity) -> Vec<&Comment> {
         self.index
@@ -135,6 +339,32 @@ impl CommentManager {
             .collect()
     }
 
+    /// Get comments by state and severity
+    pub fn get_by_state_and_severity(&self, state: CommentState, severity: Severity) -> Vec<&Comment> {
+        self.index
+            .get_by_state_and_severity(state, severity)
+            .iter()
+            .filter_map(|id| self.comments.get(id))
+            .collect()
+    }
+
+    /// Get open critical comments
+    pub fn get_open_critical(&self) -> Vec<&Comment> {
+        self.get_by_state_and_severity(CommentState::Open, Severity::Critical)
+    }
+
+    /// Get comments that are not resolved or dismissed
+    pub fn get_unresolved(&self) -> Vec<&Comment> {
+        [CommentState::Open, CommentState::Acknowledged, CommentState::Outdated]
+            .into_iter()
+            .flat_map(|state| {
+                [Severity::Critical, Severity::Warning, Severity::Info]
+                    .into_iter()
+                    .flat_map(move |severity| self.get_by_state_and_severity(state, severity))
+            })
+            .collect()
+    }
+
     /// Search comments by content
     pub fn search(&self, query: &str) -> Vec<&Comment> {
         let query_lower = query.to_lowercase();
@@ -147,6 +377,72 @@ impl CommentManager {
             .collect()
     }
 
+    /// Comments whose content is similar to `query`, most similar first
+    ///
+    /// Similarity is the case-insensitive normalized Levenshtein similarity
+    /// (1.0 for identical text). Only comments scoring at least `threshold`
+    /// are returned.
+    pub fn fuzzy_search_content(&self, query: &str, threshold: f64) -> Vec<(&Comment, f64)> {
+        let query = query.to_lowercase();
+        let mut matches: Vec<(&Comment, f64)> = self
+            .comments
+            .values()
+            .map(|c| (c, strsim::normalized_levenshtein(&query, &c.content.to_lowercase())))
+            .filter(|(_, score)| *score >= threshold)
+            .collect();
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+        matches
+    }
+
+    /// Comments whose content matches a regular expression
+    pub fn search_by_regex(&self, pattern: &str) -> Result<Vec<&Comment>> {
+        let regex = regex::Regex::new(pattern)
+            .map_err(|e| CrHelperError::Validation(format!("Invalid regex: {}", e)))?;
+        Ok(self
+            .comments
+            .values()
+            .filter(|c| regex.is_match(&c.content))
+            .collect())
+    }
+
+    /// Group comments by topic, most populous cluster first
+    ///
+    /// Comments become TF-IDF vectors over their content and tags, which are
+    /// then clustered with k-means. With `n_clusters == 0` the count is picked
+    /// by the elbow method.
+    pub fn cluster_by_topic(&self, n_clusters: usize) -> Vec<Vec<CommentId>> {
+        let comments = self.all_sorted();
+        if comments.is_empty() {
+            return Vec::new();
+        }
+        let docs: Vec<Vec<String>> = comments.iter().map(|c| topic_terms(c)).collect();
+        let vectors = cluster::tfidf(&docs);
+        let k = if n_clusters == 0 {
+            cluster::elbow_cluster_count(&vectors)
+        } else {
+            n_clusters
+        };
+
+        let (assignment, _) = cluster::kmeans(&vectors, k);
+        let mut clusters: Vec<Vec<CommentId>> = vec![Vec::new(); k.min(comments.len())];
+        for (comment, c) in comments.iter().zip(assignment) {
+            clusters[c].push(comment.id.clone());
+        }
+        clusters.retain(|c| !c.is_empty());
+        clusters.sort_by_key(|c| std::cmp::Reverse(c.len()));
+        clusters
+    }
+
+    /// Suggested label for a group of comments: its `max_terms` most frequent terms
+    pub fn topic_label(&self, ids: &[CommentId], max_terms: usize) -> Vec<String> {
+        let docs: Vec<Vec<String>> = ids
+            .iter()
+            .filter_map(|id| self.get(id))
+            .map(topic_terms)
+            .collect();
+        cluster::top_terms(&docs, max_terms)
+    }
+
     /// Get total comment count
     pub fn count(&self) -> usize {
         self.comments.len()
@@ -174,6 +470,96 @@ impl CommentManager {
             .collect()
     }
 
+    /// Get active comments whose lines are no longer in `diff`
+    pub fn get_outdated_by_diff(&self, diff: &DiffData) -> Vec<&Comment> {
+        self.get_active()
+            .into_iter()
+            .filter(|c| c.line_ids().iter().any(|id| !id.is_stable_across_rebase(diff)))
+            .collect()
+    }
+
+    /// Render comments as `file:line:col: severity: content`, like compiler errors
+    ///
+    /// Comments on a line still in `diff` are followed by that line and a
+    /// `^` under its first non-blank character; others omit the column.
+    /// Sorted by file and line, so editors can step through them.
+    pub fn to_inline_annotations(&self, diff: &DiffData) -> String {
+        let mut entries: Vec<(String, usize, String)> = self
+            .comments
+            .values()
+            .map(|comment| {
+                let file = diff.get_file(comment.file_id());
+                let path = file
+                    .map(|f| f.display_path().display().to_string())
+                    .or_else(|| comment.metadata.file_path.clone())
+                    .unwrap_or_else(|| comment.file_id().to_string());
+                let line = file.and_then(|f| {
+                    let id = comment.line_ids()[0];
+                    f.hunks.iter().flat_map(|h| &h.lines).find(|l| &l.id == id)
+                });
+                let line_number = line
+                    .and_then(|l| annotated_line_number(comment, l))
+                    .or(comment.metadata.line_number);
+                let message = format!(
+                    "{}: {}",
+                    comment.severity.to_string().to_lowercase(),
+                    comment.content.split_whitespace().collect::<Vec<_>>().join(" ")
+                );
+
+                let text = match (line_number, line) {
+                    (Some(n), Some(line)) => {
+                        let indent_len = line.content.len() - line.content.trim_start().len();
+                        let indent = &line.content[..indent_len];
+                        format!(
+                            "{}:{}:{}: {}\n    {}\n    {}^\n",
+                            path,
+                            n,
+                            indent.chars().count() + 1,
+                            message,
+                            line.content,
+                            indent
+                        )
+                    }
+                    (Some(n), None) => format!("{}:{}: {}\n", path, n, message),
+                    (None, _) => format!("{}: {}\n", path, message),
+                };
+                (path, line_number.unwrap_or(0), text)
+            })
+            .collect();
+
+        entries.sort();
+        entries.into_iter().map(|(_, _, text)| text).collect()
+    }
+
+    /// Comments from a Checkstyle XML report
+    ///
+    /// Each `<error>` becomes a comment with its `message` as content, source
+    /// `checkstyle` and tag `style`. Severity `error` is critical, `warning`
+    /// a warning and anything else info.
+    pub fn import_from_checkstyle(xml: &str) -> Result<Vec<Comment>> {
+        report_comments(parse_checkstyle(xml)?, "checkstyle", "style")
+    }
+
+    /// Comments from a PMD XML report
+    ///
+    /// Each `<violation>` becomes a comment at its `beginline` with its text
+    /// (or `rule` if empty) as content, source `pmd` and tag `quality`.
+    /// Priorities 1-2 are critical, 3 a warning and 4-5 info.
+    pub fn import_from_pmd(xml: &str) -> Result<Vec<Comment>> {
+        report_comments(parse_pmd(xml)?, "pmd", "quality")
+    }
+
+    /// Comments from a SpotBugs XML report
+    ///
+    /// Each `<BugInstance>` becomes a comment at its primary `<SourceLine>`
+    /// with its `LongMessage`, `ShortMessage` or `type` as content, source
+    /// `spotbugs` and tag `bug`. Priority 1 is critical, 2 a warning and 3
+    /// info. Source paths are resolved against the project's `<SrcDir>`s
+    /// when the file exists there; bugs with no source line are skipped.
+    pub fn import_from_spotbugs(xml: &str) -> Result<Vec<Comment>> {
+        report_comments(parse_spotbugs(xml)?, "spotbugs", "bug")
+    }
+
     /// Rebuild index (after deserialization)
     pub fn rebuild_index(&mut self) {
         self.index = CommentIndex::new();
@@ -183,6 +569,282 @@ impl CommentManager {
     }
 }
 
+/// Terms describing a comment's topic, from its content and tags
+fn topic_terms(comment: &Comment) -> Vec<String> {
+    let mut terms = cluster::tokenize(&comment.content);
+    for tag in &comment.tags {
+        terms.extend(cluster::tokenize(tag));
+    }
+    terms
+}
+
+/// Line number of `line` on the side of the diff `comment` is attached to
+fn annotated_line_number(comment: &Comment, line: &Line) -> Option<usize> {
+    let side = match &comment.line_ref {
+        LineReference::SingleLine { side, .. } | LineReference::Range { side, .. } => *side,
+    };
+    match side {
+        DiffSide::Old => line.old_line_num.or(line.new_line_num),
+        DiffSide::New => line.display_line_num(),
+    }
+}
+
+/// A finding read from a static analysis report
+struct ReportFinding {
+    path: String,
+    line: usize,
+    severity: Severity,
+    message: String,
+}
+
+/// Build comments for report findings
+fn report_comments(findings: Vec<ReportFinding>, source: &str, tag: &str) -> Result<Vec<Comment>> {
+    findings
+        .into_iter()
+        .map(|finding| {
+            CommentBuilder::at_source_line(&finding.path, finding.line)
+                .content(finding.message)
+                .severity(finding.severity)
+                .source(source)
+                .tag(tag)
+                .build()
+        })
+        .collect()
+}
+
+fn xml_error(report: &str, error: impl std::fmt::Display) -> CrHelperError {
+    CrHelperError::Validation(format!("Invalid {} report: {}", report, error))
+}
+
+/// Unescaped value of the attribute `name`
+fn xml_attr(report: &str, element: &BytesStart, name: &str) -> Result<Option<String>> {
+    let Some(attr) = element.try_get_attribute(name).map_err(|e| xml_error(report, e))? else {
+        return Ok(None);
+    };
+    let value = attr.unescape_value().map_err(|e| xml_error(report, e))?;
+    Ok(Some(value.into_owned()))
+}
+
+/// 1-based line number in the attribute `name`; missing or 0 means line 1
+fn xml_line(report: &str, element: &BytesStart, name: &str) -> Result<usize> {
+    match xml_attr(report, element, name)? {
+        Some(value) => value
+            .trim()
+            .parse::<usize>()
+            .map(|line| line.max(1))
+            .map_err(|_| xml_error(report, format!("'{}' is not a line number", value))),
+        None => Ok(1),
+    }
+}
+
+fn parse_checkstyle(xml: &str) -> Result<Vec<ReportFinding>> {
+    const REPORT: &str = "Checkstyle";
+    let mut reader = Reader::from_str(xml);
+    let mut file: Option<String> = None;
+    let mut findings = Vec::new();
+
+    loop {
+        match reader.read_event().map_err(|e| xml_error(REPORT, e))? {
+            Event::Start(e) | Event::Empty(e) => match e.name().as_ref() {
+                b"file" => file = xml_attr(REPORT, &e, "name")?,
+                b"error" => {
+                    let path = file
+                        .clone()
+                        .ok_or_else(|| xml_error(REPORT, "<error> outside a named <file>"))?;
+                    let severity = match xml_attr(REPORT, &e, "severity")?.as_deref() {
+                        Some("error") => Severity::Critical,
+                        Some("warning") => Severity::Warning,
+                        _ => Severity::Info,
+                    };
+                    findings.push(ReportFinding {
+                        path,
+                        line: xml_line(REPORT, &e, "line")?,
+                        severity,
+                        message: xml_attr(REPORT, &e, "message")?.unwrap_or_default(),
+                    });
+                }
+                _ => {}
+            },
+            Event::End(e) if e.name().as_ref() == b"file" => file = None,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(findings)
+}
+
+fn parse_pmd(xml: &str) -> Result<Vec<ReportFinding>> {
+    const REPORT: &str = "PMD";
+    let mut reader = Reader::from_str(xml);
+    let mut file: Option<String> = None;
+    // Violation being read and its rule name, completed by its text
+    let mut violation: Option<(ReportFinding, String)> = None;
+    let mut findings = Vec::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| xml_error(REPORT, e))?;
+        let empty = matches!(event, Event::Empty(_));
+        match event {
+            Event::Start(e) | Event::Empty(e) => match e.name().as_ref() {
+                b"file" => file = xml_attr(REPORT, &e, "name")?,
+                b"violation" => {
+                    let path = file
+                        .clone()
+                        .ok_or_else(|| xml_error(REPORT, "<violation> outside a named <file>"))?;
+                    let severity = match xml_attr(REPORT, &e, "priority")?.as_deref() {
+                        Some("1" | "2") => Severity::Critical,
+                        Some("3") => Severity::Warning,
+                        _ => Severity::Info,
+                    };
+                    let finding = ReportFinding {
+                        path,
+                        line: xml_line(REPORT, &e, "beginline")?,
+                        severity,
+                        message: String::new(),
+                    };
+                    let rule = xml_attr(REPORT, &e, "rule")?.unwrap_or_default();
+                    if empty {
+                        findings.push(ReportFinding { message: rule, ..finding });
+                    } else {
+                        violation = Some((finding, rule));
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(text) => {
+                if let Some((finding, _)) = violation.as_mut() {
+                    finding
+                        .message
+                        .push_str(&text.unescape().map_err(|e| xml_error(REPORT, e))?);
+                }
+            }
+            Event::End(e) => match e.name().as_ref() {
+                b"file" => file = None,
+                b"violation" => {
+                    if let Some((mut finding, rule)) = violation.take() {
+                        finding.message = finding.message.trim().to_string();
+                        if finding.message.is_empty() {
+                            finding.message = rule;
+                        }
+                        findings.push(finding);
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(findings)
+}
+
+/// A `<BugInstance>` being read from a SpotBugs report
+#[derive(Default)]
+struct SpotBug {
+    severity: Severity,
+    bug_type: String,
+    short_message: String,
+    long_message: String,
+    /// Source path and line of the bug's own `<SourceLine>`
+    location: Option<(String, usize)>,
+    /// Location of the enclosing class or method, if the bug has none
+    fallback: Option<(String, usize)>,
+}
+
+fn parse_spotbugs(xml: &str) -> Result<Vec<ReportFinding>> {
+    const REPORT: &str = "SpotBugs";
+    let mut reader = Reader::from_str(xml);
+    // Names of the open elements
+    let mut open: Vec<Vec<u8>> = Vec::new();
+    let mut src_dirs: Vec<String> = Vec::new();
+    let mut bug: Option<SpotBug> = None;
+    let mut findings = Vec::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| xml_error(REPORT, e))?;
+        let parent = open.last().map(Vec::as_slice);
+        match &event {
+            Event::Start(e) | Event::Empty(e) => match e.name().as_ref() {
+                b"BugInstance" => {
+                    let severity = match xml_attr(REPORT, e, "priority")?.as_deref() {
+                        Some("1") => Severity::Critical,
+                        Some("2") => Severity::Warning,
+                        _ => Severity::Info,
+                    };
+                    bug = Some(SpotBug {
+                        severity,
+                        bug_type: xml_attr(REPORT, e, "type")?.unwrap_or_default(),
+                        ..Default::default()
+                    });
+                }
+                b"SourceLine" => {
+                    if let (Some(bug), Some(path)) =
+                        (bug.as_mut(), xml_attr(REPORT, e, "sourcepath")?)
+                    {
+                        let location = Some((path, xml_line(REPORT, e, "start")?));
+                        let primary = xml_attr(REPORT, e, "primary")?.as_deref() == Some("true");
+                        if parent == Some(b"BugInstance") {
+                            if bug.location.is_none() || primary {
+                                bug.location = location;
+                            }
+                        } else if bug.fallback.is_none() {
+                            bug.fallback = location;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|e| xml_error(REPORT, e))?;
+                let grandparent = open.len().checked_sub(2).map(|i| open[i].as_slice());
+                match (grandparent, parent, bug.as_mut()) {
+                    (Some(b"Project"), Some(b"SrcDir"), _) => src_dirs.push(text.trim().to_string()),
+                    (Some(b"BugInstance"), Some(b"ShortMessage"), Some(bug)) => {
+                        bug.short_message.push_str(&text)
+                    }
+                    (Some(b"BugInstance"), Some(b"LongMessage"), Some(bug)) => {
+                        bug.long_message.push_str(&text)
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(e) if e.name().as_ref() == b"BugInstance" => {
+                if let Some(bug) = bug.take() {
+                    if let Some((path, line)) = bug.location.or(bug.fallback) {
+                        let message = [bug.long_message, bug.short_message, bug.bug_type]
+                            .into_iter()
+                            .map(|m| m.trim().to_string())
+                            .find(|m| !m.is_empty())
+                            .unwrap_or_default();
+                        let path = src_dirs
+                            .iter()
+                            .map(|dir| std::path::Path::new(dir).join(&path))
+                            .find(|p| p.exists())
+                            .map(|p| p.to_string_lossy().to_string())
+                            .unwrap_or(path);
+                        findings.push(ReportFinding {
+                            path,
+                            line,
+                            severity: bug.severity,
+                            message,
+                        });
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        match event {
+            Event::Start(e) => open.push(e.name().as_ref().to_vec()),
+            Event::End(_) => {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+    Ok(findings)
+}
+
 impl Default for CommentManager {
     fn default() -> Self {
         Self::new()
@@ -198,15 +860,21 @@ impl<'de> serde::de::Deserialize<'de> for CommentManager {
         #[derive(Deserialize)]
         struct CommentManagerHelper {
             comments: HashMap<CommentId, Comment>,
+            #[serde(default)]
+            order: Vec<CommentId>,
         }
 
         let helper = CommentManagerHelper::deserialize(deserializer)?;
-        let mut manager = Self {
+        let mut index = CommentIndex::new();
+        index.rebuild(helper.comments.values());
+        for issue in super::index::key_mismatches(&helper.comments) {
+            tracing::warn!("Comment index: {}", issue);
+        }
+        Ok(Self {
             comments: helper.comments,
-            index: CommentIndex::new(),
-        };
-        manager.rebuild_index();
-        Ok(manager)
+            order: helper.order,
+            index,
+        })
     }
 }
 
@@ -234,6 +902,89 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reorder() {
+        let mut manager = CommentManager::new();
+        let ids: Vec<CommentId> = ["a", "b", "c"]
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let mut comment = create_test_comment(c, Severity::Info);
+                comment.created_at += chrono::Duration::seconds(i as i64);
+                manager.add(comment).unwrap()
+            })
+            .collect();
+        let contents = |m: &CommentManager| -> Vec<String> {
+            m.all_sorted().iter().map(|c| c.content.clone()).collect()
+        };
+
+        manager.reorder(&ids[2], Some(&ids[0])).unwrap();
+        assert_eq!(contents(&manager), ["c", "a", "b"]);
+        manager.reorder(&ids[2], None).unwrap();
+        assert_eq!(contents(&manager), ["a", "b", "c"]);
+        manager.reorder(&ids[1], Some(&ids[0])).unwrap();
+        assert_eq!(contents(&manager), ["b", "a", "c"]);
+
+        let d = manager.add(create_test_comment("d", Severity::Info)).unwrap();
+        assert_eq!(contents(&manager), ["b", "a", "c", "d"]);
+        manager.delete(&ids[0]).unwrap();
+        assert_eq!(contents(&manager), ["b", "c", "d"]);
+        assert!(manager.reorder(&ids[0], None).is_err());
+
+        let json = serde_json::to_value(&manager).unwrap();
+        assert_eq!(json["order"].as_array().unwrap().len(), 3);
+        let restored: CommentManager = serde_json::from_value(json).unwrap();
+        assert_eq!(contents(&restored), ["b", "c", "d"]);
+        assert_eq!(restored.all_sorted()[2].id, d);
+    }
+
+    #[test]
+    fn test_get_outdated_by_diff_ignores_line_shift() {
+        use crate::comment::builder::CommentBuilder;
+        use crate::diff::DiffParser;
+
+        let before = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,2 +1,3 @@\n one\n+two\n three\n";
+        let after = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,3 +1,4 @@\n zero\n one\n+two\n three\n";
+        let parser = DiffParser::new();
+        let (before, after) = (parser.parse(before).unwrap(), parser.parse(after).unwrap());
+
+        let mut manager = CommentManager::new();
+        let file = &before.files[0];
+        for line in &file.hunks[0].lines {
+            let comment = CommentBuilder::new(file.id.clone(), line.id.clone(), DiffSide::New)
+                .content("note")
+                .build()
+                .unwrap();
+            manager.add(comment).unwrap();
+        }
+        assert_eq!(after.files[0].hunks[0].lines[2].new_line_num, Some(3));
+        assert!(manager.get_outdated_by_diff(&after).is_empty());
+
+        let changed = parser
+            .parse("diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,2 +1,2 @@\n one\n+TWO\n")
+            .unwrap();
+        assert_eq!(manager.get_outdated_by_diff(&changed).len(), 2);
+    }
+
+    #[test]
+    fn test_get_by_line_range() {
+        let mut manager = CommentManager::new();
+        for (content, line) in [("a", 12), ("b", 5), ("c", 10), ("d", 20)] {
+            let mut comment = create_test_comment(content, Severity::Info);
+            comment.metadata.line_number = Some(line);
+            manager.add(comment).unwrap();
+        }
+
+        let file_id = FileId::from_string("test-file");
+        let contents: Vec<_> = manager
+            .get_by_line_range(&file_id, 5, 12)
+            .iter()
+            .map(|c| c.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["b", "c", "a"]);
+        assert!(manager.get_by_line_range(&FileId::from_string("other"), 0, 100).is_empty());
+    }
+
     #[test]
     fn test_add_and_get() {
         let mut manager = CommentManager::new();
@@ -308,6 +1059,24 @@ mod tests {
         assert_eq!(manager.search("nonexistent").len(), 0);
     }
 
+    #[test]
+    fn test_fuzzy_and_regex_search() {
+        let mut manager = CommentManager::new();
+
+        manager.add(create_test_comment("Fix this bug", Severity::Critical)).unwrap();
+        manager.add(create_test_comment("Fix these bugs", Severity::Warning)).unwrap();
+        manager.add(create_test_comment("Improve performance", Severity::Info)).unwrap();
+
+        let matches = manager.fuzzy_search_content("fix this bugg", 0.6);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].0.content, "Fix this bug");
+        assert!(matches[0].1 > matches[1].1);
+
+        assert_eq!(manager.search_by_regex(r"^Fix th\w+ bugs?$").unwrap().len(), 2);
+        assert_eq!(manager.search_by_regex("(?i)PERF").unwrap().len(), 1);
+        assert!(manager.search_by_regex("(").is_err());
+    }
+
     #[test]
     fn test_count_by_severity() {
         let mut manager = CommentManager::new();
@@ -322,6 +1091,267 @@ mod tests {
         assert_eq!(counts.get(&Severity::Critical), None);
     }
 
+    #[test]
+    fn test_get_by_state_and_severity() {
+        let mut manager = CommentManager::new();
+        let critical = manager.add(create_test_comment("a", Severity::Critical)).unwrap();
+        manager.add(create_test_comment("b", Severity::Critical)).unwrap();
+        manager.add(create_test_comment("c", Severity::Warning)).unwrap();
+
+        assert_eq!(manager.get_open_critical().len(), 2);
+        assert_eq!(manager.get_unresolved().len(), 3);
+
+        manager.update_state(&critical, CommentState::Resolved).unwrap();
+        assert_eq!(manager.get_open_critical().len(), 1);
+        assert_eq!(manager.get_unresolved().len(), 2);
+        assert_eq!(
+            manager
+                .get_by_state_and_severity(CommentState::Resolved, Severity::Critical)
+                .len(),
+            1
+        );
+
+        manager
+            .update_comment(&critical, |c| c.set_severity(Severity::Info))
+            .unwrap();
+        assert!(manager
+            .get_by_state_and_severity(CommentState::Resolved, Severity::Critical)
+            .is_empty());
+        assert_eq!(manager.get_by_severity(Severity::Info).len(), 1);
+
+        manager.delete(&critical).unwrap();
+        assert!(manager
+            .get_by_state_and_severity(CommentState::Resolved, Severity::Info)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_bulk_tag() {
+        let mut manager = CommentManager::new();
+        let mut tagged = create_test_comment("Already tagged", Severity::Info);
+        tagged.tags.push("imported".to_string());
+        let id1 = manager.add(tagged).unwrap();
+        let id2 = manager.add(create_test_comment("Untagged", Severity::Warning)).unwrap();
+
+        let modified = manager.bulk_tag(&[id1.clone(), id2.clone()], "imported").unwrap();
+        assert_eq!(modified, 1);
+        assert_eq!(manager.get(&id1).unwrap().tags, vec!["imported".to_string()]);
+        assert!(manager.get(&id2).unwrap().tags.contains(&"imported".to_string()));
+
+        assert!(manager.bulk_tag(&[CommentId::new()], "imported").is_err());
+        assert!(manager.bulk_tag(&[id1], "  ").is_err());
+    }
+
+    #[test]
+    fn test_bulk_tag_filtered() {
+        let mut manager = CommentManager::new();
+        manager.add(create_test_comment("1", Severity::Critical)).unwrap();
+        manager.add(create_test_comment("2", Severity::Critical)).unwrap();
+        manager.add(create_test_comment("3", Severity::Info)).unwrap();
+
+        let modified = manager.bulk_tag_filtered(|c| c.severity == Severity::Critical, "blocker");
+        assert_eq!(modified.unwrap(), 2);
+        assert_eq!(manager.search("blocker").len(), 2);
+
+        // Tagging again is a no-op
+        assert_eq!(
+            manager.bulk_tag_filtered(|c| c.severity == Severity::Critical, "blocker").unwrap(),
+            0
+        );
+
+        assert!(matches!(
+            manager.bulk_tag_filtered(|_| true, " "),
+            Err(CrHelperError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_by_pattern() {
+        let mut manager = CommentManager::new();
+        let mut add = |path: &str, line: usize| {
+            let mut comment = create_test_comment(path, Severity::Warning);
+            comment.metadata.file_path = Some(path.to_string());
+            comment.metadata.line_number = Some(line);
+            manager.add(comment).unwrap()
+        };
+        let lib = add("src/lib.rs", 10);
+        let lib_late = add("src/lib.rs", 50);
+        let main = add("src/bin/main.rs", 10);
+        let readme = add("README.md", 10);
+
+        assert_eq!(manager.resolve_by_pattern("src/lib.rs", Some(1..=20)).unwrap(), 1);
+        assert_eq!(manager.get(&lib).unwrap().state, CommentState::Resolved);
+        assert_eq!(manager.get(&lib_late).unwrap().state, CommentState::Open);
+
+        // Already resolved comments are not counted again
+        assert_eq!(manager.resolve_by_pattern("src/**/*.rs", None).unwrap(), 2);
+        assert_eq!(manager.get(&main).unwrap().state, CommentState::Resolved);
+        assert_eq!(manager.get(&readme).unwrap().state, CommentState::Open);
+        assert_eq!(manager.get_by_state_and_severity(CommentState::Resolved, Severity::Warning).len(), 3);
+
+        assert_eq!(manager.reopen_by_pattern("src/bin/*", None).unwrap(), 1);
+        assert_eq!(manager.get(&main).unwrap().state, CommentState::Open);
+
+        assert!(manager.resolve_by_pattern("src/[", None).is_err());
+    }
+
+    #[test]
+    fn test_to_inline_annotations() {
+        use crate::comment::builder::CommentBuilder;
+        use crate::diff::DiffParser;
+
+        let diff = DiffParser::new()
+            .parse("diff --git a/src/db.rs b/src/db.rs\n--- a/src/db.rs\n+++ b/src/db.rs\n@@ -10,2 +10,2 @@\n fn query() {\n-    run(q)\n+\tlet q = format!(\"{}\", input);\n")
+            .unwrap();
+        let file = &diff.files[0];
+        let lines = &file.hunks[0].lines;
+
+        let mut manager = CommentManager::new();
+        let comment = CommentBuilder::new(file.id.clone(), lines[2].id.clone(), DiffSide::New)
+            .content("SQL injection:\nuse a bound parameter")
+            .severity(Severity::Critical)
+            .build()
+            .unwrap();
+        manager.add(comment).unwrap();
+        let comment = CommentBuilder::new(file.id.clone(), lines[1].id.clone(), DiffSide::Old)
+            .content("Was unchecked")
+            .build()
+            .unwrap();
+        manager.add(comment).unwrap();
+        let mut gone = create_test_comment("Stale note", Severity::Warning);
+        gone.metadata.file_path = Some("src/lib.rs".to_string());
+        gone.metadata.line_number = Some(3);
+        manager.add(gone).unwrap();
+
+        assert_eq!(
+            manager.to_inline_annotations(&diff),
+            "src/db.rs:11:2: critical: SQL injection: use a bound parameter\n    \tlet q = format!(\"{}\", input);\n    \t^\n\
+             src/db.rs:11:5: info: Was unchecked\n        run(q)\n        ^\n\
+             src/lib.rs:3: warning: Stale note\n"
+        );
+    }
+
+    #[test]
+    fn test_cluster_by_topic() {
+        let mut manager = CommentManager::new();
+        let contents = [
+            "SQL injection in this query",
+            "Query is open to SQL injection",
+            "Unclear variable name",
+            "Rename this unclear variable",
+        ];
+        let ids: Vec<CommentId> = contents
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let mut comment = create_test_comment(c, Severity::Info);
+                comment.created_at += chrono::Duration::seconds(i as i64);
+                manager.add(comment).unwrap()
+            })
+            .collect();
+
+        let clusters = manager.cluster_by_topic(2);
+        assert_eq!(clusters.len(), 2);
+        let together = |a: &CommentId, b: &CommentId| {
+            clusters.iter().any(|c| c.contains(a) && c.contains(b))
+        };
+        assert!(together(&ids[0], &ids[1]));
+        assert!(together(&ids[2], &ids[3]));
+        assert!(!together(&ids[0], &ids[2]));
+
+        let label = manager.topic_label(&ids[..2], 3);
+        assert_eq!(label, ["injection", "query", "sql"]);
+        assert!(!manager.cluster_by_topic(0).is_empty());
+        assert!(CommentManager::new().cluster_by_topic(3).is_empty());
+    }
+
+    #[test]
+    fn test_import_from_checkstyle() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="10.12.0">
+  <file name="src/main/java/App.java">
+    <error line="12" column="5" severity="error" message="Missing a Javadoc comment."/>
+    <error line="30" severity="warning" message="&apos;42&apos; is a magic number."/>
+  </file>
+  <file name="src/main/java/Util.java">
+    <error line="0" severity="info" message="File does not end with a newline."/>
+  </file>
+</checkstyle>"#;
+        let comments = CommentManager::import_from_checkstyle(xml).unwrap();
+        assert_eq!(comments.len(), 3);
+        assert_eq!(comments[0].severity, Severity::Critical);
+        assert_eq!(comments[0].content, "Missing a Javadoc comment.");
+        assert_eq!(comments[0].metadata.line_number, Some(12));
+        assert_eq!(comments[0].metadata.source.as_deref(), Some("checkstyle"));
+        assert_eq!(comments[0].tags, ["style"]);
+        assert_eq!(comments[1].severity, Severity::Warning);
+        assert_eq!(comments[1].content, "'42' is a magic number.");
+        assert_eq!(comments[2].severity, Severity::Info);
+        assert_eq!(comments[2].metadata.file_path.as_deref(), Some("src/main/java/Util.java"));
+        assert_eq!(comments[2].metadata.line_number, Some(1));
+
+        assert!(CommentManager::import_from_checkstyle(r#"<checkstyle><file name="a.java"></checkstyle>"#).is_err());
+        assert!(CommentManager::import_from_checkstyle(r#"<error line="1" message="m"/>"#).is_err());
+        assert!(CommentManager::import_from_checkstyle(
+            r#"<file name="a.java"><error line="x" message="m"/></file>"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_import_from_pmd() {
+        let xml = r#"<pmd version="6.55.0">
+  <file name="src/App.java">
+    <violation beginline="7" endline="7" rule="UnusedPrivateField" ruleset="Best Practices" priority="3">
+Avoid unused private fields such as 'count'.
+    </violation>
+    <violation beginline="20" rule="EmptyCatchBlock" priority="1"/>
+  </file>
+</pmd>"#;
+        let comments = CommentManager::import_from_pmd(xml).unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].content, "Avoid unused private fields such as 'count'.");
+        assert_eq!(comments[0].severity, Severity::Warning);
+        assert_eq!(comments[0].metadata.line_number, Some(7));
+        assert_eq!(comments[0].metadata.source.as_deref(), Some("pmd"));
+        assert_eq!(comments[1].content, "EmptyCatchBlock");
+        assert_eq!(comments[1].severity, Severity::Critical);
+    }
+
+    #[test]
+    fn test_import_from_spotbugs() {
+        let xml = r#"<BugCollection version="4.8.0">
+  <Project projectName="app"><SrcDir>/nonexistent/src/main/java</SrcDir></Project>
+  <BugInstance type="NP_NULL_ON_SOME_PATH" priority="1" category="CORRECTNESS">
+    <ShortMessage>Possible null pointer dereference</ShortMessage>
+    <LongMessage>Possible null pointer dereference of name in App.greet()</LongMessage>
+    <Class classname="com.example.App" primary="true">
+      <SourceLine classname="com.example.App" start="1" end="40" sourcepath="com/example/App.java"/>
+    </Class>
+    <SourceLine classname="com.example.App" primary="true" start="17" end="17" sourcepath="com/example/App.java"/>
+  </BugInstance>
+  <BugInstance type="SE_BAD_FIELD" priority="3">
+    <Class classname="com.example.Data">
+      <SourceLine classname="com.example.Data" start="3" sourcepath="com/example/Data.java"/>
+    </Class>
+  </BugInstance>
+  <BugInstance type="NM_CLASS_NAMING_CONVENTION" priority="2"/>
+</BugCollection>"#;
+        let comments = CommentManager::import_from_spotbugs(xml).unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(
+            comments[0].content,
+            "Possible null pointer dereference of name in App.greet()"
+        );
+        assert_eq!(comments[0].severity, Severity::Critical);
+        assert_eq!(comments[0].metadata.line_number, Some(17));
+        assert_eq!(comments[0].metadata.file_path.as_deref(), Some("com/example/App.java"));
+        assert_eq!(comments[0].tags, ["bug"]);
+        assert_eq!(comments[1].content, "SE_BAD_FIELD");
+        assert_eq!(comments[1].severity, Severity::Info);
+        assert_eq!(comments[1].metadata.line_number, Some(3));
+    }
+
     #[test]
     fn test_serialization() {
         let mut manager = CommentManager::new();
@@ -7,9 +7,10 @@ pub mod manager;
 pub mod index;
 pub mod validator;
 pub mod builder;
+mod cluster;
 
 pub use model::*;
 pub use manager::CommentManager;
-pub use index::CommentIndex;
-pub use validator::CommentValidator;
+pub use index::{CommentIndex, IndexValidationResult, PhantomCommentDetector};
+pub use validator::{CommentValidator, ValidationResult, ValidationWarning};
 pub use builder::CommentBuilder;
@@ -171,8 +171,8 @@ impl DiffSide {
     }
 }
 
-/// Comment severity level
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Comment severity level, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Severity {
     /// Informational - nice to have improvements
     Info,
@@ -229,7 +229,7 @@ impl Default for Severity {
 }
 
 /// Comment lifecycle state
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CommentState {
     /// Newly created, needs attention
     Open,
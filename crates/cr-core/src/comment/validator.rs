@@ -3,6 +3,8 @@
 use super::model::{Comment, LineReference};
 use crate::diff::DiffData;
 use crate::error::{CrHelperError, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
 
 /// Maximum comment length (default)
 pub const MAX_COMMENT_LENGTH: usize = 10000;
@@ -10,10 +12,47 @@ pub const MAX_COMMENT_LENGTH: usize = 10000;
 /// Minimum comment length
 pub const MIN_COMMENT_LENGTH: usize = 1;
 
+/// Non-fatal problem found while validating a comment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationWarning {
+    /// A code block in the suggested fix failed its language's syntax check
+    SuggestedFixSyntaxError {
+        /// Language from the code fence
+        language: String,
+        /// Checker output
+        error: String,
+    },
+}
+
+impl std::fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationWarning::SuggestedFixSyntaxError { language, error } => {
+                write!(f, "suggested {} fix does not parse: {}", language, error)
+            }
+        }
+    }
+}
+
+/// Warnings collected by a validation pass
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationResult {
+    /// Warnings found
+    pub warnings: Vec<ValidationWarning>,
+}
+
+impl ValidationResult {
+    /// Check if no warnings were found
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
 /// Validator for comments
 pub struct CommentValidator {
     max_length: usize,
     min_length: usize,
+    check_suggested_fix_syntax: bool,
 }
 
 impl CommentValidator {
@@ -22,6 +61,7 @@ impl CommentValidator {
         Self {
             max_length: MAX_COMMENT_LENGTH,
             min_length: MIN_COMMENT_LENGTH,
+            check_suggested_fix_syntax: false,
         }
     }
 
@@ -30,9 +70,16 @@ impl CommentValidator {
         Self {
             max_length,
             min_length: MIN_COMMENT_LENGTH,
+            check_suggested_fix_syntax: false,
         }
     }
 
+    /// Enable syntax checks of suggested fixes (runs external tools)
+    pub fn with_suggested_fix_syntax_check(mut self, enabled: bool) -> Self {
+        self.check_suggested_fix_syntax = enabled;
+        self
+    }
+
     /// Validate comment content
     pub fn validate_content(&self, content: &str) -> Result<()> {
         let trimmed = content.trim();
@@ -132,6 +179,108 @@ impl CommentValidator {
 
         Ok(())
     }
+
+    /// Check that fenced code blocks in the suggested fix parse
+    ///
+    /// Does nothing unless enabled with
+    /// [`with_suggested_fix_syntax_check`](Self::with_suggested_fix_syntax_check).
+    /// Blocks in unsupported languages, or whose checker is not installed,
+    /// are skipped.
+    pub fn check_suggested_fix_roundtrips(&self, comment: &Comment) -> ValidationResult {
+        let mut result = ValidationResult::default();
+        if !self.check_suggested_fix_syntax {
+            return result;
+        }
+        let Some(fix) = comment.extensions.suggested_fix() else {
+            return result;
+        };
+
+        for (language, code) in fenced_code_blocks(fix) {
+            if let Some(error) = check_syntax(&language, &code) {
+                result
+                    .warnings
+                    .push(ValidationWarning::SuggestedFixSyntaxError { language, error });
+            }
+        }
+        result
+    }
+}
+
+/// Extract `(language, code)` pairs from fenced code blocks
+fn fenced_code_blocks(text: &str) -> Vec<(String, String)> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(String, String)> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        match current.take() {
+            None => {
+                if let Some(info) = trimmed.strip_prefix("```") {
+                    let language = info.split_whitespace().next().unwrap_or("");
+                    current = Some((language.to_lowercase(), String::new()));
+                }
+            }
+            Some(block) if trimmed.starts_with("```") => blocks.push(block),
+            Some((language, mut code)) => {
+                code.push_str(line);
+                code.push('\n');
+                current = Some((language, code));
+            }
+        }
+    }
+    blocks
+}
+
+/// Run the syntax checker for a language, returning its error output on failure
+fn check_syntax(language: &str, code: &str) -> Option<String> {
+    match language {
+        "rust" | "rs" => {
+            // Snippets are often statements rather than items
+            let wrapped = format!("fn __suggested_fix() {{\n{}}}\n", code);
+            let args = ["--edition", "2021", "--emit", "stdout"];
+            let error = run_checker("rustfmt", &args, code)?;
+            run_checker("rustfmt", &args, &wrapped).map(|_| error)
+        }
+        "python" | "py" => run_checker(
+            "python3",
+            &["-c", "import sys; compile(sys.stdin.read(), '<fix>', 'exec')"],
+            code,
+        ),
+        "sh" | "bash" | "shell" => run_checker("sh", &["-n"], code),
+        "json" => serde_json::from_str::<serde_json::Value>(code)
+            .err()
+            .map(|e| e.to_string()),
+        _ => None,
+    }
+}
+
+/// Pipe code into a checker, returning stderr if it exits unsuccessfully
+///
+/// A checker that cannot be started counts as success.
+fn run_checker(program: &str, args: &[&str], code: &str) -> Option<String> {
+    let mut child = match Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::debug!("Skipping syntax check, cannot run {}: {}", program, e);
+            return None;
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(code.as_bytes());
+    }
+    let output = child.wait_with_output().ok()?;
+    if output.status.success() {
+        return None;
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Some(stderr.lines().next().unwrap_or("syntax error").trim().to_string())
 }
 
 impl Default for CommentValidator {
@@ -169,4 +318,48 @@ mod tests {
         let validator = CommentValidator::new();
         assert!(validator.validate_content("  Valid  ").is_ok());
     }
+
+    #[test]
+    fn test_fenced_code_blocks() {
+        let text = "Use this:\n```rust\nlet x = 1;\n```\nor\n```\nplain\n```\n";
+        let blocks = fenced_code_blocks(text);
+        assert_eq!(
+            blocks,
+            vec![
+                ("rust".to_string(), "let x = 1;\n".to_string()),
+                (String::new(), "plain\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_suggested_fix_roundtrips() {
+        use crate::comment::{CommentBuilder, DiffSide};
+        use crate::types::{FileId, LineId};
+
+        let comment = |fix: &str| {
+            CommentBuilder::new(
+                FileId::from_string("f"),
+                LineId::from_string("l"),
+                DiffSide::New,
+            )
+            .content("Fix")
+            .suggested_fix(fix)
+            .build()
+            .unwrap()
+        };
+        let bad = comment("```json\n{\"a\": }\n```");
+
+        assert!(CommentValidator::new().check_suggested_fix_roundtrips(&bad).is_clean());
+
+        let validator = CommentValidator::new().with_suggested_fix_syntax_check(true);
+        let result = validator.check_suggested_fix_roundtrips(&bad);
+        assert!(matches!(
+            &result.warnings[..],
+            [ValidationWarning::SuggestedFixSyntaxError { language, .. }] if language == "json"
+        ));
+        assert!(validator
+            .check_suggested_fix_roundtrips(&comment("```json\n{\"a\": 1}\n```"))
+            .is_clean());
+    }
 }
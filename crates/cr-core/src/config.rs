@@ -1,7 +1,9 @@
 //! Configuration management for cr-helper
 
+use crate::diff::ParserConfig;
+use crate::error::{CrHelperError, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +17,14 @@ pub struct Config {
     pub diff: DiffConfig,
     /// UI settings
     pub ui: UiConfig,
+    /// Session storage settings
+    pub storage: StorageConfig,
+    /// Other repositories whose sessions can be managed with `--workspace`
+    pub workspaces: Vec<WorkspaceConfig>,
+    /// Agent integration settings
+    pub integration: IntegrationConfig,
+    /// `cr-helper doctor` thresholds
+    pub health: HealthConfig,
 }
 
 impl Default for Config {
@@ -24,10 +34,30 @@ impl Default for Config {
             export: ExportConfig::default(),
             diff: DiffConfig::default(),
             ui: UiConfig::default(),
+            storage: StorageConfig::default(),
+            workspaces: Vec::new(),
+            integration: IntegrationConfig::default(),
+            health: HealthConfig::default(),
         }
     }
 }
 
+impl Config {
+    /// Load configuration from a TOML file (defaults if it doesn't exist)
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        toml::from_str(&content).map_err(|e| CrHelperError::Toml(e.to_string()))
+    }
+
+    /// Find a configured workspace by name
+    pub fn workspace(&self, name: &str) -> Option<&WorkspaceConfig> {
+        self.workspaces.iter().find(|w| w.name == name)
+    }
+}
+
 /// Review-related configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -38,6 +68,14 @@ pub struct ReviewConfig {
     pub max_comment_length: usize,
     /// Auto-save interval in seconds
     pub auto_save_interval: u64,
+    /// Comment validation settings
+    pub validation: ValidationConfig,
+    /// Minimum share of changed lines that should carry a comment (doctor warns below)
+    pub min_coverage_percent: f64,
+    /// Score boundaries for `Session::compute_risk_score` levels
+    pub risk_thresholds: RiskThresholds,
+    /// Keywords for `CommentBuilder::auto_severity`
+    pub severity_keywords: SeverityKeywords,
 }
 
 impl Default for ReviewConfig {
@@ -51,6 +89,66 @@ impl Default for ReviewConfig {
             ],
             max_comment_length: 2000,
             auto_save_interval: 30,
+            validation: ValidationConfig::default(),
+            min_coverage_percent: 10.0,
+            risk_thresholds: RiskThresholds::default(),
+            severity_keywords: SeverityKeywords::default(),
+        }
+    }
+}
+
+/// Comment validation configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationConfig {
+    /// Syntax-check code blocks in suggested fixes with external tools
+    pub check_suggested_fix_syntax: bool,
+}
+
+/// Lowest risk score for each level above low (`[review.risk_thresholds]`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskThresholds {
+    /// Medium risk from this score
+    pub medium: f64,
+    /// High risk from this score
+    pub high: f64,
+    /// Critical risk from this score
+    pub critical: f64,
+}
+
+impl Default for RiskThresholds {
+    fn default() -> Self {
+        Self {
+            medium: 2.0,
+            high: 5.0,
+            critical: 10.0,
+        }
+    }
+}
+
+/// Tags and content words that suggest a severity (`[review.severity_keywords]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SeverityKeywords {
+    /// Tags that make a comment critical
+    pub critical_tags: Vec<String>,
+    /// Words or phrases in the content that make a comment critical
+    pub critical_words: Vec<String>,
+    /// Tags that make a comment a warning
+    pub warning_tags: Vec<String>,
+    /// Words or phrases in the content that make a comment a warning
+    pub warning_words: Vec<String>,
+}
+
+impl Default for SeverityKeywords {
+    fn default() -> Self {
+        let strings = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+        Self {
+            critical_tags: strings(&["security", "vulnerability", "injection"]),
+            critical_words: strings(&["unsafe", "vulnerable", "credential"]),
+            warning_tags: strings(&["performance", "bug"]),
+            warning_words: strings(&["should", "could cause", "incorrect"]),
         }
     }
 }
@@ -69,6 +167,12 @@ pub struct ExportConfig {
     pub include_stats: bool,
     /// Include suggested fixes
     pub include_suggestions: bool,
+    /// Show the enclosing function instead of a fixed line window (heuristic)
+    pub use_function_context: bool,
+    /// Add a table of contents to Markdown reports with 5 or more comments
+    pub include_toc: bool,
+    /// Check exports for malformed output before writing them
+    pub validate: bool,
 }
 
 impl Default for ExportConfig {
@@ -79,6 +183,9 @@ impl Default for ExportConfig {
             context_lines: 2,
             include_stats: true,
             include_suggestions: true,
+            use_function_context: false,
+            include_toc: true,
+            validate: false,
         }
     }
 }
@@ -97,6 +204,34 @@ pub struct DiffConfig {
     pub line_numbers: bool,
     /// Side by side view
     pub side_by_side: bool,
+    /// Context lines around changes (git's default of 3 when unset)
+    pub context_lines: Option<usize>,
+    /// Ignore whitespace changes (`git diff -w`)
+    pub ignore_whitespace: bool,
+    /// Ignore changes to blank lines (`git diff --ignore-blank-lines`)
+    pub ignore_blank_lines: bool,
+    /// Files loaded into a review session before the rest of the diff is
+    /// dropped; 0 means no limit
+    pub max_files_per_session: usize,
+    /// `.crignore`-style patterns dropped by `review --exclude-lockfiles`
+    pub lockfile_patterns: Vec<String>,
+}
+
+impl DiffConfig {
+    /// Parser settings for the configured `git diff` options
+    pub fn parser_config(&self) -> ParserConfig {
+        ParserConfig {
+            context_lines: self.context_lines,
+            ignore_whitespace: self.ignore_whitespace,
+            ignore_blank_lines: self.ignore_blank_lines,
+            ..ParserConfig::default()
+        }
+    }
+
+    /// `max_files_per_session`, or `None` for no limit
+    pub fn max_files(&self) -> Option<usize> {
+        Some(self.max_files_per_session).filter(|&max| max > 0)
+    }
 }
 
 impl Default for DiffConfig {
@@ -112,6 +247,24 @@ impl Default for DiffConfig {
             delta_theme: None,
             line_numbers: true,
             side_by_side: false,
+            context_lines: None,
+            ignore_whitespace: false,
+            ignore_blank_lines: false,
+            max_files_per_session: 500,
+            lockfile_patterns: [
+                "Cargo.lock",
+                "package-lock.json",
+                "yarn.lock",
+                "pnpm-lock.yaml",
+                "go.sum",
+                "Pipfile.lock",
+                "poetry.lock",
+                "Gemfile.lock",
+                "composer.lock",
+            ]
+            .iter()
+            .map(|p| p.to_string())
+            .collect(),
         }
     }
 }
@@ -126,6 +279,18 @@ pub struct UiConfig {
     pub theme: String,
     /// Key bindings (vim/default)
     pub key_bindings: String,
+    /// Also export to a temp file when saving with `s`
+    pub export_on_save: bool,
+    /// Capture the mouse (turn off to select text with it)
+    pub mouse: bool,
+    /// Lines kept visible above and below the cursor when scrolling
+    pub scroll_padding: usize,
+    /// Show old/new line numbers in the diff
+    pub show_line_numbers: bool,
+    /// Wrap long diff lines instead of cutting them off
+    pub word_wrap: bool,
+    /// Highlight the line under the cursor
+    pub highlight_cursor_line: bool,
 }
 
 impl Default for UiConfig {
@@ -134,10 +299,66 @@ impl Default for UiConfig {
             show_file_tree: true,
             theme: "default".to_string(),
             key_bindings: "default".to_string(),
+            export_on_save: false,
+            mouse: true,
+            scroll_padding: 3,
+            show_line_numbers: true,
+            word_wrap: false,
+            highlight_cursor_line: true,
+        }
+    }
+}
+
+/// Session storage configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Keep at most this many sessions, rotating out the oldest (0 = unlimited)
+    ///
+    /// Sessions with comments are kept up to twice this many (see
+    /// `SessionStorage::rotate_sessions`); pinned ones are never rotated.
+    pub max_sessions: usize,
+    /// How new sessions are written
+    pub format: StorageFormat,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            max_sessions: 100,
+            format: StorageFormat::default(),
         }
     }
 }
 
+/// Session file format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+    /// Everything in `<id>.json`
+    #[default]
+    Json,
+    /// Diff data in a `<diff_hash>.diff.msgpack` sidecar, the rest in `<id>.json`
+    Msgpack,
+}
+
+/// A named repository in a multi-workspace setup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    /// Name used with `--workspace`
+    pub name: String,
+    /// Repository root
+    pub path: PathBuf,
+}
+
+/// Agent integration configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntegrationConfig {
+    /// Claude Code settings (`[integration.claude_code]`)
+    pub claude_code: ClaudeCodeConfig,
+}
+
 /// Claude Code integration configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -150,6 +371,8 @@ pub struct ClaudeCodeConfig {
     pub block_on_critical: bool,
     /// Output directory for review files
     pub output_dir: PathBuf,
+    /// Write `.claude/cr-helper/review-context.md` after each TUI review
+    pub auto_inject_on_review: bool,
 }
 
 impl Default for ClaudeCodeConfig {
@@ -159,6 +382,23 @@ impl Default for ClaudeCodeConfig {
             min_changes_for_review: 3,
             block_on_critical: true,
             output_dir: PathBuf::from(".claude/cr-helper"),
+            auto_inject_on_review: false,
+        }
+    }
+}
+
+/// Health check configuration (`[health]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// Slowest acceptable agent round trip for `doctor --ping`
+    pub max_agent_latency_ms: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            max_agent_latency_ms: 5000,
         }
     }
 }
@@ -173,6 +413,13 @@ mod tests {
         assert!(config.export.include_code_context);
         assert_eq!(config.export.context_lines, 2);
         assert!(config.ui.show_file_tree);
+        assert!(config.ui.mouse);
+        assert_eq!(config.ui.scroll_padding, 3);
+
+        let config: Config = toml::from_str("[ui]\nmouse = false\nword_wrap = true\n").unwrap();
+        assert!(!config.ui.mouse);
+        assert!(config.ui.word_wrap);
+        assert!(config.ui.highlight_cursor_line);
     }
 
     #[test]
@@ -184,6 +431,68 @@ mod tests {
 
         let config2: Config = toml::from_str(&toml).unwrap();
         assert_eq!(config.export.context_lines, config2.export.context_lines);
+        assert_eq!(config2.storage.max_sessions, 100);
+    }
+
+    #[test]
+    fn test_config_load() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("config.toml");
+        assert_eq!(Config::load(&path).unwrap().storage.max_sessions, 100);
+
+        std::fs::write(&path, "[storage]\nmax_sessions = 5\nformat = \"msgpack\"\n").unwrap();
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.storage.max_sessions, 5);
+        assert_eq!(config.storage.format, StorageFormat::Msgpack);
+        assert_eq!(config.export.context_lines, 2);
+
+        std::fs::write(&path, "[storage\n").unwrap();
+        assert!(Config::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_diff_options() {
+        let config = Config::default();
+        assert!(config.diff.parser_config().git_diff_flags().is_empty());
+        assert_eq!(config.diff.max_files(), Some(500));
+
+        let config: Config =
+            toml::from_str("[diff]\ncontext_lines = 5\nignore_blank_lines = true\n").unwrap();
+        assert_eq!(
+            config.diff.parser_config().git_diff_flags(),
+            vec!["-U5", "--ignore-blank-lines"]
+        );
+        assert!(config.diff.lockfile_patterns.contains(&"go.sum".to_string()));
+
+        let config: Config = toml::from_str("[diff]\nlockfile_patterns = [\"*.lockb\"]\n").unwrap();
+        assert_eq!(config.diff.lockfile_patterns, ["*.lockb"]);
+
+        let config: Config = toml::from_str("[diff]\nmax_files_per_session = 0\n").unwrap();
+        assert_eq!(config.diff.max_files(), None);
+    }
+
+    #[test]
+    fn test_workspaces() {
+        let config: Config = toml::from_str(
+            r#"
+            [[workspaces]]
+            name = "backend"
+            path = "/repos/backend"
+
+            [[workspaces]]
+            name = "web"
+            path = "/repos/web"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.workspaces.len(), 2);
+        assert_eq!(
+            config.workspace("web").unwrap().path,
+            PathBuf::from("/repos/web")
+        );
+        assert!(config.workspace("mobile").is_none());
+        assert!(toml::to_string(&config).is_ok());
     }
 
     #[test]
@@ -192,5 +501,19 @@ mod tests {
         assert!(config.auto_review_on_stop);
         assert_eq!(config.min_changes_for_review, 3);
         assert!(config.block_on_critical);
+        assert!(!config.auto_inject_on_review);
+
+        let config: Config =
+            toml::from_str("[integration.claude_code]\nauto_inject_on_review = true\n").unwrap();
+        assert!(config.integration.claude_code.auto_inject_on_review);
+        assert!(config.integration.claude_code.block_on_critical);
+    }
+
+    #[test]
+    fn test_health_config() {
+        assert_eq!(Config::default().health.max_agent_latency_ms, 5000);
+
+        let config: Config = toml::from_str("[health]\nmax_agent_latency_ms = 250\n").unwrap();
+        assert_eq!(config.health.max_agent_latency_ms, 250);
     }
 }
@@ -0,0 +1,200 @@
+//! `.crignore` path exclusions
+//!
+//! Patterns follow a small subset of `.gitignore` syntax: `*` and `?`
+//! wildcards, a trailing `/` for directories, and a `/` inside the pattern to
+//! anchor it to the project root. Blank lines and `#` comments are skipped.
+//!
+//! Patterns after a `[binary]` line don't exclude anything by themselves;
+//! they mark files that `cr-helper review --no-binary` drops along with the
+//! ones git reports as binary.
+
+use super::model::{DiffData, DiffStats};
+use crate::error::Result;
+use std::path::{Component, Path};
+
+/// Default location of the ignore file, relative to the project root
+pub const CRIGNORE_PATH: &str = ".cr-helper/.crignore";
+
+/// A parsed set of ignore patterns
+#[derive(Debug, Clone, Default)]
+pub struct CrIgnore {
+    patterns: Vec<Pattern>,
+    binary_patterns: Vec<Pattern>,
+}
+
+#[derive(Debug, Clone)]
+struct Pattern {
+    glob: String,
+    anchored: bool,
+    dir_only: bool,
+}
+
+impl CrIgnore {
+    /// Parse patterns from file content
+    pub fn parse(content: &str) -> Self {
+        let mut ignore = Self::default();
+        let mut in_binary = false;
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line == "[binary]" {
+                in_binary = true;
+                continue;
+            }
+            let dir_only = line.ends_with('/');
+            let line = line.trim_end_matches('/');
+            let pattern = Pattern {
+                glob: line.trim_start_matches('/').to_string(),
+                anchored: line.contains('/'),
+                dir_only,
+            };
+            if in_binary {
+                ignore.binary_patterns.push(pattern);
+            } else {
+                ignore.patterns.push(pattern);
+            }
+        }
+        ignore
+    }
+
+    /// Load patterns from a file, returning an empty set if it does not exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// Check if no patterns are configured
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty() && self.binary_patterns.is_empty()
+    }
+
+    /// Check if a path relative to the project root is excluded
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let parts = path_parts(path);
+        self.patterns.iter().any(|p| p.matches(&parts))
+    }
+
+    /// Check if a `[binary]` pattern marks a path as binary
+    pub fn is_binary(&self, path: &Path) -> bool {
+        let parts = path_parts(path);
+        self.binary_patterns.iter().any(|p| p.matches(&parts))
+    }
+
+    /// Drop excluded files from `diff`, returning how many were removed
+    pub fn filter_diff(&self, diff: &mut DiffData) -> usize {
+        let before = diff.files.len();
+        diff.files.retain(|f| !self.is_ignored(f.display_path()));
+        diff.stats = DiffStats::from_diff(diff);
+        before - diff.files.len()
+    }
+}
+
+impl Pattern {
+    fn matches(&self, parts: &[String]) -> bool {
+        // A directory pattern can match any ancestor, never the file itself
+        let candidates = if self.dir_only {
+            parts.len().saturating_sub(1)
+        } else {
+            parts.len()
+        };
+
+        if self.anchored {
+            (1..=candidates).any(|n| wildcard_match(&self.glob, &parts[..n].join("/")))
+        } else {
+            parts[..candidates]
+                .iter()
+                .any(|part| wildcard_match(&self.glob, part))
+        }
+    }
+}
+
+fn path_parts(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Match `text` against a pattern with `*` and `?` wildcards
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star, matched)) = backtrack {
+            pi = star + 1;
+            ti = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ignored() {
+        let ignore = CrIgnore::parse("# generated\n*.lock\ntarget/\n/docs/gen\n\n");
+
+        assert!(ignore.is_ignored(Path::new("Cargo.lock")));
+        assert!(ignore.is_ignored(Path::new("sub/yarn.lock")));
+        assert!(ignore.is_ignored(Path::new("target/debug/app")));
+        assert!(ignore.is_ignored(Path::new("crates/x/target/out.rs")));
+        assert!(!ignore.is_ignored(Path::new("target")));
+        assert!(ignore.is_ignored(Path::new("docs/gen/api.md")));
+        assert!(!ignore.is_ignored(Path::new("src/docs/gen/api.md")));
+        assert!(!ignore.is_ignored(Path::new("src/main.rs")));
+    }
+
+    #[test]
+    fn test_binary_section() {
+        let ignore = CrIgnore::parse("vendor/\n[binary]\n*.min.js\n/assets/fonts/\n");
+
+        assert!(ignore.is_ignored(Path::new("vendor/lib.rs")));
+        assert!(!ignore.is_ignored(Path::new("app.min.js")));
+        assert!(ignore.is_binary(Path::new("web/app.min.js")));
+        assert!(ignore.is_binary(Path::new("assets/fonts/mono.woff2")));
+        assert!(!ignore.is_binary(Path::new("vendor/lib.rs")));
+        assert!(!CrIgnore::parse("[binary]\n*.bin").is_empty());
+    }
+
+    #[test]
+    fn test_filter_diff() {
+        let input = "diff --git a/Cargo.lock b/Cargo.lock\n--- a/Cargo.lock\n+++ b/Cargo.lock\n@@ -1 +1 @@\n-a\n+b\n\
+                     diff --git a/src/main.rs b/src/main.rs\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1 +1,2 @@\n a\n+b\n";
+        let mut diff = crate::diff::DiffParser::new().parse(input).unwrap();
+
+        assert_eq!(CrIgnore::parse("*.lock").filter_diff(&mut diff), 1);
+        assert_eq!(diff.files.len(), 1);
+        assert_eq!(diff.stats.insertions, 1);
+        assert_eq!(diff.stats.deletions, 0);
+        assert!(diff.patch_string().starts_with("diff --git a/src/main.rs"));
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.rs", "main.rs"));
+        assert!(wildcard_match("te?t", "test"));
+        assert!(wildcard_match("a*b*c", "axxbyyc"));
+        assert!(!wildcard_match("*.rs", "main.rsx"));
+        assert!(CrIgnore::parse("").is_empty());
+    }
+}
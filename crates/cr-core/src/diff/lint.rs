@@ -0,0 +1,223 @@
+//! Built-in lint detectors that generate review comments
+//!
+//! Detectors only look at added lines, so they flag problems introduced by
+//! the diff rather than pre-existing ones.
+
+use super::model::{DiffData, Line, LineType};
+use crate::comment::builder::CommentBuilder;
+use crate::comment::model::{Comment, DiffSide, Severity};
+
+/// Source recorded on auto-generated comments
+pub const AUTO_DETECT_SOURCE: &str = "auto-detect";
+
+/// A check that turns diff lines into comments
+pub trait LintDetector: Send + Sync {
+    /// Comment content for an offending line, if any
+    fn check(&self, line: &Line) -> Option<String>;
+
+    /// Severity of generated comments
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    /// Tag added to generated comments
+    fn tag(&self) -> &str {
+        "style"
+    }
+
+    /// Scan all added lines in a diff
+    fn detect(&self, diff: &DiffData) -> Vec<Comment> {
+        let mut comments = Vec::new();
+        for file in &diff.files {
+            let path = file.display_path().to_string_lossy().to_string();
+            for line in file.hunks.iter().flat_map(|h| &h.lines) {
+                if line.line_type != LineType::Added {
+                    continue;
+                }
+                let Some(content) = self.check(line) else {
+                    continue;
+                };
+                let comment = CommentBuilder::new(file.id.clone(), line.id.clone(), DiffSide::New)
+                    .content(content)
+                    .severity(self.severity())
+                    .tag(self.tag())
+                    .source(AUTO_DETECT_SOURCE)
+                    .line_number(line.new_line_num.unwrap_or(0))
+                    .file_path(path.as_str())
+                    .build();
+                if let Ok(comment) = comment {
+                    comments.push(comment);
+                }
+            }
+        }
+        comments
+    }
+}
+
+/// Flags added lines ending in spaces or tabs
+pub struct TrailingWhitespaceDetector;
+
+impl LintDetector for TrailingWhitespaceDetector {
+    fn check(&self, line: &Line) -> Option<String> {
+        line.content
+            .ends_with([' ', '\t'])
+            .then(|| "Trailing whitespace on this line".to_string())
+    }
+}
+
+/// Flags added lines whose indentation mixes tabs and spaces
+pub struct TabVsSpaceDetector;
+
+impl LintDetector for TabVsSpaceDetector {
+    fn check(&self, line: &Line) -> Option<String> {
+        let indent: String = line
+            .content
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        (indent.contains(' ') && indent.contains('\t'))
+            .then(|| "Indentation mixes tabs and spaces".to_string())
+    }
+}
+
+/// Flags added lines longer than a maximum number of characters
+pub struct LongLineDetector(pub usize);
+
+impl LintDetector for LongLineDetector {
+    fn check(&self, line: &Line) -> Option<String> {
+        let len = line.content.chars().count();
+        (len > self.0).then(|| format!("Line is {} characters long (max {})", len, self.0))
+    }
+}
+
+/// Flags added lines that are unresolved merge conflict markers
+pub struct ConflictMarkerDetector;
+
+impl LintDetector for ConflictMarkerDetector {
+    fn check(&self, line: &Line) -> Option<String> {
+        is_conflict_marker(&line.content).then(|| "Unresolved merge conflict marker".to_string())
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Critical
+    }
+
+    fn tag(&self) -> &str {
+        "conflict"
+    }
+}
+
+/// Check for a `<<<<<<<`, `=======` or `>>>>>>>` conflict marker line
+pub(crate) fn is_conflict_marker(content: &str) -> bool {
+    content.starts_with("<<<<<<< ")
+        || content.starts_with(">>>>>>> ")
+        || matches!(content.trim_end(), "<<<<<<<" | "=======" | ">>>>>>>")
+}
+
+/// A set of detectors run together
+#[derive(Default)]
+pub struct LintDetectors {
+    detectors: Vec<Box<dyn LintDetector>>,
+}
+
+impl LintDetectors {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a detector
+    pub fn with(mut self, detector: impl LintDetector + 'static) -> Self {
+        self.detectors.push(Box::new(detector));
+        self
+    }
+
+    /// Check if no detectors are enabled
+    pub fn is_empty(&self) -> bool {
+        self.detectors.is_empty()
+    }
+
+    /// Run every detector over the diff
+    pub fn detect(&self, diff: &DiffData) -> Vec<Comment> {
+        self.detectors.iter().flat_map(|d| d.detect(diff)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comment::model::Severity;
+    use crate::diff::DiffParser;
+
+    fn parse(added: &[&str]) -> DiffData {
+        let mut input = format!(
+            "diff --git a/x.rs b/x.rs\n@@ -1,1 +1,{} @@\n ctx \n",
+            added.len() + 1
+        );
+        for line in added {
+            input.push('+');
+            input.push_str(line);
+            input.push('\n');
+        }
+        DiffParser::new().parse(&input).unwrap()
+    }
+
+    #[test]
+    fn test_trailing_whitespace() {
+        let diff = parse(&["clean", "space ", "tab\t"]);
+        let comments = TrailingWhitespaceDetector.detect(&diff);
+
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].content, "Trailing whitespace on this line");
+        assert_eq!(comments[0].severity, Severity::Warning);
+        assert_eq!(comments[0].tags, vec!["style"]);
+        assert_eq!(
+            comments[0].metadata.source.as_deref(),
+            Some(AUTO_DETECT_SOURCE)
+        );
+        assert_eq!(comments[0].metadata.line_number, Some(3));
+    }
+
+    #[test]
+    fn test_conflict_markers() {
+        let diff = parse(&[
+            "<<<<<<< HEAD",
+            "ours",
+            "=======",
+            "theirs",
+            ">>>>>>> feature",
+            "========",
+            "a <<<<<<< b",
+        ]);
+        let comments = ConflictMarkerDetector.detect(&diff);
+
+        assert_eq!(comments.len(), 3);
+        assert_eq!(comments[0].severity, Severity::Critical);
+        assert_eq!(comments[0].tags, vec!["conflict"]);
+        assert_eq!(
+            DiffParser::verify_no_conflict_markers(&diff)
+                .into_iter()
+                .map(|(_, line)| line)
+                .collect::<Vec<_>>(),
+            vec![2, 4, 6]
+        );
+    }
+
+    #[test]
+    fn test_tab_vs_space() {
+        let diff = parse(&["\t\tok", "    ok", " \tmixed", "\t x"]);
+        assert_eq!(TabVsSpaceDetector.detect(&diff).len(), 2);
+    }
+
+    #[test]
+    fn test_long_line_and_aggregate() {
+        let diff = parse(&["short", "this line is too long ", "ok"]);
+        assert_eq!(LongLineDetector(10).detect(&diff).len(), 1);
+
+        let detectors = LintDetectors::new()
+            .with(TrailingWhitespaceDetector)
+            .with(LongLineDetector(10));
+        assert_eq!(detectors.detect(&diff).len(), 2);
+        assert!(LintDetectors::new().is_empty());
+    }
+}
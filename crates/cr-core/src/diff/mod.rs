@@ -6,8 +6,15 @@ pub mod model;
 pub mod parser;
 pub mod navigator;
 pub mod delta;
+pub mod lint;
+pub mod ignore;
 
 pub use model::*;
-pub use parser::{DiffParser, ParserConfig};
-pub use navigator::{DiffNavigator, Position};
+pub use parser::{DiffParser, FileListEntry, ParserConfig, StreamingDiffParser};
+pub use navigator::{BookmarkMap, DiffNavigator, Position, VirtualLine};
 pub use delta::{DeltaRenderer, DeltaConfig};
+pub use ignore::{CrIgnore, CRIGNORE_PATH};
+pub use lint::{
+    ConflictMarkerDetector, LintDetector, LintDetectors, LongLineDetector, TabVsSpaceDetector,
+    TrailingWhitespaceDetector,
+};
@@ -1,10 +1,16 @@
 //! Diff data models
 
+use crate::error::{CrHelperError, Result};
 use crate::types::{Extensions, FileId, HunkId, LineId};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::io::BufRead;
 use std::path::PathBuf;
 
+/// Width of the bars in [`DiffData::stat_summary`]
+pub const STAT_BAR_WIDTH: usize = 40;
+
 /// Complete diff data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiffData {
@@ -26,6 +32,26 @@ impl DiffData {
         }
     }
 
+    /// Parse `git diff` output straight from a reader
+    ///
+    /// Same result as [`DiffParser::parse`](crate::diff::DiffParser::parse)
+    /// without first reading the whole diff into a string. See
+    /// [`DiffParser::parse_reader`](crate::diff::DiffParser::parse_reader)
+    /// to use a configured parser or limit the file count.
+    pub fn from_stream(reader: impl BufRead) -> Result<Self> {
+        crate::diff::DiffParser::new().parse_reader(reader, None)
+    }
+
+    /// Encode as MessagePack, which is much smaller than JSON for large diffs
+    pub fn serialize_compact(&self) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(self).map_err(|e| CrHelperError::MessagePack(e.to_string()))
+    }
+
+    /// Decode diff data written by [`serialize_compact`](Self::serialize_compact)
+    pub fn deserialize_compact(bytes: &[u8]) -> Result<Self> {
+        rmp_serde::from_slice(bytes).map_err(|e| CrHelperError::MessagePack(e.to_string()))
+    }
+
     /// Get total line count across all files
     pub fn total_lines(&self) -> usize {
         self.files.iter().map(|f| f.total_lines()).sum()
@@ -42,6 +68,147 @@ impl DiffData {
             f.new_path.as_ref() == Some(path) || f.old_path.as_ref() == Some(path)
         })
     }
+
+    /// Get the files before and after a file, as `(prev, next)`
+    pub fn get_adjacent_files(&self, file_id: &FileId) -> (Option<&FileDiff>, Option<&FileDiff>) {
+        match self.files.iter().position(|f| &f.id == file_id) {
+            Some(idx) => (
+                idx.checked_sub(1).and_then(|i| self.files.get(i)),
+                self.files.get(idx + 1),
+            ),
+            None => (None, None),
+        }
+    }
+
+    /// Get a file's position as `(index, total_count)`
+    pub fn file_position(&self, file_id: &FileId) -> Option<(usize, usize)> {
+        self.files
+            .iter()
+            .position(|f| &f.id == file_id)
+            .map(|idx| (idx, self.files.len()))
+    }
+
+    /// Get files ordered by display path
+    pub fn files_sorted_by_path(&self) -> Vec<&FileDiff> {
+        let mut files: Vec<&FileDiff> = self.files.iter().collect();
+        files.sort_by(|a, b| a.display_path().cmp(b.display_path()));
+        files
+    }
+
+    /// Per-file `(path, insertions, deletions, bar)`, like `git diff --stat`
+    ///
+    /// Bars are [`STAT_BAR_WIDTH`] wide for a file holding every change.
+    pub fn stat_summary(&self) -> Vec<(PathBuf, usize, usize, String)> {
+        let total: usize = self
+            .files
+            .iter()
+            .map(|f| {
+                let (insertions, deletions) = f.line_changes();
+                insertions + deletions
+            })
+            .sum();
+        self.files
+            .iter()
+            .map(|f| {
+                let (insertions, deletions) = f.line_changes();
+                let bar = f.stat_bar(STAT_BAR_WIDTH, total);
+                (f.display_path().clone(), insertions, deletions, bar)
+            })
+            .collect()
+    }
+
+    /// Split into parts of at most `max_files` files each
+    ///
+    /// Each part keeps the original metadata and gets its own stats.
+    pub fn split(self, max_files: usize) -> Vec<DiffData> {
+        if max_files == 0 || self.files.len() <= max_files {
+            return vec![self];
+        }
+
+        self.files
+            .chunks(max_files)
+            .map(|files| {
+                let mut part = DiffData {
+                    files: files.to_vec(),
+                    metadata: self.metadata.clone(),
+                    stats: DiffStats::default(),
+                };
+                part.stats = DiffStats::from_diff(&part);
+                part
+            })
+            .collect()
+    }
+
+    /// Keep only the first `max_files` files, with fresh stats
+    ///
+    /// Records the original file count (see [`DiffMetadata::total_files`]).
+    /// Returns whether any files were dropped.
+    pub fn truncate_files(&mut self, max_files: usize) -> bool {
+        let total = self.files.len();
+        if total <= max_files {
+            return false;
+        }
+        self.files.truncate(max_files);
+        self.stats = DiffStats::from_diff(self);
+        self.metadata.set_total_files(total);
+        true
+    }
+
+    /// A copy with only the files whose mode is one of `modes`, with fresh stats
+    pub fn filter_by_mode(&self, modes: &[FileMode]) -> DiffData {
+        let mut filtered = DiffData {
+            files: self
+                .files
+                .iter()
+                .filter(|f| modes.contains(&f.mode))
+                .cloned()
+                .collect(),
+            metadata: self.metadata.clone(),
+            stats: DiffStats::default(),
+        };
+        filtered.stats = DiffStats::from_diff(&filtered);
+        filtered
+    }
+
+    /// Counts for each file, in diff order
+    ///
+    /// Reads [`DiffStats::per_file`], computing the counts instead when it
+    /// does not cover every file (sessions saved before it was stored, or
+    /// diffs built without [`DiffStats::from_diff`]).
+    pub fn file_stats(&self) -> Cow<'_, [FileStat]> {
+        if self.stats.per_file.len() == self.files.len() {
+            Cow::Borrowed(&self.stats.per_file)
+        } else {
+            Cow::Owned(DiffStats::per_file_stats(self))
+        }
+    }
+
+    /// Reconstruct the diff as a git-style unified patch
+    ///
+    /// Index lines are not stored, so they are left out. New and deleted
+    /// files get their [`FileDiff::file_mode`], or 100644 when the diff
+    /// header had none. Lazy files that were never loaded contribute only
+    /// their headers.
+    pub fn patch_string(&self) -> String {
+        self.files.iter().map(FileDiff::unified_diff).collect()
+    }
+
+    /// Keep only the `n` hottest files (see [`DiffStats::heat_map`])
+    ///
+    /// File order is preserved. Returns the IDs of the removed files.
+    pub fn retain_hottest(&mut self, n: usize) -> Vec<FileId> {
+        let keep: Vec<FileId> = DiffStats::heat_map(self)
+            .into_iter()
+            .take(n)
+            .map(|(id, _)| id)
+            .collect();
+
+        let (kept, skipped): (Vec<_>, Vec<_>) =
+            self.files.drain(..).partition(|f| keep.contains(&f.id));
+        self.files = kept;
+        self.stats = DiffStats::from_diff(self);
+        skipped.into_iter().map(|f| f.id).collect()
+    }
 }
 
 /// Single file diff
@@ -60,6 +227,11 @@ pub struct FileDiff {
     /// Whether this file's content needs lazy loading
     #[serde(default)]
     pub lazy: bool,
+    /// Git mode bits from the diff header (e.g. `100755`), if it had any
+    ///
+    /// The new side's mode, or the old side's for deleted files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_mode: Option<String>,
 }
 
 impl FileDiff {
@@ -68,6 +240,16 @@ impl FileDiff {
         self.new_path.as_ref().or(self.old_path.as_ref()).unwrap()
     }
 
+    /// Whether the diff header marks this file as executable
+    pub fn is_executable(&self) -> bool {
+        self.file_mode.as_deref() == Some("100755")
+    }
+
+    /// Whether the diff header marks this file as a symlink
+    pub fn is_symlink(&self) -> bool {
+        self.file_mode.as_deref() == Some("120000")
+    }
+
     /// Get total line count
     pub fn total_lines(&self) -> usize {
         self.hunks.iter().map(|h| h.lines.len()).sum()
@@ -78,11 +260,111 @@ impl FileDiff {
         matches!(self.mode, FileMode::Binary)
     }
 
+    /// Number of `(insertions, deletions)`
+    pub fn line_changes(&self) -> (usize, usize) {
+        let lines = self.hunks.iter().flat_map(|h| &h.lines);
+        lines.fold((0, 0), |(ins, del), line| match line.line_type {
+            LineType::Added => (ins + 1, del),
+            LineType::Deleted => (ins, del + 1),
+            _ => (ins, del),
+        })
+    }
+
+    /// A `+++--` bar for this file's share of `total_changes`
+    ///
+    /// A file with all the changes gets `max_width` characters. Files with
+    /// any insertions (or deletions) get at least one `+` (or `-`) where
+    /// the width allows.
+    pub fn stat_bar(&self, max_width: usize, total_changes: usize) -> String {
+        let (insertions, deletions) = self.line_changes();
+        stat_bar(insertions, deletions, max_width, total_changes)
+    }
+
     /// Check if this file needs content to be loaded
     pub fn needs_loading(&self) -> bool {
         self.lazy && self.hunks.is_empty()
     }
 
+    /// Get a single hunk as a unified diff patch (header plus prefixed lines)
+    pub fn patch_for_hunk(&self, hunk_idx: usize) -> Option<String> {
+        let hunk = self.hunks.get(hunk_idx)?;
+
+        let mut patch = if hunk.header.starts_with("@@") {
+            hunk.header.clone()
+        } else {
+            format!(
+                "@@ -{},{} +{},{} @@",
+                hunk.old_range.start, hunk.old_range.count, hunk.new_range.start, hunk.new_range.count
+            )
+        };
+        patch.push('\n');
+        for line in &hunk.lines {
+            // NoNewline markers already carry their leading backslash
+            if line.line_type != LineType::NoNewline {
+                patch.push(line.line_type.prefix());
+            }
+            patch.push_str(&line.content);
+            patch.push('\n');
+        }
+
+        Some(patch)
+    }
+
+    /// Get the whole file as a unified diff with `---`/`+++` headers
+    ///
+    /// Binary files get git's "Binary files ... differ" line, and files
+    /// without hunks (pure renames, unloaded lazy files) only their
+    /// `diff --git` header.
+    pub fn unified_diff(&self) -> String {
+        let path = self.display_path().to_string_lossy();
+        let old_path = self
+            .old_path
+            .as_ref()
+            .map(|p| p.to_string_lossy())
+            .unwrap_or_else(|| path.clone());
+        let new_path = self
+            .new_path
+            .as_ref()
+            .map(|p| p.to_string_lossy())
+            .unwrap_or_else(|| path.clone());
+
+        let mut diff = format!("diff --git a/{} b/{}\n", old_path, new_path);
+        let file_mode = self.file_mode.as_deref().unwrap_or("100644");
+        match self.mode {
+            FileMode::Added => diff.push_str(&format!("new file mode {}\n", file_mode)),
+            FileMode::Deleted => diff.push_str(&format!("deleted file mode {}\n", file_mode)),
+            FileMode::Renamed => {
+                diff.push_str(&format!("rename from {}\n", old_path));
+                diff.push_str(&format!("rename to {}\n", new_path));
+            }
+            FileMode::Binary => {
+                diff.push_str(&format!("Binary files a/{} and b/{} differ\n", old_path, new_path));
+                return diff;
+            }
+            FileMode::Modified | FileMode::Copied => {}
+        }
+        if self.hunks.is_empty() {
+            return diff;
+        }
+
+        match self.mode {
+            FileMode::Added => diff.push_str("--- /dev/null\n"),
+            _ => diff.push_str(&format!("--- a/{}\n", old_path)),
+        }
+        match self.mode {
+            FileMode::Deleted => diff.push_str("+++ /dev/null\n"),
+            _ => diff.push_str(&format!("+++ b/{}\n", new_path)),
+        }
+
+        for hunk_idx in 0..self.hunks.len() {
+            if let Some(patch) = self.patch_for_hunk(hunk_idx) {
+                diff.push_str(&patch);
+            }
+        }
+
+        diff
+    }
+
     /// Create a lazy file entry (content loaded on demand)
     pub fn lazy_new(path: PathBuf) -> Self {
         Self {
@@ -92,6 +374,7 @@ impl FileDiff {
             mode: FileMode::Added,
             hunks: Vec::new(),
             lazy: true,
+            file_mode: None,
         }
     }
 }
@@ -176,6 +459,10 @@ pub struct Line {
     pub old_line_num: Option<usize>,
     /// Line number in new file
     pub new_line_num: Option<usize>,
+    /// Words of the change this line belongs to, from `git diff --word-diff`
+    /// (see [`DiffParser::parse_word_diff`](crate::diff::DiffParser::parse_word_diff))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_diff: Option<Vec<WordSpan>>,
 }
 
 impl Line {
@@ -183,6 +470,47 @@ impl Line {
     pub fn display_line_num(&self) -> Option<usize> {
         self.new_line_num.or(self.old_line_num)
     }
+
+    /// The word spans that spell out this line's content
+    ///
+    /// Added lines keep context and added words, deleted lines context and
+    /// deleted words. `None` without a word diff, or if the spans don't add
+    /// up to [`Line::content`].
+    pub fn side_word_spans(&self) -> Option<Vec<&WordSpan>> {
+        let other_side = match self.line_type {
+            LineType::Added => WordKind::Deleted,
+            LineType::Deleted => WordKind::Added,
+            _ => return None,
+        };
+        let spans: Vec<&WordSpan> = self
+            .word_diff
+            .as_ref()?
+            .iter()
+            .filter(|span| span.kind != other_side)
+            .collect();
+        let text: String = spans.iter().map(|span| span.text.as_str()).collect();
+        (text == self.content).then_some(spans)
+    }
+}
+
+/// A run of words in a word diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordSpan {
+    /// Whether the words were added, deleted or kept
+    pub kind: WordKind,
+    /// The words, including surrounding whitespace
+    pub text: String,
+}
+
+/// Kind of a [`WordSpan`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WordKind {
+    /// Words only in the new line
+    Added,
+    /// Words only in the old line
+    Deleted,
+    /// Words in both lines
+    Context,
 }
 
 /// Type of line change
@@ -224,6 +552,52 @@ pub struct DiffMetadata {
     pub extensions: Extensions,
 }
 
+/// Extension key for the extra `git diff` flags a diff was produced with
+pub const DIFF_FLAGS_KEY: &str = "diff_flags";
+
+/// Extension key for the byte offset where [`DiffParser::parse_partial`](super::DiffParser::parse_partial) stopped
+pub const TRUNCATED_AT_OFFSET_KEY: &str = "truncated_at_offset";
+
+/// Extension key for the file count of a truncated diff's full input
+pub const TOTAL_FILES_KEY: &str = "total_files";
+
+impl DiffMetadata {
+    /// Non-default `git diff` flags (e.g. `-U10`, `-w`) used for this diff
+    pub fn diff_flags(&self) -> Vec<String> {
+        self.extensions.get_as(DIFF_FLAGS_KEY).unwrap_or_default()
+    }
+
+    /// Record the non-default `git diff` flags used for this diff
+    pub fn set_diff_flags(&mut self, flags: &[String]) {
+        if flags.is_empty() {
+            self.extensions.remove(DIFF_FLAGS_KEY);
+        } else {
+            self.extensions.set(DIFF_FLAGS_KEY, flags);
+        }
+    }
+
+    /// Byte offset of the first unparsed file if the diff was truncated
+    pub fn truncated_at_offset(&self) -> Option<usize> {
+        self.extensions.get_as(TRUNCATED_AT_OFFSET_KEY)
+    }
+
+    /// Number of files in the full diff, if it was truncated
+    pub fn total_files(&self) -> Option<usize> {
+        self.extensions.get_as(TOTAL_FILES_KEY)
+    }
+
+    /// Record that parsing stopped at `offset` of an input with `total_files` files
+    pub fn set_truncation(&mut self, offset: usize, total_files: usize) {
+        self.extensions.set(TRUNCATED_AT_OFFSET_KEY, offset);
+        self.set_total_files(total_files);
+    }
+
+    /// Record that only some of `total_files` files were kept
+    pub fn set_total_files(&mut self, total_files: usize) {
+        self.extensions.set(TOTAL_FILES_KEY, total_files);
+    }
+}
+
 impl Default for DiffMetadata {
     fn default() -> Self {
         Self {
@@ -266,6 +640,25 @@ impl DiffSource {
     }
 }
 
+/// `+++--` bar for `insertions` and `deletions` out of `total_changes`
+/// (see [`FileDiff::stat_bar`])
+fn stat_bar(insertions: usize, deletions: usize, max_width: usize, total_changes: usize) -> String {
+    let changes = insertions + deletions;
+    if changes == 0 || total_changes == 0 || max_width == 0 {
+        return String::new();
+    }
+
+    let width = (changes * max_width).div_ceil(total_changes).min(max_width);
+    let mut plus = (insertions * width + changes / 2) / changes;
+    if insertions > 0 && plus == 0 {
+        plus = 1;
+    }
+    if deletions > 0 && plus == width && width > 1 {
+        plus -= 1;
+    }
+    format!("{}{}", "+".repeat(plus), "-".repeat(width - plus))
+}
+
 /// Diff statistics
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DiffStats {
@@ -275,37 +668,118 @@ pub struct DiffStats {
     pub insertions: usize,
     /// Number of deletions
     pub deletions: usize,
+    /// Counts for each file, in diff order; read it through [`DiffData::file_stats`]
+    #[serde(default)]
+    pub per_file: Vec<FileStat>,
+}
+
+/// Line and hunk counts for one file of a diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStat {
+    /// File ID
+    pub file_id: FileId,
+    /// Display path (see [`FileDiff::display_path`])
+    pub path: PathBuf,
+    /// File change mode
+    pub mode: FileMode,
+    /// Number of insertions
+    pub insertions: usize,
+    /// Number of deletions
+    pub deletions: usize,
+    /// Number of hunks
+    pub hunks: usize,
+}
+
+impl FileStat {
+    /// Insertions plus deletions
+    pub fn changes(&self) -> usize {
+        self.insertions + self.deletions
+    }
+
+    /// A `+++--` bar for this file's share of `total_changes` (see [`FileDiff::stat_bar`])
+    pub fn stat_bar(&self, max_width: usize, total_changes: usize) -> String {
+        stat_bar(self.insertions, self.deletions, max_width, total_changes)
+    }
 }
 
 impl DiffStats {
     /// Calculate stats from diff data
     pub fn from_diff(diff: &DiffData) -> Self {
-        let mut insertions = 0;
-        let mut deletions = 0;
-
-        for file in &diff.files {
-            for hunk in &file.hunks {
-                for line in &hunk.lines {
-                    match line.line_type {
-                        LineType::Added => insertions += 1,
-                        LineType::Deleted => deletions += 1,
-                        _ => {}
-                    }
-                }
-            }
-        }
-
+        let per_file = Self::per_file_stats(diff);
         Self {
             files_changed: diff.files.len(),
-            insertions,
-            deletions,
+            insertions: per_file.iter().map(|f| f.insertions).sum(),
+            deletions: per_file.iter().map(|f| f.deletions).sum(),
+            per_file,
         }
     }
+
+    /// Insertions, deletions and hunks of each file, in diff order
+    pub fn per_file_stats(diff: &DiffData) -> Vec<FileStat> {
+        diff.files
+            .iter()
+            .map(|file| {
+                let (insertions, deletions) = file.line_changes();
+                FileStat {
+                    file_id: file.id.clone(),
+                    path: file.display_path().clone(),
+                    mode: file.mode,
+                    insertions,
+                    deletions,
+                    hunks: file.hunks.len(),
+                }
+            })
+            .collect()
+    }
+
+    /// The file with the most changed lines (the first of equals), if any
+    /// file has changes
+    pub fn hottest_file(per_file: &[FileStat]) -> Option<&FileStat> {
+        per_file
+            .iter()
+            .filter(|f| f.changes() > 0)
+            .rev()
+            .max_by_key(|f| f.changes())
+    }
+
+    /// Normalized churn score per file, hottest first
+    ///
+    /// Each file scores `(insertions + deletions) / max_churn_across_files`,
+    /// so the most-changed file scores 1.0.
+    pub fn heat_map(diff: &DiffData) -> Vec<(FileId, f64)> {
+        let churn: Vec<(FileId, usize)> = diff
+            .files
+            .iter()
+            .map(|file| {
+                let changed = file
+                    .hunks
+                    .iter()
+                    .flat_map(|h| &h.lines)
+                    .filter(|l| matches!(l.line_type, LineType::Added | LineType::Deleted))
+                    .count();
+                (file.id.clone(), changed)
+            })
+            .collect();
+
+        let max = churn.iter().map(|(_, c)| *c).max().unwrap_or(0);
+        let mut scores: Vec<(FileId, f64)> = churn
+            .into_iter()
+            .map(|(id, c)| {
+                let score = if max == 0 { 0.0 } else { c as f64 / max as f64 };
+                (id, score)
+            })
+            .collect();
+
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scores
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::diff::parser::DiffParser;
+    use proptest::prelude::*;
 
     #[test]
     fn test_diff_data_empty() {
@@ -351,4 +825,326 @@ mod tests {
         assert_eq!(range.count, 5);
         assert_eq!(range.end(), 15);
     }
+
+    const SAMPLE_DIFF: &str = r#"diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,3 @@ fn main() {
+ fn main() {
+-    old();
++    new();
+@@ -10,1 +10,2 @@
+ tail
++added
+\ No newline at end of file
+"#;
+
+    #[test]
+    fn test_patch_for_hunk() {
+        let diff = DiffParser::new().parse(SAMPLE_DIFF).unwrap();
+        let file = &diff.files[0];
+
+        let patch = file.patch_for_hunk(0).unwrap();
+        assert_eq!(
+            patch,
+            "@@ -1,3 +1,3 @@ fn main() {\n fn main() {\n-    old();\n+    new();\n"
+        );
+
+        let patch = file.patch_for_hunk(1).unwrap();
+        assert!(patch.ends_with("+added\n\\ No newline at end of file\n"));
+
+        assert!(file.patch_for_hunk(2).is_none());
+    }
+
+    #[test]
+    fn test_adjacent_files() {
+        let diff = DiffParser::new()
+            .parse(
+                "diff --git a/z.rs b/z.rs\n--- a/z.rs\n+++ b/z.rs\n@@ -1 +1 @@\n-a\n+b\n\
+                 diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-a\n+b\n",
+            )
+            .unwrap();
+        let (z, a) = (&diff.files[0], &diff.files[1]);
+
+        let (prev, next) = diff.get_adjacent_files(&z.id);
+        assert!(prev.is_none());
+        assert_eq!(next.map(|f| &f.id), Some(&a.id));
+        let (prev, next) = diff.get_adjacent_files(&a.id);
+        assert_eq!(prev.map(|f| &f.id), Some(&z.id));
+        assert!(next.is_none());
+
+        assert_eq!(diff.file_position(&a.id), Some((1, 2)));
+        assert_eq!(diff.files_sorted_by_path()[0].id, a.id);
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let diff = DiffParser::new().parse(SAMPLE_DIFF).unwrap();
+        let unified = diff.files[0].unified_diff();
+
+        assert!(unified.starts_with("diff --git a/src/main.rs b/src/main.rs\n"));
+        assert!(unified.contains("--- a/src/main.rs\n+++ b/src/main.rs\n"));
+        assert!(unified.contains("@@ -10,1 +10,2 @@\n"));
+    }
+
+    #[test]
+    fn test_serialize_compact() {
+        let mut diff = DiffParser::new().parse(SAMPLE_DIFF).unwrap();
+        diff.metadata.set_diff_flags(&["-w".to_string()]);
+
+        let bytes = diff.serialize_compact().unwrap();
+        assert!(bytes.len() < serde_json::to_vec(&diff).unwrap().len());
+
+        let decoded = DiffData::deserialize_compact(&bytes).unwrap();
+        assert_eq!(decoded.files.len(), diff.files.len());
+        assert_eq!(decoded.files[0].hunks[0].lines.len(), diff.files[0].hunks[0].lines.len());
+        assert_eq!(decoded.metadata.diff_flags(), vec!["-w"]);
+        assert!(DiffData::deserialize_compact(b"not msgpack").is_err());
+    }
+
+    #[test]
+    fn test_unified_diff_added_file() {
+        let input = "diff --git a/new.rs b/new.rs\nnew file mode 100644\n--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1 @@\n+hello\n";
+        let diff = DiffParser::new().parse(input).unwrap();
+        let unified = diff.files[0].unified_diff();
+
+        assert!(unified.contains("--- /dev/null\n"));
+        let reparsed = DiffParser::new().parse(&unified).unwrap();
+        assert_eq!(reparsed.files[0].mode, FileMode::Added);
+    }
+
+    #[test]
+    fn test_stat_bar() {
+        let diff = crate::diff::DiffParser::new()
+            .parse(
+                "diff --git a/a.rs b/a.rs\n@@ -1,2 +1,4 @@\n x\n-y\n+a\n+b\n+c\n\
+                 diff --git a/b.rs b/b.rs\n@@ -1,5 +1,1 @@\n x\n-a\n-b\n-c\n-d\n",
+            )
+            .unwrap();
+
+        let a = &diff.files[0];
+        assert_eq!(a.line_changes(), (3, 1));
+        assert_eq!(a.stat_bar(8, 4), "++++++--");
+        assert_eq!(a.stat_bar(16, 8), "++++++--");
+        assert_eq!(a.stat_bar(8, 100), "+");
+        assert_eq!(a.stat_bar(8, 0), "");
+
+        let summary = diff.stat_summary();
+        assert_eq!(summary[1].0, PathBuf::from("b.rs"));
+        assert_eq!((summary[1].1, summary[1].2), (0, 4));
+        assert_eq!(summary[1].3, "-".repeat(STAT_BAR_WIDTH / 2));
+    }
+
+    #[test]
+    fn test_split() {
+        let input = (0..5)
+            .map(|i| format!("diff --git a/f{i}.rs b/f{i}.rs\n@@ -1 +1,2 @@\n a\n+b\n"))
+            .collect::<String>();
+        let diff = DiffParser::new().parse(&input).unwrap();
+
+        let parts = diff.clone().split(2);
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].files.len(), 2);
+        assert_eq!(parts[2].files.len(), 1);
+        assert_eq!(parts[2].stats.files_changed, 1);
+        assert_eq!(parts[2].stats.insertions, 1);
+
+        assert_eq!(diff.clone().split(10).len(), 1);
+        assert_eq!(diff.split(0).len(), 1);
+    }
+
+    #[test]
+    fn test_truncate_files() {
+        let input = (0..5)
+            .map(|i| format!("diff --git a/f{i}.rs b/f{i}.rs\n@@ -1 +1,2 @@\n a\n+b\n"))
+            .collect::<String>();
+        let mut diff = DiffParser::new().parse(&input).unwrap();
+
+        assert!(!diff.truncate_files(5));
+        assert_eq!(diff.metadata.total_files(), None);
+
+        assert!(diff.truncate_files(2));
+        assert_eq!(diff.files.len(), 2);
+        assert_eq!(diff.stats.files_changed, 2);
+        assert_eq!(diff.metadata.total_files(), Some(5));
+        assert_eq!(diff.metadata.truncated_at_offset(), None);
+    }
+
+    #[test]
+    fn test_filter_by_mode() {
+        let input = "diff --git a/new.rs b/new.rs\nnew file mode 100644\n--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1,2 @@\n+a\n+b\n\
+                     diff --git a/old.rs b/old.rs\ndeleted file mode 100644\n--- a/old.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-a\n\
+                     diff --git a/lib.rs b/lib.rs\n@@ -1 +1,2 @@\n a\n+b\n";
+        let diff = DiffParser::new().parse(input).unwrap();
+
+        let deleted = diff.filter_by_mode(&[FileMode::Deleted]);
+        assert_eq!(deleted.files.len(), 1);
+        assert_eq!(deleted.files[0].display_path(), &PathBuf::from("old.rs"));
+        assert_eq!(deleted.stats.files_changed, 1);
+        assert_eq!(deleted.stats.insertions, 0);
+        assert_eq!(deleted.stats.deletions, 1);
+
+        let changed = diff.filter_by_mode(&[FileMode::Added, FileMode::Modified]);
+        assert_eq!(changed.files.len(), 2);
+        assert_eq!(changed.stats.insertions, 3);
+        assert!(diff.filter_by_mode(&[FileMode::Renamed]).files.is_empty());
+    }
+
+    #[test]
+    fn test_unified_diff_keeps_file_mode() {
+        let input = "diff --git a/run.sh b/run.sh\nnew file mode 100755\nindex 0000000..1111111\n--- /dev/null\n+++ b/run.sh\n@@ -0,0 +1 @@\n+echo hi\n\
+                     diff --git a/link b/link\nindex 2222222..3333333 120000\n--- a/link\n+++ b/link\n@@ -1 +1 @@\n-old\n\\ No newline at end of file\n+new\n\\ No newline at end of file\n";
+        let diff = crate::diff::DiffParser::new().parse(input).unwrap();
+
+        assert!(diff.files[0].is_executable());
+        assert!(diff.files[1].is_symlink());
+        assert!(diff.files[0].unified_diff().contains("new file mode 100755\n"));
+        assert!(!diff.files[0].unified_diff().contains("100644"));
+    }
+
+    #[test]
+    fn test_patch_string_round_trip() {
+        let input = "diff --git a/new.rs b/new.rs\nnew file mode 100644\n--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1,2 @@\n+a\n+b\n\
+                     diff --git a/lib.rs b/lib.rs\n--- a/lib.rs\n+++ b/lib.rs\n@@ -1,2 +1,2 @@ fn main() {\n a\n-b\n+c\n\\ No newline at end of file\n\
+                     diff --git a/old.rs b/old.rs\ndeleted file mode 100644\n--- a/old.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-a\n";
+        let diff = DiffParser::new().parse(input).unwrap();
+
+        let patch = diff.patch_string();
+        assert_eq!(patch, input);
+        let reparsed = DiffParser::new().parse(&patch).unwrap();
+        assert_eq!(reparsed.files.len(), 3);
+        assert_eq!(reparsed.stats.insertions, diff.stats.insertions);
+    }
+
+    #[test]
+    fn test_heat_map() {
+        let input = "diff --git a/cold.rs b/cold.rs\n@@ -1 +1,2 @@\n a\n+b\n\
+                     diff --git a/hot.rs b/hot.rs\n@@ -1,2 +1,3 @@\n-a\n-b\n+c\n+d\n+e\n";
+        let diff = DiffParser::new().parse(input).unwrap();
+
+        let heat = DiffStats::heat_map(&diff);
+        assert_eq!(heat.len(), 2);
+        assert_eq!(heat[0].0, diff.files[1].id);
+        assert_eq!(heat[0].1, 1.0);
+        assert!((heat[1].1 - 0.2).abs() < f64::EPSILON);
+
+        assert!(DiffStats::heat_map(&DiffData::empty()).is_empty());
+    }
+
+    #[test]
+    fn test_per_file_stats() {
+        let input = "diff --git a/a.rs b/a.rs\n@@ -1 +1,2 @@\n a\n+b\n@@ -9 +10 @@\n-x\n+y\n\
+                     diff --git a/b.rs b/b.rs\ndeleted file mode 100644\n@@ -1,3 +0,0 @@\n-a\n-b\n-c\n\
+                     diff --git a/c.rs b/c.rs\n@@ -1 +1,2 @@\n a\n+b\n";
+        let diff = DiffParser::new().parse(input).unwrap();
+
+        let stats = DiffStats::per_file_stats(&diff);
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[0].file_id, diff.files[0].id);
+        assert_eq!((stats[0].insertions, stats[0].deletions, stats[0].hunks), (2, 1, 2));
+        assert_eq!(stats[1].path, PathBuf::from("b.rs"));
+        assert_eq!(stats[1].mode, FileMode::Deleted);
+        assert_eq!(stats[1].changes(), 3);
+        assert_eq!(diff.stats.per_file, stats);
+        assert_eq!(diff.stats.insertions, 3);
+
+        // Ties go to the first file
+        assert_eq!(DiffStats::hottest_file(&stats).unwrap().path, PathBuf::from("a.rs"));
+        assert!(DiffStats::hottest_file(&[]).is_none());
+
+        let json = serde_json::to_value(&diff.stats).unwrap();
+        assert_eq!(json["per_file"][1]["mode"], "Deleted");
+        let legacy: DiffStats =
+            serde_json::from_str(r#"{"files_changed": 1, "insertions": 2, "deletions": 0}"#).unwrap();
+        assert!(legacy.per_file.is_empty());
+
+        assert!(matches!(diff.file_stats(), Cow::Borrowed(_)));
+        let mut stale = diff.clone();
+        stale.stats.per_file.clear();
+        assert_eq!(stale.file_stats().as_ref(), stats.as_slice());
+    }
+
+    #[test]
+    fn test_retain_hottest() {
+        let input = "diff --git a/a.rs b/a.rs\n@@ -1 +1,2 @@\n a\n+b\n\
+                     diff --git a/b.rs b/b.rs\n@@ -1 +1,3 @@\n a\n+b\n+c\n\
+                     diff --git a/c.rs b/c.rs\n@@ -1 +1,4 @@\n a\n+b\n+c\n+d\n";
+        let mut diff = DiffParser::new().parse(input).unwrap();
+        let a = diff.files[0].id.clone();
+
+        let skipped = diff.retain_hottest(2);
+        assert_eq!(skipped, vec![a]);
+        let paths: Vec<_> = diff.files.iter().map(|f| f.display_path().clone()).collect();
+        assert_eq!(paths, vec![PathBuf::from("b.rs"), PathBuf::from("c.rs")]);
+        assert_eq!(diff.stats.files_changed, 2);
+        assert_eq!(diff.stats.insertions, 5);
+    }
+
+    fn line_strategy() -> impl Strategy<Value = (LineType, String)> {
+        (
+            prop_oneof![
+                Just(LineType::Added),
+                Just(LineType::Deleted),
+                Just(LineType::Context),
+            ],
+            "[a-zA-Z0-9 _(){};=]{0,24}",
+        )
+    }
+
+    fn render_hunk(old_start: usize, new_start: usize, lines: &[(LineType, String)]) -> String {
+        let old_count = lines.iter().filter(|(t, _)| *t != LineType::Added).count();
+        let new_count = lines.iter().filter(|(t, _)| *t != LineType::Deleted).count();
+        let mut hunk = format!("@@ -{},{} +{},{} @@\n", old_start, old_count, new_start, new_count);
+        for (line_type, content) in lines {
+            hunk.push(line_type.prefix());
+            hunk.push_str(content);
+            hunk.push('\n');
+        }
+        hunk
+    }
+
+    proptest! {
+        #[test]
+        fn prop_patch_roundtrips(
+            hunks in prop::collection::vec(
+                (1usize..500, 1usize..500, prop::collection::vec(line_strategy(), 1..20)),
+                1..5,
+            )
+        ) {
+            let mut input = String::from("diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n");
+            for (old_start, new_start, lines) in &hunks {
+                input.push_str(&render_hunk(*old_start, *new_start, lines));
+            }
+
+            let parser = DiffParser::new();
+            let original = parser.parse(&input).unwrap();
+            let file = &original.files[0];
+
+            // Each hunk patch parses back to the same hunk
+            for (idx, hunk) in file.hunks.iter().enumerate() {
+                let patch = file.patch_for_hunk(idx).unwrap();
+                let wrapped = format!("diff --git a/src/lib.rs b/src/lib.rs\n{}", patch);
+                let reparsed = parser.parse(&wrapped).unwrap();
+                let reparsed_hunk = &reparsed.files[0].hunks[0];
+
+                prop_assert_eq!(&reparsed_hunk.header, &hunk.header);
+                prop_assert_eq!(reparsed_hunk.old_range.start, hunk.old_range.start);
+                prop_assert_eq!(reparsed_hunk.new_range.count, hunk.new_range.count);
+                prop_assert_eq!(reparsed_hunk.lines.len(), hunk.lines.len());
+                for (a, b) in reparsed_hunk.lines.iter().zip(&hunk.lines) {
+                    prop_assert_eq!(a.line_type, b.line_type);
+                    prop_assert_eq!(&a.content, &b.content);
+                    prop_assert_eq!(a.old_line_num, b.old_line_num);
+                    prop_assert_eq!(a.new_line_num, b.new_line_num);
+                }
+            }
+
+            // The full unified diff parses back to the same structure
+            let reparsed = parser.parse(&file.unified_diff()).unwrap();
+            prop_assert_eq!(reparsed.files.len(), 1);
+            prop_assert_eq!(reparsed.files[0].hunks.len(), file.hunks.len());
+            prop_assert_eq!(reparsed.stats.insertions, original.stats.insertions);
+            prop_assert_eq!(reparsed.stats.deletions, original.stats.deletions);
+        }
+    }
 }
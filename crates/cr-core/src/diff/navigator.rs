@@ -1,9 +1,11 @@
 //! Diff navigation logic
 
+use crate::comment::{Comment, CommentManager};
 use crate::diff::model::{DiffData, FileDiff, Hunk, Line};
+use std::collections::HashMap;
 
 /// Position in a diff
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Position {
     /// Current file index
     pub file_idx: usize,
@@ -18,12 +20,96 @@ impl Position {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Position of the `line`th line of a file, counting across hunks
+    pub fn from_file_line(diff: &DiffData, file_idx: usize, line: usize) -> Option<Self> {
+        let mut start = 0;
+        for (hunk_idx, hunk) in diff.files.get(file_idx)?.hunks.iter().enumerate() {
+            if line < start + hunk.lines.len() {
+                return Some(Self {
+                    file_idx,
+                    hunk_idx,
+                    line_idx: line - start,
+                });
+            }
+            start += hunk.lines.len();
+        }
+        None
+    }
+
+    /// Index of this position's line within its file, counting across hunks
+    pub fn file_line(&self, diff: &DiffData) -> usize {
+        let before: usize = diff
+            .files
+            .get(self.file_idx)
+            .map(|f| {
+                f.hunks
+                    .iter()
+                    .take(self.hunk_idx)
+                    .map(|h| h.lines.len())
+                    .sum()
+            })
+            .unwrap_or(0);
+        before + self.line_idx
+    }
+}
+
+/// Named positions, like vim marks
+#[derive(Debug, Clone, Default)]
+pub struct BookmarkMap {
+    /// Positions by bookmark name
+    pub bookmarks: HashMap<String, Position>,
+}
+
+impl BookmarkMap {
+    /// Create an empty bookmark map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set (or move) a bookmark
+    pub fn set(&mut self, name: impl Into<String>, position: Position) {
+        self.bookmarks.insert(name.into(), position);
+    }
+
+    /// Get a bookmark's position
+    pub fn get(&self, name: &str) -> Option<Position> {
+        self.bookmarks.get(name).copied()
+    }
+
+    /// Bookmarks sorted by name
+    pub fn sorted(&self) -> Vec<(&str, Position)> {
+        let mut marks: Vec<_> = self
+            .bookmarks
+            .iter()
+            .map(|(name, pos)| (name.as_str(), *pos))
+            .collect();
+        marks.sort_by_key(|(name, _)| *name);
+        marks
+    }
+
+    /// Check if there are no bookmarks
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+}
+
+/// One row of the current file as the review screen shows it
+#[derive(Debug, Clone)]
+pub enum VirtualLine<'a> {
+    /// A hunk's `@@ ... @@` header
+    HunkHeader(String),
+    /// A diff line and its index within the file, counting across hunks
+    DiffLine(usize, &'a Line),
+    /// An inline comment below the diff line with the given index
+    CommentLine(usize, &'a Comment),
 }
 
 /// Navigator for traversing diff data
 pub struct DiffNavigator {
     diff: DiffData,
     position: Position,
+    bookmarks: BookmarkMap,
 }
 
 impl DiffNavigator {
@@ -32,6 +118,7 @@ impl DiffNavigator {
         Self {
             diff,
             position: Position::new(),
+            bookmarks: BookmarkMap::new(),
         }
     }
 
@@ -40,11 +127,43 @@ impl DiffNavigator {
         self.position
     }
 
+    /// Move to a position without bounds checks beyond the file index
+    pub fn set_position(&mut self, position: Position) -> bool {
+        if position.file_idx >= self.diff.files.len() {
+            return false;
+        }
+        self.position = position;
+        true
+    }
+
+    /// Save the current position under `name`
+    pub fn bookmark(&mut self, name: &str) {
+        self.bookmarks.set(name, self.position);
+    }
+
+    /// Jump to a bookmark, returning `false` if it doesn't exist
+    pub fn goto_bookmark(&mut self, name: &str) -> bool {
+        match self.bookmarks.get(name) {
+            Some(position) => self.set_position(position),
+            None => false,
+        }
+    }
+
+    /// Get the bookmarks
+    pub fn bookmarks(&self) -> &BookmarkMap {
+        &self.bookmarks
+    }
+
     /// Get reference to the diff data
     pub fn diff(&self) -> &DiffData {
         &self.diff
     }
 
+    /// Get mutable access to the diff data (e.g. to load lazy files)
+    pub fn diff_mut(&mut self) -> &mut DiffData {
+        &mut self.diff
+    }
+
     /// Move to next line
     pub fn next_line(&mut self) -> bool {
         if let Some(hunk) = self.current_hunk() {
@@ -152,6 +271,32 @@ impl DiffNavigator {
         false
     }
 
+    /// Find a file index by path
+    ///
+    /// Exact matches win; otherwise the first file whose path ends with
+    /// `path` on a component boundary (e.g. `main.rs` matches `src/main.rs`).
+    pub fn find_file_by_path(&self, path: &str) -> Option<usize> {
+        let paths: Vec<_> = self
+            .diff
+            .files
+            .iter()
+            .map(|f| f.display_path().to_string_lossy().into_owned())
+            .collect();
+
+        paths.iter().position(|p| p == path).or_else(|| {
+            let suffix = format!("/{}", path.trim_start_matches('/'));
+            paths.iter().position(|p| p.ends_with(&suffix))
+        })
+    }
+
+    /// Go to a file by path
+    pub fn goto_file_by_path(&mut self, path: &str) -> bool {
+        match self.find_file_by_path(path) {
+            Some(idx) => self.goto_file(idx),
+            None => false,
+        }
+    }
+
     /// Go to a specific line within a file
     pub fn goto_line(&mut self, file_idx: usize, global_line_idx: usize) -> bool {
         if !self.goto_file(file_idx) {
@@ -240,6 +385,81 @@ impl DiffNavigator {
         }
     }
 
+    /// Rows `scroll_offset..scroll_offset + viewport_height` of the current
+    /// file, or fewer at the end of the file
+    ///
+    /// Each hunk is a header row followed by its lines, each line followed by
+    /// its comments. Hunks above the offset are skipped by counting, so only
+    /// the returned rows are built.
+    pub fn virtual_scroll_lines<'a>(
+        &'a self,
+        scroll_offset: usize,
+        viewport_height: usize,
+        comments: &'a CommentManager,
+    ) -> Vec<VirtualLine<'a>> {
+        let mut rows = Vec::with_capacity(viewport_height);
+        let Some(file) = self.current_file() else {
+            return rows;
+        };
+
+        let mut skip = scroll_offset;
+        let mut line_idx = 0;
+        for hunk in &file.hunks {
+            if rows.len() >= viewport_height {
+                break;
+            }
+            let hunk_rows = 1 + hunk_virtual_lines(hunk, comments);
+            if skip >= hunk_rows {
+                skip -= hunk_rows;
+                line_idx += hunk.lines.len();
+                continue;
+            }
+
+            if skip == 0 {
+                rows.push(VirtualLine::HunkHeader(hunk.header.clone()));
+            } else {
+                skip -= 1;
+            }
+            for line in &hunk.lines {
+                if rows.len() >= viewport_height {
+                    break;
+                }
+                let count = comments.count_by_line(&line.id);
+                if skip > count {
+                    skip -= count + 1;
+                    line_idx += 1;
+                    continue;
+                }
+
+                if skip == 0 {
+                    rows.push(VirtualLine::DiffLine(line_idx, line));
+                } else {
+                    skip -= 1;
+                }
+                for comment in comments.get_by_line(&line.id).into_iter().skip(skip) {
+                    rows.push(VirtualLine::CommentLine(line_idx, comment));
+                }
+                skip = 0;
+                line_idx += 1;
+            }
+        }
+        rows.truncate(viewport_height);
+        rows
+    }
+
+    /// Number of rows [`virtual_scroll_lines`](Self::virtual_scroll_lines)
+    /// can return for the current file
+    pub fn total_virtual_lines(&self, comments: &CommentManager) -> usize {
+        self.current_file()
+            .map(|f| {
+                f.hunks
+                    .iter()
+                    .map(|h| 1 + hunk_virtual_lines(h, comments))
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+
     /// Get global line index (across all files)
     pub fn global_line_index(&self) -> usize {
         let mut index = 0;
@@ -264,6 +484,14 @@ impl DiffNavigator {
     }
 }
 
+/// Rows for a hunk's lines and their comments, without the header
+fn hunk_virtual_lines(hunk: &Hunk, comments: &CommentManager) -> usize {
+    hunk.lines
+        .iter()
+        .map(|l| 1 + comments.count_by_line(&l.id))
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -337,6 +565,92 @@ diff --git a/file2.rs b/file2.rs
         assert!(nav.current_line().is_some());
     }
 
+    #[test]
+    fn test_find_file_by_path() {
+        let nav = create_test_navigator();
+        assert_eq!(nav.find_file_by_path("file2.rs"), Some(1));
+        assert_eq!(nav.find_file_by_path("missing.rs"), None);
+    }
+
+    #[test]
+    fn test_find_file_by_path_suffix() {
+        let diff_str = r#"diff --git a/src/main.rs b/src/main.rs
+@@ -1 +1 @@
+-a
++b
+diff --git a/src/domain.rs b/src/domain.rs
+@@ -1 +1 @@
+-a
++b
+"#;
+        let diff = DiffParser::new().parse(diff_str).unwrap();
+        let mut nav = DiffNavigator::new(diff);
+
+        assert_eq!(nav.find_file_by_path("src/domain.rs"), Some(1));
+        assert_eq!(nav.find_file_by_path("main.rs"), Some(0));
+        assert_eq!(nav.find_file_by_path("ain.rs"), None);
+
+        assert!(nav.goto_file_by_path("domain.rs"));
+        assert_eq!(nav.current_position().file_idx, 1);
+        assert!(!nav.goto_file_by_path("missing.rs"));
+        assert_eq!(nav.current_position().file_idx, 1);
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let mut nav = create_test_navigator();
+        nav.move_down(2);
+        nav.bookmark("a");
+        let marked = nav.current_position();
+
+        nav.goto_bottom();
+        assert!(nav.goto_bookmark("a"));
+        assert_eq!(nav.current_position(), marked);
+        assert!(!nav.goto_bookmark("b"));
+        assert_eq!(nav.bookmarks().sorted(), vec![("a", marked)]);
+    }
+
+    #[test]
+    fn test_position_file_line() {
+        let nav = create_test_navigator();
+        let diff = nav.diff();
+        let lines = diff.files[0].total_lines();
+        let last = Position::from_file_line(diff, 0, lines - 1).unwrap();
+        assert_eq!(last.file_line(diff), lines - 1);
+        assert!(Position::from_file_line(diff, 0, lines).is_none());
+        assert!(Position::from_file_line(diff, 9, 0).is_none());
+    }
+
+    #[test]
+    fn test_virtual_scroll_lines() {
+        use crate::comment::{CommentBuilder, DiffSide};
+
+        let nav = create_test_navigator();
+        let file = &nav.diff().files[0];
+        let mut comments = CommentManager::new();
+        for content in ["first", "second"] {
+            let comment = CommentBuilder::new(file.id.clone(), file.hunks[0].lines[1].id.clone(), DiffSide::Old)
+                .content(content)
+                .build()
+                .unwrap();
+            comments.add(comment).unwrap();
+        }
+
+        // Header, 5 lines, 2 comments after the second line
+        assert_eq!(nav.total_virtual_lines(&comments), 8);
+        let rows = nav.virtual_scroll_lines(0, 100, &comments);
+        assert_eq!(rows.len(), 8);
+        assert!(matches!(&rows[0], VirtualLine::HunkHeader(h) if h.starts_with("@@ -1,3 +1,4 @@")));
+
+        let rows = nav.virtual_scroll_lines(3, 3, &comments);
+        assert!(matches!(rows[0], VirtualLine::CommentLine(1, c) if c.content == "first"));
+        assert!(matches!(rows[1], VirtualLine::CommentLine(1, c) if c.content == "second"));
+        assert!(matches!(rows[2], VirtualLine::DiffLine(2, l) if l.content == "line2_modified"));
+
+        assert_eq!(nav.virtual_scroll_lines(6, 10, &comments).len(), 2);
+        assert!(nav.virtual_scroll_lines(8, 10, &comments).is_empty());
+    }
+
     #[test]
     fn test_file_count() {
         let nav = create_test_navigator();
@@ -1,11 +1,17 @@
 //! Git diff parser
 
+use crate::diff::lint::is_conflict_marker;
 use crate::diff::model::*;
-use crate::error::{CrHelperError, Result};
+use crate::error::{CrHelperError, PathContext, Result};
 use crate::types::{FileId, HunkId, LineId};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// `(old_path, new_path, mode)` returned by [`DiffParser::parse_file_list_only`]
+pub type FileListEntry = (Option<PathBuf>, Option<PathBuf>, FileMode);
+
 /// Configuration for the diff parser
 #[derive(Debug, Clone)]
 pub struct ParserConfig {
@@ -13,6 +19,34 @@ pub struct ParserConfig {
     pub include_binary: bool,
     /// Maximum file size to parse (in bytes)
     pub max_file_size: Option<usize>,
+    /// Context lines around changes (`-U<n>`); `None` uses git's default
+    pub context_lines: Option<usize>,
+    /// Ignore whitespace when comparing lines (`-w`)
+    pub ignore_whitespace: bool,
+    /// Ignore changes whose lines are all blank (`--ignore-blank-lines`)
+    pub ignore_blank_lines: bool,
+    /// Attach word diffs to changed lines (see [`DiffParser::parse_word_diff`])
+    pub word_diff: bool,
+    /// Let [`DiffParser::parse`] also start files at plain `---`/`+++`
+    /// headers (`diff -u`, `patch`, quilt) without a `diff --git` line
+    pub accept_plain_unified: bool,
+}
+
+impl ParserConfig {
+    /// Extra `git diff` flags for the non-default options
+    pub fn git_diff_flags(&self) -> Vec<String> {
+        let mut flags = Vec::new();
+        if let Some(n) = self.context_lines {
+            flags.push(format!("-U{}", n));
+        }
+        if self.ignore_whitespace {
+            flags.push("-w".to_string());
+        }
+        if self.ignore_blank_lines {
+            flags.push("--ignore-blank-lines".to_string());
+        }
+        flags
+    }
 }
 
 impl Default for ParserConfig {
@@ -20,11 +54,17 @@ impl Default for ParserConfig {
         Self {
             include_binary: true,
             max_file_size: Some(10 * 1024 * 1024), // 10MB
+            context_lines: None,
+            ignore_whitespace: false,
+            ignore_blank_lines: false,
+            word_diff: false,
+            accept_plain_unified: false,
         }
     }
 }
 
 /// Git diff parser
+#[derive(Clone)]
 pub struct DiffParser {
     config: ParserConfig,
 }
@@ -44,97 +84,361 @@ impl DiffParser {
 
     /// Parse a diff string
     pub fn parse(&self, input: &str) -> Result<DiffData> {
+        Ok(self.parse_bounded(input, None)?.0)
+    }
+
+    /// Parse `git diff` output from a reader one file at a time
+    ///
+    /// Each file is yielded as soon as the next `diff --git` header (or the
+    /// end of input) is read, so callers can start working on the first files
+    /// of a huge diff without holding the whole text in memory. Iteration
+    /// stops after the first error.
+    pub fn parse_streaming<R: BufRead>(&self, reader: R) -> StreamingDiffParser<R> {
+        StreamingDiffParser {
+            parser: self.clone(),
+            reader,
+            state: GitDiffState::default(),
+            buf: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Parse a diff from a reader, building at most `max_files` files
+    ///
+    /// Files past the limit are counted but not built, and the total is
+    /// recorded as by [`DiffData::truncate_files`].
+    pub fn parse_reader(&self, reader: impl BufRead, max_files: Option<usize>) -> Result<DiffData> {
+        let mut stream = self.parse_streaming(reader);
+        let files = match max_files {
+            Some(max) => stream.by_ref().take(max).collect::<Result<Vec<_>>>()?,
+            None => stream.by_ref().collect::<Result<Vec<_>>>()?,
+        };
+        let kept = files.len();
+        let mut diff_data = diff_from_files(files);
+        let remaining = stream.count_remaining_files()?;
+        if remaining > 0 {
+            diff_data.metadata.set_total_files(kept + remaining);
+        }
+        Ok(diff_data)
+    }
+
+    /// Parse at most `max_files` files of a diff string
+    ///
+    /// Returns the files parsed so far and whether the input was truncated.
+    /// A truncated diff records where parsing stopped and how many files the
+    /// whole input has (see [`DiffMetadata::truncated_at_offset`]); pass
+    /// `&input[offset..]` to resume.
+    pub fn parse_partial(&self, input: &str, max_files: usize) -> Result<(DiffData, bool)> {
+        let (mut diff_data, offset) = self.parse_bounded(input, Some(max_files))?;
+        let Some(offset) = offset else {
+            return Ok((diff_data, false));
+        };
+
+        let remaining = input[offset..]
+            .lines()
+            .filter(|line| line.starts_with("diff --git "))
+            .count();
+        diff_data
+            .metadata
+            .set_truncation(offset, diff_data.files.len() + remaining);
+        Ok((diff_data, true))
+    }
+
+    /// Parse up to `max_files` files, returning the byte offset parsing stopped at
+    fn parse_bounded(
+        &self,
+        input: &str,
+        max_files: Option<usize>,
+    ) -> Result<(DiffData, Option<usize>)> {
         let mut files = Vec::new();
-        let mut current_file: Option<FileDiffBuilder> = None;
-        let mut current_hunk: Option<HunkBuilder> = None;
+        let mut state = GitDiffState::default();
+        let mut truncated_at = None;
+        let mut offset = 0;
+
+        for raw in input.split_inclusive('\n') {
+            let line_start = offset;
+            offset += raw.len();
+            let line = raw.strip_suffix('\n').unwrap_or(raw);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+
+            let open_files = files.len() + usize::from(state.current_file.is_some());
+            if line.starts_with("diff --git ") && max_files.is_some_and(|max| open_files >= max) {
+                truncated_at = Some(line_start);
+                break;
+            }
+            if let Some(file) = state.push_line(self, line)? {
+                files.push(file);
+            }
+        }
+        files.extend(state.finish());
+
+        Ok((diff_from_files(files), truncated_at))
+    }
+
+    /// Count files, insertions and deletions without building lines
+    ///
+    /// Produces the same totals as [`parse`](Self::parse) but skips line
+    /// IDs, which hash every line's content; `per_file` is left empty.
+    pub fn parse_with_stats_only(&self, input: &str) -> Result<DiffStats> {
+        let mut stats = DiffStats::default();
+        let mut in_hunk = false;
 
         for line in input.lines() {
-            // New file header
             if line.starts_with("diff --git ") {
-                // Save current hunk and file
-                if let Some(hunk) = current_hunk.take() {
-                    if let Some(ref mut file) = current_file {
-                        file.hunks.push(hunk.build());
-                    }
-                }
-                if let Some(file) = current_file.take() {
-                    files.push(file.build());
+                self.parse_diff_header(line)?;
+                stats.files_changed += 1;
+                in_hunk = false;
+            } else if header_mode(line).is_some() {
+                continue;
+            } else if line.starts_with("@@ ") {
+                self.parse_hunk_header(line)?;
+                in_hunk = stats.files_changed > 0;
+            } else if in_hunk {
+                match line.as_bytes().first() {
+                    Some(b'+') => stats.insertions += 1,
+                    Some(b'-') => stats.deletions += 1,
+                    _ => {}
                 }
+            }
+        }
 
-                // Parse file paths
+        Ok(stats)
+    }
+
+    /// List `(old_path, new_path, mode)` for each file without parsing hunks
+    pub fn parse_file_list_only(&self, input: &str) -> Result<Vec<FileListEntry>> {
+        let mut files = Vec::new();
+
+        for line in input.lines() {
+            if line.starts_with("diff --git ") {
                 let (old_path, new_path) = self.parse_diff_header(line)?;
-                current_file = Some(FileDiffBuilder::new(old_path, new_path));
-            }
-            // Binary file
-            else if line.starts_with("Binary files ") {
-                if let Some(ref mut file) = current_file {
-                    file.mode = FileMode::Binary;
+                files.push((old_path, new_path, FileMode::Modified));
+            } else if let Some(mode) = header_mode(line) {
+                if let Some(file) = files.last_mut() {
+                    file.2 = mode;
                 }
             }
-            // File mode indicators
-            else if line.starts_with("new file mode") {
-                if let Some(ref mut file) = current_file {
+        }
+
+        Ok(files)
+    }
+
+    /// Parse Mercurial `hg diff` output
+    ///
+    /// Files start with `diff -r <rev> [-r <rev>] <path>` headers. The path
+    /// comes from that header; `---`/`+++` lines only mark added (`/dev/null`
+    /// old side) or deleted (`/dev/null` new side) files.
+    pub fn parse_from_hg(&self, input: &str) -> Result<DiffData> {
+        let mut files = Vec::new();
+        let mut state = GitDiffState::default();
+
+        for line in input.lines() {
+            // New file header
+            if line.starts_with("diff -r ") {
+                let path = self.parse_hg_diff_header(line)?;
+                files.extend(state.start_file(FileDiffBuilder::new(Some(path.clone()), Some(path))));
+            }
+            // Added / deleted markers (before the first hunk)
+            else if state.current_hunk.is_none() && line.starts_with("--- /dev/null") {
+                if let Some(ref mut file) = state.current_file {
+                    file.old_path = None;
                     file.mode = FileMode::Added;
                 }
-            } else if line.starts_with("deleted file mode") {
-                if let Some(ref mut file) = current_file {
+            } else if state.current_hunk.is_none() && line.starts_with("+++ /dev/null") {
+                if let Some(ref mut file) = state.current_file {
+                    file.new_path = None;
                     file.mode = FileMode::Deleted;
                 }
-            } else if line.starts_with("rename from ") || line.starts_with("rename to ") {
-                if let Some(ref mut file) = current_file {
-                    file.mode = FileMode::Renamed;
-                }
-            } else if line.starts_with("copy from ") || line.starts_with("copy to ") {
-                if let Some(ref mut file) = current_file {
-                    file.mode = FileMode::Copied;
-                }
             }
-            // Hunk header
-            else if line.starts_with("@@ ") {
-                // Save current hunk
-                if let Some(hunk) = current_hunk.take() {
-                    if let Some(ref mut file) = current_file {
-                        file.hunks.push(hunk.build());
-                    }
+            // Binary file
+            else if line.starts_with("Binary file ") {
+                if let Some(ref mut file) = state.current_file {
+                    file.mode = FileMode::Binary;
                 }
+            } else {
+                state.push_hunk_line(self, line)?;
+            }
+        }
+        files.extend(state.finish());
+
+        Ok(diff_from_files(files))
+    }
 
-                let (old_range, new_range) = self.parse_hunk_header(line)?;
-                let hunk_id = if let Some(ref file) = current_file {
-                    HunkId::new(&file.id, file.hunks.len())
-                } else {
-                    HunkId::new(&FileId::from_string("unknown"), 0)
-                };
+    /// Run `hg diff -r <rev>` and parse the output
+    pub fn parse_from_hg_rev(&self, rev: &str) -> Result<DiffData> {
+        let output = Command::new("hg")
+            .args(["diff", "-r", rev])
+            .output()
+            .map_err(|e| CrHelperError::Command {
+                command: "hg diff".to_string(),
+                message: e.to_string(),
+            })?;
 
-                current_hunk = Some(HunkBuilder::new(hunk_id, line.to_string(), old_range, new_range));
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(CrHelperError::Command {
+                command: "hg diff".to_string(),
+                message: stderr.trim().to_string(),
+            });
+        }
+
+        self.parse_from_hg(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Parse Subversion `svn diff` output
+    ///
+    /// Files start with `Index: <path>` headers. SVN diffs carry no file mode,
+    /// so files are `Modified` unless a `---`/`+++` side is `(nonexistent)`
+    /// or SVN reports a binary file. `Property changes on:` sections are
+    /// skipped.
+    pub fn parse_from_svn(&self, input: &str) -> Result<DiffData> {
+        let mut files = Vec::new();
+        let mut state = GitDiffState::default();
+        let mut in_properties = false;
+
+        for line in input.lines() {
+            // New file header
+            if let Some(path) = line.strip_prefix("Index: ") {
+                let path = PathBuf::from(path.trim());
+                files.extend(state.start_file(FileDiffBuilder::new(Some(path.clone()), Some(path))));
+                in_properties = false;
+            } else if line.starts_with("Property changes on: ") {
+                state.finish_hunk();
+                in_properties = true;
+            } else if in_properties {
+                continue;
+            }
+            // Added / deleted markers (before the first hunk)
+            else if state.current_hunk.is_none() && line.starts_with("--- ") {
+                if line.ends_with("(nonexistent)") {
+                    if let Some(ref mut file) = state.current_file {
+                        file.old_path = None;
+                        file.mode = FileMode::Added;
+                    }
+                }
+            } else if state.current_hunk.is_none() && line.starts_with("+++ ") {
+                if line.ends_with("(nonexistent)") {
+                    if let Some(ref mut file) = state.current_file {
+                        file.new_path = None;
+                        file.mode = FileMode::Deleted;
+                    }
+                }
             }
-            // Diff lines
-            else if let Some(ref mut hunk) = current_hunk {
-                if let Some(line_data) = self.parse_line(line, &current_file, hunk)? {
-                    hunk.lines.push(line_data);
+            // Binary file
+            else if line.starts_with("Cannot display: file marked as a binary type") {
+                if let Some(ref mut file) = state.current_file {
+                    file.mode = FileMode::Binary;
                 }
+            } else {
+                state.push_hunk_line(self, line)?;
             }
         }
+        files.extend(state.finish());
 
-        // Save final hunk and file
-        if let Some(hunk) = current_hunk.take() {
-            if let Some(ref mut file) = current_file {
-                file.hunks.push(hunk.build());
+        Ok(diff_from_files(files))
+    }
+
+    /// Parse a plain unified diff (`diff -u`, `git diff --no-prefix`, ...)
+    ///
+    /// Same as [`parse`](Self::parse) with
+    /// [`ParserConfig::accept_plain_unified`] set: files start with a `---`
+    /// line directly followed by a `+++` line. Timestamps after a tab and
+    /// `a/`/`b/` prefixes are dropped; a `/dev/null` side marks the file as
+    /// added or deleted.
+    pub fn parse_unified(&self, input: &str) -> Result<DiffData> {
+        let config = ParserConfig {
+            accept_plain_unified: true,
+            ..self.config.clone()
+        };
+        Self::with_config(config).parse(input)
+    }
+
+    /// Read a `.patch`/`.diff` file and parse it in the format it is in
+    ///
+    /// The first line starting a file decides the format: `diff --git`
+    /// (git), `Index:` (SVN), `diff -r` (Mercurial) or `---` (plain
+    /// unified). Leading lines such as `git format-patch` mail headers are
+    /// skipped. Returns [`CrHelperError::UnsupportedDiffFormat`] with the
+    /// file's first line if none is found.
+    pub fn parse_from_file(&self, path: &Path) -> Result<DiffData> {
+        let input = std::fs::read_to_string(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => CrHelperError::FileNotFound(path.to_path_buf()),
+            _ => CrHelperError::from_io_with_path(e, path),
+        })?;
+
+        for line in input.lines() {
+            if line.starts_with("diff --git ") {
+                return self.parse(&input);
+            } else if line.starts_with("Index: ") {
+                return self.parse_from_svn(&input);
+            } else if line.starts_with("diff -r ") {
+                return self.parse_from_hg(&input);
+            } else if line.starts_with("--- ") {
+                return self.parse_unified(&input);
             }
         }
-        if let Some(file) = current_file.take() {
-            files.push(file.build());
+        let first_line = input.lines().next().unwrap_or("");
+        Err(CrHelperError::UnsupportedDiffFormat(first_line.to_string()))
+    }
+
+    /// Run `svn diff` (against `rev` if given) and parse the output
+    pub fn parse_from_svn_rev(&self, rev: Option<u64>) -> Result<DiffData> {
+        let mut command = Command::new("svn");
+        command.arg("diff");
+        if let Some(rev) = rev {
+            command.args(["-r", &rev.to_string()]);
         }
+        let output = command.output().map_err(|e| CrHelperError::Command {
+            command: "svn diff".to_string(),
+            message: e.to_string(),
+        })?;
 
-        let mut diff_data = DiffData {
-            files,
-            metadata: DiffMetadata::default(),
-            stats: DiffStats::default(),
-        };
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(CrHelperError::Command {
+                command: "svn diff".to_string(),
+                message: stderr.trim().to_string(),
+            });
+        }
+
+        self.parse_from_svn(&String::from_utf8_lossy(&output.stdout))
+    }
 
-        // Calculate stats
-        diff_data.stats = DiffStats::from_diff(&diff_data);
+    /// Find added lines that are merge conflict markers
+    ///
+    /// Returns `(file, new line number)` for each `<<<<<<<`, `=======` or
+    /// `>>>>>>>` line.
+    pub fn verify_no_conflict_markers(diff: &DiffData) -> Vec<(FileId, usize)> {
+        diff.files
+            .iter()
+            .flat_map(|file| {
+                file.hunks
+                    .iter()
+                    .flat_map(|h| &h.lines)
+                    .filter(|l| l.line_type == LineType::Added && is_conflict_marker(&l.content))
+                    .map(|l| (file.id.clone(), l.new_line_num.unwrap_or(0)))
+            })
+            .collect()
+    }
 
-        Ok(diff_data)
+    /// Get the SHA of HEAD in the repository containing `dir`
+    pub fn head_sha_in(dir: &Path) -> Result<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .map_err(|e| CrHelperError::Command {
+                command: "git rev-parse".to_string(),
+                message: e.to_string(),
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(CrHelperError::Git(stderr.trim().to_string()));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     }
 
     /// Parse diff from git command
@@ -148,25 +452,14 @@ impl DiffParser {
         source: &DiffSource,
         include_untracked: bool,
     ) -> Result<DiffData> {
-        let args = source.to_git_args();
-        let mut cmd = Command::new("git");
-        cmd.arg("diff").args(&args);
-
-        let output = cmd.output().map_err(|e| {
-            CrHelperError::Command {
-                command: "git diff".to_string(),
-                message: e.to_string(),
-            }
-        })?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(CrHelperError::Git(stderr.to_string()));
-        }
-
-        let diff_str = String::from_utf8_lossy(&output.stdout);
+        let flags = self.config.git_diff_flags();
+        let diff_str = self.git_diff(source, &[])?;
         let mut diff_data = self.parse(&diff_str)?;
         diff_data.metadata.source = source.clone();
+        diff_data.metadata.set_diff_flags(&flags);
+        if self.config.word_diff {
+            attach_word_diff(&mut diff_data, &self.git_diff(source, &["--word-diff=porcelain"])?, self)?;
+        }
 
         // Include untracked files if requested (only for WorkingTree or Staged)
         if include_untracked
@@ -177,13 +470,45 @@ impl DiffParser {
             for path in untracked_files {
                 diff_data.files.push(FileDiff::lazy_new(PathBuf::from(path)));
             }
-            // Update file count in stats
-            diff_data.stats.files_changed = diff_data.files.len();
+            diff_data.stats = DiffStats::from_diff(&diff_data);
         }
 
         Ok(diff_data)
     }
 
+    /// Parse diff from git, with word diffs on changed lines
+    ///
+    /// Runs `git diff --word-diff=porcelain` next to the usual diff and
+    /// stores each changed line's words in [`Line::word_diff`]. Lines whose
+    /// words git merged across line breaks get no word diff.
+    pub fn parse_word_diff(&self, source: &DiffSource) -> Result<DiffData> {
+        let mut diff_data = self.parse_from_git(source)?;
+        if !self.config.word_diff {
+            attach_word_diff(&mut diff_data, &self.git_diff(source, &["--word-diff=porcelain"])?, self)?;
+        }
+        Ok(diff_data)
+    }
+
+    /// Output of `git diff` for `source` with the configured and `extra` flags
+    fn git_diff(&self, source: &DiffSource, extra: &[&str]) -> Result<String> {
+        let output = Command::new("git")
+            .arg("diff")
+            .args(self.config.git_diff_flags())
+            .args(extra)
+            .args(source.to_git_args())
+            .output()
+            .map_err(|e| CrHelperError::Command {
+                command: "git diff".to_string(),
+                message: e.to_string(),
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(CrHelperError::Git(stderr.to_string()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
     /// Get list of untracked files (without loading content)
     /// Uses .gitignore for exclusions via --exclude-standard
     fn get_untracked_file_list(&self) -> Result<Vec<String>> {
@@ -242,7 +567,7 @@ impl DiffParser {
         }
 
         // Check file size
-        let metadata = fs::metadata(path).map_err(|e| CrHelperError::Io(e))?;
+        let metadata = fs::metadata(path).with_path(path)?;
         let max_size = self.config.max_file_size.unwrap_or(10 * 1024 * 1024);
         if metadata.len() as usize > max_size {
             // Too large, mark as binary
@@ -274,11 +599,12 @@ impl DiffParser {
         let mut hunk_lines = Vec::with_capacity(line_count);
         for (i, line_content) in lines.iter().enumerate() {
             hunk_lines.push(Line {
-                id: LineId::from_content(path, line_content, i + 1),
+                id: LineId::from_position(path, line_content, i + 1),
                 line_type: LineType::Added,
                 old_line_num: None,
                 new_line_num: Some(i + 1),
                 content: line_content.to_string(),
+                word_diff: None,
             });
         }
 
@@ -313,6 +639,26 @@ impl DiffParser {
         Ok((old_path, new_path))
     }
 
+    /// Parse `diff -r <rev> [-r <rev>] <path>` header to extract the path
+    fn parse_hg_diff_header(&self, line: &str) -> Result<PathBuf> {
+        let mut rest = line.strip_prefix("diff ").unwrap_or(line);
+        while let Some(after_flag) = rest.strip_prefix("-r ") {
+            rest = match after_flag.split_once(' ') {
+                Some((_rev, tail)) => tail,
+                None => "",
+            };
+        }
+
+        if rest.is_empty() {
+            return Err(CrHelperError::InvalidDiff(format!(
+                "Invalid hg diff header: {}",
+                line
+            )));
+        }
+
+        Ok(PathBuf::from(rest))
+    }
+
     /// Parse hunk header to extract ranges
     fn parse_hunk_header(&self, line: &str) -> Result<(Range, Range)> {
         // Format: "@@ -10,5 +10,7 @@" or "@@ -10 +10 @@"
@@ -351,7 +697,7 @@ impl DiffParser {
     fn parse_line(
         &self,
         line: &str,
-        current_file: &Option<FileDiffBuilder>,
+        current_file: &mut Option<FileDiffBuilder>,
         hunk: &HunkBuilder,
     ) -> Result<Option<Line>> {
         if line.is_empty() {
@@ -377,8 +723,14 @@ impl DiffParser {
             .cloned()
             .unwrap_or_else(|| PathBuf::from("unknown"));
 
-        let line_num = new_line_num.or(old_line_num).unwrap_or(0);
-        let line_id = LineId::from_content(&file_path, content, line_num);
+        let line_id = match current_file {
+            Some(file) => {
+                let seen = file.occurrences.entry(content.to_string()).or_insert(0);
+                *seen += 1;
+                LineId::from_occurrence(&file_path, line_type, content, *seen - 1)
+            }
+            None => LineId::from_content(&file_path, content),
+        };
 
         Ok(Some(Line {
             id: line_id,
@@ -386,6 +738,7 @@ impl DiffParser {
             content: content.to_string(),
             old_line_num,
             new_line_num,
+            word_diff: None,
         }))
     }
 
@@ -416,13 +769,217 @@ impl Default for DiffParser {
     }
 }
 
+/// Iterator over the files of a `git diff` read from `R`
+///
+/// Created by [`DiffParser::parse_streaming`]; only the file being parsed is
+/// kept between calls to `next()`.
+pub struct StreamingDiffParser<R> {
+    parser: DiffParser,
+    reader: R,
+    state: GitDiffState,
+    /// Raw bytes of the current line; diffs of non-UTF-8 files are decoded lossily
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl<R: BufRead> Iterator for StreamingDiffParser<R> {
+    type Item = Result<FileDiff>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.buf.clear();
+            match self.reader.read_until(b'\n', &mut self.buf) {
+                Ok(0) => {
+                    self.done = true;
+                    return self.state.finish().map(Ok);
+                }
+                Ok(_) => {
+                    let text = String::from_utf8_lossy(&self.buf);
+                    let line = text.strip_suffix('\n').unwrap_or(&text);
+                    let line = line.strip_suffix('\r').unwrap_or(line);
+                    match self.state.push_line(&self.parser, line) {
+                        Ok(Some(file)) => return Some(Ok(file)),
+                        Ok(None) => {}
+                        Err(e) => {
+                            self.done = true;
+                            return Some(Err(e));
+                        }
+                    }
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e.into()));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R: BufRead> StreamingDiffParser<R> {
+    /// Count the files not yet returned, reading the rest of the input
+    /// without building them
+    fn count_remaining_files(mut self) -> Result<usize> {
+        if self.done {
+            return Ok(0);
+        }
+        let mut count = usize::from(self.state.current_file.is_some());
+        loop {
+            self.buf.clear();
+            if self.reader.read_until(b'\n', &mut self.buf)? == 0 {
+                return Ok(count);
+            }
+            if self.buf.starts_with(b"diff --git ") {
+                count += 1;
+            }
+        }
+    }
+}
+
+/// File and hunk being assembled from `git diff` lines
+///
+/// The Mercurial and Subversion parsers use it too, handling their own file
+/// headers and passing hunks to [`push_hunk_line`](Self::push_hunk_line).
+#[derive(Default)]
+struct GitDiffState {
+    current_file: Option<FileDiffBuilder>,
+    current_hunk: Option<HunkBuilder>,
+    /// A `---` line that may start a plain unified file header
+    pending_old: Option<String>,
+}
+
+impl GitDiffState {
+    /// Feed one line (without its newline); returns the previous file once a
+    /// new file header starts the next one
+    fn push_line(&mut self, parser: &DiffParser, line: &str) -> Result<Option<FileDiff>> {
+        // Plain unified header (`ParserConfig::accept_plain_unified`)
+        if let Some(old) = self.pending_old.take() {
+            if let Some(new) = line.strip_prefix("+++ ") {
+                let finished = self.finish();
+                self.current_file = Some(unified_file(&old, new)?);
+                return Ok(finished);
+            }
+            // No `+++` follows, so the `---` was an ordinary line
+            self.push_git_line(parser, &format!("--- {}", old))?;
+        }
+        if parser.config.accept_plain_unified && self.at_file_boundary() {
+            if let Some(old) = line.strip_prefix("--- ") {
+                self.pending_old = Some(old.to_string());
+                return Ok(None);
+            }
+        }
+        self.push_git_line(parser, line)
+    }
+
+    /// Whether a `---` line here starts a new file rather than being a
+    /// deleted line or part of a `diff --git` header
+    fn at_file_boundary(&self) -> bool {
+        let in_git_header = self.current_file.as_ref().is_some_and(|f| f.hunks.is_empty())
+            && self.current_hunk.is_none();
+        !in_git_header && self.current_hunk.as_ref().is_none_or(HunkBuilder::is_complete)
+    }
+
+    fn push_git_line(&mut self, parser: &DiffParser, line: &str) -> Result<Option<FileDiff>> {
+        let mut finished = None;
+
+        // New file header
+        if line.starts_with("diff --git ") {
+            // Parse file paths
+            let (old_path, new_path) = parser.parse_diff_header(line)?;
+            finished = self.start_file(FileDiffBuilder::new(old_path, new_path));
+        }
+        // Binary file and file mode indicators
+        else if let Some(mode) = header_mode(line) {
+            if let Some(ref mut file) = self.current_file {
+                file.mode = mode;
+                if let Some(bits) = header_mode_bits(line) {
+                    file.file_mode = Some(bits.to_string());
+                }
+            }
+        } else if let Some(bits) = header_mode_bits(line).filter(|_| self.current_hunk.is_none()) {
+            if let Some(ref mut file) = self.current_file {
+                file.file_mode = Some(bits.to_string());
+            }
+        } else {
+            self.push_hunk_line(parser, line)?;
+        }
+
+        Ok(finished)
+    }
+
+    /// Finish the file being parsed and start `file`; returns the finished one
+    fn start_file(&mut self, file: FileDiffBuilder) -> Option<FileDiff> {
+        let finished = self.finish();
+        self.current_file = Some(file);
+        finished
+    }
+
+    /// Feed a hunk header or a line of the current hunk
+    fn push_hunk_line(&mut self, parser: &DiffParser, line: &str) -> Result<()> {
+        // Hunk header
+        if line.starts_with("@@ ") {
+            self.finish_hunk();
+
+            let (old_range, new_range) = parser.parse_hunk_header(line)?;
+            let hunk_id = if let Some(ref file) = self.current_file {
+                HunkId::new(&file.id, file.hunks.len())
+            } else {
+                HunkId::new(&FileId::from_string("unknown"), 0)
+            };
+
+            self.current_hunk = Some(HunkBuilder::new(hunk_id, line.to_string(), old_range, new_range));
+        }
+        // Diff lines
+        else if let Some(ref mut hunk) = self.current_hunk {
+            if let Some(line_data) = parser.parse_line(line, &mut self.current_file, hunk)? {
+                hunk.lines.push(line_data);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Save the current hunk into the current file
+    fn finish_hunk(&mut self) {
+        if let Some(hunk) = self.current_hunk.take() {
+            if let Some(ref mut file) = self.current_file {
+                file.hunks.push(hunk.build());
+            }
+        }
+    }
+
+    /// Build the file being parsed, if any
+    ///
+    /// A pending `---` line is dropped: it came after the last hunk was
+    /// complete, so it belongs to no file.
+    fn finish(&mut self) -> Option<FileDiff> {
+        self.pending_old = None;
+        self.finish_hunk();
+        self.current_file.take().map(FileDiffBuilder::build)
+    }
+}
+
+/// Diff data for parsed files, with their stats
+fn diff_from_files(files: Vec<FileDiff>) -> DiffData {
+    let mut diff_data = DiffData {
+        files,
+        metadata: DiffMetadata::default(),
+        stats: DiffStats::default(),
+    };
+    diff_data.stats = DiffStats::from_diff(&diff_data);
+    diff_data
+}
+
 /// Builder for FileDiff
 struct FileDiffBuilder {
     id: FileId,
     old_path: Option<PathBuf>,
     new_path: Option<PathBuf>,
     mode: FileMode,
+    file_mode: Option<String>,
     hunks: Vec<Hunk>,
+    /// Lines seen so far per content, for [`LineId::from_occurrence`]
+    occurrences: HashMap<String, usize>,
 }
 
 impl FileDiffBuilder {
@@ -433,7 +990,9 @@ impl FileDiffBuilder {
             old_path,
             new_path,
             mode: FileMode::Modified,
+            file_mode: None,
             hunks: Vec::new(),
+            occurrences: HashMap::new(),
         }
     }
 
@@ -445,6 +1004,7 @@ impl FileDiffBuilder {
             mode: self.mode,
             hunks: self.hunks,
             lazy: false,
+            file_mode: self.file_mode,
         }
     }
 }
@@ -469,6 +1029,16 @@ impl HunkBuilder {
         }
     }
 
+    /// All lines announced by the hunk header have been read
+    fn is_complete(&self) -> bool {
+        let count = |side: LineType| {
+            let on_side = |l: &&Line| l.line_type == LineType::Context || l.line_type == side;
+            self.lines.iter().filter(on_side).count()
+        };
+        let (old, new) = (count(LineType::Deleted), count(LineType::Added));
+        old >= self.old_range.count && new >= self.new_range.count
+    }
+
     fn build(self) -> Hunk {
         Hunk {
             id: self.id,
@@ -480,6 +1050,135 @@ impl HunkBuilder {
     }
 }
 
+/// Attach `git diff --word-diff=porcelain` output to the lines of `diff`
+///
+/// Porcelain output has one word run per line (` `, `-` or `+` prefix) and
+/// `~` for each line break. The old and new text of every changed line are
+/// rebuilt from its runs and matched against the deleted and added lines of
+/// the same file.
+fn attach_word_diff(diff: &mut DiffData, porcelain: &str, parser: &DiffParser) -> Result<()> {
+    let mut changes: Vec<(PathBuf, Vec<WordSpan>)> = Vec::new();
+    let mut path = None;
+    let mut spans = Vec::new();
+    let mut in_hunk = false;
+
+    for line in porcelain.lines() {
+        if line.starts_with("diff --git ") {
+            let (old_path, new_path) = parser.parse_diff_header(line)?;
+            path = new_path.or(old_path);
+            in_hunk = false;
+            spans.clear();
+        } else if line.starts_with("@@ ") {
+            in_hunk = true;
+            spans.clear();
+        } else if !in_hunk {
+            continue;
+        } else if line.starts_with('~') {
+            if let Some(path) = &path {
+                changes.push((path.clone(), std::mem::take(&mut spans)));
+            }
+        } else {
+            let kind = match line.chars().next() {
+                Some(' ') => WordKind::Context,
+                Some('-') => WordKind::Deleted,
+                Some('+') => WordKind::Added,
+                _ => continue,
+            };
+            spans.push(WordSpan {
+                kind,
+                text: line[1..].to_string(),
+            });
+        }
+    }
+
+    for (path, spans) in changes {
+        let Some(file) = diff.files.iter_mut().find(|f| f.display_path() == &path) else {
+            continue;
+        };
+        for (line_type, side) in [(LineType::Deleted, WordKind::Deleted), (LineType::Added, WordKind::Added)] {
+            let on_side = |span: &&WordSpan| span.kind == WordKind::Context || span.kind == side;
+            // Whole added or deleted lines have nothing to highlight
+            if !spans.iter().any(|s| s.kind == WordKind::Context) || !spans.iter().any(|s| s.kind == side) {
+                continue;
+            }
+            let text: String = spans.iter().filter(on_side).map(|s| s.text.as_str()).collect();
+            let target = file
+                .hunks
+                .iter_mut()
+                .flat_map(|h| h.lines.iter_mut())
+                .find(|l| l.line_type == line_type && l.word_diff.is_none() && l.content == text);
+            if let Some(target) = target {
+                target.word_diff = Some(spans.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// File started by a unified diff `---`/`+++` header pair
+///
+/// A `/dev/null` side marks the file as added or deleted.
+fn unified_file(old: &str, new: &str) -> Result<FileDiffBuilder> {
+    let old_path = unified_path(old, "a/");
+    let new_path = unified_path(new, "b/");
+    let mode = match (&old_path, &new_path) {
+        (None, None) => {
+            return Err(CrHelperError::InvalidDiff(format!(
+                "Both sides are /dev/null: --- {}",
+                old
+            )))
+        }
+        (None, Some(_)) => FileMode::Added,
+        (Some(_), None) => FileMode::Deleted,
+        _ => FileMode::Modified,
+    };
+    let mut file = FileDiffBuilder::new(old_path, new_path);
+    file.mode = mode;
+    Ok(file)
+}
+
+/// Path from a unified diff `---`/`+++` line, `None` for `/dev/null`
+fn unified_path(spec: &str, prefix: &str) -> Option<PathBuf> {
+    let path = spec.split('\t').next().unwrap_or(spec).trim_end();
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(PathBuf::from(path.strip_prefix(prefix).unwrap_or(path)))
+}
+
+/// File mode implied by an extended git header line, if any
+fn header_mode(line: &str) -> Option<FileMode> {
+    if line.starts_with("Binary files ") {
+        Some(FileMode::Binary)
+    } else if line.starts_with("new file mode") {
+        Some(FileMode::Added)
+    } else if line.starts_with("deleted file mode") {
+        Some(FileMode::Deleted)
+    } else if line.starts_with("rename from ") || line.starts_with("rename to ") {
+        Some(FileMode::Renamed)
+    } else if line.starts_with("copy from ") || line.starts_with("copy to ") {
+        Some(FileMode::Copied)
+    } else {
+        None
+    }
+}
+
+/// Git mode bits from an extended header line, if it carries any
+///
+/// Reads `new file mode`, `deleted file mode`, `new mode` and the mode at
+/// the end of an `index <old>..<new> <mode>` line.
+fn header_mode_bits(line: &str) -> Option<&str> {
+    ["new file mode ", "deleted file mode ", "new mode "]
+        .iter()
+        .find_map(|prefix| line.strip_prefix(prefix))
+        .or_else(|| {
+            let rest = line.strip_prefix("index ")?;
+            let (hashes, mode) = rest.split_once(' ')?;
+            hashes.contains("..").then_some(mode)
+        })
+        .map(str::trim_end)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -554,4 +1253,349 @@ index 1234567..abcdefg 100644
         assert!(diff.stats.insertions >= 2);
         assert!(diff.stats.deletions >= 1);
     }
+
+    const MULTI_FILE_DIFF: &str = "diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,3 @@
+ keep
+--- removed dashes
++++ added pluses
++more
+diff --git a/new.rs b/new.rs
+new file mode 100644
+--- /dev/null
++++ b/new.rs
+@@ -0,0 +1 @@
++fn new() {}
+diff --git a/logo.png b/logo.png
+Binary files a/logo.png and b/logo.png differ
+";
+
+    #[test]
+    fn test_identical_lines_get_distinct_ids() {
+        let diff = DiffParser::new()
+            .parse("diff --git a/a.rs b/a.rs\n@@ -1,2 +1,4 @@\n }\n+    return Ok(());\n+    return Ok(());\n }\n")
+            .unwrap();
+        let lines = &diff.files[0].hunks[0].lines;
+
+        assert_ne!(lines[1].id, lines[2].id);
+        assert_ne!(lines[0].id, lines[3].id);
+        // The first copy keeps the content-only ID
+        assert_eq!(lines[1].id, LineId::from_content(Path::new("a.rs"), "    return Ok(());"));
+
+        // IDs are stable when the same diff is parsed again
+        let again = DiffParser::new()
+            .parse("diff --git a/a.rs b/a.rs\n@@ -1,2 +1,4 @@\n }\n+    return Ok(());\n+    return Ok(());\n }\n")
+            .unwrap();
+        assert_eq!(again.files[0].hunks[0].lines[2].id, lines[2].id);
+    }
+
+    #[test]
+    fn test_parse_streaming() {
+        let parser = DiffParser::new();
+        let full = parser.parse(MULTI_FILE_DIFF).unwrap();
+
+        let mut stream = parser.parse_streaming(MULTI_FILE_DIFF.as_bytes());
+        let first = stream.next().unwrap().unwrap();
+        assert_eq!(first.display_path(), full.files[0].display_path());
+        assert_eq!(first.hunks[0].lines.len(), full.files[0].hunks[0].lines.len());
+        let rest: Vec<FileDiff> = stream.map(|f| f.unwrap()).collect();
+        assert_eq!(rest.len(), 2);
+
+        let streamed = DiffData::from_stream(MULTI_FILE_DIFF.replace('\n', "\r\n").as_bytes()).unwrap();
+        let json = |diff: &DiffData| serde_json::to_value((&diff.files, &diff.stats)).unwrap();
+        assert_eq!(json(&streamed), json(&full));
+
+        // Latin-1 content doesn't stop the stream
+        let latin1 = b"diff --git a/a.txt b/a.txt\n@@ -1 +1 @@\n-caf\xe9\n+cafe\n";
+        let file = parser.parse_streaming(&latin1[..]).next().unwrap().unwrap();
+        assert_eq!(file.hunks[0].lines[0].content, "caf\u{fffd}");
+
+        // Iteration ends after an error
+        let mut stream = parser.parse_streaming("diff --git a/a.rs b/a.rs\n@@ -x +1 @@\n+a\n".as_bytes());
+        assert!(stream.next().unwrap().is_err());
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_parse_reader_max_files() {
+        let parser = DiffParser::new();
+        let full = parser.parse(MULTI_FILE_DIFF).unwrap();
+
+        let all = parser.parse_reader(MULTI_FILE_DIFF.as_bytes(), None).unwrap();
+        assert_eq!(all.files.len(), 3);
+        assert_eq!(all.metadata.total_files(), None);
+        let exact = parser.parse_reader(MULTI_FILE_DIFF.as_bytes(), Some(3)).unwrap();
+        assert_eq!(exact.metadata.total_files(), None);
+
+        let capped = parser.parse_reader(MULTI_FILE_DIFF.as_bytes(), Some(2)).unwrap();
+        assert_eq!(capped.files.len(), 2);
+        assert_eq!(capped.files[1].display_path(), full.files[1].display_path());
+        assert_eq!(capped.stats.files_changed, 2);
+        assert_eq!(capped.metadata.total_files(), Some(3));
+
+        let none = parser.parse_reader(MULTI_FILE_DIFF.as_bytes(), Some(0)).unwrap();
+        assert!(none.files.is_empty());
+        assert_eq!(none.metadata.total_files(), Some(3));
+    }
+
+    #[test]
+    fn test_attach_word_diff() {
+        let header = "diff --git a/f.rs b/f.rs\nindex 8b6a7ff..d4aef82 100644\n--- a/f.rs\n+++ b/f.rs\n@@ -1,4 +1,4 @@\n";
+        let plain = format!(
+            "{}-let x = foo(a, b);\n+let x = bar(a, c);\n keep this\n-remove me\n-  indented old\n+  indented new value\n+added line\n",
+            header
+        );
+        let porcelain = format!(
+            "{} let x = \n-foo(a, b);\n+bar(a, c);\n~\n keep this\n~\n-remove me\n   indented \n-old\n+new value\n~\n+added line\n~\n",
+            header
+        );
+        let parser = DiffParser::new();
+        let mut diff = parser.parse(&plain).unwrap();
+        attach_word_diff(&mut diff, &porcelain, &parser).unwrap();
+
+        let lines = &diff.files[0].hunks[0].lines;
+        let side = |i: usize| -> Vec<(WordKind, String)> {
+            lines[i]
+                .side_word_spans()
+                .map(|spans| spans.iter().map(|s| (s.kind, s.text.clone())).collect())
+                .unwrap_or_default()
+        };
+        assert_eq!(
+            side(0),
+            vec![(WordKind::Context, "let x = ".to_string()), (WordKind::Deleted, "foo(a, b);".to_string())]
+        );
+        assert_eq!(
+            side(1),
+            vec![(WordKind::Context, "let x = ".to_string()), (WordKind::Added, "bar(a, c);".to_string())]
+        );
+        assert!(lines[2].word_diff.is_none());
+        // git merged "remove me" into the next line, so only the new side matches
+        assert!(lines[4].word_diff.is_none());
+        assert_eq!(side(5).last().unwrap().1, "new value");
+        assert!(lines[6].word_diff.is_none());
+    }
+
+    #[test]
+    fn test_parse_plain_unified() {
+        let input = "--- old/lib.rs\t2024-01-01 10:00:00\n+++ new/lib.rs\t2024-01-02 10:00:00\n@@ -1,2 +1,2 @@\n keep\n-old\n+new\n\
+                     --- /dev/null\n+++ b/added.rs\n@@ -0,0 +1 @@\n+fn added() {}\n";
+
+        // Without the flag plain headers start no file
+        assert!(DiffParser::new().parse(input).unwrap().files.is_empty());
+
+        let parser = DiffParser::with_config(ParserConfig {
+            accept_plain_unified: true,
+            ..ParserConfig::default()
+        });
+        let diff = parser.parse(input).unwrap();
+        assert_eq!(diff.files.len(), 2);
+        assert_eq!(diff.files[0].display_path(), &PathBuf::from("new/lib.rs"));
+        assert_eq!(diff.files[0].hunks[0].lines.len(), 3);
+        assert_eq!(diff.files[1].mode, FileMode::Added);
+        assert_eq!(diff.stats.insertions, 2);
+
+        // git diffs parse the same: their `---`/`+++` lines and `--- text`
+        // deleted lines are not file headers
+        let git = parser.parse(MULTI_FILE_DIFF).unwrap();
+        let plain = DiffParser::new().parse(MULTI_FILE_DIFF).unwrap();
+        let json = |diff: &DiffData| serde_json::to_value((&diff.files, &diff.stats)).unwrap();
+        assert_eq!(json(&git), json(&plain));
+    }
+
+    #[test]
+    fn test_parse_with_stats_only() {
+        let parser = DiffParser::new();
+        for input in [SAMPLE_DIFF, MULTI_FILE_DIFF] {
+            let full = parser.parse(input).unwrap().stats;
+            let fast = parser.parse_with_stats_only(input).unwrap();
+            assert_eq!(fast.files_changed, full.files_changed);
+            assert_eq!(fast.insertions, full.insertions);
+            assert_eq!(fast.deletions, full.deletions);
+        }
+        assert!(parser.parse_with_stats_only("diff --git broken").is_err());
+    }
+
+    #[test]
+    fn test_parse_partial() {
+        let parser = DiffParser::new();
+        let full = parser.parse(MULTI_FILE_DIFF).unwrap();
+
+        let (diff, truncated) = parser.parse_partial(MULTI_FILE_DIFF, 10).unwrap();
+        assert!(!truncated);
+        assert_eq!(diff.files.len(), 3);
+        assert_eq!(diff.metadata.truncated_at_offset(), None);
+
+        let (diff, truncated) = parser.parse_partial(MULTI_FILE_DIFF, 1).unwrap();
+        assert!(truncated);
+        assert_eq!(diff.files.len(), 1);
+        assert_eq!(diff.stats.files_changed, 1);
+        assert_eq!(diff.stats.insertions, full.files[0].line_changes().0);
+        assert_eq!(diff.metadata.total_files(), Some(3));
+
+        // Resuming from the offset parses the rest
+        let offset = diff.metadata.truncated_at_offset().unwrap();
+        let (rest, truncated) = parser.parse_partial(&MULTI_FILE_DIFF[offset..], 10).unwrap();
+        assert!(!truncated);
+        assert_eq!(rest.files.len(), 2);
+        assert_eq!(rest.files[0].new_path, full.files[1].new_path);
+    }
+
+    #[test]
+    fn test_parse_file_list_only() {
+        let parser = DiffParser::new();
+        let files = parser.parse_file_list_only(MULTI_FILE_DIFF).unwrap();
+        let full = parser.parse(MULTI_FILE_DIFF).unwrap();
+
+        assert_eq!(files.len(), 3);
+        for ((old_path, new_path, mode), file) in files.iter().zip(&full.files) {
+            assert_eq!(old_path, &file.old_path);
+            assert_eq!(new_path, &file.new_path);
+            assert_eq!(mode, &file.mode);
+        }
+        assert_eq!(files[1].2, FileMode::Added);
+        assert_eq!(files[2].2, FileMode::Binary);
+    }
+
+    const SAMPLE_HG_DIFF: &str = "diff -r 9117c6561b0b src/main.rs
+--- src/main.rs\tThu Jan 01 00:00:00 1970 +0000
++++ src/main.rs\tThu Jan 01 00:00:00 1970 +0000
+@@ -1,2 +1,2 @@
+ fn main() {
+-    old();
++    new();
+diff -r 9117c6561b0b -r 2f3a1c0d4e5b docs/new file.md
+--- /dev/null\tThu Jan 01 00:00:00 1970 +0000
++++ docs/new file.md\tThu Jan 01 00:00:00 1970 +0000
+@@ -0,0 +1,1 @@
++# Notes
+diff -r 9117c6561b0b gone.rs
+--- gone.rs\tThu Jan 01 00:00:00 1970 +0000
++++ /dev/null\tThu Jan 01 00:00:00 1970 +0000
+@@ -1,1 +0,0 @@
+-fn gone() {}
+";
+
+    #[test]
+    fn test_parse_from_hg() {
+        let parser = DiffParser::new();
+        let diff = parser.parse_from_hg(SAMPLE_HG_DIFF).unwrap();
+
+        assert_eq!(diff.files.len(), 3);
+        assert_eq!(diff.files[0].display_path(), &PathBuf::from("src/main.rs"));
+        assert_eq!(diff.files[0].mode, FileMode::Modified);
+        assert_eq!(diff.files[0].hunks[0].lines.len(), 3);
+
+        assert_eq!(diff.files[1].new_path, Some(PathBuf::from("docs/new file.md")));
+        assert_eq!(diff.files[1].old_path, None);
+        assert_eq!(diff.files[1].mode, FileMode::Added);
+
+        assert_eq!(diff.files[2].old_path, Some(PathBuf::from("gone.rs")));
+        assert_eq!(diff.files[2].mode, FileMode::Deleted);
+
+        assert_eq!(diff.stats.insertions, 2);
+        assert_eq!(diff.stats.deletions, 2);
+    }
+
+    const SAMPLE_SVN_DIFF: &str = "Index: src/main.rs
+===================================================================
+--- src/main.rs\t(revision 12)
++++ src/main.rs\t(working copy)
+@@ -1,2 +1,2 @@
+ fn main() {
+-    old();
++    new();
+Index: docs/notes.md
+===================================================================
+--- docs/notes.md\t(nonexistent)
++++ docs/notes.md\t(working copy)
+@@ -0,0 +1 @@
++# Notes
+
+Property changes on: docs/notes.md
+___________________________________________________________________
+Added: svn:eol-style
+## -0,0 +1 ##
++native
+\\ No newline at end of property
+Index: logo.png
+===================================================================
+Cannot display: file marked as a binary type.
+svn:mime-type = application/octet-stream
+";
+
+    #[test]
+    fn test_parse_from_svn() {
+        let parser = DiffParser::new();
+        let diff = parser.parse_from_svn(SAMPLE_SVN_DIFF).unwrap();
+
+        assert_eq!(diff.files.len(), 3);
+        assert_eq!(diff.files[0].display_path(), &PathBuf::from("src/main.rs"));
+        assert_eq!(diff.files[0].mode, FileMode::Modified);
+        assert_eq!(diff.files[0].hunks[0].lines.len(), 3);
+
+        assert_eq!(diff.files[1].mode, FileMode::Added);
+        assert_eq!(diff.files[1].old_path, None);
+        assert_eq!(diff.files[1].hunks[0].lines.len(), 1);
+
+        assert_eq!(diff.files[2].mode, FileMode::Binary);
+        assert!(diff.files[2].hunks.is_empty());
+
+        assert_eq!(diff.stats.insertions, 2);
+        assert_eq!(diff.stats.deletions, 1);
+    }
+
+    #[test]
+    fn test_parse_unified() {
+        let input = "--- src/lib.rs.orig\t2024-01-01 10:00:00\n+++ src/lib.rs\t2024-01-02 10:00:00\n@@ -1,3 +1,3 @@\n a\n--- not a header\n+++ still a line\n c\n--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1 @@\n+fn new() {}\n";
+        let diff = DiffParser::new().parse_unified(input).unwrap();
+
+        assert_eq!(diff.files.len(), 2);
+        assert_eq!(diff.files[0].display_path(), &PathBuf::from("src/lib.rs"));
+        assert_eq!(diff.files[0].old_path, Some(PathBuf::from("src/lib.rs.orig")));
+        assert_eq!(diff.files[0].hunks[0].lines.len(), 4);
+        assert_eq!(diff.files[1].mode, FileMode::Added);
+        assert_eq!(diff.files[1].display_path(), &PathBuf::from("new.rs"));
+        assert_eq!(diff.stats.insertions, 2);
+        assert_eq!(diff.stats.deletions, 1);
+    }
+
+    #[test]
+    fn test_parse_from_file_detects_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let parser = DiffParser::new();
+        let parse = |name: &str, content: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            parser.parse_from_file(&path)
+        };
+
+        let mail = format!("From 1234 Mon Sep 17 00:00:00 2001\nSubject: [PATCH] x\n\n{}", SAMPLE_DIFF);
+        assert_eq!(parse("git.patch", &mail).unwrap().files.len(), 1);
+        assert_eq!(parse("svn.diff", SAMPLE_SVN_DIFF).unwrap().files.len(), 3);
+        assert_eq!(parse("hg.diff", SAMPLE_HG_DIFF).unwrap().files.len(), 3);
+        let unified = "--- a/x.rs\n+++ b/x.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        assert_eq!(parse("plain.diff", unified).unwrap().files.len(), 1);
+
+        assert!(matches!(
+            parse("notes.txt", "just some notes\n"),
+            Err(CrHelperError::UnsupportedDiffFormat(line)) if line == "just some notes"
+        ));
+        assert!(matches!(
+            parser.parse_from_file(&dir.path().join("missing.patch")),
+            Err(CrHelperError::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_hg_diff_header() {
+        let parser = DiffParser::new();
+        assert_eq!(
+            parser.parse_hg_diff_header("diff -r abc -r def a/b.rs").unwrap(),
+            PathBuf::from("a/b.rs")
+        );
+        assert!(parser.parse_hg_diff_header("diff -r abc").is_err());
+    }
 }
@@ -1,6 +1,6 @@
 //! Error types for cr-helper
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 /// Main error type for cr-helper
@@ -14,6 +14,14 @@ pub enum CrHelperError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    /// IO error on a known file (see [`PathContext`])
+    #[error("IO error on path '{}': {error}", path.display())]
+    IoWithPath {
+        #[source]
+        error: std::io::Error,
+        path: PathBuf,
+    },
+
     /// Serialization error
     #[error("Serialization error: {0}")]
     Serde(#[from] serde_json::Error),
@@ -22,6 +30,10 @@ pub enum CrHelperError {
     #[error("TOML error: {0}")]
     Toml(String),
 
+    /// MessagePack encoding or decoding error
+    #[error("MessagePack error: {0}")]
+    MessagePack(String),
+
     /// Session not found
     #[error("Session not found: {0}")]
     SessionNotFound(String),
@@ -34,6 +46,10 @@ pub enum CrHelperError {
     #[error("Invalid diff format: {0}")]
     InvalidDiff(String),
 
+    /// Diff file in a format the parser does not recognize (first line)
+    #[error("Unsupported diff format: {0:?}")]
+    UnsupportedDiffFormat(String),
+
     /// Validation error
     #[error("Validation error: {0}")]
     Validation(String),
@@ -54,6 +70,10 @@ pub enum CrHelperError {
     #[error("Delta is not installed. Please install delta: https://github.com/dandavison/delta")]
     DeltaNotInstalled,
 
+    /// Template rendering error
+    #[error("Template error: {0}")]
+    Template(String),
+
     /// External command error
     #[error("Command '{command}' failed: {message}")]
     Command { command: String, message: String },
@@ -75,6 +95,26 @@ impl CrHelperError {
             source: Box::new(self),
         }
     }
+
+    /// Wrap an IO error with the path it happened on
+    pub fn from_io_with_path(err: std::io::Error, path: &Path) -> Self {
+        CrHelperError::IoWithPath {
+            error: err,
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+/// Attach the file path to IO errors: `fs::read_to_string(path).with_path(path)?`
+pub trait PathContext<T> {
+    /// Turn an IO error into [`CrHelperError::IoWithPath`] for `path`
+    fn with_path(self, path: &Path) -> Result<T>;
+}
+
+impl<T> PathContext<T> for std::io::Result<T> {
+    fn with_path(self, path: &Path) -> Result<T> {
+        self.map_err(|e| CrHelperError::from_io_with_path(e, path))
+    }
 }
 
 /// Result type alias for cr-helper
@@ -103,4 +143,14 @@ mod tests {
         let err: CrHelperError = io_err.into();
         assert!(matches!(err, CrHelperError::Io(_)));
     }
+
+    #[test]
+    fn test_io_error_with_path() {
+        let path = Path::new("/nonexistent/session.json");
+        let err = std::fs::read_to_string(path).with_path(path).unwrap_err();
+        assert!(matches!(err, CrHelperError::IoWithPath { ref path, .. } if path == Path::new("/nonexistent/session.json")));
+        assert!(err
+            .to_string()
+            .starts_with("IO error on path '/nonexistent/session.json': "));
+    }
 }
@@ -1,9 +1,14 @@
 //! Code context extraction for exports
 
 use crate::comment::model::Comment;
-use crate::diff::DiffData;
+use crate::diff::{DiffData, Hunk, LineType};
+use crate::types::{CommentId, FileId, LineId};
+use std::collections::HashMap;
 use std::path::Path;
 
+/// How far [`ContextExtractor::extract_surrounding_function`] scans up or down
+pub const FUNCTION_SCAN_LIMIT: usize = 50;
+
 /// A single line in the code context
 pub struct ContextLine {
     /// Line number (if available)
@@ -38,64 +43,179 @@ impl ContextExtractor {
         Self { context_lines }
     }
 
+    /// Number of lines shown before and after the target
+    pub fn context_lines(&self) -> usize {
+        self.context_lines
+    }
+
     /// Extract context for a comment
     pub fn extract(&self, comment: &Comment, diff: &DiffData) -> Option<CodeContext> {
-        let file = diff.get_file(comment.file_id())?;
+        let (hunk, line_idx) = Self::locate(comment, diff)?;
+        Some(self.window(hunk, line_idx))
+    }
 
-        // Get the line ID from the comment
-        let line_ids = comment.line_ids();
-        let primary_line_id = line_ids.first()?;
+    /// Extract context for many comments at once
+    ///
+    /// Same result as calling [`extract`](Self::extract) for each comment,
+    /// but every file's hunks are scanned once instead of once per comment.
+    /// Comments without context are left out of the map.
+    pub fn extract_batch<'a>(
+        &self,
+        comments: impl IntoIterator<Item = &'a Comment>,
+        diff: &DiffData,
+    ) -> HashMap<CommentId, CodeContext> {
+        let mut by_file: HashMap<&FileId, Vec<(&LineId, &CommentId)>> = HashMap::new();
+        for comment in comments {
+            if let Some(line_id) = comment.line_ids().first() {
+                by_file
+                    .entry(comment.file_id())
+                    .or_default()
+                    .push((line_id, &comment.id));
+            }
+        }
 
-        // Find the line in the diff
-        let mut found_hunk_idx = None;
-        let mut found_line_idx = None;
+        let mut contexts = HashMap::new();
+        for (file_id, targets) in by_file {
+            let Some(file) = diff.get_file(file_id) else {
+                continue;
+            };
 
-        for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
-            for (line_idx, line) in hunk.lines.iter().enumerate() {
-                if &line.id == *primary_line_id {
-                    found_hunk_idx = Some(hunk_idx);
-                    found_line_idx = Some(line_idx);
-                    break;
+            // First position of each commented line, in one pass over the hunks
+            let mut positions: HashMap<&LineId, Option<(&Hunk, usize)>> =
+                targets.iter().map(|(line_id, _)| (*line_id, None)).collect();
+            let mut remaining = positions.len();
+            'hunks: for hunk in &file.hunks {
+                for (idx, line) in hunk.lines.iter().enumerate() {
+                    if let Some(slot @ None) = positions.get_mut(&line.id) {
+                        *slot = Some((hunk, idx));
+                        remaining -= 1;
+                        if remaining == 0 {
+                            break 'hunks;
+                        }
+                    }
                 }
             }
-            if found_hunk_idx.is_some() {
-                break;
+
+            for (line_id, comment_id) in targets {
+                if let Some((hunk, line_idx)) = positions.get(line_id).copied().flatten() {
+                    contexts.insert(comment_id.clone(), self.window(hunk, line_idx));
+                }
             }
         }
+        contexts
+    }
 
-        let hunk_idx = found_hunk_idx?;
-        let line_idx = found_line_idx?;
+    /// Extract the function enclosing a comment's line
+    ///
+    /// Heuristic: scans up (at most [`FUNCTION_SCAN_LIMIT`] lines) for a
+    /// `fn`/`def`/`class` line, then down to the matching closing brace, or
+    /// to the first dedent for Python. If no start is found inside the hunk
+    /// but the hunk header names a function (`@@ ... @@ fn foo`), the hunk
+    /// start is used instead. Returns `None` if no function can be found.
+    pub fn extract_surrounding_function(
+        &self,
+        comment: &Comment,
+        diff: &DiffData,
+    ) -> Option<CodeContext> {
+        let (hunk, line_idx) = Self::locate(comment, diff)?;
+        let file = diff.get_file(comment.file_id())?;
+        let python = Self::get_language(&file.display_path().to_string_lossy()) == "python";
+
+        // Lines of the version the comment is on
+        let side = hunk.lines[line_idx].line_type;
+        let visible = |i: usize| match hunk.lines[i].line_type {
+            LineType::Added => side != LineType::Deleted,
+            LineType::Deleted => side == LineType::Deleted,
+            _ => true,
+        };
 
-        let hunk = &file.hunks[hunk_idx];
+        let scan_start = line_idx.saturating_sub(FUNCTION_SCAN_LIMIT);
+        let start = (scan_start..=line_idx)
+            .rev()
+            .find(|&i| visible(i) && is_function_start(&hunk.lines[i].content, python));
+        let start = match start {
+            Some(start) => start,
+            None if hunk_function_context(&hunk.header).is_some() => scan_start,
+            None => return None,
+        };
 
-        // Calculate context range
+        let limit = (line_idx + FUNCTION_SCAN_LIMIT + 1).min(hunk.lines.len());
+        let mut body = (start..limit).filter(|&i| visible(i));
+        let end = if python {
+            let indent = indentation(&hunk.lines[start].content);
+            body.skip(1)
+                .find(|&i| {
+                    let content = &hunk.lines[i].content;
+                    i > line_idx && !content.trim().is_empty() && indentation(content) <= indent
+                })
+                .unwrap_or(limit)
+        } else {
+            let mut depth = 0i32;
+            let mut opened = false;
+            body
+                .find(|&i| {
+                    for c in hunk.lines[i].content.chars() {
+                        match c {
+                            '{' => {
+                                depth += 1;
+                                opened = true;
+                            }
+                            '}' => depth -= 1,
+                            _ => {}
+                        }
+                    }
+                    opened && depth <= 0 && i >= line_idx
+                })
+                .map(|i| i + 1)
+                .unwrap_or(limit)
+        };
+
+        Some(Self::build_context(hunk, start..end, line_idx))
+    }
+
+    /// Find the hunk and line index a comment points at
+    fn locate<'a>(comment: &Comment, diff: &'a DiffData) -> Option<(&'a Hunk, usize)> {
+        let file = diff.get_file(comment.file_id())?;
+        let line_ids = comment.line_ids();
+        let primary_line_id = line_ids.first()?;
+
+        file.hunks.iter().find_map(|hunk| {
+            hunk.lines
+                .iter()
+                .position(|line| &line.id == *primary_line_id)
+                .map(|idx| (hunk, idx))
+        })
+    }
+
+    /// Context of `context_lines` around a hunk line
+    fn window(&self, hunk: &Hunk, line_idx: usize) -> CodeContext {
         let start = line_idx.saturating_sub(self.context_lines);
         let end = (line_idx + self.context_lines + 1).min(hunk.lines.len());
+        Self::build_context(hunk, start..end, line_idx)
+    }
 
-        // Get the target line info
-        let target_line = &hunk.lines[line_idx];
+    /// Build a context from a range of hunk lines
+    fn build_context(hunk: &Hunk, range: std::ops::Range<usize>, target_idx: usize) -> CodeContext {
+        let target_line = &hunk.lines[target_idx];
         let target_line_num = target_line.new_line_num.or(target_line.old_line_num);
 
-        // Build context lines with line numbers
-        let mut lines = Vec::new();
-        for i in start..end {
-            let line = &hunk.lines[i];
-            let prefix = line.line_type.prefix();
-            let line_num = line.new_line_num.or(line.old_line_num);
-            let is_target = i == line_idx;
-            lines.push(ContextLine {
-                line_num,
-                prefix,
-                content: line.content.clone(),
-                is_target,
-            });
-        }
+        let lines = range
+            .map(|i| {
+                let line = &hunk.lines[i];
+                ContextLine {
+                    line_num: line.new_line_num.or(line.old_line_num),
+                    prefix: line.line_type.prefix(),
+                    content: line.content.clone(),
+                    is_target: i == target_idx,
+                }
+            })
+            .collect();
 
-        Some(CodeContext {
+        CodeContext {
             lines,
             target_line_num,
             target_content: target_line.content.clone(),
-        })
+        }
     }
 
     /// Get the programming language from file extension
@@ -158,6 +278,34 @@ impl ContextExtractor {
     }
 }
 
+/// Check if a line starts a function (or class, for Python)
+fn is_function_start(content: &str, python: bool) -> bool {
+    let line = content.trim_start();
+    if python {
+        return ["def ", "async def ", "class "]
+            .iter()
+            .any(|p| line.starts_with(p));
+    }
+    let line = line
+        .strip_prefix("pub(crate) ")
+        .or_else(|| line.strip_prefix("pub "))
+        .unwrap_or(line);
+    let line = line.strip_prefix("async ").unwrap_or(line);
+    line.starts_with("fn ")
+}
+
+/// Function context git appends to a hunk header (`@@ -1,2 +1,3 @@ fn foo`)
+fn hunk_function_context(header: &str) -> Option<&str> {
+    let rest = header.strip_prefix("@@")?;
+    let (_, context) = rest.split_once("@@")?;
+    let context = context.trim();
+    (!context.is_empty()).then_some(context)
+}
+
+fn indentation(content: &str) -> usize {
+    content.len() - content.trim_start().len()
+}
+
 impl Default for ContextExtractor {
     fn default() -> Self {
         Self::new(2)
@@ -225,4 +373,94 @@ mod tests {
         let result = extractor.extract(&comment, &diff);
         assert!(result.is_none());
     }
+
+    fn comment_on(diff: &DiffData, content: &str) -> Comment {
+        use crate::comment::builder::CommentBuilder;
+        use crate::comment::model::DiffSide;
+
+        let file = &diff.files[0];
+        let line = file.hunks[0]
+            .lines
+            .iter()
+            .find(|l| l.content == content)
+            .unwrap();
+        CommentBuilder::new(file.id.clone(), line.id.clone(), DiffSide::New)
+            .content("Test")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_extract_batch_matches_extract() {
+        use crate::diff::DiffParser;
+
+        let diff = DiffParser::new()
+            .parse("diff --git a/a.rs b/a.rs\n@@ -1,3 +1,4 @@\n fn a() {\n-    old();\n+    new();\n+    more();\n }\n")
+            .unwrap();
+        let comments: Vec<Comment> = ["fn a() {", "    new();", "}"]
+            .iter()
+            .map(|content| comment_on(&diff, content))
+            .collect();
+        let extractor = ContextExtractor::new(1);
+
+        let batch = extractor.extract_batch(&comments, &diff);
+        assert_eq!(batch.len(), 3);
+        for comment in &comments {
+            let single = extractor.extract(comment, &diff).unwrap();
+            let batched = &batch[&comment.id];
+            assert_eq!(batched.target_line_num, single.target_line_num);
+            assert_eq!(batched.lines.len(), single.lines.len());
+        }
+        assert!(extractor.extract_batch(&comments, &DiffData::empty()).is_empty());
+    }
+
+    #[test]
+    fn test_extract_surrounding_function() {
+        use crate::diff::DiffParser;
+
+        let diff = DiffParser::new()
+            .parse(
+                "diff --git a/a.rs b/a.rs\n@@ -1,9 +1,10 @@\n use x;\n \n pub async fn foo() {\n     if y {\n-        old();\n+        new();\n+        more();\n     }\n }\n \n fn bar() {}\n",
+            )
+            .unwrap();
+        let extractor = ContextExtractor::new(1);
+
+        let ctx = extractor
+            .extract_surrounding_function(&comment_on(&diff, "        new();"), &diff)
+            .unwrap();
+        let contents: Vec<&str> = ctx.lines.iter().map(|l| l.content.as_str()).collect();
+        assert_eq!(contents.first(), Some(&"pub async fn foo() {"));
+        assert_eq!(contents.last(), Some(&"}"));
+        assert_eq!(contents.len(), 7);
+
+        assert!(extractor
+            .extract_surrounding_function(&comment_on(&diff, "use x;"), &diff)
+            .is_none());
+    }
+
+    #[test]
+    fn test_extract_surrounding_function_python_and_hint() {
+        use crate::diff::DiffParser;
+
+        let diff = DiffParser::new()
+            .parse(
+                "diff --git a/a.py b/a.py\n@@ -1,4 +1,5 @@\n def f():\n     x = 1\n+    y = 2\n \n def g():\n",
+            )
+            .unwrap();
+        let ctx = ContextExtractor::new(1)
+            .extract_surrounding_function(&comment_on(&diff, "    y = 2"), &diff)
+            .unwrap();
+        assert_eq!(ctx.lines.len(), 4);
+        assert_eq!(ctx.lines[0].content, "def f():");
+
+        // The function starts above the hunk; the header names it
+        let diff = DiffParser::new()
+            .parse("diff --git a/a.rs b/a.rs\n@@ -10,2 +10,2 @@ fn long() {\n     a();\n-    b();\n+    c();\n")
+            .unwrap();
+        let ctx = ContextExtractor::new(1)
+            .extract_surrounding_function(&comment_on(&diff, "    c();"), &diff)
+            .unwrap();
+        assert_eq!(ctx.lines.len(), 3);
+        assert_eq!(hunk_function_context("@@ -1 +1 @@"), None);
+    }
 }
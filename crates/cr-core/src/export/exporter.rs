@@ -1,11 +1,14 @@
 //! Exporter trait and manager
 
+use crate::comment::{Comment, CommentManager, CommentState, Severity};
+use crate::diff::{DiffStats, FileMode};
 use crate::error::{CrHelperError, Result};
 use crate::session::Session;
-use std::collections::HashMap;
+use crate::types::FileId;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Trait for session exporters
 pub trait Exporter: Send + Sync {
@@ -17,11 +20,125 @@ pub trait Exporter: Send + Sync {
 
     /// Get the file extension
     fn file_extension(&self) -> &str;
+
+    /// Export a session with only the comments matching `filter`
+    fn export_filtered(&self, session: &Session, filter: &ExportFilter) -> Result<String> {
+        self.export(&filter.apply(session)?)
+    }
+
+    /// Check that `output` from this exporter is well-formed
+    ///
+    /// Exporters without a checker accept anything.
+    fn validate_output(&self, output: &str) -> Result<ValidationReport> {
+        let _ = output;
+        Ok(ValidationReport::default())
+    }
+}
+
+/// Problems found in an export by [`Exporter::validate_output`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Human-readable descriptions of each problem
+    pub problems: Vec<String>,
+}
+
+impl ValidationReport {
+    /// Check if no problems were found
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Which comments to include in an export
+///
+/// Empty lists don't restrict anything; a comment must pass every set
+/// criterion.
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// Drop comments less severe than this
+    pub min_severity: Option<Severity>,
+    /// Keep comments in one of these states
+    pub states: Vec<CommentState>,
+    /// Keep comments with at least one of these tags
+    pub tags: Vec<String>,
+    /// Keep comments on these files, or files under these directories
+    pub files: Vec<String>,
+    /// Keep only files with one of these modes, and comments on them
+    pub file_modes: Vec<FileMode>,
+}
+
+impl ExportFilter {
+    /// Whether the filter keeps every comment
+    pub fn is_empty(&self) -> bool {
+        self.min_severity.is_none()
+            && self.states.is_empty()
+            && self.tags.is_empty()
+            && self.files.is_empty()
+            && self.file_modes.is_empty()
+    }
+
+    /// Whether `comment` passes the filter
+    pub fn matches(&self, comment: &Comment, session: &Session) -> bool {
+        if self.min_severity.is_some_and(|min| comment.severity < min) {
+            return false;
+        }
+        if !self.states.is_empty() && !self.states.contains(&comment.state) {
+            return false;
+        }
+        if !self.tags.is_empty() && !comment.tags.iter().any(|t| self.tags.contains(t)) {
+            return false;
+        }
+        if !self.files.is_empty() {
+            let path = session
+                .diff_data
+                .get_file(comment.file_id())
+                .map(|f| f.display_path().to_string_lossy().to_string())
+                .or_else(|| comment.metadata.file_path.clone())
+                .unwrap_or_default();
+            let under = |file: &String| {
+                let dir = file.trim_end_matches('/');
+                path == dir || path.starts_with(&format!("{}/", dir))
+            };
+            if !self.files.iter().any(under) {
+                return false;
+            }
+        }
+        if !self.file_modes.is_empty() {
+            let shown = session
+                .diff_data
+                .get_file(comment.file_id())
+                .is_some_and(|f| self.file_modes.contains(&f.mode));
+            if !shown {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// A copy of `session` with only the matching comments
+    pub fn apply(&self, session: &Session) -> Result<Session> {
+        let mut filtered = session.clone();
+        if self.is_empty() {
+            return Ok(filtered);
+        }
+        filtered.comments = CommentManager::new();
+        for comment in session.comments.all_sorted() {
+            if self.matches(comment, session) {
+                filtered.comments.add(comment.clone())?;
+            }
+        }
+        if !self.file_modes.is_empty() {
+            filtered.diff_data = session.diff_data.filter_by_mode(&self.file_modes);
+        }
+        Ok(filtered)
+    }
 }
 
 /// Manager for handling multiple export formats
 pub struct ExportManager {
     exporters: HashMap<String, Box<dyn Exporter>>,
+    /// Comments to include in every export
+    filter: ExportFilter,
 }
 
 impl ExportManager {
@@ -29,21 +146,70 @@ impl ExportManager {
     pub fn new() -> Self {
         let mut manager = Self {
             exporters: HashMap::new(),
+            filter: ExportFilter::default(),
         };
 
-        // Register default exporters
-        manager.register(Box::new(super::json::JsonExporter::new(false)));
-        manager.register(Box::new(super::json::JsonExporter::compact()));
-        manager.register(Box::new(super::markdown::MarkdownExporter::new()));
-        manager.register(Box::new(super::markdown::MarkdownEnhancedExporter::new()));
+        // Built-in exporters go through the same path as custom ones
+        let builtins: [Box<dyn Exporter>; 6] = [
+            Box::new(super::json::JsonExporter::new(false)),
+            Box::new(super::json::JsonExporter::compact()),
+            Box::new(super::markdown::MarkdownExporter::new()),
+            Box::new(super::markdown::MarkdownEnhancedExporter::new()),
+            Box::new(super::markdown::MarkdownExecutiveExporter::new()),
+            Box::new(super::inline::InlineAnnotationExporter),
+        ];
+        for exporter in builtins {
+            manager.register(exporter);
+        }
 
         manager
     }
 
-    /// Register a new exporter
+    /// Register a new exporter under its format name
     pub fn register(&mut self, exporter: Box<dyn Exporter>) {
-        self.exporters
-            .insert(exporter.format_name().to_string(), exporter);
+        let name = exporter.format_name().to_string();
+        self.register_custom(&name, exporter);
+    }
+
+    /// Register an exporter under `name`, replacing any exporter already
+    /// registered with that name
+    pub fn register_custom(&mut self, name: &str, exporter: Box<dyn Exporter>) {
+        self.exporters.insert(name.to_string(), exporter);
+    }
+
+    /// Load an exporter from a shared library
+    ///
+    /// The library must export `fn cr_helper_exporter() -> Box<dyn Exporter>`
+    /// (with `#[no_mangle]`), built with the same compiler and cr-core
+    /// version as cr-helper. The exporter is registered under its format
+    /// name and the library stays loaded for the rest of the process.
+    #[cfg(feature = "dynamic-plugins")]
+    pub fn register_from_dylib(&mut self, path: &Path) -> Result<()> {
+        type Constructor = fn() -> Box<dyn Exporter>;
+
+        let plugin_error = |e: libloading::Error| {
+            CrHelperError::Validation(format!("Failed to load exporter plugin {}: {}", path.display(), e))
+        };
+        // SAFETY: loading runs the library's initializers and the symbol is
+        // trusted to have the documented signature; both are the plugin
+        // author's responsibility.
+        let library = unsafe { libloading::Library::new(path) }.map_err(plugin_error)?;
+        let exporter = unsafe {
+            let constructor = library
+                .get::<Constructor>(b"cr_helper_exporter")
+                .map_err(plugin_error)?;
+            constructor()
+        };
+        // The exporter's vtable lives in the library
+        std::mem::forget(library);
+
+        self.register(exporter);
+        Ok(())
+    }
+
+    /// Only export comments matching `filter`
+    pub fn set_filter(&mut self, filter: ExportFilter) {
+        self.filter = filter;
     }
 
     /// Export a session to the specified format
@@ -52,7 +218,48 @@ impl ExportManager {
             CrHelperError::Validation(format!("Unknown export format: {}", format))
         })?;
 
-        exporter.export(session)
+        exporter.export_filtered(session, &self.filter)
+    }
+
+    /// Export a session and check the output with the exporter's validator
+    ///
+    /// Fails listing every problem if the output is malformed.
+    pub fn export_validated(&self, session: &Session, format: &str) -> Result<String> {
+        let output = self.export(session, format)?;
+        let exporter = self.exporters.get(format).ok_or_else(|| {
+            CrHelperError::Validation(format!("Unknown export format: {}", format))
+        })?;
+        let report = exporter.validate_output(&output)?;
+        if !report.is_valid() {
+            return Err(CrHelperError::Validation(format!(
+                "Malformed {} export: {}",
+                format,
+                report.problems.join("; ")
+            )));
+        }
+        Ok(output)
+    }
+
+    /// The first `max_lines` lines of an export, without writing a file
+    ///
+    /// Longer exports end with a `... (truncated)` line.
+    pub fn preview(&self, session: &Session, format: &str, max_lines: usize) -> Result<String> {
+        let content = self.export(session, format)?;
+        let mut lines = content.lines();
+        let mut preview: String = lines
+            .by_ref()
+            .take(max_lines)
+            .flat_map(|line| [line, "\n"])
+            .collect();
+        if lines.next().is_some() {
+            preview.push_str("... (truncated)\n");
+        }
+        Ok(preview)
+    }
+
+    /// Approximate size in bytes of the exported file
+    pub fn estimate_size(&self, session: &Session, format: &str) -> Result<usize> {
+        Ok(self.export(session, format)?.len())
     }
 
     /// Export a session to a file
@@ -90,6 +297,57 @@ impl ExportManager {
         Ok(())
     }
 
+    /// Export one document per file that has comments
+    ///
+    /// Each document covers a copy of the session reduced to that file. The
+    /// returned names are the file's path with `/`, `\\` and `.` replaced by
+    /// `_`, the first eight hex digits of the path's BLAKE3 hash so paths
+    /// like `src/a.rs` and `src_a.rs` don't collide, and the format's
+    /// extension (`src/main.rs` -> `src_main_rs-<hash>.md`), sorted by name.
+    pub fn split_by_file(&self, session: &Session, format: &str) -> Result<Vec<(PathBuf, String)>> {
+        let exporter = self.exporters.get(format).ok_or_else(|| {
+            CrHelperError::Validation(format!("Unknown export format: {}", format))
+        })?;
+
+        let session = &self.filter.apply(session)?;
+        let mut by_file: BTreeMap<String, FileId> = BTreeMap::new();
+        for comment in session.comments.all() {
+            let file_id = comment.file_id();
+            let path = session
+                .diff_data
+                .get_file(file_id)
+                .map(|f| f.display_path().to_string_lossy().to_string())
+                .or_else(|| comment.metadata.file_path.clone())
+                .unwrap_or_else(|| file_id.to_string());
+            by_file.insert(path, file_id.clone());
+        }
+
+        let mut reports = Vec::new();
+        for (path, file_id) in by_file {
+            let mut part = session.clone();
+            part.diff_data.files.retain(|f| f.id == file_id);
+            part.diff_data.stats = DiffStats::from_diff(&part.diff_data);
+            part.comments = CommentManager::new();
+            for comment in session.comments.all_sorted() {
+                if comment.file_id() == &file_id {
+                    part.comments.add(comment.clone())?;
+                }
+            }
+
+            let hash = blake3::hash(path.as_bytes()).to_hex();
+            let name = format!(
+                "{}-{}.{}",
+                path.replace(['/', '\\', '.'], "_"),
+                &hash[..8],
+                exporter.file_extension()
+            );
+            reports.push((PathBuf::from(name), exporter.export(&part)?));
+        }
+        reports.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(reports)
+    }
+
     /// Export a session and write to stdout
     pub fn export_to_stdout(&self, session: &Session, format: &str) -> Result<()> {
         let content = self.export(session, format)?;
@@ -97,6 +355,13 @@ impl ExportManager {
         Ok(())
     }
 
+    /// Names of every registered exporter, sorted
+    pub fn registered_formats(&self) -> Vec<&str> {
+        let mut formats: Vec<&str> = self.exporters.keys().map(String::as_str).collect();
+        formats.sort();
+        formats
+    }
+
     /// Get list of available format names
     pub fn available_formats(&self) -> Vec<String> {
         let mut formats: Vec<_> = self.exporters.keys().cloned().collect();
@@ -154,6 +419,7 @@ mod tests {
         assert!(manager.has_format("json-compact"));
         assert!(manager.has_format("markdown"));
         assert!(manager.has_format("markdown-enhanced"));
+        assert!(manager.has_format("inline-annotations"));
     }
 
     #[test]
@@ -163,6 +429,28 @@ mod tests {
         assert!(manager.has_format("test"));
     }
 
+    #[test]
+    fn test_register_custom() {
+        let mut manager = ExportManager::new();
+        manager.register_custom("my-report", Box::new(TestExporter));
+        assert!(manager.has_format("my-report"));
+        assert!(!manager.has_format("test"));
+
+        let formats = manager.registered_formats();
+        assert_eq!(
+            formats,
+            [
+                "inline-annotations",
+                "json",
+                "json-compact",
+                "markdown",
+                "markdown-enhanced",
+                "markdown-executive",
+                "my-report"
+            ]
+        );
+    }
+
     #[test]
     fn test_export_unknown_format() {
         let manager = ExportManager::new();
@@ -171,6 +459,27 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_preview_and_estimate_size() {
+        let manager = ExportManager::new();
+        let session = create_test_session();
+        let full = manager.export(&session, "markdown").unwrap();
+        assert!(full.lines().count() > 3);
+
+        let preview = manager.preview(&session, "markdown", 3).unwrap();
+        assert_eq!(preview.lines().count(), 4);
+        assert!(preview.ends_with("... (truncated)\n"));
+        assert!(full.starts_with(preview.lines().next().unwrap()));
+
+        let all = manager.preview(&session, "markdown", 1000).unwrap();
+        assert!(!all.contains("(truncated)"));
+        assert_eq!(
+            manager.estimate_size(&session, "markdown").unwrap(),
+            full.len()
+        );
+        assert!(manager.preview(&session, "unknown", 3).is_err());
+    }
+
     #[test]
     fn test_available_formats() {
         let manager = ExportManager::new();
@@ -190,6 +499,151 @@ mod tests {
         assert!(json.contains("sid"));
     }
 
+    #[test]
+    fn test_split_by_file() {
+        use crate::comment::{CommentBuilder, DiffSide};
+        use crate::types::LineId;
+
+        let mut session = create_test_session();
+        for (file, content) in [
+            ("src/main.rs", "a"),
+            ("src/lib.rs", "b"),
+            ("src/main.rs", "c"),
+            ("src_main.rs", "d"),
+        ] {
+            let comment = CommentBuilder::new(
+                FileId::from_path(Path::new(file)),
+                LineId::from_string(content),
+                DiffSide::New,
+            )
+            .content(content)
+            .file_path(file)
+            .build()
+            .unwrap();
+            session.comments.add(comment).unwrap();
+        }
+
+        let manager = ExportManager::new();
+        let reports = manager.split_by_file(&session, "markdown").unwrap();
+        let names: Vec<String> = reports
+            .iter()
+            .map(|(p, _)| p.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names.len(), 3);
+        assert!(names[0].starts_with("src_lib_rs-") && names[0].ends_with(".md"));
+        // src/main.rs and src_main.rs flatten to the same stem
+        assert!(names[1].starts_with("src_main_rs-") && names[2].starts_with("src_main_rs-"));
+        assert_ne!(names[1], names[2]);
+
+        let report = |content: &str| {
+            reports
+                .iter()
+                .find(|(_, r)| r.contains(&format!("\n{}\n", content)))
+                .unwrap()
+        };
+        assert!(!report("b").1.contains("\na\n"));
+        assert_eq!(report("a").0, report("c").0);
+        assert_ne!(report("a").0, report("d").0);
+
+        assert!(manager.split_by_file(&session, "unknown").is_err());
+    }
+
+    #[test]
+    fn test_export_filter() {
+        use crate::comment::{CommentBuilder, DiffSide};
+        use crate::types::LineId;
+
+        let mut session = create_test_session();
+        for (file, content, severity, tag) in [
+            ("src/auth/login.rs", "sql", Severity::Critical, "security"),
+            ("src/auth/login.rs", "name", Severity::Info, "style"),
+            ("src/main.rs", "unwrap", Severity::Warning, "security"),
+        ] {
+            let comment = CommentBuilder::new(
+                FileId::from_path(Path::new(file)),
+                LineId::from_string(content),
+                DiffSide::New,
+            )
+            .content(content)
+            .severity(severity)
+            .tag(tag)
+            .file_path(file)
+            .build()
+            .unwrap();
+            session.comments.add(comment).unwrap();
+        }
+
+        let count = |filter: ExportFilter| filter.apply(&session).unwrap().comments.count();
+        assert_eq!(count(ExportFilter::default()), 3);
+        assert_eq!(
+            count(ExportFilter {
+                min_severity: Some(Severity::Warning),
+                ..Default::default()
+            }),
+            2
+        );
+        assert_eq!(
+            count(ExportFilter {
+                tags: vec!["security".to_string()],
+                files: vec!["src/auth/".to_string()],
+                ..Default::default()
+            }),
+            1
+        );
+        assert_eq!(
+            count(ExportFilter {
+                states: vec![CommentState::Resolved],
+                ..Default::default()
+            }),
+            0
+        );
+
+        let mut manager = ExportManager::new();
+        manager.set_filter(ExportFilter {
+            min_severity: Some(Severity::Critical),
+            ..Default::default()
+        });
+        let json = manager.export(&session, "json").unwrap();
+        assert!(json.contains("sql") && !json.contains("unwrap"));
+        assert_eq!(
+            manager.split_by_file(&session, "markdown").unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_export_filter_file_modes() {
+        use crate::comment::{CommentBuilder, DiffSide};
+        use crate::diff::DiffParser;
+
+        let diff = DiffParser::new()
+            .parse(
+                "diff --git a/new.rs b/new.rs\nnew file mode 100644\n--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1 @@\n+a\n\
+                 diff --git a/lib.rs b/lib.rs\n@@ -1 +1,2 @@\n a\n+b\n",
+            )
+            .unwrap();
+        let mut session = Session::new(DiffSource::WorkingTree, diff);
+        for file in session.diff_data.files.clone() {
+            let line = file.hunks[0].lines[0].id.clone();
+            let comment = CommentBuilder::new(file.id.clone(), line, DiffSide::New)
+                .content(file.display_path().to_string_lossy())
+                .build()
+                .unwrap();
+            session.comments.add(comment).unwrap();
+        }
+
+        let filter = ExportFilter {
+            file_modes: vec![FileMode::Added],
+            ..Default::default()
+        };
+        let filtered = filter.apply(&session).unwrap();
+        assert_eq!(filtered.diff_data.files.len(), 1);
+        assert_eq!(filtered.diff_data.stats.insertions, 1);
+        let comments = filtered.comments.all();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].content, "new.rs");
+    }
+
     #[test]
     fn test_export_markdown() {
         let manager = ExportManager::new();
@@ -0,0 +1,51 @@
+//! Compiler-style inline annotation exporter
+
+use super::exporter::Exporter;
+use crate::error::Result;
+use crate::session::Session;
+
+/// Exporter producing `file:line:col: severity: message` lines
+///
+/// See [`CommentManager::to_inline_annotations`](crate::comment::CommentManager::to_inline_annotations).
+/// Editors with a compilation mode (emacs `compilation-mode`, vim's
+/// `errorformat`) can jump straight to each comment.
+pub struct InlineAnnotationExporter;
+
+impl Exporter for InlineAnnotationExporter {
+    fn export(&self, session: &Session) -> Result<String> {
+        Ok(session.comments.to_inline_annotations(&session.diff_data))
+    }
+
+    fn format_name(&self) -> &str {
+        "inline-annotations"
+    }
+
+    fn file_extension(&self) -> &str {
+        "txt"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comment::{CommentBuilder, DiffSide};
+    use crate::diff::DiffParser;
+    use crate::session::DiffSource;
+
+    #[test]
+    fn test_inline_annotation_export() {
+        let diff = DiffParser::new()
+            .parse("diff --git a/a.rs b/a.rs\n@@ -1 +1 @@\n-a\n+    b()\n")
+            .unwrap();
+        let mut session = Session::new(DiffSource::WorkingTree, diff.clone());
+        let file = &diff.files[0];
+        let comment = CommentBuilder::new(file.id.clone(), file.hunks[0].lines[1].id.clone(), DiffSide::New)
+            .content("Check the result")
+            .build()
+            .unwrap();
+        session.comments.add(comment).unwrap();
+
+        let output = InlineAnnotationExporter.export(&session).unwrap();
+        assert_eq!(output, "a.rs:1:5: info: Check the result\n        b()\n        ^\n");
+    }
+}
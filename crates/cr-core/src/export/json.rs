@@ -1,7 +1,7 @@
 //! JSON exporter for sessions
 
-use super::context::ContextExtractor;
-use super::exporter::Exporter;
+use super::context::{CodeContext, ContextExtractor};
+use super::exporter::{Exporter, ValidationReport};
 use crate::comment::model::Severity;
 use crate::error::Result;
 use crate::session::Session;
@@ -72,6 +72,27 @@ impl Exporter for JsonExporter {
     fn file_extension(&self) -> &str {
         "json"
     }
+
+    /// Checks the output parses and carries the current protocol version
+    fn validate_output(&self, output: &str) -> Result<ValidationReport> {
+        let mut report = ValidationReport::default();
+        let value: serde_json::Value = match serde_json::from_str(output) {
+            Ok(value) => value,
+            Err(e) => {
+                report.problems.push(format!("invalid JSON: {}", e));
+                return Ok(report);
+            }
+        };
+        let expected = ProtocolVersion::default().to_string();
+        match value.get("v").and_then(|v| v.as_str()) {
+            Some(v) if v == expected => {}
+            Some(v) => report
+                .problems
+                .push(format!("protocol version {} (expected {})", v, expected)),
+            None => report.problems.push("missing protocol version `v`".to_string()),
+        }
+        Ok(report)
+    }
 }
 
 /// Exported data structure (compact field names for token optimization)
@@ -95,11 +116,11 @@ pub struct ExportData {
 impl ExportData {
     /// Create from a session
     pub fn from_session(session: &Session, context: &ContextExtractor) -> Self {
-        let reviews: Vec<ExportReview> = session
-            .comments
-            .all()
+        let comments = session.comments.all();
+        let contexts = context.extract_batch(comments.iter().copied(), &session.diff_data);
+        let reviews: Vec<ExportReview> = comments
             .iter()
-            .map(|c| ExportReview::from_comment(c, &session.diff_data, context))
+            .map(|c| ExportReview::from_comment_with_context(c, contexts.get(&c.id)))
             .collect();
 
         let stats = ExportStats::from_session(session);
@@ -189,6 +210,14 @@ impl ExportReview {
         comment: &crate::comment::model::Comment,
         diff: &crate::diff::DiffData,
         context: &ContextExtractor,
+    ) -> Self {
+        Self::from_comment_with_context(comment, context.extract(comment, diff).as_ref())
+    }
+
+    /// Create from a comment and its already extracted context
+    pub fn from_comment_with_context(
+        comment: &crate::comment::model::Comment,
+        context: Option<&CodeContext>,
     ) -> Self {
         let file_path = comment
             .metadata
@@ -199,7 +228,7 @@ impl ExportReview {
         let line_num = comment.metadata.line_number;
 
         // Convert CodeContext to simple string for JSON
-        let ctx = context.extract(comment, diff).map(|c| {
+        let ctx = context.map(|c| {
             c.lines.iter()
                 .map(|l| {
                     let line_num = l.line_num.map(|n| format!("{:>4}", n)).unwrap_or_else(|| "    ".to_string());
@@ -400,4 +429,15 @@ mod tests {
         let json = serde_json::to_string(&range).unwrap();
         assert_eq!(json, "[10,20]");
     }
+
+    #[test]
+    fn test_validate_output() {
+        let exporter = JsonExporter::pretty();
+        let output = exporter.export(&create_session_with_comments()).unwrap();
+        assert!(exporter.validate_output(&output).unwrap().is_valid());
+
+        let report = exporter.validate_output("{\"v\": \"0.9\"}").unwrap();
+        assert_eq!(report.problems, ["protocol version 0.9 (expected 1.0)"]);
+        assert!(!exporter.validate_output("{\"v\": ").unwrap().is_valid());
+    }
 }
@@ -1,10 +1,20 @@
 //! Markdown exporters for sessions
 
-use super::context::ContextExtractor;
-use super::exporter::Exporter;
+use super::context::{CodeContext, ContextExtractor};
+use super::exporter::{Exporter, ValidationReport};
 use crate::comment::model::{Comment, Severity};
+use crate::config::RiskThresholds;
+use crate::diff::{DiffStats, WordKind, STAT_BAR_WIDTH};
 use crate::error::Result;
-use crate::session::Session;
+use crate::session::{ReviewDelta, Session};
+use crate::types::CommentId;
+use std::collections::{HashMap, HashSet};
+
+/// Sessions with fewer comments than this get no table of contents
+const TOC_MIN_COMMENTS: usize = 5;
+
+/// Maximum length of the comment excerpt in a table of contents entry
+const TOC_EXCERPT_LEN: usize = 50;
 
 /// Markdown exporter
 pub struct MarkdownExporter {
@@ -14,8 +24,12 @@ pub struct MarkdownExporter {
     include_stats: bool,
     /// Include suggestions section
     include_suggestions: bool,
+    /// Include a table of contents for larger reports
+    include_toc: bool,
     /// Context extractor
     context: ContextExtractor,
+    /// Changes since a previous review
+    delta: Option<ReviewDelta>,
 }
 
 impl MarkdownExporter {
@@ -25,7 +39,9 @@ impl MarkdownExporter {
             include_diff: true,
             include_stats: true,
             include_suggestions: true,
+            include_toc: true,
             context: ContextExtractor::new(2),
+            delta: None,
         }
     }
 
@@ -47,6 +63,178 @@ impl MarkdownExporter {
         self
     }
 
+    /// Set whether to include a table of contents
+    pub fn with_toc(mut self, include: bool) -> Self {
+        self.include_toc = include;
+        self
+    }
+
+    /// Include a "Changes Since Last Review" section
+    pub fn with_delta(mut self, delta: ReviewDelta) -> Self {
+        self.delta = Some(delta);
+        self
+    }
+
+    /// Render the changes since the baseline review
+    fn render_delta(&self) -> String {
+        let Some(delta) = &self.delta else {
+            return String::new();
+        };
+
+        let mut output = String::new();
+        output.push_str("## Changes Since Last Review\n\n");
+
+        if delta.is_empty() {
+            output.push_str("No comment changes.\n\n");
+            return output;
+        }
+
+        let summary = |c: &Comment| {
+            format!(
+                "`{}:{}` {}",
+                c.metadata.file_path.as_deref().unwrap_or("?"),
+                c.metadata.line_number.unwrap_or(0),
+                c.content.lines().next().unwrap_or("")
+            )
+        };
+
+        for comment in &delta.added {
+            output.push_str(&format!("- **Added** [{}] {}\n", comment.severity, summary(comment)));
+        }
+        for comment in &delta.resolved {
+            output.push_str(&format!("- **Resolved** {}\n", summary(comment)));
+        }
+        for (old, new) in &delta.changed {
+            if delta.resolved.iter().any(|c| c.id == new.id) {
+                continue;
+            }
+            let what = if old.state != new.state {
+                format!("{:?} → {:?}", old.state, new.state)
+            } else {
+                "edited".to_string()
+            };
+            output.push_str(&format!("- **Changed** ({}) {}\n", what, summary(new)));
+        }
+        for comment in &delta.removed {
+            output.push_str(&format!("- **Removed** {}\n", summary(comment)));
+        }
+        output.push('\n');
+
+        output
+    }
+
+    /// Render a table of contents linking to each comment
+    ///
+    /// Anchors are GitHub-style slugs of the comment headings. Returns an
+    /// empty string for sessions with fewer than 5 comments.
+    pub fn render_toc(&self, session: &Session) -> String {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        render_toc_with(session, |comment| {
+            let slug = github_slug(&comment_heading(comment));
+            let count = seen.entry(slug.clone()).or_insert(0);
+            let anchor = if *count == 0 {
+                slug
+            } else {
+                format!("{}-{}", slug, count)
+            };
+            *count += 1;
+            anchor
+        })
+    }
+
+    /// Render a one-page plain-English overview for non-technical readers
+    ///
+    /// Gives the project, review date, the session's risk level under
+    /// `thresholds`, one sentence per open critical finding (no file paths
+    /// or line numbers) and whether the change is ready to merge.
+    pub fn render_executive_summary(session: &Session, thresholds: &RiskThresholds) -> String {
+        let open: Vec<_> = session
+            .comments
+            .all_sorted()
+            .into_iter()
+            .filter(|c| c.state.is_active())
+            .collect();
+        let critical: Vec<_> = open
+            .iter()
+            .filter(|c| c.severity == Severity::Critical)
+            .collect();
+
+        let project = session
+            .metadata
+            .repository
+            .as_ref()
+            .and_then(|repo| repo.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .or_else(|| session.metadata.name.clone())
+            .unwrap_or_else(|| "Unnamed project".to_string());
+
+        let mut output = String::from("# Review Summary\n\n");
+        output.push_str(&format!("**Project:** {}\n\n", project));
+        output.push_str(&format!(
+            "**Review date:** {}\n\n",
+            session.created_at.format("%B %-d, %Y")
+        ));
+        let risk = session.compute_risk_score_with(thresholds);
+        output.push_str(&format!(
+            "**Risk:** {} (score {:.1}: {})\n\n",
+            risk.level, risk.score, risk.explanation
+        ));
+        output.push_str(&format!(
+            "**Feedback:** ~{} words of feedback\n\n",
+            session.word_count_summary().total_words
+        ));
+
+        output.push_str("## Critical Findings\n\n");
+        if critical.is_empty() {
+            output.push_str("- No critical problems remain open.\n");
+        }
+        for comment in &critical {
+            output.push_str(&format!("- {}\n", plain_sentence(&comment.content)));
+        }
+        output.push('\n');
+
+        output.push_str("## Verdict\n\n");
+        output.push_str(&format!(
+            "**Ready to merge: {}**\n",
+            if critical.is_empty() { "Yes" } else { "No" }
+        ));
+        output
+    }
+
+    /// List other comments within the code context window
+    fn render_nearby_comments(&self, comment: &Comment, session: &Session) -> String {
+        let Some(line) = comment.metadata.line_number else {
+            return String::new();
+        };
+        let window = self.context.context_lines();
+        let nearby: Vec<_> = session
+            .comments
+            .get_by_line_range(
+                comment.file_id(),
+                line.saturating_sub(window),
+                line + window,
+            )
+            .into_iter()
+            .filter(|c| c.id != comment.id)
+            .collect();
+
+        if nearby.is_empty() {
+            return String::new();
+        }
+
+        let mut output = String::from("**Nearby comments:**\n\n");
+        for other in nearby {
+            output.push_str(&format!(
+                "- Line {} ({}): {}\n",
+                other.metadata.line_number.unwrap_or(0),
+                other.severity,
+                other.content.lines().next().unwrap_or("")
+            ));
+        }
+        output.push('\n');
+        output
+    }
+
     /// Render the report header
     fn render_header(&self, session: &Session) -> String {
         let mut header = String::new();
@@ -62,7 +250,7 @@ impl MarkdownExporter {
         ));
         header.push_str(&format!(
             "**Source:** {}\n",
-            session.diff_source.description()
+            session.source_description()
         ));
 
         if let Some(ref repo) = session.metadata.repository {
@@ -76,6 +264,24 @@ impl MarkdownExporter {
             header.push_str(&format!("**Name:** {}\n", name));
         }
 
+        let hot_files: Vec<_> = DiffStats::heat_map(&session.diff_data)
+            .into_iter()
+            .take(5)
+            .filter_map(|(id, score)| {
+                session
+                    .diff_data
+                    .get_file(&id)
+                    .map(|file| (file.display_path().display().to_string(), score))
+            })
+            .collect();
+
+        if !hot_files.is_empty() {
+            header.push_str("\n**Top 5 changed files:**\n\n");
+            for (path, score) in hot_files {
+                header.push_str(&format!("- `{}` ({:.0}%)\n", path, score * 100.0));
+            }
+        }
+
         header.push('\n');
         header
     }
@@ -105,6 +311,7 @@ impl MarkdownExporter {
         stats.push_str(&format!("- {} Warnings\n", warning));
         stats.push_str(&format!("- {} Info\n", info));
         stats.push('\n');
+        stats.push_str(&render_diff_stat(session));
 
         stats
     }
@@ -114,40 +321,35 @@ impl MarkdownExporter {
         let mut output = String::new();
 
         // Group comments by severity
-        let critical: Vec<_> = session
-            .comments
-            .get_by_severity(Severity::Critical)
-            .into_iter()
-            .collect();
-        let warnings: Vec<_> = session
-            .comments
-            .get_by_severity(Severity::Warning)
-            .into_iter()
-            .collect();
-        let info: Vec<_> = session
-            .comments
-            .get_by_severity(Severity::Info)
-            .into_iter()
-            .collect();
+        let critical = comments_by_severity(session, Severity::Critical);
+        let warnings = comments_by_severity(session, Severity::Warning);
+        let info = comments_by_severity(session, Severity::Info);
+
+        let contexts = if self.include_diff {
+            self.context
+                .extract_batch(session.comments.all(), &session.diff_data)
+        } else {
+            HashMap::new()
+        };
 
         if !critical.is_empty() {
             output.push_str("## Critical Issues\n\n");
             for comment in critical {
-                output.push_str(&self.render_comment(comment, session));
+                output.push_str(&self.render_comment(comment, session, &contexts));
             }
         }
 
         if !warnings.is_empty() {
             output.push_str("## Warnings\n\n");
             for comment in warnings {
-                output.push_str(&self.render_comment(comment, session));
+                output.push_str(&self.render_comment(comment, session, &contexts));
             }
         }
 
         if !info.is_empty() {
             output.push_str("## Info\n\n");
             for comment in info {
-                output.push_str(&self.render_comment(comment, session));
+                output.push_str(&self.render_comment(comment, session, &contexts));
             }
         }
 
@@ -155,7 +357,12 @@ impl MarkdownExporter {
     }
 
     /// Render a single comment
-    fn render_comment(&self, comment: &Comment, session: &Session) -> String {
+    fn render_comment(
+        &self,
+        comment: &Comment,
+        session: &Session,
+        contexts: &HashMap<CommentId, CodeContext>,
+    ) -> String {
         let mut output = String::new();
 
         // Location header
@@ -165,22 +372,7 @@ impl MarkdownExporter {
             .clone()
             .unwrap_or_else(|| comment.file_id().to_string());
 
-        let line_info = comment
-            .metadata
-            .line_number
-            .map(|n| format!(":{}", n))
-            .unwrap_or_default();
-
-        let tags = if comment.tags.is_empty() {
-            String::new()
-        } else {
-            format!(" [{}]", comment.tags.join(", "))
-        };
-
-        output.push_str(&format!(
-            "### `{}{}`{}\n\n",
-            file_path, line_info, tags
-        ));
+        output.push_str(&format!("### {}\n\n", comment_heading(comment)));
 
         // Comment content
         output.push_str(&comment.content);
@@ -188,9 +380,11 @@ impl MarkdownExporter {
 
         // Code context
         if self.include_diff {
-            if let Some(ctx) = self.context.extract(comment, &session.diff_data) {
-                output.push_str(&ContextExtractor::format_code_block(&ctx, &file_path));
+            if let Some(ctx) = contexts.get(&comment.id) {
+                output.push_str(&ContextExtractor::format_code_block(ctx, &file_path));
                 output.push_str("\n\n");
+                output.push_str(&render_word_changes(comment, session));
+                output.push_str(&self.render_nearby_comments(comment, session));
             }
         }
 
@@ -220,6 +414,10 @@ impl Exporter for MarkdownExporter {
 
         output.push_str(&self.render_header(session));
         output.push_str(&self.render_stats(session));
+        if self.include_toc {
+            output.push_str(&self.render_toc(session));
+        }
+        output.push_str(&self.render_delta());
         output.push_str(&self.render_comments(session));
 
         Ok(output)
@@ -232,12 +430,61 @@ impl Exporter for MarkdownExporter {
     fn file_extension(&self) -> &str {
         "md"
     }
+
+    fn validate_output(&self, output: &str) -> Result<ValidationReport> {
+        Ok(validate_markdown(output))
+    }
+}
+
+/// Markdown exporter producing only the executive summary
+///
+/// See [`MarkdownExporter::render_executive_summary`].
+#[derive(Default)]
+pub struct MarkdownExecutiveExporter {
+    /// Score boundaries for the reported risk level
+    risk_thresholds: RiskThresholds,
+}
+
+impl MarkdownExecutiveExporter {
+    /// Create an executive summary exporter with the default risk thresholds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the score boundaries for the reported risk level
+    pub fn with_risk_thresholds(mut self, thresholds: RiskThresholds) -> Self {
+        self.risk_thresholds = thresholds;
+        self
+    }
+}
+
+impl Exporter for MarkdownExecutiveExporter {
+    fn export(&self, session: &Session) -> Result<String> {
+        Ok(MarkdownExporter::render_executive_summary(
+            session,
+            &self.risk_thresholds,
+        ))
+    }
+
+    fn format_name(&self) -> &str {
+        "markdown-executive"
+    }
+
+    fn file_extension(&self) -> &str {
+        "md"
+    }
+
+    fn validate_output(&self, output: &str) -> Result<ValidationReport> {
+        Ok(validate_markdown(output))
+    }
 }
 
 /// Enhanced Markdown exporter with YAML frontmatter
 pub struct MarkdownEnhancedExporter {
     /// Base Markdown exporter
     base: MarkdownExporter,
+    /// Show the enclosing function as code context
+    function_context: bool,
 }
 
 impl MarkdownEnhancedExporter {
@@ -245,9 +492,23 @@ impl MarkdownEnhancedExporter {
     pub fn new() -> Self {
         Self {
             base: MarkdownExporter::new(),
+            function_context: false,
         }
     }
 
+    /// Show the enclosing function instead of a fixed line window when one
+    /// can be found
+    pub fn with_function_context(mut self, enabled: bool) -> Self {
+        self.function_context = enabled;
+        self
+    }
+
+    /// Set whether to include a table of contents
+    pub fn with_toc(mut self, include: bool) -> Self {
+        self.base.include_toc = include;
+        self
+    }
+
     /// Render YAML frontmatter
     fn render_frontmatter(&self, session: &Session) -> String {
         let counts = session.comments.count_by_severity();
@@ -281,7 +542,12 @@ impl MarkdownEnhancedExporter {
     }
 
     /// Render enhanced comment with anchor
-    fn render_enhanced_comment(&self, comment: &Comment, session: &Session) -> String {
+    fn render_enhanced_comment(
+        &self,
+        comment: &Comment,
+        session: &Session,
+        contexts: &HashMap<CommentId, CodeContext>,
+    ) -> String {
         let mut output = String::new();
 
         // Location header with anchor
@@ -297,11 +563,11 @@ impl MarkdownEnhancedExporter {
             .map(|n| format!(":{}", n))
             .unwrap_or_default();
 
-        let short_id = &comment.id.to_string()[..8.min(comment.id.to_string().len())];
-
         output.push_str(&format!(
             "### `{}{}`  {{#{}}}\n\n",
-            file_path, line_info, short_id
+            file_path,
+            line_info,
+            short_id(comment)
         ));
 
         // Severity badge
@@ -323,10 +589,19 @@ impl MarkdownEnhancedExporter {
         output.push_str("\n\n");
 
         // Code context
-        if let Some(ctx) = self.base.context.extract(comment, &session.diff_data) {
+        let function = self
+            .function_context
+            .then(|| {
+                self.base
+                    .context
+                    .extract_surrounding_function(comment, &session.diff_data)
+            })
+            .flatten();
+        if let Some(ctx) = function.as_ref().or_else(|| contexts.get(&comment.id)) {
             output.push_str("#### Code Context\n\n");
-            output.push_str(&ContextExtractor::format_code_block(&ctx, &file_path));
+            output.push_str(&ContextExtractor::format_code_block(ctx, &file_path));
             output.push_str("\n\n");
+            output.push_str(&render_word_changes(comment, session));
         }
 
         // Suggested fix with approach
@@ -343,41 +618,33 @@ impl MarkdownEnhancedExporter {
     /// Render enhanced comments section
     fn render_enhanced_comments(&self, session: &Session) -> String {
         let mut output = String::new();
+        let contexts = self
+            .base
+            .context
+            .extract_batch(session.comments.all(), &session.diff_data);
 
-        let critical: Vec<_> = session
-            .comments
-            .get_by_severity(Severity::Critical)
-            .into_iter()
-            .collect();
-        let warnings: Vec<_> = session
-            .comments
-            .get_by_severity(Severity::Warning)
-            .into_iter()
-            .collect();
-        let info: Vec<_> = session
-            .comments
-            .get_by_severity(Severity::Info)
-            .into_iter()
-            .collect();
+        let critical = comments_by_severity(session, Severity::Critical);
+        let warnings = comments_by_severity(session, Severity::Warning);
+        let info = comments_by_severity(session, Severity::Info);
 
         if !critical.is_empty() {
             output.push_str("## Critical Issues\n\n");
             for comment in critical {
-                output.push_str(&self.render_enhanced_comment(comment, session));
+                output.push_str(&self.render_enhanced_comment(comment, session, &contexts));
             }
         }
 
         if !warnings.is_empty() {
             output.push_str("## Warnings\n\n");
             for comment in warnings {
-                output.push_str(&self.render_enhanced_comment(comment, session));
+                output.push_str(&self.render_enhanced_comment(comment, session, &contexts));
             }
         }
 
         if !info.is_empty() {
             output.push_str("## Info\n\n");
             for comment in info {
-                output.push_str(&self.render_enhanced_comment(comment, session));
+                output.push_str(&self.render_enhanced_comment(comment, session, &contexts));
             }
         }
 
@@ -398,6 +665,9 @@ impl Exporter for MarkdownEnhancedExporter {
         output.push_str(&self.render_frontmatter(session));
         output.push_str(&self.base.render_header(session));
         output.push_str(&self.base.render_stats(session));
+        if self.base.include_toc {
+            output.push_str(&render_toc_with(session, |c| short_id(c).to_string()));
+        }
         output.push_str(&self.render_enhanced_comments(session));
 
         Ok(output)
@@ -410,6 +680,248 @@ impl Exporter for MarkdownEnhancedExporter {
     fn file_extension(&self) -> &str {
         "md"
     }
+
+    fn validate_output(&self, output: &str) -> Result<ValidationReport> {
+        Ok(validate_markdown(output))
+    }
+}
+
+/// Heading text for a comment in the plain Markdown report
+fn comment_heading(comment: &Comment) -> String {
+    let tags = if comment.tags.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", comment.tags.join(", "))
+    };
+    format!("`{}`{}", comment_location(comment), tags)
+}
+
+/// `file:line` for a comment, falling back to the file ID
+fn comment_location(comment: &Comment) -> String {
+    let file_path = comment
+        .metadata
+        .file_path
+        .clone()
+        .unwrap_or_else(|| comment.file_id().to_string());
+    match comment.metadata.line_number {
+        Some(n) => format!("{}:{}", file_path, n),
+        None => file_path,
+    }
+}
+
+/// First 8 characters of the comment ID, used as the enhanced report anchor
+fn short_id(comment: &Comment) -> String {
+    comment.id.to_string().chars().take(8).collect()
+}
+
+/// `` **Changed:** `old` → `new` `` from the word diff of the commented line
+///
+/// Empty when the line has no word diff.
+fn render_word_changes(comment: &Comment, session: &Session) -> String {
+    let ids = comment.line_ids();
+    let Some(words) = session
+        .diff_data
+        .get_file(comment.file_id())
+        .into_iter()
+        .flat_map(|f| f.hunks.iter().flat_map(|h| h.lines.iter()))
+        .filter(|l| ids.contains(&&l.id))
+        .find_map(|l| l.word_diff.as_ref())
+    else {
+        return String::new();
+    };
+
+    let side = |kind: WordKind| {
+        words
+            .iter()
+            .filter(|w| w.kind == kind)
+            .map(|w| w.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let (deleted, added) = (side(WordKind::Deleted), side(WordKind::Added));
+    let change = match (deleted.is_empty(), added.is_empty()) {
+        (false, false) => format!("{} → {}", inline_code(&deleted), inline_code(&added)),
+        (true, false) => format!("added {}", inline_code(&added)),
+        (false, true) => format!("removed {}", inline_code(&deleted)),
+        (true, true) => return String::new(),
+    };
+    format!("**Changed:** {}\n\n", change)
+}
+
+/// Markdown code span for `text`, fenced with more backticks than it contains
+fn inline_code(text: &str) -> String {
+    let longest_run = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run + 1);
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{} {} {}", fence, text, fence)
+    } else {
+        format!("{}{}{}", fence, text, fence)
+    }
+}
+
+/// Comments with `severity` in the session's manual order (see
+/// [`CommentManager::reorder`](crate::comment::CommentManager::reorder)),
+/// oldest first when none is set
+fn comments_by_severity(session: &Session, severity: Severity) -> Vec<&Comment> {
+    session
+        .comments
+        .all_sorted()
+        .into_iter()
+        .filter(|c| c.severity == severity)
+        .collect()
+}
+
+/// Render a table of contents in report order, with `anchor` giving each
+/// comment's link target
+fn render_toc_with(session: &Session, mut anchor: impl FnMut(&Comment) -> String) -> String {
+    if session.comment_count() < TOC_MIN_COMMENTS {
+        return String::new();
+    }
+
+    let mut output = String::from("## Contents\n\n");
+    for severity in [Severity::Critical, Severity::Warning, Severity::Info] {
+        for comment in comments_by_severity(session, severity) {
+            let first_line = comment.content.lines().next().unwrap_or("");
+            let mut excerpt: String = first_line.chars().take(TOC_EXCERPT_LEN).collect();
+            if first_line.chars().count() > TOC_EXCERPT_LEN {
+                excerpt.push('…');
+            }
+            output.push_str(&format!(
+                "- [{} - {}: {}](#{})\n",
+                comment_location(comment),
+                comment.severity,
+                excerpt.replace(['[', ']'], ""),
+                anchor(comment)
+            ));
+        }
+    }
+    output.push('\n');
+    output
+}
+
+/// `git diff --stat`-style table of changed files, most changed first
+fn render_diff_stat(session: &Session) -> String {
+    let mut stats = session.diff_data.file_stats().into_owned();
+    if stats.is_empty() {
+        return String::new();
+    }
+    stats.sort_by_key(|f| std::cmp::Reverse(f.changes()));
+
+    let insertions: usize = stats.iter().map(|f| f.insertions).sum();
+    let deletions: usize = stats.iter().map(|f| f.deletions).sum();
+    let mut table = String::from("| File | Changes | Hunks | |\n|------|--------:|------:|---|\n");
+    for stat in &stats {
+        let bar = stat.stat_bar(STAT_BAR_WIDTH, insertions + deletions);
+        let bar = if bar.is_empty() { String::new() } else { format!("`{}`", bar) };
+        table.push_str(&format!(
+            "| `{}` | {} | {} | {} |\n",
+            stat.path.display(),
+            stat.changes(),
+            stat.hunks,
+            bar
+        ));
+    }
+    table.push_str(&format!(
+        "\n{} file(s) changed, {} insertion(s)(+), {} deletion(s)(-)\n\n",
+        stats.len(),
+        insertions,
+        deletions
+    ));
+    table
+}
+
+/// The first sentence of a comment, without code formatting
+fn plain_sentence(content: &str) -> String {
+    let first_line = content.lines().next().unwrap_or("").replace('`', "");
+    let first_line = first_line.trim();
+    let sentence = match first_line.find(". ") {
+        Some(end) => &first_line[..end],
+        None => first_line.trim_end_matches('.'),
+    };
+    format!("{}.", sentence)
+}
+
+/// Problems in a Markdown report: `#anchor` links with no matching heading,
+/// and `---` rules directly under text (Markdown reads those as a heading
+/// underline). YAML frontmatter and code blocks are skipped.
+fn validate_markdown(output: &str) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let mut targets: HashSet<String> = HashSet::new();
+    let mut slugs: HashMap<String, usize> = HashMap::new();
+    let mut links: Vec<(usize, String)> = Vec::new();
+    let mut in_frontmatter = output.starts_with("---\n");
+    let mut in_code = false;
+    let mut previous = "";
+
+    for (i, line) in output.lines().enumerate() {
+        if in_frontmatter {
+            in_frontmatter = i == 0 || line != "---";
+            continue;
+        }
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        } else if !in_code {
+            if line.starts_with('#') {
+                let text = line.trim_start_matches('#').trim();
+                match text.rfind("{#").filter(|_| text.ends_with('}')) {
+                    Some(start) => {
+                        targets.insert(text[start + 2..text.len() - 1].to_string());
+                    }
+                    None => {
+                        let slug = github_slug(text);
+                        let count = slugs.entry(slug.clone()).or_insert(0);
+                        targets.insert(if *count == 0 {
+                            slug
+                        } else {
+                            format!("{}-{}", slug, count)
+                        });
+                        *count += 1;
+                    }
+                }
+            }
+            if line.trim() == "---" && !previous.trim().is_empty() {
+                report.problems.push(format!(
+                    "line {}: `---` directly below text makes it a heading",
+                    i + 1
+                ));
+            }
+            let mut rest = line;
+            while let Some(start) = rest.find("](#") {
+                let after = &rest[start + 3..];
+                let Some(end) = after.find(')') else { break };
+                links.push((i + 1, after[..end].to_string()));
+                rest = &after[end..];
+            }
+        }
+        previous = line;
+    }
+
+    for (line, anchor) in links {
+        if !targets.contains(&anchor) {
+            report
+                .problems
+                .push(format!("line {}: link to missing anchor #{}", line, anchor));
+        }
+    }
+    report
+}
+
+/// GitHub's heading anchor: lowercase, punctuation dropped, spaces as `-`
+fn github_slug(heading: &str) -> String {
+    heading
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -517,6 +1029,244 @@ mod tests {
         assert!(!md.contains("## Summary"));
     }
 
+    #[test]
+    fn test_markdown_top_changed_files() {
+        let diff = crate::diff::DiffParser::new()
+            .parse(
+                "diff --git a/small.rs b/small.rs\n@@ -1 +1,2 @@\n a\n+b\n\
+                 diff --git a/big.rs b/big.rs\n@@ -1 +1,3 @@\n a\n+b\n+c\n",
+            )
+            .unwrap();
+        let session = Session::new(DiffSource::WorkingTree, diff);
+
+        let md = MarkdownExporter::new().export(&session).unwrap();
+        assert!(md.contains("**Top 5 changed files:**"));
+        let big = md.find("`big.rs` (100%)").unwrap();
+        let small = md.find("`small.rs` (50%)").unwrap();
+        assert!(big < small);
+
+        let md = MarkdownExporter::new().export(&create_test_session()).unwrap();
+        assert!(!md.contains("Top 5 changed files"));
+    }
+
+    #[test]
+    fn test_markdown_diff_stat() {
+        let diff = crate::diff::DiffParser::new()
+            .parse("diff --git a/a.rs b/a.rs\n@@ -1,2 +1,2 @@\n x\n-y\n+z\n")
+            .unwrap();
+        let session = Session::new(DiffSource::WorkingTree, diff);
+
+        let md = MarkdownExporter::new().export(&session).unwrap();
+        let bar = format!("`{}{}`", "+".repeat(20), "-".repeat(20));
+        assert!(md.contains(&format!("| `a.rs` | 2 | 1 | {} |", bar)));
+        assert!(md.contains("1 file(s) changed, 1 insertion(s)(+), 1 deletion(s)(-)"));
+
+        let diff = crate::diff::DiffParser::new()
+            .parse("diff --git a/small.rs b/small.rs\n@@ -1 +1,2 @@\n a\n+b\n\
+                    diff --git a/big.rs b/big.rs\n@@ -1 +1,3 @@\n a\n+b\n+c\n")
+            .unwrap();
+        let md = MarkdownExporter::new().export(&Session::new(DiffSource::WorkingTree, diff)).unwrap();
+        assert!(md.find("| `big.rs` | 2 | 1 |").unwrap() < md.find("| `small.rs` | 1 | 1 |").unwrap());
+    }
+
+    #[test]
+    fn test_markdown_nearby_comments() {
+        let diff = crate::diff::DiffParser::new()
+            .parse("diff --git a/x.rs b/x.rs\n@@ -1,2 +1,4 @@\n a\n+b\n+c\n d\n")
+            .unwrap();
+        let file = &diff.files[0];
+        let line = |i: usize| &file.hunks[0].lines[i];
+        let make = |i: usize, content: &str| {
+            CommentBuilder::new(file.id.clone(), line(i).id.clone(), DiffSide::New)
+                .content(content)
+                .line_number(line(i).new_line_num.unwrap())
+                .file_path("x.rs")
+                .build()
+                .unwrap()
+        };
+        let first = make(1, "First remark");
+        let second = make(2, "Second remark");
+
+        let mut session = Session::new(DiffSource::WorkingTree, diff.clone());
+        session.comments.add(first).unwrap();
+        session.comments.add(second).unwrap();
+
+        let md = MarkdownExporter::new().export(&session).unwrap();
+        assert!(md.contains("**Nearby comments:**"));
+        assert!(md.contains("- Line 3 (Info): Second remark"));
+        assert!(md.contains("- Line 2 (Info): First remark"));
+    }
+
+    #[test]
+    fn test_markdown_word_changes() {
+        use crate::diff::WordSpan;
+
+        let mut diff = crate::diff::DiffParser::new()
+            .parse("diff --git a/x.rs b/x.rs\n@@ -1 +1 @@\n-let x = foo(a);\n+let x = `bar`(a);\n")
+            .unwrap();
+        let span = |kind, text: &str| WordSpan { kind, text: text.to_string() };
+        let file_id = diff.files[0].id.clone();
+        let line = &mut diff.files[0].hunks[0].lines[1];
+        line.word_diff = Some(vec![
+            span(WordKind::Context, "let x = "),
+            span(WordKind::Deleted, "foo(a);"),
+            span(WordKind::Added, "`bar`(a);"),
+        ]);
+        let comment = CommentBuilder::new(file_id, line.id.clone(), DiffSide::New)
+            .content("Why rename?")
+            .line_number(1)
+            .file_path("x.rs")
+            .build()
+            .unwrap();
+        let mut session = Session::new(DiffSource::WorkingTree, diff);
+        session.comments.add(comment).unwrap();
+
+        let md = MarkdownExporter::new().export(&session).unwrap();
+        assert!(md.contains("**Changed:** `foo(a);` → `` `bar`(a); ``"));
+        let enhanced = MarkdownEnhancedExporter::new().export(&session).unwrap();
+        assert!(enhanced.contains("**Changed:** `foo(a);`"));
+    }
+
+    #[test]
+    fn test_markdown_delta_section() {
+        let baseline = create_session_with_comments();
+        let mut current = baseline.clone();
+        let resolved_id = current.comments.all_sorted()[0].id.clone();
+        current
+            .comments
+            .update_state(&resolved_id, crate::comment::CommentState::Resolved)
+            .unwrap();
+
+        let delta = current.delta_from(&baseline);
+        let md = MarkdownExporter::new().with_delta(delta).export(&current).unwrap();
+        assert!(md.contains("## Changes Since Last Review"));
+        assert!(md.contains("- **Resolved**"));
+
+        let md = MarkdownExporter::new().export(&current).unwrap();
+        assert!(!md.contains("Changes Since Last Review"));
+    }
+
+    fn create_session_with_many_comments() -> Session {
+        let mut session = create_test_session();
+        for i in 0..TOC_MIN_COMMENTS {
+            let comment = CommentBuilder::new(
+                FileId::from_string("file1"),
+                LineId::from_string(format!("line{}", i)),
+                DiffSide::New,
+            )
+            .content(format!("Issue number {}", i))
+            .warning()
+            .line_number(10)
+            .file_path("src/lib.rs")
+            .build()
+            .unwrap();
+            session.comments.add(comment).unwrap();
+        }
+        session
+    }
+
+    #[test]
+    fn test_markdown_toc() {
+        let exporter = MarkdownExporter::new();
+        assert!(exporter.render_toc(&create_session_with_comments()).is_empty());
+
+        let session = create_session_with_many_comments();
+        let toc = exporter.render_toc(&session);
+        assert!(toc.starts_with("## Contents\n\n"));
+        assert!(toc.contains("- [src/lib.rs:10 - Warning: Issue number "));
+        assert!(toc.contains("](#srclibrs10)\n"));
+        assert!(toc.contains("](#srclibrs10-1)\n"));
+
+        let output = exporter.export(&session).unwrap();
+        assert!(output.contains("## Contents"));
+        let output = exporter.with_toc(false).export(&session).unwrap();
+        assert!(!output.contains("## Contents"));
+    }
+
+    #[test]
+    fn test_markdown_follows_manual_order() {
+        let mut session = create_session_with_many_comments();
+        let ids: Vec<_> = session.comments.all_sorted().iter().map(|c| c.id.clone()).collect();
+        session.comments.reorder(&ids[3], Some(&ids[0])).unwrap();
+
+        let output = MarkdownExporter::new().export(&session).unwrap();
+        let toc_pos = |n: usize| output.find(&format!("Issue number {}", n)).unwrap();
+        assert!(toc_pos(3) < toc_pos(0));
+        assert!(toc_pos(0) < toc_pos(1));
+        let body = &output[output.find("## Warnings").unwrap()..];
+        assert!(body.find("Issue number 3").unwrap() < body.find("Issue number 0").unwrap());
+    }
+
+    #[test]
+    fn test_enhanced_markdown_toc_uses_id_anchors() {
+        let session = create_session_with_many_comments();
+        let output = MarkdownEnhancedExporter::new().export(&session).unwrap();
+
+        let comment = session.comments.all_sorted()[0];
+        let anchor = short_id(comment);
+        assert!(output.contains(&format!("](#{})", anchor)));
+        assert!(output.contains(&format!("{{#{}}}", anchor)));
+    }
+
+    #[test]
+    fn test_validate_output() {
+        let session = create_session_with_many_comments();
+        let exporters: [&dyn Exporter; 3] = [
+            &MarkdownExporter::new(),
+            &MarkdownEnhancedExporter::new(),
+            &MarkdownExecutiveExporter::new(),
+        ];
+        for exporter in exporters {
+            let output = exporter.export(&session).unwrap();
+            let report = exporter.validate_output(&output).unwrap();
+            assert!(report.is_valid(), "{}: {:?}", exporter.format_name(), report);
+        }
+
+        let broken = "---\ntitle: x\n---\n# Report\n\n- [a](#report)\n- [b](#nowhere)\ntext\n---\n";
+        let report = MarkdownExporter::new().validate_output(broken).unwrap();
+        assert_eq!(
+            report.problems,
+            [
+                "line 9: `---` directly below text makes it a heading",
+                "line 7: link to missing anchor #nowhere",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_executive_summary() {
+        let mut session = create_session_with_comments();
+        session.metadata.repository = Some("/work/shop-api".into());
+
+        let thresholds = RiskThresholds::default();
+        let summary = MarkdownExporter::render_executive_summary(&session, &thresholds);
+        assert!(summary.contains("**Project:** shop-api"));
+        assert!(summary.contains(
+            "**Risk:** critical (score 11.0: 1 critical + 0 warnings in 0 changed lines)"
+        ));
+        let words = session.word_count_summary().total_words;
+        assert!(summary.contains(&format!("**Feedback:** ~{} words of feedback", words)));
+        assert!(summary.contains("- Critical issue: SQL injection vulnerability.\n"));
+        assert!(summary.contains("**Ready to merge: No**"));
+        assert!(!summary.contains("src/database.rs"));
+        assert!(!summary.contains("```"));
+
+        let lenient = RiskThresholds { medium: 10.0, high: 20.0, critical: 40.0 };
+        let summary = MarkdownExporter::render_executive_summary(&session, &lenient);
+        assert!(summary.contains("**Risk:** medium (score 11.0"));
+
+        let summary =
+            MarkdownExporter::render_executive_summary(&create_test_session(), &thresholds);
+        assert!(summary.contains("**Project:** Unnamed project"));
+        assert!(summary.contains("**Ready to merge: Yes**"));
+        assert_eq!(plain_sentence("Use `foo()`. It panics."), "Use foo().");
+    }
+
+    #[test]
+    fn test_github_slug() {
+        assert_eq!(github_slug("`src/a.rs:42` [bug, perf]"), "srcars42-bug-perf");
+    }
+
     #[test]
     fn test_enhanced_markdown_exporter() {
         let exporter = MarkdownEnhancedExporter::new();
@@ -9,6 +9,7 @@
 //! - JSON format (compact and pretty-printed)
 //! - Markdown format (human-readable reports)
 //! - Enhanced Markdown (with YAML frontmatter and anchors)
+//! - Inline annotations (`file:line:col: message`, for editors)
 //!
 //! # Example
 //!
@@ -22,10 +23,14 @@
 
 mod context;
 mod exporter;
+mod inline;
 mod json;
 mod markdown;
+mod template;
 
 pub use context::ContextExtractor;
-pub use exporter::{ExportManager, Exporter};
+pub use exporter::{ExportFilter, ExportManager, Exporter, ValidationReport};
+pub use inline::InlineAnnotationExporter;
 pub use json::{ExportData, ExportLocation, ExportReview, ExportStats, JsonExporter, SeverityStats};
-pub use markdown::{MarkdownEnhancedExporter, MarkdownExporter};
+pub use markdown::{MarkdownEnhancedExporter, MarkdownExecutiveExporter, MarkdownExporter};
+pub use template::TemplateExporter;
@@ -0,0 +1,145 @@
+//! User-supplied template exporter (minijinja)
+
+use super::exporter::Exporter;
+use crate::error::{CrHelperError, Result};
+use crate::session::Session;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::warn;
+
+/// Renders a session through a minijinja template
+///
+/// Templates see `session`, `stats`, `files` and `comments`, plus user
+/// variables under `vars` (e.g. `{{ vars.pr_url }}`). User variables are
+/// also set at the top level, overriding built-in keys of the same name.
+pub struct TemplateExporter {
+    /// Template source
+    source: String,
+    /// User-provided variables
+    vars: HashMap<String, String>,
+}
+
+impl TemplateExporter {
+    /// Create an exporter from template source
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            vars: HashMap::new(),
+        }
+    }
+
+    /// Load the template from a file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(CrHelperError::FileNotFound(path.to_path_buf()));
+        }
+        Ok(Self::new(fs::read_to_string(path)?))
+    }
+
+    /// Set user variables
+    pub fn with_vars(mut self, vars: HashMap<String, String>) -> Self {
+        self.vars = vars;
+        self
+    }
+
+    /// Build the template context for a session
+    fn context(&self, session: &Session) -> Result<Value> {
+        let files: Vec<String> = session
+            .diff_data
+            .files
+            .iter()
+            .map(|f| f.display_path().display().to_string())
+            .collect();
+
+        let mut context = Map::new();
+        context.insert(
+            "session".to_string(),
+            json!({
+                "id": session.id.to_string(),
+                "name": session.metadata.name,
+                "source": session.source_description(),
+                "created_at": session.created_at.to_rfc3339(),
+            }),
+        );
+        context.insert(
+            "stats".to_string(),
+            serde_json::to_value(&session.diff_data.stats)?,
+        );
+        context.insert("files".to_string(), json!(files));
+        context.insert(
+            "comments".to_string(),
+            serde_json::to_value(session.comments.all_sorted())?,
+        );
+
+        for (key, value) in &self.vars {
+            if context.contains_key(key) {
+                warn!(
+                    "Template variable '{}' overrides a built-in context key",
+                    key
+                );
+            }
+            context.insert(key.clone(), json!(value));
+        }
+        context.insert("vars".to_string(), json!(self.vars));
+
+        Ok(Value::Object(context))
+    }
+}
+
+impl Exporter for TemplateExporter {
+    fn export(&self, session: &Session) -> Result<String> {
+        let context = self.context(session)?;
+        minijinja::Environment::new()
+            .render_str(&self.source, context)
+            .map_err(|e| CrHelperError::Template(e.to_string()))
+    }
+
+    fn format_name(&self) -> &str {
+        "template"
+    }
+
+    fn file_extension(&self) -> &str {
+        "txt"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::DiffData;
+    use crate::session::DiffSource;
+
+    fn create_test_session() -> Session {
+        Session::new(DiffSource::WorkingTree, DiffData::empty())
+    }
+
+    #[test]
+    fn test_render_builtin_context() {
+        let session = create_test_session();
+        let exporter = TemplateExporter::new("{{ session.id }} {{ comments | length }}");
+
+        let output = exporter.export(&session).unwrap();
+        assert_eq!(output, format!("{} 0", session.id));
+    }
+
+    #[test]
+    fn test_render_vars() {
+        let vars = HashMap::from([
+            ("pr_url".to_string(), "https://example.com/pr/1".to_string()),
+            ("files".to_string(), "overridden".to_string()),
+        ]);
+        let exporter = TemplateExporter::new("{{ vars.pr_url }} {{ files }}").with_vars(vars);
+
+        let output = exporter.export(&create_test_session()).unwrap();
+        assert_eq!(output, "https://example.com/pr/1 overridden");
+    }
+
+    #[test]
+    fn test_render_error() {
+        let exporter = TemplateExporter::new("{{ unclosed");
+        let err = exporter.export(&create_test_session()).unwrap_err();
+        assert!(matches!(err, CrHelperError::Template(_)));
+    }
+}
@@ -11,5 +11,5 @@ pub mod comment;
 pub mod session;
 pub mod export;
 
-pub use error::{CrHelperError, Result};
+pub use error::{CrHelperError, PathContext, Result};
 pub use types::*;
@@ -1,15 +1,22 @@
 //! Session manager for CRUD operations
 
-use super::model::{DiffSource, Session, SessionFilter, SessionInfo, SessionMetadata};
-use super::persistence::SessionStorage;
-use crate::diff::DiffData;
+use super::model::{
+    DiffSource, Session, SessionFilter, SessionInfo, SessionMetadata, SessionSortOrder,
+    BATCH_SIBLINGS_KEY,
+};
+use super::persistence::{SessionStorage, WatchHandle};
+use crate::diff::{DiffData, DiffParser, ParserConfig};
 use crate::error::{CrHelperError, Result};
 use crate::types::SessionId;
 use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 /// Manager for session lifecycle
+///
+/// Clones share the same storage.
+#[derive(Clone)]
 pub struct SessionManager {
     /// Storage backend
     storage: Arc<dyn SessionStorage>,
@@ -17,6 +24,12 @@ pub struct SessionManager {
     auto_save_interval: Duration,
     /// Last auto-save time
     last_auto_save: Option<Instant>,
+    /// Rotate old sessions beyond this count on create
+    max_sessions: Option<usize>,
+    /// Let rotation delete sessions that have comments
+    force_rotate: bool,
+    /// Repository whose HEAD new sessions record (see [`SessionManager::set_repo_root`])
+    repo_root: Option<PathBuf>,
 }
 
 impl SessionManager {
@@ -26,6 +39,9 @@ impl SessionManager {
             storage: Arc::new(storage),
             auto_save_interval: Duration::from_secs(30),
             last_auto_save: None,
+            max_sessions: None,
+            force_rotate: false,
+            repo_root: None,
         }
     }
 
@@ -35,6 +51,9 @@ impl SessionManager {
             storage,
             auto_save_interval: Duration::from_secs(30),
             last_auto_save: None,
+            max_sessions: None,
+            force_rotate: false,
+            repo_root: None,
         }
     }
 
@@ -43,13 +62,54 @@ impl SessionManager {
         self.auto_save_interval = interval;
     }
 
+    /// Rotate old sessions on create, keeping at most `max` (None disables)
+    pub fn set_max_sessions(&mut self, max: Option<usize>) {
+        self.max_sessions = max;
+    }
+
+    /// Allow rotation to delete sessions that have comments
+    pub fn set_force_rotate(&mut self, force: bool) {
+        self.force_rotate = force;
+    }
+
+    /// Record the HEAD of the repository at `root` on new git-backed sessions
+    ///
+    /// Without a repository, sessions get no check-in hash and are never
+    /// reported as stale.
+    pub fn set_repo_root(&mut self, root: impl Into<PathBuf>) {
+        self.repo_root = Some(root.into());
+    }
+
     /// Create a new session from diff source
     pub fn create(&self, diff_source: DiffSource, diff_data: DiffData) -> Result<Session> {
-        let session = Session::new(diff_source, diff_data);
+        let mut session = Session::new(diff_source, diff_data);
+        stamp_checkin(&mut session, self.repo_root.as_deref());
         self.storage.save(&session)?;
+        self.rotate_if_needed()?;
         Ok(session)
     }
 
+    /// Create a session with no diff data
+    pub fn create_empty(&self, diff_source: DiffSource) -> Result<Session> {
+        self.create(diff_source, DiffData::empty())
+    }
+
+    /// Run `git diff` for a git-backed source and create a session from it
+    pub fn create_from_git(
+        &self,
+        diff_source: DiffSource,
+        config: &ParserConfig,
+    ) -> Result<Session> {
+        let parser_source = diff_source.to_parser_source().ok_or_else(|| {
+            CrHelperError::Validation(format!(
+                "{} is not a git diff source",
+                diff_source.description()
+            ))
+        })?;
+        let diff_data = DiffParser::with_config(config.clone()).parse_from_git(&parser_source)?;
+        self.create(diff_source, diff_data)
+    }
+
     /// Create a new session with a specific ID
     pub fn create_with_id(
         &self,
@@ -63,7 +123,8 @@ impl SessionManager {
                 id
             )));
         }
-        let session = Session::with_id(id, diff_source, diff_data);
+        let mut session = Session::with_id(id, diff_source, diff_data);
+        stamp_checkin(&mut session, self.repo_root.as_deref());
         self.storage.save(&session)?;
         Ok(session)
     }
@@ -77,16 +138,68 @@ impl SessionManager {
     ) -> Result<Session> {
         let mut session = Session::new(diff_source, diff_data);
         session.metadata = metadata;
+        stamp_checkin(&mut session, self.repo_root.as_deref());
         self.storage.save(&session)?;
         Ok(session)
     }
 
+    /// Create linked sessions, one per diff part
+    ///
+    /// Parts are named `"<name> (i/n)"`, using the metadata name or the diff
+    /// source description, and each records the full batch under
+    /// [`BATCH_SIBLINGS_KEY`].
+    pub fn create_batch(
+        &self,
+        parts: Vec<DiffData>,
+        diff_source: DiffSource,
+        metadata: SessionMetadata,
+    ) -> Result<Vec<Session>> {
+        if parts.is_empty() {
+            return Err(CrHelperError::Validation(
+                "Cannot create an empty session batch".to_string(),
+            ));
+        }
+
+        let total = parts.len();
+        let base_name = metadata
+            .name
+            .clone()
+            .unwrap_or_else(|| diff_source.description());
+
+        let mut sessions: Vec<Session> = parts
+            .into_iter()
+            .enumerate()
+            .map(|(i, diff_data)| {
+                let mut session = Session::new(diff_source.clone(), diff_data);
+                session.metadata = metadata.clone();
+                session.metadata.name = Some(format!("{} ({}/{})", base_name, i + 1, total));
+                session
+            })
+            .collect();
+
+        let ids: Vec<SessionId> = sessions.iter().map(|s| s.id.clone()).collect();
+        // Parts share a source, so one stamp covers the batch
+        stamp_checkin(&mut sessions[0], self.repo_root.as_deref());
+        let checkin_hash = sessions[0].metadata.checkin_hash.clone();
+        for session in &mut sessions {
+            session.extensions.set(BATCH_SIBLINGS_KEY, &ids);
+            session.metadata.checkin_hash = checkin_hash.clone();
+            self.storage.save(session)?;
+        }
+        self.rotate_if_needed()?;
+
+        Ok(sessions)
+    }
+
     /// Load a session by ID
     pub fn load(&self, id: &SessionId) -> Result<Session> {
         self.storage.load(id)
     }
 
     /// Load the most recently updated session
+    ///
+    /// Goes through [`SessionStorage::list_sorted`], so file storage doesn't
+    /// parse every session.
     pub fn load_latest(&self) -> Result<Option<Session>> {
         self.storage.latest()
     }
@@ -97,6 +210,46 @@ impl SessionManager {
         self.storage.save(session)
     }
 
+    /// Load, change and save a session as one step
+    ///
+    /// Use this rather than `load` + `save` when another process may write
+    /// the same session: file storage holds a lock on it meanwhile, so
+    /// neither writer loses the other's changes.
+    pub fn update<F>(&self, id: &SessionId, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Session) -> Result<()>,
+    {
+        self.storage.update(
+            id,
+            Box::new(|session| {
+                f(session)?;
+                session.touch();
+                Ok(())
+            }),
+        )
+    }
+
+    /// Save a named checkpoint of a session, returning the checkpoint's ID
+    ///
+    /// See [`Session::checkpoint`].
+    pub fn checkpoint(&self, id: &SessionId, name: &str) -> Result<SessionId> {
+        let checkpoint = self.storage.load(id)?.checkpoint(name)?;
+        self.storage.save(&checkpoint)?;
+        Ok(checkpoint.id)
+    }
+
+    /// Checkpoints taken of a session, oldest first
+    pub fn checkpoints(&self, id: &SessionId) -> Result<Vec<SessionInfo>> {
+        let mut checkpoints: Vec<SessionInfo> = self
+            .storage
+            .list()?
+            .into_iter()
+            .filter(|info| info.checkpoint_of.as_ref() == Some(id))
+            .collect();
+        checkpoints.sort_by_key(|info| info.created_at);
+        Ok(checkpoints)
+    }
+
     /// Auto-save with throttling
     pub fn auto_save(&mut self, session: &mut Session) -> Result<bool> {
         let now = Instant::now();
@@ -124,6 +277,22 @@ impl SessionManager {
         Ok(sessions)
     }
 
+    /// List sessions in the given order, returning at most `limit`
+    pub fn list_sorted(
+        &self,
+        order: SessionSortOrder,
+        limit: Option<usize>,
+    ) -> Result<Vec<SessionInfo>> {
+        self.storage.list_sorted(order, limit)
+    }
+
+    /// Call `on_change` with a session's ID whenever it is written
+    ///
+    /// The watch runs until the returned handle is dropped.
+    pub fn watch(&self, on_change: impl Fn(SessionId) + Send + 'static) -> Result<WatchHandle> {
+        self.storage.watch(Box::new(on_change))
+    }
+
     /// Search sessions with filter
     pub fn search(&self, filter: SessionFilter) -> Result<Vec<SessionInfo>> {
         let sessions = self.list()?;
@@ -135,13 +304,13 @@ impl SessionManager {
         self.storage.delete(id)
     }
 
-    /// Clean up sessions older than the given date
+    /// Clean up unpinned sessions older than the given date
     pub fn clean(&self, before: DateTime<Utc>) -> Result<usize> {
         let sessions = self.storage.list()?;
         let mut deleted = 0;
 
         for info in sessions {
-            if info.updated_at < before {
+            if info.updated_at < before && !info.metadata.pinned {
                 if self.storage.delete(&info.id).is_ok() {
                     deleted += 1;
                 }
@@ -161,12 +330,51 @@ impl SessionManager {
         Ok(self.storage.list()?.len())
     }
 
+    /// Apply the configured session rotation
+    fn rotate_if_needed(&self) -> Result<()> {
+        let Some(max) = self.max_sessions else {
+            return Ok(());
+        };
+        let rotated = if self.force_rotate {
+            self.storage.force_rotate_sessions(max)?
+        } else {
+            self.storage.rotate_sessions(max)?
+        };
+        if rotated > 0 {
+            tracing::info!("Rotated {} old sessions (max {})", rotated, max);
+        }
+        Ok(())
+    }
+
+    /// Parse a full session ID, falling back to a unique short ID prefix
+    pub fn resolve_id(&self, id: &str) -> Result<SessionId> {
+        if let Ok(session_id) = SessionId::from_string(id) {
+            return Ok(session_id);
+        }
+        self.storage
+            .lookup_by_short_id(id)?
+            .ok_or_else(|| CrHelperError::SessionNotFound(id.to_string()))
+    }
+
     /// Get access to the underlying storage
     pub fn storage(&self) -> &dyn SessionStorage {
         self.storage.as_ref()
     }
 }
 
+/// Record HEAD of `repo` on a new session (no-op outside a repository)
+///
+/// Only git sources are stamped; patches, stdin, imports and other VCSs
+/// don't depend on HEAD, so they skip running git.
+fn stamp_checkin(session: &mut Session, repo: Option<&Path>) {
+    let Some(repo) = repo else {
+        return;
+    };
+    if session.metadata.checkin_hash.is_none() && session.diff_source.to_parser_source().is_some() {
+        session.metadata.checkin_hash = DiffParser::head_sha_in(repo).ok();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -186,6 +394,60 @@ mod tests {
         assert!(manager.exists(&session.id));
     }
 
+    #[test]
+    fn test_create_from_git_rejects_non_git_source() {
+        let manager = create_manager();
+        let source = DiffSource::Mercurial {
+            rev: "tip".to_string(),
+        };
+
+        let result = manager.create_from_git(source, &ParserConfig::default());
+        assert!(matches!(result, Err(CrHelperError::Validation(_))));
+        assert_eq!(manager.count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_rotation_on_create() {
+        use crate::comment::{CommentBuilder, DiffSide};
+        use crate::types::{FileId, LineId};
+
+        let mut manager = create_manager();
+        let mut commented = manager
+            .create(DiffSource::WorkingTree, DiffData::empty())
+            .unwrap();
+        let comment = CommentBuilder::new(
+            FileId::from_string("f"),
+            LineId::from_string("l"),
+            DiffSide::New,
+        )
+        .content("keep me")
+        .build()
+        .unwrap();
+        commented.comments.add(comment).unwrap();
+        manager.save(&mut commented).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        let old = manager
+            .create(DiffSource::WorkingTree, DiffData::empty())
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+
+        manager.set_max_sessions(Some(1));
+        let newest = manager
+            .create(DiffSource::WorkingTree, DiffData::empty())
+            .unwrap();
+        assert!(manager.exists(&newest.id));
+        assert!(!manager.exists(&old.id));
+        assert!(manager.exists(&commented.id));
+
+        manager.set_force_rotate(true);
+        std::thread::sleep(Duration::from_millis(5));
+        let latest = manager
+            .create(DiffSource::WorkingTree, DiffData::empty())
+            .unwrap();
+        assert_eq!(manager.count().unwrap(), 1);
+        assert!(manager.exists(&latest.id));
+    }
+
     #[test]
     fn test_create_with_id() {
         let manager = create_manager();
@@ -202,6 +464,30 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_resolve_short_id() {
+        let manager = create_manager();
+        for id in ["20240101000000-abcd1234", "20240102000000-abff5678"] {
+            let id = SessionId::from_string(id).unwrap();
+            manager
+                .create_with_id(id, DiffSource::Staged, DiffData::empty())
+                .unwrap();
+        }
+
+        let full = manager.resolve_id("20240101000000-abcd1234").unwrap();
+        assert_eq!(full.as_str(), "20240101000000-abcd1234");
+        assert_eq!(manager.resolve_id("abcd").unwrap(), full);
+        assert_eq!(manager.resolve_id("abcd1234").unwrap(), full);
+
+        let err = manager.resolve_id("ab").unwrap_err().to_string();
+        assert!(err.contains("20240101000000-abcd1234"));
+        assert!(err.contains("20240102000000-abff5678"));
+        assert!(matches!(
+            manager.resolve_id("ffff"),
+            Err(CrHelperError::SessionNotFound(_))
+        ));
+    }
+
     #[test]
     fn test_create_with_metadata() {
         let manager = create_manager();
@@ -215,6 +501,54 @@ mod tests {
         assert!(session.metadata.tags.contains(&"security".to_string()));
     }
 
+    #[test]
+    fn test_create_batch() {
+        let manager = create_manager();
+        let parts = vec![DiffData::empty(), DiffData::empty(), DiffData::empty()];
+
+        let sessions = manager
+            .create_batch(parts, DiffSource::Staged, SessionMetadata::default())
+            .unwrap();
+
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(
+            sessions[1].metadata.name,
+            Some("Staged changes (2/3)".to_string())
+        );
+
+        let ids: Vec<_> = sessions.iter().map(|s| s.id.clone()).collect();
+        for session in &sessions {
+            assert!(manager.exists(&session.id));
+            assert_eq!(manager.load(&session.id).unwrap().batch_siblings(), ids);
+        }
+
+        let result = manager.create_batch(vec![], DiffSource::Staged, SessionMetadata::default());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_create_records_checkin_hash() {
+        let mut manager = create_manager();
+        let session = manager
+            .create(DiffSource::WorkingTree, DiffData::empty())
+            .unwrap();
+        assert_eq!(session.metadata.checkin_hash, None);
+
+        let repo = Path::new(".");
+        manager.set_repo_root(repo);
+        let session = manager
+            .create(DiffSource::WorkingTree, DiffData::empty())
+            .unwrap();
+        assert_eq!(session.metadata.checkin_hash, DiffParser::head_sha_in(repo).ok());
+        assert!(!session.is_stale_in(repo).unwrap());
+
+        let patch = DiffSource::Patch {
+            path: "fix.patch".to_string(),
+        };
+        let session = manager.create(patch, DiffData::empty()).unwrap();
+        assert_eq!(session.metadata.checkin_hash, None);
+    }
+
     #[test]
     fn test_load_session() {
         let manager = create_manager();
@@ -270,6 +604,50 @@ mod tests {
         assert!(session.updated_at > old_updated);
     }
 
+    #[test]
+    fn test_update() {
+        let manager = create_manager();
+        let session = manager
+            .create(DiffSource::WorkingTree, DiffData::empty())
+            .unwrap();
+
+        manager
+            .update(&session.id, |s| {
+                s.metadata.tags.push("urgent".to_string());
+                Ok(())
+            })
+            .unwrap();
+        let saved = manager.load(&session.id).unwrap();
+        assert_eq!(saved.metadata.tags, ["urgent"]);
+        assert!(saved.updated_at >= session.updated_at);
+
+        let failed = manager.update(&session.id, |_| {
+            Err(CrHelperError::Validation("nope".to_string()))
+        });
+        assert!(failed.is_err());
+    }
+
+    #[test]
+    fn test_checkpoints() {
+        let manager = create_manager();
+        let session = manager
+            .create(DiffSource::WorkingTree, DiffData::empty())
+            .unwrap();
+        assert!(manager.checkpoints(&session.id).unwrap().is_empty());
+
+        let first = manager.checkpoint(&session.id, "security").unwrap();
+        let second = manager.checkpoint(&session.id, "performance").unwrap();
+        manager.checkpoint(&first, "nested").unwrap();
+
+        let ids: Vec<SessionId> = manager
+            .checkpoints(&session.id)
+            .unwrap()
+            .into_iter()
+            .map(|info| info.id)
+            .collect();
+        assert_eq!(ids, [first, second]);
+    }
+
     #[test]
     fn test_auto_save_throttle() {
         let mut manager = create_manager();
@@ -350,16 +728,26 @@ mod tests {
     fn test_clean_old_sessions() {
         let manager = create_manager();
 
-        // Create a session
+        // Create a session, and a pinned one
         manager
             .create(DiffSource::WorkingTree, DiffData::empty())
             .unwrap();
+        let pinned = manager
+            .create(DiffSource::Staged, DiffData::empty())
+            .unwrap();
+        manager
+            .update(&pinned.id, |s| {
+                s.pin();
+                Ok(())
+            })
+            .unwrap();
 
-        // Clean with future date should delete all
+        // Clean with future date should delete all but the pinned session
         let future = Utc::now() + chrono::Duration::hours(1);
         let deleted = manager.clean(future).unwrap();
         assert_eq!(deleted, 1);
-        assert_eq!(manager.count().unwrap(), 0);
+        assert_eq!(manager.count().unwrap(), 1);
+        assert!(manager.exists(&pinned.id));
     }
 
     #[test]
@@ -1,14 +1,23 @@
 //! Session file format and schema migration
 
 use super::model::Session;
-use crate::error::{CrHelperError, Result};
+use crate::diff::DiffData;
+use crate::error::{CrHelperError, PathContext, Result};
 use crate::types::ProtocolVersion;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 
 /// Current schema version
 pub const CURRENT_SCHEMA_VERSION: &str = "1.0";
 
+/// Schema that keeps the diff in a `<diff_hash>.diff.msgpack` sidecar
+pub const SCHEMA_V2: &str = "2.0";
+
+/// Diff metadata extension key for a v2 session's sidecar hash
+pub const DIFF_HASH_KEY: &str = "diff_hash";
+
 /// Session file format with schema version
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionFile {
@@ -46,6 +55,11 @@ impl SessionFile {
         let minor = parts[1].parse().ok()?;
         Some(ProtocolVersion { major, minor })
     }
+
+    /// Whether the diff lives in a sidecar (see [`SessionMigrator::migrate_to_v2`])
+    pub fn is_v2(&self) -> bool {
+        self.parse_version().is_some_and(|v| v.major == 2)
+    }
 }
 
 /// Session schema migrator
@@ -85,6 +99,71 @@ impl SessionMigrator {
     pub fn current_version() -> &'static str {
         CURRENT_SCHEMA_VERSION
     }
+
+    /// File name of the sidecar holding the diff with `hash`
+    pub fn sidecar_name(hash: &str) -> String {
+        format!("{}.diff.msgpack", hash)
+    }
+
+    /// The [`DIFF_HASH_KEY`] of the session file at `path`, if it has one
+    ///
+    /// Only that extension is parsed, like [`SessionInfo::from_json_fast`]
+    /// does for the listing fields, so the session itself is never built.
+    ///
+    /// [`SessionInfo::from_json_fast`]: super::SessionInfo::from_json_fast
+    pub fn read_diff_hash(path: &Path) -> Result<Option<String>> {
+        let bytes = fs::read(path).with_path(path)?;
+        let header: DiffHashHeader = serde_json::from_slice(&bytes)?;
+        Ok(header.session.diff_data.metadata.extensions.diff_hash)
+    }
+
+    /// Move the diff out of a session file, returning the sidecar contents
+    ///
+    /// The diff is encoded as MessagePack, to be written to
+    /// [`sidecar_name`](Self::sidecar_name) of its BLAKE3 hash so identical
+    /// diffs share a file. The file keeps an empty diff holding only the
+    /// metadata, the stats and the hash.
+    pub fn migrate_to_v2(mut file: SessionFile) -> Result<(SessionFile, Vec<u8>)> {
+        if file.is_v2() {
+            return Err(CrHelperError::Validation(
+                "Session file is already v2".to_string(),
+            ));
+        }
+
+        let mut diff = std::mem::replace(&mut file.session.diff_data, DiffData::empty());
+        diff.metadata.extensions.remove(DIFF_HASH_KEY);
+        let bytes = diff.serialize_compact()?;
+        let hash = blake3::hash(&bytes).to_hex().to_string();
+
+        file.session.diff_data.metadata = diff.metadata;
+        file.session.diff_data.stats = diff.stats;
+        file.session.diff_data.metadata.extensions.set(DIFF_HASH_KEY, &hash);
+        file.schema_version = SCHEMA_V2.to_string();
+        Ok((file, bytes))
+    }
+
+    /// Put a v2 session's diff back from its sidecar in `dir`
+    ///
+    /// The loaded diff keeps [`DIFF_HASH_KEY`] so a later save can write the
+    /// session back as v2.
+    pub fn load_v2_diff(file: &mut SessionFile, dir: &Path) -> Result<()> {
+        let hash: String = file
+            .session
+            .diff_data
+            .metadata
+            .extensions
+            .get_as(DIFF_HASH_KEY)
+            .ok_or_else(|| {
+                CrHelperError::Validation("v2 session has no diff hash".to_string())
+            })?;
+
+        let sidecar = dir.join(Self::sidecar_name(&hash));
+        let bytes = fs::read(&sidecar).with_path(&sidecar)?;
+        let mut diff = DiffData::deserialize_compact(&bytes)?;
+        diff.metadata.extensions.set(DIFF_HASH_KEY, &hash);
+        file.session.diff_data = diff;
+        Ok(())
+    }
 }
 
 // Future migration functions would be added here:
@@ -94,6 +173,35 @@ impl SessionMigrator {
 //     Ok(file)
 // }
 
+/// The path to a session file's [`DIFF_HASH_KEY`]; serde skips every other
+/// field as unknown
+#[derive(Deserialize)]
+struct DiffHashHeader {
+    session: DiffHashSession,
+}
+
+#[derive(Deserialize)]
+struct DiffHashSession {
+    diff_data: DiffHashDiff,
+}
+
+#[derive(Deserialize)]
+struct DiffHashDiff {
+    metadata: DiffHashMetadata,
+}
+
+#[derive(Deserialize)]
+struct DiffHashMetadata {
+    #[serde(default)]
+    extensions: DiffHashExtensions,
+}
+
+#[derive(Default, Deserialize)]
+struct DiffHashExtensions {
+    // Keep in sync with DIFF_HASH_KEY
+    diff_hash: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,6 +268,54 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_migrate_to_v2() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let diff = crate::diff::DiffParser::new()
+            .parse("diff --git a/a.rs b/a.rs\n@@ -1 +1 @@\n-a\n+b\n")
+            .unwrap();
+        let session = Session::new(DiffSource::WorkingTree, diff);
+        let v1_len = serde_json::to_string(&SessionFile::new(session.clone()))
+            .unwrap()
+            .len();
+
+        let (mut file, bytes) = SessionMigrator::migrate_to_v2(SessionFile::new(session)).unwrap();
+        assert!(file.is_v2());
+        assert!(file.session.diff_data.files.is_empty());
+        assert_eq!(file.session.diff_data.stats.files_changed, 1);
+        assert!(serde_json::to_string(&file).unwrap().len() < v1_len);
+        assert!(SessionMigrator::migrate_to_v2(file.clone()).is_err());
+        let hash: String = file.session.diff_data.metadata.extensions.get_as(DIFF_HASH_KEY).unwrap();
+        std::fs::write(temp.path().join(SessionMigrator::sidecar_name(&hash)), &bytes).unwrap();
+
+        SessionMigrator::load_v2_diff(&mut file, temp.path()).unwrap();
+        assert_eq!(file.session.diff_data.files.len(), 1);
+
+        // Saving the loaded diff again gives the same sidecar
+        file.schema_version = CURRENT_SCHEMA_VERSION.to_string();
+        let (file, rebytes) = SessionMigrator::migrate_to_v2(file).unwrap();
+        let rehash: String = file.session.diff_data.metadata.extensions.get_as(DIFF_HASH_KEY).unwrap();
+        assert_eq!(rehash, hash);
+        assert_eq!(rebytes, bytes);
+
+        let path = temp.path().join("session.json");
+        std::fs::write(&path, serde_json::to_vec(&file).unwrap()).unwrap();
+        assert_eq!(SessionMigrator::read_diff_hash(&path).unwrap(), Some(hash.clone()));
+        std::fs::remove_file(temp.path().join(SessionMigrator::sidecar_name(&hash))).unwrap();
+        let mut file = file;
+        let err = SessionMigrator::load_v2_diff(&mut file, temp.path()).unwrap_err();
+        assert!(err.to_string().contains(&hash));
+    }
+
+    #[test]
+    fn test_read_diff_hash_v1() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("session.json");
+        let session = Session::new(DiffSource::WorkingTree, crate::diff::DiffData::empty());
+        std::fs::write(&path, serde_json::to_vec(&SessionFile::new(session)).unwrap()).unwrap();
+        assert_eq!(SessionMigrator::read_diff_hash(&path).unwrap(), None);
+    }
+
     #[test]
     fn test_session_file_serialization() {
         let session = create_test_session();
@@ -34,9 +34,16 @@ mod persistence;
 
 // Re-export public API
 pub use manager::SessionManager;
-pub use migration::{SessionFile, SessionMigrator, CURRENT_SCHEMA_VERSION};
-pub use model::{DiffSource, Session, SessionFilter, SessionInfo, SessionMetadata};
-pub use persistence::SessionStorage;
+pub use migration::{SessionFile, SessionMigrator, CURRENT_SCHEMA_VERSION, DIFF_HASH_KEY, SCHEMA_V2};
+pub use model::{
+    BaselineComparison, DiffSource, ReviewAssignment, ReviewDelta, ReviewSummary, RiskLevel, RiskScore, Session, SessionFilter, SessionInfo, SessionMetadata,
+    SessionSortOrder, SortDirection, SortField, Verdict, WordCountSummary, BATCH_SIBLINGS_KEY, BOOKMARKS_KEY,
+    CHECKPOINT_OF_KEY, INCREMENTAL_BASE_SESSION_KEY, SKIPPED_FILES_KEY,
+};
+pub use persistence::{
+    match_short_id, SessionStorage, SessionUpdate, WatchCallback, WatchHandle,
+    COMMENTED_SESSION_LIMIT,
+};
 
 // Re-export memory storage for testing
 #[cfg(test)]
@@ -1,11 +1,30 @@
 //! Session data models
 
-use crate::comment::CommentManager;
-use crate::diff::DiffData;
-use crate::types::{Extensions, SessionId};
+use crate::comment::{Comment, CommentManager, CommentState, Severity};
+use crate::config::RiskThresholds;
+use crate::diff::{DiffData, DiffParser, DiffStats, FileDiff, LineType};
+use crate::error::{CrHelperError, PathContext, Result};
+use crate::types::{Extensions, FileId, LineId, SessionId};
 use chrono::{DateTime, Utc};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Extension key linking the sessions of a split review
+pub const BATCH_SIBLINGS_KEY: &str = "batch_siblings";
+
+/// Extension key listing files left out of a focused review
+pub const SKIPPED_FILES_KEY: &str = "skipped_files";
+
+/// Extension key linking an incremental review to the session it follows up
+pub const INCREMENTAL_BASE_SESSION_KEY: &str = "incremental_base_session";
+
+/// Extension key pointing a checkpoint at the session it was taken from
+pub const CHECKPOINT_OF_KEY: &str = "checkpoint_of";
+
+/// Extension key for TUI bookmarks
+pub const BOOKMARKS_KEY: &str = "bookmarks";
 
 /// A code review session containing diff data and comments
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,15 +90,626 @@ impl Session {
         self.comments.count()
     }
 
+    /// Protect the session from rotation and `session clean`
+    pub fn pin(&mut self) {
+        self.metadata.pinned = true;
+        self.touch();
+    }
+
+    /// Let rotation and `session clean` delete the session again
+    pub fn unpin(&mut self) {
+        self.metadata.pinned = false;
+        self.touch();
+    }
+
+    /// Check whether the session is pinned
+    pub fn is_pinned(&self) -> bool {
+        self.metadata.pinned
+    }
+
+    /// Check whether HEAD of the repository at `repo` has moved since the
+    /// session was created
+    ///
+    /// Sessions without a recorded hash are never stale.
+    pub fn is_stale_in(&self, repo: &Path) -> Result<bool> {
+        match &self.metadata.checkin_hash {
+            Some(hash) => Ok(*hash != DiffParser::head_sha_in(repo)?),
+            None => Ok(false),
+        }
+    }
+
     /// Get the number of files in the diff
     pub fn file_count(&self) -> usize {
         self.diff_data.files.len()
     }
 
+    /// IDs of all sessions in this session's batch, in order (empty if not batched)
+    pub fn batch_siblings(&self) -> Vec<SessionId> {
+        self.extensions
+            .get_as(BATCH_SIBLINGS_KEY)
+            .unwrap_or_default()
+    }
+
+    /// ID of the session this is a checkpoint of, if it is one
+    pub fn checkpoint_of(&self) -> Option<SessionId> {
+        self.extensions.get_as(CHECKPOINT_OF_KEY)
+    }
+
+    /// A named snapshot of this session under a new ID
+    ///
+    /// The copy keeps the diff and comments (with their IDs, so
+    /// [`delta_from`](Self::delta_from) works between checkpoints), is named
+    /// `"<name> (checkpoint: <checkpoint name>)"` and records this session
+    /// under [`CHECKPOINT_OF_KEY`]. It is not saved.
+    pub fn checkpoint(&self, name: &str) -> Result<Session> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(CrHelperError::Validation(
+                "Checkpoint name cannot be empty".to_string(),
+            ));
+        }
+
+        let mut checkpoint = self.clone();
+        checkpoint.id = SessionId::generate();
+        checkpoint.created_at = Utc::now();
+        checkpoint.updated_at = checkpoint.created_at;
+        let original = self
+            .metadata
+            .name
+            .clone()
+            .unwrap_or_else(|| self.source_description());
+        checkpoint.metadata.name = Some(format!("{} (checkpoint: {})", original, name));
+        checkpoint.extensions.set(CHECKPOINT_OF_KEY, &self.id);
+        Ok(checkpoint)
+    }
+
+    /// Files skipped when the review was created (e.g. by `--focus-hot-files`)
+    pub fn skipped_files(&self) -> Vec<FileId> {
+        self.extensions
+            .get_as(SKIPPED_FILES_KEY)
+            .unwrap_or_default()
+    }
+
+    /// TUI bookmarks as name -> `(file, line index within file)`
+    ///
+    /// Keyed by file id rather than position so marks survive a review
+    /// that only shows some of the files (e.g. `--reviewer-filter`).
+    pub fn bookmarks(&self) -> HashMap<String, (FileId, usize)> {
+        self.extensions.get_as(BOOKMARKS_KEY).unwrap_or_default()
+    }
+
+    /// Replace the TUI bookmarks
+    pub fn set_bookmarks(&mut self, bookmarks: &HashMap<String, (FileId, usize)>) {
+        if bookmarks.is_empty() {
+            self.extensions.remove(BOOKMARKS_KEY);
+        } else {
+            self.extensions.set(BOOKMARKS_KEY, bookmarks);
+        }
+    }
+
+    /// Diff source description, plus any non-default `git diff` flags
+    pub fn source_description(&self) -> String {
+        let description = self.diff_source.description();
+        let flags = self.diff_data.metadata.diff_flags();
+        if flags.is_empty() {
+            description
+        } else {
+            format!("{} ({})", description, flags.join(" "))
+        }
+    }
+
+    /// Files matching any of `reviewer`'s assignment patterns
+    ///
+    /// Invalid patterns are ignored.
+    pub fn files_for_reviewer(&self, reviewer: &str) -> Vec<&FileDiff> {
+        let patterns = self
+            .metadata
+            .assignments
+            .iter()
+            .filter(|a| a.reviewer == reviewer)
+            .flat_map(|a| &a.file_patterns);
+        let globs = build_globset(patterns);
+        self.diff_data
+            .files
+            .iter()
+            .filter(|file| globs.is_match(file.display_path()))
+            .collect()
+    }
+
+    /// Reviewers whose assignments match `file`, in assignment order
+    pub fn reviewers_for_file(&self, file: &FileDiff) -> Vec<&str> {
+        self.metadata
+            .assignments
+            .iter()
+            .filter(|a| build_globset(&a.file_patterns).is_match(file.display_path()))
+            .map(|a| a.reviewer.as_str())
+            .collect()
+    }
+
     /// Get session info summary
     pub fn info(&self) -> SessionInfo {
         SessionInfo::from(self)
     }
+
+    /// Fraction of added and deleted lines that carry at least one comment
+    ///
+    /// Returns 0.0 for a diff without changed lines.
+    pub fn annotation_coverage(&self) -> f64 {
+        let changed: HashSet<&LineId> = self
+            .diff_data
+            .files
+            .iter()
+            .flat_map(|f| f.hunks.iter().flat_map(|h| &h.lines))
+            .filter(|l| matches!(l.line_type, LineType::Added | LineType::Deleted))
+            .map(|l| &l.id)
+            .collect();
+        if changed.is_empty() {
+            return 0.0;
+        }
+
+        let commented: HashSet<&LineId> = self
+            .comments
+            .all()
+            .into_iter()
+            .flat_map(|c| c.line_ids())
+            .filter(|id| changed.contains(id))
+            .collect();
+        commented.len() as f64 / changed.len() as f64
+    }
+
+    /// Risk of the change, using the default thresholds
+    pub fn compute_risk_score(&self) -> RiskScore {
+        self.compute_risk_score_with(&RiskThresholds::default())
+    }
+
+    /// Risk of the change from its open comments
+    ///
+    /// Open critical, warning and info comments count 10, 3 and 1 points;
+    /// the total is divided by the natural log of the number of changed
+    /// lines so large diffs are not penalized for their size alone.
+    pub fn compute_risk_score_with(&self, thresholds: &RiskThresholds) -> RiskScore {
+        let changed: usize = self
+            .diff_data
+            .files
+            .iter()
+            .map(|f| {
+                let (insertions, deletions) = f.line_changes();
+                insertions + deletions
+            })
+            .sum();
+        self.risk_score_for_changes(changed, thresholds)
+    }
+
+    /// [`compute_risk_score_with`](Self::compute_risk_score_with) for a
+    /// known number of changed lines
+    fn risk_score_for_changes(&self, changed: usize, thresholds: &RiskThresholds) -> RiskScore {
+        let (mut critical, mut warnings, mut info) = (0, 0, 0);
+        for comment in self.comments.all() {
+            if !comment.state.is_active() {
+                continue;
+            }
+            match comment.severity {
+                Severity::Critical => critical += 1,
+                Severity::Warning => warnings += 1,
+                Severity::Info => info += 1,
+            }
+        }
+
+        let points = (critical * 10 + warnings * 3 + info) as f64;
+        // ln() is below 1 for tiny diffs (and 0 for a single line)
+        let score = points / (changed as f64).ln().max(1.0);
+        let level = RiskLevel::from_score(score, thresholds);
+        RiskScore {
+            score,
+            level,
+            explanation: format!(
+                "{} critical + {} warnings in {} changed lines",
+                critical, warnings, changed
+            ),
+        }
+    }
+
+    /// Amount of written feedback, counting comments and suggested fixes
+    pub fn word_count_summary(&self) -> WordCountSummary {
+        let mut summary = WordCountSummary::default();
+        let mut comments = 0;
+        for comment in self.comments.all() {
+            let fix = comment.extensions.suggested_fix().unwrap_or("");
+            let words = comment.content.split_whitespace().count() + fix.split_whitespace().count();
+            summary.total_words += words;
+            summary.total_chars += comment.content.chars().count() + fix.chars().count();
+            summary.longest_comment = summary.longest_comment.max(words);
+            *summary.by_severity.entry(comment.severity).or_insert(0) += words;
+            comments += 1;
+        }
+        if comments > 0 {
+            summary.avg_comment_length = summary.total_words as f64 / comments as f64;
+        }
+        summary
+    }
+
+    /// Review progress summary
+    pub fn summary(&self) -> ReviewSummary {
+        ReviewSummary {
+            file_count: self.file_count(),
+            comment_count: self.comment_count(),
+            coverage_percent: self.annotation_coverage() * 100.0,
+        }
+    }
+
+    /// Combine another session's metadata into this one
+    ///
+    /// See [`SessionMetadata::merge_with`].
+    pub fn merge_metadata(&mut self, other: &SessionMetadata) {
+        self.metadata = self.metadata.merge_with(other);
+        self.touch();
+    }
+
+    /// Compare this session's comments against an earlier baseline
+    pub fn delta_from(&self, baseline: &Session) -> ReviewDelta {
+        let mut delta = ReviewDelta::default();
+
+        for comment in self.comments.all_sorted() {
+            match baseline.comments.get(&comment.id) {
+                None => delta.added.push(comment.clone()),
+                Some(old) if ReviewDelta::differs(old, comment) => {
+                    if comment.state == CommentState::Resolved && old.state != CommentState::Resolved {
+                        delta.resolved.push(comment.clone());
+                    }
+                    delta.changed.push((old.clone(), comment.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+
+        for comment in baseline.comments.all_sorted() {
+            if self.comments.get(&comment.id).is_none() {
+                delta.removed.push(comment.clone());
+            }
+        }
+
+        delta
+    }
+
+    /// Compare review metrics against an older session
+    ///
+    /// Counts are differences (current minus baseline), so a negative
+    /// `new_critical` means fewer open critical comments than before. Risk
+    /// levels use `thresholds`.
+    pub fn compare_to_baseline(
+        &self,
+        baseline: &Session,
+        thresholds: &RiskThresholds,
+    ) -> BaselineComparison {
+        let current = SeverityCounts::of(self);
+        let old = SeverityCounts::of(baseline);
+        let new_critical = current.open_critical - old.open_critical;
+        let risk = self.compute_risk_score_with(thresholds);
+        let baseline_risk = baseline.compute_risk_score_with(thresholds);
+        let risk_delta = risk.score - baseline_risk.score;
+
+        BaselineComparison {
+            new_critical,
+            new_warnings: current.open_warnings - old.open_warnings,
+            resolved_critical: current.resolved_critical - old.resolved_critical,
+            resolved_warnings: current.resolved_warnings - old.resolved_warnings,
+            coverage_delta: self.summary().coverage_percent - baseline.summary().coverage_percent,
+            risk_delta,
+            baseline_risk_level: baseline_risk.level,
+            risk_level: risk.level,
+            verdict: Verdict::from_deltas(new_critical, risk_delta),
+        }
+    }
+
+    /// Resolve the comments an agent's summary says it has dealt with
+    ///
+    /// Each line of `text` is checked for `Fixed: <file>:<line>`,
+    /// `Addressed comment about <text>` (matched against comment content
+    /// like [`CommentManager::fuzzy_search_content`], ignoring phrases of
+    /// fewer than two words) and `Resolved <severity> issue in <file>`.
+    /// Returns how many open or acknowledged comments were resolved.
+    pub fn apply_review_suggestions(&mut self, text: &str) -> Result<usize> {
+        let fixed = regex::Regex::new(r"(?i)\bfixed:?\s+`?([^\s`:]+):(\d+)").expect("valid regex");
+        let addressed = regex::Regex::new(r"(?i)\baddressed (?:the )?comment about\s+(.+)")
+            .expect("valid regex");
+        let resolved =
+            regex::Regex::new(r"(?i)\bresolved (critical|warning|info) issues? in\s+`?([^\s`]+)")
+                .expect("valid regex");
+
+        let active: Vec<&Comment> = self.comments.get_active();
+        let mut matched: HashSet<_> = HashSet::new();
+        for line in text.lines() {
+            if let Some(caps) = fixed.captures(line) {
+                let line_number = caps[2].parse::<usize>().ok();
+                for comment in &active {
+                    let (path, number) = self.comment_location(comment);
+                    if same_path(&path, &caps[1]) && number.is_some() && number == line_number {
+                        matched.insert(comment.id.clone());
+                    }
+                }
+            }
+            let about = addressed.captures(line).map(|caps| {
+                caps[1]
+                    .trim()
+                    .trim_end_matches(['.', '"', '\'', '`'])
+                    .to_lowercase()
+            });
+            // "it" or an empty phrase would match almost any comment
+            if let Some(about) = about.filter(|a| a.split_whitespace().count() >= SUGGESTION_MIN_WORDS) {
+                let best = active
+                    .iter()
+                    .map(|c| {
+                        let content = c.content.to_lowercase();
+                        let score = if content.contains(&about) {
+                            1.0
+                        } else {
+                            strsim::normalized_levenshtein(&about, &content)
+                        };
+                        (c, score)
+                    })
+                    .filter(|(_, score)| *score >= SUGGESTION_SIMILARITY)
+                    .max_by(|a, b| a.1.total_cmp(&b.1));
+                if let Some((comment, _)) = best {
+                    matched.insert(comment.id.clone());
+                }
+            }
+            if let Some(caps) = resolved.captures(line) {
+                let file = caps[2].trim_end_matches(['.', ',', ')']);
+                for comment in &active {
+                    let severity = comment.severity.to_string();
+                    if severity.eq_ignore_ascii_case(&caps[1])
+                        && same_path(&self.comment_location(comment).0, file)
+                    {
+                        matched.insert(comment.id.clone());
+                    }
+                }
+            }
+        }
+
+        for id in &matched {
+            self.comments.update_state(id, CommentState::Resolved)?;
+        }
+        if !matched.is_empty() {
+            self.touch();
+        }
+        Ok(matched.len())
+    }
+
+    /// Display path and line number of a comment
+    fn comment_location(&self, comment: &Comment) -> (String, Option<usize>) {
+        let file = self.diff_data.get_file(comment.file_id());
+        let path = file
+            .map(|f| f.display_path().display().to_string())
+            .or_else(|| comment.metadata.file_path.clone())
+            .unwrap_or_default();
+        let line = comment.metadata.line_number.or_else(|| {
+            let id = comment.line_ids()[0];
+            file?
+                .hunks
+                .iter()
+                .flat_map(|h| &h.lines)
+                .find(|l| &l.id == id)?
+                .display_line_num()
+        });
+        (path, line)
+    }
+}
+
+/// Lowest similarity for `Addressed comment about ...` to match a comment
+const SUGGESTION_SIMILARITY: f64 = 0.6;
+
+/// Fewest words `Addressed comment about ...` needs to be matched at all
+const SUGGESTION_MIN_WORDS: usize = 2;
+
+/// Whether two paths name the same file, allowing either to be a suffix
+fn same_path(a: &str, b: &str) -> bool {
+    !a.is_empty()
+        && !b.is_empty()
+        && (a == b || a.ends_with(&format!("/{}", b)) || b.ends_with(&format!("/{}", a)))
+}
+
+/// Review progress for a session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReviewSummary {
+    /// Number of files in the diff
+    pub file_count: usize,
+    /// Number of comments
+    pub comment_count: usize,
+    /// Percentage of changed lines with at least one comment
+    pub coverage_percent: f64,
+}
+
+/// Overall risk level of a review
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    /// Below the medium threshold
+    Low,
+    /// Worth a second look
+    Medium,
+    /// Should be fixed before merging
+    High,
+    /// Must not be merged as is
+    Critical,
+}
+
+impl RiskLevel {
+    /// Level for a risk score
+    pub fn from_score(score: f64, thresholds: &RiskThresholds) -> Self {
+        if score >= thresholds.critical {
+            RiskLevel::Critical
+        } else if score >= thresholds.high {
+            RiskLevel::High
+        } else if score >= thresholds.medium {
+            RiskLevel::Medium
+        } else {
+            RiskLevel::Low
+        }
+    }
+
+    /// Display name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "low",
+            RiskLevel::Medium => "medium",
+            RiskLevel::High => "high",
+            RiskLevel::Critical => "critical",
+        }
+    }
+}
+
+impl std::fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Risk of a review (see `Session::compute_risk_score`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskScore {
+    /// Weighted open comments per log of changed lines
+    pub score: f64,
+    /// Level for `score`
+    pub level: RiskLevel,
+    /// e.g. "3 critical + 7 warnings in 150 changed lines"
+    pub explanation: String,
+}
+
+/// Written feedback in a review (see `Session::word_count_summary`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WordCountSummary {
+    /// Words across all comments and suggested fixes
+    pub total_words: usize,
+    /// Characters across all comments and suggested fixes
+    pub total_chars: usize,
+    /// Average words per comment
+    pub avg_comment_length: f64,
+    /// Words in the longest comment
+    pub longest_comment: usize,
+    /// Words per severity
+    pub by_severity: HashMap<Severity, usize>,
+}
+
+/// Comment changes between two versions of a review
+#[derive(Debug, Clone, Default)]
+pub struct ReviewDelta {
+    /// Comments only in the newer session
+    pub added: Vec<Comment>,
+    /// Comments only in the baseline
+    pub removed: Vec<Comment>,
+    /// Comments whose content, severity, tags or state changed (old, new)
+    pub changed: Vec<(Comment, Comment)>,
+    /// Changed comments that became resolved (new version)
+    pub resolved: Vec<Comment>,
+}
+
+impl ReviewDelta {
+    /// Check if nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    fn differs(old: &Comment, new: &Comment) -> bool {
+        old.content != new.content
+            || old.severity != new.severity
+            || old.tags != new.tags
+            || old.state != new.state
+    }
+}
+
+/// Review metrics of a session relative to an older one (see
+/// `Session::compare_to_baseline`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineComparison {
+    /// Change in open critical comments
+    pub new_critical: i64,
+    /// Change in open warnings
+    pub new_warnings: i64,
+    /// Change in resolved critical comments
+    pub resolved_critical: i64,
+    /// Change in resolved warnings
+    pub resolved_warnings: i64,
+    /// Change in annotation coverage, in percentage points
+    pub coverage_delta: f64,
+    /// Change in risk score
+    pub risk_delta: f64,
+    /// Risk level of the baseline session
+    pub baseline_risk_level: RiskLevel,
+    /// Risk level of the current session
+    pub risk_level: RiskLevel,
+    /// Overall direction
+    pub verdict: Verdict,
+}
+
+/// Whether a codebase is getting better or worse between two reviews
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// Fewer open critical comments or a lower risk score
+    Improving,
+    /// No meaningful change
+    Stable,
+    /// More open critical comments or a higher risk score
+    Degrading,
+}
+
+impl Verdict {
+    /// Risk score changes smaller than this count as stable
+    pub const RISK_TOLERANCE: f64 = 0.5;
+
+    /// Verdict for a change in open critical comments and risk score
+    ///
+    /// A new critical comment is always degrading.
+    pub fn from_deltas(new_critical: i64, risk_delta: f64) -> Self {
+        if new_critical > 0 || risk_delta > Self::RISK_TOLERANCE {
+            Verdict::Degrading
+        } else if new_critical < 0 || risk_delta < -Self::RISK_TOLERANCE {
+            Verdict::Improving
+        } else {
+            Verdict::Stable
+        }
+    }
+
+    /// Display name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Improving => "improving",
+            Verdict::Stable => "stable",
+            Verdict::Degrading => "degrading",
+        }
+    }
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Open and resolved critical/warning comments in a session
+#[derive(Default)]
+struct SeverityCounts {
+    open_critical: i64,
+    open_warnings: i64,
+    resolved_critical: i64,
+    resolved_warnings: i64,
+}
+
+impl SeverityCounts {
+    fn of(session: &Session) -> Self {
+        let mut counts = Self::default();
+        for comment in session.comments.all() {
+            let resolved = comment.state == CommentState::Resolved;
+            match (comment.severity, resolved) {
+                (Severity::Critical, false) if comment.state.is_active() => counts.open_critical += 1,
+                (Severity::Warning, false) if comment.state.is_active() => counts.open_warnings += 1,
+                (Severity::Critical, true) => counts.resolved_critical += 1,
+                (Severity::Warning, true) => counts.resolved_warnings += 1,
+                _ => {}
+            }
+        }
+        counts
+    }
 }
 
 /// Source of the diff data
@@ -118,10 +748,42 @@ pub enum DiffSource {
         /// Raw git diff arguments
         args: Vec<String>,
     },
+    /// Mercurial working directory compared against a revision (`hg diff -r`)
+    Mercurial {
+        /// Revision to compare against
+        rev: String,
+    },
+    /// Subversion working copy, optionally against a revision (`svn diff -r`)
+    Svn {
+        /// Revision to compare against (default: BASE)
+        rev: Option<u64>,
+    },
+    /// Inline TODO-style comments imported from source files
+    Todos {
+        /// Directory that was scanned
+        path: String,
+    },
+    /// Diagnostics imported from a language server
+    Lsp {
+        /// Language server name
+        server: String,
+    },
+    /// Findings imported from a static analysis report
+    Report {
+        /// Tool that wrote the report (e.g. `checkstyle`)
+        tool: String,
+    },
+    /// Patch or diff file (`review --from-patch`)
+    Patch {
+        /// Path of the file
+        path: String,
+    },
+    /// Diff piped to `review --stdin`
+    Stdin,
 }
 
 impl DiffSource {
-    /// Convert to git diff arguments
+    /// Convert to git diff arguments (empty for Mercurial, SVN, TODO, LSP, report, patch and stdin sources)
     pub fn to_git_args(&self) -> Vec<String> {
         match self {
             DiffSource::WorkingTree => vec![],
@@ -131,6 +793,45 @@ impl DiffSource {
             DiffSource::Branch { branch } => vec![branch.clone()],
             DiffSource::PullRequest { base, .. } => vec![format!("{}..HEAD", base)],
             DiffSource::Custom { args } => args.clone(),
+            DiffSource::Mercurial { .. }
+            | DiffSource::Svn { .. }
+            | DiffSource::Todos { .. }
+            | DiffSource::Lsp { .. }
+            | DiffSource::Report { .. }
+            | DiffSource::Patch { .. }
+            | DiffSource::Stdin => vec![],
+        }
+    }
+
+    /// Convert to the parser's git diff source (None for non-git sources)
+    pub fn to_parser_source(&self) -> Option<crate::diff::DiffSource> {
+        use crate::diff::DiffSource as ParserDiffSource;
+
+        match self {
+            DiffSource::WorkingTree => Some(ParserDiffSource::WorkingTree),
+            DiffSource::Staged => Some(ParserDiffSource::Staged),
+            DiffSource::Commit { commit } => Some(ParserDiffSource::Commit {
+                commit: commit.clone(),
+            }),
+            DiffSource::CommitRange { from, to } => Some(ParserDiffSource::CommitRange {
+                from: from.clone(),
+                to: to.clone(),
+            }),
+            DiffSource::Branch { branch } => Some(ParserDiffSource::Branch {
+                branch: branch.clone(),
+            }),
+            DiffSource::PullRequest { base, .. } => Some(ParserDiffSource::CommitRange {
+                from: base.clone(),
+                to: "HEAD".to_string(),
+            }),
+            DiffSource::Custom { args } => Some(ParserDiffSource::Custom { args: args.clone() }),
+            DiffSource::Mercurial { .. }
+            | DiffSource::Svn { .. }
+            | DiffSource::Todos { .. }
+            | DiffSource::Lsp { .. }
+            | DiffSource::Report { .. }
+            | DiffSource::Patch { .. }
+            | DiffSource::Stdin => None,
         }
     }
 
@@ -150,6 +851,14 @@ impl DiffSource {
             DiffSource::Branch { branch } => format!("Branch: {}", branch),
             DiffSource::PullRequest { number, .. } => format!("PR #{}", number),
             DiffSource::Custom { args } => format!("Custom: {}", args.join(" ")),
+            DiffSource::Mercurial { rev } => format!("Mercurial: {}", rev),
+            DiffSource::Svn { rev: Some(rev) } => format!("Subversion: r{}", rev),
+            DiffSource::Svn { rev: None } => "Subversion working copy".to_string(),
+            DiffSource::Todos { path } => format!("TODOs in {}", path),
+            DiffSource::Lsp { server } => format!("LSP diagnostics ({})", server),
+            DiffSource::Report { tool } => format!("{} report", tool),
+            DiffSource::Patch { path } => format!("Patch: {}", path),
+            DiffSource::Stdin => "Standard input".to_string(),
         }
     }
 }
@@ -174,6 +883,24 @@ pub struct SessionMetadata {
     pub tags: Vec<String>,
     /// Reviewer name
     pub reviewer: Option<String>,
+    /// Git HEAD SHA when the session was created
+    #[serde(default)]
+    pub checkin_hash: Option<String>,
+    /// Which reviewers own which files
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assignments: Vec<ReviewAssignment>,
+    /// Never deleted by rotation or `session clean` (see `Session::pin`)
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Files a reviewer is responsible for, as glob patterns (e.g. `src/auth/**`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewAssignment {
+    /// Reviewer name
+    pub reviewer: String,
+    /// Glob patterns matched against file paths in the diff
+    pub file_patterns: Vec<String>,
 }
 
 impl SessionMetadata {
@@ -196,6 +923,60 @@ impl SessionMetadata {
         self.tags.push(tag.into());
         self
     }
+
+    /// Combine with metadata from another source
+    ///
+    /// Single-valued fields keep `self`'s value unless it is `None`. Tags are
+    /// merged without duplicates, and both descriptions are kept, separated
+    /// by a newline.
+    pub fn merge_with(&self, other: &SessionMetadata) -> SessionMetadata {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.tags.iter().chain(&other.tags) {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+
+        let description = match (&self.description, &other.description) {
+            (Some(a), Some(b)) if a != b => Some(format!("{}\n{}", a, b)),
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+
+        let mut assignments = self.assignments.clone();
+        for assignment in &other.assignments {
+            if !assignments.contains(assignment) {
+                assignments.push(assignment.clone());
+            }
+        }
+
+        SessionMetadata {
+            name: self.name.clone().or_else(|| other.name.clone()),
+            description,
+            repository: self.repository.clone().or_else(|| other.repository.clone()),
+            tags,
+            reviewer: self.reviewer.clone().or_else(|| other.reviewer.clone()),
+            checkin_hash: self
+                .checkin_hash
+                .clone()
+                .or_else(|| other.checkin_hash.clone()),
+            assignments,
+            pinned: self.pinned || other.pinned,
+        }
+    }
+}
+
+/// Compile glob patterns, skipping invalid ones
+fn build_globset<'a>(patterns: impl IntoIterator<Item = &'a String>) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        match Glob::new(pattern) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => tracing::warn!("Ignoring invalid file pattern '{}': {}", pattern, e),
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
 }
 
 /// Session summary information (for listing)
@@ -215,6 +996,18 @@ pub struct SessionInfo {
     pub file_count: usize,
     /// Diff source description
     pub source_description: String,
+    /// Risk score (see `Session::compute_risk_score`)
+    #[serde(default)]
+    pub risk_score: f64,
+    /// Words of feedback (see `Session::word_count_summary`)
+    #[serde(default)]
+    pub word_count: usize,
+    /// Session this is a checkpoint of (see `Session::checkpoint`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_of: Option<SessionId>,
+    /// Path of the file with the most changed lines (see `DiffStats::hottest_file`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hottest_file: Option<String>,
 }
 
 impl From<&Session> for SessionInfo {
@@ -226,7 +1019,211 @@ impl From<&Session> for SessionInfo {
             metadata: session.metadata.clone(),
             comment_count: session.comment_count(),
             file_count: session.file_count(),
-            source_description: session.diff_source.description(),
+            source_description: session.source_description(),
+            risk_score: session.compute_risk_score().score,
+            word_count: session.word_count_summary().total_words,
+            checkpoint_of: session.checkpoint_of(),
+            hottest_file: DiffStats::hottest_file(&session.diff_data.file_stats())
+                .map(|f| f.path.display().to_string()),
+        }
+    }
+}
+
+impl SessionInfo {
+    /// Read a session file's info without building its diff
+    ///
+    /// Of the diff's files only the paths and line types are read, for the
+    /// file count, the hottest file and the changed lines behind the risk
+    /// score; v2 files, whose diff lives in a sidecar, use the stats kept
+    /// in the session file instead. Files the full loader would migrate are
+    /// rejected so callers can fall back to it.
+    pub fn from_json_fast(path: &Path) -> Result<Self> {
+        use crate::session::migration::{CURRENT_SCHEMA_VERSION, SCHEMA_V2};
+
+        // Parsing from a slice is much faster than serde_json's reader
+        let bytes = std::fs::read(path).with_path(path)?;
+        let header: SessionFileHeader = serde_json::from_slice(&bytes)?;
+        if header.schema_version != CURRENT_SCHEMA_VERSION && header.schema_version != SCHEMA_V2 {
+            return Err(CrHelperError::UnsupportedSchemaVersion(header.schema_version));
+        }
+
+        let SessionWithoutFiles { fields, mut file_count, mut changed_lines, mut hottest_file } = header.session;
+        let session: Session = serde_json::from_value(serde_json::Value::Object(fields))?;
+        if header.schema_version == SCHEMA_V2 {
+            let stats = &session.diff_data.stats;
+            file_count = stats.per_file.len();
+            changed_lines = stats.insertions + stats.deletions;
+            hottest_file = DiffStats::hottest_file(&stats.per_file).map(|f| f.path.display().to_string());
+        }
+
+        let mut info = SessionInfo::from(&session);
+        info.file_count = file_count;
+        info.hottest_file = hottest_file;
+        info.risk_score = session
+            .risk_score_for_changes(changed_lines, &RiskThresholds::default())
+            .score;
+        Ok(info)
+    }
+}
+
+/// The parts of a session file [`SessionInfo::from_json_fast`] reads
+#[derive(Deserialize)]
+struct SessionFileHeader {
+    schema_version: String,
+    session: SessionWithoutFiles,
+}
+
+/// A session's JSON fields with the file list of `diff_data` left empty
+///
+/// Only the paths and line types of the files are parsed (see
+/// [`DiffSummary`]), so no hunk headers or line contents are allocated.
+struct SessionWithoutFiles {
+    fields: serde_json::Map<String, serde_json::Value>,
+    file_count: usize,
+    changed_lines: usize,
+    hottest_file: Option<String>,
+}
+
+/// `diff_data` reduced to what [`SessionInfo`] needs; serde skips the
+/// other fields as unknown
+#[derive(Deserialize)]
+struct DiffSummary {
+    files: Vec<FileSummary>,
+    metadata: serde_json::Value,
+    stats: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct FileSummary {
+    old_path: Option<PathBuf>,
+    new_path: Option<PathBuf>,
+    hunks: Vec<HunkSummary>,
+}
+
+impl FileSummary {
+    fn changed_lines(&self) -> usize {
+        self.hunks
+            .iter()
+            .flat_map(|h| &h.lines)
+            .filter(|l| matches!(l.line_type, LineType::Added | LineType::Deleted))
+            .count()
+    }
+}
+
+#[derive(Deserialize)]
+struct HunkSummary {
+    lines: Vec<LineSummary>,
+}
+
+#[derive(Deserialize)]
+struct LineSummary {
+    line_type: LineType,
+}
+
+impl<'de> Deserialize<'de> for SessionWithoutFiles {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct SessionVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for SessionVisitor {
+            type Value = SessionWithoutFiles;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a session object")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> std::result::Result<Self::Value, A::Error> {
+                let mut session = SessionWithoutFiles {
+                    fields: serde_json::Map::new(),
+                    file_count: 0,
+                    changed_lines: 0,
+                    hottest_file: None,
+                };
+                while let Some(key) = map.next_key::<String>()? {
+                    let value = if key == "diff_data" {
+                        let diff: DiffSummary = map.next_value()?;
+                        session.file_count = diff.files.len();
+                        let changes: Vec<usize> = diff.files.iter().map(FileSummary::changed_lines).collect();
+                        session.changed_lines = changes.iter().sum();
+                        // Same choice as `DiffStats::hottest_file`: the first of equals
+                        session.hottest_file = diff
+                            .files
+                            .iter()
+                            .zip(&changes)
+                            .filter(|(_, changed)| **changed > 0)
+                            .rev()
+                            .max_by_key(|(_, changed)| **changed)
+                            .and_then(|(f, _)| f.new_path.as_ref().or(f.old_path.as_ref()))
+                            .map(|path| path.display().to_string());
+                        serde_json::json!({ "files": [], "metadata": diff.metadata, "stats": diff.stats })
+                    } else {
+                        map.next_value()?
+                    };
+                    session.fields.insert(key, value);
+                }
+                Ok(session)
+            }
+        }
+
+        deserializer.deserialize_map(SessionVisitor)
+    }
+}
+
+/// Field to sort session listings by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    /// Last update time
+    UpdatedAt,
+    /// Creation time
+    CreatedAt,
+    /// Number of comments
+    CommentCount,
+    /// Number of files
+    FileCount,
+    /// Risk score
+    RiskScore,
+    /// Words of feedback
+    WordCount,
+}
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    /// Smallest / oldest first
+    Ascending,
+    /// Largest / newest first
+    Descending,
+}
+
+/// Ordering for [`SessionStorage::list_sorted`](super::SessionStorage::list_sorted)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionSortOrder {
+    /// Field to sort by
+    pub by: SortField,
+    /// Sort direction
+    pub direction: SortDirection,
+}
+
+impl SessionSortOrder {
+    /// Most recently updated first
+    pub fn newest_first() -> Self {
+        Self {
+            by: SortField::UpdatedAt,
+            direction: SortDirection::Descending,
+        }
+    }
+
+    /// Sort session infos in place
+    pub fn sort(&self, sessions: &mut [SessionInfo]) {
+        match self.by {
+            SortField::UpdatedAt => sessions.sort_by_key(|s| s.updated_at),
+            SortField::CreatedAt => sessions.sort_by_key(|s| s.created_at),
+            SortField::CommentCount => sessions.sort_by_key(|s| s.comment_count),
+            SortField::FileCount => sessions.sort_by_key(|s| s.file_count),
+            SortField::RiskScore => sessions.sort_by(|a, b| a.risk_score.total_cmp(&b.risk_score)),
+            SortField::WordCount => sessions.sort_by_key(|s| s.word_count),
+        }
+        if self.direction == SortDirection::Descending {
+            sessions.reverse();
         }
     }
 }
@@ -244,6 +1241,8 @@ pub struct SessionFilter {
     pub created_before: Option<DateTime<Utc>>,
     /// Has comments
     pub has_comments: Option<bool>,
+    /// Pinned (see `Session::pin`)
+    pub pinned: Option<bool>,
 }
 
 impl SessionFilter {
@@ -277,6 +1276,12 @@ impl SessionFilter {
         self
     }
 
+    /// Filter by pin status
+    pub fn with_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = Some(pinned);
+        self
+    }
+
     /// Check if a session matches this filter
     pub fn matches(&self, info: &SessionInfo) -> bool {
         // Name filter
@@ -325,6 +1330,11 @@ impl SessionFilter {
             }
         }
 
+        // Pinned filter
+        if self.pinned.is_some_and(|pinned| pinned != info.metadata.pinned) {
+            return false;
+        }
+
         true
     }
 }
@@ -337,6 +1347,43 @@ mod tests {
         Session::new(DiffSource::WorkingTree, DiffData::empty())
     }
 
+    #[test]
+    fn test_session_info_from_json_fast() {
+        use crate::comment::{CommentBuilder, DiffSide};
+        use crate::session::{SessionFile, SessionMigrator};
+
+        let diff = DiffParser::new()
+            .parse("diff --git a/b.rs b/b.rs\n@@ -1 +1,2 @@\n a\n+b\n\
+                    diff --git a/a.rs b/a.rs\n@@ -1,2 +1,3 @@\n a\n-b\n+c\n+d\n")
+            .unwrap();
+        let mut session = Session::new(DiffSource::Staged, diff.clone());
+        let comment = CommentBuilder::new(diff.files[1].id.clone(), diff.files[1].hunks[0].lines[2].id.clone(), DiffSide::New)
+            .content("Unchecked unwrap here")
+            .severity(Severity::Critical)
+            .build()
+            .unwrap();
+        session.comments.add(comment).unwrap();
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("session.json");
+        std::fs::write(&path, serde_json::to_string(&SessionFile::new(session.clone())).unwrap()).unwrap();
+        let fast = SessionInfo::from_json_fast(&path).unwrap();
+        assert_eq!(fast.file_count, 2);
+        assert_eq!(fast.hottest_file.as_deref(), Some("a.rs"));
+        assert_eq!(serde_json::to_value(&fast).unwrap(), serde_json::to_value(session.info()).unwrap());
+
+        // v2 files are read without their sidecar
+        let (v2, _) = SessionMigrator::migrate_to_v2(SessionFile::new(session.clone())).unwrap();
+        std::fs::write(&path, serde_json::to_string(&v2).unwrap()).unwrap();
+        let fast = SessionInfo::from_json_fast(&path).unwrap();
+        assert_eq!(serde_json::to_value(&fast).unwrap(), serde_json::to_value(session.info()).unwrap());
+
+        let mut future = SessionFile::new(session);
+        future.schema_version = "3.0".to_string();
+        std::fs::write(&path, serde_json::to_string(&future).unwrap()).unwrap();
+        assert!(SessionInfo::from_json_fast(&path).is_err());
+    }
+
     #[test]
     fn test_session_creation() {
         let session = create_test_session();
@@ -345,6 +1392,183 @@ mod tests {
         assert!(session.created_at <= session.updated_at);
     }
 
+    #[test]
+    fn test_apply_review_suggestions() {
+        use crate::comment::{CommentBuilder, DiffSide};
+
+        let diff = DiffParser::new()
+            .parse("diff --git a/src/lib.rs b/src/lib.rs\n@@ -1,3 +1,3 @@\n a\n-b\n+c\n d\n")
+            .unwrap();
+        let mut session = Session::new(DiffSource::WorkingTree, diff.clone());
+        let file = &diff.files[0];
+        let comment = |line: usize, content: &str, severity: Severity| {
+            CommentBuilder::new(
+                file.id.clone(),
+                file.hunks[0].lines[line].id.clone(),
+                DiffSide::New,
+            )
+            .content(content)
+            .severity(severity)
+            .build()
+            .unwrap()
+        };
+        let on_line_2 = comment(2, "Handle the error case", Severity::Info);
+        let unwrap = comment(3, "Avoid unwrap in library code", Severity::Info);
+        let critical = comment(0, "SQL injection risk", Severity::Critical);
+        let untouched = comment(0, "Consider renaming", Severity::Warning);
+        for c in [&on_line_2, &unwrap, &critical, &untouched] {
+            session.comments.add(c.clone()).unwrap();
+        }
+
+        let response = "Fixed: `lib.rs:2`\n\
+            Addressed comment about avoid unwrap in library code.\n\
+            Resolved critical issue in src/lib.rs\n\
+            Fixed: other.rs:1\n";
+        assert_eq!(session.apply_review_suggestions(response).unwrap(), 3);
+        for c in [&on_line_2, &unwrap, &critical] {
+            assert_eq!(
+                session.comments.get(&c.id).unwrap().state,
+                CommentState::Resolved
+            );
+        }
+        assert!(session
+            .comments
+            .get(&untouched.id)
+            .unwrap()
+            .state
+            .is_active());
+
+        // Already resolved comments are not counted again
+        assert_eq!(session.apply_review_suggestions(response).unwrap(), 0);
+
+        // Empty or one-word phrases resolve nothing, even ones the
+        // remaining comment contains
+        let vague = "Addressed comment about .\nAddressed comment about renaming\n";
+        assert_eq!(session.apply_review_suggestions(vague).unwrap(), 0);
+        assert!(session.comments.get(&untouched.id).unwrap().state.is_active());
+    }
+
+    #[test]
+    fn test_compare_to_baseline() {
+        use crate::comment::{CommentBuilder, DiffSide};
+        use crate::types::LineId;
+
+        let comment = |severity: Severity| {
+            CommentBuilder::new(FileId::from_string("f"), LineId::from_string("l"), DiffSide::New)
+                .content("issue")
+                .severity(severity)
+                .build()
+                .unwrap()
+        };
+
+        let mut baseline = create_test_session();
+        let fixed = comment(Severity::Critical);
+        baseline.comments.add(fixed.clone()).unwrap();
+        baseline.comments.add(comment(Severity::Critical)).unwrap();
+
+        let mut current = baseline.clone();
+        current.comments.update_state(&fixed.id, CommentState::Resolved).unwrap();
+        current.comments.add(comment(Severity::Warning)).unwrap();
+
+        let thresholds = RiskThresholds::default();
+        let comparison = current.compare_to_baseline(&baseline, &thresholds);
+        assert_eq!(comparison.new_critical, -1);
+        assert_eq!(comparison.new_warnings, 1);
+        assert_eq!(comparison.resolved_critical, 1);
+        assert_eq!(comparison.resolved_warnings, 0);
+        assert_eq!(comparison.coverage_delta, 0.0);
+        assert!(comparison.risk_delta < 0.0);
+        assert_eq!(comparison.baseline_risk_level, RiskLevel::Critical);
+        assert_eq!(comparison.risk_level, RiskLevel::Critical);
+        assert_eq!(comparison.verdict, Verdict::Improving);
+
+        // Levels follow the configured thresholds
+        let lenient = RiskThresholds { medium: 5.0, high: 15.0, critical: 30.0 };
+        let comparison = current.compare_to_baseline(&baseline, &lenient);
+        assert_eq!(comparison.baseline_risk_level, RiskLevel::High);
+        assert_eq!(comparison.risk_level, RiskLevel::Medium);
+
+        assert_eq!(baseline.compare_to_baseline(&current, &thresholds).verdict, Verdict::Degrading);
+        assert_eq!(current.compare_to_baseline(&current, &thresholds).verdict, Verdict::Stable);
+    }
+
+    #[test]
+    fn test_delta_from() {
+        use crate::comment::{CommentBuilder, DiffSide};
+        use crate::types::LineId;
+
+        let comment = |content: &str| {
+            CommentBuilder::new(
+                FileId::from_string("f"),
+                LineId::from_string("l"),
+                DiffSide::New,
+            )
+            .content(content)
+            .build()
+            .unwrap()
+        };
+
+        let mut baseline = create_test_session();
+        let kept = comment("unchanged");
+        let edited = comment("before");
+        let fixed = comment("bug");
+        let dropped = comment("dropped");
+        for c in [&kept, &edited, &fixed, &dropped] {
+            baseline.comments.add(c.clone()).unwrap();
+        }
+
+        let mut current = baseline.clone();
+        current.comments.update_comment(&edited.id, |c| c.update_content("after")).unwrap();
+        current.comments.update_state(&fixed.id, CommentState::Resolved).unwrap();
+        current.comments.delete(&dropped.id).unwrap();
+        let new = comment("new");
+        current.comments.add(new.clone()).unwrap();
+
+        let delta = current.delta_from(&baseline);
+        assert_eq!(delta.added.len(), 1);
+        assert_eq!(delta.added[0].id, new.id);
+        assert_eq!(delta.removed.len(), 1);
+        assert_eq!(delta.removed[0].id, dropped.id);
+        assert_eq!(delta.changed.len(), 2);
+        assert_eq!(delta.resolved.len(), 1);
+        assert_eq!(delta.resolved[0].id, fixed.id);
+
+        assert!(baseline.delta_from(&baseline).is_empty());
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        use crate::comment::{CommentBuilder, DiffSide};
+
+        let mut session = create_test_session();
+        session.metadata.name = Some("Auth rewrite".to_string());
+        let comment = CommentBuilder::new(
+            FileId::from_string("f"),
+            LineId::from_string("l"),
+            DiffSide::New,
+        )
+        .content("check expiry")
+        .build()
+        .unwrap();
+        let comment_id = session.comments.add(comment).unwrap();
+
+        let checkpoint = session.checkpoint("after security pass").unwrap();
+        assert_ne!(checkpoint.id, session.id);
+        assert_eq!(
+            checkpoint.metadata.name.as_deref(),
+            Some("Auth rewrite (checkpoint: after security pass)")
+        );
+        assert_eq!(checkpoint.checkpoint_of(), Some(session.id.clone()));
+        assert_eq!(checkpoint.info().checkpoint_of, Some(session.id.clone()));
+        assert_eq!(session.checkpoint_of(), None);
+
+        session.comments.update_state(&comment_id, CommentState::Resolved).unwrap();
+        let delta = session.delta_from(&checkpoint);
+        assert_eq!(delta.resolved.len(), 1);
+
+        assert!(session.checkpoint("  ").is_err());
+    }
+
     #[test]
     fn test_session_with_id() {
         let id = SessionId::generate();
@@ -361,6 +1585,19 @@ mod tests {
         assert!(session.updated_at > old_updated);
     }
 
+    #[test]
+    fn test_is_stale() {
+        let mut session = create_test_session();
+        assert!(!session.is_stale_in(Path::new(".")).unwrap());
+
+        session.metadata.checkin_hash = Some("0000000000000000000000000000000000000000".to_string());
+        assert!(session.is_stale_in(Path::new(".")).unwrap());
+
+        // HEAD is looked up in the given repository, not the current directory
+        let outside = tempfile::TempDir::new().unwrap();
+        assert!(session.is_stale_in(outside.path()).is_err());
+    }
+
     #[test]
     fn test_diff_source_git_args() {
         assert_eq!(DiffSource::WorkingTree.to_git_args(), Vec::<String>::new());
@@ -400,6 +1637,15 @@ mod tests {
             .description(),
             "Commit abc1234"
         );
+
+        let mut session = create_test_session();
+        assert_eq!(session.source_description(), "Working tree changes");
+        session
+            .diff_data
+            .metadata
+            .set_diff_flags(&["-U10".to_string(), "-w".to_string()]);
+        assert_eq!(session.source_description(), "Working tree changes (-U10 -w)");
+        assert_eq!(session.info().source_description, "Working tree changes (-U10 -w)");
     }
 
     #[test]
@@ -436,6 +1682,10 @@ mod tests {
             comment_count: 0,
             file_count: 0,
             source_description: "test".to_string(),
+            risk_score: 0.0,
+            word_count: 0,
+            checkpoint_of: None,
+            hottest_file: None,
         };
 
         // No name - doesn't match
@@ -462,6 +1712,10 @@ mod tests {
             comment_count: 0,
             file_count: 0,
             source_description: "test".to_string(),
+            risk_score: 0.0,
+            word_count: 0,
+            checkpoint_of: None,
+            hottest_file: None,
         };
 
         // No tags - doesn't match
@@ -484,6 +1738,10 @@ mod tests {
             comment_count: 0,
             file_count: 0,
             source_description: "test".to_string(),
+            risk_score: 0.0,
+            word_count: 0,
+            checkpoint_of: None,
+            hottest_file: None,
         };
 
         // No comments - doesn't match
@@ -494,6 +1752,21 @@ mod tests {
         assert!(filter.matches(&info));
     }
 
+    #[test]
+    fn test_pin() {
+        let mut session = create_test_session();
+        assert!(!session.is_pinned());
+        assert!(SessionFilter::new().with_pinned(false).matches(&session.info()));
+
+        session.pin();
+        assert!(session.is_pinned());
+        assert!(SessionFilter::new().with_pinned(true).matches(&session.info()));
+        assert!(!SessionFilter::new().with_pinned(false).matches(&session.info()));
+
+        session.unpin();
+        assert!(!session.is_pinned());
+    }
+
     #[test]
     fn test_session_serialization() {
         let session = create_test_session();
@@ -502,4 +1775,200 @@ mod tests {
         assert_eq!(session.id, session2.id);
         assert_eq!(session.diff_source, session2.diff_source);
     }
+
+    #[test]
+    fn test_annotation_coverage() {
+        use crate::comment::{CommentBuilder, DiffSide};
+
+        let diff = DiffParser::new()
+            .parse("diff --git a/a.rs b/a.rs\n@@ -1,2 +1,3 @@\n ctx\n-old\n+new\n+more\n")
+            .unwrap();
+        let mut session = Session::new(DiffSource::WorkingTree, diff);
+        assert_eq!(session.annotation_coverage(), 0.0);
+
+        let file_id = session.diff_data.files[0].id.clone();
+        let lines = session.diff_data.files[0].hunks[0].lines.clone();
+        for line in [&lines[0], &lines[2], &lines[2]] {
+            let comment = CommentBuilder::new(file_id.clone(), line.id.clone(), DiffSide::New)
+                .content("note")
+                .build()
+                .unwrap();
+            session.comments.add(comment).unwrap();
+        }
+
+        // Context lines don't count; duplicate comments on a line count once
+        assert!((session.annotation_coverage() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(session.summary().comment_count, 3);
+        assert_eq!(create_test_session().annotation_coverage(), 0.0);
+    }
+
+    #[test]
+    fn test_word_count_summary() {
+        use crate::comment::{CommentBuilder, DiffSide};
+
+        let mut session = Session::new(DiffSource::WorkingTree, DiffData::empty());
+        assert_eq!(session.word_count_summary(), WordCountSummary::default());
+
+        let file = FileId::from_string("f1");
+        let line = LineId::from_string("l1");
+        let fix = CommentBuilder::new(file.clone(), line.clone(), DiffSide::New)
+            .content("Use a prepared statement here")
+            .severity(Severity::Critical)
+            .suggested_fix("bind the user id")
+            .build()
+            .unwrap();
+        session.comments.add(fix).unwrap();
+        let typo = CommentBuilder::new(file, line, DiffSide::New)
+            .content("typo")
+            .build()
+            .unwrap();
+        session.comments.add(typo).unwrap();
+
+        let summary = session.word_count_summary();
+        assert_eq!(summary.total_words, 10);
+        assert_eq!(summary.longest_comment, 9);
+        assert_eq!(summary.avg_comment_length, 5.0);
+        assert_eq!(summary.by_severity[&Severity::Critical], 9);
+        assert_eq!(summary.total_chars, 29 + 16 + 4);
+        assert_eq!(session.info().word_count, 10);
+    }
+
+    #[test]
+    fn test_compute_risk_score() {
+        use crate::comment::{CommentBuilder, DiffSide};
+
+        let added: String = (0..20).map(|i| format!("+line {}\n", i)).collect();
+        let diff = DiffParser::new()
+            .parse(&format!("diff --git a/a.rs b/a.rs\n@@ -0,0 +1,20 @@\n{}", added))
+            .unwrap();
+        let mut session = Session::new(DiffSource::WorkingTree, diff);
+        assert_eq!(session.compute_risk_score().level, RiskLevel::Low);
+
+        let file_id = session.diff_data.files[0].id.clone();
+        let line_id = session.diff_data.files[0].hunks[0].lines[0].id.clone();
+        let mut add = |severity, state| {
+            let mut comment = CommentBuilder::new(file_id.clone(), line_id.clone(), DiffSide::New)
+                .content("note")
+                .severity(severity)
+                .build()
+                .unwrap();
+            comment.set_state(state);
+            session.comments.add(comment).unwrap();
+        };
+        add(Severity::Critical, CommentState::Open);
+        add(Severity::Warning, CommentState::Open);
+        add(Severity::Critical, CommentState::Resolved);
+
+        // (10 + 3) / ln(20)
+        let risk = session.compute_risk_score();
+        assert!((risk.score - 13.0 / 20f64.ln()).abs() < 1e-9);
+        assert_eq!(risk.level, RiskLevel::Medium);
+        assert_eq!(risk.explanation, "1 critical + 1 warnings in 20 changed lines");
+        assert_eq!(session.info().risk_score, risk.score);
+
+        let strict = RiskThresholds { medium: 1.0, high: 2.0, critical: 4.0 };
+        assert_eq!(session.compute_risk_score_with(&strict).level, RiskLevel::Critical);
+    }
+
+    #[test]
+    fn test_files_for_reviewer() {
+        let input = ["src/auth/login.rs", "src/auth/token.rs", "docs/guide.md"]
+            .iter()
+            .map(|p| format!("diff --git a/{p} b/{p}\n--- a/{p}\n+++ b/{p}\n@@ -1 +1 @@\n-a\n+b\n"))
+            .collect::<String>();
+        let diff = crate::diff::DiffParser::new().parse(&input).unwrap();
+        let mut session = Session::new(DiffSource::WorkingTree, diff);
+        session.metadata.assignments = vec![
+            ReviewAssignment {
+                reviewer: "alex".to_string(),
+                file_patterns: vec!["src/auth/**".to_string(), "[".to_string()],
+            },
+            ReviewAssignment {
+                reviewer: "sam".to_string(),
+                file_patterns: vec!["**/*.md".to_string(), "src/auth/token.rs".to_string()],
+            },
+        ];
+
+        let paths = |reviewer| -> Vec<String> {
+            session
+                .files_for_reviewer(reviewer)
+                .iter()
+                .map(|f| f.display_path().display().to_string())
+                .collect()
+        };
+        assert_eq!(paths("alex"), vec!["src/auth/login.rs", "src/auth/token.rs"]);
+        assert_eq!(paths("sam"), vec!["src/auth/token.rs", "docs/guide.md"]);
+        assert!(paths("kim").is_empty());
+        assert_eq!(
+            session.reviewers_for_file(&session.diff_data.files[1]),
+            vec!["alex", "sam"]
+        );
+    }
+
+    #[test]
+    fn test_merge_metadata() {
+        let mut session = create_test_session();
+        session.metadata = SessionMetadata {
+            description: Some("first".to_string()),
+            ..SessionMetadata::default().with_tag("a").with_tag("b")
+        };
+        let other = SessionMetadata {
+            description: Some("second".to_string()),
+            reviewer: Some("sam".to_string()),
+            ..SessionMetadata::with_name("other").with_tag("b").with_tag("c")
+        };
+
+        session.merge_metadata(&other);
+        assert_eq!(session.metadata.name.as_deref(), Some("other"));
+        assert_eq!(session.metadata.tags, vec!["a", "b", "c"]);
+        assert_eq!(session.metadata.description.as_deref(), Some("first\nsecond"));
+        assert_eq!(session.metadata.reviewer.as_deref(), Some("sam"));
+
+        // Our name wins once set
+        session.merge_metadata(&SessionMetadata::with_name("third"));
+        assert_eq!(session.metadata.name.as_deref(), Some("other"));
+    }
+
+    mod merge_props {
+        use super::*;
+        use proptest::prelude::*;
+        use std::collections::BTreeSet;
+
+        fn metadata_strategy() -> impl Strategy<Value = SessionMetadata> {
+            (
+                prop::option::of("[a-z]{1,8}"),
+                prop::collection::vec("[a-c]{1,2}", 0..6),
+            )
+                .prop_map(|(name, tags)| SessionMetadata {
+                    name,
+                    tags,
+                    ..Default::default()
+                })
+        }
+
+        proptest! {
+            #[test]
+            fn prop_merge_tags_commute(a in metadata_strategy(), b in metadata_strategy()) {
+                let ab: BTreeSet<_> = a.merge_with(&b).tags.into_iter().collect();
+                let ba: BTreeSet<_> = b.merge_with(&a).tags.into_iter().collect();
+                prop_assert_eq!(&ab, &ba);
+
+                let merged = a.merge_with(&b).tags;
+                prop_assert_eq!(merged.len(), ab.len());
+            }
+
+            #[test]
+            fn prop_merge_name_commutes_when_unambiguous(
+                a in metadata_strategy(),
+                b in metadata_strategy(),
+            ) {
+                let ab = a.merge_with(&b).name;
+                let ba = b.merge_with(&a).name;
+                prop_assert_eq!(ab.is_some(), ba.is_some());
+                if a.name.is_none() || b.name.is_none() || a.name == b.name {
+                    prop_assert_eq!(ab, ba);
+                }
+            }
+        }
+    }
 }
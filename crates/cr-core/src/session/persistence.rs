@@ -1,8 +1,29 @@
 //! Session storage trait and abstractions
 
-use super::model::{Session, SessionInfo};
-use crate::error::Result;
+use super::model::{Session, SessionInfo, SessionSortOrder};
+use crate::error::{CrHelperError, Result};
 use crate::types::SessionId;
+use std::any::Any;
+
+/// Callback invoked with the ID of a changed session
+pub type WatchCallback = Box<dyn Fn(SessionId) + Send + 'static>;
+
+/// Change applied to a session by [`SessionStorage::update`]
+pub type SessionUpdate<'a> = Box<dyn FnOnce(&mut Session) -> Result<()> + 'a>;
+
+/// Keeps a session watch alive; dropping it stops the watch
+pub struct WatchHandle {
+    _guard: Box<dyn Any + Send>,
+}
+
+impl WatchHandle {
+    /// Wrap whatever keeps the watch running (e.g. a file watcher)
+    pub fn new(guard: impl Any + Send) -> Self {
+        Self {
+            _guard: Box::new(guard),
+        }
+    }
+}
 
 /// Trait for session storage implementations
 pub trait SessionStorage: Send + Sync {
@@ -21,19 +42,160 @@ pub trait SessionStorage: Send + Sync {
     /// Check if a session exists
     fn exists(&self, id: &SessionId) -> bool;
 
+    /// List sessions in the given order, returning at most `limit`
+    ///
+    /// The default sorts the full [`list`](Self::list); backends can
+    /// override it to avoid reading every session.
+    fn list_sorted(
+        &self,
+        order: SessionSortOrder,
+        limit: Option<usize>,
+    ) -> Result<Vec<SessionInfo>> {
+        let mut sessions = self.list()?;
+        order.sort(&mut sessions);
+        if let Some(limit) = limit {
+            sessions.truncate(limit);
+        }
+        Ok(sessions)
+    }
+
     /// Get the latest session (by updated_at)
     fn latest(&self) -> Result<Option<Session>> {
-        let sessions = self.list()?;
-        if sessions.is_empty() {
-            return Ok(None);
+        match self
+            .list_sorted(SessionSortOrder::newest_first(), Some(1))?
+            .pop()
+        {
+            Some(info) => self.load(&info.id).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Find the session whose [short ID](SessionId::short) starts with `short`
+    ///
+    /// Fails, listing the full IDs, if more than one session matches.
+    fn lookup_by_short_id(&self, short: &str) -> Result<Option<SessionId>> {
+        let ids = self.list()?.into_iter().map(|info| info.id);
+        match_short_id(ids, short)
+    }
+
+    /// Delete the oldest sessions (by `updated_at`) beyond `max_count`
+    ///
+    /// Only sessions outside the newest `max_count` are candidates, and
+    /// pinned ones are always kept. Sessions with comments are kept too,
+    /// until commented sessions alone take the total past
+    /// [`COMMENTED_SESSION_LIMIT`] times `max_count`; then the oldest of them
+    /// go as well, so storage stays bounded even when every review has
+    /// comments. Returns the number deleted.
+    fn rotate_sessions(&self, max_count: usize) -> Result<usize> {
+        rotate_oldest(self, max_count, max_count.saturating_mul(COMMENTED_SESSION_LIMIT))
+    }
+
+    /// Like [`rotate_sessions`](Self::rotate_sessions), but sessions with
+    /// comments are deleted like any other (`review --force-rotate`)
+    fn force_rotate_sessions(&self, max_count: usize) -> Result<usize> {
+        rotate_oldest(self, max_count, max_count)
+    }
+
+    /// Call `callback` with a session's ID whenever it is written
+    ///
+    /// Backends that can't observe changes return an error.
+    fn watch(&self, callback: WatchCallback) -> Result<WatchHandle> {
+        let _ = callback;
+        Err(CrHelperError::Validation(
+            "This storage backend does not support watching".to_string(),
+        ))
+    }
+
+    /// Load a session, apply `update` and save it as one step
+    ///
+    /// Storage shared between processes overrides this to hold a lock for
+    /// the whole read-modify-write; the default just loads and saves.
+    fn update(&self, id: &SessionId, update: SessionUpdate<'_>) -> Result<()> {
+        let mut session = self.load(id)?;
+        update(&mut session)?;
+        self.save(&session)
+    }
+
+    /// Remove temp files left behind by interrupted writes
+    ///
+    /// Returns the number of files removed. Storage backends without temp
+    /// files don't need to override this.
+    fn prune_orphaned_temp_files(&self) -> Result<usize> {
+        Ok(0)
+    }
+}
+
+/// Rotation keeps sessions with comments up to this multiple of the
+/// configured maximum (see [`SessionStorage::rotate_sessions`])
+pub const COMMENTED_SESSION_LIMIT: usize = 2;
+
+/// Delete the oldest unpinned sessions outside the newest `max_count`:
+/// those without comments until `max_count` remain, then those with
+/// comments until `commented_limit` remain
+fn rotate_oldest<S: SessionStorage + ?Sized>(
+    storage: &S,
+    max_count: usize,
+    commented_limit: usize,
+) -> Result<usize> {
+    let mut sessions = storage.list()?;
+    if sessions.len() <= max_count {
+        return Ok(0);
+    }
+
+    sessions.sort_by_key(|s| s.updated_at);
+    let mut remaining = sessions.len();
+    let candidates: Vec<_> = sessions
+        .into_iter()
+        .take(remaining - max_count)
+        .filter(|info| !info.metadata.pinned)
+        .collect();
+    let (commented, uncommented): (Vec<_>, Vec<_>) =
+        candidates.into_iter().partition(|info| info.comment_count > 0);
+
+    let mut deleted = 0;
+    for info in uncommented {
+        storage.delete(&info.id)?;
+        remaining -= 1;
+        deleted += 1;
+    }
+    for info in commented {
+        if remaining <= commented_limit {
+            break;
         }
+        storage.delete(&info.id)?;
+        remaining -= 1;
+        deleted += 1;
+    }
 
-        let latest_info = sessions
-            .into_iter()
-            .max_by_key(|s| s.updated_at)
-            .expect("Non-empty list should have max");
+    Ok(deleted)
+}
 
-        self.load(&latest_info.id).map(Some)
+/// Pick the one ID in `ids` whose short ID starts with `short`
+///
+/// Shared by [`SessionStorage::lookup_by_short_id`] implementations.
+pub fn match_short_id(
+    ids: impl IntoIterator<Item = SessionId>,
+    short: &str,
+) -> Result<Option<SessionId>> {
+    if short.is_empty() {
+        return Ok(None);
+    }
+    let mut matches: Vec<SessionId> = ids
+        .into_iter()
+        .filter(|id| id.short().starts_with(short))
+        .collect();
+    match matches.len() {
+        0 => Ok(None),
+        1 => Ok(matches.pop()),
+        _ => {
+            matches.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+            let ids: Vec<&str> = matches.iter().map(SessionId::as_str).collect();
+            Err(CrHelperError::Validation(format!(
+                "Session ID '{}' is ambiguous, it matches: {}",
+                short,
+                ids.join(", ")
+            )))
+        }
     }
 }
 
@@ -163,6 +325,61 @@ pub mod memory {
             assert_eq!(latest.id, expected_id);
         }
 
+        #[test]
+        fn test_rotate_sessions_bounds_commented_sessions() {
+            use crate::comment::{CommentBuilder, DiffSide};
+            use crate::types::{FileId, LineId};
+
+            let storage = MemoryStorage::new();
+            let start = chrono::Utc::now();
+            let at = |secs: i64| start + chrono::Duration::seconds(secs);
+            let mut pinned = create_test_session();
+            pinned.pin();
+            pinned.updated_at = at(0);
+            storage.save(&pinned).unwrap();
+
+            let mut newest = Vec::new();
+            for i in 1..=10 {
+                let mut session = create_test_session();
+                session.updated_at = at(i);
+                let comment = CommentBuilder::new(FileId::from_string("f"), LineId::from_string("l"), DiffSide::New)
+                    .content("keep me")
+                    .build()
+                    .unwrap();
+                session.comments.add(comment).unwrap();
+                storage.save(&session).unwrap();
+                newest.push(session.id);
+                storage.rotate_sessions(2).unwrap();
+
+                // Commented sessions outlive the maximum, but only up to the limit
+                assert!(storage.list().unwrap().len() <= 2 * COMMENTED_SESSION_LIMIT);
+            }
+            assert_eq!(storage.list().unwrap().len(), 2 * COMMENTED_SESSION_LIMIT);
+            assert!(storage.exists(&pinned.id));
+            assert!(newest[7..].iter().all(|id| storage.exists(id)));
+
+            // Sessions without comments go first, even when newer
+            let plain: Vec<_> = (11..=13)
+                .map(|i| {
+                    let mut session = create_test_session();
+                    session.updated_at = at(i);
+                    storage.save(&session).unwrap();
+                    session.id
+                })
+                .collect();
+            assert_eq!(storage.rotate_sessions(2).unwrap(), 3);
+            assert!(!storage.exists(&plain[0]));
+            assert!(storage.exists(&newest[9]));
+
+            // Forced rotation only keeps pinned sessions past the maximum
+            storage.force_rotate_sessions(2).unwrap();
+            let mut kept: Vec<_> = storage.list().unwrap().into_iter().map(|info| info.id).collect();
+            kept.sort_by_key(|id| id.to_string());
+            let mut expected = vec![pinned.id.clone(), plain[1].clone(), plain[2].clone()];
+            expected.sort_by_key(|id| id.to_string());
+            assert_eq!(kept, expected);
+        }
+
         #[test]
         fn test_memory_storage_load_nonexistent() {
             let storage = MemoryStorage::new();
@@ -1,5 +1,6 @@
 //! Core type definitions for cr-helper
 
+use crate::diff::{DiffData, LineType};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
@@ -53,11 +54,48 @@ pub struct LineId(pub String);
 
 impl LineId {
     /// Create a LineId from file path and line content
-    pub fn from_content(file_path: &Path, content: &str, line_num: usize) -> Self {
+    ///
+    /// The ID does not depend on the line number, so it survives rebases that
+    /// shift lines. Identical lines in the same file share an ID; the diff
+    /// parser uses [`LineId::from_occurrence`] to tell them apart.
+    pub fn from_content(file_path: &Path, content: &str) -> Self {
+        let hash = blake3::hash(format!("{}:{}", file_path.display(), content).as_bytes());
+        LineId(format!("l_{}", &hash.to_hex()[..16]))
+    }
+
+    /// Create a LineId for the `occurrence`-th (0-based) line of a file with
+    /// this content
+    ///
+    /// The first occurrence gets the [`LineId::from_content`] ID. Later copies
+    /// (every `}`, blank line or repeated `return Ok(())`) also hash the line
+    /// type and occurrence, so each copy has its own ID.
+    pub fn from_occurrence(file_path: &Path, line_type: LineType, content: &str, occurrence: usize) -> Self {
+        if occurrence == 0 {
+            return Self::from_content(file_path, content);
+        }
+        let key = format!("{}:{:?}:{}:{}", file_path.display(), line_type, occurrence, content);
+        LineId(format!("l_{}", &blake3::hash(key.as_bytes()).to_hex()[..16]))
+    }
+
+    /// Create a LineId from file path, line number and content
+    ///
+    /// Used for whole files loaded lazily, where every line is distinct by
+    /// position.
+    pub fn from_position(file_path: &Path, content: &str, line_num: usize) -> Self {
         let hash = blake3::hash(format!("{}:{}:{}", file_path.display(), line_num, content).as_bytes());
         LineId(format!("l_{}", &hash.to_hex()[..16]))
     }
 
+    /// Check whether a line with this ID is still present in `new_diff`
+    pub fn is_stable_across_rebase(&self, new_diff: &DiffData) -> bool {
+        new_diff
+            .files
+            .iter()
+            .flat_map(|f| &f.hunks)
+            .flat_map(|h| &h.lines)
+            .any(|l| &l.id == self)
+    }
+
     /// Create a LineId from a string
     pub fn from_string(s: impl Into<String>) -> Self {
         LineId(s.into())
@@ -143,6 +181,11 @@ impl SessionId {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// The 8-character UUID part after the timestamp
+    pub fn short(&self) -> &str {
+        self.0.split_once('-').map_or(&self.0, |(_, short)| short)
+    }
 }
 
 impl fmt::Display for SessionId {
@@ -263,9 +306,13 @@ mod tests {
     fn test_line_id_stability() {
         let path = PathBuf::from("src/main.rs");
         let content = "fn main() {}";
-        let id1 = LineId::from_content(&path, content, 1);
-        let id2 = LineId::from_content(&path, content, 1);
+        let id1 = LineId::from_content(&path, content);
+        let id2 = LineId::from_content(&path, content);
         assert_eq!(id1, id2);
+
+        let id1 = LineId::from_position(&path, content, 1);
+        let id2 = LineId::from_position(&path, content, 2);
+        assert_ne!(id1, id2);
     }
 
     #[test]
@@ -289,6 +336,13 @@ mod tests {
         assert!(SessionId::from_string("2024-abcd1234").is_err());
     }
 
+    #[test]
+    fn test_session_id_short() {
+        let id = SessionId::from_string("20241231120000-abcd1234").unwrap();
+        assert_eq!(id.short(), "abcd1234");
+        assert_eq!(SessionId::generate().short().len(), 8);
+    }
+
     #[test]
     fn test_protocol_version_compatibility() {
         let v1_0 = ProtocolVersion::V1_0;
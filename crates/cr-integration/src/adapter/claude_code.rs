@@ -2,10 +2,16 @@
 //!
 //! Implementation of AgentAdapter for Claude Code.
 
-use super::{AgentAdapter, AgentInfo, AgentType, InstallScope};
-use anyhow::Result;
+use super::{AgentAdapter, AgentInfo, AgentSession, AgentType, InstallScope};
+use crate::lock::LockFile;
+use anyhow::{bail, Context, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime};
+
+/// File written by [`ClaudeCodeAdapter::inject_review_context`], relative to `.claude/`
+pub const REVIEW_CONTEXT_FILE: &str = "cr-helper/review-context.md";
 
 /// Claude Code adapter
 pub struct ClaudeCodeAdapter {
@@ -62,6 +68,102 @@ impl ClaudeCodeAdapter {
         result
     }
 
+    /// Last assistant message of the most recent conversation in `.claude/`
+    ///
+    /// Looks at `.json` and `.jsonl` files (settings files excluded) and
+    /// picks the newest by modification time. Messages may carry `role`
+    /// directly or under `message`, with string or text-block `content`.
+    pub fn read_latest_context(&self) -> Result<Option<String>> {
+        let mut files = Vec::new();
+        collect_conversation_files(&self.project_claude_dir(), &mut files)?;
+        let Some((_, path)) = files.into_iter().max_by_key(|(modified, _)| *modified) else {
+            return Ok(None);
+        };
+
+        let content = fs::read_to_string(&path)?;
+        let messages: Vec<serde_json::Value> = if path.extension().is_some_and(|e| e == "jsonl") {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        } else {
+            match serde_json::from_str(&content)? {
+                serde_json::Value::Array(messages) => messages,
+                value => value
+                    .get("messages")
+                    .and_then(|m| m.as_array())
+                    .cloned()
+                    .unwrap_or_default(),
+            }
+        };
+
+        Ok(messages.iter().rev().find_map(assistant_text))
+    }
+
+    /// Path of the injected review context
+    pub fn review_context_path(&self) -> PathBuf {
+        self.project_claude_dir().join(REVIEW_CONTEXT_FILE)
+    }
+
+    /// Write the session summary, file list and open critical/warning
+    /// comments to `.claude/cr-helper/review-context.md`
+    pub fn inject_review_context(&self, session: &cr_core::session::Session) -> Result<()> {
+        let path = self.review_context_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, self.format_review_context(session))?;
+        Ok(())
+    }
+
+    /// Review context for Claude, limited to open issues that need action
+    fn format_review_context(&self, session: &cr_core::session::Session) -> String {
+        use cr_core::comment::{CommentState, Severity};
+
+        let open: Vec<_> = session
+            .comments
+            .all_sorted()
+            .into_iter()
+            .filter(|c| c.state == CommentState::Open && c.severity != Severity::Info)
+            .collect();
+        let stats = &session.diff_data.stats;
+
+        let mut context = String::from("# cr-helper Review Context\n\n");
+        context.push_str(&format!(
+            "**Session**: {} ({})\n",
+            session.id,
+            session.source_description()
+        ));
+        context.push_str(&format!(
+            "**Summary**: {} files changed, {} insertions(+), {} deletions(-), {} open critical/warning comments\n\n",
+            stats.files_changed,
+            stats.insertions,
+            stats.deletions,
+            open.len()
+        ));
+
+        if !session.diff_data.files.is_empty() {
+            context.push_str("## Files Changed\n\n");
+            for file in &session.diff_data.files {
+                context.push_str(&format!("- {}\n", self.format_location(file)));
+            }
+            context.push('\n');
+        }
+
+        context.push_str("## Open Issues\n\n");
+        if open.is_empty() {
+            context.push_str("No open critical or warning comments.\n");
+        }
+        for severity in [Severity::Critical, Severity::Warning] {
+            for comment in open.iter().filter(|c| c.severity == severity) {
+                context.push_str(&self.format_comment(comment));
+                context.push_str("\n\n");
+            }
+        }
+
+        context
+    }
+
     /// Format location for Claude Code
     fn format_location(&self, file: &cr_core::diff::FileDiff) -> String {
         let mode_icon = match file.mode {
@@ -76,6 +178,48 @@ impl ClaudeCodeAdapter {
     }
 }
 
+/// Collect `.json`/`.jsonl` conversation files under `dir` with their mtimes
+fn collect_conversation_files(dir: &Path, files: &mut Vec<(SystemTime, PathBuf)>) -> Result<()> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect_conversation_files(&path, files)?;
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_conversation = (name.ends_with(".json") || name.ends_with(".jsonl"))
+            && !name.starts_with("settings");
+        if is_conversation {
+            files.push((entry.metadata()?.modified()?, path));
+        }
+    }
+    Ok(())
+}
+
+/// Text of an assistant message, or `None` for other entries
+fn assistant_text(entry: &serde_json::Value) -> Option<String> {
+    let message = entry.get("message").unwrap_or(entry);
+    if message.get("role").and_then(|r| r.as_str()) != Some("assistant") {
+        return None;
+    }
+    match message.get("content")? {
+        serde_json::Value::String(text) => Some(text.clone()),
+        serde_json::Value::Array(blocks) => {
+            let text: Vec<&str> = blocks
+                .iter()
+                .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+                .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                .collect();
+            (!text.is_empty()).then(|| text.join("\n"))
+        }
+        _ => None,
+    }
+}
+
 impl Default for ClaudeCodeAdapter {
     fn default() -> Self {
         Self::new()
@@ -108,6 +252,7 @@ impl AgentAdapter for ClaudeCodeAdapter {
                 None
             },
             global_dir: if has_global { global_dir } else { None },
+            confidence: 100,
         }))
     }
 
@@ -214,6 +359,29 @@ impl AgentAdapter for ClaudeCodeAdapter {
             InstallScope::Global => self.global_claude_dir().map(|d| d.join("settings.json")),
         }
     }
+
+    fn list_running_sessions(&self) -> Result<Vec<AgentSession>> {
+        Ok(LockFile::read(&self.project_dir)?
+            .into_iter()
+            .filter(|s| s.is_alive())
+            .collect())
+    }
+
+    fn ping(&self) -> Result<Duration> {
+        let start = Instant::now();
+        let output = Command::new("claude")
+            .arg("--version")
+            .output()
+            .context("Failed to run claude (is Claude Code installed?)")?;
+        let elapsed = start.elapsed();
+        if !output.status.success() {
+            bail!(
+                "claude --version failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(elapsed)
+    }
 }
 
 #[cfg(test)]
@@ -253,6 +421,7 @@ mod tests {
             mode: FileMode::Modified,
             hunks: vec![],
             lazy: false,
+            file_mode: None,
         };
 
         let formatted = adapter.format_location(&file);
@@ -285,4 +454,68 @@ mod tests {
         assert!(formatted.contains("WARNING"));
         assert!(formatted.contains("src/main.rs:42"));
     }
+
+    #[test]
+    fn test_read_latest_context() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let adapter = ClaudeCodeAdapter::with_project_dir(temp.path().to_path_buf());
+        assert!(adapter.read_latest_context().unwrap().is_none());
+
+        let dir = temp.path().join(".claude/projects");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(temp.path().join(".claude/settings.json"), "{}").unwrap();
+        fs::write(
+            dir.join("old.json"),
+            r#"[{"role": "assistant", "content": "old"}]"#,
+        )
+        .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        fs::write(
+            dir.join("new.jsonl"),
+            concat!(
+                r#"{"type": "user", "message": {"role": "user", "content": "hi"}}"#,
+                "\n",
+                r#"{"message": {"role": "assistant", "content": [{"type": "text", "text": "done"}]}}"#,
+                "\n",
+                r#"{"message": {"role": "user", "content": "thanks"}}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(adapter.read_latest_context().unwrap().as_deref(), Some("done"));
+    }
+
+    #[test]
+    fn test_inject_review_context() {
+        use cr_core::comment::{CommentBuilder, CommentState, DiffSide};
+        use cr_core::types::{FileId, LineId};
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let adapter = ClaudeCodeAdapter::with_project_dir(temp.path().to_path_buf());
+        let mut session = cr_core::session::Session::new(
+            cr_core::session::DiffSource::WorkingTree,
+            cr_core::diff::DiffData::empty(),
+        );
+        for (content, severity, state) in [
+            ("open critical", cr_core::comment::Severity::Critical, CommentState::Open),
+            ("open info", cr_core::comment::Severity::Info, CommentState::Open),
+            ("resolved warning", cr_core::comment::Severity::Warning, CommentState::Resolved),
+        ] {
+            let comment = CommentBuilder::new(FileId::from_string("f"), LineId::from_string(content), DiffSide::New)
+                .content(content)
+                .severity(severity)
+                .state(state)
+                .build()
+                .unwrap();
+            session.comments.add(comment).unwrap();
+        }
+
+        adapter.inject_review_context(&session).unwrap();
+        let context = fs::read_to_string(adapter.review_context_path()).unwrap();
+        assert!(context.contains(&session.id.to_string()));
+        assert!(context.contains("open critical"));
+        assert!(!context.contains("open info"));
+        assert!(!context.contains("resolved warning"));
+    }
 }
@@ -0,0 +1,145 @@
+//! GitHub adapter
+//!
+//! Imports pull request review comments through the `gh` CLI.
+
+use anyhow::{bail, Context, Result};
+use cr_core::comment::{Comment, CommentBuilder};
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// GitHub adapter
+pub struct GitHubAdapter {
+    /// Repository checkout `gh` resolves `{owner}/{repo}` from
+    project_dir: PathBuf,
+}
+
+impl GitHubAdapter {
+    /// Create a new GitHub adapter for the current directory
+    pub fn new() -> Self {
+        Self {
+            project_dir: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        }
+    }
+
+    /// Create with a specific project directory
+    pub fn with_project_dir(project_dir: PathBuf) -> Self {
+        Self { project_dir }
+    }
+
+    /// Review comments on pull request `pr_number` of this repository
+    ///
+    /// Calls `GET /repos/{owner}/{repo}/pulls/{pull_number}/comments` with
+    /// `gh api --paginate`, so `gh` must be installed and logged in.
+    pub fn import_pr_comments(&self, pr_number: u64) -> Result<Vec<Comment>> {
+        let endpoint = format!("repos/{{owner}}/{{repo}}/pulls/{}/comments", pr_number);
+        let output = Command::new("gh")
+            .args(["api", "--paginate", &endpoint])
+            .current_dir(&self.project_dir)
+            .output()
+            .context("Failed to run gh (is the GitHub CLI installed?)")?;
+        if !output.status.success() {
+            bail!(
+                "gh api {} failed: {}",
+                endpoint,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        parse_pr_comments(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Base branch name of pull request `pr_number`
+    pub fn pr_base(&self, pr_number: u64) -> Result<String> {
+        let output = Command::new("gh")
+            .args([
+                "pr",
+                "view",
+                &pr_number.to_string(),
+                "--json",
+                "baseRefName",
+                "-q",
+                ".baseRefName",
+            ])
+            .current_dir(&self.project_dir)
+            .output()
+            .context("Failed to run gh (is the GitHub CLI installed?)")?;
+        if !output.status.success() {
+            bail!(
+                "gh pr view {} failed: {}",
+                pr_number,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Round-trip time of a `GET /rate_limit` request
+    ///
+    /// The rate limit endpoint is GitHub's cheapest authenticated call and
+    /// does not itself count against the limit.
+    pub fn ping(&self) -> Result<Duration> {
+        let start = Instant::now();
+        let output = Command::new("gh")
+            .args(["api", "rate_limit", "--silent"])
+            .current_dir(&self.project_dir)
+            .output()
+            .context("Failed to run gh (is the GitHub CLI installed?)")?;
+        let elapsed = start.elapsed();
+        if !output.status.success() {
+            bail!(
+                "gh api rate_limit failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(elapsed)
+    }
+}
+
+impl Default for GitHubAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse review comments from the API's JSON arrays
+///
+/// `gh api --paginate` prints one array per page back to back.
+pub fn parse_pr_comments(json: &str) -> Result<Vec<Comment>> {
+    let mut comments = Vec::new();
+    for page in serde_json::Deserializer::from_str(json).into_iter::<serde_json::Value>() {
+        let page = page.context("Invalid JSON from the GitHub API")?;
+        let Some(items) = page.as_array() else {
+            bail!("Expected a JSON array of review comments");
+        };
+        for item in items {
+            let id = item.get("id").map(|v| v.to_string()).unwrap_or_default();
+            let builder = CommentBuilder::from_pr_review_comment(item)
+                .context(format!("Review comment {}", id))?;
+            comments.push(builder.build()?);
+        }
+    }
+    Ok(comments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cr_core::comment::Severity;
+
+    #[test]
+    fn test_parse_pr_comments_pages() {
+        let json = r#"[{"id": 1, "path": "src/a.rs", "line": 3, "body": "🔴 **CRITICAL**: Leaks the token"}]
+[{"id": 2, "path": "src/b.rs", "line": 7, "body": "Nit: rename this"},
+ {"id": 3, "path": "src/c.rs", "line": null, "original_line": null, "subject_type": "file", "body": "Split this up"}]"#;
+        let comments = parse_pr_comments(json).unwrap();
+        assert_eq!(comments.len(), 3);
+        assert_eq!(comments[2].metadata.line_number, None);
+        assert_eq!(comments[0].severity, Severity::Critical);
+        assert_eq!(comments[0].content, "Leaks the token");
+        assert_eq!(comments[1].metadata.file_path.as_deref(), Some("src/b.rs"));
+
+        assert!(parse_pr_comments("[]").unwrap().is_empty());
+        assert!(parse_pr_comments(r#"{"message": "Not Found"}"#).is_err());
+        assert!(parse_pr_comments(r#"[{"id": 3, "body": "no path"}]"#).is_err());
+    }
+}
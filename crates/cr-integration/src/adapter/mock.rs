@@ -0,0 +1,231 @@
+//! Mock adapter
+//!
+//! A configurable AgentAdapter for tests that records every call.
+
+use super::{AgentAdapter, AgentInfo, AgentSession, AgentType, InstallScope};
+use anyhow::Result;
+use cr_core::types::SessionId;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A recorded call on a [`MockAgentAdapter`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockCall {
+    /// `detect()`
+    Detect,
+    /// `format_context()` for a session
+    FormatContext(SessionId),
+    /// `export_to_file()` for a session and path
+    ExportToFile(SessionId, PathBuf),
+    /// `settings_path()` for a scope
+    SettingsPath(InstallScope),
+    /// `list_running_sessions()`
+    ListRunningSessions,
+    /// `ping()`
+    Ping,
+}
+
+/// Agent adapter with canned responses
+pub struct MockAgentAdapter {
+    agent_type: AgentType,
+    detection: Option<AgentInfo>,
+    format_context: String,
+    export_error: Option<String>,
+    settings_dir: Option<PathBuf>,
+    running_sessions: Vec<AgentSession>,
+    ping_latency: Option<Duration>,
+    calls: Arc<Mutex<Vec<MockCall>>>,
+}
+
+impl MockAgentAdapter {
+    /// Create a mock that detects nothing and formats to an empty string
+    pub fn new() -> Self {
+        Self {
+            agent_type: AgentType::Other,
+            detection: None,
+            format_context: String::new(),
+            export_error: None,
+            settings_dir: None,
+            running_sessions: Vec::new(),
+            ping_latency: None,
+            calls: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Set the result of `detect()`
+    pub fn with_detection(mut self, result: Option<AgentInfo>) -> Self {
+        if let Some(info) = &result {
+            self.agent_type = info.agent_type;
+        }
+        self.detection = result;
+        self
+    }
+
+    /// Set the output of `format_context()`
+    pub fn with_format_context(mut self, output: impl Into<String>) -> Self {
+        self.format_context = output.into();
+        self
+    }
+
+    /// Make `export_to_file()` fail with this error
+    pub fn with_export_error(mut self, err: anyhow::Error) -> Self {
+        self.export_error = Some(format!("{:#}", err));
+        self
+    }
+
+    /// Set the directory `settings_path()` resolves into
+    pub fn with_settings_dir(mut self, dir: PathBuf) -> Self {
+        self.settings_dir = Some(dir);
+        self
+    }
+
+    /// Set the result of `list_running_sessions()`
+    pub fn with_running_sessions(mut self, sessions: Vec<AgentSession>) -> Self {
+        self.running_sessions = sessions;
+        self
+    }
+
+    /// Make `ping()` succeed with this latency (it fails by default)
+    pub fn with_ping_latency(mut self, latency: Duration) -> Self {
+        self.ping_latency = Some(latency);
+        self
+    }
+
+    /// Calls recorded so far
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Shared handle to the call log (stays valid after the adapter is boxed)
+    pub fn call_log(&self) -> Arc<Mutex<Vec<MockCall>>> {
+        Arc::clone(&self.calls)
+    }
+
+    fn record(&self, call: MockCall) {
+        self.calls.lock().unwrap().push(call);
+    }
+}
+
+impl Default for MockAgentAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AgentAdapter for MockAgentAdapter {
+    fn agent_type(&self) -> AgentType {
+        self.agent_type
+    }
+
+    fn detect(&self) -> Result<Option<AgentInfo>> {
+        self.record(MockCall::Detect);
+        Ok(self.detection.clone())
+    }
+
+    fn format_context(&self, session: &cr_core::session::Session) -> Result<String> {
+        self.record(MockCall::FormatContext(session.id.clone()));
+        Ok(self.format_context.clone())
+    }
+
+    fn export_to_file(&self, session: &cr_core::session::Session, path: &Path) -> Result<()> {
+        self.record(MockCall::ExportToFile(
+            session.id.clone(),
+            path.to_path_buf(),
+        ));
+        if let Some(err) = &self.export_error {
+            anyhow::bail!("{}", err);
+        }
+        fs::write(path, &self.format_context)?;
+        Ok(())
+    }
+
+    fn settings_path(&self, scope: InstallScope) -> Option<PathBuf> {
+        self.record(MockCall::SettingsPath(scope));
+        let dir = self.settings_dir.as_ref()?;
+        Some(match scope {
+            InstallScope::Project | InstallScope::Global => dir.join("settings.json"),
+            InstallScope::Local => dir.join("settings.local.json"),
+        })
+    }
+
+    fn list_running_sessions(&self) -> Result<Vec<AgentSession>> {
+        self.record(MockCall::ListRunningSessions);
+        Ok(self.running_sessions.clone())
+    }
+
+    fn ping(&self) -> Result<Duration> {
+        self.record(MockCall::Ping);
+        self.ping_latency
+            .ok_or_else(|| anyhow::anyhow!("mock agent is not reachable"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cr_core::diff::DiffData;
+    use cr_core::session::{DiffSource, Session};
+
+    fn claude_info() -> AgentInfo {
+        AgentInfo {
+            agent_type: AgentType::ClaudeCode,
+            name: "Claude Code".to_string(),
+            version: Some("1.0.0".to_string()),
+            project_dir: None,
+            global_dir: None,
+            confidence: 100,
+        }
+    }
+
+    #[test]
+    fn test_detection() {
+        let adapter = MockAgentAdapter::new();
+        assert!(!adapter.is_installed());
+
+        let adapter = MockAgentAdapter::new().with_detection(Some(claude_info()));
+        assert!(adapter.is_installed());
+        assert_eq!(adapter.agent_type(), AgentType::ClaudeCode);
+        assert_eq!(adapter.calls(), vec![MockCall::Detect]);
+    }
+
+    #[test]
+    fn test_export_records_calls() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("review.md");
+        let session = Session::new(DiffSource::WorkingTree, DiffData::empty());
+
+        let adapter = MockAgentAdapter::new().with_format_context("# Review");
+        let log = adapter.call_log();
+
+        adapter.export_to_file(&session, &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Review");
+        assert_eq!(
+            log.lock().unwrap().as_slice(),
+            &[MockCall::ExportToFile(session.id.clone(), path)]
+        );
+    }
+
+    #[test]
+    fn test_export_error() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let session = Session::new(DiffSource::WorkingTree, DiffData::empty());
+
+        let adapter = MockAgentAdapter::new().with_export_error(anyhow::anyhow!("disk full"));
+        let err = adapter
+            .export_to_file(&session, &temp.path().join("out.md"))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+    }
+
+    #[test]
+    fn test_ping() {
+        let adapter = MockAgentAdapter::new();
+        assert!(adapter.ping().is_err());
+
+        let adapter = MockAgentAdapter::new().with_ping_latency(Duration::from_millis(45));
+        assert_eq!(adapter.ping().unwrap(), Duration::from_millis(45));
+        assert_eq!(adapter.calls(), vec![MockCall::Ping]);
+    }
+}
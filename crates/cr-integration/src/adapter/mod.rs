@@ -3,19 +3,47 @@
 //! Provides traits and implementations for integrating with various Agent CLIs.
 
 pub mod claude_code;
+pub mod github;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 /// Agent type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AgentType {
     /// Claude Code
     ClaudeCode,
+    /// Cursor
+    Cursor,
+    /// GitHub Copilot
+    Copilot,
+    /// Windsurf
+    Windsurf,
+    /// Aider
+    Aider,
     /// Other/Unknown agent
     Other,
 }
 
+impl AgentType {
+    /// Human-readable agent name
+    pub fn name(&self) -> &'static str {
+        match self {
+            AgentType::ClaudeCode => "Claude Code",
+            AgentType::Cursor => "Cursor",
+            AgentType::Copilot => "GitHub Copilot",
+            AgentType::Windsurf => "Windsurf",
+            AgentType::Aider => "Aider",
+            AgentType::Other => "Other",
+        }
+    }
+}
+
 /// Information about a detected agent
 #[derive(Debug, Clone)]
 pub struct AgentInfo {
@@ -29,6 +57,26 @@ pub struct AgentInfo {
     pub project_dir: Option<std::path::PathBuf>,
     /// Global config directory
     pub global_dir: Option<std::path::PathBuf>,
+    /// Confidence score, summed from the matching [`DetectionSignal`](crate::DetectionSignal)s
+    pub confidence: u32,
+}
+
+/// A review in progress, as recorded in its lock file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentSession {
+    /// Process ID of the running `cr-helper review`
+    pub pid: u32,
+    /// Project being reviewed
+    pub project_path: PathBuf,
+    /// When the review started
+    pub started_at: DateTime<Utc>,
+}
+
+impl AgentSession {
+    /// Check whether the review process is still running
+    pub fn is_alive(&self) -> bool {
+        crate::lock::process_alive(self.pid)
+    }
 }
 
 /// Trait for agent adapters
@@ -52,6 +100,16 @@ pub trait AgentAdapter: Send + Sync {
 
     /// Get the settings path for this agent
     fn settings_path(&self, scope: InstallScope) -> Option<std::path::PathBuf>;
+
+    /// Reviews currently running for this agent's project
+    fn list_running_sessions(&self) -> Result<Vec<AgentSession>> {
+        Ok(Vec::new())
+    }
+
+    /// Time a trivial round trip to the agent, for health checks
+    fn ping(&self) -> Result<Duration> {
+        anyhow::bail!("{} does not support ping", self.agent_type().name())
+    }
 }
 
 /// Installation scope
@@ -83,6 +141,7 @@ mod tests {
             version: Some("1.0.0".to_string()),
             project_dir: None,
             global_dir: None,
+            confidence: 100,
         };
         assert_eq!(info.agent_type, AgentType::ClaudeCode);
         assert!(info.version.is_some());
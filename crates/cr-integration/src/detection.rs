@@ -1,10 +1,16 @@
 //! Agent detection utilities
 //!
-//! Utilities for detecting installed Agent CLIs.
+//! Utilities for detecting installed Agent CLIs and the project's VCS, and
+//! for reading the project's git history.
 
 use crate::adapter::{AgentAdapter, AgentInfo, AgentType};
-use crate::adapter::claude_code::ClaudeCodeAdapter;
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// `git log --format` for [`CommitInfo`]: fields split by the unit separator
+const COMMIT_FORMAT: &str = "--format=%H%x1f%h%x1f%an%x1f%aI%x1f%s";
 
 /// Detected agents result
 pub struct DetectedAgents {
@@ -27,33 +33,486 @@ impl DetectedAgents {
     pub fn has_agent(&self, agent_type: AgentType) -> bool {
         self.agents.iter().any(|a| a.agent_type == agent_type)
     }
+
+    /// Agent types with their confidence scores, in detection order
+    pub fn ranked(&self) -> Vec<(AgentType, u32)> {
+        self.agents.iter().map(|a| (a.agent_type, a.confidence)).collect()
+    }
 }
 
-/// Detect all installed agents
+/// Detect the agents used in the current directory
+///
+/// See [`detect_agents_in`].
 pub fn detect_agents() -> Result<DetectedAgents> {
-    let mut agents = Vec::new();
+    detect_agents_in(Path::new("."))
+}
+
+/// Detect the agents used in `dir`, highest confidence first
+///
+/// Agents are scored by the [`DetectionSignal`]s found in `dir`, so a
+/// project set up for several agents lists each of them. A global
+/// `~/.claude` install also counts for Claude Code.
+pub fn detect_agents_in(dir: &Path) -> Result<DetectedAgents> {
+    let global_claude_dir = dirs::home_dir().map(|h| h.join(".claude"));
+    Ok(detect_agents_from(dir, global_claude_dir.as_deref()))
+}
+
+/// Confidence for a global Claude Code install with no project `.claude/`
+const GLOBAL_CLAUDE_CONFIDENCE: u32 = 50;
 
-    // Try Claude Code
-    let claude_adapter = ClaudeCodeAdapter::new();
-    if let Ok(Some(info)) = claude_adapter.detect() {
-        agents.push(info);
+fn detect_agents_from(dir: &Path, global_claude_dir: Option<&Path>) -> DetectedAgents {
+    let mut agents: Vec<AgentInfo> = AgentType::from_detection_signals(&DetectionSignal::scan(dir))
+        .into_iter()
+        .map(|(agent_type, confidence)| AgentInfo {
+            agent_type,
+            name: agent_type.name().to_string(),
+            version: None,
+            project_dir: Some(dir.to_path_buf()),
+            global_dir: None,
+            confidence,
+        })
+        .collect();
+
+    if let Some(global) = global_claude_dir.filter(|d| d.is_dir()) {
+        match agents.iter_mut().find(|a| a.agent_type == AgentType::ClaudeCode) {
+            Some(claude) => claude.global_dir = Some(global.to_path_buf()),
+            None => {
+                agents.push(AgentInfo {
+                    agent_type: AgentType::ClaudeCode,
+                    name: AgentType::ClaudeCode.name().to_string(),
+                    version: None,
+                    project_dir: None,
+                    global_dir: Some(global.to_path_buf()),
+                    confidence: GLOBAL_CLAUDE_CONFIDENCE,
+                });
+                agents.sort_by_key(|a| std::cmp::Reverse(a.confidence));
+            }
+        }
     }
 
-    // Add more agent detectors here as they are implemented
+    DetectedAgents { agents }
+}
+
+/// Detect agents using the given adapters
+pub fn detect_agents_with(adapters: &[&dyn AgentAdapter]) -> Result<DetectedAgents> {
+    let agents = adapters
+        .iter()
+        .filter_map(|adapter| adapter.detect().ok().flatten())
+        .collect();
 
     Ok(DetectedAgents { agents })
 }
 
+/// A project-level hint that an agent is in use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectionSignal {
+    /// `.claude/` directory
+    ClaudeDir,
+    /// `.cursor/` directory or `.cursorrules`
+    CursorDir,
+    /// `.github/copilot-instructions.md`
+    CopilotConfig,
+    /// `.windsurf/` directory or `.windsurfrules`
+    WindsurfDir,
+    /// `.aider.conf.yml`
+    AiderConfig,
+}
+
+impl DetectionSignal {
+    /// Collect the signals present in `dir`
+    pub fn scan(dir: &Path) -> Vec<DetectionSignal> {
+        let checks = [
+            (DetectionSignal::ClaudeDir, dir.join(".claude").is_dir()),
+            (
+                DetectionSignal::CursorDir,
+                dir.join(".cursor").is_dir() || dir.join(".cursorrules").is_file(),
+            ),
+            (
+                DetectionSignal::CopilotConfig,
+                dir.join(".github/copilot-instructions.md").is_file(),
+            ),
+            (
+                DetectionSignal::WindsurfDir,
+                dir.join(".windsurf").is_dir() || dir.join(".windsurfrules").is_file(),
+            ),
+            (DetectionSignal::AiderConfig, dir.join(".aider.conf.yml").is_file()),
+        ];
+        checks
+            .into_iter()
+            .filter(|(_, present)| *present)
+            .map(|(signal, _)| signal)
+            .collect()
+    }
+
+    /// The agent this signal points at and how strongly (0-100)
+    fn weight(&self) -> (AgentType, u32) {
+        match self {
+            DetectionSignal::ClaudeDir => (AgentType::ClaudeCode, 100),
+            DetectionSignal::CursorDir => (AgentType::Cursor, 100),
+            // Instructions are often committed for the whole team, so weaker
+            DetectionSignal::CopilotConfig => (AgentType::Copilot, 60),
+            DetectionSignal::WindsurfDir => (AgentType::Windsurf, 100),
+            DetectionSignal::AiderConfig => (AgentType::Aider, 80),
+        }
+    }
+}
+
+impl AgentType {
+    /// Score agents by the signals found, highest confidence first
+    pub fn from_detection_signals(signals: &[DetectionSignal]) -> Vec<(AgentType, u32)> {
+        let mut scores: Vec<(AgentType, u32)> = Vec::new();
+        for signal in signals {
+            let (agent, weight) = signal.weight();
+            match scores.iter_mut().find(|(a, _)| *a == agent) {
+                Some((_, score)) => *score += weight,
+                None => scores.push((agent, weight)),
+            }
+        }
+        scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+        scores
+    }
+}
+
+/// Detects Mercurial working copies (a `.hg` directory)
+pub struct HgDetector;
+
+impl HgDetector {
+    /// Find the Mercurial repository root containing `dir`
+    pub fn find_root(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .find(|d| d.join(".hg").is_dir())
+            .map(Path::to_path_buf)
+    }
+
+    /// Check whether `dir` is inside a Mercurial repository
+    pub fn is_hg_repo(dir: &Path) -> bool {
+        Self::find_root(dir).is_some()
+    }
+}
+
+/// Detects Subversion working copies (a `.svn` directory)
+pub struct SvnDetector;
+
+impl SvnDetector {
+    /// Find the Subversion working copy root containing `dir`
+    pub fn find_root(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .find(|d| d.join(".svn").is_dir())
+            .map(Path::to_path_buf)
+    }
+
+    /// Check whether `dir` is inside a Subversion working copy
+    pub fn is_svn_working_copy(dir: &Path) -> bool {
+        Self::find_root(dir).is_some()
+    }
+}
+
+/// A commit from [`GitLog::fetch`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitInfo {
+    /// Full commit hash
+    pub sha: String,
+    /// Abbreviated commit hash
+    pub short_sha: String,
+    /// First line of the commit message
+    pub subject: String,
+    /// Author name
+    pub author: String,
+    /// Author date
+    pub date: DateTime<Utc>,
+}
+
+/// Reads commit history with the `git` CLI
+pub struct GitLog;
+
+impl GitLog {
+    /// Up to `count` commits around `around_ref` in the current directory's
+    /// repository, newest first
+    ///
+    /// See [`fetch_in`](Self::fetch_in).
+    pub fn fetch(around_ref: &str, count: usize) -> Result<Vec<CommitInfo>> {
+        Self::fetch_in(Path::new("."), around_ref, count)
+    }
+
+    /// Up to `count` commits around `around_ref` in the repository at `dir`,
+    /// newest first
+    ///
+    /// The commit is centered: up to half the commits are its descendants
+    /// on the way to `HEAD`, the rest are the commit and its ancestors.
+    /// Either side takes up what the other lacks.
+    pub fn fetch_in(dir: &Path, around_ref: &str, count: usize) -> Result<Vec<CommitInfo>> {
+        let sha = Self::resolve_in(dir, around_ref)?;
+        let older = parse_commits(&git(dir, &["log", COMMIT_FORMAT, &format!("-{}", count), &sha])?)?;
+        // Zero when `around_ref` is not an ancestor of HEAD
+        let range = format!("{}..HEAD", sha);
+        let newer_total: usize = git(dir, &["rev-list", "--count", "--ancestry-path", &range])?
+            .trim()
+            .parse()
+            .context("Unexpected git rev-list --count output")?;
+
+        // Only the descendants closest to the commit, which `git log` lists last
+        let newer_count = newer_total.min((count / 2).max(count.saturating_sub(older.len())));
+        let mut commits = if newer_count == 0 {
+            Vec::new()
+        } else {
+            let skip = format!("--skip={}", newer_total - newer_count);
+            let limit = format!("-{}", newer_count);
+            parse_commits(&git(dir, &["log", COMMIT_FORMAT, "--ancestry-path", &skip, &limit, &range])?)?
+        };
+        commits.extend(older.into_iter().take(count - newer_count));
+        Ok(commits)
+    }
+
+    /// Full hash of the commit `rev` names in the current directory's repository
+    pub fn resolve(rev: &str) -> Result<String> {
+        Self::resolve_in(Path::new("."), rev)
+    }
+
+    /// Full hash of the commit `rev` names in the repository at `dir`
+    pub fn resolve_in(dir: &Path, rev: &str) -> Result<String> {
+        let out = git(dir, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", rev)])
+            .context(format!("Unknown commit '{}'", rev))?;
+        Ok(out.trim().to_string())
+    }
+
+    /// Contents of `path` at `rev` in the repository at `dir`
+    ///
+    /// An empty `rev` reads the index (`git cat-file blob :path`).
+    pub fn blob_in(dir: &Path, rev: &str, path: &Path) -> Result<Vec<u8>> {
+        let spec = format!("{}:{}", rev, path.to_string_lossy());
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["cat-file", "blob", &spec])
+            .output()
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            bail!(
+                "git cat-file blob {} failed: {}",
+                spec,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(output.stdout)
+    }
+
+    /// `git show --stat --patch` output for `sha` in the current directory's repository
+    pub fn show(sha: &str) -> Result<String> {
+        git(Path::new("."), &["show", "--stat", "--patch", "--no-color", sha])
+    }
+}
+
+/// Run `git` in `dir`, returning its stdout
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse `git log` output in [`COMMIT_FORMAT`]
+fn parse_commits(output: &str) -> Result<Vec<CommitInfo>> {
+    output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.splitn(5, '\x1f').collect();
+            let [sha, short_sha, author, date, subject] = fields[..] else {
+                bail!("Unexpected git log line: {}", line);
+            };
+            Ok(CommitInfo {
+                sha: sha.to_string(),
+                short_sha: short_sha.to_string(),
+                subject: subject.to_string(),
+                author: author.to_string(),
+                date: DateTime::parse_from_rfc3339(date)
+                    .context(format!("Bad commit date '{}'", date))?
+                    .with_timezone(&Utc),
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::adapter::mock::{MockAgentAdapter, MockCall};
+
+    fn claude_info() -> AgentInfo {
+        AgentInfo {
+            agent_type: AgentType::ClaudeCode,
+            name: "Claude Code".to_string(),
+            version: None,
+            project_dir: None,
+            global_dir: None,
+            confidence: 100,
+        }
+    }
+
     #[test]
     fn test_detect_agents() {
         let result = detect_agents();
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_detect_agents_with_installed() {
+        let installed = MockAgentAdapter::new().with_detection(Some(claude_info()));
+        let missing = MockAgentAdapter::new();
+
+        let detected = detect_agents_with(&[&installed, &missing]).unwrap();
+        assert_eq!(detected.agents.len(), 1);
+        assert!(detected.has_agent(AgentType::ClaudeCode));
+        assert_eq!(installed.calls(), vec![MockCall::Detect]);
+        assert_eq!(missing.calls(), vec![MockCall::Detect]);
+    }
+
+    #[test]
+    fn test_detect_agents_with_none_installed() {
+        let missing = MockAgentAdapter::new();
+        let detected = detect_agents_with(&[&missing]).unwrap();
+        assert!(detected.is_empty());
+        assert!(detected.first().is_none());
+    }
+
+    #[test]
+    fn test_from_detection_signals() {
+        let ranked = AgentType::from_detection_signals(&[
+            DetectionSignal::CopilotConfig,
+            DetectionSignal::ClaudeDir,
+        ]);
+        assert_eq!(
+            ranked,
+            vec![(AgentType::ClaudeCode, 100), (AgentType::Copilot, 60)]
+        );
+        assert!(AgentType::from_detection_signals(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_detect_agents_in() {
+        let temp = tempfile::TempDir::new().unwrap();
+        assert!(detect_agents_from(temp.path(), None).is_empty());
+
+        std::fs::create_dir(temp.path().join(".claude")).unwrap();
+        std::fs::write(temp.path().join(".aider.conf.yml"), "model: x\n").unwrap();
+        let detected = detect_agents_from(temp.path(), None);
+        assert_eq!(detected.first().unwrap().name, "Claude Code");
+        assert_eq!(
+            detected.ranked(),
+            vec![(AgentType::ClaudeCode, 100), (AgentType::Aider, 80)]
+        );
+    }
+
+    #[test]
+    fn test_detect_agents_global_claude_install() {
+        let project = tempfile::TempDir::new().unwrap();
+        let home = tempfile::TempDir::new().unwrap();
+        let global = home.path().join(".claude");
+
+        // Missing global directory is ignored
+        assert!(detect_agents_from(project.path(), Some(&global)).is_empty());
+
+        std::fs::create_dir(&global).unwrap();
+        std::fs::write(project.path().join(".aider.conf.yml"), "model: x\n").unwrap();
+        let detected = detect_agents_from(project.path(), Some(&global));
+        assert_eq!(
+            detected.ranked(),
+            vec![(AgentType::Aider, 80), (AgentType::ClaudeCode, 50)]
+        );
+        let claude = &detected.agents[1];
+        assert_eq!(claude.project_dir, None);
+        assert_eq!(claude.global_dir.as_deref(), Some(global.as_path()));
+
+        // A project .claude/ keeps full confidence and records the global install
+        std::fs::create_dir(project.path().join(".claude")).unwrap();
+        let detected = detect_agents_from(project.path(), Some(&global));
+        assert_eq!(detected.ranked()[0], (AgentType::ClaudeCode, 100));
+        assert_eq!(detected.first().unwrap().global_dir.as_deref(), Some(global.as_path()));
+    }
+
+    #[test]
+    fn test_hg_detector() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let nested = temp.path().join("repo/src");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert!(!HgDetector::is_hg_repo(&nested));
+
+        std::fs::create_dir(temp.path().join("repo/.hg")).unwrap();
+        assert_eq!(
+            HgDetector::find_root(&nested),
+            Some(temp.path().join("repo"))
+        );
+    }
+
+    #[test]
+    fn test_svn_detector() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let nested = temp.path().join("wc/trunk/src");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert!(!SvnDetector::is_svn_working_copy(&nested));
+
+        std::fs::create_dir(temp.path().join("wc/.svn")).unwrap();
+        assert_eq!(SvnDetector::find_root(&nested), Some(temp.path().join("wc")));
+    }
+
+    #[test]
+    fn test_git_log_fetch() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let run = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(temp.path())
+                .args(["-c", "user.name=Dev", "-c", "user.email=dev@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        run(&["init", "-q"]);
+        for i in 1..=5 {
+            run(&["commit", "-q", "--allow-empty", "-m", &format!("Commit {}", i)]);
+        }
+
+        let subjects = |commits: Vec<CommitInfo>| -> Vec<String> {
+            commits.into_iter().map(|c| c.subject).collect()
+        };
+        let around = GitLog::fetch_in(temp.path(), "HEAD~2", 4).unwrap();
+        assert_eq!(around[2].sha, GitLog::resolve_in(temp.path(), "HEAD~2").unwrap());
+        assert_eq!(around[2].author, "Dev");
+        assert!(around[2].sha.starts_with(&around[2].short_sha));
+        assert_eq!(subjects(around), ["Commit 5", "Commit 4", "Commit 3", "Commit 2"]);
+
+        // Nothing newer than HEAD, and only five commits in all
+        let head = GitLog::fetch_in(temp.path(), "HEAD", 10).unwrap();
+        assert_eq!(subjects(head), ["Commit 5", "Commit 4", "Commit 3", "Commit 2", "Commit 1"]);
+        let root = GitLog::fetch_in(temp.path(), "HEAD~4", 3).unwrap();
+        assert_eq!(subjects(root), ["Commit 3", "Commit 2", "Commit 1"]);
+
+        assert!(GitLog::fetch_in(temp.path(), "no-such-ref", 10).is_err());
+
+        std::fs::write(temp.path().join("a.bin"), b"\x00one").unwrap();
+        run(&["add", "a.bin"]);
+        run(&["commit", "-q", "-m", "Add a.bin"]);
+        std::fs::write(temp.path().join("a.bin"), b"\x00two").unwrap();
+        run(&["add", "a.bin"]);
+        let blob = |rev: &str| GitLog::blob_in(temp.path(), rev, Path::new("a.bin"));
+        assert_eq!(blob("HEAD").unwrap(), b"\x00one");
+        assert_eq!(blob("").unwrap(), b"\x00two");
+        assert!(blob("HEAD~1").is_err());
+    }
+
     #[test]
     fn test_detected_agents_is_empty() {
         let detected = DetectedAgents { agents: vec![] };
@@ -63,13 +522,7 @@ mod tests {
     #[test]
     fn test_detected_agents_has_agent() {
         let detected = DetectedAgents {
-            agents: vec![AgentInfo {
-                agent_type: AgentType::ClaudeCode,
-                name: "Claude Code".to_string(),
-                version: None,
-                project_dir: None,
-                global_dir: None,
-            }],
+            agents: vec![claude_info()],
         };
         assert!(detected.has_agent(AgentType::ClaudeCode));
         assert!(!detected.has_agent(AgentType::Other));
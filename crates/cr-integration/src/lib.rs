@@ -7,6 +7,7 @@
 //! - Agent adapter trait for extensibility
 //! - Claude Code adapter implementation
 //! - Installation and verification utilities
+//! - `MockAgentAdapter` for tests (behind the `mock` feature)
 //!
 //! ## Usage
 //!
@@ -21,9 +22,17 @@
 
 pub mod adapter;
 pub mod detection;
+pub mod lock;
 pub mod verification;
 
-pub use adapter::{AgentAdapter, AgentInfo, AgentType};
-pub use adapter::claude_code::ClaudeCodeAdapter;
-pub use detection::detect_agents;
+pub use adapter::{AgentAdapter, AgentInfo, AgentSession, AgentType};
+pub use adapter::claude_code::{ClaudeCodeAdapter, REVIEW_CONTEXT_FILE};
+pub use adapter::github::GitHubAdapter;
+#[cfg(any(test, feature = "mock"))]
+pub use adapter::mock::{MockAgentAdapter, MockCall};
+pub use detection::{
+    detect_agents, detect_agents_in, detect_agents_with, CommitInfo, DetectionSignal, GitLog, HgDetector,
+    SvnDetector,
+};
+pub use lock::{LockFile, LOCK_FILE};
 pub use verification::VerificationResult;
@@ -0,0 +1,167 @@
+//! Review lock file
+//!
+//! `cr-helper review` writes `.claude/cr-helper.lock` while the TUI is open so
+//! agents can tell a review is in progress.
+
+use crate::adapter::AgentSession;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Lock file path relative to the project directory
+pub const LOCK_FILE: &str = ".claude/cr-helper.lock";
+
+/// A held review lock, removed on drop
+#[derive(Debug)]
+pub struct LockFile {
+    path: PathBuf,
+    pid: u32,
+    /// `.claude/` was created for the lock and is removed with it if empty
+    created_dir: Option<PathBuf>,
+}
+
+impl LockFile {
+    /// Write a lock for the current process in `project_dir`
+    ///
+    /// An existing lock is replaced; it belongs to a review that exited
+    /// without cleaning up or to a concurrent review in the same project.
+    pub fn acquire(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(LOCK_FILE);
+        let dir = path.parent().unwrap_or(project_dir).to_path_buf();
+        let created_dir = if dir.exists() {
+            None
+        } else {
+            fs::create_dir_all(&dir)?;
+            Some(dir)
+        };
+
+        let session = AgentSession {
+            pid: std::process::id(),
+            project_path: project_dir
+                .canonicalize()
+                .unwrap_or_else(|_| project_dir.to_path_buf()),
+            started_at: Utc::now(),
+        };
+        if let Some(existing) = Self::read(project_dir)? {
+            tracing::warn!("Replacing review lock held by pid {}", existing.pid);
+        }
+        fs::write(&path, serde_json::to_string_pretty(&session)?)
+            .context(format!("Failed to write {}", path.display()))?;
+
+        Ok(Self {
+            path,
+            pid: session.pid,
+            created_dir,
+        })
+    }
+
+    /// Read the lock in `project_dir`, if any
+    pub fn read(project_dir: &Path) -> Result<Option<AgentSession>> {
+        let path = project_dir.join(LOCK_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)?;
+        let session = serde_json::from_str(&content)
+            .context(format!("Invalid lock file {}", path.display()))?;
+        Ok(Some(session))
+    }
+
+    /// Remove the lock in `project_dir` if its process has exited
+    ///
+    /// Returns the stale entry that was removed.
+    pub fn remove_stale(project_dir: &Path) -> Result<Option<AgentSession>> {
+        match Self::read(project_dir)? {
+            Some(session) if !session.is_alive() => {
+                fs::remove_file(project_dir.join(LOCK_FILE))?;
+                Ok(Some(session))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Path of the lock file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        // Leave a lock that a later review has taken over
+        let ours = fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|c| serde_json::from_str::<AgentSession>(&c).ok())
+            .is_some_and(|s| s.pid == self.pid);
+        if ours {
+            let _ = fs::remove_file(&self.path);
+        }
+        if let Some(dir) = &self.created_dir {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+/// Check whether a process is still running
+pub(crate) fn process_alive(pid: u32) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        Path::new("/proc").join(pid.to_string()).exists()
+    }
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(true)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_acquire_and_drop() {
+        let dir = TempDir::new().unwrap();
+
+        let lock = LockFile::acquire(dir.path()).unwrap();
+        assert!(lock.path().exists());
+        let session = LockFile::read(dir.path()).unwrap().unwrap();
+        assert_eq!(session.pid, std::process::id());
+        assert!(session.is_alive());
+
+        drop(lock);
+        assert!(LockFile::read(dir.path()).unwrap().is_none());
+        assert!(!dir.path().join(".claude").exists());
+    }
+
+    #[test]
+    fn test_remove_stale() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join(".claude")).unwrap();
+        let stale = AgentSession {
+            pid: u32::MAX,
+            project_path: dir.path().to_path_buf(),
+            started_at: Utc::now(),
+        };
+        fs::write(
+            dir.path().join(LOCK_FILE),
+            serde_json::to_string(&stale).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(LockFile::remove_stale(dir.path()).unwrap(), Some(stale));
+        assert!(LockFile::read(dir.path()).unwrap().is_none());
+        assert!(dir.path().join(".claude").exists());
+    }
+}
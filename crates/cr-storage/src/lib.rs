@@ -2,6 +2,124 @@
 //!
 //! This crate provides storage implementations for sessions and other data.
 
+mod project;
 mod session_store;
 
+pub use project::{ProjectEntry, ProjectIndex, StorageKey};
 pub use session_store::FileSystemStorage;
+
+use cr_core::config::WorkspaceConfig;
+use cr_core::error::Result;
+use cr_core::session::{SessionManager, SessionStorage};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Session storages for named workspaces
+#[derive(Default, Clone)]
+pub struct StorageRegistry {
+    storages: HashMap<String, Arc<dyn SessionStorage>>,
+}
+
+impl StorageRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the storage of every configured workspace
+    ///
+    /// Workspaces whose path does not exist are skipped.
+    pub fn from_workspaces(workspaces: &[WorkspaceConfig]) -> Result<Self> {
+        let index = ProjectIndex::load(&ProjectIndex::default_path())?;
+        let mut registry = Self::new();
+        for workspace in workspaces {
+            if !workspace.path.is_dir() {
+                tracing::debug!(
+                    "Workspace '{}' not found at {:?}",
+                    workspace.name,
+                    workspace.path
+                );
+                continue;
+            }
+            let storage = storage_for_dir(&index, &workspace.path)?;
+            registry.register(&workspace.name, Arc::new(storage));
+        }
+        Ok(registry)
+    }
+
+    /// Add or replace a workspace's storage
+    pub fn register(&mut self, name: impl Into<String>, storage: Arc<dyn SessionStorage>) {
+        self.storages.insert(name.into(), storage);
+    }
+
+    /// Get a workspace's storage
+    pub fn get(&self, name: &str) -> Option<Arc<dyn SessionStorage>> {
+        self.storages.get(name).cloned()
+    }
+
+    /// Registered workspace names, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.storages.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Session manager for a workspace, or for the current directory if `None`
+    pub fn manager(&self, name: Option<&str>) -> Result<SessionManager> {
+        match name {
+            Some(name) => self
+                .get(name)
+                .map(SessionManager::with_storage)
+                .ok_or_else(|| {
+                    cr_core::CrHelperError::Config(format!("Unknown workspace: {}", name))
+                }),
+            None => Ok(SessionManager::new(FileSystemStorage::new(
+                ".cr-helper/sessions",
+            )?)),
+        }
+    }
+}
+
+/// Storage for a repository: its registered project storage, or its local
+/// `.cr-helper/sessions` directory
+fn storage_for_dir(index: &ProjectIndex, dir: &Path) -> Result<FileSystemStorage> {
+    match index.find_containing(dir) {
+        Some(entry) => FileSystemStorage::for_project(&entry.path),
+        None => FileSystemStorage::new(dir.join(".cr-helper/sessions")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cr_core::diff::DiffData;
+    use cr_core::session::DiffSource;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_storage_registry() {
+        let backend = TempDir::new().unwrap();
+        let workspaces = vec![
+            WorkspaceConfig {
+                name: "backend".to_string(),
+                path: backend.path().to_path_buf(),
+            },
+            WorkspaceConfig {
+                name: "missing".to_string(),
+                path: backend.path().join("nope"),
+            },
+        ];
+
+        let registry = StorageRegistry::from_workspaces(&workspaces).unwrap();
+        assert_eq!(registry.names(), vec!["backend"]);
+
+        let manager = registry.manager(Some("backend")).unwrap();
+        manager
+            .create(DiffSource::WorkingTree, DiffData::empty())
+            .unwrap();
+        assert_eq!(manager.count().unwrap(), 1);
+        assert!(backend.path().join(".cr-helper/sessions").exists());
+        assert!(registry.manager(Some("missing")).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! Per-project storage keys and the project index
+
+use cr_core::error::{CrHelperError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Identifies an isolated storage root for a project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageKey {
+    /// Human-readable name (the project directory name)
+    pub name: String,
+    /// Hash of the canonical project path
+    pub hash: String,
+}
+
+impl StorageKey {
+    /// Compute the key for a project directory
+    pub fn for_path(project_path: &Path) -> Result<Self> {
+        let canonical = project_path.canonicalize().map_err(|e| {
+            CrHelperError::Io(std::io::Error::new(
+                e.kind(),
+                format!("Failed to resolve project path {:?}: {}", project_path, e),
+            ))
+        })?;
+
+        let name = canonical
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "root".to_string())
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        let hash = blake3::hash(canonical.to_string_lossy().as_bytes())
+            .to_hex()
+            .chars()
+            .take(16)
+            .collect();
+
+        Ok(Self { name, hash })
+    }
+
+    /// Directory name used under `projects/`
+    pub fn dir_name(&self) -> String {
+        format!("{}-{}", self.name, self.hash)
+    }
+}
+
+impl std::fmt::Display for StorageKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.dir_name())
+    }
+}
+
+/// A registered project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectEntry {
+    /// Canonical project path
+    pub path: PathBuf,
+    /// Storage key for the project
+    pub key: StorageKey,
+}
+
+/// Index of registered projects (`~/.cr-helper/projects.toml`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectIndex {
+    /// Registered projects
+    #[serde(default)]
+    pub projects: Vec<ProjectEntry>,
+}
+
+impl ProjectIndex {
+    /// Root directory for global cr-helper data (~/.cr-helper)
+    pub fn root_dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".cr-helper")
+    }
+
+    /// Default index file path
+    pub fn default_path() -> PathBuf {
+        Self::root_dir().join("projects.toml")
+    }
+
+    /// Load the index from a file (empty if it doesn't exist)
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content).map_err(|e| CrHelperError::Toml(e.to_string()))
+    }
+
+    /// Save the index to a file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content =
+            toml::to_string_pretty(self).map_err(|e| CrHelperError::Toml(e.to_string()))?;
+        fs::write(path, content)?;
+        debug!("Saved project index to {:?}", path);
+        Ok(())
+    }
+
+    /// Register a project, returning its entry (idempotent)
+    pub fn register(&mut self, project_path: &Path) -> Result<ProjectEntry> {
+        let path = project_path.canonicalize()?;
+        if let Some(entry) = self.projects.iter().find(|p| p.path == path) {
+            return Ok(entry.clone());
+        }
+
+        let entry = ProjectEntry {
+            key: StorageKey::for_path(&path)?,
+            path,
+        };
+        self.projects.push(entry.clone());
+        Ok(entry)
+    }
+
+    /// Find the registered project containing a directory
+    ///
+    /// The deepest match wins so nested repositories in a monorepo resolve
+    /// to their own project.
+    pub fn find_containing(&self, dir: &Path) -> Option<&ProjectEntry> {
+        let dir = dir.canonicalize().ok()?;
+        self.projects
+            .iter()
+            .filter(|p| dir.starts_with(&p.path))
+            .max_by_key(|p| p.path.components().count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_storage_key_is_stable() {
+        let temp = TempDir::new().unwrap();
+        let key1 = StorageKey::for_path(temp.path()).unwrap();
+        let key2 = StorageKey::for_path(temp.path()).unwrap();
+        assert_eq!(key1, key2);
+        assert_eq!(key1.hash.len(), 16);
+        assert!(key1.dir_name().ends_with(&key1.hash));
+    }
+
+    #[test]
+    fn test_storage_key_differs_per_path() {
+        let temp = TempDir::new().unwrap();
+        let a = temp.path().join("a");
+        let b = temp.path().join("b");
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        assert_ne!(
+            StorageKey::for_path(&a).unwrap(),
+            StorageKey::for_path(&b).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_index_register_and_find() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("mono");
+        let nested = repo.join("services/api");
+        fs::create_dir_all(&nested).unwrap();
+
+        let mut index = ProjectIndex::default();
+        let outer = index.register(&repo).unwrap();
+        let inner = index.register(&nested).unwrap();
+        index.register(&repo).unwrap();
+        assert_eq!(index.projects.len(), 2);
+
+        assert_eq!(index.find_containing(&nested).unwrap().key, inner.key);
+        assert_eq!(
+            index.find_containing(&repo.join("services")).unwrap().key,
+            outer.key
+        );
+        assert!(index.find_containing(temp.path()).is_none());
+    }
+
+    #[test]
+    fn test_index_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let index_path = temp.path().join("projects.toml");
+
+        let mut index = ProjectIndex::default();
+        index.register(temp.path()).unwrap();
+        index.save(&index_path).unwrap();
+
+        let loaded = ProjectIndex::load(&index_path).unwrap();
+        assert_eq!(loaded.projects.len(), 1);
+        assert_eq!(loaded.projects[0].key, index.projects[0].key);
+    }
+}
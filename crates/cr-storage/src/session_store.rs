@@ -1,21 +1,41 @@
 //! File system storage for sessions
 
-use cr_core::error::{CrHelperError, Result};
+use crate::project::{ProjectIndex, StorageKey};
+use cr_core::config::StorageFormat;
+use cr_core::error::{CrHelperError, PathContext, Result};
 use cr_core::session::{
-    Session, SessionFile, SessionInfo, SessionMigrator, SessionStorage, CURRENT_SCHEMA_VERSION,
+    match_short_id, Session, SessionFile, SessionInfo, SessionMigrator, SessionSortOrder,
+    SessionStorage, SessionUpdate, SortDirection, SortField, WatchCallback, WatchHandle,
+    CURRENT_SCHEMA_VERSION, DIFF_HASH_KEY,
 };
 use cr_core::types::SessionId;
+use fs2::FileExt;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{BufReader, BufWriter, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime};
 use tracing::{debug, info, warn};
 
+/// Temp files older than this are assumed to be left by a killed process
+const ORPHANED_TEMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Unreferenced sidecars newer than this may belong to a save in progress
+const SIDECAR_GRACE: Duration = Duration::from_secs(60);
+
+/// Writes to the same session within this window are reported once
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
 /// File system based session storage
 pub struct FileSystemStorage {
     /// Base directory for session storage
     base_dir: PathBuf,
     /// Sessions subdirectory
     sessions_dir: PathBuf,
+    /// Format for sessions without a sidecar yet
+    format: StorageFormat,
 }
 
 impl FileSystemStorage {
@@ -27,9 +47,19 @@ impl FileSystemStorage {
         let storage = Self {
             base_dir,
             sessions_dir,
+            format: StorageFormat::default(),
         };
 
         storage.ensure_dirs()?;
+
+        // Clean up after interrupted writes without delaying startup
+        let sessions_dir = storage.sessions_dir.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = prune_temp_files(&sessions_dir, ORPHANED_TEMP_AGE) {
+                warn!("Failed to prune temp files in {:?}: {}", sessions_dir, e);
+            }
+        });
+
         Ok(storage)
     }
 
@@ -46,15 +76,29 @@ impl FileSystemStorage {
         Self::new(base_dir)
     }
 
+    /// Create isolated storage for a project (~/.cr-helper/projects/<key>)
+    pub fn for_project(project_path: &Path) -> Result<Self> {
+        Self::for_project_in(&ProjectIndex::root_dir(), project_path)
+    }
+
+    /// Create isolated storage for a project under a custom root
+    pub fn for_project_in(root: &Path, project_path: &Path) -> Result<Self> {
+        let key = StorageKey::for_path(project_path)?;
+        Self::new(root.join("projects").join(key.dir_name()))
+    }
+
+    /// Write new sessions in `format`
+    ///
+    /// Sessions already saved with a MessagePack sidecar keep using it.
+    pub fn with_format(mut self, format: StorageFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     /// Ensure required directories exist
     fn ensure_dirs(&self) -> Result<()> {
         if !self.sessions_dir.exists() {
-            fs::create_dir_all(&self.sessions_dir).map_err(|e| {
-                CrHelperError::Io(std::io::Error::new(
-                    e.kind(),
-                    format!("Failed to create sessions directory: {}", e),
-                ))
-            })?;
+            fs::create_dir_all(&self.sessions_dir).with_path(&self.sessions_dir)?;
             debug!("Created sessions directory: {:?}", self.sessions_dir);
         }
         Ok(())
@@ -70,39 +114,163 @@ impl FileSystemStorage {
         self.sessions_dir.join(format!(".{}.json.tmp", id))
     }
 
+    /// Get the path for the MessagePack sidecar of the diff with `hash`
+    fn sidecar_path(&self, hash: &str) -> PathBuf {
+        self.sessions_dir.join(SessionMigrator::sidecar_name(hash))
+    }
+
+    /// Get the path for a session's advisory lock file
+    fn lock_path(&self, id: &SessionId) -> PathBuf {
+        self.sessions_dir.join(format!("{}.lock", id))
+    }
+
+    /// Take an exclusive lock on a session, held until the file is dropped
+    fn lock(&self, id: &SessionId) -> Result<fs::File> {
+        let lock_path = self.lock_path(id);
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .with_path(&lock_path)?;
+        file.lock_exclusive().with_path(&lock_path)?;
+        Ok(file)
+    }
+
+    /// Read-modify-write a session while holding its lock
+    ///
+    /// Takes an exclusive `flock` on `<id>.lock`, loads the latest saved
+    /// session, and saves the session `f` returns before releasing the lock,
+    /// so writers in other processes can't overwrite each other's changes.
+    /// `f` must not save the session itself; that would wait on the lock.
+    pub fn with_lock<F, R>(&self, id: &SessionId, f: F) -> Result<R>
+    where
+        F: FnOnce(&Session) -> Result<(Session, R)>,
+    {
+        let _lock = self.lock(id)?;
+        let session = self.load(id)?;
+        let (session, result) = f(&session)?;
+        self.save_unlocked(&session)?;
+        Ok(result)
+    }
+
+    /// Save in the configured format without taking the session lock
+    fn save_unlocked(&self, session: &Session) -> Result<()> {
+        if self.format == StorageFormat::Msgpack
+            || session.diff_data.metadata.extensions.get(DIFF_HASH_KEY).is_some()
+        {
+            self.save_compact(session)
+        } else {
+            self.atomic_write(&session.id, session)
+        }
+    }
+
     /// Write session atomically (write to temp, then rename)
     fn atomic_write(&self, id: &SessionId, session: &Session) -> Result<()> {
-        let temp_path = self.temp_path(id);
-        let final_path = self.session_path(id);
-
         // Create session file with schema version
-        let file = SessionFile::new(session.clone());
-
-        // Write to temp file
-        let temp_file = fs::File::create(&temp_path).map_err(|e| {
-            CrHelperError::Io(std::io::Error::new(
-                e.kind(),
-                format!("Failed to create temp file: {}", e),
-            ))
-        })?;
-        let mut writer = BufWriter::new(temp_file);
-        serde_json::to_writer_pretty(&mut writer, &file)?;
-        writer.flush()?;
-
-        // Rename to final path (atomic on most filesystems)
-        fs::rename(&temp_path, &final_path).map_err(|e| {
-            // Clean up temp file on failure
-            let _ = fs::remove_file(&temp_path);
-            CrHelperError::Io(std::io::Error::new(
-                e.kind(),
-                format!("Failed to rename temp file: {}", e),
-            ))
+        self.write_session_file(id, &SessionFile::new(session.clone()))
+    }
+
+    /// Write a session file atomically
+    fn write_session_file(&self, id: &SessionId, file: &SessionFile) -> Result<()> {
+        let final_path = self.session_path(id);
+        write_atomically(&self.temp_path(id), &final_path, |writer| {
+            serde_json::to_writer_pretty(writer, file)?;
+            Ok(())
         })?;
 
         debug!("Saved session {} to {:?}", id, final_path);
         Ok(())
     }
 
+    /// Save as a schema v2 file whose diff lives in a `<diff_hash>.diff.msgpack` sidecar
+    ///
+    /// `<id>.json` keeps an empty placeholder diff holding the hash;
+    /// [`load`](SessionStorage::load) puts the real diff back. Sessions with
+    /// identical diffs share a sidecar, so a sidecar the session no longer
+    /// uses is only removed once no other session refers to it.
+    pub fn save_compact(&self, session: &Session) -> Result<()> {
+        let id = &session.id;
+        let previous = SessionMigrator::read_diff_hash(&self.session_path(id)).ok().flatten();
+        let (file, bytes) = SessionMigrator::migrate_to_v2(SessionFile::new(session.clone()))?;
+        let hash: String = file
+            .session
+            .diff_data
+            .metadata
+            .extensions
+            .get_as(DIFF_HASH_KEY)
+            .unwrap_or_default();
+
+        let sidecar_path = self.sidecar_path(&hash);
+        if !sidecar_path.exists() {
+            // Named after the session, whose lock we hold, so concurrent
+            // saves of the same diff don't share a temp file
+            let temp_path = self
+                .sessions_dir
+                .join(format!(".{}.{}.diff.msgpack.tmp", hash, id));
+            write_atomically(&temp_path, &sidecar_path, |writer| {
+                writer.write_all(&bytes).with_path(&temp_path)?;
+                Ok(())
+            })?;
+        }
+        self.write_session_file(id, &file)?;
+
+        if previous.is_some_and(|previous| previous != hash) {
+            self.prune_sidecars_logged();
+        }
+        Ok(())
+    }
+
+    /// Delete diff sidecars that no session file refers to
+    ///
+    /// Sidecars written in the last minute are kept, since their session
+    /// file may not have been written yet. Returns the number removed.
+    pub fn prune_sidecars(&self) -> Result<usize> {
+        let mut referenced = HashSet::new();
+        for path in self.session_files()? {
+            if let Some(hash) = SessionMigrator::read_diff_hash(&path)? {
+                referenced.insert(SessionMigrator::sidecar_name(&hash));
+            }
+        }
+
+        let now = SystemTime::now();
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.sessions_dir).with_path(&self.sessions_dir)? {
+            let path = entry.with_path(&self.sessions_dir)?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if name.starts_with('.') || !name.ends_with(".diff.msgpack") || referenced.contains(name) {
+                continue;
+            }
+            let modified = match fs::metadata(&path).and_then(|m| m.modified()) {
+                Ok(modified) => modified,
+                Err(_) => continue,
+            };
+            if now.duration_since(modified).unwrap_or_default() < SIDECAR_GRACE {
+                continue;
+            }
+
+            match fs::remove_file(&path) {
+                Ok(()) => {
+                    debug!("Removed unreferenced sidecar {:?}", path);
+                    removed += 1;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(CrHelperError::from_io_with_path(e, &path)),
+            }
+        }
+        Ok(removed)
+    }
+
+    /// [`prune_sidecars`](Self::prune_sidecars) after a save or delete,
+    /// which has already succeeded, so failures are only logged
+    fn prune_sidecars_logged(&self) {
+        if let Err(e) = self.prune_sidecars() {
+            warn!("Failed to prune diff sidecars in {:?}: {}", self.sessions_dir, e);
+        }
+    }
+
     /// Read and parse a session file
     fn read_session(&self, path: &PathBuf) -> Result<Session> {
         let file = fs::File::open(path).map_err(|e| {
@@ -114,12 +282,18 @@ impl FileSystemStorage {
                     .unwrap_or("unknown");
                 CrHelperError::SessionNotFound(id.to_string())
             } else {
-                CrHelperError::Io(e)
+                CrHelperError::from_io_with_path(e, path)
             }
         })?;
 
         let reader = BufReader::new(file);
-        let session_file: SessionFile = serde_json::from_reader(reader)?;
+        let mut session_file: SessionFile = serde_json::from_reader(reader)?;
+
+        if session_file.is_v2() {
+            let dir = path.parent().unwrap_or(&self.sessions_dir);
+            SessionMigrator::load_v2_diff(&mut session_file, dir)?;
+            return Ok(session_file.into_session());
+        }
 
         // Migrate if needed
         let migrated = if SessionMigrator::needs_migration(&session_file) {
@@ -136,9 +310,53 @@ impl FileSystemStorage {
     }
 
     /// Read session info from a file (without loading full diff)
+    ///
+    /// Tries [`SessionInfo::from_json_fast`] first, which skips the diff's
+    /// file list, and falls back to loading the whole session.
     fn read_session_info(&self, path: &PathBuf) -> Result<SessionInfo> {
-        let session = self.read_session(path)?;
-        Ok(session.info())
+        match SessionInfo::from_json_fast(path) {
+            Ok(info) => Ok(info),
+            Err(e) => {
+                debug!("Fast session info failed for {:?}, loading it fully: {}", path, e);
+                let session = self.read_session(path)?;
+                Ok(session.info())
+            }
+        }
+    }
+
+    /// Paths of all session files, skipping temp and non-JSON files
+    fn session_files(&self) -> Result<Vec<PathBuf>> {
+        let entries = fs::read_dir(&self.sessions_dir).with_path(&self.sessions_dir)?;
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(e) => {
+                    warn!("Failed to read directory entry: {}", e);
+                    continue;
+                }
+            };
+
+            let path = entry.path();
+
+            // Skip non-json files and temp files
+            if !path.extension().map(|e| e == "json").unwrap_or(false) {
+                continue;
+            }
+            if path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with('.'))
+                .unwrap_or(false)
+            {
+                continue;
+            }
+
+            files.push(path);
+        }
+
+        Ok(files)
     }
 
     /// Get base directory
@@ -150,63 +368,267 @@ impl FileSystemStorage {
     pub fn sessions_dir(&self) -> &PathBuf {
         &self.sessions_dir
     }
+
+    /// Temp files that [`prune_orphaned_temp_files`](SessionStorage::prune_orphaned_temp_files)
+    /// would remove
+    pub fn orphaned_temp_files(&self) -> Result<Vec<PathBuf>> {
+        old_temp_files(&self.sessions_dir, ORPHANED_TEMP_AGE)
+    }
+
+    /// `<id>.lock` files whose session no longer exists
+    ///
+    /// `delete` leaves the lock file in place (see there), so these pile up
+    /// as sessions are deleted.
+    pub fn orphaned_lock_files(&self) -> Result<Vec<PathBuf>> {
+        let mut found = Vec::new();
+        for entry in fs::read_dir(&self.sessions_dir).with_path(&self.sessions_dir)? {
+            let path = entry.with_path(&self.sessions_dir)?.path();
+            let id = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix(".lock"))
+                .and_then(|stem| SessionId::from_string(stem).ok());
+            if let Some(id) = id {
+                if !self.session_path(&id).exists() {
+                    found.push(path);
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    /// Remove [`orphaned_lock_files`](Self::orphaned_lock_files)
+    ///
+    /// Lock files held by another process are skipped, and a lock file is
+    /// only removed while holding it. Returns the number of files removed.
+    pub fn prune_orphaned_lock_files(&self) -> Result<usize> {
+        let mut removed = 0;
+        for path in self.orphaned_lock_files()? {
+            let file = match fs::OpenOptions::new().write(true).open(&path) {
+                Ok(file) => file,
+                // Already removed by a concurrent prune
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(CrHelperError::from_io_with_path(e, &path)),
+            };
+            if file.try_lock_exclusive().is_err() {
+                continue;
+            }
+            // The session may have been saved again before we took the lock
+            let saved = session_id_from_path(&path.with_extension("json"))
+                .is_some_and(|id| self.session_path(&id).exists());
+            if !saved {
+                fs::remove_file(&path).with_path(&path)?;
+                debug!("Removed orphaned lock file {:?}", path);
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Total size in bytes of the session files and their diff sidecars
+    pub fn disk_usage(&self) -> Result<u64> {
+        let mut total = 0;
+        for entry in fs::read_dir(&self.sessions_dir).with_path(&self.sessions_dir)? {
+            let entry = entry.with_path(&self.sessions_dir)?;
+            let metadata = entry.metadata().with_path(&entry.path())?;
+            if metadata.is_file() {
+                total += metadata.len();
+            }
+        }
+        Ok(total)
+    }
+
+    /// Move every session from storage at `from` to storage at `to`
+    ///
+    /// Each session (and the diff sidecar it refers to, if any) is copied, then loaded
+    /// back and checked against the original's comment count. Originals are
+    /// only deleted once every copy has been validated; on any error the
+    /// copies made so far are removed. Returns the number of sessions moved.
+    pub fn migrate_storage(from: &Path, to: &Path) -> Result<usize> {
+        let source = Self::new(from)?;
+        let target = Self::new(to)?;
+        if source.sessions_dir == target.sessions_dir {
+            return Err(CrHelperError::Validation(
+                "Source and target storage are the same".to_string(),
+            ));
+        }
+
+        // Check every session before copying any, so a bad one leaves the
+        // target untouched
+        let mut sessions = Vec::new();
+        for path in source.session_files()? {
+            let Some(id) = session_id_from_path(&path) else {
+                continue;
+            };
+            if target.exists(&id) {
+                return Err(CrHelperError::Validation(format!(
+                    "Session {} already exists in {}",
+                    id,
+                    to.display()
+                )));
+            }
+            let comments = source.load(&id)?.comments.count();
+            sessions.push((path, id, comments));
+        }
+
+        let mut copied = Vec::new();
+        if let Err(e) = Self::copy_sessions(&source, &target, &sessions, &mut copied) {
+            for path in &copied {
+                let _ = fs::remove_file(path);
+            }
+            return Err(e);
+        }
+
+        let migrated: Vec<_> = sessions.into_iter().map(|(_, id, _)| id).collect();
+        for id in &migrated {
+            source.delete(id)?;
+        }
+        info!(
+            "Migrated {} sessions from {:?} to {:?}",
+            migrated.len(),
+            from,
+            to
+        );
+        Ok(migrated.len())
+    }
+
+    /// Copy checked sessions and their sidecars, recording each file created
+    /// in `copied`
+    fn copy_sessions(
+        source: &Self,
+        target: &Self,
+        sessions: &[(PathBuf, SessionId, usize)],
+        copied: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        for (path, id, comments) in sessions {
+            if let Some(hash) = SessionMigrator::read_diff_hash(path)? {
+                let sidecar = source.sidecar_path(&hash);
+                let copy = target.sidecar_path(&hash);
+                if !copy.exists() {
+                    fs::copy(&sidecar, &copy).with_path(&sidecar)?;
+                    copied.push(copy);
+                }
+            }
+            let copy = target.session_path(id);
+            fs::copy(path, &copy).with_path(path)?;
+            copied.push(copy);
+
+            let count = target.load(id)?.comments.count();
+            if count != *comments {
+                return Err(CrHelperError::Validation(format!(
+                    "Copy of session {} has {} comments, expected {}",
+                    id, count, comments
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl SessionStorage for FileSystemStorage {
     fn save(&self, session: &Session) -> Result<()> {
-        self.atomic_write(&session.id, session)
+        let _lock = self.lock(&session.id)?;
+        self.save_unlocked(session)
+    }
+
+    fn update(&self, id: &SessionId, update: SessionUpdate<'_>) -> Result<()> {
+        self.with_lock(id, |session| {
+            let mut session = session.clone();
+            update(&mut session)?;
+            Ok((session, ()))
+        })
     }
 
     fn load(&self, id: &SessionId) -> Result<Session> {
         let path = self.session_path(id);
-        self.read_session(&path)
+        let mut session = self.read_session(&path)?;
+
+        let phantoms = session.comments.heal();
+        debug!("Healed {} phantom comment IDs in session {}", phantoms, id);
+        if phantoms > 0 {
+            warn!("Removed {} phantom comment IDs from the index of session {}", phantoms, id);
+        }
+        Ok(session)
     }
 
     fn list(&self) -> Result<Vec<SessionInfo>> {
         let mut sessions = Vec::new();
 
-        let entries = fs::read_dir(&self.sessions_dir).map_err(|e| {
-            CrHelperError::Io(std::io::Error::new(
-                e.kind(),
-                format!("Failed to read sessions directory: {}", e),
-            ))
-        })?;
-
-        for entry in entries {
-            let entry = match entry {
-                Ok(e) => e,
+        for path in self.session_files()? {
+            match self.read_session_info(&path) {
+                Ok(info) => sessions.push(info),
                 Err(e) => {
-                    warn!("Failed to read directory entry: {}", e);
-                    continue;
+                    warn!("Failed to read session file {:?}: {}", path, e);
                 }
-            };
+            }
+        }
 
-            let path = entry.path();
+        Ok(sessions)
+    }
 
-            // Skip non-json files and temp files
-            if !path.extension().map(|e| e == "json").unwrap_or(false) {
-                continue;
-            }
-            if path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .map(|n| n.starts_with('.'))
-                .unwrap_or(false)
-            {
-                continue;
-            }
+    /// Sorting by `UpdatedAt` orders files by modification time and only
+    /// parses the first `limit` of them; other fields parse every file.
+    fn list_sorted(
+        &self,
+        order: SessionSortOrder,
+        limit: Option<usize>,
+    ) -> Result<Vec<SessionInfo>> {
+        if order.by != SortField::UpdatedAt {
+            let mut sessions = self.list()?;
+            order.sort(&mut sessions);
+            sessions.truncate(limit.unwrap_or(usize::MAX));
+            return Ok(sessions);
+        }
+
+        let mut files: Vec<(SystemTime, PathBuf)> = self
+            .session_files()?
+            .into_iter()
+            .map(|path| {
+                let modified = fs::metadata(&path)
+                    .and_then(|m| m.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                (modified, path)
+            })
+            .collect();
+        files.sort_by_key(|(modified, _)| *modified);
+        if order.direction == SortDirection::Descending {
+            files.reverse();
+        }
 
+        // Files sharing the cutoff's mtime are all read, so ties are broken
+        // by `updated_at` rather than directory order
+        let limit = limit.unwrap_or(usize::MAX);
+        let mut sessions = Vec::new();
+        let mut last_modified = None;
+        for (modified, path) in files {
+            if sessions.len() >= limit && last_modified != Some(modified) {
+                break;
+            }
             match self.read_session_info(&path) {
-                Ok(info) => sessions.push(info),
+                Ok(info) => {
+                    sessions.push(info);
+                    last_modified = Some(modified);
+                }
                 Err(e) => {
                     warn!("Failed to read session file {:?}: {}", path, e);
                 }
             }
         }
+        order.sort(&mut sessions);
+        sessions.truncate(limit);
 
         Ok(sessions)
     }
 
+    /// Matches file names, without reading the sessions
+    fn lookup_by_short_id(&self, short: &str) -> Result<Option<SessionId>> {
+        let ids = self
+            .session_files()?
+            .into_iter()
+            .filter_map(|path| session_id_from_path(&path));
+        match_short_id(ids, short)
+    }
+
     fn delete(&self, id: &SessionId) -> Result<()> {
         let path = self.session_path(id);
 
@@ -214,12 +636,16 @@ impl SessionStorage for FileSystemStorage {
             return Err(CrHelperError::SessionNotFound(id.to_string()));
         }
 
-        fs::remove_file(&path).map_err(|e| {
-            CrHelperError::Io(std::io::Error::new(
-                e.kind(),
-                format!("Failed to delete session file: {}", e),
-            ))
-        })?;
+        // The lock file stays: unlinking it would let a writer waiting on the
+        // old file and one opening a new file both hold "the" lock. `doctor
+        // --fix` removes it later (see `prune_orphaned_lock_files`).
+        let lock = self.lock(id)?;
+        let has_sidecar = SessionMigrator::read_diff_hash(&path).ok().flatten().is_some();
+        fs::remove_file(&path).with_path(&path)?;
+        drop(lock);
+        if has_sidecar {
+            self.prune_sidecars_logged();
+        }
 
         debug!("Deleted session {} from {:?}", id, path);
         Ok(())
@@ -228,6 +654,146 @@ impl SessionStorage for FileSystemStorage {
     fn exists(&self, id: &SessionId) -> bool {
         self.session_path(id).exists()
     }
+
+    /// Watches the sessions directory; the atomic rename in `save` is seen
+    /// as a create or modify of `<id>.json`.
+    fn watch(&self, callback: WatchCallback) -> Result<WatchHandle> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let _ = tx.send(event);
+            })
+            .map_err(watch_error)?;
+        watcher
+            .watch(&self.sessions_dir, RecursiveMode::NonRecursive)
+            .map_err(watch_error)?;
+
+        // Exits when the watcher (and with it the sender) is dropped
+        std::thread::spawn(move || debounce_session_events(rx, callback));
+
+        Ok(WatchHandle::new(watcher))
+    }
+
+    /// Only temp files older than an hour are removed, so writes in
+    /// progress in other processes are left alone.
+    fn prune_orphaned_temp_files(&self) -> Result<usize> {
+        prune_temp_files(&self.sessions_dir, ORPHANED_TEMP_AGE)
+    }
+}
+
+fn watch_error(e: notify::Error) -> CrHelperError {
+    CrHelperError::Io(std::io::Error::other(format!(
+        "Failed to watch sessions: {}",
+        e
+    )))
+}
+
+/// Report each written session once it has been quiet for `WATCH_DEBOUNCE`
+fn debounce_session_events(
+    rx: mpsc::Receiver<notify::Result<notify::Event>>,
+    callback: WatchCallback,
+) {
+    let mut pending: HashMap<SessionId, Instant> = HashMap::new();
+    loop {
+        match rx.recv_timeout(WATCH_DEBOUNCE) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    for id in event.paths.iter().filter_map(|p| session_id_from_path(p)) {
+                        pending.insert(id, Instant::now());
+                    }
+                }
+            }
+            Ok(Err(e)) => warn!("Session watch error: {}", e),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        }
+
+        let ready: Vec<SessionId> = pending
+            .iter()
+            .filter(|(_, last)| last.elapsed() >= WATCH_DEBOUNCE)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in ready {
+            pending.remove(&id);
+            callback(id);
+        }
+    }
+}
+
+/// Session ID of a `<id>.json` file, ignoring temp files
+fn session_id_from_path(path: &Path) -> Option<SessionId> {
+    let name = path.file_name()?.to_str()?;
+    let stem = name.strip_suffix(".json")?;
+    if stem.starts_with('.') {
+        return None;
+    }
+    SessionId::from_string(stem).ok()
+}
+
+/// Write a file through `temp_path`, renaming it into place when complete
+fn write_atomically(
+    temp_path: &Path,
+    final_path: &Path,
+    write: impl FnOnce(&mut BufWriter<fs::File>) -> Result<()>,
+) -> Result<()> {
+    let temp_file = fs::File::create(temp_path).with_path(temp_path)?;
+    let mut writer = BufWriter::new(temp_file);
+    write(&mut writer)?;
+    writer.flush().with_path(temp_path)?;
+
+    // Rename to final path (atomic on most filesystems)
+    fs::rename(temp_path, final_path).map_err(|e| {
+        // Clean up temp file on failure
+        let _ = fs::remove_file(temp_path);
+        CrHelperError::from_io_with_path(e, final_path)
+    })
+}
+
+/// Delete `.*.tmp` files in `dir` last modified more than `max_age` ago
+fn prune_temp_files(dir: &Path, max_age: Duration) -> Result<usize> {
+    let mut removed = 0;
+    for path in old_temp_files(dir, max_age)? {
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                debug!("Removed orphaned temp file {:?}", path);
+                removed += 1;
+            }
+            // Already removed by a concurrent prune
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(CrHelperError::from_io_with_path(e, &path)),
+        }
+    }
+
+    Ok(removed)
+}
+
+/// `.*.tmp` files in `dir` last modified more than `max_age` ago
+fn old_temp_files(dir: &Path, max_age: Duration) -> Result<Vec<PathBuf>> {
+    let now = SystemTime::now();
+    let mut found = Vec::new();
+
+    for entry in fs::read_dir(dir).with_path(dir)? {
+        let path = entry.with_path(dir)?.path();
+        let is_temp = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.starts_with('.') && n.ends_with(".tmp"))
+            .unwrap_or(false);
+        if !is_temp {
+            continue;
+        }
+
+        let modified = match fs::metadata(&path).and_then(|m| m.modified()) {
+            Ok(modified) => modified,
+            Err(_) => continue,
+        };
+        if now.duration_since(modified).unwrap_or_default() < max_age {
+            continue;
+        }
+        found.push(path);
+    }
+
+    Ok(found)
 }
 
 #[cfg(test)]
@@ -253,6 +819,96 @@ mod tests {
         assert!(storage.sessions_dir().exists());
     }
 
+    #[test]
+    fn test_for_project_isolates_storage() {
+        let root = TempDir::new().unwrap();
+        let projects = TempDir::new().unwrap();
+        let a = projects.path().join("a");
+        let b = projects.path().join("b");
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+
+        let storage_a = FileSystemStorage::for_project_in(root.path(), &a).unwrap();
+        let storage_b = FileSystemStorage::for_project_in(root.path(), &b).unwrap();
+        assert!(storage_a
+            .sessions_dir()
+            .starts_with(root.path().join("projects")));
+        assert_ne!(storage_a.sessions_dir(), storage_b.sessions_dir());
+
+        storage_a.save(&create_test_session()).unwrap();
+        assert_eq!(storage_a.list().unwrap().len(), 1);
+        assert!(storage_b.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_prune_orphaned_temp_files() {
+        // A plain directory: FileSystemStorage::new prunes in the background,
+        // which would race with the calls below
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        let old = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+
+        let orphan = dir.join(".abc.json.tmp");
+        fs::File::create(&orphan)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let in_flight = dir.join(".def.json.tmp");
+        fs::File::create(&in_flight).unwrap();
+        let other = dir.join("notes.tmp");
+        fs::File::create(&other).unwrap().set_modified(old).unwrap();
+
+        assert_eq!(old_temp_files(dir, ORPHANED_TEMP_AGE).unwrap(), vec![orphan.clone()]);
+        assert_eq!(prune_temp_files(dir, ORPHANED_TEMP_AGE).unwrap(), 1);
+        assert!(!orphan.exists());
+        assert!(in_flight.exists());
+        assert!(other.exists());
+        assert_eq!(prune_temp_files(dir, ORPHANED_TEMP_AGE).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_prune_orphaned_lock_files() {
+        let (storage, _temp) = create_test_storage();
+        let kept = create_test_session();
+        let deleted = create_test_session();
+        storage.save(&kept).unwrap();
+        storage.save(&deleted).unwrap();
+        storage.delete(&deleted.id).unwrap();
+
+        let lock = storage.lock_path(&deleted.id);
+        assert!(lock.exists());
+        assert_eq!(storage.orphaned_lock_files().unwrap(), vec![lock.clone()]);
+
+        // Held elsewhere: left alone
+        let held = storage.lock(&deleted.id).unwrap();
+        assert_eq!(storage.prune_orphaned_lock_files().unwrap(), 0);
+        drop(held);
+
+        assert_eq!(storage.prune_orphaned_lock_files().unwrap(), 1);
+        assert!(!lock.exists());
+        assert!(storage.lock_path(&kept.id).exists());
+        assert!(storage.orphaned_lock_files().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_new_prunes_temp_files_in_background() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("sessions");
+        fs::create_dir_all(&dir).unwrap();
+        let orphan = dir.join(".abc.json.tmp");
+        fs::File::create(&orphan)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(2 * 60 * 60))
+            .unwrap();
+
+        FileSystemStorage::new(temp.path()).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while orphan.exists() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!orphan.exists());
+    }
+
     #[test]
     fn test_session_path() {
         let (storage, _temp) = create_test_storage();
@@ -304,6 +960,20 @@ mod tests {
         assert_eq!(list.len(), 2);
     }
 
+    #[test]
+    fn test_lookup_by_short_id() {
+        let (storage, _temp) = create_test_storage();
+        let session = create_test_session();
+        storage.save(&session).unwrap();
+
+        let short = &session.id.short()[..4];
+        assert_eq!(
+            storage.lookup_by_short_id(short).unwrap(),
+            Some(session.id.clone())
+        );
+        assert_eq!(storage.lookup_by_short_id("zzzz").unwrap(), None);
+    }
+
     #[test]
     fn test_delete_session() {
         let (storage, _temp) = create_test_storage();
@@ -348,6 +1018,200 @@ mod tests {
         assert!(content.contains(&id.to_string()));
     }
 
+    /// A diff of `files` files with ten changed lines each
+    fn monorepo_diff(files: usize) -> DiffData {
+        let input: String = (0..files)
+            .map(|i| {
+                let mut file = format!(
+                    "diff --git a/pkg{i}/src/lib.rs b/pkg{i}/src/lib.rs\n--- a/pkg{i}/src/lib.rs\n+++ b/pkg{i}/src/lib.rs\n@@ -1,10 +1,10 @@\n"
+                );
+                for line in 0..10 {
+                    file.push_str(&format!("-    let value_{line} = compute({line});\n"));
+                    file.push_str(&format!("+    let value_{line} = compute_checked({line})?;\n"));
+                }
+                file
+            })
+            .collect();
+        cr_core::diff::DiffParser::new().parse(&input).unwrap()
+    }
+
+    #[test]
+    fn test_with_lock_serializes_writers() {
+        let (storage, temp) = create_test_storage();
+        let session = create_test_session();
+        storage.save(&session).unwrap();
+
+        // Separate storages open separate lock files, like separate processes
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let base = temp.path().to_path_buf();
+                let id = session.id.clone();
+                std::thread::spawn(move || {
+                    let storage = FileSystemStorage::new(base).unwrap();
+                    storage
+                        .with_lock(&id, |session| {
+                            let mut session = session.clone();
+                            session.metadata.tags.push(format!("t{}", i));
+                            Ok((session, ()))
+                        })
+                        .unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let saved = storage.load(&session.id).unwrap();
+        assert_eq!(saved.metadata.tags.len(), 8);
+
+        let count = storage
+            .with_lock(&session.id, |session| Ok((session.clone(), session.metadata.tags.len())))
+            .unwrap();
+        assert_eq!(count, 8);
+    }
+
+    /// Diff sidecars in the sessions directory
+    fn sidecars(storage: &FileSystemStorage) -> Vec<PathBuf> {
+        fs::read_dir(storage.sessions_dir())
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.to_string_lossy().ends_with(".diff.msgpack"))
+            .collect()
+    }
+
+    /// Make every sidecar look older than `SIDECAR_GRACE`
+    fn age_sidecars(storage: &FileSystemStorage) {
+        let old = SystemTime::now() - 2 * SIDECAR_GRACE;
+        for path in sidecars(storage) {
+            fs::File::options().write(true).open(path).unwrap().set_modified(old).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_save_compact() {
+        let (storage, temp) = create_test_storage();
+        let storage = storage.with_format(StorageFormat::Msgpack);
+        let session = Session::new(DiffSource::WorkingTree, monorepo_diff(1000));
+        let id = session.id.clone();
+
+        storage.save(&session).unwrap();
+        let content = fs::read_to_string(storage.session_path(&id)).unwrap();
+        assert!(content.contains("\"schema_version\": \"2.0\""));
+        let sidecar = sidecars(&storage).pop().unwrap();
+        let compact_size = content.len() as u64 + fs::metadata(&sidecar).unwrap().len();
+
+        let loaded = storage.load(&id).unwrap();
+        assert_eq!(loaded.diff_data.files.len(), 1000);
+        assert_eq!(loaded.info().file_count, 1000);
+        assert_eq!(storage.list().unwrap()[0].file_count, 1000);
+
+        // Sessions with a sidecar stay compact with the default format
+        let json_storage = FileSystemStorage::new(temp.path()).unwrap();
+        json_storage.save(&loaded).unwrap();
+        assert_eq!(sidecars(&storage), vec![sidecar.clone()]);
+
+        let json_session = Session::new(DiffSource::WorkingTree, monorepo_diff(1000));
+        json_storage.save(&json_session).unwrap();
+        let json_size = fs::metadata(json_storage.session_path(&json_session.id))
+            .unwrap()
+            .len();
+        assert!(
+            compact_size * 2 < json_size,
+            "msgpack {} bytes vs json {} bytes",
+            compact_size,
+            json_size
+        );
+
+        age_sidecars(&storage);
+        storage.delete(&id).unwrap();
+        assert!(!sidecar.exists());
+    }
+
+    #[test]
+    fn test_save_compact_shares_and_prunes_sidecars() {
+        let (storage, _temp) = create_test_storage();
+        let storage = storage.with_format(StorageFormat::Msgpack);
+        let first = Session::new(DiffSource::WorkingTree, monorepo_diff(2));
+        let mut second = first.clone();
+        second.id = SessionId::generate();
+        storage.save_compact(&first).unwrap();
+        storage.save_compact(&second).unwrap();
+        assert_eq!(sidecars(&storage).len(), 1);
+
+        // A shared sidecar outlives one of its sessions
+        age_sidecars(&storage);
+        storage.delete(&first.id).unwrap();
+        assert_eq!(sidecars(&storage).len(), 1);
+
+        // Replacing the diff drops the sidecar nothing refers to any more
+        let mut changed = storage.load(&second.id).unwrap();
+        changed.diff_data = monorepo_diff(3);
+        storage.save(&changed).unwrap();
+        let remaining = sidecars(&storage);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(storage.load(&second.id).unwrap().diff_data.files.len(), 3);
+
+        // Fresh unreferenced sidecars may belong to a save in progress
+        fs::write(storage.sessions_dir().join("abc.diff.msgpack"), b"").unwrap();
+        assert_eq!(storage.prune_sidecars().unwrap(), 0);
+        age_sidecars(&storage);
+        assert_eq!(storage.prune_sidecars().unwrap(), 1);
+        assert_eq!(sidecars(&storage), remaining);
+    }
+
+    #[test]
+    fn test_migrate_storage() {
+        use cr_core::comment::{CommentBuilder, DiffSide};
+        use cr_core::types::{FileId, LineId};
+
+        let (source, temp) = create_test_storage();
+        let mut commented = create_test_session();
+        let comment = CommentBuilder::new(
+            FileId::from_string("f1"),
+            LineId::from_string("l1"),
+            DiffSide::New,
+        )
+        .content("check this")
+        .build()
+        .unwrap();
+        commented.comments.add(comment).unwrap();
+        source.save(&commented).unwrap();
+        let compact = Session::new(DiffSource::WorkingTree, monorepo_diff(3));
+        source.save_compact(&compact).unwrap();
+        assert!(source.disk_usage().unwrap() > 0);
+
+        let target_dir = temp.path().join("moved");
+        let moved = FileSystemStorage::migrate_storage(temp.path(), &target_dir).unwrap();
+        assert_eq!(moved, 2);
+        assert!(source.list().unwrap().is_empty());
+
+        let target = FileSystemStorage::new(&target_dir).unwrap();
+        assert_eq!(target.load(&commented.id).unwrap().comments.count(), 1);
+        assert_eq!(target.load(&compact.id).unwrap().diff_data.files.len(), 3);
+        assert_eq!(sidecars(&target).len(), 1);
+
+        assert!(FileSystemStorage::migrate_storage(&target_dir, &target_dir).is_err());
+    }
+
+    #[test]
+    fn test_migrate_storage_with_bad_session() {
+        let (source, temp) = create_test_storage();
+        let sessions: Vec<_> = (0..3).map(|_| create_test_session()).collect();
+        for session in &sessions {
+            source.save(session).unwrap();
+        }
+        let bad = source.session_path(&sessions[1].id);
+        fs::write(&bad, "{ not a session").unwrap();
+
+        let target_dir = temp.path().join("moved");
+        assert!(FileSystemStorage::migrate_storage(temp.path(), &target_dir).is_err());
+
+        let target = FileSystemStorage::new(&target_dir).unwrap();
+        assert!(target.session_files().unwrap().is_empty());
+        assert_eq!(source.session_files().unwrap().len(), 3);
+    }
+
     #[test]
     fn test_latest_session() {
         let (storage, _temp) = create_test_storage();
@@ -368,6 +1232,69 @@ mod tests {
         assert_eq!(latest.id, expected_id);
     }
 
+    #[test]
+    fn test_list_sorted() {
+        let (storage, _temp) = create_test_storage();
+
+        let mut ids = Vec::new();
+        for files in 0..3 {
+            let mut session = create_test_session();
+            for i in 0..files {
+                let path = PathBuf::from(format!("f{}.rs", i));
+                session.diff_data.files.push(cr_core::diff::FileDiff {
+                    id: cr_core::types::FileId::from_path(&path),
+                    old_path: None,
+                    new_path: Some(path),
+                    mode: cr_core::diff::FileMode::Added,
+                    hunks: Vec::new(),
+                    lazy: false,
+                    file_mode: None,
+                });
+            }
+            storage.save(&session).unwrap();
+            ids.push(session.id);
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        let newest = storage
+            .list_sorted(SessionSortOrder::newest_first(), Some(2))
+            .unwrap();
+        let newest: Vec<_> = newest.into_iter().map(|s| s.id).collect();
+        assert_eq!(newest, vec![ids[2].clone(), ids[1].clone()]);
+
+        let order = SessionSortOrder {
+            by: SortField::FileCount,
+            direction: SortDirection::Ascending,
+        };
+        let by_files: Vec<_> = storage
+            .list_sorted(order, None)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.file_count)
+            .collect();
+        assert_eq!(by_files, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_watch() {
+        let (storage, _temp) = create_test_storage();
+        let (tx, rx) = mpsc::channel();
+        let handle = storage
+            .watch(Box::new(move |id| tx.send(id).unwrap()))
+            .unwrap();
+
+        let session = create_test_session();
+        storage.save(&session).unwrap();
+        storage.save(&session).unwrap();
+
+        let id = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(id, session.id);
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+
+        drop(handle);
+        assert!(session_id_from_path(Path::new("/s/.abc.json.tmp")).is_none());
+    }
+
     #[test]
     fn test_ignores_temp_files() {
         let (storage, _temp) = create_test_storage();
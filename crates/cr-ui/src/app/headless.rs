@@ -0,0 +1,116 @@
+//! Scripted TUI operations for tests
+
+use cr_core::comment::Severity;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::{AppMode, AppState};
+use crate::input::{Command, CommandParser};
+
+/// A single step of a scripted review (see [`App::run_headless`](super::App::run_headless))
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeadlessCommand {
+    /// Move the cursor down this many lines
+    MoveDown(usize),
+    /// Move the cursor up this many lines
+    MoveUp(usize),
+    /// Go to the next file
+    NextFile,
+    /// Comment on the current line
+    AddComment {
+        /// Comment text
+        content: String,
+        /// Comment severity
+        severity: Severity,
+    },
+    /// Delete the latest comment on the current line
+    DeleteComment,
+    /// Save the session (`s`)
+    Save,
+    /// Export the session in this format (`E`)
+    Export(String),
+    /// Stop processing commands
+    Quit,
+}
+
+/// Turns keyboard events from a real TUI session into [`HeadlessCommand`]s
+///
+/// Only keys with a matching command are recorded. Repeated `j`/`k` presses
+/// are merged into one move. Each key is recorded against the app's state
+/// before the key is handled (see [`App::start_recording`](super::App::start_recording)),
+/// so keys typed into prompts and popups are not mistaken for commands.
+#[derive(Debug, Clone, Default)]
+pub struct HeadlessRecorder {
+    commands: Vec<HeadlessCommand>,
+}
+
+impl HeadlessRecorder {
+    /// Create an empty recorder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a key press, given the state the key is about to be handled in
+    pub fn record(&mut self, key: KeyEvent, state: &AppState) {
+        match state.mode {
+            AppMode::Normal => self.record_normal(key, state),
+            AppMode::Insert => {
+                // File comments have no headless command
+                let content = &state.editor_content;
+                if key.code == KeyCode::Enter && !state.is_file_comment && !content.trim().is_empty() {
+                    self.commands.push(HeadlessCommand::AddComment {
+                        content: content.clone(),
+                        severity: Severity::Info,
+                    });
+                }
+            }
+            AppMode::Command => {
+                if key.code != KeyCode::Enter {
+                    return;
+                }
+                match CommandParser::parse(&state.command_input) {
+                    Ok(Command::Save) => self.push(HeadlessCommand::Save),
+                    Ok(Command::Quit) => self.push(HeadlessCommand::Quit),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn record_normal(&mut self, key: KeyEvent, state: &AppState) {
+        if state.pending_mark.is_some() || key.modifiers.contains(KeyModifiers::CONTROL) {
+            return;
+        }
+        let command = match key.code {
+            KeyCode::Char('j') | KeyCode::Down => HeadlessCommand::MoveDown(1),
+            KeyCode::Char('k') | KeyCode::Up => HeadlessCommand::MoveUp(1),
+            KeyCode::Char('n') => HeadlessCommand::NextFile,
+            KeyCode::Char('x') => HeadlessCommand::DeleteComment,
+            KeyCode::Char('s') => HeadlessCommand::Save,
+            KeyCode::Char('E') => {
+                HeadlessCommand::Export(state.export_format.clone().unwrap_or_else(|| "markdown".to_string()))
+            }
+            KeyCode::Char('q') => HeadlessCommand::Quit,
+            _ => return,
+        };
+        self.push(command);
+    }
+
+    /// Commands recorded so far
+    pub fn commands(&self) -> &[HeadlessCommand] {
+        &self.commands
+    }
+
+    /// Stop recording and return the commands
+    pub fn finish(self) -> Vec<HeadlessCommand> {
+        self.commands
+    }
+
+    fn push(&mut self, command: HeadlessCommand) {
+        match (self.commands.last_mut(), &command) {
+            (Some(HeadlessCommand::MoveDown(n)), HeadlessCommand::MoveDown(m))
+            | (Some(HeadlessCommand::MoveUp(n)), HeadlessCommand::MoveUp(m)) => *n += m,
+            _ => self.commands.push(command),
+        }
+    }
+}
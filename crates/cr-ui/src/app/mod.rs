@@ -1,5 +1,68 @@
 //! Application module
 
+mod headless;
 mod state;
 
-pub use state::{App, AppMode, AppState};
+pub use headless::{HeadlessCommand, HeadlessRecorder};
+pub use state::{App, AppMode, AppState, EditorCommand, SearchMode};
+
+use cr_core::config::UiConfig;
+
+/// TUI settings, from `[ui]` in config.toml
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TuiConfig {
+    /// Capture the mouse
+    pub mouse: bool,
+    /// Color theme name (see `THEME_NAMES`)
+    pub theme_name: String,
+    /// Lines kept visible above and below the cursor when scrolling
+    pub scroll_padding: usize,
+    /// Show old/new line numbers in the diff
+    pub show_line_numbers: bool,
+    /// Wrap long diff lines
+    pub word_wrap: bool,
+    /// Highlight the line under the cursor
+    pub highlight_cursor_line: bool,
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        Self::from(&UiConfig::default())
+    }
+}
+
+impl From<&UiConfig> for TuiConfig {
+    fn from(ui: &UiConfig) -> Self {
+        Self {
+            mouse: ui.mouse,
+            theme_name: ui.theme.clone(),
+            scroll_padding: ui.scroll_padding,
+            show_line_numbers: ui.show_line_numbers,
+            word_wrap: ui.word_wrap,
+            highlight_cursor_line: ui.highlight_cursor_line,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tui_config_from_ui() {
+        let ui = UiConfig {
+            theme: "dark".to_string(),
+            mouse: false,
+            scroll_padding: 5,
+            word_wrap: true,
+            ..UiConfig::default()
+        };
+        let tui = TuiConfig::from(&ui);
+        assert!(!tui.mouse);
+        assert_eq!(tui.theme_name, "dark");
+        assert_eq!(tui.scroll_padding, 5);
+        assert!(tui.word_wrap);
+        assert!(tui.show_line_numbers);
+        assert!(tui.highlight_cursor_line);
+    }
+}
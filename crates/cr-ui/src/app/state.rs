@@ -1,23 +1,38 @@
 //! Application state and main app structure
 
 use anyhow::Result;
-use cr_core::comment::Comment;
-use cr_core::diff::{DiffNavigator, DiffParser, FileDiff, LineType};
+use cr_core::comment::{Comment, CommentBuilder, CommentManager, Severity};
+use cr_core::diff::{
+    DiffData, DiffNavigator, DiffParser, ParserConfig, DiffStats, FileDiff, FileMode, FileStat, LineType, Position, VirtualLine, WordKind,
+};
 use cr_core::diff::Line as DiffLine;
-use cr_core::session::Session;
+use cr_core::config::{RiskThresholds, SeverityKeywords};
+use cr_core::session::{DiffSource, RiskLevel, Session, SessionInfo, SessionManager, SessionSortOrder};
 use cr_core::types::{CommentId, FileId, LineId};
+use cr_integration::{CommitInfo, GitLog, HgDetector, LockFile};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent, KeyModifiers},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, KeyCode,
+        KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{prelude::*, widgets::*};
+use ratatui::{backend::TestBackend, buffer::Buffer, prelude::*, widgets::*};
 use ratatui::text::Line as TextLine;
 use std::collections::HashMap;
 use std::io::{self, Stdout};
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
+use super::headless::{HeadlessCommand, HeadlessRecorder};
+use super::TuiConfig;
+use crate::components::BinaryPreviewRenderer;
 use crate::highlight::Highlighter;
+use crate::input::{Command, CommandParser, SessionAction};
+use crate::recording::AsciicastWriter;
+use crate::screenshot::buffer_to_svg;
+use crate::theme::{heat_bar, heat_color, Theme, ThemeManager, THEME_NAMES};
 
 /// Application mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,6 +43,22 @@ pub enum AppMode {
     Insert,
     /// Help mode
     Help,
+    /// Go-to-file prompt
+    GotoFile,
+    /// Command palette (`:`)
+    Command,
+    /// Side-by-side theme comparison (`:set theme`)
+    ThemePreview,
+    /// Reviewer assignment popup (`A`)
+    Assignments,
+    /// Comment search prompt (`/`)
+    Search,
+    /// Commit history sidebar (`L`, see `--context-commit`)
+    CommitLog,
+    /// A commit from the sidebar, opened with `Enter`
+    CommitDiff,
+    /// Session picker (`:session list`)
+    SessionPicker,
 }
 
 impl Default for AppMode {
@@ -36,6 +67,30 @@ impl Default for AppMode {
     }
 }
 
+/// How the `/` prompt matches comment content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    /// Case-insensitive substring match
+    #[default]
+    Substring,
+    /// Regular expression (`--regex`)
+    Regex,
+    /// Normalized Levenshtein similarity (`--fuzzy`)
+    Fuzzy,
+}
+
+/// Minimum similarity for `/--fuzzy` matches
+const FUZZY_SEARCH_THRESHOLD: f64 = 0.5;
+
+/// How long the `E` export path stays in the status bar
+const EXPORT_MESSAGE_DURATION: Duration = Duration::from_secs(5);
+
+/// Where `Ctrl-P` saves screenshots
+const SCREENSHOT_DIR: &str = ".cr-helper/screenshots";
+
+/// Width of the title bar's `+++--` bar for a file holding every change
+const TITLE_STAT_BAR_WIDTH: usize = 20;
+
 /// Application state
 #[derive(Debug, Clone, Default)]
 pub struct AppState {
@@ -57,6 +112,80 @@ pub struct AppState {
     pub editor_cursor: usize,
     /// Is this a file-level comment?
     pub is_file_comment: bool,
+    /// Last key was `g` (for two-key commands like `gf`)
+    pub pending_g: bool,
+    /// Last key was `m` or `'`, waiting for a bookmark name
+    pub pending_mark: Option<char>,
+    /// Bookmark names by line index in the current file
+    pub bookmarked_lines: HashMap<usize, String>,
+    /// Go-to-file prompt input
+    pub goto_query: String,
+    /// File paths matching the go-to-file prompt
+    pub goto_candidates: Vec<String>,
+    /// Command palette input
+    pub command_input: String,
+    /// Comment search prompt input
+    pub search_query: String,
+    /// How the last search matched (set by a leading `--regex`/`--fuzzy`)
+    pub search_mode: SearchMode,
+    /// Comments matching the last search, best match first
+    pub search_results: Vec<CommentId>,
+    /// Only show comments of this severity
+    pub severity_filter: Option<Severity>,
+    /// Only visit files with these change modes (empty shows all)
+    pub file_mode_filter: Vec<FileMode>,
+    /// Color theme
+    pub themes: ThemeManager,
+    /// Index into `THEME_NAMES` of the theme being previewed
+    pub theme_preview: usize,
+    /// HEAD has moved since the session was created
+    pub session_stale: bool,
+    /// The session has at least one saved checkpoint
+    pub has_checkpoints: bool,
+    /// Files shown and in the full diff when `[diff] max_files_per_session` cut it short
+    pub truncated_files: Option<(usize, usize)>,
+    /// Normalized churn score per file (see `DiffStats::heat_map`)
+    pub file_heat: HashMap<FileId, f64>,
+    /// Line and hunk counts per file (see `DiffStats::per_file_stats`)
+    pub file_stats: HashMap<FileId, FileStat>,
+    /// `git diff --stat`-style `+++--` bar per file (see `FileStat::stat_bar`)
+    pub file_stat_bars: HashMap<FileId, String>,
+    /// Share of changed lines with comments (see `Session::annotation_coverage`)
+    pub coverage_percent: f64,
+    /// Risk level shown in the title bar (see `Session::compute_risk_score`)
+    pub risk_level: Option<RiskLevel>,
+    /// Thresholds for `risk_level` (`[review.risk_thresholds]`)
+    pub risk_thresholds: RiskThresholds,
+    /// Keywords for the editor's severity suggestion (`[review.severity_keywords]`)
+    pub severity_keywords: SeverityKeywords,
+    /// Current file as `(index, total_count)` (see `DiffData::file_position`)
+    pub file_position: Option<(usize, usize)>,
+    /// When the status message should be cleared, if it is temporary
+    pub message_expires: Option<Instant>,
+    /// Format used by `E` (defaults to markdown)
+    pub export_format: Option<String>,
+    /// Also export when saving with `s`
+    pub export_on_save: bool,
+    /// Show binary files as "Binary file" only (`--no-binary-preview`)
+    pub no_binary_preview: bool,
+    /// Preview lines for the binary files visited so far (see [`BinaryPreviewRenderer`])
+    pub binary_previews: HashMap<FileId, Vec<String>>,
+    /// Settings from `[ui]` (see [`App::new`])
+    pub config: TuiConfig,
+    /// Commits around the reviewed one, newest first (`review --context-commit`)
+    pub commit_log: Vec<CommitInfo>,
+    /// Full hash of the reviewed commit, highlighted in the sidebar
+    pub context_commit: Option<String>,
+    /// Selected sidebar entry
+    pub commit_log_selected: usize,
+    /// `git show` lines of the commit opened from the sidebar
+    pub commit_diff: Vec<String>,
+    /// Sessions listed by `:session list`, newest first
+    pub session_list: Vec<SessionInfo>,
+    /// Selected session picker entry
+    pub session_list_selected: usize,
+    /// First `commit_diff` line shown
+    pub commit_diff_scroll: usize,
 }
 
 impl AppState {
@@ -68,11 +197,93 @@ impl AppState {
     /// Set status message
     pub fn set_message(&mut self, msg: impl Into<String>) {
         self.message = Some(msg.into());
+        self.message_expires = None;
+    }
+
+    /// Set a status message that clears itself after `duration`
+    pub fn set_timed_message(&mut self, msg: impl Into<String>, duration: Duration) {
+        self.message = Some(msg.into());
+        self.message_expires = Some(Instant::now() + duration);
+    }
+
+    /// Whether the file mode filter lets `mode` through
+    pub fn shows_file_mode(&self, mode: FileMode) -> bool {
+        self.file_mode_filter.is_empty() || self.file_mode_filter.contains(&mode)
+    }
+
+    /// Severity suggested for the comment being typed, if any
+    pub fn suggested_severity(&self) -> Option<Severity> {
+        if self.editor_content.trim().is_empty() {
+            return None;
+        }
+        Some(CommentBuilder::auto_severity_with(
+            &self.editor_content,
+            &[],
+            &self.severity_keywords,
+        ))
     }
 
     /// Clear status message
     pub fn clear_message(&mut self) {
         self.message = None;
+        self.message_expires = None;
+    }
+
+    /// Clear a temporary message whose time is up
+    pub fn expire_message(&mut self, now: Instant) {
+        if self.message_expires.is_some_and(|at| now >= at) {
+            self.clear_message();
+        }
+    }
+}
+
+/// External editor from `$VISUAL` / `$EDITOR`
+///
+/// The variable may hold a command with arguments (e.g. `code --wait`); it is
+/// split on whitespace and run directly, never through a shell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditorCommand {
+    /// Editor binary
+    pub program: String,
+    /// Arguments configured alongside the binary
+    pub args: Vec<String>,
+}
+
+impl EditorCommand {
+    /// Read `$VISUAL`, then `$EDITOR`, falling back to `vi`
+    pub fn from_env() -> Self {
+        Self::from_vars(std::env::var("VISUAL").ok(), std::env::var("EDITOR").ok())
+    }
+
+    fn from_vars(visual: Option<String>, editor: Option<String>) -> Self {
+        [visual, editor]
+            .into_iter()
+            .flatten()
+            .find_map(|value| Self::parse(&value))
+            .unwrap_or_else(|| Self {
+                program: "vi".to_string(),
+                args: Vec::new(),
+            })
+    }
+
+    /// Parse an editor command line (`None` if blank)
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut words = value.split_whitespace().map(str::to_string);
+        let program = words.next()?;
+        Some(Self {
+            program,
+            args: words.collect(),
+        })
+    }
+
+    /// Build the command that opens `path` at `line_num`
+    pub fn command(&self, path: &Path, line_num: usize) -> std::process::Command {
+        let mut command = std::process::Command::new(&self.program);
+        command
+            .args(&self.args)
+            .arg(format!("+{}", line_num))
+            .arg(path);
+        command
     }
 }
 
@@ -84,64 +295,117 @@ pub struct App {
     pub session: Session,
     /// Diff navigator
     pub navigator: DiffNavigator,
-    /// Terminal
-    terminal: Terminal<CrosstermBackend<Stdout>>,
+    /// Terminal (`None` for a headless app, see [`App::headless`])
+    terminal: Option<Terminal<CrosstermBackend<Stdout>>>,
+    /// Screenshot size for a headless app
+    headless_size: (u16, u16),
     /// Diff parser for lazy loading
     parser: DiffParser,
-    /// Line comments cache: FileId -> LineId -> Vec<CommentId>
-    line_comments: HashMap<FileId, HashMap<LineId, Vec<CommentId>>>,
     /// Syntax highlighter
     highlighter: Highlighter,
+    /// Comments or diff lines changed since `coverage_percent` was computed
+    coverage_stale: bool,
+    /// Comments, diff lines or thresholds changed since `risk_level` was computed
+    risk_stale: bool,
+    /// Storage for `:session new` and `:session list` (see [`App::set_session_manager`])
+    manager: Option<SessionManager>,
+    /// Full diff when only some files are shown (see [`App::set_unfiltered_diff`])
+    unfiltered_diff: Option<DiffData>,
+    /// Key presses being recorded (see [`App::start_recording`])
+    recorder: Option<HeadlessRecorder>,
 }
 
 impl App {
     /// Create a new app with the given session
-    pub fn new(session: Session) -> Result<Self> {
+    ///
+    /// The mouse is only captured if `config.mouse` is set.
+    pub fn new(session: Session, config: TuiConfig) -> Result<Self> {
         // Install panic hook to restore terminal on panic
         let original_hook = std::panic::take_hook();
         std::panic::set_hook(Box::new(move |panic_info| {
             let _ = disable_raw_mode();
-            let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
+            let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste);
             original_hook(panic_info);
         }));
 
         // Setup terminal
         enable_raw_mode()?;
         let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        // Bracketed paste delivers pasted text as one `Event::Paste`
+        execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
+        if config.mouse {
+            execute!(stdout, EnableMouseCapture)?;
+        }
         let backend = CrosstermBackend::new(stdout);
         let terminal = Terminal::new(backend)?;
 
+        Ok(Self::build(session, config, Some(terminal)))
+    }
+
+    /// Create an app that renders only to screenshots, without touching the
+    /// terminal
+    pub fn headless(session: Session, width: u16, height: u16) -> Self {
+        let mut app = Self::build(session, TuiConfig::default(), None);
+        app.headless_size = (width, height);
+        app
+    }
+
+    fn build(session: Session, config: TuiConfig, terminal: Option<Terminal<CrosstermBackend<Stdout>>>) -> Self {
         let navigator = DiffNavigator::new(session.diff_data.clone());
 
-        // Build line comments index
-        let mut line_comments: HashMap<FileId, HashMap<LineId, Vec<CommentId>>> = HashMap::new();
-        for comment in session.comments.all_sorted() {
-            let file_id = comment.file_id().clone();
-            for line_id in comment.line_ids() {
-                line_comments
-                    .entry(file_id.clone())
-                    .or_default()
-                    .entry(line_id.clone())
-                    .or_default()
-                    .push(comment.id.clone());
-            }
+        let mut state = AppState::new();
+        if let Err(e) = state.themes.set(&config.theme_name) {
+            tracing::warn!("{}, using default", e);
         }
+        state.config = config;
+        state.truncated_files = session
+            .diff_data
+            .metadata
+            .total_files()
+            .map(|total| (session.diff_data.files.len(), total));
+        state.file_heat = DiffStats::heat_map(&session.diff_data).into_iter().collect();
 
         let mut app = Self {
-            state: AppState::new(),
+            state,
             session,
             navigator,
             terminal,
+            headless_size: (120, 40),
             parser: DiffParser::new(),
-            line_comments,
             highlighter: Highlighter::new(),
+            coverage_stale: true,
+            risk_stale: true,
+            manager: None,
+            unfiltered_diff: None,
+            recorder: None,
         };
 
+        app.state.session_stale = app.session.is_stale_in(&app.repo_root()).unwrap_or(false);
+        app.refresh_file_stats();
+
         // Load first file if it's lazy
         app.load_current_file();
+        app.restore_bookmarks();
+
+        app
+    }
+
+    /// Set the score boundaries for the title bar's risk level
+    pub fn set_risk_thresholds(&mut self, thresholds: RiskThresholds) {
+        self.state.risk_thresholds = thresholds;
+        self.risk_stale = true;
+    }
+
+    /// Let the palette's `:session` commands save, create and load sessions
+    pub fn set_session_manager(&mut self, manager: SessionManager) {
+        self.manager = Some(manager);
+    }
 
-        Ok(app)
+    /// Keep the full diff of a session shown with some files removed
+    ///
+    /// [`App::get_session`] puts it back, so the hidden files survive a save.
+    pub fn set_unfiltered_diff(&mut self, diff_data: DiffData) {
+        self.unfiltered_diff = Some(diff_data);
     }
 
     /// Run the main application loop
@@ -152,11 +416,16 @@ impl App {
 
             // Handle input
             if event::poll(Duration::from_millis(100))? {
-                if let event::Event::Key(key) = event::read()? {
-                    self.handle_input(key)?;
+                match event::read()? {
+                    event::Event::Key(key) => self.handle_input(key)?,
+                    event::Event::Paste(text) => self.handle_paste(&text)?,
+                    event::Event::Mouse(mouse) => self.handle_mouse(mouse),
+                    _ => {}
                 }
             }
 
+            self.state.expire_message(Instant::now());
+
             // Check if should quit
             if self.state.should_quit {
                 break;
@@ -166,56 +435,423 @@ impl App {
         Ok(())
     }
 
+    /// Apply `commands` to `session` without a terminal
+    ///
+    /// Renders a frame after each command and returns them, ending early at
+    /// [`HeadlessCommand::Quit`]. The updated session is written back to
+    /// `session`, as the TUI hands it back for saving when it exits.
+    pub fn run_headless(session: &mut Session, commands: &[HeadlessCommand]) -> Result<Vec<Buffer>> {
+        let mut app = Self::headless(session.clone(), 120, 40);
+        let mut frames = Vec::with_capacity(commands.len());
+
+        for command in commands {
+            app.apply_headless_command(command);
+            frames.push(app.render_buffer()?);
+            if app.state.should_quit {
+                break;
+            }
+        }
+
+        *session = app.get_session();
+        Ok(frames)
+    }
+
+    /// Record a walkthrough of a headless app's session as an asciicast v2 file
+    ///
+    /// Steps through every line of every file shown with
+    /// [`HeadlessCommand`]s, one frame per step at `fps` frames per second,
+    /// at the size given to [`App::headless`]. Play it back with
+    /// `asciinema play <path>`.
+    pub fn record_session_video(&mut self, path: &Path, fps: u8) -> Result<()> {
+        self.record_session_video_with_speed(path, fps, 1.0)
+    }
+
+    /// [`record_session_video`](Self::record_session_video) played back `speed` times faster
+    pub fn record_session_video_with_speed(&mut self, path: &Path, fps: u8, speed: f64) -> Result<()> {
+        if fps == 0 {
+            anyhow::bail!("Frame rate must be at least 1");
+        }
+        if !speed.is_finite() || speed <= 0.0 {
+            anyhow::bail!("Playback speed must be a positive number, got {}", speed);
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let (width, height) = self.headless_size;
+        let file = io::BufWriter::new(std::fs::File::create(path)?);
+        let mut cast = AsciicastWriter::new(file, width, height, fps, speed)?;
+
+        cast.write_frame(&self.render_buffer()?)?;
+        loop {
+            while self.state.current_line + 1 < self.current_file_line_count() {
+                self.apply_headless_command(&HeadlessCommand::MoveDown(1));
+                cast.write_frame(&self.render_buffer()?)?;
+            }
+            let file = self.state.current_file;
+            self.apply_headless_command(&HeadlessCommand::NextFile);
+            if self.state.current_file == file {
+                break;
+            }
+            cast.write_frame(&self.render_buffer()?)?;
+        }
+        cast.finish()?;
+        Ok(())
+    }
+
+    /// Apply one scripted step, as the matching keys would
+    fn apply_headless_command(&mut self, command: &HeadlessCommand) {
+        match command {
+            HeadlessCommand::MoveDown(n) => (0..*n).for_each(|_| self.move_down()),
+            HeadlessCommand::MoveUp(n) => (0..*n).for_each(|_| self.move_up()),
+            HeadlessCommand::NextFile => self.next_file(),
+            HeadlessCommand::AddComment { content, severity } => {
+                self.state.editor_content = content.clone();
+                self.state.is_file_comment = false;
+                self.add_comment(*severity);
+                self.state.editor_content.clear();
+            }
+            HeadlessCommand::DeleteComment => self.delete_comment_at_line(),
+            HeadlessCommand::Save => self.save_session(),
+            HeadlessCommand::Export(format) => {
+                if let Err(e) = self.export_current_session(format) {
+                    self.state.set_message(format!("Export failed: {}", e));
+                }
+            }
+            HeadlessCommand::Quit => self.state.should_quit = true,
+        }
+    }
+
     /// Draw the UI
     fn draw(&mut self) -> Result<()> {
-        let state = self.state.clone();
-        let files = &self.session.diff_data.files;
-        let comments = &self.session.comments;
-        let line_comments = &self.line_comments;
-        let highlighter = &self.highlighter;
+        self.update_derived_state();
+        if let Some(terminal) = &mut self.terminal {
+            terminal.draw(|frame| {
+                render_app(frame, &self.state, &self.session, &self.navigator, &self.highlighter)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Refresh state shown in the title and status bars
+    fn update_derived_state(&mut self) {
+        let position = Position::from_file_line(self.navigator.diff(), self.state.current_file, self.state.current_line)
+            .unwrap_or(Position { file_idx: self.state.current_file, ..Position::new() });
+        self.navigator.set_position(position);
+        if std::mem::take(&mut self.coverage_stale) {
+            self.state.coverage_percent = self.session.summary().coverage_percent;
+        }
+        if std::mem::take(&mut self.risk_stale) {
+            self.state.risk_level = Some(self.session.compute_risk_score_with(&self.state.risk_thresholds).level);
+        }
+        self.state.file_position = self
+            .current_file_id()
+            .and_then(|id| self.session.diff_data.file_position(&id));
+        self.state.bookmarked_lines = self
+            .navigator
+            .bookmarks()
+            .sorted()
+            .into_iter()
+            .rev()
+            .filter(|(_, pos)| pos.file_idx == self.state.current_file)
+            .map(|(name, pos)| (pos.file_line(&self.session.diff_data), name.to_string()))
+            .collect();
+        self.load_binary_preview();
+    }
+
+    /// Build the preview of the current file if it is binary and not yet previewed
+    ///
+    /// Git sources whose new side is not the working tree (staged changes,
+    /// commits, ranges and pull requests) read the blob from git. The rest
+    /// read the working tree; for sources other than `WorkingTree` (e.g.
+    /// `hg diff -r`, `svn diff -r`) the preview says so.
+    /// Patch files and stdin have no content to preview.
+    fn load_binary_preview(&mut self) {
+        if self.state.no_binary_preview {
+            return;
+        }
+        let Some(file) = self.session.diff_data.files.get(self.state.current_file) else {
+            return;
+        };
+        if !file.is_binary() || self.state.binary_previews.contains_key(&file.id) {
+            return;
+        }
+        let Some(path) = file.new_path.as_ref() else {
+            let lines = vec!["(Binary file deleted in this diff)".to_string()];
+            self.state.binary_previews.insert(file.id.clone(), lines);
+            return;
+        };
 
-        // Get current file
-        let current_file = files.get(state.current_file);
-        let file_count = files.len();
-        let session_id = self.session.id.to_string();
+        let root = self.repo_root();
+        let renderer = BinaryPreviewRenderer::new();
+        let blob_rev = match &self.session.diff_source {
+            DiffSource::Staged => Some(String::new()),
+            DiffSource::Commit { commit } => Some(commit.clone()),
+            DiffSource::CommitRange { to, .. } => Some(to.clone()),
+            DiffSource::PullRequest { .. } => Some("HEAD".to_string()),
+            _ => None,
+        };
+        let lines = match (&self.session.diff_source, blob_rev) {
+            (DiffSource::Patch { .. } | DiffSource::Stdin, _) => {
+                vec!["(Binary preview unavailable for this diff source)".to_string()]
+            }
+            (_, Some(rev)) => match GitLog::blob_in(&root, &rev, path) {
+                Ok(bytes) => renderer.render(path, &bytes),
+                Err(_) => vec!["(Binary file not found in git)".to_string()],
+            },
+            (source, None) => match renderer.render_file(&root.join(path)) {
+                Ok(mut lines) if !matches!(source, DiffSource::WorkingTree) => {
+                    lines.insert(0, "(Working tree copy)".to_string());
+                    lines
+                }
+                Ok(lines) => lines,
+                Err(_) => vec!["(Binary file not in the working tree)".to_string()],
+            },
+        };
+        self.state.binary_previews.insert(file.id.clone(), lines);
+    }
 
-        // Collect comments for rendering
-        let all_comments: Vec<_> = comments.all_sorted().into_iter().cloned().collect();
+    /// Directory the session's file paths are relative to
+    ///
+    /// The session's repository if recorded, otherwise the repository
+    /// containing the current directory. `svn diff` paths are relative to the
+    /// current directory itself.
+    fn repo_root(&self) -> PathBuf {
+        if let Some(repo) = &self.session.metadata.repository {
+            return repo.clone();
+        }
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let root = match self.session.diff_source {
+            DiffSource::Mercurial { .. } => HgDetector::find_root(&cwd),
+            DiffSource::Svn { .. } => None,
+            _ => cwd
+                .ancestors()
+                .find(|dir| dir.join(".git").exists())
+                .map(Path::to_path_buf),
+        };
+        root.unwrap_or(cwd)
+    }
 
-        self.terminal.draw(|frame| {
-            let area = frame.area();
+    /// Save the current screen as an SVG image
+    ///
+    /// Uses the terminal size, or the size given to [`App::headless`].
+    pub fn screenshot(&mut self, path: &Path) -> Result<()> {
+        if path.extension().is_some_and(|e| e != "svg") {
+            anyhow::bail!("Screenshots are saved as SVG: {}", path.display());
+        }
 
-            match state.mode {
-                AppMode::Help => render_help(frame, area),
-                AppMode::Insert => render_with_editor(frame, area, &state, current_file, file_count, &all_comments, line_comments, &session_id, highlighter),
-                AppMode::Normal => render_diff_only(frame, area, &state, current_file, file_count, &all_comments, line_comments, &session_id, highlighter),
+        let (width, height) = match &self.terminal {
+            Some(terminal) => {
+                let size = terminal.size()?;
+                (size.width, size.height)
             }
-        })?;
+            None => self.headless_size,
+        };
+
+        let buffer = self.render_to(width, height)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, buffer_to_svg(&buffer))?;
         Ok(())
     }
 
+    /// Render a headless app's screen
+    fn render_buffer(&mut self) -> Result<Buffer> {
+        let (width, height) = self.headless_size;
+        self.render_to(width, height)
+    }
+
+    /// Render the screen into an off-screen buffer of the given size
+    fn render_to(&mut self, width: u16, height: u16) -> Result<Buffer> {
+        self.update_derived_state();
+        let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+        terminal.draw(|frame| {
+            render_app(frame, &self.state, &self.session, &self.navigator, &self.highlighter)
+        })?;
+        Ok(terminal.backend().buffer().clone())
+    }
+
+    /// `Ctrl-P`: screenshot to `.cr-helper/screenshots/<session_id>-<timestamp>.svg`
+    fn take_screenshot(&mut self) {
+        let path = PathBuf::from(SCREENSHOT_DIR).join(format!(
+            "{}-{}.svg",
+            self.session.id,
+            chrono::Utc::now().format("%Y%m%d-%H%M%S")
+        ));
+        match self.screenshot(&path) {
+            Ok(()) => self
+                .state
+                .set_timed_message(format!("Screenshot saved to {}", path.display()), EXPORT_MESSAGE_DURATION),
+            Err(e) => self.state.set_message(format!("Screenshot failed: {}", e)),
+        }
+    }
+
+    /// Record the keys handled from now on as [`HeadlessCommand`]s
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(HeadlessRecorder::new());
+    }
+
+    /// Stop recording and return the commands (empty if not recording)
+    pub fn finish_recording(&mut self) -> Vec<HeadlessCommand> {
+        self.recorder.take().map(HeadlessRecorder::finish).unwrap_or_default()
+    }
+
     /// Handle keyboard input
     fn handle_input(&mut self, key: KeyEvent) -> Result<()> {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(key, &self.state);
+        }
         match self.state.mode {
             AppMode::Normal => self.handle_normal_input(key),
             AppMode::Insert => self.handle_insert_input(key),
-            AppMode::Help => self.handle_help_input(key),
+            AppMode::Help | AppMode::Assignments => self.handle_help_input(key),
+            AppMode::GotoFile => self.handle_goto_file_input(key),
+            AppMode::Command => self.handle_command_input(key),
+            AppMode::Search => self.handle_search_input(key),
+            AppMode::ThemePreview => self.handle_theme_preview_input(key),
+            AppMode::CommitLog => self.handle_commit_log_input(key),
+            AppMode::CommitDiff => self.handle_commit_diff_input(key),
+            AppMode::SessionPicker => self.handle_session_picker_input(key),
+        }
+    }
+
+    /// Clicking a commit in the sidebar opens it
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if !matches!(self.state.mode, AppMode::CommitLog | AppMode::CommitDiff)
+            || mouse.kind != MouseEventKind::Down(MouseButton::Left)
+        {
+            return;
+        }
+        let (width, height) = self.screen_size();
+        let (_, sidebar) = commit_log_layout(Rect::new(0, 0, width, height));
+        let inner = sidebar.inner(Margin::new(1, 1));
+        if inner.contains(ratatui::layout::Position::new(mouse.column, mouse.row)) {
+            let index = (mouse.row - inner.y) as usize;
+            if index < self.state.commit_log.len() {
+                self.state.commit_log_selected = index;
+                self.open_commit_diff();
+            }
+        }
+    }
+
+    /// `L`: show the commit history sidebar
+    fn open_commit_log(&mut self) {
+        if self.state.commit_log.is_empty() {
+            self.state.set_message("No commit history (start the review with --context-commit <sha>)");
+            return;
+        }
+        self.state.commit_log_selected = self
+            .state
+            .context_commit
+            .as_ref()
+            .and_then(|sha| self.state.commit_log.iter().position(|c| &c.sha == sha))
+            .unwrap_or(0);
+        self.state.mode = AppMode::CommitLog;
+    }
+
+    /// Select commits with `j`/`k`, open one with `Enter`, close with `L`/`Esc`
+    fn handle_commit_log_input(&mut self, key: KeyEvent) -> Result<()> {
+        let last = self.state.commit_log.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.state.commit_log_selected = (self.state.commit_log_selected + 1).min(last);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.state.commit_log_selected = self.state.commit_log_selected.saturating_sub(1);
+            }
+            KeyCode::Enter => self.open_commit_diff(),
+            KeyCode::Char('L') | KeyCode::Esc | KeyCode::Char('q') => self.state.mode = AppMode::Normal,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Show the selected sidebar commit's diff in place of the review
+    fn open_commit_diff(&mut self) {
+        let Some(commit) = self.state.commit_log.get(self.state.commit_log_selected) else {
+            return;
+        };
+        match GitLog::show(&commit.sha) {
+            Ok(output) => {
+                self.state.commit_diff = output.lines().map(str::to_string).collect();
+                self.state.commit_diff_scroll = 0;
+                self.state.mode = AppMode::CommitDiff;
+            }
+            Err(e) => self.state.set_message(format!("Failed to show commit {}: {}", commit.short_sha, e)),
+        }
+    }
+
+    /// Scroll a commit's diff; `Esc` goes back to the sidebar
+    fn handle_commit_diff_input(&mut self, key: KeyEvent) -> Result<()> {
+        let last = self.state.commit_diff.len().saturating_sub(1);
+        let scroll = &mut self.state.commit_diff_scroll;
+        match key.code {
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => *scroll = scroll.saturating_sub(20),
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => *scroll = (*scroll + 20).min(last),
+            KeyCode::Char('j') | KeyCode::Down => *scroll = (*scroll + 1).min(last),
+            KeyCode::Char('k') | KeyCode::Up => *scroll = scroll.saturating_sub(1),
+            KeyCode::Char('g') => *scroll = 0,
+            KeyCode::Char('G') => *scroll = last,
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('q') => self.state.mode = AppMode::CommitLog,
+            _ => {}
         }
+        Ok(())
     }
 
     /// Handle input in normal mode
     fn handle_normal_input(&mut self, key: KeyEvent) -> Result<()> {
+        let pending_g = std::mem::take(&mut self.state.pending_g);
+        if let Some(kind) = self.state.pending_mark.take() {
+            if let KeyCode::Char(name) = key.code {
+                match kind {
+                    'm' => self.set_bookmark(name),
+                    _ => self.goto_bookmark(name),
+                }
+            }
+            return Ok(());
+        }
         match key.code {
+            KeyCode::Char('f') if pending_g => self.open_goto_file(),
+
             KeyCode::Char('q') => self.state.should_quit = true,
             KeyCode::Char('?') => self.state.mode = AppMode::Help,
+            KeyCode::Char('A') => self.state.mode = AppMode::Assignments,
+            KeyCode::Char('L') => self.open_commit_log(),
+            KeyCode::Char(':') => {
+                self.state.mode = AppMode::Command;
+                self.state.command_input.clear();
+                self.state.clear_message();
+            }
+            KeyCode::Char('/') => {
+                self.state.mode = AppMode::Search;
+                self.state.search_query.clear();
+                self.state.clear_message();
+            }
+
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => self.take_screenshot(),
+
+            // Comment priority
+            KeyCode::Up if key.modifiers.contains(KeyModifiers::CONTROL) => self.move_comment_at_line(true),
+            KeyCode::Down if key.modifiers.contains(KeyModifiers::CONTROL) => self.move_comment_at_line(false),
 
             // Line navigation (vim-like)
             KeyCode::Char('j') | KeyCode::Down => self.move_down(),
             KeyCode::Char('k') | KeyCode::Up => self.move_up(),
-            KeyCode::Char('g') => self.goto_top(),
+            KeyCode::Char('g') if pending_g => self.goto_top(),
+            KeyCode::Char('g') => self.state.pending_g = true,
             KeyCode::Char('G') => self.goto_bottom(),
 
+            // Bookmarks (vim-like marks)
+            KeyCode::Char('m') => self.state.pending_mark = Some('m'),
+            KeyCode::Char('\'') => self.state.pending_mark = Some('\''),
+
             // File navigation
+            KeyCode::Char('1') => self.toggle_file_mode(FileMode::Added),
+            KeyCode::Char('2') => self.toggle_file_mode(FileMode::Deleted),
+            KeyCode::Char('3') => self.toggle_file_mode(FileMode::Modified),
+            KeyCode::Char('4') => self.toggle_file_mode(FileMode::Renamed),
+            KeyCode::Char('0') => self.set_file_mode_filter(Vec::new()),
             KeyCode::Char('n') => self.next_file(),
             KeyCode::Char('N') => self.prev_file(),
             KeyCode::Char(']') if key.modifiers.contains(KeyModifiers::NONE) => self.next_comment(),
@@ -242,13 +878,43 @@ impl App {
             KeyCode::Char('x') => self.delete_comment_at_line(),
 
             // Session
-            KeyCode::Char('s') => self.state.set_message("Session saved"),
+            KeyCode::Char('s') => self.save_session(),
+
+            // Open the current line in $VISUAL / $EDITOR
+            KeyCode::Char('e') => self.open_in_editor()?,
+
+            // Page through the export without writing it
+            KeyCode::Char('P') => self.preview_export()?,
+
+            // Export without leaving the TUI
+            KeyCode::Char('E') => {
+                let format = self.state.export_format.clone();
+                let format = format.as_deref().unwrap_or("markdown");
+                if let Err(e) = self.export_current_session(format) {
+                    self.state.set_message(format!("Export failed: {}", e));
+                }
+            }
 
             _ => {}
         }
         Ok(())
     }
 
+    /// Insert pasted text at the editor cursor
+    ///
+    /// The comment editor is single-line, so pasted lines are joined with
+    /// spaces. Pastes outside insert mode are ignored.
+    pub fn handle_paste(&mut self, text: &str) -> Result<()> {
+        if self.state.mode != AppMode::Insert {
+            return Ok(());
+        }
+        let text = text.lines().collect::<Vec<_>>().join(" ");
+        let byte_pos = self.char_to_byte_pos(self.state.editor_cursor);
+        self.state.editor_content.insert_str(byte_pos, &text);
+        self.state.editor_cursor += text.chars().count();
+        Ok(())
+    }
+
     /// Handle input in insert mode
     fn handle_insert_input(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
@@ -259,7 +925,7 @@ impl App {
             }
             KeyCode::Enter => {
                 if !self.state.editor_content.trim().is_empty() {
-                    self.add_comment();
+                    self.add_comment(Severity::Info);
                 }
                 self.state.mode = AppMode::Normal;
                 self.state.editor_content.clear();
@@ -296,94 +962,750 @@ impl App {
         Ok(())
     }
 
-    /// Convert character position to byte position
-    fn char_to_byte_pos(&self, char_pos: usize) -> usize {
-        self.state.editor_content
-            .char_indices()
-            .nth(char_pos)
-            .map(|(i, _)| i)
-            .unwrap_or(self.state.editor_content.len())
-    }
-
-    /// Handle input in help mode
-    fn handle_help_input(&mut self, _key: KeyEvent) -> Result<()> {
-        self.state.mode = AppMode::Normal;
+    /// Handle input in the go-to-file prompt
+    fn handle_goto_file_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc => self.state.mode = AppMode::Normal,
+            KeyCode::Enter => {
+                // Prefer an exact/suffix match, then the best fuzzy candidate
+                let query = self.state.goto_query.clone();
+                let found = self.goto_file_path(&query)
+                    || match self.state.goto_candidates.first().cloned() {
+                        Some(candidate) => self.goto_file_path(&candidate),
+                        None => false,
+                    };
+                if !found {
+                    self.state.set_message(format!("No file matching '{}'", query));
+                }
+                self.state.mode = AppMode::Normal;
+            }
+            KeyCode::Tab => {
+                // Complete to the best match
+                if let Some(first) = self.state.goto_candidates.first().cloned() {
+                    self.state.goto_query = first;
+                    self.update_goto_candidates();
+                }
+            }
+            KeyCode::Char(c) => {
+                self.state.goto_query.push(c);
+                self.update_goto_candidates();
+            }
+            KeyCode::Backspace => {
+                self.state.goto_query.pop();
+                self.update_goto_candidates();
+            }
+            _ => {}
+        }
         Ok(())
     }
 
-    /// Get total lines in current file
-    fn current_file_line_count(&self) -> usize {
-        self.session.diff_data.files
-            .get(self.state.current_file)
-            .map(|f| f.total_lines())
-            .unwrap_or(0)
-    }
-
-    fn move_down(&mut self) {
-        let max = self.current_file_line_count().saturating_sub(1);
-        if self.state.current_line < max {
-            self.state.current_line += 1;
-            self.ensure_visible();
+    /// Handle input in the comment search prompt
+    fn handle_search_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc => self.state.mode = AppMode::Normal,
+            KeyCode::Enter => {
+                self.state.mode = AppMode::Normal;
+                let query = self.state.search_query.clone();
+                self.search_comments(&query);
+            }
+            KeyCode::Char(c) => self.state.search_query.push(c),
+            KeyCode::Backspace => {
+                self.state.search_query.pop();
+            }
+            _ => {}
         }
+        Ok(())
     }
 
-    fn move_up(&mut self) {
-        if self.state.current_line > 0 {
-            self.state.current_line -= 1;
-            self.ensure_visible();
+    /// Search comment content and jump to the first match
+    ///
+    /// A leading `--regex` or `--fuzzy` selects the `SearchMode`.
+    fn search_comments(&mut self, input: &str) {
+        let (mode, query) = if let Some(rest) = input.strip_prefix("--regex ") {
+            (SearchMode::Regex, rest.trim())
+        } else if let Some(rest) = input.strip_prefix("--fuzzy ") {
+            (SearchMode::Fuzzy, rest.trim())
+        } else {
+            (SearchMode::Substring, input.trim())
+        };
+        self.state.search_mode = mode;
+        if query.is_empty() {
+            return;
         }
-    }
 
-    fn page_up(&mut self) {
-        self.state.current_line = self.state.current_line.saturating_sub(20);
-        self.ensure_visible();
-    }
+        let comments = &self.session.comments;
+        let results: Vec<CommentId> = match mode {
+            SearchMode::Substring => comments.search(query).into_iter().map(|c| c.id.clone()).collect(),
+            SearchMode::Regex => match comments.search_by_regex(query) {
+                Ok(found) => found.into_iter().map(|c| c.id.clone()).collect(),
+                Err(e) => {
+                    self.state.set_message(format!("{}", e));
+                    return;
+                }
+            },
+            SearchMode::Fuzzy => comments
+                .fuzzy_search_content(query, FUZZY_SEARCH_THRESHOLD)
+                .into_iter()
+                .map(|(c, _)| c.id.clone())
+                .collect(),
+        };
+        self.state.search_results = results;
 
-    fn page_down(&mut self) {
-        let max = self.current_file_line_count().saturating_sub(1);
-        self.state.current_line = (self.state.current_line + 20).min(max);
-        self.ensure_visible();
+        let Some(first) = self.state.search_results.first().cloned() else {
+            self.state.set_message(format!("No comments matching '{}'", query));
+            return;
+        };
+        self.jump_to_comment(&first);
+        self.state
+            .set_message(format!("Match 1/{}", self.state.search_results.len()));
     }
 
-    fn ensure_visible(&mut self) {
-        // Keep cursor visible in viewport
-        let viewport_height = 20; // approximate
-        if self.state.current_line < self.state.scroll_offset {
-            self.state.scroll_offset = self.state.current_line;
-        } else if self.state.current_line >= self.state.scroll_offset + viewport_height {
-            self.state.scroll_offset = self.state.current_line - viewport_height + 1;
+    /// Move the cursor to the first line of a comment
+    fn jump_to_comment(&mut self, id: &CommentId) {
+        let Some(comment) = self.session.comments.get(id) else {
+            return;
+        };
+        let file_id = comment.file_id().clone();
+        let line_id = comment.line_ids()[0].clone();
+        self.select_file(&file_id);
+        let line_idx = self
+            .session
+            .diff_data
+            .files
+            .get(self.state.current_file)
+            .and_then(|f| f.hunks.iter().flat_map(|h| &h.lines).position(|l| l.id == line_id));
+        if let Some(idx) = line_idx {
+            self.state.current_line = idx;
+            self.ensure_visible();
         }
     }
 
-    fn next_file(&mut self) {
-        if self.state.current_file < self.session.diff_data.files.len().saturating_sub(1) {
-            self.state.current_file += 1;
-            self.state.current_line = 0;
-            self.state.scroll_offset = 0;
-            self.load_current_file();
+    /// Handle input in the command palette
+    fn handle_command_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc => self.state.mode = AppMode::Normal,
+            KeyCode::Enter => {
+                self.state.mode = AppMode::Normal;
+                match CommandParser::parse(&self.state.command_input) {
+                    Ok(command) => self.execute_command(command),
+                    Err(e) => self.state.set_message(format!("{}", e)),
+                }
+            }
+            KeyCode::Tab => {
+                let completions = CommandParser::completions(&self.state.command_input);
+                if let Some(first) = completions.first() {
+                    self.state.command_input = first.to_string();
+                }
+            }
+            KeyCode::Char(c) => self.state.command_input.push(c),
+            KeyCode::Backspace if self.state.command_input.is_empty() => {
+                self.state.mode = AppMode::Normal;
+            }
+            KeyCode::Backspace => {
+                self.state.command_input.pop();
+            }
+            _ => {}
         }
+        Ok(())
     }
 
-    fn prev_file(&mut self) {
-        if self.state.current_file > 0 {
-            self.state.current_file -= 1;
-            self.state.current_line = 0;
-            self.state.scroll_offset = 0;
-            self.load_current_file();
+    /// Run a palette command
+    fn execute_command(&mut self, command: Command) {
+        match command {
+            Command::Save => self.save_session(),
+            Command::Quit => self.state.should_quit = true,
+            Command::Help => self.state.mode = AppMode::Help,
+            Command::Export { format, path } => self.export_session(&format, path),
+            Command::SetTheme(name) => match self.state.themes.set(&name) {
+                Ok(()) => self.state.set_message(format!("Theme set to {}", name)),
+                Err(e) => self.state.set_message(e.to_string()),
+            },
+            Command::PreviewTheme => {
+                self.state.theme_preview = THEME_NAMES
+                    .iter()
+                    .position(|n| *n == self.state.themes.name())
+                    .unwrap_or(0);
+                self.state.mode = AppMode::ThemePreview;
+            }
+            Command::Goto(path) => {
+                if !self.goto_file_path(&path) {
+                    self.state.set_message(format!("No file matching '{}'", path));
+                }
+            }
+            Command::Session(SessionAction::New) => self.new_session(),
+            Command::Session(SessionAction::List) => self.open_session_picker(),
+            Command::Marks => self.show_bookmarks(),
+            Command::FilterSeverity(filter) => {
+                self.state.severity_filter = filter;
+                match filter {
+                    Some(severity) => self.state.set_message(format!("Showing {} comments", severity)),
+                    None => self.state.set_message("Showing all comments"),
+                }
+            }
         }
     }
 
-    /// Load current file if it's lazy
-    fn load_current_file(&mut self) {
-        if let Some(file) = self.session.diff_data.files.get_mut(self.state.current_file) {
-            if file.needs_loading() {
-                if let Err(e) = self.parser.load_lazy_file(file) {
-                    self.state.set_message(format!("Failed to load file: {}", e));
+    /// `:session new`: save this session and start a new one from the same source
+    ///
+    /// Git sources are diffed again; other sources reuse the current diff.
+    fn new_session(&mut self) {
+        let Some(manager) = &self.manager else {
+            self.state.set_message("Sessions are not available in this view");
+            return;
+        };
+        let current = self.get_session();
+        let created = match current.diff_source.to_parser_source() {
+            Some(_) => manager.create_from_git(current.diff_source.clone(), &ParserConfig::default()),
+            None => manager.create(current.diff_source.clone(), current.diff_data.clone()),
+        };
+        match created {
+            Ok(session) => {
+                let id = session.id.short().to_string();
+                if self.switch_session(session) {
+                    self.state.set_message(format!("Started session {}", id));
                 }
             }
+            Err(e) => self.state.set_message(format!("Failed to create session: {}", e)),
         }
     }
 
+    /// `:session list`: pick a session to switch to
+    fn open_session_picker(&mut self) {
+        let Some(manager) = &self.manager else {
+            self.state.set_message("Sessions are not available in this view");
+            return;
+        };
+        match manager.list_sorted(SessionSortOrder::newest_first(), None) {
+            Ok(sessions) => {
+                self.state.session_list_selected = sessions
+                    .iter()
+                    .position(|s| s.id == self.session.id)
+                    .unwrap_or(0);
+                self.state.session_list = sessions;
+                self.state.mode = AppMode::SessionPicker;
+            }
+            Err(e) => self.state.set_message(format!("Failed to list sessions: {}", e)),
+        }
+    }
+
+    /// Move through the session picker; `Enter` switches to the selected session
+    fn handle_session_picker_input(&mut self, key: KeyEvent) -> Result<()> {
+        let last = self.state.session_list.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.state.session_list_selected = (self.state.session_list_selected + 1).min(last);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.state.session_list_selected = self.state.session_list_selected.saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                self.state.mode = AppMode::Normal;
+                let Some(id) = self
+                    .state
+                    .session_list
+                    .get(self.state.session_list_selected)
+                    .map(|s| s.id.clone())
+                else {
+                    return Ok(());
+                };
+                if id == self.session.id {
+                    return Ok(());
+                }
+                let loaded = match &self.manager {
+                    Some(manager) => manager.load(&id),
+                    None => return Ok(()),
+                };
+                match loaded {
+                    Ok(session) => {
+                        if self.switch_session(session) {
+                            self.state.set_message(format!("Switched to session {}", id.short()));
+                        }
+                    }
+                    Err(e) => self.state.set_message(format!("Failed to load session {}: {}", id.short(), e)),
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.state.mode = AppMode::Normal,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Save the current session, then review `session` in its place
+    ///
+    /// Keeps the terminal and review settings (theme, export, filters).
+    /// Returns `false`, leaving the current session open, if saving fails.
+    fn switch_session(&mut self, session: Session) -> bool {
+        let Some(manager) = self.manager.take() else {
+            return false;
+        };
+        let mut current = self.get_session();
+        if let Err(e) = manager.save(&mut current) {
+            self.state.set_message(format!("Failed to save session: {}", e));
+            self.manager = Some(manager);
+            return false;
+        }
+
+        let has_checkpoints = manager.checkpoints(&session.id).is_ok_and(|c| !c.is_empty());
+        let mut app = Self::build(session, self.state.config.clone(), self.terminal.take());
+        app.headless_size = self.headless_size;
+        app.manager = Some(manager);
+        let old = std::mem::take(&mut self.state);
+        app.state.themes = old.themes;
+        app.state.has_checkpoints = has_checkpoints;
+        app.state.export_format = old.export_format;
+        app.state.export_on_save = old.export_on_save;
+        app.state.no_binary_preview = old.no_binary_preview;
+        app.set_risk_thresholds(old.risk_thresholds);
+        app.state.severity_keywords = old.severity_keywords;
+        app.state.severity_filter = old.severity_filter;
+        app.state.commit_log = old.commit_log;
+        app.state.context_commit = old.context_commit;
+        if !old.file_mode_filter.is_empty() {
+            app.set_file_mode_filter(old.file_mode_filter);
+        }
+        *self = app;
+        true
+    }
+
+    /// `m<char>`: bookmark the current line
+    fn set_bookmark(&mut self, name: char) {
+        let diff = &self.session.diff_data;
+        let Some(position) = Position::from_file_line(diff, self.state.current_file, self.state.current_line) else {
+            self.state.set_message("Nothing to bookmark here");
+            return;
+        };
+        self.navigator.set_position(position);
+        self.navigator.bookmark(&name.to_string());
+
+        // Keep marks on files this review doesn't show
+        let mut bookmarks = self.session.bookmarks();
+        bookmarks.retain(|_, (id, _)| self.session.diff_data.file_position(id).is_none());
+        bookmarks.extend(self.navigator.bookmarks().sorted().into_iter().filter_map(|(name, pos)| {
+            let file = self.session.diff_data.files.get(pos.file_idx)?;
+            Some((name.to_string(), (file.id.clone(), pos.file_line(&self.session.diff_data))))
+        }));
+        self.session.set_bookmarks(&bookmarks);
+        self.state.set_message(format!("Mark '{}' set", name));
+    }
+
+    /// `'<char>`: jump to a bookmark
+    fn goto_bookmark(&mut self, name: char) {
+        if !self.navigator.goto_bookmark(&name.to_string()) {
+            self.state.set_message(format!("Mark '{}' not set", name));
+            return;
+        }
+        let position = self.navigator.current_position();
+        self.state.current_file = position.file_idx;
+        self.load_current_file();
+        self.state.current_line = position.file_line(&self.session.diff_data);
+        self.ensure_visible();
+    }
+
+    /// `:marks`: list bookmarks in the status bar
+    fn show_bookmarks(&mut self) {
+        let marks: Vec<String> = self
+            .navigator
+            .bookmarks()
+            .sorted()
+            .into_iter()
+            .filter_map(|(name, pos)| {
+                let file = self.session.diff_data.files.get(pos.file_idx)?;
+                let line = file.hunks.get(pos.hunk_idx)?.lines.get(pos.line_idx)?;
+                let line_num = line.new_line_num.or(line.old_line_num).unwrap_or(0);
+                Some(format!("{} {}:{}", name, file.display_path().display(), line_num))
+            })
+            .collect();
+        if marks.is_empty() {
+            self.state.set_message("No marks set");
+        } else {
+            self.state.set_message(format!("Marks: {}", marks.join("  ")));
+        }
+    }
+
+    /// Load bookmarks saved in the session
+    fn restore_bookmarks(&mut self) {
+        for (name, (file_id, line)) in self.session.bookmarks() {
+            let Some((file_idx, _)) = self.session.diff_data.file_position(&file_id) else {
+                continue;
+            };
+            if self.load_file(file_idx).is_err() {
+                continue;
+            }
+            if let Some(position) = Position::from_file_line(&self.session.diff_data, file_idx, line) {
+                self.navigator.set_position(position);
+                self.navigator.bookmark(&name);
+            }
+        }
+        self.navigator.set_position(Position::new());
+    }
+
+    fn save_session(&mut self) {
+        self.state.set_message("Session saved");
+        if self.state.export_on_save {
+            let format = self.state.export_format.clone();
+            if let Err(e) = self.export_current_session(format.as_deref().unwrap_or("markdown")) {
+                self.state.set_message(format!("Session saved, export failed: {}", e));
+            }
+        }
+    }
+
+    /// Write the review lock for the current directory
+    ///
+    /// The lock is removed when the returned guard is dropped.
+    pub fn acquire_lock(&self) -> Result<LockFile> {
+        LockFile::acquire(&std::env::current_dir()?)
+    }
+
+    /// Export the session to `cr-helper-export-<id>.<ext>` in the temp directory
+    ///
+    /// Shows the path in the status bar for a few seconds.
+    pub fn export_current_session(&mut self, format: &str) -> Result<()> {
+        use cr_core::export::ExportManager;
+
+        let manager = ExportManager::new();
+        let exporter = manager
+            .get(format)
+            .ok_or_else(|| anyhow::anyhow!("unknown export format: {}", format))?;
+        let path = std::env::temp_dir().join(format!(
+            "cr-helper-export-{}.{}",
+            self.session.id,
+            exporter.file_extension()
+        ));
+
+        manager.export_to_file(&self.session, format, &path)?;
+        self.state
+            .set_timed_message(format!("Exported to {}", path.display()), EXPORT_MESSAGE_DURATION);
+        Ok(())
+    }
+
+    fn export_session(&mut self, format: &str, path: Option<String>) {
+        use cr_core::export::ExportManager;
+
+        let manager = ExportManager::new();
+        let Some(exporter) = manager.get(format) else {
+            self.state.set_message(format!("Unknown export format: {}", format));
+            return;
+        };
+
+        let path = path.map(std::path::PathBuf::from).unwrap_or_else(|| {
+            std::path::PathBuf::from(".cr-helper/exports")
+                .join(self.session.id.to_string())
+                .with_extension(exporter.file_extension())
+        });
+
+        match manager.export_to_file(&self.session, format, &path) {
+            Ok(()) => self.state.set_message(format!("Exported to {}", path.display())),
+            Err(e) => self.state.set_message(format!("Export failed: {}", e)),
+        }
+    }
+
+    fn open_goto_file(&mut self) {
+        self.state.mode = AppMode::GotoFile;
+        self.state.goto_query.clear();
+        self.state.clear_message();
+        self.update_goto_candidates();
+    }
+
+    fn update_goto_candidates(&mut self) {
+        let query = &self.state.goto_query;
+        self.state.goto_candidates = self
+            .session
+            .diff_data
+            .files
+            .iter()
+            .map(|f| f.display_path().to_string_lossy().into_owned())
+            .filter(|p| fuzzy_match(p, query))
+            .collect();
+    }
+
+    /// Jump to a file by path (exact or suffix match)
+    pub fn goto_file_path(&mut self, path: &str) -> bool {
+        if !self.navigator.goto_file_by_path(path) {
+            return false;
+        }
+        self.state.current_file = self.navigator.current_position().file_idx;
+        self.state.current_line = 0;
+        self.state.scroll_offset = 0;
+        self.load_current_file();
+        true
+    }
+
+    /// Convert character position to byte position
+    fn char_to_byte_pos(&self, char_pos: usize) -> usize {
+        self.state.editor_content
+            .char_indices()
+            .nth(char_pos)
+            .map(|(i, _)| i)
+            .unwrap_or(self.state.editor_content.len())
+    }
+
+    /// Handle input in help mode
+    fn handle_help_input(&mut self, _key: KeyEvent) -> Result<()> {
+        self.state.mode = AppMode::Normal;
+        Ok(())
+    }
+
+    /// Cycle candidate themes with Left/Right, apply with Enter
+    fn handle_theme_preview_input(&mut self, key: KeyEvent) -> Result<()> {
+        let count = THEME_NAMES.len();
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.state.theme_preview = (self.state.theme_preview + count - 1) % count;
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.state.theme_preview = (self.state.theme_preview + 1) % count;
+            }
+            KeyCode::Enter => {
+                let name = THEME_NAMES[self.state.theme_preview];
+                self.execute_command(Command::SetTheme(name.to_string()));
+                self.state.mode = AppMode::Normal;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.state.mode = AppMode::Normal,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Get total lines in current file
+    fn current_file_line_count(&self) -> usize {
+        self.session.diff_data.files
+            .get(self.state.current_file)
+            .map(|f| f.total_lines())
+            .unwrap_or(0)
+    }
+
+    fn move_down(&mut self) {
+        let max = self.current_file_line_count().saturating_sub(1);
+        if self.state.current_line < max {
+            self.state.current_line += 1;
+            self.ensure_visible();
+        }
+    }
+
+    fn move_up(&mut self) {
+        if self.state.current_line > 0 {
+            self.state.current_line -= 1;
+            self.ensure_visible();
+        }
+    }
+
+    fn page_up(&mut self) {
+        self.state.current_line = self.state.current_line.saturating_sub(20);
+        self.ensure_visible();
+    }
+
+    fn page_down(&mut self) {
+        let max = self.current_file_line_count().saturating_sub(1);
+        self.state.current_line = (self.state.current_line + 20).min(max);
+        self.ensure_visible();
+    }
+
+    /// Scroll so the cursor has `scroll_padding` lines above and below it
+    fn ensure_visible(&mut self) {
+        let viewport_height = self.diff_viewport_height();
+        let padding = self.state.config.scroll_padding.min(viewport_height.saturating_sub(1) / 2);
+        let line = self.state.current_line;
+        if line < self.state.scroll_offset + padding {
+            self.state.scroll_offset = line.saturating_sub(padding);
+        } else if line + padding >= self.state.scroll_offset + viewport_height {
+            self.state.scroll_offset = line + padding + 1 - viewport_height;
+        }
+        if self.state.config.word_wrap {
+            self.fit_wrapped_cursor(viewport_height, padding);
+        }
+    }
+
+    /// With `word_wrap`, scroll further until the screen rows from the top
+    /// of the view through `padding` rows below the cursor fit the viewport
+    ///
+    /// Rows are wrapped the way the diff view's paragraph wraps them.
+    fn fit_wrapped_cursor(&mut self, viewport_height: usize, padding: usize) {
+        let width = self.screen_size().0.saturating_sub(2).max(1);
+        let file_path = self
+            .navigator
+            .current_file()
+            .map(|f| f.display_path().to_string_lossy().to_string())
+            .unwrap_or_default();
+        let rows = self.navigator.virtual_scroll_lines(
+            self.state.scroll_offset,
+            2 * viewport_height,
+            &self.session.comments,
+        );
+        let cursor = self.state.current_line;
+        let Some(cursor_row) = rows
+            .iter()
+            .position(|row| matches!(row, VirtualLine::DiffLine(idx, _) if *idx == cursor))
+        else {
+            return;
+        };
+
+        let end = (cursor_row + padding + 1).min(rows.len());
+        let heights: Vec<usize> = rows[..end]
+            .iter()
+            .map(|row| {
+                render_virtual_line(&self.state, row, &file_path, &self.highlighter)
+                    .map_or(0, |line| Paragraph::new(line).wrap(Wrap { trim: false }).line_count(width))
+            })
+            .collect();
+        let mut total: usize = heights.iter().sum();
+        for height in &heights[..cursor_row] {
+            if total <= viewport_height {
+                break;
+            }
+            total -= height;
+            self.state.scroll_offset += 1;
+        }
+    }
+
+    /// Rows inside the diff view's borders, below the title bar and mode
+    /// tabs and above the status bar
+    fn diff_viewport_height(&self) -> usize {
+        let (_, height) = self.screen_size();
+        height.saturating_sub(4 + mode_tabs_height(&self.state)).max(1) as usize
+    }
+
+    /// Terminal size, or the size given to [`App::headless`]
+    fn screen_size(&self) -> (u16, u16) {
+        match &self.terminal {
+            Some(terminal) => terminal
+                .size()
+                .map(|s| (s.width, s.height))
+                .unwrap_or(self.headless_size),
+            None => self.headless_size,
+        }
+    }
+
+    /// Move to the next file the mode filter shows
+    fn next_file(&mut self) {
+        let next = self
+            .session
+            .diff_data
+            .files
+            .iter()
+            .skip(self.state.current_file + 1)
+            .find(|f| self.state.shows_file_mode(f.mode))
+            .map(|f| f.id.clone());
+        if let Some(id) = next {
+            self.select_file(&id);
+        }
+    }
+
+    /// Move to the previous file the mode filter shows
+    fn prev_file(&mut self) {
+        let prev = self
+            .session
+            .diff_data
+            .files
+            .iter()
+            .take(self.state.current_file)
+            .rev()
+            .find(|f| self.state.shows_file_mode(f.mode))
+            .map(|f| f.id.clone());
+        if let Some(id) = prev {
+            self.select_file(&id);
+        }
+    }
+
+    /// Only visit files with these modes, moving off a hidden current file
+    pub fn set_file_mode_filter(&mut self, modes: Vec<FileMode>) {
+        self.state.file_mode_filter = modes;
+        let current_shown = self
+            .session
+            .diff_data
+            .files
+            .get(self.state.current_file)
+            .is_some_and(|f| self.state.shows_file_mode(f.mode));
+        if current_shown {
+            return;
+        }
+        let first = self
+            .session
+            .diff_data
+            .files
+            .iter()
+            .find(|f| self.state.shows_file_mode(f.mode))
+            .map(|f| f.id.clone());
+        match first {
+            Some(id) => self.select_file(&id),
+            None => self.state.set_message("No files match the mode filter"),
+        }
+    }
+
+    /// Turn one mode of the file filter on or off (keys 1-4)
+    fn toggle_file_mode(&mut self, mode: FileMode) {
+        let mut modes = self.state.file_mode_filter.clone();
+        match modes.iter().position(|m| *m == mode) {
+            Some(idx) => {
+                modes.remove(idx);
+            }
+            None => modes.push(mode),
+        }
+        self.set_file_mode_filter(modes);
+    }
+
+    fn current_file_id(&self) -> Option<FileId> {
+        self.session
+            .diff_data
+            .files
+            .get(self.state.current_file)
+            .map(|f| f.id.clone())
+    }
+
+    /// Move to the top of a file
+    fn select_file(&mut self, id: &FileId) {
+        if let Some((idx, _)) = self.session.diff_data.file_position(id) {
+            self.state.current_file = idx;
+            self.state.current_line = 0;
+            self.state.scroll_offset = 0;
+            self.load_current_file();
+        }
+    }
+
+    /// Load current file if it's lazy
+    fn load_current_file(&mut self) {
+        if let Err(e) = self.load_file(self.state.current_file) {
+            self.state.set_message(format!("Failed to load file: {}", e));
+        }
+    }
+
+    /// Load a lazy file in the navigator's diff and copy it to the session's
+    ///
+    /// Both stay in step, so the renderer, bookmarks and line jumps all see
+    /// the loaded hunks.
+    fn load_file(&mut self, idx: usize) -> Result<()> {
+        let Some(file) = self.navigator.diff_mut().files.get_mut(idx) else {
+            return Ok(());
+        };
+        if !file.needs_loading() {
+            return Ok(());
+        }
+        self.parser.load_lazy_file(file)?;
+        if let (Some(loaded), Some(shown)) = (
+            self.navigator.diff().files.get(idx),
+            self.session.diff_data.files.get_mut(idx),
+        ) {
+            *shown = loaded.clone();
+        }
+        let diff = self.navigator.diff_mut();
+        diff.stats = DiffStats::from_diff(diff);
+        self.session.diff_data.stats = DiffStats::from_diff(&self.session.diff_data);
+        self.refresh_file_stats();
+        self.coverage_stale = true;
+        self.risk_stale = true;
+        Ok(())
+    }
+
+    /// Rebuild the title bar's per-file counts from the session's [`DiffStats`]
+    fn refresh_file_stats(&mut self) {
+        let file_stats = self.session.diff_data.file_stats();
+        let total_changes: usize = file_stats.iter().map(FileStat::changes).sum();
+        self.state.file_stat_bars = file_stats
+            .iter()
+            .map(|f| (f.file_id.clone(), f.stat_bar(TITLE_STAT_BAR_WIDTH, total_changes)))
+            .collect();
+        self.state.file_stats = file_stats.iter().map(|f| (f.file_id.clone(), f.clone())).collect();
+    }
+
     fn goto_top(&mut self) {
         self.state.current_line = 0;
         self.state.scroll_offset = 0;
@@ -397,19 +1719,18 @@ impl App {
     fn next_comment(&mut self) {
         // Jump to next line with comment
         if let Some(file) = self.session.diff_data.files.get(self.state.current_file) {
-            let file_comments = self.line_comments.get(&file.id);
-            if let Some(fc) = file_comments {
-                let mut line_idx = 0;
-                for hunk in &file.hunks {
-                    for line in &hunk.lines {
-                        if line_idx > self.state.current_line && fc.contains_key(&line.id) {
-                            self.state.current_line = line_idx;
-                            self.ensure_visible();
-                            return;
-                        }
-                        line_idx += 1;
-                    }
-                }
+            let next = file
+                .hunks
+                .iter()
+                .flat_map(|h| &h.lines)
+                .enumerate()
+                .skip(self.state.current_line + 1)
+                .find(|(_, line)| self.session.comments.count_by_line(&line.id) > 0)
+                .map(|(idx, _)| idx);
+            if let Some(idx) = next {
+                self.state.current_line = idx;
+                self.ensure_visible();
+                return;
             }
         }
         self.state.set_message("No more comments");
@@ -418,30 +1739,114 @@ impl App {
     fn prev_comment(&mut self) {
         // Jump to previous line with comment
         if let Some(file) = self.session.diff_data.files.get(self.state.current_file) {
-            let file_comments = self.line_comments.get(&file.id);
-            if let Some(fc) = file_comments {
-                let mut line_idx = 0;
-                let mut last_comment_line = None;
-                for hunk in &file.hunks {
-                    for line in &hunk.lines {
-                        if line_idx < self.state.current_line && fc.contains_key(&line.id) {
-                            last_comment_line = Some(line_idx);
-                        }
-                        line_idx += 1;
-                    }
-                }
-                if let Some(idx) = last_comment_line {
-                    self.state.current_line = idx;
-                    self.ensure_visible();
-                    return;
-                }
+            let prev = file
+                .hunks
+                .iter()
+                .flat_map(|h| &h.lines)
+                .enumerate()
+                .take(self.state.current_line)
+                .filter(|(_, line)| self.session.comments.count_by_line(&line.id) > 0)
+                .map(|(idx, _)| idx)
+                .last();
+            if let Some(idx) = prev {
+                self.state.current_line = idx;
+                self.ensure_visible();
+                return;
             }
         }
         self.state.set_message("No previous comments");
     }
 
-    fn add_comment(&mut self) {
-        use cr_core::comment::builder::CommentBuilder;
+    /// Suspend the TUI and open the current file in the external editor
+    fn open_in_editor(&mut self) -> Result<()> {
+        let Some(terminal) = self.terminal.as_mut() else {
+            return Ok(());
+        };
+        let Some(file) = self.session.diff_data.files.get(self.state.current_file) else {
+            return Ok(());
+        };
+        let Some(path) = file.new_path.clone() else {
+            self.state.set_message("File was deleted");
+            return Ok(());
+        };
+        let line_num = file
+            .hunks
+            .iter()
+            .flat_map(|h| &h.lines)
+            .nth(self.state.current_line)
+            .and_then(|l| l.new_line_num.or(l.old_line_num))
+            .unwrap_or(1);
+
+        let editor = EditorCommand::from_env();
+
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste)?;
+        let status = editor.command(&path, line_num).status();
+        enable_raw_mode()?;
+        execute!(terminal.backend_mut(), EnterAlternateScreen, EnableBracketedPaste)?;
+        if self.state.config.mouse {
+            execute!(terminal.backend_mut(), EnableMouseCapture)?;
+        }
+        terminal.clear()?;
+
+        match status {
+            Ok(status) if status.success() => self.state.clear_message(),
+            Ok(status) => self.state.set_message(format!("{} exited with {}", editor.program, status)),
+            Err(e) => self.state.set_message(format!("Failed to run {}: {}", editor.program, e)),
+        }
+        Ok(())
+    }
+
+    /// Suspend the TUI and page through the export in `less -R`
+    ///
+    /// Nothing is written to disk. The built-in formats have no color, so
+    /// escape sequences (e.g. pasted into comments) are stripped first.
+    fn preview_export(&mut self) -> Result<()> {
+        use cr_core::export::ExportManager;
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let format = self.state.export_format.clone();
+        let format = format.as_deref().unwrap_or("markdown");
+        let content = match ExportManager::new().export(&self.session, format) {
+            Ok(content) => strip_ansi(&content),
+            Err(e) => {
+                self.state.set_message(format!("Export failed: {}", e));
+                return Ok(());
+            }
+        };
+        let Some(terminal) = self.terminal.as_mut() else {
+            return Ok(());
+        };
+
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste)?;
+        let status = Command::new("less")
+            .arg("-R")
+            .stdin(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                // Quitting less early closes the pipe; that is not an error
+                if let Some(mut stdin) = child.stdin.take() {
+                    let _ = stdin.write_all(content.as_bytes());
+                }
+                child.wait()
+            });
+        enable_raw_mode()?;
+        execute!(terminal.backend_mut(), EnterAlternateScreen, EnableBracketedPaste)?;
+        if self.state.config.mouse {
+            execute!(terminal.backend_mut(), EnableMouseCapture)?;
+        }
+        terminal.clear()?;
+
+        match status {
+            Ok(_) => self.state.clear_message(),
+            Err(e) => self.state.set_message(format!("Failed to run less: {}", e)),
+        }
+        Ok(())
+    }
+
+    fn add_comment(&mut self, severity: Severity) {
         use cr_core::comment::model::DiffSide;
 
         if let Some(file) = self.session.diff_data.files.get(self.state.current_file) {
@@ -481,92 +1886,295 @@ impl App {
 
             if let Ok(comment) = CommentBuilder::new(file_id.clone(), line_id.clone(), side)
                 .content(&self.state.editor_content)
+                .severity(severity)
                 .file_path(&file_path)
                 .line_number(line_number)
                 .build()
             {
-                let comment_id = comment.id.clone();
                 if self.session.comments.add(comment).is_ok() {
-                    // Update line comments cache
-                    self.line_comments
-                        .entry(file_id)
-                        .or_default()
-                        .entry(line_id)
-                        .or_default()
-                        .push(comment_id);
+                    self.coverage_stale = true;
+                    self.risk_stale = true;
                     self.state.set_message("Comment added");
                 }
             }
         }
     }
 
-    fn delete_comment_at_line(&mut self) {
-        if let Some(file) = self.session.diff_data.files.get(self.state.current_file) {
-            // Find line at current position
-            let mut line_idx = 0;
-            for hunk in &file.hunks {
-                for line in &hunk.lines {
-                    if line_idx == self.state.current_line {
-                        // Find comments on this line
-                        if let Some(fc) = self.line_comments.get_mut(&file.id) {
-                            if let Some(comment_ids) = fc.get_mut(&line.id) {
-                                if let Some(id) = comment_ids.pop() {
-                                    if self.session.comments.delete(&id).is_ok() {
-                                        self.state.set_message("Comment deleted");
-                                        return;
-                                    }
-                                }
-                            }
-                        }
-                        self.state.set_message("No comment on this line");
-                        return;
-                    }
-                    line_idx += 1;
-                }
+    /// Most recent comment on the current line
+    fn comment_id_at_line(&self) -> Option<CommentId> {
+        let file = self.session.diff_data.files.get(self.state.current_file)?;
+        let line = file
+            .hunks
+            .iter()
+            .flat_map(|h| &h.lines)
+            .nth(self.state.current_line)?;
+        self.session.comments.get_by_line(&line.id).last().map(|c| c.id.clone())
+    }
+
+    /// Move the comment on the current line one place up or down in the
+    /// report order
+    fn move_comment_at_line(&mut self, up: bool) {
+        let Some(id) = self.comment_id_at_line() else {
+            self.state.set_message("No comment on this line");
+            return;
+        };
+        let order: Vec<CommentId> = self.session.comments.all_sorted().iter().map(|c| c.id.clone()).collect();
+        let Some(pos) = order.iter().position(|o| o == &id) else {
+            return;
+        };
+
+        let (before, new_pos) = if up {
+            if pos == 0 {
+                self.state.set_message("Comment is already first");
+                return;
+            }
+            (Some(order[pos - 1].clone()), pos - 1)
+        } else {
+            if pos + 1 >= order.len() {
+                self.state.set_message("Comment is already last");
+                return;
             }
+            (order.get(pos + 2).cloned(), pos + 1)
+        };
+
+        match self.session.comments.reorder(&id, before.as_ref()) {
+            Ok(()) => self.state.set_message(format!("Comment moved to {}/{}", new_pos + 1, order.len())),
+            Err(e) => self.state.set_message(format!("Failed to reorder: {}", e)),
+        }
+    }
+
+    fn delete_comment_at_line(&mut self) {
+        let Some(id) = self.comment_id_at_line() else {
+            self.state.set_message("No comment on this line");
+            return;
+        };
+        if self.session.comments.delete(&id).is_ok() {
+            self.coverage_stale = true;
+            self.risk_stale = true;
+            self.state.set_message("Comment deleted");
         }
     }
 
     /// Get a clone of the current session
     pub fn get_session(&self) -> Session {
-        self.session.clone()
+        let mut session = self.session.clone();
+        if let Some(diff_data) = &self.unfiltered_diff {
+            session.diff_data = diff_data.clone();
+        }
+        session
     }
 }
 
 impl Drop for App {
     fn drop(&mut self) {
-        let _ = disable_raw_mode();
-        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture);
-        let _ = self.terminal.show_cursor();
+        if let Some(terminal) = &mut self.terminal {
+            let _ = disable_raw_mode();
+            let _ = execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste);
+            let _ = terminal.show_cursor();
+        }
     }
 }
 
 // Render functions
 
+fn render_app(
+    frame: &mut Frame,
+    state: &AppState,
+    session: &Session,
+    navigator: &DiffNavigator,
+    highlighter: &Highlighter,
+) {
+    let files = &session.diff_data.files;
+    let current_file = files.get(state.current_file);
+    let file_count = files.len();
+    let session_id = session.id.short().to_string();
+    let area = frame.area();
+
+    match state.mode {
+        AppMode::Help => render_help(frame, area),
+        AppMode::Insert => render_with_editor(frame, area, state, current_file, file_count, navigator, &session.comments, &session_id, highlighter),
+        AppMode::Normal | AppMode::GotoFile | AppMode::Search => render_diff_only(frame, area, state, current_file, file_count, navigator, &session.comments, &session_id, highlighter),
+        AppMode::Command => {
+            render_diff_only(frame, area, state, current_file, file_count, navigator, &session.comments, &session_id, highlighter);
+            render_command_palette(frame, area, state);
+        }
+        AppMode::ThemePreview => {
+            render_diff_only(frame, area, state, current_file, file_count, navigator, &session.comments, &session_id, highlighter);
+            render_theme_preview(frame, area, state);
+        }
+        AppMode::Assignments => {
+            render_diff_only(frame, area, state, current_file, file_count, navigator, &session.comments, &session_id, highlighter);
+            render_assignments(frame, area, session);
+        }
+        AppMode::CommitLog => {
+            let (main, sidebar) = commit_log_layout(area);
+            render_diff_only(frame, main, state, current_file, file_count, navigator, &session.comments, &session_id, highlighter);
+            render_commit_log(frame, sidebar, state);
+        }
+        AppMode::CommitDiff => {
+            let (main, sidebar) = commit_log_layout(area);
+            render_commit_diff(frame, main, state);
+            render_commit_log(frame, sidebar, state);
+        }
+        AppMode::SessionPicker => {
+            render_diff_only(frame, area, state, current_file, file_count, navigator, &session.comments, &session_id, highlighter);
+            render_session_picker(frame, area, state, session);
+        }
+    }
+}
+
+/// Sessions from `:session list`; the one under review is bold
+fn render_session_picker(frame: &mut Frame, area: Rect, state: &AppState, session: &Session) {
+    let items: Vec<ListItem> = state
+        .session_list
+        .iter()
+        .map(|info| {
+            let style = if info.id == session.id {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let name = info.metadata.name.clone().unwrap_or_else(|| info.source_description.clone());
+            ListItem::new(TextLine::from(vec![
+                Span::styled(format!("{} ", info.id.short()), Style::default().fg(Color::Yellow)),
+                Span::styled(name, style),
+                Span::styled(
+                    format!(
+                        "  {} files, {} comments, {}",
+                        info.file_count,
+                        info.comment_count,
+                        info.updated_at.format("%Y-%m-%d %H:%M")
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        })
+        .collect();
+
+    let popup = centered_rect(70, 60, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(" Sessions (Enter: open, Esc: close) ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .highlight_style(Style::default().bg(Color::DarkGray));
+    let mut list_state = ListState::default().with_selected(Some(state.session_list_selected));
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(list, popup, &mut list_state);
+}
+
+/// The review on the left, the commit history sidebar in the right 30%
+fn commit_log_layout(area: Rect) -> (Rect, Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+        .split(area);
+    (chunks[0], chunks[1])
+}
+
+/// Commits around the reviewed one; the reviewed commit is bold
+fn render_commit_log(frame: &mut Frame, area: Rect, state: &AppState) {
+    let items: Vec<ListItem> = state
+        .commit_log
+        .iter()
+        .map(|commit| {
+            let style = if state.context_commit.as_ref() == Some(&commit.sha) {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(TextLine::from(vec![
+                Span::styled(format!("{} ", commit.short_sha), Style::default().fg(Color::Yellow)),
+                Span::styled(commit.subject.clone(), style),
+                Span::styled(
+                    format!("  {}, {}", commit.author, commit.date.format("%Y-%m-%d")),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        })
+        .collect();
+
+    let focused = state.mode == AppMode::CommitLog;
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(" Commits (Enter: diff, L: close) ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(if focused { Color::Cyan } else { Color::DarkGray })),
+        )
+        .highlight_style(Style::default().bg(Color::DarkGray));
+    let mut list_state = ListState::default().with_selected(Some(state.commit_log_selected));
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
+
+/// `git show` output of the commit opened from the sidebar
+fn render_commit_diff(frame: &mut Frame, area: Rect, state: &AppState) {
+    let title = state
+        .commit_log
+        .get(state.commit_log_selected)
+        .map(|c| format!(" {} {} (Esc: back) ", c.short_sha, c.subject))
+        .unwrap_or_default();
+    let lines: Vec<TextLine> = state
+        .commit_diff
+        .iter()
+        .skip(state.commit_diff_scroll)
+        .map(|line| {
+            let color = if line.starts_with("+++") || line.starts_with("---") || line.starts_with("diff ") {
+                Color::White
+            } else if line.starts_with('+') {
+                Color::Green
+            } else if line.starts_with('-') {
+                Color::Red
+            } else if line.starts_with("@@") {
+                Color::Cyan
+            } else if line.starts_with("commit ") {
+                Color::Yellow
+            } else {
+                Color::Reset
+            };
+            TextLine::from(Span::styled(line.as_str(), Style::default().fg(color)))
+        })
+        .collect();
+
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().title(title).borders(Borders::ALL)),
+        area,
+    );
+}
+
 fn render_diff_only(
     frame: &mut Frame,
     area: Rect,
     state: &AppState,
     file: Option<&FileDiff>,
     file_count: usize,
-    comments: &[Comment],
-    line_comments: &HashMap<FileId, HashMap<LineId, Vec<CommentId>>>,
+    navigator: &DiffNavigator,
+    comments: &CommentManager,
     session_id: &str,
     highlighter: &Highlighter,
 ) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(1), Constraint::Min(3), Constraint::Length(1)])
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(mode_tabs_height(state)),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
         .split(area);
 
     // Title bar
     render_title_bar(frame, chunks[0], state, file, file_count);
+    render_mode_tabs(frame, chunks[1], state);
 
     // Diff content with inline comments
-    render_diff_with_comments(frame, chunks[1], state, file, comments, line_comments, highlighter);
+    render_diff_with_comments(frame, chunks[2], state, file, navigator, comments, highlighter);
 
     // Status bar
-    render_status_bar(frame, chunks[2], state, file_count, comments.len(), session_id);
+    render_status_bar(frame, chunks[3], state, file_count, comments.count(), session_id);
 }
 
 fn render_with_editor(
@@ -575,20 +2183,27 @@ fn render_with_editor(
     state: &AppState,
     file: Option<&FileDiff>,
     file_count: usize,
-    comments: &[Comment],
-    line_comments: &HashMap<FileId, HashMap<LineId, Vec<CommentId>>>,
+    navigator: &DiffNavigator,
+    comments: &CommentManager,
     session_id: &str,
     highlighter: &Highlighter,
 ) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(1), Constraint::Min(3), Constraint::Length(3), Constraint::Length(1)])
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(mode_tabs_height(state)),
+            Constraint::Min(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
         .split(area);
 
     render_title_bar(frame, chunks[0], state, file, file_count);
-    render_diff_with_comments(frame, chunks[1], state, file, comments, line_comments, highlighter);
-    render_comment_editor(frame, chunks[2], state);
-    render_status_bar(frame, chunks[3], state, file_count, comments.len(), session_id);
+    render_mode_tabs(frame, chunks[1], state);
+    render_diff_with_comments(frame, chunks[2], state, file, navigator, comments, highlighter);
+    render_comment_editor(frame, chunks[3], state);
+    render_status_bar(frame, chunks[4], state, file_count, comments.count(), session_id);
 }
 
 fn render_title_bar(frame: &mut Frame, area: Rect, state: &AppState, file: Option<&FileDiff>, file_count: usize) {
@@ -602,9 +2217,29 @@ fn render_title_bar(frame: &mut Frame, area: Rect, state: &AppState, file: Optio
             cr_core::diff::FileMode::Copied => "C",
             cr_core::diff::FileMode::Binary => "B",
         };
-        format!(" {} {} [{}/{}]", mode_icon, path, state.current_file + 1, file_count)
+        let stale = if state.session_stale { " ~" } else { "" };
+        let (position, total) = state.file_position.unwrap_or((state.current_file, file_count));
+        let heat = state.file_heat.get(&f.id).copied().unwrap_or(0.0);
+        let bar = state.file_stat_bars.get(&f.id).map(String::as_str).unwrap_or("");
+        let plus = bar.chars().take_while(|&c| c == '+').count();
+        let counts = state
+            .file_stats
+            .get(&f.id)
+            .map(|s| format!(" +{} -{} in {} hunk(s)", s.insertions, s.deletions, s.hunks))
+            .unwrap_or_default();
+        TextLine::from(vec![
+            Span::raw(format!(" {} {} [{}/{}]{} ", mode_icon, path, position + 1, total, stale)),
+            Span::styled(heat_bar(heat, 5), Style::default().fg(heat_color(heat))),
+            Span::raw(" "),
+            Span::styled(bar[..plus].to_string(), Style::default().fg(Color::Green)),
+            Span::styled(bar[plus..].to_string(), Style::default().fg(Color::Red)),
+            Span::raw(counts),
+            risk_span(state.risk_level),
+            Span::raw(if state.has_checkpoints { "  [checkpoint available]" } else { "" }),
+            truncation_span(state.truncated_files),
+        ])
     } else {
-        " No files".to_string()
+        TextLine::from(" No files")
     };
 
     frame.render_widget(
@@ -613,13 +2248,72 @@ fn render_title_bar(frame: &mut Frame, area: Rect, state: &AppState, file: Optio
     );
 }
 
+/// Title bar warning for a diff cut at `[diff] max_files_per_session`
+fn truncation_span(truncated: Option<(usize, usize)>) -> Span<'static> {
+    match truncated {
+        Some((shown, total)) => Span::styled(
+            format!("  ⚠ Large diff: showing first {} of {} files", shown, total),
+            Style::default().fg(Color::Yellow),
+        ),
+        None => Span::raw(""),
+    }
+}
+
+/// Height of the mode tabs row: shown only while a mode filter is on
+fn mode_tabs_height(state: &AppState) -> u16 {
+    if state.file_mode_filter.is_empty() {
+        0
+    } else {
+        1
+    }
+}
+
+/// File mode filter buttons; active modes are highlighted
+fn render_mode_tabs(frame: &mut Frame, area: Rect, state: &AppState) {
+    if area.height == 0 {
+        return;
+    }
+    let titles: Vec<TextLine> = [
+        ("1 Added", FileMode::Added),
+        ("2 Deleted", FileMode::Deleted),
+        ("3 Modified", FileMode::Modified),
+        ("4 Renamed", FileMode::Renamed),
+    ]
+    .into_iter()
+    .map(|(label, mode)| {
+        let style = if state.file_mode_filter.contains(&mode) {
+            Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        TextLine::from(Span::styled(label, style))
+    })
+    .collect();
+
+    frame.render_widget(Tabs::new(titles).select(None).divider("|"), area);
+}
+
+/// ` risk: high` for the title bar, colored by level
+fn risk_span(level: Option<RiskLevel>) -> Span<'static> {
+    let Some(level) = level else {
+        return Span::raw("");
+    };
+    let color = match level {
+        RiskLevel::Low => Color::Green,
+        RiskLevel::Medium => Color::Yellow,
+        RiskLevel::High => Color::LightRed,
+        RiskLevel::Critical => Color::Red,
+    };
+    Span::styled(format!("  risk: {}", level), Style::default().fg(color))
+}
+
 fn render_diff_with_comments(
     frame: &mut Frame,
     area: Rect,
     state: &AppState,
     file: Option<&FileDiff>,
-    comments: &[Comment],
-    line_comments: &HashMap<FileId, HashMap<LineId, Vec<CommentId>>>,
+    navigator: &DiffNavigator,
+    comments: &CommentManager,
     highlighter: &Highlighter,
 ) {
     let Some(file) = file else {
@@ -630,120 +2324,221 @@ fn render_diff_with_comments(
         return;
     };
 
-    // Build comment lookup
-    let file_line_comments = line_comments.get(&file.id);
-    let comment_map: HashMap<CommentId, &Comment> = comments.iter().map(|c| (c.id.clone(), c)).collect();
-
-    let mut lines_to_render: Vec<TextLine> = Vec::new();
-    let mut line_idx = 0;
+    if let Some(preview) = state.binary_previews.get(&file.id).filter(|_| file.is_binary()) {
+        render_binary_preview(frame, area, state, file, preview, comments);
+        return;
+    }
 
     // Get file path for syntax detection
     let file_path = file.display_path().to_string_lossy().to_string();
 
-    for hunk in &file.hunks {
-        // Hunk header
+    // Only the rows inside the borders are built; comments hidden by the
+    // severity filter are skipped and the rows below pulled up
+    let viewport_height = area.height.saturating_sub(2) as usize;
+    let mut lines_to_render: Vec<TextLine> = Vec::with_capacity(viewport_height);
+    let mut offset = state.scroll_offset;
+    while lines_to_render.len() < viewport_height {
+        let rows = navigator.virtual_scroll_lines(offset, viewport_height - lines_to_render.len(), comments);
+        if rows.is_empty() {
+            break;
+        }
+        offset += rows.len();
+        lines_to_render.extend(rows.iter().filter_map(|row| render_virtual_line(state, row, &file_path, highlighter)));
+    }
+
+    // Handle lazy files with no content
+    if file.lazy && file.hunks.is_empty() {
         lines_to_render.push(TextLine::from(Span::styled(
-            &hunk.header,
-            Style::default().fg(Color::Cyan).add_modifier(Modifier::DIM)
+            "  (Content not loaded - navigate to load)",
+            Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC)
         )));
+    }
 
-        for line in &hunk.lines {
-            let is_current = line_idx == state.current_line;
+    let mut paragraph = Paragraph::new(lines_to_render)
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(state.themes.current().unfocus_border)));
+    if state.config.word_wrap {
+        paragraph = paragraph.wrap(Wrap { trim: false });
+    }
 
-            // Build line number display
-            let line_num = match (line.old_line_num, line.new_line_num) {
-                (Some(o), Some(n)) => format!("{:>4} {:>4}", o, n),
-                (Some(o), None) => format!("{:>4}     ", o),
-                (None, Some(n)) => format!("     {:>4}", n),
-                (None, None) => "         ".to_string(),
-            };
+    frame.render_widget(paragraph, area);
+}
 
-            // Line prefix and base style for diff markers
-            let (prefix, diff_style) = match line.line_type {
-                LineType::Added => ("+", Style::default().fg(Color::Green)),
-                LineType::Deleted => ("-", Style::default().fg(Color::Red)),
-                LineType::Context => (" ", Style::default()),
-                LineType::NoNewline => ("\\", Style::default().fg(Color::DarkGray)),
-            };
+/// One row of the diff view, or `None` for a comment the severity filter hides
+fn render_virtual_line<'a>(
+    state: &AppState,
+    row: &VirtualLine<'a>,
+    file_path: &str,
+    highlighter: &Highlighter,
+) -> Option<TextLine<'a>> {
+    match row {
+        VirtualLine::HunkHeader(header) => Some(TextLine::from(Span::styled(
+            header.clone(),
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::DIM)
+        ))),
+        VirtualLine::DiffLine(line_idx, line) => Some(render_diff_line(state, *line_idx, line, file_path, highlighter)),
+        VirtualLine::CommentLine(_, comment) => {
+            if state.severity_filter.is_some_and(|s| s != comment.severity) {
+                return None;
+            }
+            Some(render_comment_line(state, comment))
+        }
+    }
+}
 
-            // Build spans for the line
-            let mut spans: Vec<Span> = vec![
-                Span::styled(line_num, Style::default().fg(Color::DarkGray)),
-                Span::raw(" "),
-                Span::styled(prefix.to_string(), diff_style),
-            ];
-
-            // Apply syntax highlighting for non-special lines
-            if line.line_type != LineType::NoNewline {
-                let highlighted = highlighter.highlight_line(&line.content, &file_path);
-                for span in highlighted {
-                    // Apply diff background color if needed
-                    let mut span_style = span.style;
-                    if is_current {
-                        span_style = span_style.bg(Color::DarkGray).add_modifier(Modifier::BOLD);
-                    } else {
-                        // Tint syntax highlighting with diff color
-                        match line.line_type {
-                            LineType::Added => {
-                                span_style = span_style.bg(Color::Rgb(0, 40, 0));
-                            }
-                            LineType::Deleted => {
-                                span_style = span_style.bg(Color::Rgb(40, 0, 0));
-                            }
-                            _ => {}
-                        }
-                    }
-                    spans.push(Span::styled(span.content.to_string(), span_style));
-                }
-            } else {
-                // NoNewline marker - just show the content
-                let mut style = diff_style;
+/// A comment shown under the line it is on
+fn render_comment_line<'a>(state: &AppState, comment: &'a Comment) -> TextLine<'a> {
+    let severity_style = match comment.severity {
+        Severity::Critical => Style::default().fg(state.themes.current().critical),
+        Severity::Warning => Style::default().fg(state.themes.current().warning),
+        Severity::Info => Style::default().fg(state.themes.current().info),
+    };
+    TextLine::from(vec![
+        Span::raw("         "),
+        Span::styled(format!("│ {} ", comment.severity.emoji()), severity_style),
+        Span::styled(comment.content.as_str(), Style::default().fg(Color::White)),
+    ])
+}
+
+/// A binary file's comments (all file-level) above its preview
+fn render_binary_preview(
+    frame: &mut Frame,
+    area: Rect,
+    state: &AppState,
+    file: &FileDiff,
+    preview: &[String],
+    comments: &CommentManager,
+) {
+    let mut lines: Vec<TextLine> = comments
+        .get_by_file(&file.id)
+        .into_iter()
+        .filter(|c| state.severity_filter.is_none_or(|s| s == c.severity))
+        .map(|c| render_comment_line(state, c))
+        .collect();
+    if !lines.is_empty() {
+        lines.push(TextLine::default());
+    }
+    lines.extend(preview.iter().map(|l| TextLine::from(Span::raw(format!("  {}", l)))));
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(state.themes.current().unfocus_border)));
+    frame.render_widget(paragraph, area);
+}
+
+/// A diff line with line numbers, bookmark column and syntax highlighting
+fn render_diff_line(
+    state: &AppState,
+    line_idx: usize,
+    line: &DiffLine,
+    file_path: &str,
+    highlighter: &Highlighter,
+) -> TextLine<'static> {
+    let is_current = state.config.highlight_cursor_line && line_idx == state.current_line;
+
+    // Build line number display
+    let line_num = match (line.old_line_num, line.new_line_num) {
+        _ if !state.config.show_line_numbers => String::new(),
+        (Some(o), Some(n)) => format!("{:>4} {:>4}", o, n),
+        (Some(o), None) => format!("{:>4}     ", o),
+        (None, Some(n)) => format!("     {:>4}", n),
+        (None, None) => "         ".to_string(),
+    };
+
+    // Line prefix and base style for diff markers
+    let (prefix, diff_style) = match line.line_type {
+        LineType::Added => ("+", Style::default().fg(state.themes.current().added)),
+        LineType::Deleted => ("-", Style::default().fg(state.themes.current().deleted)),
+        LineType::Context => (" ", Style::default()),
+        LineType::NoNewline => ("\\", Style::default().fg(Color::DarkGray)),
+    };
+
+    // Build spans for the line, with a bookmark column if the file has any
+    let mut spans: Vec<Span> = vec![Span::styled(line_num, Style::default().fg(Color::DarkGray))];
+    if !state.bookmarked_lines.is_empty() {
+        let mark = match state.bookmarked_lines.get(&line_idx) {
+            Some(name) => format!("[{}]", name),
+            None => "   ".to_string(),
+        };
+        spans.push(Span::styled(mark, Style::default().fg(Color::Magenta)));
+    }
+    spans.push(Span::raw(" "));
+    spans.push(Span::styled(prefix.to_string(), diff_style));
+
+    // Apply syntax highlighting for non-special lines
+    if line.line_type != LineType::NoNewline {
+        let highlighted = highlighter.highlight_line(&line.content, file_path);
+        // Changed words (from a word diff) get a stronger tint
+        let mut changed = word_changes(line);
+        if highlighted.iter().map(|s| s.content.len()).sum::<usize>() != line.content.len() {
+            changed.clear();
+        }
+        let mut offset = 0;
+        for span in highlighted {
+            for (text, in_change) in split_word_changes(&span.content, offset, &changed) {
+                // Apply diff background color if needed
+                let mut span_style = span.style;
                 if is_current {
-                    style = style.bg(Color::DarkGray).add_modifier(Modifier::BOLD);
-                }
-                spans.push(Span::styled(line.content.clone(), style));
-            }
-
-            lines_to_render.push(TextLine::from(spans));
-
-            // Render inline comments for this line
-            if let Some(fc) = file_line_comments {
-                if let Some(comment_ids) = fc.get(&line.id) {
-                    for cid in comment_ids {
-                        if let Some(comment) = comment_map.get(cid) {
-                            let severity_style = match comment.severity {
-                                cr_core::comment::Severity::Critical => Style::default().fg(Color::Red),
-                                cr_core::comment::Severity::Warning => Style::default().fg(Color::Yellow),
-                                cr_core::comment::Severity::Info => Style::default().fg(Color::Blue),
-                            };
-                            let icon = comment.severity.emoji();
-                            lines_to_render.push(TextLine::from(vec![
-                                Span::raw("         "),
-                                Span::styled(format!("│ {} ", icon), severity_style),
-                                Span::styled(&comment.content, Style::default().fg(Color::White)),
-                            ]));
-                        }
+                    span_style = span_style.bg(Color::DarkGray).add_modifier(Modifier::BOLD);
+                } else {
+                    // Tint syntax highlighting with diff color
+                    match (line.line_type, in_change) {
+                        (LineType::Added, false) => span_style = span_style.bg(Color::Rgb(0, 40, 0)),
+                        (LineType::Added, true) => span_style = span_style.bg(Color::Rgb(0, 100, 0)),
+                        (LineType::Deleted, false) => span_style = span_style.bg(Color::Rgb(40, 0, 0)),
+                        (LineType::Deleted, true) => span_style = span_style.bg(Color::Rgb(100, 0, 0)),
+                        _ => {}
                     }
                 }
+                spans.push(Span::styled(text.to_string(), span_style));
             }
-
-            line_idx += 1;
+            offset += span.content.len();
         }
+    } else {
+        // NoNewline marker - just show the content
+        let mut style = diff_style;
+        if is_current {
+            style = style.bg(Color::DarkGray).add_modifier(Modifier::BOLD);
+        }
+        spans.push(Span::styled(line.content.clone(), style));
     }
 
-    // Handle lazy files with no content
-    if file.lazy && file.hunks.is_empty() {
-        lines_to_render.push(TextLine::from(Span::styled(
-            "  (Content not loaded - navigate to load)",
-            Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC)
-        )));
-    }
+    TextLine::from(spans)
+}
 
-    let paragraph = Paragraph::new(lines_to_render)
-        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::DarkGray)))
-        .scroll((state.scroll_offset as u16, 0));
+/// Byte ranges of a line's added or deleted words (empty without a word diff)
+fn word_changes(line: &DiffLine) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+    for span in line.side_word_spans().unwrap_or_default() {
+        if span.kind != WordKind::Context {
+            ranges.push((offset, offset + span.text.len()));
+        }
+        offset += span.text.len();
+    }
+    ranges
+}
 
-    frame.render_widget(paragraph, area);
+/// Split `text`, found at byte `offset` of its line, into pieces inside and
+/// outside the `changed` ranges
+fn split_word_changes<'a>(text: &'a str, offset: usize, changed: &[(usize, usize)]) -> Vec<(&'a str, bool)> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let at = offset + start;
+        let inside = changed.iter().find(|(from, to)| *from <= at && at < *to);
+        let end = match inside {
+            Some((_, to)) => to - offset,
+            None => changed
+                .iter()
+                .filter(|(from, _)| *from > at)
+                .map(|(from, _)| from - offset)
+                .min()
+                .unwrap_or(text.len()),
+        };
+        let end = end.min(text.len());
+        pieces.push((&text[start..end], inside.is_some()));
+        start = end;
+    }
+    pieces
 }
 
 fn render_comment_editor(frame: &mut Frame, area: Rect, state: &AppState) {
@@ -789,17 +2584,66 @@ fn render_status_bar(
         AppMode::Normal => "NORMAL",
         AppMode::Insert => "INSERT",
         AppMode::Help => "HELP",
+        AppMode::GotoFile => "GOTO",
+        AppMode::Command => "COMMAND",
+        AppMode::ThemePreview => "THEME",
+        AppMode::Assignments => "ASSIGN",
+        AppMode::Search => "SEARCH",
+        AppMode::CommitLog => "LOG",
+        AppMode::CommitDiff => "COMMIT",
+        AppMode::SessionPicker => "SESSIONS",
     };
 
+    if state.mode == AppMode::Command {
+        frame.render_widget(
+            Paragraph::new(format!(":{}", state.command_input))
+                .style(Style::default().bg(Color::DarkGray).fg(Color::White)),
+            area,
+        );
+        return;
+    }
+
+    if state.mode == AppMode::Search {
+        frame.render_widget(
+            Paragraph::new(format!("/{}", state.search_query))
+                .style(Style::default().bg(Color::DarkGray).fg(Color::White)),
+            area,
+        );
+        return;
+    }
+
+    if state.mode == AppMode::GotoFile {
+        let hint = match state.goto_candidates.len() {
+            0 => "no match".to_string(),
+            1 => state.goto_candidates[0].clone(),
+            n => format!("{} (+{} more)", state.goto_candidates[0], n - 1),
+        };
+        frame.render_widget(
+            Paragraph::new(format!(" Go to file: {}  [{}]", state.goto_query, hint))
+                .style(Style::default().bg(Color::DarkGray).fg(Color::White)),
+            area,
+        );
+        return;
+    }
+
     let line_info = format!("L{}", state.current_line + 1);
 
+    let suggestion = match state.mode {
+        AppMode::Insert => state
+            .suggested_severity()
+            .map(|severity| format!(" | Suggested severity: {}", severity))
+            .unwrap_or_default(),
+        _ => String::new(),
+    };
     let text = state.message.clone().unwrap_or_else(|| {
         format!(
-            " {} | {} | {} comments | {} ",
+            " {} | {} | {} comments | Coverage: {:.0}%{} | {} ",
             mode,
             line_info,
             comment_count,
-            &session_id[..14.min(session_id.len())]
+            state.coverage_percent,
+            suggestion,
+            session_id
         )
     });
 
@@ -809,25 +2653,113 @@ fn render_status_bar(
     );
 }
 
+fn render_command_palette(frame: &mut Frame, area: Rect, state: &AppState) {
+    let completions = CommandParser::completions(&state.command_input);
+    if completions.is_empty() || area.height < 4 {
+        return;
+    }
+
+    // Sit directly above the status bar
+    let height = (completions.len() as u16 + 2).min(area.height - 2);
+    let width = 40.min(area.width);
+    let palette_area = Rect::new(area.x, area.bottom() - 1 - height, width, height);
+
+    let items: Vec<ListItem> = completions
+        .iter()
+        .map(|c| ListItem::new(format!(":{}", c)))
+        .collect();
+
+    frame.render_widget(Clear, palette_area);
+    frame.render_widget(
+        List::new(items).block(
+            Block::default()
+                .title("Commands (Tab to complete)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(state.themes.current().focus_border)),
+        ),
+        palette_area,
+    );
+}
+
+fn render_theme_preview(frame: &mut Frame, area: Rect, state: &AppState) {
+    let candidate_name = THEME_NAMES[state.theme_preview % THEME_NAMES.len()];
+    let candidate = Theme::by_name(candidate_name).unwrap_or_default();
+
+    let popup = centered_rect(80, 50, area);
+    frame.render_widget(Clear, popup);
+    let block = Block::default()
+        .title("Theme preview (←/→ cycle, Enter apply, Esc cancel)")
+        .borders(Borders::ALL);
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(inner);
+    let current_title = format!("Current: {}", state.themes.name());
+    let candidate_title = format!("Candidate: {}", candidate_name);
+    render_theme_sample(frame, panes[0], &current_title, state.themes.current());
+    render_theme_sample(frame, panes[1], &candidate_title, &candidate);
+}
+
+/// A small diff with a comment of each severity, drawn in `theme`
+fn render_theme_sample(frame: &mut Frame, area: Rect, title: &str, theme: &Theme) {
+    let fg = |color| Style::default().fg(color);
+    let text = vec![
+        TextLine::from(Span::styled("   fn total(items: &[Item]) -> u32 {", fg(theme.context))),
+        TextLine::from(Span::styled(" - items.iter().map(|i| i.price).sum()", fg(theme.deleted))),
+        TextLine::from(Span::styled(" + items.iter().map(Item::price).sum()", fg(theme.added))),
+        TextLine::from(Span::styled("   }", fg(theme.context))),
+        TextLine::from(""),
+        TextLine::from(Span::styled(" ● Critical: overflow on large carts", fg(theme.critical))),
+        TextLine::from(Span::styled(" ● Warning: consider checked_add", fg(theme.warning))),
+        TextLine::from(Span::styled(" ● Info: nice cleanup", fg(theme.info))),
+    ];
+    frame.render_widget(
+        Paragraph::new(text).block(
+            Block::default()
+                .title(title.to_string())
+                .borders(Borders::ALL)
+                .border_style(fg(theme.focus_border)),
+        ),
+        area,
+    );
+}
+
 fn render_help(frame: &mut Frame, area: Rect) {
     let text = vec![
         Line::from(Span::styled("cr-helper - Code Review", Style::default().add_modifier(Modifier::BOLD))),
         Line::from(""),
         Line::from(Span::styled("Navigation", Style::default().fg(Color::Yellow))),
         Line::from("  j/k         Move cursor up/down"),
-        Line::from("  g/G         Go to top/bottom"),
+        Line::from("  gg/G        Go to top/bottom"),
         Line::from("  Ctrl-u/d    Page up/down"),
         Line::from("  n/N         Next/Previous file"),
+        Line::from("  gf          Go to file (Tab completes)"),
         Line::from("  ]/[         Next/Previous comment"),
+        Line::from("  /           Search comments (/--regex, /--fuzzy)"),
+        Line::from("  1-4         Toggle added/deleted/modified/renamed files (0 clears)"),
         Line::from(""),
         Line::from(Span::styled("Comments", Style::default().fg(Color::Yellow))),
         Line::from("  c           Add comment on current line"),
         Line::from("  C           Add file-level comment"),
         Line::from("  x           Delete comment on current line"),
+        Line::from("  Ctrl-↑/↓    Move comment up/down in report order"),
         Line::from(""),
         Line::from(Span::styled("Other", Style::default().fg(Color::Yellow))),
         Line::from("  s           Save session"),
+        Line::from("  e           Open line in $VISUAL / $EDITOR"),
+        Line::from("  E           Export session to a temp file"),
+        Line::from("  P           Preview the export in less"),
+        Line::from("  Ctrl-p      Save a screenshot (SVG)"),
+        Line::from("  m<char>     Set bookmark <char> on current line"),
+        Line::from("  '<char>     Jump to bookmark <char> (:marks lists them)"),
         Line::from("  q           Quit"),
+        Line::from("  :           Command palette (:save, :export, :goto, ...)"),
+        Line::from("  :set theme  Preview and switch color themes"),
+        Line::from("  A           Show reviewer assignments"),
+        Line::from("  L           Commit history sidebar (--context-commit)"),
         Line::from("  ?           Show this help"),
         Line::from(""),
         Line::from(Span::styled("Press any key to close", Style::default().fg(Color::DarkGray))),
@@ -846,6 +2778,80 @@ fn render_help(frame: &mut Frame, area: Rect) {
     );
 }
 
+/// Files with their assigned reviewers; files for the session reviewer are highlighted
+fn render_assignments(frame: &mut Frame, area: Rect, session: &Session) {
+    let reviewer = session.metadata.reviewer.as_deref();
+    let mine: Vec<_> = reviewer
+        .map(|r| session.files_for_reviewer(r).into_iter().map(|f| f.id.clone()).collect())
+        .unwrap_or_default();
+
+    let mut text = vec![match reviewer {
+        Some(r) => Line::from(format!("Reviewer: {}", r)),
+        None => Line::from(Span::styled("No reviewer set for this session", Style::default().fg(Color::DarkGray))),
+    }];
+    text.push(Line::from(""));
+    for file in &session.diff_data.files {
+        let reviewers = session.reviewers_for_file(file);
+        let assigned = if reviewers.is_empty() {
+            "(unassigned)".to_string()
+        } else {
+            reviewers.join(", ")
+        };
+        let style = if mine.contains(&file.id) {
+            Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        text.push(Line::from(vec![
+            Span::styled(file.display_path().display().to_string(), style),
+            Span::styled(format!("  {}", assigned), Style::default().fg(Color::DarkGray)),
+        ]));
+    }
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled("Press any key to close", Style::default().fg(Color::DarkGray))));
+
+    let popup = centered_rect(70, 60, area);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(text).block(
+            Block::default()
+                .title("Assignments")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+        ),
+        popup
+    );
+}
+
+/// `text` without ANSI CSI escape sequences (`ESC [ ... final-byte`)
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next();
+            // Parameter and intermediate bytes, then one final byte in @..=~
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Case-insensitive subsequence match of `query` against `path`
+fn fuzzy_match(path: &str, query: &str) -> bool {
+    let mut chars = path.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|q| chars.any(|c| c == q))
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup = Layout::default()
         .direction(Direction::Vertical)
@@ -881,6 +2887,705 @@ mod tests {
         assert!(!state.should_quit);
     }
 
+    #[test]
+    fn test_bookmarks_round_trip() {
+        let diff = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,2 +1,3 @@\n one\n+two\n three\n";
+        let diff = DiffParser::new().parse(diff).unwrap();
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        let mut app = App::headless(session, 80, 20);
+        app.move_down();
+        app.move_down();
+        app.handle_normal_input(key('m')).unwrap();
+        app.handle_normal_input(key('a')).unwrap();
+        app.goto_top();
+        app.handle_normal_input(key('\'')).unwrap();
+        app.handle_normal_input(key('a')).unwrap();
+        assert_eq!(app.state.current_line, 2);
+        let file_id = app.session.diff_data.files[0].id.clone();
+        assert_eq!(app.session.bookmarks().get("a"), Some(&(file_id, 2)));
+
+        let app = App::headless(app.session.clone(), 80, 20);
+        assert_eq!(app.navigator.bookmarks().get("a").map(|p| p.line_idx), Some(2));
+    }
+
+    #[test]
+    fn test_bookmarks_survive_file_filter() {
+        let diff = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1 +1,2 @@\n one\n+two\n\
+                    diff --git a/b.rs b/b.rs\n--- a/b.rs\n+++ b/b.rs\n@@ -1 +1,2 @@\n one\n+two\n";
+        let diff = DiffParser::new().parse(diff).unwrap();
+        let full = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        // Mark a line while only b.rs is shown, as with --reviewer-filter
+        let mut shown = full.clone();
+        shown.diff_data.files.remove(0);
+        let mut app = App::headless(shown, 80, 20);
+        app.move_down();
+        app.handle_normal_input(key('m')).unwrap();
+        app.handle_normal_input(key('b')).unwrap();
+
+        let mut full = full;
+        full.set_bookmarks(&app.session.bookmarks());
+        let app = App::headless(full, 80, 20);
+        let mark = app.navigator.bookmarks().get("b").unwrap();
+        assert_eq!((mark.file_idx, mark.line_idx), (1, 1));
+    }
+
+    fn recorder_test_app() -> App {
+        let diff = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,2 +1,3 @@\n one\n+two\n three\n";
+        let diff = DiffParser::new().parse(diff).unwrap();
+        App::headless(Session::new(DiffSource::WorkingTree, diff), 80, 20)
+    }
+
+    #[test]
+    fn test_recorder() {
+        let mut app = recorder_test_app();
+        app.start_recording();
+        for code in [
+            KeyCode::Char('j'),
+            KeyCode::Down,
+            KeyCode::Char('?'),
+            KeyCode::Esc,
+            KeyCode::Char('c'),
+            KeyCode::Char('o'),
+            KeyCode::Char('k'),
+            KeyCode::Enter,
+            KeyCode::Char('c'),
+            KeyCode::Esc,
+            KeyCode::Char('k'),
+            KeyCode::Char('s'),
+            KeyCode::Char('q'),
+        ] {
+            app.handle_input(KeyEvent::new(code, KeyModifiers::NONE)).unwrap();
+        }
+
+        assert_eq!(
+            app.finish_recording(),
+            vec![
+                HeadlessCommand::MoveDown(2),
+                HeadlessCommand::AddComment {
+                    content: "ok".to_string(),
+                    severity: Severity::Info,
+                },
+                HeadlessCommand::MoveUp(1),
+                HeadlessCommand::Save,
+                HeadlessCommand::Quit,
+            ]
+        );
+        assert!(app.finish_recording().is_empty());
+    }
+
+    #[test]
+    fn test_recorder_follows_app_modes() {
+        let mut app = recorder_test_app();
+        app.start_recording();
+        let mut keys = |text: &str| {
+            for c in text.chars() {
+                let code = match c {
+                    '\n' => KeyCode::Enter,
+                    '\x1b' => KeyCode::Esc,
+                    c => KeyCode::Char(c),
+                };
+                app.handle_input(KeyEvent::new(code, KeyModifiers::NONE)).unwrap();
+            }
+        };
+
+        // Search, go-to-file and file comments don't record their input
+        keys("/jjq\n");
+        keys("gfsrc/x.rs\x1b");
+        keys("Cnope\n");
+        // Bookmark names and help are skipped
+        keys("mj?xj");
+        // Without a commit history `L` stays in normal mode
+        keys("Lj");
+        // Palette commands are recorded as commands
+        keys(":w\n");
+
+        assert_eq!(
+            app.finish_recording(),
+            vec![HeadlessCommand::MoveDown(2), HeadlessCommand::Save]
+        );
+    }
+
+    #[test]
+    fn test_coverage_and_risk_follow_comments() {
+        let diff = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,2 +1,3 @@\n one\n+two\n three\n";
+        let diff = DiffParser::new().parse(diff).unwrap();
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+
+        let mut app = App::headless(session, 80, 20);
+        app.render_buffer().unwrap();
+        assert_eq!(app.state.coverage_percent, 0.0);
+        assert_eq!(app.state.risk_level, Some(RiskLevel::Low));
+
+        app.move_down();
+        app.state.editor_content = "Why?".to_string();
+        app.add_comment(Severity::Info);
+        app.render_buffer().unwrap();
+        assert_eq!(app.state.coverage_percent, 100.0);
+        assert_eq!(app.state.risk_level, Some(RiskLevel::Low));
+
+        app.set_risk_thresholds(RiskThresholds { medium: 0.5, high: 0.8, critical: 0.9 });
+        app.render_buffer().unwrap();
+        assert_eq!(app.state.risk_level, Some(RiskLevel::Critical));
+
+        app.delete_comment_at_line();
+        app.render_buffer().unwrap();
+        assert_eq!(app.state.coverage_percent, 0.0);
+        assert_eq!(app.state.risk_level, Some(RiskLevel::Low));
+    }
+
+    #[test]
+    fn test_session_commands() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let manager = SessionManager::new(cr_storage::FileSystemStorage::new(temp.path()).unwrap());
+        let diff = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,2 +1,3 @@\n one\n+two\n three\n";
+        let diff = DiffParser::new().parse(diff).unwrap();
+        let first = manager.create(DiffSource::Stdin, diff).unwrap();
+
+        let mut app = App::headless(first.clone(), 80, 20);
+        app.set_session_manager(manager.clone());
+        app.move_down();
+        app.state.editor_content = "Why?".to_string();
+        app.add_comment(Severity::Info);
+
+        // `:session new` saves the current session and switches to a copy of its diff
+        app.execute_command(Command::Session(SessionAction::New));
+        assert_ne!(app.session.id, first.id);
+        assert_eq!(app.session.diff_data.files.len(), 1);
+        assert!(app.session.comments.is_empty());
+        assert_eq!(manager.load(&first.id).unwrap().comments.count(), 1);
+
+        // `:session list` opens the picker on the current session, newest first
+        app.execute_command(Command::Session(SessionAction::List));
+        assert_eq!(app.state.mode, AppMode::SessionPicker);
+        assert_eq!(app.state.session_list.len(), 2);
+        assert_eq!(app.state.session_list[app.state.session_list_selected].id, app.session.id);
+        app.render_buffer().unwrap();
+
+        let target = app.state.session_list.iter().position(|s| s.id == first.id).unwrap();
+        app.state.session_list_selected = target;
+        app.handle_input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).unwrap();
+        assert_eq!(app.state.mode, AppMode::Normal);
+        assert_eq!(app.session.id, first.id);
+        assert_eq!(app.session.comments.count(), 1);
+    }
+
+    #[test]
+    fn test_g_prefix() {
+        let diff = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,2 +1,3 @@\n one\n+two\n three\n";
+        let diff = DiffParser::new().parse(diff).unwrap();
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        let mut app = App::headless(session, 80, 20);
+        app.move_down();
+        app.move_down();
+        app.handle_normal_input(key('g')).unwrap();
+        assert_eq!(app.state.current_line, 2);
+        app.handle_normal_input(key('f')).unwrap();
+        assert_eq!(app.state.mode, AppMode::GotoFile);
+        assert_eq!(app.state.current_line, 2);
+
+        app.state.mode = AppMode::Normal;
+        app.handle_normal_input(key('g')).unwrap();
+        app.handle_normal_input(key('g')).unwrap();
+        assert_eq!(app.state.current_line, 0);
+    }
+
+    #[test]
+    fn test_search_jumps_to_comment() {
+        use cr_core::comment::{CommentBuilder, DiffSide};
+
+        let diff = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,2 +1,3 @@\n one\n+two\n three\n";
+        let diff = DiffParser::new().parse(diff).unwrap();
+        let mut session = Session::new(cr_core::session::DiffSource::WorkingTree, diff.clone());
+        let file = &diff.files[0];
+        let comment = CommentBuilder::new(file.id.clone(), file.hunks[0].lines[1].id.clone(), DiffSide::New)
+            .content("Handle the error case")
+            .build()
+            .unwrap();
+        session.comments.add(comment).unwrap();
+
+        let mut app = App::headless(session, 80, 20);
+        app.search_comments("--regex err(or|ors)");
+        assert_eq!(app.state.search_mode, SearchMode::Regex);
+        assert_eq!(app.state.search_results.len(), 1);
+        assert_eq!(app.state.current_line, 1);
+
+        app.goto_top();
+        app.search_comments("--fuzzy handle the eror case");
+        assert_eq!(app.state.search_mode, SearchMode::Fuzzy);
+        assert_eq!(app.state.current_line, 1);
+
+        app.search_comments("missing");
+        assert!(app.state.search_results.is_empty());
+    }
+
+    #[test]
+    fn test_handle_paste_joins_lines() {
+        let diff = DiffParser::new().parse("diff --git a/a.rs b/a.rs\n@@ -1 +1 @@\n-a\n+b\n").unwrap();
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+        let mut app = App::headless(session, 80, 20);
+
+        app.handle_paste("ignored").unwrap();
+        assert!(app.state.editor_content.is_empty());
+
+        app.state.mode = AppMode::Insert;
+        app.state.editor_content = "Use ;".to_string();
+        app.state.editor_cursor = 4;
+        app.handle_paste("foo()\r\n  .bär()\n").unwrap();
+        assert_eq!(app.state.editor_content, "Use foo()   .bär();");
+        assert_eq!(app.state.editor_cursor, 18);
+    }
+
+    #[test]
+    fn test_suggested_severity_in_status_bar() {
+        let diff = DiffParser::new().parse("diff --git a/a.rs b/a.rs\n@@ -1 +1 @@\n-a\n+b\n").unwrap();
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+        let mut app = App::headless(session, 120, 20);
+        app.state.mode = AppMode::Insert;
+        assert_eq!(app.state.suggested_severity(), None);
+
+        for c in "this should be a set".chars() {
+            app.handle_insert_input(KeyEvent::from(KeyCode::Char(c))).unwrap();
+        }
+        assert_eq!(app.state.suggested_severity(), Some(Severity::Warning));
+        let screen: String = app.render_buffer().unwrap().content.iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("Suggested severity: Warning"));
+
+        app.state.editor_content.push_str(" (unsafe)");
+        assert_eq!(app.state.suggested_severity(), Some(Severity::Critical));
+    }
+
+    #[test]
+    fn test_diff_view_renders_visible_rows() {
+        let diff = DiffParser::new()
+            .parse("diff --git a/a.rs b/a.rs\n@@ -1 +1 @@\n-a\n+b\ndiff --git a/b.rs b/b.rs\n@@ -1,3 +1,3 @@\n one\n-two\n+zwei\n three\n")
+            .unwrap();
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+        let mut app = App::headless(session, 80, 20);
+        app.next_file();
+        app.move_down();
+        app.state.editor_content = "Translate back".to_string();
+        app.add_comment(Severity::Info);
+
+        let screen: String = app.render_buffer().unwrap().content.iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("@@ -1,3 +1,3 @@"));
+        assert!(screen.contains("Translate back"));
+        assert!(!screen.contains("+b "));
+
+        // Scrolled past the header and the first two lines
+        app.state.scroll_offset = 3;
+        let screen: String = app.render_buffer().unwrap().content.iter().map(|c| c.symbol()).collect();
+        assert!(!screen.contains("@@"));
+        assert!(!screen.contains("two"));
+        assert!(screen.contains("Translate back"));
+        assert!(screen.contains("three"));
+    }
+
+    fn screen_text(app: &mut App) -> String {
+        app.render_buffer().unwrap().content.iter().map(|c| c.symbol()).collect()
+    }
+
+    #[test]
+    fn test_config_line_numbers_and_wrap() {
+        let long_line = format!("{}TAIL", "x".repeat(100));
+        let diff = DiffParser::new()
+            .parse(&format!("diff --git a/a.rs b/a.rs\n@@ -1 +1 @@\n-alpha\n+{}\n", long_line))
+            .unwrap();
+        let mut app = App::headless(Session::new(cr_core::session::DiffSource::WorkingTree, diff), 80, 20);
+
+        let screen = screen_text(&mut app);
+        assert!(screen.contains("   1      -alpha"));
+        assert!(!screen.contains("TAIL"));
+
+        app.state.config.show_line_numbers = false;
+        app.state.config.word_wrap = true;
+        let screen = screen_text(&mut app);
+        assert!(screen.contains("│ -alpha"));
+        assert!(screen.contains("TAIL"));
+    }
+
+    #[test]
+    fn test_config_highlight_cursor_line() {
+        let diff = DiffParser::new()
+            .parse("diff --git a/a.rs b/a.rs\n@@ -1 +1 @@\n-alpha\n+beta\n")
+            .unwrap();
+        let mut app = App::headless(Session::new(cr_core::session::DiffSource::WorkingTree, diff), 80, 20);
+        let alpha_bg = |app: &mut App| {
+            let buffer = app.render_buffer().unwrap();
+            let area = buffer.area;
+            (0..area.height)
+                .flat_map(|y| (0..area.width - 1).map(move |x| (x, y)))
+                .find(|&(x, y)| buffer.cell((x, y)).unwrap().symbol() == "-" && buffer.cell((x + 1, y)).unwrap().symbol() == "a")
+                .map(|(x, y)| buffer.cell((x + 1, y)).unwrap().bg)
+                .unwrap()
+        };
+
+        assert_eq!(alpha_bg(&mut app), Color::DarkGray);
+        app.state.config.highlight_cursor_line = false;
+        assert_eq!(alpha_bg(&mut app), Color::Rgb(40, 0, 0));
+    }
+
+    #[test]
+    fn test_config_scroll_padding() {
+        let mut input = "diff --git a/a.rs b/a.rs\n@@ -0,0 +1,40 @@\n".to_string();
+        (0..40).for_each(|i| input.push_str(&format!("+line {}\n", i)));
+        let diff = DiffParser::new().parse(&input).unwrap();
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+
+        // 20 rows leave 16 for the diff
+        let mut app = App::headless(session.clone(), 80, 20);
+        (0..13).for_each(|_| app.move_down());
+        assert_eq!(app.state.scroll_offset, 1);
+
+        let mut app = App::headless(session, 80, 20);
+        app.state.config.scroll_padding = 0;
+        (0..13).for_each(|_| app.move_down());
+        assert_eq!(app.state.scroll_offset, 0);
+        (0..3).for_each(|_| app.move_down());
+        assert_eq!(app.state.scroll_offset, 1);
+    }
+
+    #[test]
+    fn test_scroll_padding_with_word_wrap() {
+        let mut input = "diff --git a/a.rs b/a.rs\n@@ -0,0 +1,20 @@\n".to_string();
+        (0..20).for_each(|i| input.push_str(&format!("+{:02}{}\n", i, "x".repeat(200))));
+        let diff = DiffParser::new().parse(&input).unwrap();
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+
+        // Each line wraps to 4 of the 16 rows: the header and 3 lines fit
+        let mut app = App::headless(session, 80, 20);
+        app.state.config.scroll_padding = 0;
+        app.state.config.word_wrap = true;
+        (0..2).for_each(|_| app.move_down());
+        assert_eq!(app.state.scroll_offset, 0);
+        app.move_down();
+        assert_eq!(app.state.scroll_offset, 1);
+        app.move_down();
+        assert_eq!(app.state.scroll_offset, 2);
+        let screen = screen_text(&mut app);
+        assert!(screen.contains("+04xx"));
+        assert!(!screen.contains("+00xx"));
+
+        // Unwrapped, the same lines fit without scrolling
+        app.state.config.word_wrap = false;
+        app.state.scroll_offset = 0;
+        app.move_down();
+        assert_eq!(app.state.scroll_offset, 0);
+    }
+
+    #[test]
+    fn test_binary_file_preview() {
+        let diff = DiffParser::new()
+            .parse("diff --git a/missing.png b/missing.png\nBinary files a/missing.png and b/missing.png differ\n")
+            .unwrap();
+        assert!(diff.files[0].is_binary());
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+        let mut app = App::headless(session.clone(), 80, 20);
+        app.state.editor_content = "Compress this".to_string();
+        app.add_comment(Severity::Warning);
+
+        let screen: String = app.render_buffer().unwrap().content.iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("Compress this"));
+        assert!(screen.contains("(Binary file not in the working tree)"));
+
+        let mut app = App::headless(session.clone(), 80, 20);
+        app.state.no_binary_preview = true;
+        let screen: String = app.render_buffer().unwrap().content.iter().map(|c| c.symbol()).collect();
+        assert!(!screen.contains("Binary file not"));
+        assert!(app.state.binary_previews.is_empty());
+
+        // Paths are resolved against the repository, not the current directory
+        let repo = tempfile::TempDir::new().unwrap();
+        std::fs::write(repo.path().join("missing.png"), b"not really a png").unwrap();
+        let mut in_repo = session.clone();
+        in_repo.metadata.repository = Some(repo.path().to_path_buf());
+        let mut app = App::headless(in_repo.clone(), 80, 20);
+        app.render_buffer().unwrap();
+        let preview = &app.state.binary_previews[&in_repo.diff_data.files[0].id];
+        assert_eq!(preview[0], "Binary file, 16 bytes");
+
+        in_repo.diff_source = cr_core::session::DiffSource::Commit {
+            commit: "abc123".to_string(),
+        };
+        let preview = |session: &Session| {
+            let mut app = App::headless(session.clone(), 80, 20);
+            app.render_buffer().unwrap();
+            app.state.binary_previews[&session.diff_data.files[0].id].clone()
+        };
+        assert_eq!(preview(&in_repo), ["(Binary file not found in git)"]);
+
+        in_repo.diff_source = cr_core::session::DiffSource::Stdin;
+        assert_eq!(preview(&in_repo), ["(Binary preview unavailable for this diff source)"]);
+
+        // hg and svn diffs against a revision show the working copy, labelled
+        in_repo.diff_source = cr_core::session::DiffSource::Svn { rev: Some(3) };
+        let svn = preview(&in_repo);
+        assert_eq!(svn[0], "(Working tree copy)");
+        assert_eq!(svn[1], "Binary file, 16 bytes");
+
+        // Staged changes preview the index, not the working tree
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(repo.path())
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["add", "missing.png"]);
+        std::fs::write(repo.path().join("missing.png"), b"changed after staging").unwrap();
+        in_repo.diff_source = cr_core::session::DiffSource::Staged;
+        assert_eq!(preview(&in_repo)[0], "Binary file, 16 bytes");
+    }
+
+    #[test]
+    fn test_file_mode_filter() {
+        let diff = DiffParser::new()
+            .parse(
+                "diff --git a/new.rs b/new.rs\nnew file mode 100644\n--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1 @@\n+a\n\
+                 diff --git a/lib.rs b/lib.rs\n@@ -1 +1,2 @@\n a\n+b\n\
+                 diff --git a/old.rs b/old.rs\ndeleted file mode 100644\n--- a/old.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-a\n",
+            )
+            .unwrap();
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+        let mut app = App::headless(session, 100, 20);
+
+        app.set_file_mode_filter(vec![FileMode::Deleted]);
+        assert_eq!(app.state.current_file, 2);
+        app.prev_file();
+        assert_eq!(app.state.current_file, 2);
+
+        app.handle_normal_input(KeyEvent::from(KeyCode::Char('1'))).unwrap();
+        assert_eq!(app.state.file_mode_filter, vec![FileMode::Deleted, FileMode::Added]);
+        app.prev_file();
+        assert_eq!(app.state.current_file, 0);
+        let screen: String = app.render_buffer().unwrap().content.iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("1 Added") && screen.contains("4 Renamed"));
+
+        app.handle_normal_input(KeyEvent::from(KeyCode::Char('0'))).unwrap();
+        app.next_file();
+        assert_eq!(app.state.current_file, 1);
+        let screen: String = app.render_buffer().unwrap().content.iter().map(|c| c.symbol()).collect();
+        assert!(!screen.contains("4 Renamed"));
+    }
+
+    #[test]
+    fn test_file_stats_in_title_bar() {
+        let input = "diff --git a/a.rs b/a.rs\n@@ -1 +1,2 @@\n-a\n+b\n+c\n@@ -9 +10 @@\n-x\n+y\n";
+        let diff = DiffParser::new().parse(input).unwrap();
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+
+        let mut app = App::headless(session, 120, 20);
+        let screen: String = app.render_buffer().unwrap().content.iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("+3 -2 in 2 hunk(s)"));
+    }
+
+    #[test]
+    fn test_truncated_diff_in_title_bar() {
+        let input = "diff --git a/a.rs b/a.rs\n@@ -1 +1 @@\n-a\n+b\n\
+                     diff --git a/b.rs b/b.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        let (diff, truncated) = DiffParser::new().parse_partial(input, 1).unwrap();
+        assert!(truncated);
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+
+        let mut app = App::headless(session, 120, 20);
+        assert_eq!(app.state.truncated_files, Some((1, 2)));
+        let screen: String = app.render_buffer().unwrap().content.iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("Large diff: showing first 1 of 2 files"));
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("\x1b[1;31mred\x1b[0m and plain"), "red and plain");
+        assert_eq!(strip_ansi("no escapes [here]"), "no escapes [here]");
+    }
+
+    #[test]
+    fn test_lazy_file_loads_into_navigator_and_session() {
+        // Tests run in the crate directory
+        let mut diff = cr_core::diff::DiffData::empty();
+        diff.files.push(FileDiff::lazy_new(PathBuf::from("Cargo.toml")));
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+
+        let app = App::headless(session, 80, 20);
+        let shown = &app.session.diff_data.files[0];
+        assert!(!shown.needs_loading());
+        assert!(!shown.hunks.is_empty());
+        assert_eq!(app.navigator.diff().files[0].total_lines(), shown.total_lines());
+
+        // Loading refreshes the stored stats and the title bar's counts
+        let stats = &app.session.diff_data.stats;
+        assert_eq!(stats.per_file.len(), 1);
+        assert!(stats.insertions > 0);
+        assert_eq!(app.navigator.diff().stats.insertions, stats.insertions);
+        assert_eq!(app.state.file_stats[&shown.id].insertions, stats.insertions);
+    }
+
+    #[test]
+    fn test_lazy_file_renders() {
+        // Tests run in the crate directory
+        let mut diff = cr_core::diff::DiffData::empty();
+        diff.files.push(FileDiff::lazy_new(PathBuf::from("Cargo.toml")));
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+
+        let mut app = App::headless(session, 80, 20);
+        let screen: String = app.render_buffer().unwrap().content.iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("[package]"));
+    }
+
+    #[test]
+    fn test_run_headless() {
+        let diff = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,2 +1,3 @@\n one\n+two\n three\n";
+        let diff = DiffParser::new().parse(diff).unwrap();
+        let mut session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+
+        let frames = App::run_headless(
+            &mut session,
+            &[
+                HeadlessCommand::MoveDown(1),
+                HeadlessCommand::AddComment {
+                    content: "Unchecked input".to_string(),
+                    severity: Severity::Critical,
+                },
+                HeadlessCommand::MoveDown(1),
+                HeadlessCommand::AddComment {
+                    content: "Typo".to_string(),
+                    severity: Severity::Info,
+                },
+                HeadlessCommand::DeleteComment,
+                HeadlessCommand::Quit,
+                HeadlessCommand::NextFile,
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(frames.len(), 6);
+        let screen: String = frames[1].content.iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("Unchecked input"));
+
+        let comments = session.comments.all();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].severity, Severity::Critical);
+        assert_eq!(comments[0].metadata.line_number, Some(2));
+    }
+
+    #[test]
+    fn test_record_session_video() {
+        let diff = "diff --git a/a.rs b/a.rs\n@@ -1,2 +1,3 @@\n one\n+two\n three\n\
+                    diff --git a/b.rs b/b.rs\n@@ -1 +1 @@\n-x\n+y\n";
+        let diff = DiffParser::new().parse(diff).unwrap();
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, diff);
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("review.cast");
+
+        App::headless(session.clone(), 80, 20).record_session_video_with_speed(&path, 4, 2.0).unwrap();
+        let cast = std::fs::read_to_string(&path).unwrap();
+        let events: Vec<serde_json::Value> = cast.lines().skip(1).map(|l| serde_json::from_str(l).unwrap()).collect();
+        // Three lines of a.rs, then both lines of b.rs
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[4][0].as_f64(), Some(0.5));
+        assert!(events[4][2].as_str().unwrap().contains("b.rs"));
+
+        assert!(App::headless(session.clone(), 80, 20).record_session_video(&path, 0).is_err());
+        assert!(App::headless(session, 80, 20).record_session_video_with_speed(&path, 10, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_editor_command_with_args() {
+        let editor = EditorCommand::parse("vim -u /tmp/vimrc").unwrap();
+        let command = editor.command(Path::new("src/main.rs"), 42);
+
+        assert_eq!(command.get_program(), "vim");
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, ["-u", "/tmp/vimrc", "+42", "src/main.rs"]);
+    }
+
+    #[test]
+    fn test_editor_command_precedence() {
+        let visual = Some("code --wait".to_string());
+        let editor = Some("vim -u /tmp/vimrc".to_string());
+
+        assert_eq!(EditorCommand::from_vars(visual, editor.clone()).program, "code");
+        assert_eq!(EditorCommand::from_vars(Some("  ".to_string()), editor).program, "vim");
+        assert_eq!(EditorCommand::from_vars(None, None).program, "vi");
+    }
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert!(fuzzy_match("src/app/state.rs", "state"));
+        assert!(fuzzy_match("src/app/state.rs", "sapst"));
+        assert!(fuzzy_match("src/App.rs", "app"));
+        assert!(fuzzy_match("src/app.rs", ""));
+        assert!(!fuzzy_match("src/app.rs", "state"));
+    }
+
+    #[test]
+    fn test_commit_log_sidebar() {
+        let diff = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        let session = Session::new(cr_core::session::DiffSource::WorkingTree, DiffParser::new().parse(diff).unwrap());
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let commit = |sha: &str, subject: &str| CommitInfo {
+            sha: sha.repeat(40),
+            short_sha: sha.repeat(7),
+            subject: subject.to_string(),
+            author: "Ann".to_string(),
+            date: chrono::Utc::now(),
+        };
+
+        let mut app = App::headless(session, 100, 20);
+        app.handle_normal_input(key(KeyCode::Char('L'))).unwrap();
+        assert_eq!(app.state.mode, AppMode::Normal);
+
+        app.state.commit_log = vec![commit("f", "Newer change"), commit("e", "Reviewed change")];
+        app.state.context_commit = Some("e".repeat(40));
+        app.handle_normal_input(key(KeyCode::Char('L'))).unwrap();
+        assert_eq!(app.state.mode, AppMode::CommitLog);
+        assert_eq!(app.state.commit_log_selected, 1);
+
+        let buffer = app.render_buffer().unwrap();
+        let screen: String = buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("Commits"));
+        assert!(screen.contains("eeeeeee Reviewed change"));
+
+        // Clicking the first row selects it; the made-up commit can't be shown
+        app.handle_mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 80,
+            row: 1,
+            modifiers: KeyModifiers::NONE,
+        });
+        assert_eq!(app.state.commit_log_selected, 0);
+        assert_eq!(app.state.mode, AppMode::CommitLog);
+        assert!(app.state.message.as_deref().unwrap().starts_with("Failed to show commit fffffff"));
+
+        app.state.commit_diff = vec!["commit ffff".to_string(), "+added".to_string()];
+        app.state.mode = AppMode::CommitDiff;
+        app.handle_input(key(KeyCode::Char('j'))).unwrap();
+        assert_eq!(app.state.commit_diff_scroll, 1);
+        app.handle_input(key(KeyCode::Esc)).unwrap();
+        app.handle_input(key(KeyCode::Esc)).unwrap();
+        assert_eq!(app.state.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_split_word_changes() {
+        let changed = [(8, 18)];
+        assert_eq!(split_word_changes("let x = ", 0, &changed), vec![("let x = ", false)]);
+        assert_eq!(
+            split_word_changes("= bar(a, c); // x", 6, &changed),
+            vec![("= ", false), ("bar(a, c);", true), (" // x", false)]
+        );
+        assert_eq!(split_word_changes("", 0, &changed), vec![]);
+        assert_eq!(split_word_changes("abc", 0, &[]), vec![("abc", false)]);
+    }
+
     #[test]
     fn test_app_state_message() {
         let mut state = AppState::new();
@@ -890,4 +3595,20 @@ mod tests {
         state.clear_message();
         assert!(state.message.is_none());
     }
+
+    #[test]
+    fn test_timed_message_expires() {
+        let mut state = AppState::new();
+        state.set_timed_message("Exported", Duration::from_secs(5));
+        let start = Instant::now();
+
+        state.expire_message(start);
+        assert_eq!(state.message.as_deref(), Some("Exported"));
+        state.expire_message(start + Duration::from_secs(6));
+        assert!(state.message.is_none());
+
+        state.set_message("Sticky");
+        state.expire_message(start + Duration::from_secs(60));
+        assert_eq!(state.message.as_deref(), Some("Sticky"));
+    }
 }
@@ -0,0 +1,229 @@
+//! Previews for binary files, which have no diff lines to show
+
+use image::{imageops, ImageFormat};
+use std::fs::File;
+use std::io::{self, Cursor, Read, Seek};
+use std::path::Path;
+
+/// Bytes shown in the hex dump
+const HEX_DUMP_BYTES: usize = 256;
+
+/// Largest image decoded for a thumbnail
+const MAX_IMAGE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Archive entries listed before the rest are summarized
+const MAX_ARCHIVE_ENTRIES: usize = 20;
+
+/// Characters for the ASCII thumbnail, darkest first
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+/// Builds the text shown in place of the diff for a binary file
+///
+/// Images get their dimensions and an ASCII thumbnail, ZIP archives their
+/// file index and PDFs their version; every file ends with an `xxd`-style
+/// hex dump of its first 256 bytes.
+#[derive(Debug, Clone)]
+pub struct BinaryPreviewRenderer {
+    /// Thumbnail width in characters
+    pub thumbnail_width: u32,
+}
+
+impl Default for BinaryPreviewRenderer {
+    fn default() -> Self {
+        Self { thumbnail_width: 32 }
+    }
+}
+
+impl BinaryPreviewRenderer {
+    /// Create a renderer with a 32-column thumbnail
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Preview lines for the file at `path` (used for its extension) holding `bytes`
+    pub fn render(&self, path: &Path, bytes: &[u8]) -> Vec<String> {
+        let head = &bytes[..bytes.len().min(HEX_DUMP_BYTES)];
+        self.render_from(path, bytes.len() as u64, head, Cursor::new(bytes))
+    }
+
+    /// Preview lines for the file at `path`, read from disk
+    ///
+    /// Only the first 256 bytes are read up front. ZIP archives are listed
+    /// from their central directory, and images are only decoded for a
+    /// thumbnail when they are at most 4 MiB.
+    pub fn render_file(&self, path: &Path) -> io::Result<Vec<String>> {
+        let mut file = File::open(path)?;
+        let size = file.metadata()?.len();
+        let mut head = Vec::with_capacity(HEX_DUMP_BYTES);
+        (&mut file).take(HEX_DUMP_BYTES as u64).read_to_end(&mut head)?;
+        Ok(self.render_from(path, size, &head, file))
+    }
+
+    /// Preview lines for a file of `size` bytes starting with `head`
+    fn render_from<R: Read + Seek>(
+        &self,
+        path: &Path,
+        size: u64,
+        head: &[u8],
+        mut content: R,
+    ) -> Vec<String> {
+        let mut lines = vec![format!("Binary file, {} bytes", size), String::new()];
+
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if let Some(format) = ImageFormat::from_extension(extension) {
+            if size > MAX_IMAGE_BYTES {
+                lines.push(format!("{:?} image, too large for a thumbnail", format));
+                lines.push(String::new());
+            } else {
+                let mut bytes = Vec::new();
+                match content.rewind().and_then(|_| content.read_to_end(&mut bytes)) {
+                    Ok(_) => lines.extend(self.image_lines(&bytes, format)),
+                    Err(e) => lines.extend([format!("Unreadable {:?} image: {}", format, e), String::new()]),
+                }
+            }
+        } else if head.starts_with(b"PK\x03\x04") {
+            lines.extend(archive_lines(content));
+        } else if let Some(version) = pdf_version(head) {
+            lines.push(format!("PDF document, version {}", version));
+            lines.push(String::new());
+        }
+
+        lines.extend(hex_dump(head));
+        lines
+    }
+
+    /// Image metadata and an ASCII thumbnail
+    fn image_lines(&self, bytes: &[u8], format: ImageFormat) -> Vec<String> {
+        let image = match image::load_from_memory_with_format(bytes, format) {
+            Ok(image) => image,
+            Err(e) => return vec![format!("Unreadable {:?} image: {}", format, e), String::new()],
+        };
+
+        let mut lines = vec![
+            format!("{:?} image, {}x{}", format, image.width(), image.height()),
+            String::new(),
+        ];
+        // Terminal cells are about twice as tall as they are wide
+        let width = self.thumbnail_width.min(image.width()).max(1);
+        let height = ((image.height() as f64 * width as f64 / image.width() as f64) / 2.0)
+            .round()
+            .max(1.0) as u32;
+        let thumbnail = imageops::thumbnail(&image.to_luma8(), width, height);
+        for row in thumbnail.rows() {
+            lines.push(
+                row.map(|pixel| {
+                    let level = pixel.0[0] as usize * (ASCII_RAMP.len() - 1) / 255;
+                    ASCII_RAMP[level] as char
+                })
+                .collect(),
+            );
+        }
+        lines.push(String::new());
+        lines
+    }
+}
+
+/// Entry names and sizes of a ZIP archive
+fn archive_lines<R: Read + Seek>(content: R) -> Vec<String> {
+    let mut archive = match zip::ZipArchive::new(content) {
+        Ok(archive) => archive,
+        Err(e) => return vec![format!("Unreadable ZIP archive: {}", e), String::new()],
+    };
+
+    let mut lines = vec![format!("ZIP archive, {} entries", archive.len())];
+    for i in 0..archive.len().min(MAX_ARCHIVE_ENTRIES) {
+        if let Ok(entry) = archive.by_index(i) {
+            lines.push(format!("  {:>10}  {}", entry.size(), entry.name()));
+        }
+    }
+    if archive.len() > MAX_ARCHIVE_ENTRIES {
+        lines.push(format!("  ... and {} more", archive.len() - MAX_ARCHIVE_ENTRIES));
+    }
+    lines.push(String::new());
+    lines
+}
+
+/// Version from a `%PDF-1.7` header
+fn pdf_version(bytes: &[u8]) -> Option<String> {
+    let rest = bytes.strip_prefix(b"%PDF-")?;
+    let version: String = rest
+        .iter()
+        .take_while(|b| b.is_ascii_digit() || **b == b'.')
+        .map(|b| *b as char)
+        .collect();
+    (!version.is_empty()).then_some(version)
+}
+
+/// `xxd`-style lines: offset, 16 bytes in groups of two, then the ASCII column
+pub fn hex_dump(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut hex = String::new();
+            for (j, byte) in chunk.iter().enumerate() {
+                if j > 0 && j % 2 == 0 {
+                    hex.push(' ');
+                }
+                hex.push_str(&format!("{:02x}", byte));
+            }
+            let ascii: String = chunk
+                .iter()
+                .map(|b| if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' })
+                .collect();
+            format!("{:08x}: {:<39}  {}", i * 16, hex, ascii)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_hex_dump() {
+        let lines = hex_dump(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR\x01");
+        assert_eq!(
+            lines,
+            [
+                "00000000: 8950 4e47 0d0a 1a0a 0000 000d 4948 4452  .PNG........IHDR",
+                "00000010: 01                                       .",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_image() {
+        let image = image::GrayImage::from_fn(8, 4, |x, _| image::Luma([if x < 4 { 0 } else { 255 }]));
+        let mut png = Vec::new();
+        image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png).unwrap();
+
+        let lines = BinaryPreviewRenderer::new().render(Path::new("logo.png"), &png);
+        assert_eq!(lines[0], format!("Binary file, {} bytes", png.len()));
+        assert_eq!(lines[2], "Png image, 8x4");
+        assert_eq!(lines[4], "    @@@@");
+        assert!(lines.last().unwrap().starts_with("000000"));
+    }
+
+    #[test]
+    fn test_render_archive_and_pdf() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("docs/readme.txt", zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"hello").unwrap();
+        let bytes = zip.finish().unwrap().into_inner();
+
+        let lines = BinaryPreviewRenderer::new().render(Path::new("bundle.jar"), &bytes);
+        assert_eq!(lines[2], "ZIP archive, 1 entries");
+        assert_eq!(lines[3], "           5  docs/readme.txt");
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("bundle.jar");
+        std::fs::write(&path, &bytes).unwrap();
+        let from_file = BinaryPreviewRenderer::new().render_file(&path).unwrap();
+        assert_eq!(from_file, lines);
+
+        let lines = BinaryPreviewRenderer::new().render(Path::new("spec.pdf"), b"%PDF-1.7\n%\xe2\xe3");
+        assert_eq!(lines[2], "PDF document, version 1.7");
+    }
+}
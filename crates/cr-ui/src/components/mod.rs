@@ -1,5 +1,9 @@
 //! UI components
 
+mod binary_preview;
+
+pub use binary_preview::{hex_dump, BinaryPreviewRenderer};
+
 // Placeholder types - can be expanded later
 pub struct DiffView;
 pub struct CommentList;
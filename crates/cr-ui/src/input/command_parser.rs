@@ -0,0 +1,191 @@
+//! Command palette parsing (`:` commands)
+
+use anyhow::{bail, Result};
+use cr_core::comment::Severity;
+
+/// Commands shown in the palette autocomplete list
+pub const COMMANDS: &[&str] = &[
+    "save",
+    "export json",
+    "export markdown",
+    "set theme",
+    "set theme default",
+    "set theme high-contrast",
+    "set theme monokai",
+    "set theme solarized-dark",
+    "set theme solarized-light",
+    "session new",
+    "session list",
+    "goto ",
+    "marks",
+    "filter severity critical",
+    "filter severity warning",
+    "filter severity info",
+    "filter off",
+    "help",
+    "quit",
+];
+
+/// Session actions available from the palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionAction {
+    /// Start a new session
+    New,
+    /// List sessions
+    List,
+}
+
+/// A parsed palette command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Save the session (`:save`, `:w`)
+    Save,
+    /// Export the session in a format, optionally to a path
+    Export {
+        /// Export format name
+        format: String,
+        /// Output path
+        path: Option<String>,
+    },
+    /// Switch color theme
+    SetTheme(String),
+    /// Open the theme preview (`:set theme` without a name)
+    PreviewTheme,
+    /// Session management
+    Session(SessionAction),
+    /// Jump to a file by path
+    Goto(String),
+    /// List bookmarks (`:marks`)
+    Marks,
+    /// Only show comments of this severity (`None` clears the filter)
+    FilterSeverity(Option<Severity>),
+    /// Show help
+    Help,
+    /// Quit (`:quit`, `:q`)
+    Quit,
+}
+
+/// Parser for palette input
+pub struct CommandParser;
+
+impl CommandParser {
+    /// Parse a command line (without the leading `:`)
+    pub fn parse(input: &str) -> Result<Command> {
+        let mut words = input.split_whitespace();
+        let Some(name) = words.next() else {
+            bail!("Empty command");
+        };
+        let args: Vec<&str> = words.collect();
+
+        let command = match (name, args.as_slice()) {
+            ("save" | "w", []) => Command::Save,
+            ("quit" | "q", []) => Command::Quit,
+            ("help", []) => Command::Help,
+            ("export", [format]) => Command::Export {
+                format: format.to_string(),
+                path: None,
+            },
+            ("export", [format, path]) => Command::Export {
+                format: format.to_string(),
+                path: Some(path.to_string()),
+            },
+            ("set", ["theme"]) => Command::PreviewTheme,
+            ("set", ["theme", theme]) => Command::SetTheme(theme.to_string()),
+            ("session", ["new"]) => Command::Session(SessionAction::New),
+            ("session", ["list"]) => Command::Session(SessionAction::List),
+            ("goto", [path]) => Command::Goto(path.to_string()),
+            ("marks", []) => Command::Marks,
+            ("filter", ["off" | "clear"]) => Command::FilterSeverity(None),
+            ("filter", ["severity", severity]) => match Severity::from_short_string(severity) {
+                Some(severity) => Command::FilterSeverity(Some(severity)),
+                None => bail!("Unknown severity: {}", severity),
+            },
+            _ => bail!("Unknown command: {}", input.trim()),
+        };
+
+        Ok(command)
+    }
+
+    /// Palette entries starting with the current input
+    pub fn completions(input: &str) -> Vec<&'static str> {
+        let input = input.trim_start();
+        COMMANDS
+            .iter()
+            .copied()
+            .filter(|c| c.starts_with(input))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simple_commands() {
+        assert_eq!(CommandParser::parse("save").unwrap(), Command::Save);
+        assert_eq!(CommandParser::parse("w").unwrap(), Command::Save);
+        assert_eq!(CommandParser::parse(" q ").unwrap(), Command::Quit);
+        assert_eq!(CommandParser::parse("marks").unwrap(), Command::Marks);
+        assert_eq!(
+            CommandParser::parse("session list").unwrap(),
+            Command::Session(SessionAction::List)
+        );
+    }
+
+    #[test]
+    fn test_parse_with_arguments() {
+        assert_eq!(
+            CommandParser::parse("export json").unwrap(),
+            Command::Export {
+                format: "json".to_string(),
+                path: None
+            }
+        );
+        assert_eq!(
+            CommandParser::parse("export markdown out.md").unwrap(),
+            Command::Export {
+                format: "markdown".to_string(),
+                path: Some("out.md".to_string())
+            }
+        );
+        assert_eq!(
+            CommandParser::parse("set theme solarized").unwrap(),
+            Command::SetTheme("solarized".to_string())
+        );
+        assert_eq!(
+            CommandParser::parse("set theme").unwrap(),
+            Command::PreviewTheme
+        );
+        assert_eq!(
+            CommandParser::parse("goto src/main.rs").unwrap(),
+            Command::Goto("src/main.rs".to_string())
+        );
+        assert_eq!(
+            CommandParser::parse("filter severity critical").unwrap(),
+            Command::FilterSeverity(Some(Severity::Critical))
+        );
+        assert_eq!(
+            CommandParser::parse("filter off").unwrap(),
+            Command::FilterSeverity(None)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(CommandParser::parse("").is_err());
+        assert!(CommandParser::parse("frobnicate").is_err());
+        assert!(CommandParser::parse("goto").is_err());
+        assert!(CommandParser::parse("filter severity urgent").is_err());
+    }
+
+    #[test]
+    fn test_completions() {
+        assert_eq!(
+            CommandParser::completions("exp"),
+            vec!["export json", "export markdown"]
+        );
+        assert!(CommandParser::completions("xyz").is_empty());
+        assert_eq!(CommandParser::completions("").len(), COMMANDS.len());
+    }
+}
@@ -1,5 +1,9 @@
 //! Input handling
 
+pub mod command_parser;
+
+pub use command_parser::{Command, CommandParser, SessionAction};
+
 /// Input handler
 pub struct InputHandler;
 
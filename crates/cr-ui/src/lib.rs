@@ -13,10 +13,10 @@
 //! # Example
 //!
 //! ```ignore
-//! use cr_ui::App;
+//! use cr_ui::{App, TuiConfig};
 //! use cr_core::session::Session;
 //!
-//! let app = App::new(session)?;
+//! let app = App::new(session, TuiConfig::default())?;
 //! app.run()?;
 //! ```
 
@@ -26,7 +26,9 @@ pub mod events;
 pub mod highlight;
 pub mod input;
 pub mod layout;
+pub mod recording;
+pub mod screenshot;
 pub mod theme;
 
-pub use app::{App, AppMode, AppState};
+pub use app::{App, AppMode, AppState, EditorCommand, HeadlessCommand, HeadlessRecorder, SearchMode, TuiConfig};
 pub use highlight::Highlighter;
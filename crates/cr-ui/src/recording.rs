@@ -0,0 +1,171 @@
+//! asciicast v2 recordings of rendered frames
+
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthStr;
+
+/// Render a buffer as ANSI text that redraws the whole screen
+///
+/// The cursor is sent home first; rows are separated by `\r\n` and each
+/// run of equally styled cells is preceded by its SGR sequence.
+pub fn buffer_to_ansi(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut out = String::from("\x1b[H");
+
+    for y in 0..area.height {
+        if y > 0 {
+            out.push_str("\r\n");
+        }
+        let mut style = None;
+        let mut x = 0;
+        while x < area.width {
+            let cell = &buffer[(area.x + x, area.y + y)];
+            let cell_style = (cell.fg, cell.bg, cell.modifier);
+            if style != Some(cell_style) {
+                out.push_str(&sgr(cell.fg, cell.bg, cell.modifier));
+                style = Some(cell_style);
+            }
+            out.push_str(cell.symbol());
+            // Cells behind a wide character are not drawn
+            x += cell.symbol().width().max(1) as u16;
+        }
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+/// Writes an asciicast v2 recording one frame at a time
+///
+/// Frames are shown at `fps` frames per second, played back `speed` times
+/// faster. Each frame is written as soon as it is added, so a long
+/// recording never holds more than one frame in memory.
+pub struct AsciicastWriter<W: Write> {
+    out: W,
+    fps: u8,
+    speed: f64,
+    frames: usize,
+}
+
+impl<W: Write> AsciicastWriter<W> {
+    /// Write the header for a `width` x `height` recording
+    pub fn new(mut out: W, width: u16, height: u16, fps: u8, speed: f64) -> io::Result<Self> {
+        let header = serde_json::json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": chrono::Utc::now().timestamp(),
+            "env": { "TERM": "xterm-256color" },
+        });
+        writeln!(out, "{}", header)?;
+        Ok(Self {
+            out,
+            fps,
+            speed,
+            frames: 0,
+        })
+    }
+
+    /// Append a frame as one output event
+    pub fn write_frame(&mut self, frame: &Buffer) -> io::Result<()> {
+        let mut data = buffer_to_ansi(frame);
+        if self.frames == 0 {
+            data.insert_str(0, "\x1b[2J");
+        }
+        let time = self.frames as f64 / self.fps as f64 / self.speed;
+        writeln!(self.out, "{}", serde_json::json!([(time * 1e6).round() / 1e6, "o", data]))?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Flush and return the underlying writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// `ESC [ ... m` selecting a cell's colors and modifiers
+fn sgr(fg: Color, bg: Color, modifier: Modifier) -> String {
+    let mut codes = vec!["0".to_string()];
+    for (flag, code) in [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ] {
+        if modifier.contains(flag) {
+            codes.push(code.to_string());
+        }
+    }
+    codes.extend(color_code(fg, false));
+    codes.extend(color_code(bg, true));
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+/// SGR parameter for a foreground (or background) color; none for `Reset`
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let (base, extended) = if background { (40, 48) } else { (30, 38) };
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base.to_string(),
+        Color::Red => (base + 1).to_string(),
+        Color::Green => (base + 2).to_string(),
+        Color::Yellow => (base + 3).to_string(),
+        Color::Blue => (base + 4).to_string(),
+        Color::Magenta => (base + 5).to_string(),
+        Color::Cyan => (base + 6).to_string(),
+        Color::Gray => (base + 7).to_string(),
+        Color::DarkGray => (base + 60).to_string(),
+        Color::LightRed => (base + 61).to_string(),
+        Color::LightGreen => (base + 62).to_string(),
+        Color::LightYellow => (base + 63).to_string(),
+        Color::LightBlue => (base + 64).to_string(),
+        Color::LightMagenta => (base + 65).to_string(),
+        Color::LightCyan => (base + 66).to_string(),
+        Color::White => (base + 67).to_string(),
+        Color::Indexed(i) => format!("{};5;{}", extended, i),
+        Color::Rgb(r, g, b) => format!("{};2;{};{};{}", extended, r, g, b),
+    };
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    #[test]
+    fn test_buffer_to_ansi() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 2));
+        buffer.set_string(0, 0, "ab", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD));
+        buffer.set_string(0, 1, "✓界x", Style::default().bg(Color::Rgb(1, 2, 3)));
+
+        assert_eq!(
+            buffer_to_ansi(&buffer),
+            "\x1b[H\x1b[0;1;32mab\x1b[0m    \x1b[0m\r\n\x1b[0;48;2;1;2;3m✓界x\x1b[0m  \x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_asciicast_writer() {
+        let frame = Buffer::empty(Rect::new(0, 0, 3, 1));
+        let mut writer = AsciicastWriter::new(Vec::new(), 3, 1, 10, 2.0).unwrap();
+        for _ in 0..3 {
+            writer.write_frame(&frame).unwrap();
+        }
+        let cast = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let lines: Vec<serde_json::Value> = cast.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!((lines[0]["width"].as_u64(), lines[0]["height"].as_u64()), (Some(3), Some(1)));
+        assert_eq!(lines[1][1], "o");
+        assert!(lines[1][2].as_str().unwrap().starts_with("\x1b[2J\x1b[H"));
+        assert_eq!(lines[3][0].as_f64(), Some(0.1));
+        assert_eq!(lines[3][2], "\x1b[H\x1b[0m   \x1b[0m");
+    }
+}
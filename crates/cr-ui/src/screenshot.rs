@@ -0,0 +1,191 @@
+//! SVG snapshots of rendered frames
+
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+
+/// Width of one terminal cell in pixels
+const CELL_WIDTH: f32 = 8.4;
+/// Height of one terminal row in pixels
+const CELL_HEIGHT: f32 = 17.0;
+/// Foreground for `Color::Reset`
+const DEFAULT_FG: &str = "#d0d0d0";
+/// Background for `Color::Reset`
+const DEFAULT_BG: &str = "#1e1e1e";
+
+/// Render a buffer as a standalone SVG document
+///
+/// Each run of equally styled cells in a row becomes a `<text>` element;
+/// non-default backgrounds are drawn as rectangles beneath.
+pub fn buffer_to_svg(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let width = area.width as f32 * CELL_WIDTH;
+    let height = area.height as f32 * CELL_HEIGHT;
+
+    let mut backgrounds = String::new();
+    let mut text = String::new();
+
+    for y in 0..area.height {
+        let row: Vec<_> = (0..area.width)
+            .map(|x| &buffer[(area.x + x, area.y + y)])
+            .collect();
+        let baseline = (y as f32 + 0.8) * CELL_HEIGHT;
+
+        let mut x = 0;
+        while x < row.len() {
+            let (fg, bg, modifier) = (row[x].fg, row[x].bg, row[x].modifier);
+            let start = x;
+            let mut run = String::new();
+            while x < row.len() && row[x].fg == fg && row[x].bg == bg && row[x].modifier == modifier
+            {
+                run.push_str(row[x].symbol());
+                x += 1;
+            }
+
+            let (fg, bg) = if modifier.contains(Modifier::REVERSED) {
+                (bg, fg)
+            } else {
+                (fg, bg)
+            };
+            if let Some(bg) = color_hex(bg) {
+                backgrounds.push_str(&format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>\n",
+                    start as f32 * CELL_WIDTH,
+                    y as f32 * CELL_HEIGHT,
+                    (x - start) as f32 * CELL_WIDTH,
+                    CELL_HEIGHT,
+                    bg
+                ));
+            }
+            if run.trim().is_empty() {
+                continue;
+            }
+
+            let mut attrs = format!(
+                "fill=\"{}\"",
+                color_hex(fg).unwrap_or_else(|| DEFAULT_FG.to_string())
+            );
+            if modifier.contains(Modifier::BOLD) {
+                attrs.push_str(" font-weight=\"bold\"");
+            }
+            if modifier.contains(Modifier::ITALIC) {
+                attrs.push_str(" font-style=\"italic\"");
+            }
+            if modifier.contains(Modifier::UNDERLINED) {
+                attrs.push_str(" text-decoration=\"underline\"");
+            }
+            text.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" {}>{}</text>\n",
+                start as f32 * CELL_WIDTH,
+                baseline,
+                attrs,
+                escape_xml(&run)
+            ));
+        }
+    }
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.1} {h:.1}\" \
+         font-family=\"Menlo, Consolas, 'DejaVu Sans Mono', monospace\" font-size=\"14\" xml:space=\"preserve\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"{bg}\"/>\n{backgrounds}{text}</svg>\n",
+        w = width,
+        h = height,
+        bg = DEFAULT_BG,
+    )
+}
+
+/// RGB for the 16 ANSI colors, in xterm index order
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 49, 49),
+    (13, 188, 121),
+    (229, 229, 16),
+    (36, 114, 200),
+    (188, 63, 188),
+    (17, 168, 205),
+    (204, 204, 204),
+    (118, 118, 118),
+    (241, 76, 76),
+    (35, 209, 139),
+    (245, 245, 67),
+    (59, 142, 234),
+    (214, 112, 214),
+    (41, 184, 219),
+    (229, 229, 229),
+];
+
+/// Hex color for a terminal color, or `None` for the default
+fn color_hex(color: Color) -> Option<String> {
+    let index = match color {
+        Color::Reset => return None,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(i) => i,
+        Color::Rgb(r, g, b) => return Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+    };
+    let (r, g, b) = indexed_rgb(index);
+    Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+/// RGB for an xterm 256-color palette index
+fn indexed_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI_RGB[i as usize],
+        16..=231 => {
+            let i = i - 16;
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            (level(i / 36), level((i / 6) % 6), level(i % 6))
+        }
+        _ => {
+            let v = 8 + (i - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    #[test]
+    fn test_buffer_to_svg() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 10, 2));
+        buffer.set_string(0, 0, "+ a<b", Style::default().fg(Color::Green));
+        buffer.set_string(0, 1, "x", Style::default().bg(Color::Rgb(1, 2, 3)));
+
+        let svg = buffer_to_svg(&buffer);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("fill=\"#0dbc79\">+ a&lt;b</text>"));
+        assert!(svg.contains("fill=\"#010203\"/>"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_indexed_colors() {
+        assert_eq!(indexed_rgb(16), (0, 0, 0));
+        assert_eq!(indexed_rgb(231), (255, 255, 255));
+        assert_eq!(indexed_rgb(232), (8, 8, 8));
+        assert_eq!(color_hex(Color::Indexed(2)), color_hex(Color::Green));
+    }
+}
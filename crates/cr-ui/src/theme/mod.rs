@@ -1,7 +1,17 @@
 //! Theme system
 
+use anyhow::{bail, Result};
 use ratatui::prelude::*;
 
+/// Names of the built-in themes, in preview order
+pub const THEME_NAMES: &[&str] = &[
+    "default",
+    "high-contrast",
+    "monokai",
+    "solarized-dark",
+    "solarized-light",
+];
+
 /// Application theme
 #[derive(Debug, Clone)]
 pub struct Theme {
@@ -37,3 +47,173 @@ impl Default for Theme {
         }
     }
 }
+
+impl Theme {
+    /// Bright, saturated colors for low-contrast terminals
+    pub fn high_contrast() -> Self {
+        Self {
+            focus_border: Color::White,
+            unfocus_border: Color::Gray,
+            added: Color::LightGreen,
+            deleted: Color::LightRed,
+            context: Color::White,
+            critical: Color::LightRed,
+            warning: Color::LightYellow,
+            info: Color::LightCyan,
+        }
+    }
+
+    /// Monokai palette
+    pub fn monokai() -> Self {
+        Self {
+            focus_border: Color::Rgb(102, 217, 239),
+            unfocus_border: Color::Rgb(117, 113, 94),
+            added: Color::Rgb(166, 226, 46),
+            deleted: Color::Rgb(249, 38, 114),
+            context: Color::Rgb(248, 248, 242),
+            critical: Color::Rgb(249, 38, 114),
+            warning: Color::Rgb(230, 219, 116),
+            info: Color::Rgb(174, 129, 255),
+        }
+    }
+
+    /// Solarized (dark) palette
+    pub fn solarized() -> Self {
+        Self {
+            focus_border: Color::Rgb(42, 161, 152),
+            unfocus_border: Color::Rgb(88, 110, 117),
+            added: Color::Rgb(133, 153, 0),
+            deleted: Color::Rgb(220, 50, 47),
+            context: Color::Rgb(131, 148, 150),
+            critical: Color::Rgb(220, 50, 47),
+            warning: Color::Rgb(181, 137, 0),
+            info: Color::Rgb(38, 139, 210),
+        }
+    }
+
+    /// Solarized (light) palette
+    pub fn solarized_light() -> Self {
+        Self {
+            unfocus_border: Color::Rgb(147, 161, 161),
+            context: Color::Rgb(101, 123, 131),
+            ..Self::solarized()
+        }
+    }
+
+    /// Look up a built-in theme by name
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "monokai" => Some(Self::monokai()),
+            "solarized" | "solarized-dark" => Some(Self::solarized()),
+            "solarized-light" => Some(Self::solarized_light()),
+            _ => None,
+        }
+    }
+}
+
+/// The active theme, switchable at runtime
+///
+/// Rendering reads [`ThemeManager::current`] on every draw, so a change takes
+/// effect on the next frame.
+#[derive(Debug, Clone)]
+pub struct ThemeManager {
+    name: String,
+    current: Theme,
+}
+
+impl Default for ThemeManager {
+    fn default() -> Self {
+        Self {
+            name: "default".to_string(),
+            current: Theme::default(),
+        }
+    }
+}
+
+impl ThemeManager {
+    /// Create a manager using the default theme
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The active theme
+    pub fn current(&self) -> &Theme {
+        &self.current
+    }
+
+    /// Name of the active theme
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Switch to a built-in theme
+    pub fn set(&mut self, name: &str) -> Result<()> {
+        let Some(theme) = Theme::by_name(name) else {
+            bail!("Unknown theme: {}", name);
+        };
+        self.name = name.to_string();
+        self.current = theme;
+        Ok(())
+    }
+
+    /// Names of all built-in themes
+    pub fn list_available(&self) -> Vec<String> {
+        THEME_NAMES.iter().map(|n| n.to_string()).collect()
+    }
+}
+
+/// Color for a heat score in `0.0..=1.0`, from green through yellow to red
+pub fn heat_color(score: f64) -> Color {
+    let score = score.clamp(0.0, 1.0);
+    if score < 0.5 {
+        let t = score * 2.0;
+        Color::Rgb((255.0 * t) as u8, 200, 0)
+    } else {
+        let t = (score - 0.5) * 2.0;
+        Color::Rgb(255, (200.0 * (1.0 - t)) as u8, 0)
+    }
+}
+
+/// A fixed-width bar for a heat score, e.g. `■■■□□`
+pub fn heat_bar(score: f64, width: usize) -> String {
+    let filled = (score.clamp(0.0, 1.0) * width as f64).round() as usize;
+    format!("{}{}", "■".repeat(filled), "□".repeat(width - filled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heat_gradient() {
+        assert_eq!(heat_color(0.0), Color::Rgb(0, 200, 0));
+        assert_eq!(heat_color(0.5), Color::Rgb(255, 200, 0));
+        assert_eq!(heat_color(1.0), Color::Rgb(255, 0, 0));
+        assert_eq!(heat_color(2.0), heat_color(1.0));
+    }
+
+    #[test]
+    fn test_theme_manager() {
+        let mut themes = ThemeManager::new();
+        assert_eq!(themes.name(), "default");
+        for name in themes.list_available() {
+            assert!(Theme::by_name(&name).is_some());
+        }
+
+        themes.set("monokai").unwrap();
+        assert_eq!(themes.name(), "monokai");
+        assert_eq!(themes.current().added, Theme::monokai().added);
+
+        assert!(themes.set("nope").is_err());
+        assert_eq!(themes.name(), "monokai");
+    }
+
+    #[test]
+    fn test_heat_bar() {
+        assert_eq!(heat_bar(0.0, 5), "□□□□□");
+        assert_eq!(heat_bar(0.6, 5), "■■■□□");
+        assert_eq!(heat_bar(1.0, 5), "■■■■■");
+    }
+}